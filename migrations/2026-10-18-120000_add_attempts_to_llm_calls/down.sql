@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS llm_calls_logical_call_id_idx;
+
+ALTER TABLE llm_calls
+DROP COLUMN IF EXISTS attempt,
+DROP COLUMN IF EXISTS logical_call_id;
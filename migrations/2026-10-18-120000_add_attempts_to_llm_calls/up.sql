@@ -0,0 +1,7 @@
+ALTER TABLE llm_calls
+ADD COLUMN logical_call_id UUID NULL,
+ADD COLUMN attempt INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX IF NOT EXISTS llm_calls_logical_call_id_idx
+ON llm_calls(logical_call_id)
+WHERE logical_call_id IS NOT NULL;
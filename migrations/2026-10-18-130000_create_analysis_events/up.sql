@@ -0,0 +1,9 @@
+CREATE TABLE analysis_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    analysis_id UUID NOT NULL REFERENCES landscape_analyses(id) ON DELETE CASCADE,
+    seq BIGINT NOT NULL,
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT analysis_events_analysis_id_seq_key UNIQUE (analysis_id, seq)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE embeddings (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    entity_type TEXT NOT NULL,
+    entity_id UUID NOT NULL,
+    model TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    vector REAL[] NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT embeddings_entity_type_check CHECK (entity_type IN ('LANDMARK', 'ELEMENT')),
+    CONSTRAINT embeddings_entity_model_key UNIQUE (entity_type, entity_id, model)
+);
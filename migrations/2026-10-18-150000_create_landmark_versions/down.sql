@@ -0,0 +1 @@
+DROP TABLE IF EXISTS landmark_versions;
@@ -0,0 +1,18 @@
+CREATE TABLE landmark_versions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    landmark_id UUID NOT NULL REFERENCES landmarks(id) ON DELETE CASCADE,
+    landscape_analysis_id UUID NOT NULL REFERENCES landscape_analyses(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    subtitle TEXT NOT NULL,
+    content TEXT NOT NULL,
+    landmark_type TEXT NOT NULL,
+    maturing_state TEXT NOT NULL,
+    related_elements_count INTEGER NOT NULL DEFAULT 0,
+    last_related_element_at TIMESTAMP NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT landmark_versions_analysis_landmark_key UNIQUE (landscape_analysis_id, landmark_id)
+);
+
+CREATE INDEX landmark_versions_landmark_id_idx
+    ON landmark_versions (landmark_id);
@@ -0,0 +1,10 @@
+CREATE TABLE analysis_configs (
+    analysis_id UUID PRIMARY KEY REFERENCES landscape_analyses(id) ON DELETE CASCADE,
+    model TEXT NOT NULL,
+    matching_confidence_threshold REAL NOT NULL,
+    feature_flag_run_high_level_analysis BOOLEAN NOT NULL,
+    llm_provider TEXT NOT NULL,
+    is_override BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
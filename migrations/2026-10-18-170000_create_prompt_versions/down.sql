@@ -0,0 +1,5 @@
+ALTER TABLE llm_calls
+    DROP COLUMN IF EXISTS prompt_version,
+    DROP COLUMN IF EXISTS prompt_id;
+
+DROP TABLE IF EXISTS prompt_versions;
@@ -0,0 +1,20 @@
+CREATE TABLE prompt_versions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    prompt_id TEXT NOT NULL,
+    version INT NOT NULL CHECK (version > 0),
+    system_prompt TEXT NOT NULL,
+    schema TEXT,
+    source TEXT NOT NULL CHECK (source IN ('ADMIN', 'DIRECTORY')),
+    is_active BOOLEAN NOT NULL DEFAULT FALSE,
+    activated_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT prompt_versions_prompt_id_version_key UNIQUE (prompt_id, version)
+);
+
+CREATE UNIQUE INDEX prompt_versions_one_active_per_prompt
+    ON prompt_versions (prompt_id)
+    WHERE is_active;
+
+ALTER TABLE llm_calls
+    ADD COLUMN prompt_id TEXT,
+    ADD COLUMN prompt_version INT;
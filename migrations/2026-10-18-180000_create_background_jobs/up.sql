@@ -0,0 +1,35 @@
+CREATE TABLE background_jobs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    job_type TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status TEXT NOT NULL DEFAULT 'PENDING'
+        CHECK (status IN ('PENDING', 'RUNNING', 'SUCCEEDED', 'FAILED')),
+    dedupe_key TEXT,
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL DEFAULT 3,
+    timeout_seconds INT NOT NULL DEFAULT 600,
+    run_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    lock_owner UUID,
+    lock_until TIMESTAMP,
+    last_error TEXT,
+    result JSONB,
+    started_at TIMESTAMP,
+    finished_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX background_jobs_active_dedupe_key
+    ON background_jobs (dedupe_key)
+    WHERE status IN ('PENDING', 'RUNNING');
+
+CREATE INDEX background_jobs_due
+    ON background_jobs (run_at)
+    WHERE status IN ('PENDING', 'RUNNING');
+
+CREATE TABLE job_schedules (
+    job_type TEXT PRIMARY KEY,
+    interval_seconds INT NOT NULL CHECK (interval_seconds > 0),
+    next_run_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,13 @@
+CREATE TABLE bio_profiles (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    version INT NOT NULL CHECK (version > 0),
+    trace_id UUID NOT NULL REFERENCES traces(id) ON DELETE CASCADE,
+    analysis_id UUID NOT NULL REFERENCES landscape_analyses(id) ON DELETE CASCADE,
+    summary TEXT NOT NULL,
+    profile JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT bio_profiles_user_id_version_key UNIQUE (user_id, version)
+);
+
+CREATE INDEX bio_profiles_analysis_id ON bio_profiles (analysis_id);
@@ -0,0 +1,2 @@
+ALTER TABLE users
+ADD COLUMN monthly_recap_enabled BOOLEAN NOT NULL DEFAULT FALSE;
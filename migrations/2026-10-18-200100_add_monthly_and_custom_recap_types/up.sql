@@ -0,0 +1,75 @@
+ALTER TABLE landscape_analyses
+DROP CONSTRAINT IF EXISTS landscape_analyses_landscape_analysis_type_check;
+
+ALTER TABLE landscape_analyses
+ADD CONSTRAINT landscape_analyses_landscape_analysis_type_check
+CHECK (
+    landscape_analysis_type IN (
+        'TRACE_INCREMENTAL',
+        'DAILY_RECAP',
+        'WEEKLY_RECAP',
+        'MONTHLY_RECAP',
+        'CUSTOM_RECAP',
+        'HLP',
+        'BIO'
+    )
+);
+
+-- Custom recaps are requested on demand for arbitrary ranges and may overlap.
+CREATE OR REPLACE FUNCTION prevent_overlapping_lens_analysis_scopes()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+AS $$
+DECLARE
+    candidate_type TEXT;
+    candidate_period_start TIMESTAMP;
+    candidate_period_end TIMESTAMP;
+BEGIN
+    SELECT
+        la.landscape_analysis_type,
+        la.period_start,
+        la.period_end
+    INTO
+        candidate_type,
+        candidate_period_start,
+        candidate_period_end
+    FROM landscape_analyses la
+    WHERE la.id = NEW.landscape_analysis_id;
+
+    IF candidate_period_start IS NULL OR candidate_period_end IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    IF candidate_type = 'CUSTOM_RECAP' THEN
+        RETURN NEW;
+    END IF;
+
+    IF EXISTS (
+        SELECT 1
+        FROM lens_analysis_scopes las
+        JOIN landscape_analyses existing_la ON existing_la.id = las.landscape_analysis_id
+        WHERE las.lens_id = NEW.lens_id
+          AND las.landscape_analysis_id <> NEW.landscape_analysis_id
+          AND existing_la.landscape_analysis_type = candidate_type
+          AND (
+              (
+                  existing_la.period_start = candidate_period_start
+                  AND existing_la.period_end = candidate_period_end
+              )
+              OR (
+                  existing_la.period_start < candidate_period_end
+                  AND candidate_period_start < existing_la.period_end
+              )
+          )
+    ) THEN
+        RAISE EXCEPTION USING
+            MESSAGE = format(
+                'Overlapping analysis scope detected for lens %s and analysis type %s',
+                NEW.lens_id,
+                candidate_type
+            );
+    END IF;
+
+    RETURN NEW;
+END;
+$$;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS analysis_unlock_audit_events;
+DROP TABLE IF EXISTS analysis_unlock_sessions;
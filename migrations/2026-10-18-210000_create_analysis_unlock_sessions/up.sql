@@ -0,0 +1,35 @@
+CREATE TABLE analysis_unlock_sessions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    lens_id UUID NOT NULL REFERENCES lenses(id) ON DELETE CASCADE,
+    key_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'ACTIVE'
+        CHECK (status IN ('ACTIVE', 'CLOSED', 'EXPIRED', 'REVOKED')),
+    consent_derived_context BOOLEAN NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    closed_at TIMESTAMP,
+    decrypted_trace_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_analysis_unlock_sessions_user_created_at
+ON analysis_unlock_sessions (user_id, created_at DESC);
+
+CREATE INDEX idx_analysis_unlock_sessions_active_lens
+ON analysis_unlock_sessions (lens_id, key_id)
+WHERE status = 'ACTIVE';
+
+CREATE TABLE analysis_unlock_audit_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    session_id UUID NOT NULL REFERENCES analysis_unlock_sessions(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    trace_id UUID REFERENCES traces(id) ON DELETE SET NULL,
+    analysis_id UUID REFERENCES landscape_analyses(id) ON DELETE SET NULL,
+    detail TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_analysis_unlock_audit_events_session_created_at
+ON analysis_unlock_audit_events (session_id, created_at ASC);
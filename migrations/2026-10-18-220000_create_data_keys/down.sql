@@ -0,0 +1,7 @@
+ALTER TABLE trace_search_documents
+ADD COLUMN content TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE trace_search_documents
+DROP COLUMN content_vector;
+
+DROP TABLE IF EXISTS data_keys;
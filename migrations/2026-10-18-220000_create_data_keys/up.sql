@@ -0,0 +1,28 @@
+CREATE TABLE data_keys (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    master_key_id TEXT NOT NULL,
+    wrapped_key TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'ACTIVE'
+        CHECK (status IN ('ACTIVE', 'RETIRED')),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    retired_at TIMESTAMP
+);
+
+-- One active key per user; rows without a user (platform key) share the nil uuid slot.
+CREATE UNIQUE INDEX idx_data_keys_active_owner
+ON data_keys ((COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid)))
+WHERE status = 'ACTIVE';
+
+CREATE INDEX idx_data_keys_master_key_id
+ON data_keys (master_key_id);
+
+-- Search keeps a derived index of trace content instead of a plaintext copy.
+ALTER TABLE trace_search_documents
+ADD COLUMN content_vector TSVECTOR NOT NULL DEFAULT ''::tsvector;
+
+UPDATE trace_search_documents
+SET content_vector = to_tsvector('simple', content);
+
+ALTER TABLE trace_search_documents
+DROP COLUMN content;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS landmark_aliases;
+DROP TABLE IF EXISTS landmark_curations;
@@ -0,0 +1,27 @@
+CREATE TABLE landmark_curations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    landmark_id UUID NOT NULL REFERENCES landmarks(id) ON DELETE CASCADE,
+    action TEXT NOT NULL
+        CHECK (action IN ('MERGE', 'SPLIT', 'RENAME', 'RETYPE')),
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_landmark_curations_landmark_created
+ON landmark_curations (landmark_id, created_at DESC);
+
+-- Titles a user has folded into a landmark; the pipeline reuses the landmark instead of
+-- recreating one under an alias title.
+CREATE TABLE landmark_aliases (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    landmark_id UUID NOT NULL REFERENCES landmarks(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    normalized_title TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT landmark_aliases_user_title_key UNIQUE (user_id, normalized_title)
+);
+
+CREATE INDEX idx_landmark_aliases_landmark_id
+ON landmark_aliases (landmark_id);
@@ -0,0 +1,18 @@
+-- Passages of finalized traces for semantic search. Content is sealed like the trace content;
+-- vectors are filled by the embeddings backfill and reset when the passage text changes.
+CREATE TABLE trace_search_chunks (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    trace_id UUID NOT NULL REFERENCES traces(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    chunk_index INT NOT NULL CHECK (chunk_index >= 0),
+    content TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    model TEXT NULL,
+    vector REAL[] NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT trace_search_chunks_trace_chunk_key UNIQUE (trace_id, chunk_index)
+);
+
+CREATE INDEX idx_trace_search_chunks_user_model
+ON trace_search_chunks (user_id, model);
@@ -0,0 +1,6 @@
+ALTER TABLE trace_search_documents
+DROP COLUMN IF EXISTS search_config;
+
+DROP TEXT SEARCH CONFIGURATION IF EXISTS english_unaccent;
+DROP TEXT SEARCH CONFIGURATION IF EXISTS french_unaccent;
+DROP TEXT SEARCH CONFIGURATION IF EXISTS simple_unaccent;
@@ -0,0 +1,19 @@
+CREATE EXTENSION IF NOT EXISTS unaccent;
+
+-- Accent-insensitive variants of the configurations traces are indexed with.
+CREATE TEXT SEARCH CONFIGURATION simple_unaccent (COPY = simple);
+ALTER TEXT SEARCH CONFIGURATION simple_unaccent
+    ALTER MAPPING FOR hword, hword_part, word WITH unaccent, simple;
+
+CREATE TEXT SEARCH CONFIGURATION french_unaccent (COPY = french);
+ALTER TEXT SEARCH CONFIGURATION french_unaccent
+    ALTER MAPPING FOR hword, hword_part, word WITH unaccent, french_stem;
+
+CREATE TEXT SEARCH CONFIGURATION english_unaccent (COPY = english);
+ALTER TEXT SEARCH CONFIGURATION english_unaccent
+    ALTER MAPPING FOR hword, hword_part, word WITH unaccent, english_stem;
+
+-- Documents indexed before keep `simple` until their trace is refreshed.
+ALTER TABLE trace_search_documents
+ADD COLUMN search_config TEXT NOT NULL DEFAULT 'simple'
+CHECK (search_config IN ('simple', 'simple_unaccent', 'french_unaccent', 'english_unaccent'));
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS journal_import_batch_traces;
+DROP TABLE IF EXISTS journal_import_batches;
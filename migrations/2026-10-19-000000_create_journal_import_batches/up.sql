@@ -0,0 +1,28 @@
+-- One row per committed text import, so an import can be listed and rolled back as a whole.
+CREATE TABLE journal_import_batches (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    journal_id UUID NOT NULL REFERENCES journals(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'COMPLETED' CHECK (status IN ('COMPLETED', 'ROLLED_BACK')),
+    file_name TEXT NULL,
+    total_blocks INT NOT NULL DEFAULT 0,
+    created_count INT NOT NULL DEFAULT 0,
+    skipped_count INT NOT NULL DEFAULT 0,
+    failed_count INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    rolled_back_at TIMESTAMP NULL
+);
+
+CREATE INDEX idx_journal_import_batches_journal_created
+ON journal_import_batches (journal_id, created_at DESC);
+
+CREATE TABLE journal_import_batch_traces (
+    batch_id UUID NOT NULL REFERENCES journal_import_batches(id) ON DELETE CASCADE,
+    trace_id UUID NOT NULL REFERENCES traces(id) ON DELETE CASCADE,
+    block_index INT NOT NULL,
+    content_hash TEXT NOT NULL,
+    PRIMARY KEY (batch_id, trace_id)
+);
+
+CREATE INDEX idx_journal_import_batch_traces_trace
+ON journal_import_batch_traces (trace_id);
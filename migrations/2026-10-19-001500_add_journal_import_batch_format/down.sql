@@ -0,0 +1 @@
+ALTER TABLE journal_import_batches DROP COLUMN IF EXISTS format;
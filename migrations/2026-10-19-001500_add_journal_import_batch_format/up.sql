@@ -0,0 +1,3 @@
+ALTER TABLE journal_import_batches
+ADD COLUMN format TEXT NOT NULL DEFAULT 'text'
+CHECK (format IN ('text', 'day_one', 'obsidian', 'notion', 'front_matter'));
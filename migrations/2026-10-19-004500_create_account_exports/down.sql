@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS account_export_audit_events;
+DROP TABLE IF EXISTS account_exports;
@@ -0,0 +1,32 @@
+-- One row per "download all my data" request, with the archive it produced.
+CREATE TABLE account_exports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'PENDING'
+        CHECK (status IN ('PENDING', 'RUNNING', 'READY', 'FAILED')),
+    job_id UUID REFERENCES background_jobs(id) ON DELETE SET NULL,
+    asset_id UUID REFERENCES assets(id) ON DELETE SET NULL,
+    file_name TEXT,
+    size_bytes BIGINT,
+    error TEXT,
+    link_expires_at TIMESTAMP,
+    outbound_email_id UUID REFERENCES outbound_emails(id) ON DELETE SET NULL,
+    completed_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_account_exports_user_created_at
+ON account_exports (user_id, created_at DESC);
+
+CREATE TABLE account_export_audit_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    export_id UUID NOT NULL REFERENCES account_exports(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    detail TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_account_export_audit_events_export_created_at
+ON account_export_audit_events (export_id, created_at ASC);
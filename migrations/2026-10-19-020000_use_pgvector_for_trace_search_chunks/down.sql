@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_trace_search_chunks_vector_1536;
+
+ALTER TABLE trace_search_chunks
+ALTER COLUMN vector TYPE REAL[] USING vector::real[];
@@ -0,0 +1,11 @@
+CREATE EXTENSION IF NOT EXISTS vector;
+
+-- Passage vectors become pgvector values so the closest passages are ranked in the database.
+ALTER TABLE trace_search_chunks
+ALTER COLUMN vector TYPE vector USING vector::vector;
+
+-- Approximate index for the default embedding model; other sizes fall back to an exact scan.
+CREATE INDEX idx_trace_search_chunks_vector_1536
+ON trace_search_chunks
+USING hnsw ((vector::vector(1536)) vector_cosine_ops)
+WHERE vector_dims(vector) = 1536;
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS llm_calls_user_id_idx;
+
+ALTER TABLE llm_calls
+DROP COLUMN IF EXISTS user_id;
@@ -0,0 +1,12 @@
+-- Owner of each call, kept when its analysis is deleted so account exports still include it.
+ALTER TABLE llm_calls
+ADD COLUMN user_id UUID NULL REFERENCES users(id) ON DELETE SET NULL;
+
+UPDATE llm_calls lc
+SET user_id = la.user_id
+FROM landscape_analyses la
+WHERE la.id = lc.analysis_id;
+
+CREATE INDEX IF NOT EXISTS llm_calls_user_id_idx
+ON llm_calls(user_id)
+WHERE user_id IS NOT NULL;
@@ -0,0 +1,14 @@
+DROP INDEX IF EXISTS idx_landmark_aliases_user_title;
+
+ALTER TABLE landmark_aliases
+DROP CONSTRAINT IF EXISTS landmark_aliases_user_landmark_title_key;
+
+-- Keep the latest landmark per title, as the previous constraint allowed only one.
+DELETE FROM landmark_aliases a
+USING landmark_aliases newer
+WHERE newer.user_id = a.user_id
+  AND newer.normalized_title = a.normalized_title
+  AND (newer.created_at, newer.id) > (a.created_at, a.id);
+
+ALTER TABLE landmark_aliases
+ADD CONSTRAINT landmark_aliases_user_title_key UNIQUE (user_id, normalized_title);
@@ -0,0 +1,11 @@
+-- An alias belongs to the landmark it was folded into: the same title may alias landmarks
+-- of other lenses or types without being moved between them.
+ALTER TABLE landmark_aliases
+DROP CONSTRAINT IF EXISTS landmark_aliases_user_title_key;
+
+ALTER TABLE landmark_aliases
+ADD CONSTRAINT landmark_aliases_user_landmark_title_key
+UNIQUE (user_id, landmark_id, normalized_title);
+
+CREATE INDEX IF NOT EXISTS idx_landmark_aliases_user_title
+ON landmark_aliases (user_id, normalized_title);
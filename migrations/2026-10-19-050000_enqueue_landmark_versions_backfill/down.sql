@@ -0,0 +1,3 @@
+DELETE FROM background_jobs
+WHERE job_type = 'BACKFILL_LANDMARK_VERSIONS'
+  AND status = 'PENDING';
@@ -0,0 +1,5 @@
+-- Existing analyses get their landmark versions from a background job that walks each lens
+-- chain once, instead of expanding every analysis into its ancestors in this transaction.
+INSERT INTO background_jobs (job_type, payload, dedupe_key, max_attempts, timeout_seconds)
+VALUES ('BACKFILL_LANDMARK_VERSIONS', '{}'::jsonb, 'BACKFILL_LANDMARK_VERSIONS', 2, 1800)
+ON CONFLICT (dedupe_key) WHERE status IN ('PENDING', 'RUNNING') DO NOTHING;
@@ -0,0 +1,90 @@
+//! Scores the mirror and element pipelines against golden cases.
+//!
+//! Usage: `pipeline_eval <cases_dir> [--fixtures <dir>] [--live | --record] [--out <dir>]`
+//!
+//! By default LLM responses are replayed from the fixtures directory (`LLM_FIXTURES_DIR`).
+//! `--live` calls the provider from `LLM_PROVIDER`; `--record` replays and records misses with it.
+//! Writes `report.json` and `report.md` to the output directory and prints the markdown.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use web_server::entities_v2::error::{ErrorType, PpdcError};
+use web_server::environment;
+use web_server::openai_handler::llm_provider::{
+    self, FixtureReplayProvider, LlmProvider, LlmProviderKind, LlmProviderSelection,
+};
+use web_server::work_analyzer::pipeline_eval::{load_cases, run_cases};
+
+fn usage_error(message: &str) -> PpdcError {
+    PpdcError::new(
+        400,
+        ErrorType::ApiError,
+        format!(
+            "{}\nusage: pipeline_eval <cases_dir> [--fixtures <dir>] [--live | --record] [--out <dir>]",
+            message
+        ),
+    )
+}
+
+fn io_error(err: std::io::Error) -> PpdcError {
+    PpdcError::new(500, ErrorType::InternalError, err.to_string())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), PpdcError> {
+    let mut cases_dir: Option<PathBuf> = None;
+    let mut fixtures_dir = PathBuf::from(environment::get_llm_fixtures_dir());
+    let mut out_dir = PathBuf::from("eval_reports");
+    let mut mode = "recorded";
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fixtures" => {
+                fixtures_dir = args
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| usage_error("--fixtures needs a directory"))?;
+            }
+            "--out" => {
+                out_dir = args
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| usage_error("--out needs a directory"))?;
+            }
+            "--live" => mode = "live",
+            "--record" => mode = "record",
+            _ if cases_dir.is_none() && !arg.starts_with("--") => {
+                cases_dir = Some(PathBuf::from(arg));
+            }
+            _ => return Err(usage_error(&format!("unexpected argument {}", arg))),
+        }
+    }
+    let cases_dir = cases_dir.ok_or_else(|| usage_error("missing cases directory"))?;
+
+    let provider: Arc<dyn LlmProvider> = match mode {
+        "live" => LlmProviderKind::from_env().build(),
+        "record" => Arc::new(
+            FixtureReplayProvider::from_directory(&fixtures_dir)
+                .with_recorder(LlmProviderKind::from_env().build()),
+        ),
+        _ => Arc::new(FixtureReplayProvider::from_directory(&fixtures_dir)),
+    };
+
+    let cases = load_cases(&cases_dir)?;
+    let report =
+        llm_provider::with_selection(LlmProviderSelection::new(provider), run_cases(&cases, mode))
+            .await;
+
+    let markdown = report.to_markdown();
+    std::fs::create_dir_all(&out_dir).map_err(io_error)?;
+    std::fs::write(
+        out_dir.join("report.json"),
+        serde_json::to_string_pretty(&report)?,
+    )
+    .map_err(io_error)?;
+    std::fs::write(out_dir.join("report.md"), &markdown).map_err(io_error)?;
+    println!("{}", markdown);
+    Ok(())
+}
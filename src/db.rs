@@ -16,6 +16,10 @@ pub fn get_global_pool() -> &'static DbPool {
     GLOBAL_POOL.get().expect("Global pool not initialized")
 }
 
+pub fn try_get_global_pool() -> Option<&'static DbPool> {
+    GLOBAL_POOL.get()
+}
+
 pub fn create_pool() -> DbPool {
     let database_url = get_database_url();
     let manager = ConnectionManager::<PgConnection>::new(database_url);
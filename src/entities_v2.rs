@@ -6,14 +6,22 @@ pub mod shared;
 pub mod social;
 
 // Backward-compatible re-exports for existing imports across the codebase.
-pub use analysis_orchestration::{landscape_analysis, lens};
-pub use derived_context::{analysis_summary, element, landmark, reference, trace_mirror};
+pub use analysis_orchestration::{
+    analysis_config, analysis_event, analysis_unlock, landscape_analysis, landscape_diff,
+    landscape_graph, lens,
+};
+pub use derived_context::{
+    analysis_summary, bio_profile, element, embedding, landmark, landmark_curation,
+    landmark_version, reference, trace_mirror,
+};
 pub use platform_infra::{
-    asset, device, error, llm_call, mailer, notification, push, session, transcription,
-    url_preview, usage_event, user, user_secure_action,
+    account_export, asset, background_job, data_key, device, error, llm_call, mailer, notification,
+    prompt_version, push, session, transcription, url_preview, usage_event, user,
+    user_secure_action,
 };
 pub use records::{
-    document, journal, journal_import, journal_share_link, trace, trace_attachment, trace_search,
+    document, journal, journal_export, journal_import, journal_share_link, trace, trace_attachment,
+    trace_search,
 };
 pub use shared::MaturingState;
 pub use social::{
@@ -0,0 +1,6 @@
+pub mod model;
+pub mod persist;
+pub mod routes;
+
+pub use model::{AnalysisConfigOverride, RecordedAnalysisConfig};
+pub use routes::get_analysis_config_route;
@@ -0,0 +1,53 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities_v2::error::{ErrorType, PpdcError};
+
+/// Pipeline settings requested for a replay; unset fields keep the default configuration.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AnalysisConfigOverride {
+    pub model: Option<String>,
+    pub matching_confidence_threshold: Option<f32>,
+    pub feature_flag_run_high_level_analysis: Option<bool>,
+}
+
+impl AnalysisConfigOverride {
+    pub fn validate(&self) -> Result<(), PpdcError> {
+        if let Some(model) = &self.model {
+            if model.trim().is_empty() {
+                return Err(PpdcError::new(
+                    400,
+                    ErrorType::ApiError,
+                    "model must not be empty".to_string(),
+                ));
+            }
+        }
+        if let Some(threshold) = self.matching_confidence_threshold {
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err(PpdcError::new(
+                    400,
+                    ErrorType::ApiError,
+                    "matching_confidence_threshold must be between 0 and 1".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Configuration an analysis was (or will be) run with.
+#[derive(Serialize, Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::analysis_configs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RecordedAnalysisConfig {
+    pub analysis_id: Uuid,
+    pub model: String,
+    pub matching_confidence_threshold: f32,
+    pub feature_flag_run_high_level_analysis: bool,
+    pub llm_provider: String,
+    pub is_override: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
@@ -0,0 +1,63 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::PpdcError;
+use crate::schema::analysis_configs;
+use crate::work_analyzer::analysis_processor::AnalysisConfig;
+
+use super::model::RecordedAnalysisConfig;
+
+impl RecordedAnalysisConfig {
+    pub fn find(
+        analysis_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<Option<RecordedAnalysisConfig>, PpdcError> {
+        let mut conn = pool.get()?;
+        let config = analysis_configs::table
+            .filter(analysis_configs::analysis_id.eq(analysis_id))
+            .select(RecordedAnalysisConfig::as_select())
+            .first::<RecordedAnalysisConfig>(&mut conn)
+            .optional()?;
+        Ok(config)
+    }
+}
+
+impl AnalysisConfig {
+    /// Stores the configuration used for an analysis, replacing any previous record.
+    pub fn record(
+        &self,
+        analysis_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<RecordedAnalysisConfig, PpdcError> {
+        let mut conn = pool.get()?;
+        let llm_provider = self.llm_providers.default.name().to_string();
+        let config = diesel::insert_into(analysis_configs::table)
+            .values((
+                analysis_configs::analysis_id.eq(analysis_id),
+                analysis_configs::model.eq(&self.model),
+                analysis_configs::matching_confidence_threshold
+                    .eq(self.matching_confidence_threshold),
+                analysis_configs::feature_flag_run_high_level_analysis
+                    .eq(self.feature_flag_run_high_level_analysis),
+                analysis_configs::llm_provider.eq(&llm_provider),
+                analysis_configs::is_override.eq(self.is_override),
+            ))
+            .on_conflict(analysis_configs::analysis_id)
+            .do_update()
+            .set((
+                analysis_configs::model.eq(&self.model),
+                analysis_configs::matching_confidence_threshold
+                    .eq(self.matching_confidence_threshold),
+                analysis_configs::feature_flag_run_high_level_analysis
+                    .eq(self.feature_flag_run_high_level_analysis),
+                analysis_configs::llm_provider.eq(&llm_provider),
+                analysis_configs::is_override.eq(self.is_override),
+                analysis_configs::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .returning(RecordedAnalysisConfig::as_returning())
+            .get_result::<RecordedAnalysisConfig>(&mut conn)?;
+        Ok(config)
+    }
+}
@@ -0,0 +1,27 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Json, Path},
+};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    error::PpdcError, landscape_analysis::LandscapeAnalysis, session::Session,
+};
+
+use super::model::RecordedAnalysisConfig;
+
+/// Returns the pipeline configuration an analysis ran with, or `null` if it has not run yet.
+#[debug_handler]
+pub async fn get_analysis_config_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Option<RecordedAnalysisConfig>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let analysis = LandscapeAnalysis::find_full_analysis(id, &pool)?;
+    if analysis.user_id != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    Ok(Json(RecordedAnalysisConfig::find(id, &pool)?))
+}
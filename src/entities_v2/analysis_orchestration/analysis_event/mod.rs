@@ -0,0 +1,4 @@
+pub mod persist;
+pub mod routes;
+
+pub use routes::{get_analysis_events_route, get_analysis_events_stream_route};
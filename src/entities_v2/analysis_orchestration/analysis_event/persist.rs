@@ -0,0 +1,109 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Text, Timestamp, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::schema::analysis_events;
+use crate::work_analyzer::observability::{AnalysisEvent, AnalysisEventData};
+
+/// Concurrent steps of one analysis may race for the same `seq`; the loser retries.
+const SEQ_CONFLICT_RETRIES: usize = 5;
+
+#[derive(QueryableByName)]
+struct InsertedSeq {
+    #[diesel(sql_type = BigInt)]
+    seq: i64,
+}
+
+type AnalysisEventTuple = (Uuid, i64, NaiveDateTime, String);
+
+impl TryFrom<AnalysisEventTuple> for AnalysisEvent {
+    type Error = PpdcError;
+
+    fn try_from(row: AnalysisEventTuple) -> Result<Self, Self::Error> {
+        let (analysis_id, seq, timestamp, payload) = row;
+        Ok(AnalysisEvent {
+            analysis_id,
+            seq: seq as u64,
+            timestamp,
+            event: serde_json::from_str::<AnalysisEventData>(&payload)?,
+        })
+    }
+}
+
+impl AnalysisEvent {
+    /// Appends an event to the analysis log, numbering it after the last one stored.
+    pub fn create(
+        analysis_id: Uuid,
+        event: AnalysisEventData,
+        pool: &DbPool,
+    ) -> Result<AnalysisEvent, PpdcError> {
+        let mut conn = pool.get()?;
+        let payload = serde_json::to_string(&event)?;
+        let timestamp = Utc::now().naive_utc();
+
+        for _ in 0..SEQ_CONFLICT_RETRIES {
+            let inserted = sql_query(
+                r#"
+                INSERT INTO analysis_events (id, analysis_id, seq, event_type, payload, created_at)
+                SELECT $1, $2, COALESCE(MAX(seq), 0) + 1, $3, CAST($4 AS jsonb), $5
+                FROM analysis_events
+                WHERE analysis_id = $2
+                ON CONFLICT (analysis_id, seq) DO NOTHING
+                RETURNING seq
+                "#,
+            )
+            .bind::<SqlUuid, _>(Uuid::new_v4())
+            .bind::<SqlUuid, _>(analysis_id)
+            .bind::<Text, _>(event.event_type())
+            .bind::<Text, _>(&payload)
+            .bind::<Timestamp, _>(timestamp)
+            .get_results::<InsertedSeq>(&mut conn)?;
+
+            if let Some(inserted) = inserted.first() {
+                return Ok(AnalysisEvent {
+                    analysis_id,
+                    seq: inserted.seq as u64,
+                    timestamp,
+                    event,
+                });
+            }
+        }
+
+        Err(PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!(
+                "Could not allocate an event sequence number for analysis {}",
+                analysis_id
+            ),
+        ))
+    }
+
+    /// Events of an analysis in emission order, starting after `after_seq` when given.
+    pub fn find_for_analysis(
+        analysis_id: Uuid,
+        after_seq: Option<u64>,
+        limit: i64,
+        pool: &DbPool,
+    ) -> Result<Vec<AnalysisEvent>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = analysis_events::table
+            .filter(analysis_events::analysis_id.eq(analysis_id))
+            .filter(analysis_events::seq.gt(after_seq.unwrap_or(0) as i64))
+            .order(analysis_events::seq.asc())
+            .limit(limit)
+            .select((
+                analysis_events::analysis_id,
+                analysis_events::seq,
+                analysis_events::created_at,
+                diesel::dsl::sql::<Text>("payload::text"),
+            ))
+            .load::<AnalysisEventTuple>(&mut conn)?;
+
+        rows.into_iter().map(TryInto::try_into).collect()
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::{
+    debug_handler,
+    extract::{Extension, Json, Path, Query},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    error::PpdcError,
+    landscape_analysis::{LandscapeAnalysis, LandscapeProcessingState},
+    session::Session,
+};
+use crate::work_analyzer::observability::AnalysisEvent;
+
+const DEFAULT_EVENTS_LIMIT: i64 = 500;
+const MAX_EVENTS_LIMIT: i64 = 2000;
+const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Deserialize)]
+pub struct AnalysisEventsQuery {
+    pub after_seq: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+fn find_owned_analysis(
+    analysis_id: Uuid,
+    session: &Session,
+    pool: &DbPool,
+) -> Result<LandscapeAnalysis, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let analysis = LandscapeAnalysis::find_full_analysis(analysis_id, pool)?;
+    if analysis.user_id != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    Ok(analysis)
+}
+
+fn is_finished(processing_state: LandscapeProcessingState) -> bool {
+    matches!(
+        processing_state,
+        LandscapeProcessingState::Completed | LandscapeProcessingState::Failed
+    )
+}
+
+#[debug_handler]
+pub async fn get_analysis_events_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(analysis_id): Path<Uuid>,
+    Query(query): Query<AnalysisEventsQuery>,
+) -> Result<Json<Vec<AnalysisEvent>>, PpdcError> {
+    find_owned_analysis(analysis_id, &session, &pool)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_EVENTS_LIMIT)
+        .clamp(1, MAX_EVENTS_LIMIT);
+    let events = AnalysisEvent::find_for_analysis(analysis_id, query.after_seq, limit, &pool)?;
+    Ok(Json(events))
+}
+
+struct EventStreamState {
+    pool: DbPool,
+    analysis_id: Uuid,
+    last_seq: Option<u64>,
+    pending: VecDeque<AnalysisEvent>,
+    finished: bool,
+    first_poll: bool,
+}
+
+/// Polls the event log and yields new events until the analysis completes or fails.
+fn analysis_event_stream(state: EventStreamState) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                state.last_seq = Some(event.seq);
+                let sse_event = Event::default()
+                    .id(event.seq.to_string())
+                    .event(event.event.event_type())
+                    .json_data(&event)
+                    .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()));
+                return Some((Ok(sse_event), state));
+            }
+            if state.finished {
+                return None;
+            }
+            if !state.first_poll {
+                tokio::time::sleep(STREAM_POLL_INTERVAL).await;
+            }
+            state.first_poll = false;
+
+            // Read the state before the events so the last batch is never missed.
+            let finished = LandscapeAnalysis::find_full_analysis(state.analysis_id, &state.pool)
+                .map(|analysis| is_finished(analysis.processing_state))
+                .unwrap_or(true);
+            match AnalysisEvent::find_for_analysis(
+                state.analysis_id,
+                state.last_seq,
+                MAX_EVENTS_LIMIT,
+                &state.pool,
+            ) {
+                Ok(events) => {
+                    state.finished = finished && (events.len() as i64) < MAX_EVENTS_LIMIT;
+                    state.pending.extend(events);
+                }
+                Err(e) => {
+                    state.finished = true;
+                    return Some((Ok(Event::default().event("error").data(e.message)), state));
+                }
+            }
+        }
+    })
+}
+
+#[debug_handler]
+pub async fn get_analysis_events_stream_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(analysis_id): Path<Uuid>,
+    Query(query): Query<AnalysisEventsQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, PpdcError> {
+    find_owned_analysis(analysis_id, &session, &pool)?;
+    let stream = analysis_event_stream(EventStreamState {
+        pool,
+        analysis_id,
+        last_seq: query.after_seq,
+        pending: VecDeque::new(),
+        finished: false,
+        first_poll: true,
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,185 @@
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::entities_v2::trace::TraceDataKey;
+
+/// How clients wrap a data key for the server.
+///
+/// The client makes an ephemeral X25519 key pair, derives a key-encryption key with
+/// HKDF-SHA256 over the shared secret (info = `analysis-unlock-v1:` + `key_id`), and seals
+/// the 32-byte data key with AES-256-GCM using `key_id` as associated data.
+pub const UNLOCK_WRAP_ALGORITHM: &str = "X25519-HKDF-SHA256-AES-256-GCM";
+const HKDF_INFO_PREFIX: &[u8] = b"analysis-unlock-v1:";
+
+fn decode_fixed<const N: usize>(value: &str, field: &str) -> Result<[u8; N], PpdcError> {
+    BASE64
+        .decode(value.trim())
+        .ok()
+        .and_then(|bytes| <[u8; N]>::try_from(bytes.as_slice()).ok())
+        .ok_or_else(|| {
+            PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                format!("{} must be {} base64-encoded bytes", field, N),
+            )
+        })
+}
+
+fn server_secret(server_private_key: &str) -> Result<StaticSecret, PpdcError> {
+    let bytes = Zeroizing::new(
+        decode_fixed::<32>(server_private_key, "ANALYSIS_UNLOCK_PRIVATE_KEY").map_err(|_| {
+            PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                "ANALYSIS_UNLOCK_PRIVATE_KEY must be 32 base64-encoded bytes".to_string(),
+            )
+        })?,
+    );
+    Ok(StaticSecret::from(*bytes))
+}
+
+fn key_encryption_key(
+    secret: &StaticSecret,
+    peer_public_key: &PublicKey,
+    key_id: &str,
+) -> Result<Zeroizing<[u8; 32]>, PpdcError> {
+    let shared = secret.diffie_hellman(peer_public_key);
+    let info = [HKDF_INFO_PREFIX, key_id.as_bytes()].concat();
+    let mut kek = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(None, shared.as_bytes())
+        .expand(&info, kek.as_mut_slice())
+        .map_err(|_| {
+            PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                "Failed to derive key-encryption key".to_string(),
+            )
+        })?;
+    Ok(kek)
+}
+
+/// Returns the base64 public key clients wrap data keys for.
+pub fn server_public_key(server_private_key: &str) -> Result<String, PpdcError> {
+    let secret = server_secret(server_private_key)?;
+    Ok(BASE64.encode(PublicKey::from(&secret).as_bytes()))
+}
+
+/// Unwraps a client-wrapped data key; fails when any part was tampered with or `key_id` differs.
+pub fn unwrap_data_key(
+    server_private_key: &str,
+    client_public_key: &str,
+    wrap_nonce: &str,
+    wrapped_key: &str,
+    key_id: &str,
+) -> Result<TraceDataKey, PpdcError> {
+    let secret = server_secret(server_private_key)?;
+    let client_public_key =
+        PublicKey::from(decode_fixed::<32>(client_public_key, "client_public_key")?);
+    let nonce = decode_fixed::<12>(wrap_nonce, "wrap_nonce")?;
+    let wrapped_key = BASE64.decode(wrapped_key.trim()).map_err(|_| {
+        PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "wrapped_key must be base64".to_string(),
+        )
+    })?;
+
+    let kek = key_encryption_key(&secret, &client_public_key, key_id)?;
+    let cipher = Aes256Gcm::new_from_slice(kek.as_slice()).map_err(|_| {
+        PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            "Invalid key-encryption key length".to_string(),
+        )
+    })?;
+    let unwrapped = Zeroizing::new(
+        cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &wrapped_key,
+                    aad: key_id.as_bytes(),
+                },
+            )
+            .map_err(|_| {
+                PpdcError::new(
+                    400,
+                    ErrorType::ApiError,
+                    "wrapped_key could not be unwrapped".to_string(),
+                )
+            })?,
+    );
+    let key = <[u8; 32]>::try_from(unwrapped.as_slice()).map_err(|_| {
+        PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "wrapped_key must wrap a 32-byte data key".to_string(),
+        )
+    })?;
+    Ok(Zeroizing::new(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrap_for_server(
+        server_public_key: &str,
+        data_key: &[u8; 32],
+        key_id: &str,
+    ) -> (String, String, String) {
+        let client_secret = StaticSecret::from([9u8; 32]);
+        let server_public =
+            PublicKey::from(decode_fixed::<32>(server_public_key, "server").unwrap());
+        let kek = key_encryption_key(&client_secret, &server_public, key_id).unwrap();
+        let nonce = [4u8; 12];
+        let wrapped = Aes256Gcm::new_from_slice(kek.as_slice())
+            .unwrap()
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: data_key,
+                    aad: key_id.as_bytes(),
+                },
+            )
+            .unwrap();
+        (
+            BASE64.encode(PublicKey::from(&client_secret).as_bytes()),
+            BASE64.encode(nonce),
+            BASE64.encode(wrapped),
+        )
+    }
+
+    #[test]
+    fn unwraps_a_key_wrapped_for_the_server_public_key() {
+        let server_private_key = BASE64.encode([5u8; 32]);
+        let public_key = server_public_key(&server_private_key).unwrap();
+        let (client_public, nonce, wrapped) = wrap_for_server(&public_key, &[1u8; 32], "key-a");
+
+        let key = unwrap_data_key(
+            &server_private_key,
+            &client_public,
+            &nonce,
+            &wrapped,
+            "key-a",
+        )
+        .unwrap();
+        assert_eq!(*key, [1u8; 32]);
+
+        assert!(unwrap_data_key(
+            &server_private_key,
+            &client_public,
+            &nonce,
+            &wrapped,
+            "key-b"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,14 @@
+pub mod key_wrap;
+pub mod model;
+pub mod persist;
+pub mod routes;
+
+pub use model::{
+    AnalysisUnlockAuditEvent, AnalysisUnlockAuditEventType, AnalysisUnlockSession,
+    AnalysisUnlockSessionStatus, NewAnalysisUnlockSession,
+};
+pub use routes::{
+    delete_analysis_unlock_session_route, get_analysis_unlock_public_key_route,
+    get_analysis_unlock_session_audit_route, get_analysis_unlock_sessions_route,
+    post_analysis_unlock_session_route,
+};
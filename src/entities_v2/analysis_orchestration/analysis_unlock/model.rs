@@ -0,0 +1,181 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AnalysisUnlockSessionStatus {
+    Active,
+    Closed,
+    Expired,
+    Revoked,
+}
+
+impl AnalysisUnlockSessionStatus {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            AnalysisUnlockSessionStatus::Active => "ACTIVE",
+            AnalysisUnlockSessionStatus::Closed => "CLOSED",
+            AnalysisUnlockSessionStatus::Expired => "EXPIRED",
+            AnalysisUnlockSessionStatus::Revoked => "REVOKED",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "ACTIVE" => AnalysisUnlockSessionStatus::Active,
+            "EXPIRED" => AnalysisUnlockSessionStatus::Expired,
+            "REVOKED" => AnalysisUnlockSessionStatus::Revoked,
+            _ => AnalysisUnlockSessionStatus::Closed,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AnalysisUnlockAuditEventType {
+    Unlocked,
+    TraceDecrypted,
+    Closed,
+    Expired,
+    Revoked,
+}
+
+impl AnalysisUnlockAuditEventType {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            AnalysisUnlockAuditEventType::Unlocked => "UNLOCKED",
+            AnalysisUnlockAuditEventType::TraceDecrypted => "TRACE_DECRYPTED",
+            AnalysisUnlockAuditEventType::Closed => "CLOSED",
+            AnalysisUnlockAuditEventType::Expired => "EXPIRED",
+            AnalysisUnlockAuditEventType::Revoked => "REVOKED",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "UNLOCKED" => AnalysisUnlockAuditEventType::Unlocked,
+            "TRACE_DECRYPTED" => AnalysisUnlockAuditEventType::TraceDecrypted,
+            "EXPIRED" => AnalysisUnlockAuditEventType::Expired,
+            "REVOKED" => AnalysisUnlockAuditEventType::Revoked,
+            _ => AnalysisUnlockAuditEventType::Closed,
+        }
+    }
+}
+
+/// A short-lived grant letting the server decrypt one key's traces while it runs one lens.
+///
+/// Only metadata lives here; the unwrapped data key is held in process memory.
+#[derive(Serialize, Debug, Clone)]
+pub struct AnalysisUnlockSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub lens_id: Uuid,
+    pub key_id: String,
+    pub status: AnalysisUnlockSessionStatus,
+    pub consent_derived_context: bool,
+    pub expires_at: NaiveDateTime,
+    pub closed_at: Option<NaiveDateTime>,
+    pub decrypted_trace_count: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::analysis_unlock_sessions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct AnalysisUnlockSessionRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub lens_id: Uuid,
+    pub key_id: String,
+    pub status: String,
+    pub consent_derived_context: bool,
+    pub expires_at: NaiveDateTime,
+    pub closed_at: Option<NaiveDateTime>,
+    pub decrypted_trace_count: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<AnalysisUnlockSessionRow> for AnalysisUnlockSession {
+    fn from(row: AnalysisUnlockSessionRow) -> Self {
+        AnalysisUnlockSession {
+            id: row.id,
+            user_id: row.user_id,
+            lens_id: row.lens_id,
+            key_id: row.key_id,
+            status: AnalysisUnlockSessionStatus::from_db(&row.status),
+            consent_derived_context: row.consent_derived_context,
+            expires_at: row.expires_at,
+            closed_at: row.closed_at,
+            decrypted_trace_count: row.decrypted_trace_count,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::analysis_unlock_sessions)]
+pub struct NewAnalysisUnlockSession {
+    pub user_id: Uuid,
+    pub lens_id: Uuid,
+    pub key_id: String,
+    pub status: String,
+    pub consent_derived_context: bool,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AnalysisUnlockAuditEvent {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: AnalysisUnlockAuditEventType,
+    pub trace_id: Option<Uuid>,
+    pub analysis_id: Option<Uuid>,
+    pub detail: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::analysis_unlock_audit_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct AnalysisUnlockAuditEventRow {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: String,
+    pub trace_id: Option<Uuid>,
+    pub analysis_id: Option<Uuid>,
+    pub detail: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<AnalysisUnlockAuditEventRow> for AnalysisUnlockAuditEvent {
+    fn from(row: AnalysisUnlockAuditEventRow) -> Self {
+        AnalysisUnlockAuditEvent {
+            id: row.id,
+            session_id: row.session_id,
+            user_id: row.user_id,
+            event_type: AnalysisUnlockAuditEventType::from_db(&row.event_type),
+            trace_id: row.trace_id,
+            analysis_id: row.analysis_id,
+            detail: row.detail,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::analysis_unlock_audit_events)]
+pub struct NewAnalysisUnlockAuditEvent {
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: String,
+    pub trace_id: Option<Uuid>,
+    pub analysis_id: Option<Uuid>,
+    pub detail: Option<String>,
+}
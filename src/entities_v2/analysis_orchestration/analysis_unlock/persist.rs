@@ -0,0 +1,226 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Text, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::schema::{analysis_unlock_audit_events, analysis_unlock_sessions};
+
+use super::model::{
+    AnalysisUnlockAuditEvent, AnalysisUnlockAuditEventRow, AnalysisUnlockAuditEventType,
+    AnalysisUnlockSession, AnalysisUnlockSessionRow, AnalysisUnlockSessionStatus,
+    NewAnalysisUnlockAuditEvent, NewAnalysisUnlockSession,
+};
+
+#[derive(diesel::QueryableByName)]
+struct IdRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+}
+
+impl NewAnalysisUnlockSession {
+    pub fn create(self, pool: &DbPool) -> Result<AnalysisUnlockSession, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = diesel::insert_into(analysis_unlock_sessions::table)
+            .values(&self)
+            .returning(AnalysisUnlockSessionRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+}
+
+impl AnalysisUnlockSession {
+    pub fn find(id: Uuid, pool: &DbPool) -> Result<AnalysisUnlockSession, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = analysis_unlock_sessions::table
+            .find(id)
+            .select(AnalysisUnlockSessionRow::as_select())
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| {
+                PpdcError::new(
+                    404,
+                    ErrorType::ApiError,
+                    "Analysis unlock session not found".to_string(),
+                )
+            })?;
+        Ok(row.into())
+    }
+
+    pub fn list_for_user(
+        user_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<Vec<AnalysisUnlockSession>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = analysis_unlock_sessions::table
+            .filter(analysis_unlock_sessions::user_id.eq(user_id))
+            .order(analysis_unlock_sessions::created_at.desc())
+            .select(AnalysisUnlockSessionRow::as_select())
+            .load(&mut conn)?;
+        Ok(rows.into_iter().map(AnalysisUnlockSession::from).collect())
+    }
+
+    /// Whether traces encrypted under `key_id` may currently be analyzed for this lens.
+    pub fn has_active_for_lens_key(
+        lens_id: Uuid,
+        key_id: &str,
+        pool: &DbPool,
+    ) -> Result<bool, PpdcError> {
+        let mut conn = pool.get()?;
+        let found = analysis_unlock_sessions::table
+            .filter(analysis_unlock_sessions::lens_id.eq(lens_id))
+            .filter(analysis_unlock_sessions::key_id.eq(key_id))
+            .filter(
+                analysis_unlock_sessions::status.eq(AnalysisUnlockSessionStatus::Active.to_db()),
+            )
+            .filter(analysis_unlock_sessions::expires_at.gt(Utc::now().naive_utc()))
+            .select(analysis_unlock_sessions::id)
+            .first::<Uuid>(&mut conn)
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// Latest encrypted trace of the user under `key_id`, used to check an unwrapped key.
+    pub fn find_probe_trace_id(
+        user_id: Uuid,
+        key_id: &str,
+        pool: &DbPool,
+    ) -> Result<Option<Uuid>, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = sql_query(
+            r#"
+SELECT id
+FROM traces
+WHERE user_id = $1
+  AND is_encrypted = TRUE
+  AND content <> ''
+  AND encryption_metadata->>'key_id' = $2
+ORDER BY interaction_date DESC
+LIMIT 1
+            "#,
+        )
+        .bind::<SqlUuid, _>(user_id)
+        .bind::<Text, _>(key_id)
+        .get_result::<IdRow>(&mut conn)
+        .optional()?;
+        Ok(row.map(|row| row.id))
+    }
+
+    /// Moves an active session to a final status and audits it; finished sessions are returned unchanged.
+    pub fn finish(
+        self,
+        status: AnalysisUnlockSessionStatus,
+        detail: Option<String>,
+        pool: &DbPool,
+    ) -> Result<AnalysisUnlockSession, PpdcError> {
+        let mut conn = pool.get()?;
+        let now = Utc::now().naive_utc();
+        let updated = diesel::update(
+            analysis_unlock_sessions::table
+                .filter(analysis_unlock_sessions::id.eq(self.id))
+                .filter(
+                    analysis_unlock_sessions::status
+                        .eq(AnalysisUnlockSessionStatus::Active.to_db()),
+                ),
+        )
+        .set((
+            analysis_unlock_sessions::status.eq(status.to_db()),
+            analysis_unlock_sessions::closed_at.eq(Some(now)),
+            analysis_unlock_sessions::updated_at.eq(now),
+        ))
+        .returning(AnalysisUnlockSessionRow::as_returning())
+        .get_result(&mut conn)
+        .optional()?;
+
+        let Some(updated) = updated else {
+            return AnalysisUnlockSession::find(self.id, pool);
+        };
+        let event_type = match status {
+            AnalysisUnlockSessionStatus::Expired => AnalysisUnlockAuditEventType::Expired,
+            AnalysisUnlockSessionStatus::Revoked => AnalysisUnlockAuditEventType::Revoked,
+            _ => AnalysisUnlockAuditEventType::Closed,
+        };
+        AnalysisUnlockAuditEvent::record(
+            self.id,
+            self.user_id,
+            event_type,
+            None,
+            None,
+            detail,
+            pool,
+        )?;
+        Ok(updated.into())
+    }
+
+    /// Audits one in-memory decryption and bumps the session counter.
+    pub fn record_trace_decrypted(
+        session_id: Uuid,
+        user_id: Uuid,
+        trace_id: Uuid,
+        analysis_id: Option<Uuid>,
+        pool: &DbPool,
+    ) -> Result<(), PpdcError> {
+        let mut conn = pool.get()?;
+        diesel::update(analysis_unlock_sessions::table.find(session_id))
+            .set((
+                analysis_unlock_sessions::decrypted_trace_count
+                    .eq(analysis_unlock_sessions::decrypted_trace_count + 1),
+                analysis_unlock_sessions::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(&mut conn)?;
+        AnalysisUnlockAuditEvent::record(
+            session_id,
+            user_id,
+            AnalysisUnlockAuditEventType::TraceDecrypted,
+            Some(trace_id),
+            analysis_id,
+            None,
+            pool,
+        )?;
+        Ok(())
+    }
+}
+
+impl AnalysisUnlockAuditEvent {
+    pub fn record(
+        session_id: Uuid,
+        user_id: Uuid,
+        event_type: AnalysisUnlockAuditEventType,
+        trace_id: Option<Uuid>,
+        analysis_id: Option<Uuid>,
+        detail: Option<String>,
+        pool: &DbPool,
+    ) -> Result<AnalysisUnlockAuditEvent, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = diesel::insert_into(analysis_unlock_audit_events::table)
+            .values(NewAnalysisUnlockAuditEvent {
+                session_id,
+                user_id,
+                event_type: event_type.to_db().to_string(),
+                trace_id,
+                analysis_id,
+                detail,
+            })
+            .returning(AnalysisUnlockAuditEventRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+
+    pub fn list_for_session(
+        session_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<Vec<AnalysisUnlockAuditEvent>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = analysis_unlock_audit_events::table
+            .filter(analysis_unlock_audit_events::session_id.eq(session_id))
+            .order(analysis_unlock_audit_events::created_at.asc())
+            .select(AnalysisUnlockAuditEventRow::as_select())
+            .load(&mut conn)?;
+        Ok(rows
+            .into_iter()
+            .map(AnalysisUnlockAuditEvent::from)
+            .collect())
+    }
+}
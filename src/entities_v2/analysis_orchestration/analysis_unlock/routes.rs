@@ -0,0 +1,211 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Json, Path},
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    error::{ErrorType, PpdcError},
+    lens::Lens,
+    session::Session,
+    trace::{Trace, TraceEncryptionMetadata},
+};
+use crate::environment;
+use crate::work_analyzer::analysis_unlock::{release_key, spawn_unlocked_lens_run};
+
+use super::key_wrap::{server_public_key, unwrap_data_key, UNLOCK_WRAP_ALGORITHM};
+use super::model::{
+    AnalysisUnlockAuditEvent, AnalysisUnlockAuditEventType, AnalysisUnlockSession,
+    AnalysisUnlockSessionStatus, NewAnalysisUnlockSession,
+};
+
+const DEFAULT_UNLOCK_TTL_SECONDS: i64 = 600;
+const MIN_UNLOCK_TTL_SECONDS: i64 = 60;
+const MAX_UNLOCK_TTL_SECONDS: i64 = 900;
+
+#[derive(Serialize)]
+pub struct AnalysisUnlockPublicKey {
+    pub algorithm: &'static str,
+    pub public_key: String,
+}
+
+#[derive(Deserialize)]
+pub struct NewAnalysisUnlockSessionDto {
+    pub lens_id: Uuid,
+    pub key_id: String,
+    pub client_public_key: String,
+    pub wrap_nonce: String,
+    pub wrapped_key: String,
+    pub ttl_seconds: Option<i64>,
+    pub consent_derived_context: bool,
+}
+
+fn unlock_private_key() -> Result<String, PpdcError> {
+    environment::get_analysis_unlock_private_key().ok_or_else(|| {
+        PpdcError::new(
+            503,
+            ErrorType::ApiError,
+            "Analysis unlock is not configured on this server".to_string(),
+        )
+    })
+}
+
+fn find_owned_session(
+    id: Uuid,
+    user_id: Uuid,
+    pool: &DbPool,
+) -> Result<AnalysisUnlockSession, PpdcError> {
+    let session = AnalysisUnlockSession::find(id, pool)?;
+    if session.user_id != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    Ok(session)
+}
+
+#[debug_handler]
+pub async fn get_analysis_unlock_public_key_route(
+    Extension(session): Extension<Session>,
+) -> Result<Json<AnalysisUnlockPublicKey>, PpdcError> {
+    session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    Ok(Json(AnalysisUnlockPublicKey {
+        algorithm: UNLOCK_WRAP_ALGORITHM,
+        public_key: server_public_key(&unlock_private_key()?)?,
+    }))
+}
+
+/// Unwraps a data key for one lens, checks it against a trace, and starts the unlocked run.
+#[debug_handler]
+pub async fn post_analysis_unlock_session_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<NewAnalysisUnlockSessionDto>,
+) -> Result<Json<AnalysisUnlockSession>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    if !payload.consent_derived_context {
+        return Err(PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "consent_derived_context must be true to unlock analysis".to_string(),
+        ));
+    }
+    let ttl_seconds = payload.ttl_seconds.unwrap_or(DEFAULT_UNLOCK_TTL_SECONDS);
+    if !(MIN_UNLOCK_TTL_SECONDS..=MAX_UNLOCK_TTL_SECONDS).contains(&ttl_seconds) {
+        return Err(PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            format!(
+                "ttl_seconds must be between {} and {}",
+                MIN_UNLOCK_TTL_SECONDS, MAX_UNLOCK_TTL_SECONDS
+            ),
+        ));
+    }
+    let key_id = payload.key_id.trim().to_string();
+    let lens = Lens::find_full_lens(payload.lens_id, &pool)?;
+    if lens.user_id != Some(user_id) {
+        return Err(PpdcError::unauthorized());
+    }
+
+    let key = unwrap_data_key(
+        &unlock_private_key()?,
+        &payload.client_public_key,
+        &payload.wrap_nonce,
+        &payload.wrapped_key,
+        &key_id,
+    )?;
+    let probe_trace_id = AnalysisUnlockSession::find_probe_trace_id(user_id, &key_id, &pool)?
+        .ok_or_else(|| {
+            PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                "No encrypted trace uses this key_id".to_string(),
+            )
+        })?;
+    let probe_trace = Trace::find_full_trace(probe_trace_id, &pool)?;
+    let metadata = probe_trace
+        .encryption_metadata
+        .as_ref()
+        .ok_or_else(|| {
+            PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                "No encrypted trace uses this key_id".to_string(),
+            )
+        })
+        .and_then(TraceEncryptionMetadata::parse)?;
+    metadata
+        .decrypt_content(&probe_trace.content, &key)
+        .map_err(|_| {
+            PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                "wrapped_key does not decrypt traces under this key_id".to_string(),
+            )
+        })?;
+
+    let unlock_session = NewAnalysisUnlockSession {
+        user_id,
+        lens_id: lens.id,
+        key_id,
+        status: AnalysisUnlockSessionStatus::Active.to_db().to_string(),
+        consent_derived_context: true,
+        expires_at: (Utc::now() + Duration::seconds(ttl_seconds)).naive_utc(),
+    }
+    .create(&pool)?;
+    AnalysisUnlockAuditEvent::record(
+        unlock_session.id,
+        user_id,
+        AnalysisUnlockAuditEventType::Unlocked,
+        None,
+        None,
+        Some(format!("ttl_seconds={}", ttl_seconds)),
+        &pool,
+    )?;
+
+    lens.plan_pending_analyses_for_target(&pool)?;
+    spawn_unlocked_lens_run(unlock_session.clone(), key);
+
+    Ok(Json(unlock_session))
+}
+
+#[debug_handler]
+pub async fn get_analysis_unlock_sessions_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<AnalysisUnlockSession>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    Ok(Json(AnalysisUnlockSession::list_for_user(user_id, &pool)?))
+}
+
+#[debug_handler]
+pub async fn get_analysis_unlock_session_audit_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<AnalysisUnlockAuditEvent>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let unlock_session = find_owned_session(id, user_id, &pool)?;
+    Ok(Json(AnalysisUnlockAuditEvent::list_for_session(
+        unlock_session.id,
+        &pool,
+    )?))
+}
+
+/// Drops the data key right away; analyses not yet claimed stay pending until the next unlock.
+#[debug_handler]
+pub async fn delete_analysis_unlock_session_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AnalysisUnlockSession>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let unlock_session = find_owned_session(id, user_id, &pool)?;
+    release_key(unlock_session.id);
+    Ok(Json(unlock_session.finish(
+        AnalysisUnlockSessionStatus::Revoked,
+        None,
+        &pool,
+    )?))
+}
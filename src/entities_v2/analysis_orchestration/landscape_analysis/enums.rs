@@ -9,6 +9,8 @@ pub enum LandscapeProcessingState {
     ReplayRequested,
     Completed,
     Failed,
+    /// The trace is encrypted and this instance held no key for it; reopened by an unlock session.
+    Locked,
 }
 
 impl LandscapeProcessingState {
@@ -20,6 +22,7 @@ impl LandscapeProcessingState {
             LandscapeProcessingState::ReplayRequested => "REPLAY_REQUESTED",
             LandscapeProcessingState::Completed => "COMPLETED",
             LandscapeProcessingState::Failed => "FAILED",
+            LandscapeProcessingState::Locked => "LOCKED",
         }
     }
 
@@ -32,6 +35,7 @@ impl LandscapeProcessingState {
             "REPLAY_REQUESTED" | "replay_requested" => LandscapeProcessingState::ReplayRequested,
             "COMPLETED" | "completed" => LandscapeProcessingState::Completed,
             "FAILED" | "failed" => LandscapeProcessingState::Failed,
+            "LOCKED" | "locked" => LandscapeProcessingState::Locked,
             _ => LandscapeProcessingState::Pending,
         }
     }
@@ -42,6 +46,8 @@ impl LandscapeProcessingState {
 pub enum LandscapeAnalysisType {
     DailyRecap,
     WeeklyRecap,
+    MonthlyRecap,
+    CustomRecap,
     Hlp,
     Bio,
     TraceIncremental,
@@ -52,6 +58,8 @@ impl LandscapeAnalysisType {
         match self {
             LandscapeAnalysisType::DailyRecap => "DAILY_RECAP",
             LandscapeAnalysisType::WeeklyRecap => "WEEKLY_RECAP",
+            LandscapeAnalysisType::MonthlyRecap => "MONTHLY_RECAP",
+            LandscapeAnalysisType::CustomRecap => "CUSTOM_RECAP",
             LandscapeAnalysisType::Hlp => "HLP",
             LandscapeAnalysisType::Bio => "BIO",
             LandscapeAnalysisType::TraceIncremental => "TRACE_INCREMENTAL",
@@ -62,11 +70,21 @@ impl LandscapeAnalysisType {
         match value {
             "DAILY_RECAP" | "daily_recap" => LandscapeAnalysisType::DailyRecap,
             "WEEKLY_RECAP" | "weekly_recap" => LandscapeAnalysisType::WeeklyRecap,
+            "MONTHLY_RECAP" | "monthly_recap" => LandscapeAnalysisType::MonthlyRecap,
+            "CUSTOM_RECAP" | "custom_recap" => LandscapeAnalysisType::CustomRecap,
             "HLP" | "hlp" => LandscapeAnalysisType::Hlp,
             "BIO" | "bio" => LandscapeAnalysisType::Bio,
             _ => LandscapeAnalysisType::TraceIncremental,
         }
     }
+
+    /// Recap types that roll up daily and weekly summaries instead of reading traces.
+    pub fn is_period_rollup(self) -> bool {
+        matches!(
+            self,
+            LandscapeAnalysisType::MonthlyRecap | LandscapeAnalysisType::CustomRecap
+        )
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -10,14 +10,16 @@ pub use model::{
 };
 pub use persist::{
     add_landmark_ref, add_trace_input, add_trace_mirror_input, claim_next_pending_for_lens,
-    copy_landmark_links_from_analysis, create_for_trace_and_lens,
+    copy_landmark_links_from_analysis, create_custom_recap_for_lens, create_for_trace_and_lens,
     create_for_trace_and_lens_with_options, create_for_trace_and_lens_with_options_and_anchor,
     delete_leaf_and_cleanup, find_lens_ids_with_pending_analyses,
-    refresh_pending_summary_covered_inputs_for_trace, replace_covered_inputs_for_period,
+    has_pending_encrypted_analyses_for_key, refresh_pending_summary_covered_inputs_for_trace,
+    reopen_locked_encrypted_analyses_for_key, replace_covered_inputs_for_period,
 };
 pub use routes::{
     delete_analysis_route, get_analysis_parents_route, get_analysis_route,
     get_analysis_trace_mirrors_route, get_analysis_traces_route, get_current_lens_analysis_route,
     get_elements_route, get_landmarks_route, get_last_analysis_route, post_analysis_route,
-    post_replan_autoplay_lenses_route, post_run_pending_analyses_route, NewAnalysisDto,
+    post_custom_recap_route, post_replan_autoplay_lenses_route, post_run_pending_analyses_route,
+    replan_autoplay_lenses, NewAnalysisDto, NewCustomRecapDto, ReplanAutoplayLensesResponse,
 };
@@ -138,6 +138,61 @@ impl NewLandscapeAnalysis {
         }
     }
 
+    /// Builds the monthly recap job that rolls up the weekly recaps of one local calendar month.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_monthly_recap(
+        title: String,
+        subtitle: String,
+        plain_text_state_summary: String,
+        user_id: Uuid,
+        interaction_date: NaiveDateTime,
+        period_start: NaiveDateTime,
+        period_end: NaiveDateTime,
+        parent_analysis_id: Option<Uuid>,
+        replayed_from_id: Option<Uuid>,
+    ) -> NewLandscapeAnalysis {
+        NewLandscapeAnalysis {
+            title,
+            subtitle,
+            plain_text_state_summary,
+            interaction_date,
+            period_start,
+            period_end,
+            user_id,
+            parent_analysis_id,
+            analyzed_trace_id: None,
+            replayed_from_id,
+            trace_mirror_id: None,
+            landscape_analysis_type: LandscapeAnalysisType::MonthlyRecap,
+        }
+    }
+
+    /// Builds an on-demand recap job over an arbitrary date range, anchored on the range end.
+    pub fn new_custom_recap(
+        title: String,
+        subtitle: String,
+        plain_text_state_summary: String,
+        user_id: Uuid,
+        period_start: NaiveDateTime,
+        period_end: NaiveDateTime,
+        parent_analysis_id: Option<Uuid>,
+    ) -> NewLandscapeAnalysis {
+        NewLandscapeAnalysis {
+            title,
+            subtitle,
+            plain_text_state_summary,
+            interaction_date: period_end,
+            period_start,
+            period_end,
+            user_id,
+            parent_analysis_id,
+            analyzed_trace_id: None,
+            replayed_from_id: None,
+            trace_mirror_id: None,
+            landscape_analysis_type: LandscapeAnalysisType::CustomRecap,
+        }
+    }
+
     /// Builds the special high-level-project analysis that is treated as contextual input for a lens.
     pub fn new_hlp(
         title: String,
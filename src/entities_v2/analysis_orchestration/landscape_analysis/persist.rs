@@ -9,10 +9,12 @@ use uuid::Uuid;
 
 use crate::db::DbPool;
 use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::entities_v2::landmark_version::LandmarkVersion;
 use crate::entities_v2::reference::Reference;
 use crate::entities_v2::{
+    analysis_unlock::AnalysisUnlockSession,
     lens::Lens,
-    trace::{Trace, TraceType},
+    trace::{Trace, TraceEncryptionMetadata, TraceType},
     trace_search::TraceSearchDocument,
     user::User,
 };
@@ -263,6 +265,45 @@ fn week_period_for_datetime(
     ))
 }
 
+/// Computes the user-local calendar month window used by monthly recap analyses.
+fn month_period_for_datetime(
+    datetime_utc: NaiveDateTime,
+    tz: Tz,
+) -> Result<(NaiveDateTime, NaiveDateTime), PpdcError> {
+    let utc_dt = DateTime::<Utc>::from_naive_utc_and_offset(datetime_utc, Utc);
+    let local_date = utc_dt.with_timezone(&tz).date_naive();
+    let (month_start_date, next_month_start_date) = month_bounds_for_date(local_date)?;
+
+    let local_month_start = local_midnight(tz, month_start_date)?;
+    let local_month_end = local_midnight(tz, next_month_start_date)?;
+
+    Ok((
+        local_month_start.with_timezone(&Utc).naive_utc(),
+        local_month_end.with_timezone(&Utc).naive_utc(),
+    ))
+}
+
+/// Returns the first day of the month containing the date and the first day of the following month.
+fn month_bounds_for_date(date: NaiveDate) -> Result<(NaiveDate, NaiveDate), PpdcError> {
+    let invalid_month = || {
+        PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!("Failed to compute month bounds for {}", date),
+        )
+    };
+    let month_start =
+        NaiveDate::from_ymd_opt(date.year(), date.month(), 1).ok_or_else(invalid_month)?;
+    let next_month_start = if date.month() == 12 {
+        NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+    }
+    .ok_or_else(invalid_month)?;
+
+    Ok((month_start, next_month_start))
+}
+
 /// Finds an existing recap analysis whose coverage period already contains the given moment for this lens.
 fn find_analysis_covering_moment_for_lens(
     lens_id: Uuid,
@@ -488,7 +529,7 @@ pub fn create_for_trace_and_lens_with_options_and_anchor(
     })?;
     let user = User::find(&user_id, pool)?;
     let trace = Trace::find_full_trace(trace_id, pool)?;
-    if trace.is_encrypted {
+    if trace.is_encrypted && !is_trace_unlocked_for_lens(&trace, lens_id, pool)? {
         return Ok(vec![]);
     }
     let trace_datetime = trace_effective_datetime(&trace);
@@ -675,12 +716,147 @@ pub fn create_for_trace_and_lens_with_options_and_anchor(
                 weekly.id, lens_id, user_id, week_start, week_end, pool,
             )?);
         }
+
+        if user.monthly_recap_enabled {
+            if let Some(existing_monthly) = find_analysis_covering_moment_for_lens(
+                lens_id,
+                LandscapeAnalysisType::MonthlyRecap,
+                trace_datetime,
+                pool,
+            )? {
+                let _ = replace_covered_inputs_for_period(
+                    existing_monthly.id,
+                    lens_id,
+                    user_id,
+                    existing_monthly.period_start,
+                    existing_monthly.period_end,
+                    pool,
+                )?;
+                let _ = refresh_recap_processing_state(
+                    existing_monthly.id,
+                    lens_id,
+                    user_id,
+                    existing_monthly.period_start,
+                    existing_monthly.period_end,
+                    pool,
+                )?;
+            } else {
+                let previous_monthly = find_latest_prior_analysis_for_lens(
+                    lens_id,
+                    LandscapeAnalysisType::MonthlyRecap,
+                    trace_datetime,
+                    pool,
+                )?;
+                let (candidate_month_start, candidate_month_end) =
+                    month_period_for_datetime(trace_datetime, tz)?;
+                let (month_start, month_end) = adjust_recap_period_start_for_previous_period(
+                    candidate_month_start,
+                    candidate_month_end,
+                    previous_monthly.map(|analysis| analysis.period_end),
+                )?;
+                let monthly = NewLandscapeAnalysis::new_monthly_recap(
+                    format!("Monthly recap {}", month_start.date()),
+                    String::new(),
+                    String::new(),
+                    user_id,
+                    trace_datetime,
+                    month_start,
+                    month_end,
+                    None,
+                    None,
+                )
+                .create_for_lens(lens_id, pool)?;
+                let _ = replace_covered_inputs_for_period(
+                    monthly.id,
+                    lens_id,
+                    user_id,
+                    month_start,
+                    month_end,
+                    pool,
+                )?;
+                created.push(refresh_recap_processing_state(
+                    monthly.id,
+                    lens_id,
+                    user_id,
+                    month_start,
+                    month_end,
+                    pool,
+                )?);
+            }
+        }
     }
 
     Ok(created)
 }
 
-/// Rebuilds the covered inputs of pending or blocked daily and weekly recaps affected by one newly available trace.
+/// Queues an on-demand recap over the user-local dates `start_date..=end_date` for a lens.
+///
+/// Requesting the same range twice returns the existing recap instead of creating a duplicate.
+pub fn create_custom_recap_for_lens(
+    lens_id: Uuid,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    pool: &DbPool,
+) -> Result<LandscapeAnalysis, PpdcError> {
+    let lens = Lens::find_full_lens(lens_id, pool)?;
+    let user_id = lens.user_id.ok_or_else(|| {
+        PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "Lens user_id is missing".to_string(),
+        )
+    })?;
+    let user = User::find(&user_id, pool)?;
+    let tz = parse_user_timezone_or_utc(&user);
+    let period_start = local_midnight(tz, start_date)?
+        .with_timezone(&Utc)
+        .naive_utc();
+    let period_end = local_midnight(tz, end_date + Duration::days(1))?
+        .with_timezone(&Utc)
+        .naive_utc();
+
+    let mut conn = pool.get()?;
+    let existing_id = lens_analysis_scopes::table
+        .inner_join(
+            landscape_analyses::table
+                .on(lens_analysis_scopes::landscape_analysis_id.eq(landscape_analyses::id)),
+        )
+        .filter(lens_analysis_scopes::lens_id.eq(lens_id))
+        .filter(
+            landscape_analyses::landscape_analysis_type
+                .eq(LandscapeAnalysisType::CustomRecap.to_db()),
+        )
+        .filter(landscape_analyses::period_start.eq(period_start))
+        .filter(landscape_analyses::period_end.eq(period_end))
+        .select(landscape_analyses::id)
+        .first::<Uuid>(&mut conn)
+        .optional()?;
+    if let Some(existing_id) = existing_id {
+        return LandscapeAnalysis::find_full_analysis(existing_id, pool);
+    }
+
+    let custom = NewLandscapeAnalysis::new_custom_recap(
+        format!("Recap {} - {}", start_date, end_date),
+        String::new(),
+        String::new(),
+        user_id,
+        period_start,
+        period_end,
+        None,
+    )
+    .create_for_lens(lens_id, pool)?;
+    let _ = replace_covered_inputs_for_period(
+        custom.id,
+        lens_id,
+        user_id,
+        period_start,
+        period_end,
+        pool,
+    )?;
+    refresh_recap_processing_state(custom.id, lens_id, user_id, period_start, period_end, pool)
+}
+
+/// Rebuilds the covered inputs of pending or blocked period recaps affected by one newly available trace.
 pub fn refresh_pending_summary_covered_inputs_for_trace(
     lens_id: Uuid,
     user_id: Uuid,
@@ -699,6 +875,8 @@ pub fn refresh_pending_summary_covered_inputs_for_trace(
         .filter(landscape_analyses::landscape_analysis_type.eq_any(vec![
             LandscapeAnalysisType::DailyRecap.to_db(),
             LandscapeAnalysisType::WeeklyRecap.to_db(),
+            LandscapeAnalysisType::MonthlyRecap.to_db(),
+            LandscapeAnalysisType::CustomRecap.to_db(),
         ]))
         .filter(landscape_analyses::processing_state.eq_any(vec![
             LandscapeProcessingState::Pending.to_db(),
@@ -746,6 +924,79 @@ pub fn refresh_pending_summary_covered_inputs_for_trace(
     ))
 }
 
+/// Encrypted traces are only planned while the lens holds an active unlock session for their key.
+fn is_trace_unlocked_for_lens(
+    trace: &Trace,
+    lens_id: Uuid,
+    pool: &DbPool,
+) -> Result<bool, PpdcError> {
+    let Some(metadata) = trace
+        .encryption_metadata
+        .as_ref()
+        .and_then(|value| TraceEncryptionMetadata::parse(value).ok())
+    else {
+        return Ok(false);
+    };
+    AnalysisUnlockSession::has_active_for_lens_key(lens_id, &metadata.key_id, pool)
+}
+
+/// Whether the lens still has pending analyses of traces encrypted under `key_id`.
+pub fn has_pending_encrypted_analyses_for_key(
+    lens_id: Uuid,
+    key_id: &str,
+    pool: &DbPool,
+) -> Result<bool, PpdcError> {
+    let mut conn = pool.get()?;
+    let row = sql_query(
+        r#"
+SELECT EXISTS (
+    SELECT 1
+    FROM landscape_analyses la
+    INNER JOIN lens_analysis_scopes las
+        ON las.landscape_analysis_id = la.id
+    INNER JOIN traces t
+        ON t.id = la.analyzed_trace_id
+    WHERE las.lens_id = $1
+      AND la.processing_state IN ('PENDING', 'RUNNING')
+      AND t.is_encrypted = TRUE
+      AND t.encryption_metadata->>'key_id' = $2
+) AS value
+        "#,
+    )
+    .bind::<SqlUuid, _>(lens_id)
+    .bind::<diesel::sql_types::Text, _>(key_id)
+    .get_result::<BoolRow>(&mut conn)?;
+    Ok(row.value)
+}
+
+/// Puts the lens analyses locked on traces encrypted under `key_id` back to pending, once an
+/// instance holding that key is about to run the lens.
+pub fn reopen_locked_encrypted_analyses_for_key(
+    lens_id: Uuid,
+    key_id: &str,
+    pool: &DbPool,
+) -> Result<usize, PpdcError> {
+    let mut conn = pool.get()?;
+    let reopened = sql_query(
+        r#"
+UPDATE landscape_analyses la
+SET processing_state = 'PENDING',
+    updated_at = NOW()
+FROM lens_analysis_scopes las, traces t
+WHERE las.landscape_analysis_id = la.id
+  AND las.lens_id = $1
+  AND t.id = la.analyzed_trace_id
+  AND la.processing_state = 'LOCKED'
+  AND t.is_encrypted = TRUE
+  AND t.encryption_metadata->>'key_id' = $2
+        "#,
+    )
+    .bind::<SqlUuid, _>(lens_id)
+    .bind::<diesel::sql_types::Text, _>(key_id)
+    .execute(&mut conn)?;
+    Ok(reopened)
+}
+
 /// Atomically claims the next runnable pending analysis for a lens so concurrent workers do not process the same job.
 pub fn claim_next_pending_for_lens(
     lens_id: Uuid,
@@ -764,8 +1015,22 @@ WITH candidate AS (
     WHERE las.lens_id = $1
       AND la.processing_state = 'PENDING'
       AND la.period_end <= $2
+      AND NOT EXISTS (
+        SELECT 1
+        FROM traces et
+        WHERE et.id = la.analyzed_trace_id
+          AND et.is_encrypted = TRUE
+          AND NOT EXISTS (
+              SELECT 1
+              FROM analysis_unlock_sessions aus
+              WHERE aus.lens_id = las.lens_id
+                AND aus.status = 'ACTIVE'
+                AND aus.expires_at > NOW()
+                AND aus.key_id = et.encryption_metadata->>'key_id'
+          )
+      )
       AND (
-        la.landscape_analysis_type NOT IN ('DAILY_RECAP', 'WEEKLY_RECAP')
+        la.landscape_analysis_type NOT IN ('DAILY_RECAP', 'WEEKLY_RECAP', 'MONTHLY_RECAP', 'CUSTOM_RECAP')
         OR NOT EXISTS (
             SELECT 1
             FROM traces t
@@ -783,11 +1048,27 @@ WITH candidate AS (
               )
         )
       )
+      AND (
+        la.landscape_analysis_type NOT IN ('MONTHLY_RECAP', 'CUSTOM_RECAP')
+        OR NOT EXISTS (
+            SELECT 1
+            FROM landscape_analyses pending_recap
+            INNER JOIN lens_analysis_scopes las_pending
+                ON las_pending.landscape_analysis_id = pending_recap.id
+            WHERE las_pending.lens_id = las.lens_id
+              AND pending_recap.landscape_analysis_type IN ('DAILY_RECAP', 'WEEKLY_RECAP')
+              AND pending_recap.processing_state IN ('PENDING', 'BLOCKED_WAITING_COVERAGE', 'RUNNING')
+              AND pending_recap.period_start >= la.period_start
+              AND pending_recap.period_end <= la.period_end
+        )
+      )
     ORDER BY
       la.period_end ASC,
       CASE la.landscape_analysis_type
         WHEN 'DAILY_RECAP' THEN 0
         WHEN 'WEEKLY_RECAP' THEN 1
+        WHEN 'MONTHLY_RECAP' THEN 2
+        WHEN 'CUSTOM_RECAP' THEN 3
         ELSE 0
       END ASC,
       la.created_at ASC
@@ -829,6 +1110,20 @@ INNER JOIN users u
     ON u.id = l.user_id
 WHERE la.processing_state = 'PENDING'
   AND la.period_end <= NOW()
+  AND NOT EXISTS (
+    SELECT 1
+    FROM traces et
+    WHERE et.id = la.analyzed_trace_id
+      AND et.is_encrypted = TRUE
+      AND NOT EXISTS (
+          SELECT 1
+          FROM analysis_unlock_sessions aus
+          WHERE aus.lens_id = las.lens_id
+            AND aus.status = 'ACTIVE'
+            AND aus.expires_at > NOW()
+            AND aus.key_id = et.encryption_metadata->>'key_id'
+      )
+  )
   AND l.processing_state != 'FAILED'
   AND u.ai_features_enabled = TRUE
   AND NOT (
@@ -1043,23 +1338,27 @@ pub fn add_landmark_ref(
     pool: &DbPool,
 ) -> Result<(), PpdcError> {
     let mut conn = pool.get()?;
-    diesel::insert_into(landscape_landmarks::table)
-        .values((
-            landscape_landmarks::landscape_analysis_id.eq(landscape_analysis_id),
-            landscape_landmarks::landmark_id.eq(landmark_id),
-            landscape_landmarks::relation_type.eq("REFERENCED"),
-        ))
-        .on_conflict((
-            landscape_landmarks::landscape_analysis_id,
-            landscape_landmarks::landmark_id,
-            landscape_landmarks::relation_type,
-        ))
-        .do_nothing()
-        .execute(&mut conn)?;
+    conn.transaction::<(), diesel::result::Error, _>(|conn| {
+        diesel::insert_into(landscape_landmarks::table)
+            .values((
+                landscape_landmarks::landscape_analysis_id.eq(landscape_analysis_id),
+                landscape_landmarks::landmark_id.eq(landmark_id),
+                landscape_landmarks::relation_type.eq("REFERENCED"),
+            ))
+            .on_conflict((
+                landscape_landmarks::landscape_analysis_id,
+                landscape_landmarks::landmark_id,
+                landscape_landmarks::relation_type,
+            ))
+            .do_nothing()
+            .execute(conn)?;
+        LandmarkVersion::ensure_for_analysis(landscape_analysis_id, landmark_id, None, conn)
+    })?;
     Ok(())
 }
 
 /// Copies referenced landmark links from one analysis to another when a rerun should inherit prior context.
+/// Landmark versions are carried over with the source counts, so the target starts from the source state.
 pub fn copy_landmark_links_from_analysis(
     source_landscape_analysis_id: Uuid,
     target_landscape_analysis_id: Uuid,
@@ -1067,36 +1366,45 @@ pub fn copy_landmark_links_from_analysis(
 ) -> Result<usize, PpdcError> {
     let mut conn = pool.get()?;
 
-    let source_landmark_ids = landscape_landmarks::table
-        .filter(landscape_landmarks::landscape_analysis_id.eq(source_landscape_analysis_id))
-        .select(landscape_landmarks::landmark_id)
-        .distinct()
-        .load::<Uuid>(&mut conn)?;
+    let inserted = conn.transaction::<usize, diesel::result::Error, _>(|conn| {
+        let source_landmark_ids = landscape_landmarks::table
+            .filter(landscape_landmarks::landscape_analysis_id.eq(source_landscape_analysis_id))
+            .select(landscape_landmarks::landmark_id)
+            .distinct()
+            .load::<Uuid>(conn)?;
 
-    let mut inserted = 0usize;
-    for landmark_id in source_landmark_ids {
-        let rows = diesel::insert_into(landscape_landmarks::table)
-            .values((
-                landscape_landmarks::landscape_analysis_id.eq(target_landscape_analysis_id),
-                landscape_landmarks::landmark_id.eq(landmark_id),
-                landscape_landmarks::relation_type.eq("REFERENCED"),
-            ))
-            .on_conflict((
-                landscape_landmarks::landscape_analysis_id,
-                landscape_landmarks::landmark_id,
-                landscape_landmarks::relation_type,
-            ))
-            .do_nothing()
-            .execute(&mut conn)?;
-        inserted += rows as usize;
-    }
+        let mut inserted = 0usize;
+        for landmark_id in source_landmark_ids {
+            let rows = diesel::insert_into(landscape_landmarks::table)
+                .values((
+                    landscape_landmarks::landscape_analysis_id.eq(target_landscape_analysis_id),
+                    landscape_landmarks::landmark_id.eq(landmark_id),
+                    landscape_landmarks::relation_type.eq("REFERENCED"),
+                ))
+                .on_conflict((
+                    landscape_landmarks::landscape_analysis_id,
+                    landscape_landmarks::landmark_id,
+                    landscape_landmarks::relation_type,
+                ))
+                .do_nothing()
+                .execute(conn)?;
+            inserted += rows;
+        }
+
+        LandmarkVersion::copy_from_analysis(
+            source_landscape_analysis_id,
+            target_landscape_analysis_id,
+            conn,
+        )?;
+        Ok(inserted)
+    })?;
 
     Ok(inserted)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::adjust_recap_period_start_for_previous_period;
+    use super::{adjust_recap_period_start_for_previous_period, month_bounds_for_date};
     use chrono::NaiveDate;
 
     fn dt(year: i32, month: u32, day: u32, hour: u32) -> chrono::NaiveDateTime {
@@ -1143,4 +1451,13 @@ mod tests {
 
         assert_eq!(error.status_code, 409);
     }
+
+    #[test]
+    fn month_bounds_roll_over_to_next_year_in_december() {
+        let (start, end) =
+            month_bounds_for_date(NaiveDate::from_ymd_opt(2026, 12, 17).unwrap()).unwrap();
+
+        assert_eq!(start, NaiveDate::from_ymd_opt(2026, 12, 1).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2027, 1, 1).unwrap());
+    }
 }
@@ -11,6 +11,7 @@ use uuid::Uuid;
 
 use crate::db::DbPool;
 use crate::entities_v2::{
+    background_job::{BackgroundJob, BackgroundJobType},
     element::Element,
     error::{ErrorType, PpdcError},
     landmark::Landmark,
@@ -21,13 +22,12 @@ use crate::entities_v2::{
     user::User,
 };
 use crate::environment;
-use crate::work_analyzer;
 
 use super::model::{
     LandscapeAnalysis, LandscapeAnalysisType, LandscapeProcessingState, NewLandscapeAnalysis,
 };
 use super::persist::{
-    delete_leaf_and_cleanup, find_last_analysis_resource, find_lens_ids_with_pending_analyses,
+    create_custom_recap_for_lens, delete_leaf_and_cleanup, find_last_analysis_resource,
 };
 
 #[derive(Deserialize)]
@@ -36,17 +36,14 @@ pub struct NewAnalysisDto {
     pub user_id: Uuid,
 }
 
-#[derive(Serialize)]
-pub struct PendingAnalysesRunError {
-    pub lens_id: Uuid,
-    pub message: String,
-}
+/// Longest range accepted for an on-demand recap, in days.
+const MAX_CUSTOM_RECAP_DAYS: i64 = 366;
 
-#[derive(Serialize)]
-pub struct PendingAnalysesRunResponse {
-    pub candidate_lens_ids: Vec<Uuid>,
-    pub processed_lens_ids: Vec<Uuid>,
-    pub failed: Vec<PendingAnalysesRunError>,
+#[derive(Deserialize)]
+pub struct NewCustomRecapDto {
+    pub lens_id: Option<Uuid>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
 }
 
 #[derive(Serialize)]
@@ -191,12 +188,12 @@ fn filtered_completed_analyses_for_lens(
     Ok(analyses)
 }
 
-/// Internal cron-triggered route that runs pending analysis work for every lens with runnable backlog.
+/// Internal cron-triggered route that queues a sweep of every lens with runnable backlog.
 #[debug_handler]
 pub async fn post_run_pending_analyses_route(
     Extension(pool): Extension<DbPool>,
     headers: HeaderMap,
-) -> Result<Json<PendingAnalysesRunResponse>, PpdcError> {
+) -> Result<Json<BackgroundJob>, PpdcError> {
     let provided_token = headers
         .get("x-internal-cron-token")
         .and_then(|value| value.to_str().ok())
@@ -206,43 +203,15 @@ pub async fn post_run_pending_analyses_route(
         return Err(PpdcError::unauthorized());
     }
 
-    let candidate_lens_ids = find_lens_ids_with_pending_analyses(&pool)?;
-    let mut processed_lens_ids = Vec::new();
-    let mut failed = Vec::new();
-
-    for lens_id in candidate_lens_ids.iter().copied() {
-        match work_analyzer::run_lens(lens_id).await {
-            Ok(_) => processed_lens_ids.push(lens_id),
-            Err(err) => failed.push(PendingAnalysesRunError {
-                lens_id,
-                message: err.message,
-            }),
-        }
-    }
-
-    Ok(Json(PendingAnalysesRunResponse {
-        candidate_lens_ids,
-        processed_lens_ids,
-        failed,
-    }))
+    Ok(Json(BackgroundJob::enqueue_singleton(
+        BackgroundJobType::RunPendingAnalyses,
+        &pool,
+    )?))
 }
 
-/// Internal repair route that re-runs queue planning for autoplay lenses without changing their targets or executing analysis work.
-#[debug_handler]
-pub async fn post_replan_autoplay_lenses_route(
-    Extension(pool): Extension<DbPool>,
-    headers: HeaderMap,
-) -> Result<Json<ReplanAutoplayLensesResponse>, PpdcError> {
-    let provided_token = headers
-        .get("x-internal-cron-token")
-        .and_then(|value| value.to_str().ok())
-        .ok_or_else(PpdcError::unauthorized)?;
-
-    if provided_token != environment::get_internal_cron_token() {
-        return Err(PpdcError::unauthorized());
-    }
-
-    let autoplay_lenses = Lens::get_autoplay_lenses(&pool)?;
+/// Re-runs queue planning for autoplay lenses without changing their targets or executing analysis work.
+pub fn replan_autoplay_lenses(pool: &DbPool) -> Result<ReplanAutoplayLensesResponse, PpdcError> {
+    let autoplay_lenses = Lens::get_autoplay_lenses(pool)?;
     let candidate_lens_ids = autoplay_lenses
         .iter()
         .map(|lens| lens.id)
@@ -270,7 +239,7 @@ pub async fn post_replan_autoplay_lenses_route(
             continue;
         }
 
-        match lens.plan_pending_analyses_for_target(&pool) {
+        match lens.plan_pending_analyses_for_target(pool) {
             Ok(_) => planned_lens_ids.push(lens_id),
             Err(err) => failed.push(ReplanAutoplayLensesError {
                 lens_id,
@@ -279,12 +248,33 @@ pub async fn post_replan_autoplay_lenses_route(
         }
     }
 
-    Ok(Json(ReplanAutoplayLensesResponse {
+    Ok(ReplanAutoplayLensesResponse {
         candidate_lens_ids,
         planned_lens_ids,
         skipped,
         failed,
-    }))
+    })
+}
+
+/// Internal repair route that queues a replan of autoplay lenses.
+#[debug_handler]
+pub async fn post_replan_autoplay_lenses_route(
+    Extension(pool): Extension<DbPool>,
+    headers: HeaderMap,
+) -> Result<Json<BackgroundJob>, PpdcError> {
+    let provided_token = headers
+        .get("x-internal-cron-token")
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(PpdcError::unauthorized)?;
+
+    if provided_token != environment::get_internal_cron_token() {
+        return Err(PpdcError::unauthorized());
+    }
+
+    Ok(Json(BackgroundJob::enqueue_singleton(
+        BackgroundJobType::ReplanAutoplayLenses,
+        &pool,
+    )?))
 }
 
 /// Creates a manual analysis anchored on a given day for the authenticated user.
@@ -346,6 +336,51 @@ pub async fn post_analysis_route(
     Ok(Json(analysis))
 }
 
+/// Queues an on-demand recap for a date range; the analysis sweep runs it once the range has ended.
+#[debug_handler]
+pub async fn post_custom_recap_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<NewCustomRecapDto>,
+) -> Result<Json<LandscapeAnalysis>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    if payload.end_date < payload.start_date {
+        return Err(PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "end_date must not be before start_date".to_string(),
+        ));
+    }
+    if (payload.end_date - payload.start_date).num_days() >= MAX_CUSTOM_RECAP_DAYS {
+        return Err(PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            format!("Recap range cannot exceed {} days", MAX_CUSTOM_RECAP_DAYS),
+        ));
+    }
+
+    let lens_id = match payload.lens_id {
+        Some(lens_id) => lens_id,
+        None => User::find(&user_id, &pool)?
+            .current_lens_id
+            .ok_or_else(|| {
+                PpdcError::new(
+                    400,
+                    ErrorType::ApiError,
+                    "current_lens_id is not set for user".to_string(),
+                )
+            })?,
+    };
+    let lens = Lens::find_full_lens(lens_id, &pool)?;
+    if lens.user_id != Some(user_id) {
+        return Err(PpdcError::unauthorized());
+    }
+
+    let analysis =
+        create_custom_recap_for_lens(lens.id, payload.start_date, payload.end_date, &pool)?;
+    Ok(Json(analysis))
+}
+
 /// Returns the completed analyses currently visible from the user's default lens.
 #[debug_handler]
 pub async fn get_current_lens_analysis_route(
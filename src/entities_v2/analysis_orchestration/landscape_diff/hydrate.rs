@@ -0,0 +1,51 @@
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::PpdcError;
+use crate::entities_v2::landscape_analysis::LandscapeAnalysis;
+use crate::entities_v2::trace_mirror::TraceMirror;
+
+use super::model::LandscapeDiff;
+
+/// Trace mirrors produced along the lineage of an analysis, most recent analysis first.
+fn lineage_trace_mirrors(
+    analysis: &LandscapeAnalysis,
+    pool: &DbPool,
+) -> Result<Vec<TraceMirror>, PpdcError> {
+    let mut trace_mirrors = TraceMirror::find_by_analysis_lineage(analysis.id, pool)?;
+    // A trace mirrored several times in a lineage is represented by its latest mirror.
+    let mut seen_traces = std::collections::HashSet::new();
+    trace_mirrors.retain(|mirror| seen_traces.insert(mirror.trace_id));
+    Ok(trace_mirrors)
+}
+
+impl LandscapeDiff {
+    pub fn between_analyses(
+        base: &LandscapeAnalysis,
+        compared: &LandscapeAnalysis,
+        pool: &DbPool,
+    ) -> Result<LandscapeDiff, PpdcError> {
+        let base_landmarks = base.get_landmarks(None, pool)?;
+        let compared_landmarks = compared.get_landmarks(None, pool)?;
+        let base_trace_mirrors = lineage_trace_mirrors(base, pool)?;
+        let compared_trace_mirrors = lineage_trace_mirrors(compared, pool)?;
+        Ok(LandscapeDiff::compute(
+            base.id,
+            compared.id,
+            &base_landmarks,
+            &compared_landmarks,
+            &base_trace_mirrors,
+            &compared_trace_mirrors,
+        ))
+    }
+
+    pub fn between_analysis_ids(
+        base_analysis_id: Uuid,
+        compared_analysis_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<LandscapeDiff, PpdcError> {
+        let base = LandscapeAnalysis::find_full_analysis(base_analysis_id, pool)?;
+        let compared = LandscapeAnalysis::find_full_analysis(compared_analysis_id, pool)?;
+        LandscapeDiff::between_analyses(&base, &compared, pool)
+    }
+}
@@ -0,0 +1,9 @@
+pub mod hydrate;
+pub mod model;
+pub mod routes;
+
+pub use model::{
+    LandmarkCountChange, LandmarkDiffEntry, LandmarkRename, LandscapeDiff,
+    TraceMirrorPrimaryResourceChange,
+};
+pub use routes::{get_analysis_diff_route, get_lens_diff_route};
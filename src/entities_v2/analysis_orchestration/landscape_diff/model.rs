@@ -0,0 +1,333 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities_v2::landmark::{Landmark, LandmarkType};
+use crate::entities_v2::trace_mirror::TraceMirror;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LandmarkDiffEntry {
+    pub landmark_id: Uuid,
+    pub title: String,
+    pub landmark_type: LandmarkType,
+    pub related_elements_count: i32,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LandmarkRename {
+    pub landmark_id: Uuid,
+    pub base_title: String,
+    pub compared_title: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LandmarkCountChange {
+    pub base_landmark_id: Uuid,
+    pub compared_landmark_id: Uuid,
+    pub title: String,
+    pub base_related_elements_count: i32,
+    pub compared_related_elements_count: i32,
+    pub delta: i32,
+    pub base_last_related_element_at: Option<NaiveDateTime>,
+    pub compared_last_related_element_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TraceMirrorPrimaryResourceChange {
+    pub trace_id: Uuid,
+    pub base_trace_mirror_id: Uuid,
+    pub compared_trace_mirror_id: Uuid,
+    pub base_primary_resource_id: Option<Uuid>,
+    pub base_primary_resource_title: Option<String>,
+    pub compared_primary_resource_id: Option<Uuid>,
+    pub compared_primary_resource_title: Option<String>,
+}
+
+/// What a compared analysis concluded relative to a base analysis, typically the heads of two lenses.
+#[derive(Serialize, Debug, Clone)]
+pub struct LandscapeDiff {
+    pub base_analysis_id: Uuid,
+    pub compared_analysis_id: Uuid,
+    pub landmarks_added: Vec<LandmarkDiffEntry>,
+    pub landmarks_removed: Vec<LandmarkDiffEntry>,
+    pub landmarks_renamed: Vec<LandmarkRename>,
+    pub element_count_changes: Vec<LandmarkCountChange>,
+    pub trace_mirror_changes: Vec<TraceMirrorPrimaryResourceChange>,
+}
+
+fn normalized_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn diff_entry(landmark: &Landmark) -> LandmarkDiffEntry {
+    LandmarkDiffEntry {
+        landmark_id: landmark.id,
+        title: landmark.title.clone(),
+        landmark_type: landmark.landmark_type,
+        related_elements_count: landmark.related_elements_count,
+    }
+}
+
+/// Pairs compared landmarks with base landmarks. Lenses forked from one another share landmark ids
+/// up to the fork point; landmarks created after it are paired by type and normalized title.
+fn match_landmarks(base: &[Landmark], compared: &[Landmark]) -> HashMap<Uuid, Uuid> {
+    let base_ids = base
+        .iter()
+        .map(|landmark| landmark.id)
+        .collect::<HashSet<_>>();
+    let mut matches = HashMap::new();
+    let mut matched_base = HashSet::new();
+
+    for landmark in compared {
+        if base_ids.contains(&landmark.id) {
+            matches.insert(landmark.id, landmark.id);
+            matched_base.insert(landmark.id);
+        }
+    }
+
+    let mut unmatched_base_by_key = HashMap::<(&'static str, String), Vec<Uuid>>::new();
+    for landmark in base.iter().filter(|l| !matched_base.contains(&l.id)) {
+        unmatched_base_by_key
+            .entry((
+                landmark.landmark_type.to_code(),
+                normalized_title(&landmark.title),
+            ))
+            .or_default()
+            .push(landmark.id);
+    }
+    for landmark in compared.iter().filter(|l| !base_ids.contains(&l.id)) {
+        let key = (
+            landmark.landmark_type.to_code(),
+            normalized_title(&landmark.title),
+        );
+        if let Some(candidates) = unmatched_base_by_key.get_mut(&key) {
+            if !candidates.is_empty() {
+                matches.insert(landmark.id, candidates.remove(0));
+            }
+        }
+    }
+
+    matches
+}
+
+impl LandscapeDiff {
+    /// Compares landmarks as versioned for each analysis and the trace mirrors of each lineage.
+    pub fn compute(
+        base_analysis_id: Uuid,
+        compared_analysis_id: Uuid,
+        base_landmarks: &[Landmark],
+        compared_landmarks: &[Landmark],
+        base_trace_mirrors: &[TraceMirror],
+        compared_trace_mirrors: &[TraceMirror],
+    ) -> LandscapeDiff {
+        let matches = match_landmarks(base_landmarks, compared_landmarks);
+        let matched_base_ids = matches.values().copied().collect::<HashSet<_>>();
+        let base_by_id = base_landmarks
+            .iter()
+            .map(|landmark| (landmark.id, landmark))
+            .collect::<HashMap<_, _>>();
+        let compared_by_id = compared_landmarks
+            .iter()
+            .map(|landmark| (landmark.id, landmark))
+            .collect::<HashMap<_, _>>();
+
+        let landmarks_added = compared_landmarks
+            .iter()
+            .filter(|landmark| !matches.contains_key(&landmark.id))
+            .map(diff_entry)
+            .collect();
+        let landmarks_removed = base_landmarks
+            .iter()
+            .filter(|landmark| !matched_base_ids.contains(&landmark.id))
+            .map(diff_entry)
+            .collect();
+
+        let mut landmarks_renamed = vec![];
+        let mut element_count_changes = vec![];
+        for compared in compared_landmarks {
+            let Some(base) = matches.get(&compared.id).and_then(|id| base_by_id.get(id)) else {
+                continue;
+            };
+            if base.id == compared.id && base.title != compared.title {
+                landmarks_renamed.push(LandmarkRename {
+                    landmark_id: base.id,
+                    base_title: base.title.clone(),
+                    compared_title: compared.title.clone(),
+                });
+            }
+            if base.related_elements_count != compared.related_elements_count {
+                element_count_changes.push(LandmarkCountChange {
+                    base_landmark_id: base.id,
+                    compared_landmark_id: compared.id,
+                    title: compared.title.clone(),
+                    base_related_elements_count: base.related_elements_count,
+                    compared_related_elements_count: compared.related_elements_count,
+                    delta: compared.related_elements_count - base.related_elements_count,
+                    base_last_related_element_at: base.last_related_element_at,
+                    compared_last_related_element_at: compared.last_related_element_at,
+                });
+            }
+        }
+        element_count_changes.sort_by_key(|change| std::cmp::Reverse(change.delta.abs()));
+
+        let base_mirrors_by_trace = base_trace_mirrors
+            .iter()
+            .map(|mirror| (mirror.trace_id, mirror))
+            .collect::<HashMap<_, _>>();
+        let mut trace_mirror_changes = vec![];
+        for compared in compared_trace_mirrors {
+            let Some(base) = base_mirrors_by_trace.get(&compared.trace_id) else {
+                continue;
+            };
+            let compared_resource_in_base = compared
+                .primary_resource_id
+                .map(|id| matches.get(&id).copied().unwrap_or(id));
+            if compared_resource_in_base == base.primary_resource_id {
+                continue;
+            }
+            trace_mirror_changes.push(TraceMirrorPrimaryResourceChange {
+                trace_id: compared.trace_id,
+                base_trace_mirror_id: base.id,
+                compared_trace_mirror_id: compared.id,
+                base_primary_resource_id: base.primary_resource_id,
+                base_primary_resource_title: base
+                    .primary_resource_id
+                    .and_then(|id| base_by_id.get(&id))
+                    .map(|landmark| landmark.title.clone()),
+                compared_primary_resource_id: compared.primary_resource_id,
+                compared_primary_resource_title: compared
+                    .primary_resource_id
+                    .and_then(|id| compared_by_id.get(&id))
+                    .map(|landmark| landmark.title.clone()),
+            });
+        }
+
+        LandscapeDiff {
+            base_analysis_id,
+            compared_analysis_id,
+            landmarks_added,
+            landmarks_removed,
+            landmarks_renamed,
+            element_count_changes,
+            trace_mirror_changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities_v2::shared::MaturingState;
+    use crate::entities_v2::trace_mirror::TraceMirrorType;
+
+    fn landmark(title: &str, count: i32) -> Landmark {
+        let now = chrono::Utc::now().naive_utc();
+        Landmark {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            subtitle: String::new(),
+            content: String::new(),
+            external_content_url: None,
+            comment: None,
+            image_url: None,
+            landmark_type: LandmarkType::Resource,
+            maturing_state: MaturingState::Finished,
+            related_elements_count: count,
+            last_related_element_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn mirror(trace_id: Uuid, primary_resource_id: Option<Uuid>) -> TraceMirror {
+        let now = chrono::Utc::now().naive_utc();
+        TraceMirror {
+            id: Uuid::new_v4(),
+            title: String::new(),
+            subtitle: String::new(),
+            content: String::new(),
+            trace_mirror_type: TraceMirrorType::Note,
+            tags: vec![],
+            trace_id,
+            landscape_analysis_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            primary_resource_id,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn reports_added_removed_renamed_and_count_changes() {
+        let shared = landmark("Dune", 2);
+        let mut renamed = shared.clone();
+        renamed.title = "Dune Messiah".to_string();
+        renamed.related_elements_count = 5;
+        let removed = landmark("Piano", 1);
+        let base_twin = landmark("Running", 3);
+        let compared_twin = landmark("running ", 4);
+        let added = landmark("Chess", 1);
+
+        let diff = LandscapeDiff::compute(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            &[shared.clone(), removed.clone(), base_twin.clone()],
+            &[renamed, compared_twin.clone(), added.clone()],
+            &[],
+            &[],
+        );
+
+        assert_eq!(diff.landmarks_added.len(), 1);
+        assert_eq!(diff.landmarks_added[0].landmark_id, added.id);
+        assert_eq!(diff.landmarks_removed.len(), 1);
+        assert_eq!(diff.landmarks_removed[0].landmark_id, removed.id);
+        assert_eq!(diff.landmarks_renamed.len(), 1);
+        assert_eq!(diff.landmarks_renamed[0].compared_title, "Dune Messiah");
+        assert_eq!(diff.element_count_changes.len(), 2);
+        assert_eq!(diff.element_count_changes[0].delta, 3);
+        assert_eq!(
+            diff.element_count_changes[1].compared_landmark_id,
+            compared_twin.id
+        );
+        assert_eq!(diff.element_count_changes[1].base_landmark_id, base_twin.id);
+    }
+
+    #[test]
+    fn reports_trace_mirrors_with_a_different_primary_resource() {
+        let base_book = landmark("Dune", 1);
+        let compared_book = landmark("Dune", 1);
+        let other_book = landmark("Foundation", 1);
+        let same_trace = Uuid::new_v4();
+        let changed_trace = Uuid::new_v4();
+
+        let diff = LandscapeDiff::compute(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            std::slice::from_ref(&base_book),
+            &[compared_book.clone(), other_book.clone()],
+            &[
+                mirror(same_trace, Some(base_book.id)),
+                mirror(changed_trace, Some(base_book.id)),
+            ],
+            &[
+                mirror(same_trace, Some(compared_book.id)),
+                mirror(changed_trace, Some(other_book.id)),
+            ],
+        );
+
+        assert_eq!(diff.trace_mirror_changes.len(), 1);
+        let change = &diff.trace_mirror_changes[0];
+        assert_eq!(change.trace_id, changed_trace);
+        assert_eq!(change.base_primary_resource_title.as_deref(), Some("Dune"));
+        assert_eq!(
+            change.compared_primary_resource_title.as_deref(),
+            Some("Foundation")
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Json, Path},
+};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    error::{ErrorType, PpdcError},
+    landscape_analysis::LandscapeAnalysis,
+    lens::Lens,
+    session::Session,
+};
+
+use super::model::LandscapeDiff;
+
+fn find_user_lens(id: Uuid, user_id: Uuid, pool: &DbPool) -> Result<Lens, PpdcError> {
+    let lens = Lens::find_full_lens(id, pool)?;
+    if lens.user_id != Some(user_id) {
+        return Err(PpdcError::unauthorized());
+    }
+    Ok(lens)
+}
+
+fn find_user_analysis(
+    id: Uuid,
+    user_id: Uuid,
+    pool: &DbPool,
+) -> Result<LandscapeAnalysis, PpdcError> {
+    let analysis = LandscapeAnalysis::find_full_analysis(id, pool)?;
+    if analysis.user_id != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    Ok(analysis)
+}
+
+/// Compares the current analyses of two lenses, `a` being the base and `b` the compared track.
+#[debug_handler]
+pub async fn get_lens_diff_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path((a, b)): Path<(Uuid, Uuid)>,
+) -> Result<Json<LandscapeDiff>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let lens_a = find_user_lens(a, user_id, &pool)?;
+    let lens_b = find_user_lens(b, user_id, &pool)?;
+    let (Some(base_id), Some(compared_id)) =
+        (lens_a.current_landscape_id, lens_b.current_landscape_id)
+    else {
+        return Err(PpdcError::new(
+            409,
+            ErrorType::ApiError,
+            "Both lenses need a current analysis to be compared".to_string(),
+        ));
+    };
+    let diff = LandscapeDiff::between_analysis_ids(base_id, compared_id, &pool)?;
+    Ok(Json(diff))
+}
+
+/// Compares two landscape analyses, `id` being the base and `other_id` the compared one.
+#[debug_handler]
+pub async fn get_analysis_diff_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path((id, other_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<LandscapeDiff>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let base = find_user_analysis(id, user_id, &pool)?;
+    let compared = find_user_analysis(other_id, user_id, &pool)?;
+    let diff = LandscapeDiff::between_analyses(&base, &compared, &pool)?;
+    Ok(Json(diff))
+}
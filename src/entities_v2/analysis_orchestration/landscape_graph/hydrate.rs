@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Array, BigInt, Nullable, Text, Timestamp, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::entities_v2::landmark::activity::load_activity_events;
+use crate::entities_v2::landscape_analysis::LandscapeAnalysis;
+use crate::entities_v2::lens::Lens;
+use crate::schema::{landmark_relations, landmarks};
+
+use super::model::{GraphEdge, GraphEdgeType, GraphFilter, GraphNode, LandscapeGraph};
+
+const CHILD_OF_RELATION: &str = "CHILD_OF";
+const HIGH_LEVEL_PROJECT_RELATION: &str = "HIGH_LEVEL_PROJECT_RELATED_TO";
+
+#[derive(QueryableByName)]
+struct CountedEdgeRow {
+    #[diesel(sql_type = SqlUuid)]
+    source_id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    target_id: Uuid,
+    #[diesel(sql_type = Nullable<Text>)]
+    relation_type: Option<String>,
+    #[diesel(sql_type = BigInt)]
+    weight: i64,
+}
+
+/// Landmark pairs sharing a trace mirror or an element, counted once per shared context.
+fn co_occurrence_edges(
+    landmark_ids: &[Uuid],
+    analysis_ids: &[Uuid],
+    filter: &GraphFilter,
+    conn: &mut PgConnection,
+) -> Result<Vec<CountedEdgeRow>, diesel::result::Error> {
+    sql_query(
+        r#"
+        WITH links AS (
+            SELECT 'mirror:' || r.trace_mirror_id::text AS context_key,
+                   r.landmark_id AS landmark_id,
+                   t.interaction_date AS occurred_at
+            FROM "references" r
+            INNER JOIN trace_mirrors tm ON tm.id = r.trace_mirror_id
+            INNER JOIN traces t ON t.id = tm.trace_id
+            WHERE r.landmark_id = ANY($1)
+              AND r.landscape_analysis_id = ANY($2)
+            UNION
+            SELECT 'element:' || e.id::text,
+                   el.landmark_id,
+                   COALESCE(e.interaction_date, e.created_at)
+            FROM element_landmarks el
+            INNER JOIN elements e ON e.id = el.element_id
+            WHERE el.landmark_id = ANY($1)
+              AND e.analysis_id = ANY($2)
+        )
+        SELECT a.landmark_id AS source_id,
+               b.landmark_id AS target_id,
+               NULL::text AS relation_type,
+               COUNT(DISTINCT a.context_key) AS weight
+        FROM links a
+        INNER JOIN links b
+            ON b.context_key = a.context_key
+            AND a.landmark_id < b.landmark_id
+        WHERE ($3::timestamp IS NULL OR a.occurred_at >= $3)
+          AND ($4::timestamp IS NULL OR a.occurred_at < $4)
+        GROUP BY a.landmark_id, b.landmark_id
+        "#,
+    )
+    .bind::<Array<SqlUuid>, _>(landmark_ids)
+    .bind::<Array<SqlUuid>, _>(analysis_ids)
+    .bind::<Nullable<Timestamp>, _>(filter.from)
+    .bind::<Nullable<Timestamp>, _>(filter.to)
+    .load::<CountedEdgeRow>(conn)
+}
+
+/// Element relations lifted to the landmarks their elements are linked to.
+fn element_relation_edges(
+    landmark_ids: &[Uuid],
+    analysis_ids: &[Uuid],
+    filter: &GraphFilter,
+    conn: &mut PgConnection,
+) -> Result<Vec<CountedEdgeRow>, diesel::result::Error> {
+    sql_query(
+        r#"
+        SELECT origin_link.landmark_id AS source_id,
+               target_link.landmark_id AS target_id,
+               er.relation_type AS relation_type,
+               COUNT(DISTINCT er.id) AS weight
+        FROM element_relations er
+        INNER JOIN elements origin ON origin.id = er.origin_element_id
+        INNER JOIN element_landmarks origin_link ON origin_link.element_id = er.origin_element_id
+        INNER JOIN element_landmarks target_link ON target_link.element_id = er.target_element_id
+        WHERE origin_link.landmark_id = ANY($1)
+          AND target_link.landmark_id = ANY($1)
+          AND origin_link.landmark_id <> target_link.landmark_id
+          AND origin.analysis_id = ANY($2)
+          AND ($3::timestamp IS NULL OR COALESCE(origin.interaction_date, origin.created_at) >= $3)
+          AND ($4::timestamp IS NULL OR COALESCE(origin.interaction_date, origin.created_at) < $4)
+        GROUP BY origin_link.landmark_id, target_link.landmark_id, er.relation_type
+        "#,
+    )
+    .bind::<Array<SqlUuid>, _>(landmark_ids)
+    .bind::<Array<SqlUuid>, _>(analysis_ids)
+    .bind::<Nullable<Timestamp>, _>(filter.from)
+    .bind::<Nullable<Timestamp>, _>(filter.to)
+    .load::<CountedEdgeRow>(conn)
+}
+
+impl LandscapeGraph {
+    /// Builds the graph of the current analysis of a lens, weighted over the lens scope.
+    pub fn for_lens(lens: &Lens, filter: &GraphFilter, pool: &DbPool) -> Result<Self, PpdcError> {
+        let user_id = lens.user_id.ok_or_else(PpdcError::unauthorized)?;
+        let analysis_id = lens.current_landscape_id.ok_or_else(|| {
+            PpdcError::new(
+                409,
+                ErrorType::ApiError,
+                "Lens has no current analysis".to_string(),
+            )
+        })?;
+        let analysis = LandscapeAnalysis::find_full_analysis(analysis_id, pool)?;
+        let analysis_ids = lens.get_analysis_scope_ids(pool)?;
+        let landmarks = analysis.get_landmarks(None, pool)?;
+        let landmark_ids = landmarks
+            .iter()
+            .map(|landmark| landmark.id)
+            .collect::<Vec<_>>();
+
+        let mut activity = HashMap::<Uuid, (i64, Option<NaiveDateTime>)>::new();
+        for event in load_activity_events(user_id, None, Some(&analysis_ids), pool)? {
+            if !filter.contains(event.occurred_at) {
+                continue;
+            }
+            let entry = activity.entry(event.landmark_id).or_insert((0, None));
+            entry.0 += 1;
+            entry.1 = entry.1.max(Some(event.occurred_at));
+        }
+        let nodes = landmarks
+            .into_iter()
+            .map(|landmark| {
+                let (weight, last_seen_at) =
+                    activity.get(&landmark.id).copied().unwrap_or((0, None));
+                GraphNode {
+                    id: landmark.id,
+                    title: landmark.title,
+                    landmark_type: landmark.landmark_type,
+                    weight,
+                    last_seen_at,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut conn = pool.get()?;
+        let mut edges = landmarks::table
+            .filter(landmarks::id.eq_any(&landmark_ids))
+            .filter(landmarks::parent_id.eq_any(&landmark_ids))
+            .select((landmarks::id, landmarks::parent_id.assume_not_null()))
+            .load::<(Uuid, Uuid)>(&mut conn)?
+            .into_iter()
+            .map(|(source, target)| (source, target, CHILD_OF_RELATION.to_string()))
+            .collect::<Vec<_>>();
+        edges.extend(
+            landmark_relations::table
+                .filter(landmark_relations::origin_landmark_id.eq_any(&landmark_ids))
+                .filter(landmark_relations::target_landmark_id.eq_any(&landmark_ids))
+                .filter(
+                    landmark_relations::relation_type
+                        .eq_any([CHILD_OF_RELATION, HIGH_LEVEL_PROJECT_RELATION]),
+                )
+                .select((
+                    landmark_relations::origin_landmark_id,
+                    landmark_relations::target_landmark_id,
+                    landmark_relations::relation_type,
+                ))
+                .load::<(Uuid, Uuid, String)>(&mut conn)?,
+        );
+        edges.sort();
+        edges.dedup();
+        let mut graph_edges = edges
+            .into_iter()
+            .map(|(source, target, relation_type)| GraphEdge {
+                source,
+                target,
+                edge_type: if relation_type == CHILD_OF_RELATION {
+                    GraphEdgeType::Parent
+                } else {
+                    GraphEdgeType::HighLevelProject
+                },
+                relation_type: None,
+                weight: 1,
+            })
+            .collect::<Vec<_>>();
+        for (edge_type, rows) in [
+            (
+                GraphEdgeType::CoOccurrence,
+                co_occurrence_edges(&landmark_ids, &analysis_ids, filter, &mut conn)?,
+            ),
+            (
+                GraphEdgeType::ElementRelation,
+                element_relation_edges(&landmark_ids, &analysis_ids, filter, &mut conn)?,
+            ),
+        ] {
+            graph_edges.extend(rows.into_iter().map(|row| GraphEdge {
+                source: row.source_id,
+                target: row.target_id,
+                edge_type,
+                relation_type: row.relation_type,
+                weight: row.weight,
+            }));
+        }
+
+        Ok(LandscapeGraph {
+            lens_id: lens.id,
+            analysis_id,
+            nodes,
+            edges: graph_edges,
+        }
+        .apply_filter(filter))
+    }
+}
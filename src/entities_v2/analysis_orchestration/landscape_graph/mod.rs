@@ -0,0 +1,6 @@
+pub mod hydrate;
+pub mod model;
+pub mod routes;
+
+pub use model::{GraphEdge, GraphEdgeType, GraphFilter, GraphFormat, GraphNode, LandscapeGraph};
+pub use routes::get_lens_graph_route;
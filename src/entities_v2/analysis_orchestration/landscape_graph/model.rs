@@ -0,0 +1,325 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities_v2::landmark::LandmarkType;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphFormat {
+    Json,
+    Graphml,
+    Dot,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphEdgeType {
+    /// From a landmark to its parent.
+    Parent,
+    /// From a landmark to the high-level project that groups it.
+    HighLevelProject,
+    /// Landmarks referenced in the same trace mirror or linked to the same element.
+    CoOccurrence,
+    /// Relations between elements of two landmarks, keyed by relation type.
+    ElementRelation,
+}
+
+impl GraphEdgeType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GraphEdgeType::Parent => "parent",
+            GraphEdgeType::HighLevelProject => "high_level_project",
+            GraphEdgeType::CoOccurrence => "co_occurrence",
+            GraphEdgeType::ElementRelation => "element_relation",
+        }
+    }
+
+    /// Counted edges carry a weight and are subject to `min_weight`.
+    pub fn is_counted(self) -> bool {
+        matches!(
+            self,
+            GraphEdgeType::CoOccurrence | GraphEdgeType::ElementRelation
+        )
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub id: Uuid,
+    pub title: String,
+    pub landmark_type: LandmarkType,
+    /// References and element links in the time window.
+    pub weight: i64,
+    pub last_seen_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GraphEdge {
+    pub source: Uuid,
+    pub target: Uuid,
+    pub edge_type: GraphEdgeType,
+    /// Element relation type for `element_relation` edges.
+    pub relation_type: Option<String>,
+    pub weight: i64,
+}
+
+/// Landmarks of the current analysis of a lens and how they connect.
+#[derive(Serialize, Debug, Clone)]
+pub struct LandscapeGraph {
+    pub lens_id: Uuid,
+    pub analysis_id: Uuid,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GraphFilter {
+    /// Keeps only these landmark types; all types when empty.
+    pub landmark_types: Vec<LandmarkType>,
+    /// Drops nodes with no activity in the window when a bound is set.
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    pub min_weight: i64,
+}
+
+impl GraphFilter {
+    pub fn has_window(&self) -> bool {
+        self.from.is_some() || self.to.is_some()
+    }
+
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        self.from.is_none_or(|from| at >= from) && self.to.is_none_or(|to| at < to)
+    }
+}
+
+impl LandscapeGraph {
+    /// Drops filtered-out nodes, light counted edges, and edges left without both ends.
+    pub fn apply_filter(mut self, filter: &GraphFilter) -> Self {
+        self.nodes.retain(|node| {
+            (filter.landmark_types.is_empty()
+                || filter.landmark_types.contains(&node.landmark_type))
+                && node.weight >= filter.min_weight
+                && (!filter.has_window() || node.weight > 0)
+        });
+        let kept = self
+            .nodes
+            .iter()
+            .map(|node| node.id)
+            .collect::<HashSet<_>>();
+        self.edges.retain(|edge| {
+            kept.contains(&edge.source)
+                && kept.contains(&edge.target)
+                && (!edge.edge_type.is_counted() || edge.weight >= filter.min_weight.max(1))
+        });
+        self
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        out.push_str(
+            "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n",
+        );
+        out.push_str(
+            "  <key id=\"landmark_type\" for=\"node\" attr.name=\"landmark_type\" attr.type=\"string\"/>\n",
+        );
+        out.push_str(
+            "  <key id=\"node_weight\" for=\"node\" attr.name=\"weight\" attr.type=\"long\"/>\n",
+        );
+        out.push_str(
+            "  <key id=\"edge_type\" for=\"edge\" attr.name=\"edge_type\" attr.type=\"string\"/>\n",
+        );
+        out.push_str(
+            "  <key id=\"relation_type\" for=\"edge\" attr.name=\"relation_type\" attr.type=\"string\"/>\n",
+        );
+        out.push_str(
+            "  <key id=\"edge_weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"long\"/>\n",
+        );
+        let _ = writeln!(
+            out,
+            "  <graph id=\"{}\" edgedefault=\"directed\">",
+            self.analysis_id
+        );
+        for node in &self.nodes {
+            let _ = writeln!(out, "    <node id=\"{}\">", node.id);
+            let _ = writeln!(
+                out,
+                "      <data key=\"title\">{}</data>",
+                escape_xml(&node.title)
+            );
+            let _ = writeln!(
+                out,
+                "      <data key=\"landmark_type\">{}</data>",
+                node.landmark_type.to_api_value()
+            );
+            let _ = writeln!(
+                out,
+                "      <data key=\"node_weight\">{}</data>",
+                node.weight
+            );
+            out.push_str("    </node>\n");
+        }
+        for (index, edge) in self.edges.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">",
+                index, edge.source, edge.target
+            );
+            let _ = writeln!(
+                out,
+                "      <data key=\"edge_type\">{}</data>",
+                edge.edge_type.as_str()
+            );
+            if let Some(relation_type) = &edge.relation_type {
+                let _ = writeln!(
+                    out,
+                    "      <data key=\"relation_type\">{}</data>",
+                    escape_xml(relation_type)
+                );
+            }
+            let _ = writeln!(
+                out,
+                "      <data key=\"edge_weight\">{}</data>",
+                edge.weight
+            );
+            out.push_str("    </edge>\n");
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph landscape {\n");
+        for node in &self.nodes {
+            let _ = writeln!(
+                out,
+                "  \"{}\" [label=\"{}\", landmark_type=\"{}\", weight={}];",
+                node.id,
+                escape_dot(&node.title),
+                node.landmark_type.to_api_value(),
+                node.weight
+            );
+        }
+        for edge in &self.edges {
+            let label = match &edge.relation_type {
+                Some(relation_type) => format!("{}:{}", edge.edge_type.as_str(), relation_type),
+                None => edge.edge_type.as_str().to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\" [label=\"{}\", weight={}];",
+                edge.source,
+                edge.target,
+                escape_dot(&label),
+                edge.weight
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn escape_dot(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(title: &str, landmark_type: LandmarkType, weight: i64) -> GraphNode {
+        GraphNode {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            landmark_type,
+            weight,
+            last_seen_at: None,
+        }
+    }
+
+    fn edge(
+        source: &GraphNode,
+        target: &GraphNode,
+        edge_type: GraphEdgeType,
+        weight: i64,
+    ) -> GraphEdge {
+        GraphEdge {
+            source: source.id,
+            target: target.id,
+            edge_type,
+            relation_type: None,
+            weight,
+        }
+    }
+
+    fn graph(nodes: Vec<GraphNode>, edges: Vec<GraphEdge>) -> LandscapeGraph {
+        LandscapeGraph {
+            lens_id: Uuid::nil(),
+            analysis_id: Uuid::nil(),
+            nodes,
+            edges,
+        }
+    }
+
+    #[test]
+    fn filter_drops_nodes_and_dangling_edges() {
+        let project = node("Atlas", LandmarkType::Project, 5);
+        let person = node("Marie", LandmarkType::Person, 1);
+        let topic = node("Cartes", LandmarkType::Topic, 3);
+        let edges = vec![
+            edge(&project, &person, GraphEdgeType::CoOccurrence, 4),
+            edge(&project, &topic, GraphEdgeType::CoOccurrence, 1),
+            edge(&topic, &project, GraphEdgeType::Parent, 1),
+        ];
+        let filtered =
+            graph(vec![project.clone(), person, topic], edges).apply_filter(&GraphFilter {
+                min_weight: 2,
+                ..GraphFilter::default()
+            });
+
+        assert_eq!(filtered.nodes.len(), 2);
+        assert_eq!(filtered.edges.len(), 1);
+        assert_eq!(filtered.edges[0].edge_type, GraphEdgeType::Parent);
+
+        let topics_only = graph(vec![project], vec![]).apply_filter(&GraphFilter {
+            landmark_types: vec![LandmarkType::Topic],
+            ..GraphFilter::default()
+        });
+        assert!(topics_only.nodes.is_empty());
+    }
+
+    #[test]
+    fn exports_escape_titles() {
+        let quoted = node("\"R&D\" <core>", LandmarkType::Topic, 2);
+        let other = node("Équipe", LandmarkType::Organization, 1);
+        let exported = graph(
+            vec![quoted.clone(), other.clone()],
+            vec![edge(&quoted, &other, GraphEdgeType::CoOccurrence, 2)],
+        );
+
+        let graphml = exported.to_graphml();
+        assert!(graphml.contains("&quot;R&amp;D&quot; &lt;core&gt;"));
+        assert!(graphml.contains(&format!("source=\"{}\"", quoted.id)));
+
+        let dot = exported.to_dot();
+        assert!(dot.contains("label=\"\\\"R&D\\\" <core>\""));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\"", quoted.id, other.id)));
+    }
+}
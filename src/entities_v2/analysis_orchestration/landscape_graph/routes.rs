@@ -0,0 +1,88 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Json, Path, Query, RawQuery},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{Duration, NaiveDate};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    error::{ErrorType, PpdcError},
+    landmark::LandmarkType,
+    lens::Lens,
+    session::Session,
+};
+use crate::pagination::parse_repeated_query_param;
+
+use super::model::{GraphFilter, GraphFormat, LandscapeGraph};
+
+#[derive(Deserialize)]
+pub struct LandscapeGraphQuery {
+    /// `json` (default), `graphml` or `dot`.
+    pub format: Option<GraphFormat>,
+    /// Inclusive first day of the activity window.
+    pub from: Option<NaiveDate>,
+    /// Inclusive last day of the activity window.
+    pub to: Option<NaiveDate>,
+    pub min_weight: Option<i64>,
+}
+
+/// Exports the landmark graph of the current analysis of a lens.
+#[debug_handler]
+pub async fn get_lens_graph_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<LandscapeGraphQuery>,
+    RawQuery(raw_query): RawQuery,
+) -> Result<Response, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let lens = Lens::find_full_lens(id, &pool)?;
+    if lens.user_id != Some(user_id) {
+        return Err(PpdcError::unauthorized());
+    }
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from > to {
+            return Err(PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                "`from` must be <= `to`".to_string(),
+            ));
+        }
+    }
+    let filter = GraphFilter {
+        landmark_types: parse_repeated_query_param::<LandmarkType>(
+            raw_query.as_deref(),
+            "landmark_type",
+        )?,
+        from: params
+            .from
+            .map(|day| day.and_hms_opt(0, 0, 0).expect("valid day start")),
+        to: params.to.map(|day| {
+            (day + Duration::days(1))
+                .and_hms_opt(0, 0, 0)
+                .expect("valid day start")
+        }),
+        min_weight: params.min_weight.unwrap_or(0),
+    };
+    let graph = LandscapeGraph::for_lens(&lens, &filter, &pool)?;
+    Ok(match params.format.unwrap_or(GraphFormat::Json) {
+        GraphFormat::Json => Json(graph).into_response(),
+        GraphFormat::Graphml => (
+            [(
+                header::CONTENT_TYPE,
+                "application/graphml+xml; charset=utf-8",
+            )],
+            graph.to_graphml(),
+        )
+            .into_response(),
+        GraphFormat::Dot => (
+            [(header::CONTENT_TYPE, "text/vnd.graphviz; charset=utf-8")],
+            graph.to_dot(),
+        )
+            .into_response(),
+    })
+}
@@ -4,9 +4,9 @@ pub mod model;
 pub mod persist;
 pub mod routes;
 
-pub use model::{Lens, LensProcessingState, NewLens, NewLensDto};
+pub use model::{Lens, LensProcessingState, LensReplayDto, NewLens, NewLensDto};
 pub use persist::{create_landscape_placeholders, delete_lens_and_landscapes};
 pub use routes::{
     delete_lens_route, get_lens_analysis_route, get_lens_week_events_route, get_user_lenses_route,
-    post_lens_retry_route, post_lens_route, put_lens_route,
+    post_lens_replay_route, post_lens_retry_route, post_lens_route, put_lens_route,
 };
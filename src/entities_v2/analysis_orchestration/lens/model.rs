@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::entities_v2::analysis_config::AnalysisConfigOverride;
+
 pub use super::enums::LensProcessingState;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -45,3 +47,11 @@ pub struct NewLensDto {
     pub target_trace_id: Option<Uuid>,
     pub autoplay: Option<bool>,
 }
+
+#[derive(Deserialize)]
+pub struct LensReplayDto {
+    pub start_trace_id: Option<Uuid>,
+    pub start_analysis_id: Option<Uuid>,
+    #[serde(default)]
+    pub config: AnalysisConfigOverride,
+}
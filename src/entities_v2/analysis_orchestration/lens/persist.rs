@@ -352,6 +352,45 @@ impl Lens {
         Lens::find_full_lens(self.id, pool)
     }
 
+    /// Creates a lens sharing this lens's history up to `fork_analysis_id` (included), then plans
+    /// the remaining traces up to this lens's target. Returns the new lens and its shared analyses.
+    pub fn fork_at(
+        &self,
+        fork_analysis_id: Option<Uuid>,
+        pool: &DbPool,
+    ) -> Result<(Lens, Vec<Uuid>), PpdcError> {
+        let user_id = self.user_id.ok_or_else(|| {
+            PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                "Lens user_id is missing".to_string(),
+            )
+        })?;
+        let fork = NewLens {
+            processing_state: LensProcessingState::OutOfSync,
+            fork_landscape_id: fork_analysis_id,
+            target_trace_id: None,
+            current_landscape_id: fork_analysis_id,
+            autoplay: false,
+            user_id,
+        }
+        .create(pool)?;
+
+        let mut shared_analysis_ids = vec![];
+        if let Some(fork_analysis_id) = fork_analysis_id {
+            let fork_analysis = LandscapeAnalysis::find_full_analysis(fork_analysis_id, pool)?;
+            shared_analysis_ids.push(fork_analysis.id);
+            for parent in fork_analysis.find_all_parents(pool)? {
+                ensure_lens_analysis_scope_relation(fork.id, parent.id, pool)?;
+                shared_analysis_ids.push(parent.id);
+            }
+        }
+
+        let fork = fork.set_target_trace(self.target_trace_id, pool)?;
+        fork.clone().plan_pending_analyses_for_target(pool)?;
+        Ok((Lens::find_full_lens(fork.id, pool)?, shared_analysis_ids))
+    }
+
     pub fn update_autoplay(self, autoplay: bool, pool: &DbPool) -> Result<Lens, PpdcError> {
         let mut conn = pool.get()?;
         diesel::update(lenses::table.filter(lenses::id.eq(self.id)))
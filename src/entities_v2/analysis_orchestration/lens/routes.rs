@@ -17,9 +17,9 @@ use crate::entities_v2::{
     trace::Trace,
     user::{ensure_user_has_any_lens, User},
 };
-use crate::work_analyzer;
+use crate::work_analyzer::{self, analysis_processor::AnalysisConfig};
 
-use super::model::{Lens, NewLens, NewLensDto};
+use super::model::{Lens, LensReplayDto, NewLens, NewLensDto};
 use super::persist::delete_lens_and_landscapes;
 
 #[derive(Serialize)]
@@ -267,10 +267,88 @@ pub async fn post_lens_retry_route(
     Ok(Json(lens))
 }
 
+/// Finds the analysis a replay starts from: the given analysis, or the latest one of the lens
+/// lineage that analyzed the given trace.
+fn find_replay_start_analysis(
+    lens: &Lens,
+    payload: &LensReplayDto,
+    pool: &DbPool,
+) -> Result<LandscapeAnalysis, PpdcError> {
+    let not_found = || {
+        PpdcError::new(
+            404,
+            ErrorType::ApiError,
+            "Replay start not found in this lens".to_string(),
+        )
+    };
+    if let Some(start_analysis_id) = payload.start_analysis_id {
+        if !lens
+            .get_analysis_scope_ids(pool)?
+            .contains(&start_analysis_id)
+        {
+            return Err(not_found());
+        }
+        return LandscapeAnalysis::find_full_analysis(start_analysis_id, pool);
+    }
+    let start_trace_id = payload.start_trace_id.ok_or_else(|| {
+        PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "start_trace_id or start_analysis_id is required".to_string(),
+        )
+    })?;
+    let head_id = lens.current_landscape_id.ok_or_else(not_found)?;
+    let head = LandscapeAnalysis::find_full_analysis(head_id, pool)?;
+    let mut lineage = vec![head.clone()];
+    lineage.extend(head.find_all_parents(pool)?);
+    lineage
+        .into_iter()
+        .find(|analysis| analysis.analyzed_trace_id == Some(start_trace_id))
+        .ok_or_else(not_found)
+}
+
+/// Forks the lens just before the start analysis and replays the following traces with a
+/// configuration override, recorded on every replayed analysis.
+#[debug_handler]
+pub async fn post_lens_replay_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<LensReplayDto>,
+) -> Result<Json<Lens>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let lens = Lens::find_full_lens(id, &pool)?;
+    if lens.user_id != Some(user_id) {
+        return Err(PpdcError::new(
+            403,
+            ErrorType::ApiError,
+            "Lens does not belong to current user".to_string(),
+        ));
+    }
+    payload.config.validate()?;
+
+    let start_analysis = find_replay_start_analysis(&lens, &payload, &pool)?;
+    let (fork, shared_analysis_ids) = lens.fork_at(start_analysis.parent_analysis_id, &pool)?;
+
+    let replay_config = AnalysisConfig::from_env().with_override(&payload.config);
+    for analysis_id in fork.get_analysis_scope_ids(&pool)? {
+        if !shared_analysis_ids.contains(&analysis_id) {
+            replay_config.record(analysis_id, &pool)?;
+        }
+    }
+
+    let fork_id = fork.id;
+    tokio::spawn(async move { work_analyzer::run_lens(fork_id).await });
+
+    Ok(Json(fork))
+}
+
 fn retry_priority_for_analysis_type(analysis_type: LandscapeAnalysisType) -> i32 {
     match analysis_type {
         LandscapeAnalysisType::DailyRecap => 0,
         LandscapeAnalysisType::WeeklyRecap => 1,
+        LandscapeAnalysisType::MonthlyRecap => 2,
+        LandscapeAnalysisType::CustomRecap => 3,
         _ => 0,
     }
 }
@@ -1,2 +1,7 @@
+pub mod analysis_config;
+pub mod analysis_event;
+pub mod analysis_unlock;
 pub mod landscape_analysis;
+pub mod landscape_diff;
+pub mod landscape_graph;
 pub mod lens;
@@ -4,9 +4,12 @@ use uuid::Uuid;
 
 use crate::db::DbPool;
 use crate::entities_v2::error::{ErrorType, PpdcError};
-use crate::schema::analysis_summaries;
+use crate::entities_v2::landscape_analysis::model::{
+    LandscapeAnalysisType, LandscapeProcessingState,
+};
+use crate::schema::{analysis_summaries, landscape_analyses, landscape_analysis_inputs, traces};
 
-use super::model::{AnalysisSummary, AnalysisSummaryType};
+use super::model::{AnalysisSummary, AnalysisSummaryType, PeriodRecap};
 
 type AnalysisSummaryTuple = (
     Uuid,
@@ -125,4 +128,74 @@ impl AnalysisSummary {
             .load::<AnalysisSummaryTuple>(&mut conn)?;
         Ok(rows.into_iter().map(tuple_to_analysis_summary).collect())
     }
+
+    /// Summaries of the user's completed recaps whose period overlaps `[from, to)`, oldest
+    /// period first. Only recaps covering traces of the journal and of no other journal are
+    /// kept, so they do not carry what the user wrote elsewhere.
+    pub fn find_recaps_for_journal_in_period(
+        user_id: Uuid,
+        journal_id: Uuid,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        pool: &DbPool,
+    ) -> Result<Vec<PeriodRecap>, PpdcError> {
+        let recap_types = [
+            LandscapeAnalysisType::DailyRecap,
+            LandscapeAnalysisType::WeeklyRecap,
+            LandscapeAnalysisType::MonthlyRecap,
+            LandscapeAnalysisType::CustomRecap,
+        ]
+        .map(|analysis_type| analysis_type.to_db());
+        let mut conn = pool.get()?;
+        let rows = analysis_summaries::table
+            .inner_join(landscape_analyses::table)
+            .filter(analysis_summaries::user_id.eq(user_id))
+            .filter(landscape_analyses::landscape_analysis_type.eq_any(recap_types))
+            .filter(
+                landscape_analyses::processing_state
+                    .eq(LandscapeProcessingState::Completed.to_db()),
+            )
+            .filter(landscape_analyses::period_start.lt(to))
+            .filter(landscape_analyses::period_end.gt(from))
+            .filter(
+                landscape_analyses::id.eq_any(
+                    landscape_analysis_inputs::table
+                        .inner_join(traces::table)
+                        .filter(traces::journal_id.eq(journal_id))
+                        .select(landscape_analysis_inputs::landscape_analysis_id),
+                ),
+            )
+            .filter(
+                landscape_analyses::id.ne_all(
+                    landscape_analysis_inputs::table
+                        .inner_join(traces::table)
+                        .filter(traces::user_id.eq(user_id))
+                        .filter(traces::journal_id.ne(journal_id))
+                        .select(landscape_analysis_inputs::landscape_analysis_id),
+                ),
+            )
+            .select((
+                select_analysis_summary_columns(),
+                landscape_analyses::landscape_analysis_type,
+                landscape_analyses::period_start,
+                landscape_analyses::period_end,
+            ))
+            .order((
+                landscape_analyses::period_start.asc(),
+                landscape_analyses::period_end.desc(),
+                analysis_summaries::created_at.asc(),
+            ))
+            .load::<(AnalysisSummaryTuple, String, NaiveDateTime, NaiveDateTime)>(&mut conn)?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(summary, analysis_type, period_start, period_end)| PeriodRecap {
+                    summary: tuple_to_analysis_summary(summary),
+                    analysis_type: LandscapeAnalysisType::from_db(&analysis_type),
+                    period_start,
+                    period_end,
+                },
+            )
+            .collect())
+    }
 }
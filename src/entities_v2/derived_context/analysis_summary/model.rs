@@ -2,6 +2,8 @@ use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::entities_v2::landscape_analysis::model::LandscapeAnalysisType;
+
 pub use super::enums::AnalysisSummaryType;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -25,6 +27,15 @@ pub struct AnalysisSummary {
     pub updated_at: NaiveDateTime,
 }
 
+/// Summary of a recap analysis with the period it covers.
+#[derive(Serialize, Debug, Clone)]
+pub struct PeriodRecap {
+    pub summary: AnalysisSummary,
+    pub analysis_type: LandscapeAnalysisType,
+    pub period_start: NaiveDateTime,
+    pub period_end: NaiveDateTime,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct NewAnalysisSummaryDto {
     pub summary_type: Option<AnalysisSummaryType>,
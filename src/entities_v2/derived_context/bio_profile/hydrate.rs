@@ -0,0 +1,90 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Int4, Nullable, Text, Timestamp, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::PpdcError;
+
+use super::model::BioProfile;
+
+pub(super) const BIO_PROFILE_COLUMNS: &str =
+    "id, user_id, version, trace_id, analysis_id, summary, profile::text AS profile, created_at";
+
+#[derive(QueryableByName)]
+pub(super) struct BioProfileRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    user_id: Uuid,
+    #[diesel(sql_type = Int4)]
+    version: i32,
+    #[diesel(sql_type = SqlUuid)]
+    trace_id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    analysis_id: Uuid,
+    #[diesel(sql_type = Text)]
+    summary: String,
+    #[diesel(sql_type = Text)]
+    profile: String,
+    #[diesel(sql_type = Timestamp)]
+    created_at: NaiveDateTime,
+}
+
+impl TryFrom<BioProfileRow> for BioProfile {
+    type Error = PpdcError;
+
+    fn try_from(row: BioProfileRow) -> Result<Self, Self::Error> {
+        Ok(BioProfile {
+            id: row.id,
+            user_id: row.user_id,
+            version: row.version,
+            trace_id: row.trace_id,
+            analysis_id: row.analysis_id,
+            summary: row.summary,
+            sections: serde_json::from_str(&row.profile)?,
+            created_at: row.created_at,
+        })
+    }
+}
+
+impl BioProfile {
+    /// Latest profile built by `analysis_id` or one of its ancestors, so lenses never read each
+    /// other's profile. `version` picks an older one of that lineage.
+    pub fn find_in_lineage(
+        user_id: Uuid,
+        analysis_id: Uuid,
+        version: Option<i32>,
+        pool: &DbPool,
+    ) -> Result<Option<BioProfile>, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = sql_query(format!(
+            r#"
+            WITH RECURSIVE lineage AS (
+                SELECT id, parent_id
+                FROM landscape_analyses
+                WHERE id = $2
+                UNION
+                SELECT parent.id, parent.parent_id
+                FROM landscape_analyses parent
+                INNER JOIN lineage ON parent.id = lineage.parent_id
+            )
+            SELECT {}
+            FROM bio_profiles
+            WHERE user_id = $1
+              AND analysis_id IN (SELECT id FROM lineage)
+              AND ($3::int4 IS NULL OR version = $3)
+            ORDER BY version DESC
+            LIMIT 1
+            "#,
+            BIO_PROFILE_COLUMNS
+        ))
+        .bind::<SqlUuid, _>(user_id)
+        .bind::<SqlUuid, _>(analysis_id)
+        .bind::<Nullable<Int4>, _>(version)
+        .get_result::<BioProfileRow>(&mut conn)
+        .optional()?;
+        row.map(TryInto::try_into).transpose()
+    }
+}
@@ -0,0 +1,9 @@
+pub mod hydrate;
+pub mod model;
+pub mod persist;
+pub mod routes;
+
+pub use model::{
+    BioProfile, BioProfileEntry, BioProfileEntryKind, BioProfileSections, NewBioProfile,
+};
+pub use routes::get_user_bio_profile_route;
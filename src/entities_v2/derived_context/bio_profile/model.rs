@@ -0,0 +1,170 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities_v2::landmark::LandmarkType;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BioProfileEntryKind {
+    Role,
+    Skill,
+    Place,
+    Organization,
+}
+
+impl BioProfileEntryKind {
+    pub const ALL: [BioProfileEntryKind; 4] = [
+        BioProfileEntryKind::Role,
+        BioProfileEntryKind::Skill,
+        BioProfileEntryKind::Place,
+        BioProfileEntryKind::Organization,
+    ];
+
+    pub fn landmark_type(self) -> LandmarkType {
+        match self {
+            BioProfileEntryKind::Role => LandmarkType::Role,
+            BioProfileEntryKind::Skill => LandmarkType::Skill,
+            BioProfileEntryKind::Place => LandmarkType::Place,
+            BioProfileEntryKind::Organization => LandmarkType::Organization,
+        }
+    }
+}
+
+/// One fact of the profile, backed by the landmark created for it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BioProfileEntry {
+    pub landmark_id: Uuid,
+    pub title: String,
+    pub subtitle: String,
+    pub content: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BioProfileSections {
+    #[serde(default)]
+    pub roles: Vec<BioProfileEntry>,
+    #[serde(default)]
+    pub skills: Vec<BioProfileEntry>,
+    #[serde(default)]
+    pub places: Vec<BioProfileEntry>,
+    #[serde(default)]
+    pub organizations: Vec<BioProfileEntry>,
+}
+
+impl BioProfileSections {
+    pub fn section(&self, kind: BioProfileEntryKind) -> &[BioProfileEntry] {
+        match kind {
+            BioProfileEntryKind::Role => &self.roles,
+            BioProfileEntryKind::Skill => &self.skills,
+            BioProfileEntryKind::Place => &self.places,
+            BioProfileEntryKind::Organization => &self.organizations,
+        }
+    }
+
+    pub fn section_mut(&mut self, kind: BioProfileEntryKind) -> &mut Vec<BioProfileEntry> {
+        match kind {
+            BioProfileEntryKind::Role => &mut self.roles,
+            BioProfileEntryKind::Skill => &mut self.skills,
+            BioProfileEntryKind::Place => &mut self.places,
+            BioProfileEntryKind::Organization => &mut self.organizations,
+        }
+    }
+
+    /// Points the entries backed by one of `source_ids` at `target_id`, keeping only the first
+    /// entry of a section when several now share the target. Returns whether anything changed.
+    pub fn retarget_landmarks(&mut self, source_ids: &[Uuid], target_id: Uuid) -> bool {
+        let mut changed = false;
+        for kind in BioProfileEntryKind::ALL {
+            let section = self.section_mut(kind);
+            if !section
+                .iter()
+                .any(|entry| source_ids.contains(&entry.landmark_id))
+            {
+                continue;
+            }
+            changed = true;
+            let mut seen_target = false;
+            section.retain_mut(|entry| {
+                if source_ids.contains(&entry.landmark_id) {
+                    entry.landmark_id = target_id;
+                }
+                if entry.landmark_id != target_id {
+                    return true;
+                }
+                !std::mem::replace(&mut seen_target, true)
+            });
+        }
+        changed
+    }
+}
+
+/// Structured profile extracted from a bio trace. Each bio analysis stores a new version.
+#[derive(Serialize, Debug, Clone)]
+pub struct BioProfile {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub version: i32,
+    pub trace_id: Uuid,
+    pub analysis_id: Uuid,
+    pub summary: String,
+    #[serde(flatten)]
+    pub sections: BioProfileSections,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewBioProfile {
+    pub user_id: Uuid,
+    pub trace_id: Uuid,
+    pub analysis_id: Uuid,
+    pub summary: String,
+    pub sections: BioProfileSections,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(landmark_id: Uuid, title: &str) -> BioProfileEntry {
+        BioProfileEntry {
+            landmark_id,
+            title: title.to_string(),
+            subtitle: String::new(),
+            content: String::new(),
+        }
+    }
+
+    #[test]
+    fn retarget_landmarks_moves_entries_and_drops_duplicates() {
+        let source = Uuid::new_v4();
+        let target = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let mut sections = BioProfileSections {
+            roles: vec![entry(target, "Designer"), entry(source, "UX designer")],
+            skills: vec![entry(source, "Figma"), entry(other, "Rust")],
+            ..Default::default()
+        };
+
+        assert!(sections.retarget_landmarks(&[source], target));
+
+        assert_eq!(sections.roles, vec![entry(target, "Designer")]);
+        assert_eq!(
+            sections.skills,
+            vec![entry(target, "Figma"), entry(other, "Rust")]
+        );
+    }
+
+    #[test]
+    fn retarget_landmarks_leaves_unrelated_profiles_untouched() {
+        let kept = Uuid::new_v4();
+        let mut sections = BioProfileSections {
+            places: vec![entry(kept, "Lyon")],
+            ..Default::default()
+        };
+        let before = sections.clone();
+
+        assert!(!sections.retarget_landmarks(&[Uuid::new_v4()], Uuid::new_v4()));
+        assert_eq!(sections, before);
+    }
+}
@@ -0,0 +1,92 @@
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Text, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+
+use super::hydrate::{BioProfileRow, BIO_PROFILE_COLUMNS};
+use super::model::{BioProfile, BioProfileSections, NewBioProfile};
+
+/// Two bio analyses of the same user may race for the next version; the loser retries.
+const VERSION_CONFLICT_RETRIES: usize = 5;
+
+impl NewBioProfile {
+    /// Stores the profile as the next version for the user.
+    pub fn create(self, pool: &DbPool) -> Result<BioProfile, PpdcError> {
+        let mut conn = pool.get()?;
+        let profile = serde_json::to_string(&self.sections)?;
+
+        for _ in 0..VERSION_CONFLICT_RETRIES {
+            let inserted = sql_query(format!(
+                r#"
+                INSERT INTO bio_profiles (user_id, version, trace_id, analysis_id, summary, profile)
+                SELECT $1, COALESCE(MAX(version), 0) + 1, $2, $3, $4, CAST($5 AS jsonb)
+                FROM bio_profiles
+                WHERE user_id = $1
+                ON CONFLICT (user_id, version) DO NOTHING
+                RETURNING {}
+                "#,
+                BIO_PROFILE_COLUMNS
+            ))
+            .bind::<SqlUuid, _>(self.user_id)
+            .bind::<SqlUuid, _>(self.trace_id)
+            .bind::<SqlUuid, _>(self.analysis_id)
+            .bind::<Text, _>(&self.summary)
+            .bind::<Text, _>(&profile)
+            .get_result::<BioProfileRow>(&mut conn)
+            .optional()?;
+
+            if let Some(row) = inserted {
+                return row.try_into();
+            }
+        }
+
+        Err(PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!(
+                "Could not allocate a bio profile version for user {}",
+                self.user_id
+            ),
+        ))
+    }
+}
+
+#[derive(QueryableByName)]
+struct BioProfileDocumentRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+    #[diesel(sql_type = Text)]
+    profile: String,
+}
+
+/// Rewrites the user's profile versions so entries backed by a merged landmark point at the
+/// landmark it was merged into.
+pub fn retarget_bio_profile_landmarks(
+    user_id: Uuid,
+    source_ids: &[Uuid],
+    target_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<(), diesel::result::Error> {
+    let rows = sql_query(
+        "SELECT id, profile::text AS profile FROM bio_profiles WHERE user_id = $1 FOR UPDATE",
+    )
+    .bind::<SqlUuid, _>(user_id)
+    .load::<BioProfileDocumentRow>(conn)?;
+    for row in rows {
+        let mut sections: BioProfileSections = serde_json::from_str(&row.profile)
+            .map_err(|err| diesel::result::Error::DeserializationError(Box::new(err)))?;
+        if !sections.retarget_landmarks(source_ids, target_id) {
+            continue;
+        }
+        let profile = serde_json::to_string(&sections)
+            .map_err(|err| diesel::result::Error::SerializationError(Box::new(err)))?;
+        sql_query("UPDATE bio_profiles SET profile = CAST($2 AS jsonb) WHERE id = $1")
+            .bind::<SqlUuid, _>(row.id)
+            .bind::<Text, _>(&profile)
+            .execute(conn)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Json, Path, Query},
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    error::{ErrorType, PpdcError},
+    lens::Lens,
+    session::Session,
+    user::User,
+};
+
+use super::model::BioProfile;
+
+#[derive(Deserialize)]
+pub struct BioProfileQuery {
+    /// Defaults to the latest version.
+    pub version: Option<i32>,
+    /// Defaults to the user's current lens.
+    pub lens_id: Option<Uuid>,
+}
+
+#[debug_handler]
+pub async fn get_user_bio_profile_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<BioProfileQuery>,
+) -> Result<Json<BioProfile>, PpdcError> {
+    let session_user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    if session_user_id != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    let lens_id = match query.lens_id {
+        Some(lens_id) => lens_id,
+        None => User::find(&user_id, &pool)?
+            .current_lens_id
+            .ok_or_else(|| {
+                PpdcError::new(
+                    400,
+                    ErrorType::ApiError,
+                    "current_lens_id is not set for user".to_string(),
+                )
+            })?,
+    };
+    let lens = Lens::find_full_lens(lens_id, &pool)?;
+    if lens.user_id != Some(user_id) {
+        return Err(PpdcError::unauthorized());
+    }
+    let profile = match lens.current_landscape_id {
+        Some(head_id) => BioProfile::find_in_lineage(user_id, head_id, query.version, &pool)?,
+        None => None,
+    };
+    profile.map(Json).ok_or_else(|| {
+        PpdcError::new(
+            404,
+            ErrorType::ApiError,
+            "Bio profile not found".to_string(),
+        )
+    })
+}
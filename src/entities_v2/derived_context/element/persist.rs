@@ -5,6 +5,7 @@ use uuid::Uuid;
 
 use crate::db::DbPool;
 use crate::entities_v2::error::PpdcError;
+use crate::entities_v2::landmark_version::LandmarkVersion;
 use crate::schema::{element_landmarks, elements};
 
 use super::model::{Element, NewElement};
@@ -72,6 +73,7 @@ impl NewElement {
                     .do_nothing()
                     .execute(conn)?;
                 if inserted > 0 {
+                    LandmarkVersion::record_element_link(id, lid, conn)?;
                     refresh_landmark_related_elements_stats(lid, conn)?;
                 }
             }
@@ -103,6 +105,7 @@ pub fn link_to_landmark(
             .do_nothing()
             .execute(conn)?;
         if inserted > 0 {
+            LandmarkVersion::record_element_link(element_id, landmark_id, conn)?;
             refresh_landmark_related_elements_stats(landmark_id, conn)?;
         }
         Ok(())
@@ -110,7 +113,8 @@ pub fn link_to_landmark(
     Ok(landmark_id)
 }
 
-fn refresh_landmark_related_elements_stats(
+/// Keeps the landmark-level counters read by `Landmark::find` in step with its links.
+pub(crate) fn refresh_landmark_related_elements_stats(
     landmark_id: Uuid,
     conn: &mut diesel::PgConnection,
 ) -> Result<(), diesel::result::Error> {
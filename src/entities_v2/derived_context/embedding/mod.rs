@@ -0,0 +1,8 @@
+pub mod model;
+pub mod persist;
+pub mod routes;
+
+pub use model::{embedding_text, Embedding, EmbeddingEntityType, EmbeddingSource, NewEmbedding};
+pub use routes::{
+    backfill_embeddings_batch, post_backfill_embeddings_route, EmbeddingsBackfillResponse,
+};
@@ -0,0 +1,114 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_types::{Text, Uuid as SqlUuid};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::entities_v2::error::{ErrorType, PpdcError};
+
+/// Long contents are cut before embedding; the title and subtitle carry most of the signal.
+const MAX_EMBEDDED_CONTENT_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingEntityType {
+    Landmark,
+    Element,
+}
+
+impl EmbeddingEntityType {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            EmbeddingEntityType::Landmark => "LANDMARK",
+            EmbeddingEntityType::Element => "ELEMENT",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Result<Self, PpdcError> {
+        match value {
+            "LANDMARK" | "landmark" => Ok(EmbeddingEntityType::Landmark),
+            "ELEMENT" | "element" => Ok(EmbeddingEntityType::Element),
+            _ => Err(PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                format!("Invalid embedding entity type: {}", value),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::embeddings)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Embedding {
+    pub id: Uuid,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub model: String,
+    pub content_hash: String,
+    pub vector: Vec<f32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::embeddings)]
+pub struct NewEmbedding {
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub model: String,
+    pub content_hash: String,
+    pub vector: Vec<f32>,
+}
+
+impl NewEmbedding {
+    pub fn new(
+        entity_type: EmbeddingEntityType,
+        entity_id: Uuid,
+        model: String,
+        text: &str,
+        vector: Vec<f32>,
+    ) -> Self {
+        Self {
+            entity_type: entity_type.to_db().to_string(),
+            entity_id,
+            model,
+            content_hash: content_hash(text),
+            vector,
+        }
+    }
+}
+
+/// A landmark or element row reduced to what gets embedded.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct EmbeddingSource {
+    #[diesel(sql_type = SqlUuid)]
+    pub id: Uuid,
+    #[diesel(sql_type = Text)]
+    pub title: String,
+    #[diesel(sql_type = Text)]
+    pub subtitle: String,
+    #[diesel(sql_type = Text)]
+    pub content: String,
+}
+
+impl EmbeddingSource {
+    pub fn text(&self) -> String {
+        embedding_text(&self.title, &self.subtitle, &self.content)
+    }
+}
+
+pub fn embedding_text(title: &str, subtitle: &str, content: &str) -> String {
+    let content: String = content.chars().take(MAX_EMBEDDED_CONTENT_CHARS).collect();
+    [title.trim(), subtitle.trim(), content.trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Stored vectors are reused only while the embedded text is unchanged.
+pub fn content_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
@@ -0,0 +1,86 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Text};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::PpdcError;
+use crate::schema::embeddings;
+
+use super::model::{Embedding, EmbeddingEntityType, EmbeddingSource, NewEmbedding};
+
+impl NewEmbedding {
+    pub fn upsert(self, pool: &DbPool) -> Result<(), PpdcError> {
+        let mut conn = pool.get()?;
+        diesel::insert_into(embeddings::table)
+            .values(&self)
+            .on_conflict((
+                embeddings::entity_type,
+                embeddings::entity_id,
+                embeddings::model,
+            ))
+            .do_update()
+            .set((
+                embeddings::content_hash.eq(&self.content_hash),
+                embeddings::vector.eq(&self.vector),
+                embeddings::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+}
+
+impl Embedding {
+    pub fn find_for_entities(
+        entity_type: EmbeddingEntityType,
+        entity_ids: &[Uuid],
+        model: &str,
+        pool: &DbPool,
+    ) -> Result<Vec<Embedding>, PpdcError> {
+        if entity_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut conn = pool.get()?;
+        let rows = embeddings::table
+            .filter(embeddings::entity_type.eq(entity_type.to_db()))
+            .filter(embeddings::entity_id.eq_any(entity_ids))
+            .filter(embeddings::model.eq(model))
+            .select(Embedding::as_select())
+            .load::<Embedding>(&mut conn)?;
+        Ok(rows)
+    }
+
+    /// Landmarks or elements that have no vector yet for `model`, oldest first.
+    pub fn find_sources_missing_embedding(
+        entity_type: EmbeddingEntityType,
+        model: &str,
+        limit: i64,
+        pool: &DbPool,
+    ) -> Result<Vec<EmbeddingSource>, PpdcError> {
+        let source_table = match entity_type {
+            EmbeddingEntityType::Landmark => "landmarks",
+            EmbeddingEntityType::Element => "elements",
+        };
+        let query = format!(
+            r#"
+            SELECT s.id, s.title, s.subtitle, s.content
+            FROM {source_table} s
+            LEFT JOIN embeddings e
+                ON e.entity_type = $1
+                AND e.entity_id = s.id
+                AND e.model = $2
+            WHERE e.id IS NULL
+            ORDER BY s.created_at ASC
+            LIMIT $3
+            "#
+        );
+        let mut conn = pool.get()?;
+        let rows = sql_query(query)
+            .bind::<Text, _>(entity_type.to_db())
+            .bind::<Text, _>(model)
+            .bind::<BigInt, _>(limit)
+            .load::<EmbeddingSource>(&mut conn)?;
+        Ok(rows)
+    }
+}
@@ -0,0 +1,81 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Json},
+    http::HeaderMap,
+};
+use serde::Serialize;
+
+use crate::db::DbPool;
+use crate::entities_v2::background_job::{BackgroundJob, BackgroundJobType};
+use crate::entities_v2::error::PpdcError;
+use crate::entities_v2::trace_search;
+use crate::environment;
+use crate::openai_handler::EmbeddingProviderKind;
+use crate::work_analyzer::candidate_retrieval;
+
+use super::model::EmbeddingEntityType;
+
+const EMBEDDING_BACKFILL_BATCH_LIMIT: i64 = 200;
+
+#[derive(Serialize, Default)]
+pub struct EmbeddingsBackfillResponse {
+    pub model: String,
+    pub landmarks_embedded: usize,
+    pub elements_embedded: usize,
+    pub trace_chunks_embedded: usize,
+}
+
+/// Embeds one batch of landmarks, elements and trace passages that have no vector yet.
+pub async fn backfill_embeddings_batch(
+    pool: &DbPool,
+) -> Result<EmbeddingsBackfillResponse, PpdcError> {
+    let provider = EmbeddingProviderKind::from_env().build();
+    let landmarks_embedded = candidate_retrieval::backfill_embeddings(
+        provider.as_ref(),
+        EmbeddingEntityType::Landmark,
+        EMBEDDING_BACKFILL_BATCH_LIMIT,
+        pool,
+    )
+    .await?;
+    let elements_embedded = candidate_retrieval::backfill_embeddings(
+        provider.as_ref(),
+        EmbeddingEntityType::Element,
+        EMBEDDING_BACKFILL_BATCH_LIMIT,
+        pool,
+    )
+    .await?;
+    let trace_chunks_embedded = trace_search::backfill_chunk_embeddings(
+        provider.as_ref(),
+        EMBEDDING_BACKFILL_BATCH_LIMIT,
+        pool,
+    )
+    .await?;
+
+    Ok(EmbeddingsBackfillResponse {
+        model: provider.model().to_string(),
+        landmarks_embedded,
+        elements_embedded,
+        trace_chunks_embedded,
+    })
+}
+
+/// Queues a backfill job, which embeds batches until nothing is left.
+#[debug_handler]
+pub async fn post_backfill_embeddings_route(
+    Extension(pool): Extension<DbPool>,
+    headers: HeaderMap,
+) -> Result<Json<BackgroundJob>, PpdcError> {
+    let provided_token = headers
+        .get("x-internal-cron-token")
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(PpdcError::unauthorized)?;
+
+    if provided_token != environment::get_internal_cron_token() {
+        return Err(PpdcError::unauthorized());
+    }
+
+    Ok(Json(BackgroundJob::enqueue_singleton(
+        BackgroundJobType::BackfillEmbeddings,
+        &pool,
+    )?))
+}
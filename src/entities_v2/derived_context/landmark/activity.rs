@@ -0,0 +1,407 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Array, BigInt, Nullable, Text, Timestamp, Uuid as SqlUuid};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::PpdcError;
+
+use super::model::Landmark;
+
+/// Recent activity is compared with the window right before it to get the momentum.
+pub const MOMENTUM_WINDOW_DAYS: i64 = 28;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityGranularity {
+    Day,
+    Week,
+}
+
+impl ActivityGranularity {
+    /// First day of the bucket `day` falls in; weeks start on Monday.
+    pub fn bucket_start(self, day: NaiveDate) -> NaiveDate {
+        match self {
+            ActivityGranularity::Day => day,
+            ActivityGranularity::Week => {
+                day - Duration::days(day.weekday().num_days_from_monday() as i64)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LandmarkActivitySort {
+    /// Last seen first.
+    Recency,
+    /// Most references and elements first.
+    Frequency,
+    /// Fastest growing first; stale landmarks end up last.
+    Momentum,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityKind {
+    Reference,
+    Element,
+}
+
+/// One reference or element link of a landmark, dated by the trace or element it comes from.
+#[derive(Debug, Clone)]
+pub struct ActivityEvent {
+    pub landmark_id: Uuid,
+    pub kind: ActivityKind,
+    pub occurred_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ActivityBucket {
+    pub period_start: NaiveDate,
+    pub references_count: i64,
+    pub elements_count: i64,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ActivityStats {
+    pub references_count: i64,
+    pub elements_count: i64,
+    pub first_seen_at: Option<NaiveDateTime>,
+    pub last_seen_at: Option<NaiveDateTime>,
+    /// Events in the last `MOMENTUM_WINDOW_DAYS` days.
+    pub recent_count: i64,
+    /// Events in the window before that.
+    pub previous_count: i64,
+    pub momentum: f64,
+}
+
+impl ActivityStats {
+    pub fn from_events<'a>(
+        events: impl IntoIterator<Item = &'a ActivityEvent>,
+        now: NaiveDateTime,
+    ) -> Self {
+        let recent_from = now - Duration::days(MOMENTUM_WINDOW_DAYS);
+        let previous_from = recent_from - Duration::days(MOMENTUM_WINDOW_DAYS);
+        let mut stats = ActivityStats::default();
+        for event in events {
+            match event.kind {
+                ActivityKind::Reference => stats.references_count += 1,
+                ActivityKind::Element => stats.elements_count += 1,
+            }
+            if event.occurred_at >= recent_from {
+                stats.recent_count += 1;
+            } else if event.occurred_at >= previous_from {
+                stats.previous_count += 1;
+            }
+            stats.first_seen_at = Some(
+                stats
+                    .first_seen_at
+                    .map_or(event.occurred_at, |first| first.min(event.occurred_at)),
+            );
+            stats.last_seen_at = Some(
+                stats
+                    .last_seen_at
+                    .map_or(event.occurred_at, |last| last.max(event.occurred_at)),
+            );
+        }
+        stats.momentum = momentum(stats.recent_count, stats.previous_count);
+        stats
+    }
+}
+
+/// Relative growth between two windows; a landmark new in the recent window counts as growing
+/// by its own size.
+pub fn momentum(recent_count: i64, previous_count: i64) -> f64 {
+    (recent_count - previous_count) as f64 / previous_count.max(1) as f64
+}
+
+/// Non-empty buckets in chronological order.
+pub fn bucket_events(
+    events: &[ActivityEvent],
+    granularity: ActivityGranularity,
+) -> Vec<ActivityBucket> {
+    let mut buckets = BTreeMap::<NaiveDate, ActivityBucket>::new();
+    for event in events {
+        let period_start = granularity.bucket_start(event.occurred_at.date());
+        let bucket = buckets.entry(period_start).or_insert(ActivityBucket {
+            period_start,
+            references_count: 0,
+            elements_count: 0,
+        });
+        match event.kind {
+            ActivityKind::Reference => bucket.references_count += 1,
+            ActivityKind::Element => bucket.elements_count += 1,
+        }
+    }
+    buckets.into_values().collect()
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LandmarkTimelineTrace {
+    pub trace_id: Uuid,
+    pub title: String,
+    pub interaction_date: NaiveDateTime,
+    pub references_count: i64,
+    pub mentions: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LandmarkTimeline {
+    pub landmark_id: Uuid,
+    pub lens_id: Option<Uuid>,
+    pub granularity: ActivityGranularity,
+    #[serde(flatten)]
+    pub stats: ActivityStats,
+    pub buckets: Vec<ActivityBucket>,
+    pub traces: Vec<LandmarkTimelineTrace>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LandmarkActivity {
+    #[serde(flatten)]
+    pub landmark: Landmark,
+    pub activity: ActivityStats,
+}
+
+#[derive(QueryableByName)]
+struct ActivityEventRow {
+    #[diesel(sql_type = SqlUuid)]
+    landmark_id: Uuid,
+    #[diesel(sql_type = Text)]
+    kind: String,
+    #[diesel(sql_type = Timestamp)]
+    occurred_at: NaiveDateTime,
+}
+
+#[derive(QueryableByName)]
+struct TimelineTraceRow {
+    #[diesel(sql_type = SqlUuid)]
+    trace_id: Uuid,
+    #[diesel(sql_type = Text)]
+    title: String,
+    #[diesel(sql_type = Timestamp)]
+    interaction_date: NaiveDateTime,
+    #[diesel(sql_type = BigInt)]
+    references_count: i64,
+    #[diesel(sql_type = Array<Text>)]
+    mentions: Vec<String>,
+}
+
+/// References are dated by their trace, elements by their interaction date.
+///
+/// `analysis_ids` restricts events to the analyses of a lens; `landmark_id` to one landmark.
+pub fn load_activity_events(
+    user_id: Uuid,
+    landmark_id: Option<Uuid>,
+    analysis_ids: Option<&[Uuid]>,
+    pool: &DbPool,
+) -> Result<Vec<ActivityEvent>, PpdcError> {
+    let mut conn = pool.get()?;
+    let rows = sql_query(
+        r#"
+        SELECT r.landmark_id AS landmark_id, 'REFERENCE' AS kind, t.interaction_date AS occurred_at
+        FROM "references" r
+        INNER JOIN trace_mirrors tm ON tm.id = r.trace_mirror_id
+        INNER JOIN traces t ON t.id = tm.trace_id
+        WHERE r.user_id = $1
+          AND r.landmark_id IS NOT NULL
+          AND ($2::uuid IS NULL OR r.landmark_id = $2)
+          AND ($3::uuid[] IS NULL OR r.landscape_analysis_id = ANY($3))
+        UNION ALL
+        SELECT el.landmark_id, 'ELEMENT', COALESCE(e.interaction_date, e.created_at)
+        FROM element_landmarks el
+        INNER JOIN elements e ON e.id = el.element_id
+        INNER JOIN landmarks l ON l.id = el.landmark_id
+        WHERE l.user_id = $1
+          AND ($2::uuid IS NULL OR el.landmark_id = $2)
+          AND ($3::uuid[] IS NULL OR e.analysis_id = ANY($3))
+        "#,
+    )
+    .bind::<SqlUuid, _>(user_id)
+    .bind::<Nullable<SqlUuid>, _>(landmark_id)
+    .bind::<Nullable<Array<SqlUuid>>, _>(analysis_ids)
+    .load::<ActivityEventRow>(&mut conn)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| ActivityEvent {
+            landmark_id: row.landmark_id,
+            kind: if row.kind == "REFERENCE" {
+                ActivityKind::Reference
+            } else {
+                ActivityKind::Element
+            },
+            occurred_at: row.occurred_at,
+        })
+        .collect())
+}
+
+impl Landmark {
+    pub fn find_timeline(
+        &self,
+        user_id: Uuid,
+        lens_id: Option<Uuid>,
+        analysis_ids: Option<&[Uuid]>,
+        granularity: ActivityGranularity,
+        now: NaiveDateTime,
+        pool: &DbPool,
+    ) -> Result<LandmarkTimeline, PpdcError> {
+        let events = load_activity_events(user_id, Some(self.id), analysis_ids, pool)?;
+        let mut conn = pool.get()?;
+        let traces = sql_query(
+            r#"
+            SELECT
+                t.id AS trace_id,
+                t.title,
+                t.interaction_date,
+                COUNT(r.id) AS references_count,
+                array_agg(DISTINCT r.mention) AS mentions
+            FROM "references" r
+            INNER JOIN trace_mirrors tm ON tm.id = r.trace_mirror_id
+            INNER JOIN traces t ON t.id = tm.trace_id
+            WHERE r.landmark_id = $1
+              AND ($2::uuid[] IS NULL OR r.landscape_analysis_id = ANY($2))
+            GROUP BY t.id, t.title, t.interaction_date
+            ORDER BY t.interaction_date ASC, t.id ASC
+            "#,
+        )
+        .bind::<SqlUuid, _>(self.id)
+        .bind::<Nullable<Array<SqlUuid>>, _>(analysis_ids)
+        .load::<TimelineTraceRow>(&mut conn)?
+        .into_iter()
+        .map(|row| LandmarkTimelineTrace {
+            trace_id: row.trace_id,
+            title: row.title,
+            interaction_date: row.interaction_date,
+            references_count: row.references_count,
+            mentions: row.mentions,
+        })
+        .collect();
+
+        Ok(LandmarkTimeline {
+            landmark_id: self.id,
+            lens_id,
+            granularity,
+            stats: ActivityStats::from_events(&events, now),
+            buckets: bucket_events(&events, granularity),
+            traces,
+        })
+    }
+
+    /// Landmarks of a user with any activity in scope, with their activity stats.
+    pub fn find_activity_for_user(
+        user_id: Uuid,
+        analysis_ids: Option<&[Uuid]>,
+        sort: LandmarkActivitySort,
+        now: NaiveDateTime,
+        pool: &DbPool,
+    ) -> Result<Vec<LandmarkActivity>, PpdcError> {
+        let mut events_by_landmark = HashMap::<Uuid, Vec<ActivityEvent>>::new();
+        for event in load_activity_events(user_id, None, analysis_ids, pool)? {
+            events_by_landmark
+                .entry(event.landmark_id)
+                .or_default()
+                .push(event);
+        }
+        let landmark_ids = events_by_landmark.keys().copied().collect::<Vec<_>>();
+        let mut activities = Landmark::find_by_ids(&landmark_ids, pool)?
+            .into_iter()
+            .map(|landmark| {
+                let activity = ActivityStats::from_events(
+                    events_by_landmark.get(&landmark.id).into_iter().flatten(),
+                    now,
+                );
+                LandmarkActivity { landmark, activity }
+            })
+            .collect::<Vec<_>>();
+        sort_activities(&mut activities, sort);
+        Ok(activities)
+    }
+}
+
+fn sort_activities(activities: &mut [LandmarkActivity], sort: LandmarkActivitySort) {
+    match sort {
+        LandmarkActivitySort::Recency => activities.sort_by(|a, b| {
+            b.activity
+                .last_seen_at
+                .cmp(&a.activity.last_seen_at)
+                .then_with(|| b.landmark.created_at.cmp(&a.landmark.created_at))
+        }),
+        LandmarkActivitySort::Frequency => activities.sort_by(|a, b| {
+            let total =
+                |activity: &ActivityStats| activity.references_count + activity.elements_count;
+            total(&b.activity)
+                .cmp(&total(&a.activity))
+                .then_with(|| b.activity.last_seen_at.cmp(&a.activity.last_seen_at))
+        }),
+        LandmarkActivitySort::Momentum => activities.sort_by(|a, b| {
+            b.activity
+                .momentum
+                .total_cmp(&a.activity.momentum)
+                .then_with(|| b.activity.recent_count.cmp(&a.activity.recent_count))
+                .then_with(|| b.activity.last_seen_at.cmp(&a.activity.last_seen_at))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: &str) -> NaiveDateTime {
+        NaiveDate::parse_from_str(day, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    fn event(kind: ActivityKind, day: &str) -> ActivityEvent {
+        ActivityEvent {
+            landmark_id: Uuid::nil(),
+            kind,
+            occurred_at: at(day),
+        }
+    }
+
+    #[test]
+    fn buckets_by_monday_weeks() {
+        let events = vec![
+            event(ActivityKind::Reference, "2026-10-14"),
+            event(ActivityKind::Element, "2026-10-12"),
+            event(ActivityKind::Reference, "2026-10-05"),
+        ];
+        let buckets = bucket_events(&events, ActivityGranularity::Week);
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].period_start, at("2026-10-05").date());
+        assert_eq!(buckets[1].period_start, at("2026-10-12").date());
+        assert_eq!(buckets[1].references_count, 1);
+        assert_eq!(buckets[1].elements_count, 1);
+    }
+
+    #[test]
+    fn stats_compare_recent_and_previous_windows() {
+        let events = vec![
+            event(ActivityKind::Reference, "2026-10-10"),
+            event(ActivityKind::Element, "2026-10-01"),
+            event(ActivityKind::Reference, "2026-09-01"),
+            event(ActivityKind::Reference, "2026-01-01"),
+        ];
+        let stats = ActivityStats::from_events(&events, at("2026-10-18"));
+        assert_eq!(stats.references_count, 3);
+        assert_eq!(stats.elements_count, 1);
+        assert_eq!(stats.recent_count, 2);
+        assert_eq!(stats.previous_count, 1);
+        assert_eq!(stats.momentum, 1.0);
+        assert_eq!(stats.first_seen_at, Some(at("2026-01-01")));
+        assert_eq!(stats.last_seen_at, Some(at("2026-10-10")));
+    }
+
+    #[test]
+    fn momentum_is_negative_for_stale_landmarks() {
+        assert_eq!(momentum(0, 4), -1.0);
+        assert_eq!(momentum(3, 0), 3.0);
+    }
+}
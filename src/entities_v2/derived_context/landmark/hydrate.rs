@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use uuid::Uuid;
@@ -6,6 +8,7 @@ use super::model::{Landmark, LandmarkType, LandmarkWithParentsAndElements};
 use crate::db::DbPool;
 use crate::entities_v2::element::model::Element;
 use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::entities_v2::landmark_version::LandmarkVersion;
 use crate::entities_v2::shared::MaturingState;
 use crate::schema::{landmarks, landscape_landmarks};
 
@@ -114,6 +117,18 @@ impl Landmark {
         })
     }
 
+    pub fn find_by_ids(ids: &[Uuid], pool: &DbPool) -> Result<Vec<Landmark>, PpdcError> {
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut conn = pool.get()?;
+        let rows = landmarks::table
+            .filter(landmarks::id.eq_any(ids))
+            .select(select_landmark_columns())
+            .load::<LandmarkTuple>(&mut conn)?;
+        Ok(rows.into_iter().map(tuple_to_landmark).collect())
+    }
+
     pub fn find_user_id(id: Uuid, pool: &DbPool) -> Result<Uuid, PpdcError> {
         let mut conn = pool.get()?;
         let user_id = landmarks::table
@@ -146,8 +161,14 @@ impl Landmark {
         id: Uuid,
         pool: &DbPool,
     ) -> Result<LandmarkWithParentsAndElements, PpdcError> {
-        let origin_landmark = Landmark::find(id, pool)?;
+        let mut origin_landmark = Landmark::find(id, pool)?;
         let mut elements: Vec<Element> = origin_landmark.find_elements(pool)?;
+        // Counts on the landmark row are not maintained anymore, they live in landmark versions.
+        origin_landmark.related_elements_count = elements.len() as i32;
+        origin_landmark.last_related_element_at = elements
+            .iter()
+            .filter_map(|element| element.interaction_date)
+            .max();
         let mut parents: Vec<Landmark> = vec![];
         let mut current_landmark = origin_landmark.clone();
         while let Some(parent) = current_landmark.find_parent(pool)? {
@@ -186,9 +207,21 @@ impl Landmark {
             .select(landscape_landmarks::landmark_id)
             .load::<Uuid>(&mut conn)?;
 
+        let versions =
+            LandmarkVersion::find_for_analysis(landscape_analysis_id, &landmark_ids, pool)?
+                .into_iter()
+                .map(|version| (version.landmark_id, version))
+                .collect::<HashMap<Uuid, LandmarkVersion>>();
+
         landmark_ids
             .into_iter()
-            .map(|landmark_id| Landmark::find(landmark_id, pool))
+            .map(|landmark_id| {
+                let landmark = Landmark::find(landmark_id, pool)?;
+                Ok(match versions.get(&landmark_id) {
+                    Some(version) => version.apply_to(landmark),
+                    None => landmark,
+                })
+            })
             .collect::<Result<Vec<_>, _>>()
     }
 
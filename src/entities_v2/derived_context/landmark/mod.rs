@@ -1,3 +1,4 @@
+pub mod activity;
 pub mod enums;
 pub mod hydrate;
 pub mod model;
@@ -6,4 +7,4 @@ pub mod routes;
 
 pub use model::{Landmark, LandmarkType, LandmarkWithParentsAndElements, NewLandmark};
 pub use persist::create_copy_child_and_return;
-pub use routes::get_landmark_route;
+pub use routes::{get_landmark_route, get_landmark_timeline_route, get_user_landmarks_route};
@@ -3,6 +3,9 @@ use uuid::Uuid;
 
 use crate::db::DbPool;
 use crate::entities_v2::error::PpdcError;
+use crate::entities_v2::landmark_curation::find_alias_target;
+use crate::entities_v2::landmark_version::LandmarkVersion;
+use crate::entities_v2::landscape_analysis::add_landmark_ref;
 use crate::schema::{landmark_relations, landmarks, landscape_landmarks};
 
 use super::model::{Landmark, NewLandmark};
@@ -29,7 +32,21 @@ impl Landmark {
 }
 
 impl NewLandmark {
+    /// Creates the landmark, or reuses the one of the same type its title was merged into by
+    /// the user, when the analysis lineage knows it.
     pub fn create(self, pool: &DbPool) -> Result<Landmark, PpdcError> {
+        if self.parent_id.is_none() {
+            if let Some(alias_target_id) = find_alias_target(
+                self.user_id,
+                self.analysis_id,
+                &self.title,
+                self.landmark_type,
+                pool,
+            )? {
+                add_landmark_ref(self.analysis_id, alias_target_id, self.user_id, pool)?;
+                return Landmark::find(alias_target_id, pool);
+            }
+        }
         let mut conn = pool.get()?;
 
         let analysis_id = self.analysis_id;
@@ -62,6 +79,7 @@ impl NewLandmark {
             ))
             .do_nothing()
             .execute(&mut conn)?;
+        LandmarkVersion::ensure_for_analysis(analysis_id, id, None, &mut conn)?;
 
         if let Some(parent_landmark_id) = parent_id {
             diesel::insert_into(landmark_relations::table)
@@ -1,14 +1,50 @@
 use axum::{
     debug_handler,
-    extract::{Extension, Json, Path},
+    extract::{Extension, Json, Path, Query},
 };
+use chrono::Utc;
+use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::db::DbPool;
-use crate::entities_v2::{error::PpdcError, session::Session};
+use crate::entities_v2::{error::PpdcError, lens::Lens, session::Session};
 
+use super::activity::{
+    ActivityGranularity, LandmarkActivity, LandmarkActivitySort, LandmarkTimeline,
+};
 use super::model::{Landmark, LandmarkWithParentsAndElements};
 
+#[derive(Deserialize)]
+pub struct LandmarkTimelineQuery {
+    /// Restricts activity to the analyses of this lens.
+    pub lens_id: Option<Uuid>,
+    /// `day` or `week` (default).
+    pub granularity: Option<ActivityGranularity>,
+}
+
+#[derive(Deserialize)]
+pub struct UserLandmarksQuery {
+    pub lens_id: Option<Uuid>,
+    /// `recency` (default), `frequency` or `momentum`.
+    pub sort: Option<LandmarkActivitySort>,
+}
+
+/// Analysis ids of the lens, after checking it belongs to the user.
+fn lens_analysis_ids(
+    lens_id: Option<Uuid>,
+    user_id: Uuid,
+    pool: &DbPool,
+) -> Result<Option<Vec<Uuid>>, PpdcError> {
+    let Some(lens_id) = lens_id else {
+        return Ok(None);
+    };
+    let lens = Lens::find_full_lens(lens_id, pool)?;
+    if lens.user_id != Some(user_id) {
+        return Err(PpdcError::unauthorized());
+    }
+    lens.get_analysis_scope_ids(pool).map(Some)
+}
+
 #[debug_handler]
 pub async fn get_landmark_route(
     Extension(pool): Extension<DbPool>,
@@ -23,3 +59,49 @@ pub async fn get_landmark_route(
     let landmark = Landmark::find_with_parents(id, &pool)?;
     Ok(Json(landmark))
 }
+
+#[debug_handler]
+pub async fn get_landmark_timeline_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<LandmarkTimelineQuery>,
+) -> Result<Json<LandmarkTimeline>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let landmark_user_id = Landmark::find_user_id(id, &pool)?;
+    if landmark_user_id != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    let analysis_ids = lens_analysis_ids(params.lens_id, user_id, &pool)?;
+    let timeline = Landmark::find(id, &pool)?.find_timeline(
+        user_id,
+        params.lens_id,
+        analysis_ids.as_deref(),
+        params.granularity.unwrap_or(ActivityGranularity::Week),
+        Utc::now().naive_utc(),
+        &pool,
+    )?;
+    Ok(Json(timeline))
+}
+
+#[debug_handler]
+pub async fn get_user_landmarks_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(user_id): Path<Uuid>,
+    Query(params): Query<UserLandmarksQuery>,
+) -> Result<Json<Vec<LandmarkActivity>>, PpdcError> {
+    let session_user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    if session_user_id != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    let analysis_ids = lens_analysis_ids(params.lens_id, user_id, &pool)?;
+    let landmarks = Landmark::find_activity_for_user(
+        user_id,
+        analysis_ids.as_deref(),
+        params.sort.unwrap_or(LandmarkActivitySort::Recency),
+        Utc::now().naive_utc(),
+        &pool,
+    )?;
+    Ok(Json(landmarks))
+}
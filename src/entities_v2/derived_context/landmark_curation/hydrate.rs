@@ -0,0 +1,114 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Text, Timestamp, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::PpdcError;
+use crate::entities_v2::landmark::LandmarkType;
+
+use super::model::{normalize_alias_title, LandmarkCuration, LandmarkCurationAction};
+
+pub(super) const LANDMARK_CURATION_COLUMNS: &str =
+    "id, user_id, landmark_id, action, details::text AS details, created_at";
+
+#[derive(QueryableByName)]
+pub(super) struct LandmarkCurationRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    user_id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    landmark_id: Uuid,
+    #[diesel(sql_type = Text)]
+    action: String,
+    #[diesel(sql_type = Text)]
+    details: String,
+    #[diesel(sql_type = Timestamp)]
+    created_at: NaiveDateTime,
+}
+
+impl TryFrom<LandmarkCurationRow> for LandmarkCuration {
+    type Error = PpdcError;
+
+    fn try_from(row: LandmarkCurationRow) -> Result<Self, Self::Error> {
+        Ok(LandmarkCuration {
+            id: row.id,
+            user_id: row.user_id,
+            landmark_id: row.landmark_id,
+            action: LandmarkCurationAction::from_db(&row.action),
+            details: serde_json::from_str(&row.details)?,
+            created_at: row.created_at,
+        })
+    }
+}
+
+impl LandmarkCuration {
+    /// Curations of a landmark, including those moved onto it by merges, newest first.
+    pub fn find_for_landmark(
+        landmark_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<Vec<LandmarkCuration>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = sql_query(format!(
+            "SELECT {} FROM landmark_curations WHERE landmark_id = $1 ORDER BY created_at DESC",
+            LANDMARK_CURATION_COLUMNS
+        ))
+        .bind::<SqlUuid, _>(landmark_id)
+        .load::<LandmarkCurationRow>(&mut conn)?;
+        rows.into_iter().map(TryInto::try_into).collect()
+    }
+}
+
+#[derive(QueryableByName)]
+struct AliasTargetRow {
+    #[diesel(sql_type = SqlUuid)]
+    landmark_id: Uuid,
+}
+
+/// Landmark a user folded `title` into, if any, among the landmarks of the same type that the
+/// analysis lineage (the analysis and its parents) already knows.
+pub fn find_alias_target(
+    user_id: Uuid,
+    analysis_id: Uuid,
+    title: &str,
+    landmark_type: LandmarkType,
+    pool: &DbPool,
+) -> Result<Option<Uuid>, PpdcError> {
+    let mut conn = pool.get()?;
+    let row = sql_query(
+        r#"
+        WITH RECURSIVE lineage AS (
+            SELECT id, parent_id
+            FROM landscape_analyses
+            WHERE id = $2
+            UNION
+            SELECT parent.id, parent.parent_id
+            FROM landscape_analyses parent
+            INNER JOIN lineage ON parent.id = lineage.parent_id
+        )
+        SELECT la.landmark_id
+        FROM landmark_aliases la
+        INNER JOIN landmarks l ON l.id = la.landmark_id
+        WHERE la.user_id = $1
+          AND la.normalized_title = $3
+          AND l.landmark_type = $4
+          AND EXISTS (
+              SELECT 1
+              FROM landscape_landmarks ll
+              INNER JOIN lineage ON lineage.id = ll.landscape_analysis_id
+              WHERE ll.landmark_id = la.landmark_id
+          )
+        ORDER BY la.created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind::<SqlUuid, _>(user_id)
+    .bind::<SqlUuid, _>(analysis_id)
+    .bind::<Text, _>(normalize_alias_title(title))
+    .bind::<Text, _>(landmark_type.to_code())
+    .get_result::<AliasTargetRow>(&mut conn)
+    .optional()?;
+    Ok(row.map(|row| row.landmark_id))
+}
@@ -0,0 +1,14 @@
+pub mod hydrate;
+pub mod model;
+pub mod persist;
+pub mod routes;
+
+pub use hydrate::find_alias_target;
+pub use model::{
+    LandmarkCuration, LandmarkCurationAction, MergeLandmarksDto,
+    RefreshLandmarkSearchDocumentsPayload, SplitLandmarkDto, UpdateLandmarkDto,
+};
+pub use routes::{
+    get_landmark_curations_route, patch_landmark_route, post_merge_landmarks_route,
+    post_split_landmark_route,
+};
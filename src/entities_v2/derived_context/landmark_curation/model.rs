@@ -0,0 +1,134 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::entities_v2::landmark::LandmarkType;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LandmarkCurationAction {
+    Merge,
+    Split,
+    Rename,
+    Retype,
+}
+
+impl LandmarkCurationAction {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            LandmarkCurationAction::Merge => "MERGE",
+            LandmarkCurationAction::Split => "SPLIT",
+            LandmarkCurationAction::Rename => "RENAME",
+            LandmarkCurationAction::Retype => "RETYPE",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "MERGE" => LandmarkCurationAction::Merge,
+            "SPLIT" => LandmarkCurationAction::Split,
+            "RETYPE" => LandmarkCurationAction::Retype,
+            _ => LandmarkCurationAction::Rename,
+        }
+    }
+}
+
+/// A correction made by the user on a landmark, kept as an audit trail.
+///
+/// Merges also leave aliases behind, so later analyses map the merged titles back onto the
+/// surviving landmark instead of recreating them.
+#[derive(Serialize, Debug, Clone)]
+pub struct LandmarkCuration {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub landmark_id: Uuid,
+    pub action: LandmarkCurationAction,
+    pub details: Value,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct MergeLandmarksDto {
+    pub target_landmark_id: Uuid,
+    pub source_landmark_ids: Vec<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct SplitLandmarkDto {
+    /// Elements moved from the landmark to the new one.
+    pub element_ids: Vec<Uuid>,
+    pub title: String,
+    pub subtitle: Option<String>,
+    /// Defaults to the type of the split landmark.
+    pub landmark_type: Option<LandmarkType>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateLandmarkDto {
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub landmark_type: Option<LandmarkType>,
+}
+
+/// Payload of a `REFRESH_LANDMARK_SEARCH_DOCUMENTS` job.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefreshLandmarkSearchDocumentsPayload {
+    pub landmark_id: Uuid,
+}
+
+/// Sorted, deduplicated merge sources, or why the merge request is invalid.
+pub fn merge_source_ids(
+    target_id: Uuid,
+    mut source_ids: Vec<Uuid>,
+) -> Result<Vec<Uuid>, &'static str> {
+    source_ids.sort();
+    source_ids.dedup();
+    if source_ids.is_empty() {
+        return Err("At least one source landmark is required");
+    }
+    if source_ids.contains(&target_id) {
+        return Err("The target landmark cannot be merged into itself");
+    }
+    Ok(source_ids)
+}
+
+/// Key under which an alias title is matched: trimmed, lowercased, single-spaced.
+pub fn normalize_alias_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_case_and_whitespace() {
+        assert_eq!(
+            normalize_alias_title("  Projet   Atlas\tV2 "),
+            "projet atlas v2"
+        );
+        assert_eq!(normalize_alias_title("Élodie"), "élodie");
+    }
+
+    #[test]
+    fn merge_sources_are_deduplicated() {
+        let target = Uuid::new_v4();
+        let source = Uuid::new_v4();
+        assert_eq!(
+            merge_source_ids(target, vec![source, source]),
+            Ok(vec![source])
+        );
+    }
+
+    #[test]
+    fn merge_rejects_empty_sources_and_self_merge() {
+        let target = Uuid::new_v4();
+        assert!(merge_source_ids(target, vec![]).is_err());
+        assert!(merge_source_ids(target, vec![Uuid::new_v4(), target]).is_err());
+    }
+}
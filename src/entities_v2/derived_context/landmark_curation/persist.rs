@@ -0,0 +1,486 @@
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Array, Text, Uuid as SqlUuid};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::background_job::{BackgroundJob, BackgroundJobType};
+use crate::entities_v2::bio_profile::persist::retarget_bio_profile_landmarks;
+use crate::entities_v2::element::persist::refresh_landmark_related_elements_stats;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::entities_v2::landmark::Landmark;
+use crate::entities_v2::landmark_version::LandmarkVersion;
+use crate::entities_v2::trace_search::TraceSearchDocument;
+use crate::schema::{
+    element_landmarks, landmark_relations, landmark_versions, landmarks, references, trace_mirrors,
+};
+
+use super::model::{
+    merge_source_ids, normalize_alias_title, LandmarkCurationAction, MergeLandmarksDto,
+    RefreshLandmarkSearchDocumentsPayload, SplitLandmarkDto, UpdateLandmarkDto,
+};
+
+/// Folds the source landmarks into the target and deletes them.
+///
+/// Everything pointing at a source is moved onto the target, and each source title becomes an
+/// alias of the target so later analyses do not recreate the duplicate.
+pub fn merge_landmarks(
+    user_id: Uuid,
+    payload: MergeLandmarksDto,
+    pool: &DbPool,
+) -> Result<Landmark, PpdcError> {
+    let target_id = payload.target_landmark_id;
+    let source_ids =
+        merge_source_ids(target_id, payload.source_landmark_ids).map_err(bad_request)?;
+    let sources = source_ids
+        .iter()
+        .map(|id| Landmark::find(*id, pool))
+        .collect::<Result<Vec<_>, _>>()?;
+    let details = json!({
+        "merged_landmarks": sources
+            .iter()
+            .map(|landmark| json!({
+                "id": landmark.id,
+                "title": landmark.title,
+                "landmark_type": landmark.landmark_type,
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let mut conn = pool.get()?;
+    conn.transaction::<(), diesel::result::Error, _>(|conn| {
+        sql_query(
+            r#"UPDATE "references" SET landmark_id = $1, updated_at = NOW() WHERE landmark_id = ANY($2)"#,
+        )
+        .bind::<SqlUuid, _>(target_id)
+        .bind::<Array<SqlUuid>, _>(&source_ids)
+        .execute(conn)?;
+        sql_query(
+            r#"
+            UPDATE trace_mirrors
+            SET primary_landmark_id = $1, updated_at = NOW()
+            WHERE primary_landmark_id = ANY($2)
+            "#,
+        )
+        .bind::<SqlUuid, _>(target_id)
+        .bind::<Array<SqlUuid>, _>(&source_ids)
+        .execute(conn)?;
+        sql_query(
+            r#"
+            INSERT INTO element_landmarks (element_id, landmark_id)
+            SELECT element_id, $1
+            FROM element_landmarks
+            WHERE landmark_id = ANY($2)
+            ON CONFLICT (element_id, landmark_id) DO NOTHING
+            "#,
+        )
+        .bind::<SqlUuid, _>(target_id)
+        .bind::<Array<SqlUuid>, _>(&source_ids)
+        .execute(conn)?;
+        sql_query(
+            r#"
+            INSERT INTO landscape_landmarks (landscape_analysis_id, landmark_id, relation_type)
+            SELECT landscape_analysis_id, $1, relation_type
+            FROM landscape_landmarks
+            WHERE landmark_id = ANY($2)
+            ON CONFLICT (landscape_analysis_id, landmark_id, relation_type) DO NOTHING
+            "#,
+        )
+        .bind::<SqlUuid, _>(target_id)
+        .bind::<Array<SqlUuid>, _>(&source_ids)
+        .execute(conn)?;
+        let analysis_ids = landmark_versions::table
+            .filter(landmark_versions::landmark_id.eq_any(&source_ids))
+            .select(landmark_versions::landscape_analysis_id)
+            .distinct()
+            .load::<Uuid>(conn)?;
+        for analysis_id in analysis_ids {
+            LandmarkVersion::ensure_for_analysis(analysis_id, target_id, None, conn)?;
+        }
+
+        // Relations are rewritten onto the target; those that would loop on it are dropped.
+        sql_query(
+            r#"
+            INSERT INTO landmark_relations (origin_landmark_id, target_landmark_id, relation_type)
+            SELECT origin_id, target_id, relation_type
+            FROM (
+                SELECT
+                    CASE WHEN origin_landmark_id = ANY($2) THEN $1 ELSE origin_landmark_id END AS origin_id,
+                    CASE WHEN target_landmark_id = ANY($2) THEN $1 ELSE target_landmark_id END AS target_id,
+                    relation_type
+                FROM landmark_relations
+                WHERE origin_landmark_id = ANY($2) OR target_landmark_id = ANY($2)
+            ) rewritten
+            WHERE origin_id <> target_id
+            ON CONFLICT (origin_landmark_id, target_landmark_id, relation_type) DO NOTHING
+            "#,
+        )
+        .bind::<SqlUuid, _>(target_id)
+        .bind::<Array<SqlUuid>, _>(&source_ids)
+        .execute(conn)?;
+        diesel::delete(
+            landmark_relations::table
+                .filter(landmark_relations::origin_landmark_id.eq(target_id))
+                .filter(landmark_relations::target_landmark_id.eq_any(&source_ids)),
+        )
+        .execute(conn)?;
+        sql_query(
+            r#"
+            UPDATE landmarks target
+            SET parent_id = CASE
+                    WHEN source.parent_id = ANY($2) OR source.parent_id = $1 THEN NULL
+                    ELSE source.parent_id
+                END,
+                updated_at = NOW()
+            FROM landmarks source
+            WHERE target.id = $1
+              AND source.id = target.parent_id
+              AND source.id = ANY($2)
+            "#,
+        )
+        .bind::<SqlUuid, _>(target_id)
+        .bind::<Array<SqlUuid>, _>(&source_ids)
+        .execute(conn)?;
+        diesel::update(
+            landmarks::table
+                .filter(landmarks::parent_id.eq_any(&source_ids))
+                .filter(landmarks::id.ne(target_id)),
+        )
+        .set(landmarks::parent_id.eq(target_id))
+        .execute(conn)?;
+
+        // Source aliases are copied; the rows themselves go with the deleted sources.
+        sql_query(
+            r#"
+            INSERT INTO landmark_aliases (user_id, landmark_id, title, normalized_title)
+            SELECT user_id, $1, title, normalized_title
+            FROM landmark_aliases
+            WHERE landmark_id = ANY($2)
+            ON CONFLICT (user_id, landmark_id, normalized_title) DO NOTHING
+            "#,
+        )
+        .bind::<SqlUuid, _>(target_id)
+        .bind::<Array<SqlUuid>, _>(&source_ids)
+        .execute(conn)?;
+        sql_query("UPDATE landmark_curations SET landmark_id = $1 WHERE landmark_id = ANY($2)")
+            .bind::<SqlUuid, _>(target_id)
+            .bind::<Array<SqlUuid>, _>(&source_ids)
+            .execute(conn)?;
+        for source in &sources {
+            upsert_alias(user_id, target_id, &source.title, conn)?;
+        }
+        retarget_bio_profile_landmarks(user_id, &source_ids, target_id, conn)?;
+
+        sql_query("DELETE FROM embeddings WHERE entity_type = 'LANDMARK' AND entity_id = ANY($1)")
+            .bind::<Array<SqlUuid>, _>(&source_ids)
+            .execute(conn)?;
+        diesel::delete(landmarks::table.filter(landmarks::id.eq_any(&source_ids)))
+            .execute(conn)?;
+        LandmarkVersion::recount_for_landmark(target_id, conn)?;
+        refresh_landmark_related_elements_stats(target_id, conn)?;
+        record_curation(
+            user_id,
+            target_id,
+            LandmarkCurationAction::Merge,
+            &details,
+            conn,
+        )
+    })?;
+
+    enqueue_search_documents_refresh(target_id, pool)?;
+    Landmark::find(target_id, pool)
+}
+
+/// Moves the selected elements of a landmark to a new sibling landmark.
+pub fn split_landmark(
+    user_id: Uuid,
+    landmark_id: Uuid,
+    payload: SplitLandmarkDto,
+    pool: &DbPool,
+) -> Result<Landmark, PpdcError> {
+    let title = payload.title.trim().to_string();
+    if title.is_empty() {
+        return Err(bad_request("A title is required for the new landmark"));
+    }
+    let mut element_ids = payload.element_ids;
+    element_ids.sort();
+    element_ids.dedup();
+    if element_ids.is_empty() {
+        return Err(bad_request("At least one element is required"));
+    }
+    let source = Landmark::find(landmark_id, pool)?;
+
+    let mut conn = pool.get()?;
+    let linked_count = element_landmarks::table
+        .filter(element_landmarks::landmark_id.eq(landmark_id))
+        .filter(element_landmarks::element_id.eq_any(&element_ids))
+        .count()
+        .get_result::<i64>(&mut conn)?;
+    if linked_count != element_ids.len() as i64 {
+        return Err(bad_request("Every element must be linked to the landmark"));
+    }
+    let (analysis_id, parent_id) = landmarks::table
+        .filter(landmarks::id.eq(landmark_id))
+        .select((landmarks::analysis_id, landmarks::parent_id))
+        .first::<(Uuid, Option<Uuid>)>(&mut conn)?;
+    let landmark_type = payload.landmark_type.unwrap_or(source.landmark_type);
+
+    let new_id = conn.transaction::<Uuid, diesel::result::Error, _>(|conn| {
+        let new_id: Uuid = diesel::insert_into(landmarks::table)
+            .values((
+                landmarks::analysis_id.eq(analysis_id),
+                landmarks::user_id.eq(user_id),
+                landmarks::parent_id.eq(parent_id),
+                landmarks::title.eq(&title),
+                landmarks::subtitle.eq(payload.subtitle.unwrap_or_default()),
+                landmarks::content.eq(""),
+                landmarks::landmark_type.eq(landmark_type.to_code()),
+                landmarks::maturing_state.eq(source.maturing_state.to_code()),
+            ))
+            .returning(landmarks::id)
+            .get_result(conn)?;
+        if let Some(parent_id) = parent_id {
+            diesel::insert_into(landmark_relations::table)
+                .values((
+                    landmark_relations::origin_landmark_id.eq(new_id),
+                    landmark_relations::target_landmark_id.eq(parent_id),
+                    landmark_relations::relation_type.eq("CHILD_OF"),
+                ))
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+        }
+        sql_query(
+            r#"
+            INSERT INTO landscape_landmarks (landscape_analysis_id, landmark_id, relation_type)
+            SELECT landscape_analysis_id, $1, relation_type
+            FROM landscape_landmarks
+            WHERE landmark_id = $2
+            ON CONFLICT (landscape_analysis_id, landmark_id, relation_type) DO NOTHING
+            "#,
+        )
+        .bind::<SqlUuid, _>(new_id)
+        .bind::<SqlUuid, _>(landmark_id)
+        .execute(conn)?;
+        diesel::update(
+            element_landmarks::table
+                .filter(element_landmarks::landmark_id.eq(landmark_id))
+                .filter(element_landmarks::element_id.eq_any(&element_ids)),
+        )
+        .set((
+            element_landmarks::landmark_id.eq(new_id),
+            element_landmarks::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(conn)?;
+
+        let analysis_ids = landmark_versions::table
+            .filter(landmark_versions::landmark_id.eq(landmark_id))
+            .select(landmark_versions::landscape_analysis_id)
+            .load::<Uuid>(conn)?;
+        for analysis_id in analysis_ids {
+            LandmarkVersion::ensure_for_analysis(analysis_id, new_id, None, conn)?;
+        }
+        LandmarkVersion::recount_for_landmark(landmark_id, conn)?;
+        LandmarkVersion::recount_for_landmark(new_id, conn)?;
+        refresh_landmark_related_elements_stats(landmark_id, conn)?;
+        refresh_landmark_related_elements_stats(new_id, conn)?;
+
+        let details = json!({
+            "split_from_landmark_id": landmark_id,
+            "split_into_landmark_id": new_id,
+            "element_ids": element_ids,
+        });
+        record_curation(
+            user_id,
+            landmark_id,
+            LandmarkCurationAction::Split,
+            &details,
+            conn,
+        )?;
+        record_curation(
+            user_id,
+            new_id,
+            LandmarkCurationAction::Split,
+            &details,
+            conn,
+        )?;
+        Ok(new_id)
+    })?;
+
+    Landmark::find(new_id, pool)
+}
+
+/// Renames or retypes a landmark.
+///
+/// The landmark row and its versions in the current landscape of each lens are updated; versions
+/// of earlier analyses keep the title and type they had. The previous title is kept as an alias
+/// so the pipeline maps it back onto this landmark.
+pub fn update_landmark(
+    user_id: Uuid,
+    landmark_id: Uuid,
+    payload: UpdateLandmarkDto,
+    pool: &DbPool,
+) -> Result<Landmark, PpdcError> {
+    let current = Landmark::find(landmark_id, pool)?;
+    let title = match payload.title.map(|title| title.trim().to_string()) {
+        Some(title) if title.is_empty() => return Err(bad_request("Title cannot be empty")),
+        Some(title) => title,
+        None => current.title.clone(),
+    };
+    let subtitle = payload.subtitle.unwrap_or_else(|| current.subtitle.clone());
+    let landmark_type = payload.landmark_type.unwrap_or(current.landmark_type);
+    let renamed = title != current.title;
+    let retyped = landmark_type != current.landmark_type;
+
+    let mut conn = pool.get()?;
+    conn.transaction::<(), diesel::result::Error, _>(|conn| {
+        diesel::update(landmarks::table.filter(landmarks::id.eq(landmark_id)))
+            .set((
+                landmarks::title.eq(&title),
+                landmarks::subtitle.eq(&subtitle),
+                landmarks::landmark_type.eq(landmark_type.to_code()),
+                landmarks::updated_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)?;
+        let head_analysis_ids = sql_query(
+            r#"
+            SELECT DISTINCT lenses.current_landscape_id AS id
+            FROM lenses
+            INNER JOIN landscape_landmarks ll
+                ON ll.landscape_analysis_id = lenses.current_landscape_id
+            WHERE lenses.user_id = $1
+              AND ll.landmark_id = $2
+            "#,
+        )
+        .bind::<SqlUuid, _>(user_id)
+        .bind::<SqlUuid, _>(landmark_id)
+        .load::<AnalysisIdRow>(conn)?
+        .into_iter()
+        .map(|row| row.id)
+        .collect::<Vec<_>>();
+        for analysis_id in &head_analysis_ids {
+            LandmarkVersion::ensure_for_analysis(*analysis_id, landmark_id, None, conn)?;
+        }
+        diesel::update(
+            landmark_versions::table
+                .filter(landmark_versions::landmark_id.eq(landmark_id))
+                .filter(landmark_versions::landscape_analysis_id.eq_any(&head_analysis_ids)),
+        )
+        .set((
+            landmark_versions::title.eq(&title),
+            landmark_versions::subtitle.eq(&subtitle),
+            landmark_versions::landmark_type.eq(landmark_type.to_code()),
+            landmark_versions::updated_at.eq(diesel::dsl::now),
+        ))
+        .execute(conn)?;
+        if renamed {
+            upsert_alias(user_id, landmark_id, &current.title, conn)?;
+            record_curation(
+                user_id,
+                landmark_id,
+                LandmarkCurationAction::Rename,
+                &json!({ "previous_title": current.title, "title": title }),
+                conn,
+            )?;
+        }
+        if retyped {
+            record_curation(
+                user_id,
+                landmark_id,
+                LandmarkCurationAction::Retype,
+                &json!({
+                    "previous_landmark_type": current.landmark_type,
+                    "landmark_type": landmark_type,
+                }),
+                conn,
+            )?;
+        }
+        Ok(())
+    })?;
+
+    if renamed || retyped {
+        enqueue_search_documents_refresh(landmark_id, pool)?;
+    }
+    Landmark::find(landmark_id, pool)
+}
+
+fn upsert_alias(
+    user_id: Uuid,
+    landmark_id: Uuid,
+    title: &str,
+    conn: &mut PgConnection,
+) -> Result<(), diesel::result::Error> {
+    sql_query(
+        r#"
+        INSERT INTO landmark_aliases (user_id, landmark_id, title, normalized_title)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, landmark_id, normalized_title) DO NOTHING
+        "#,
+    )
+    .bind::<SqlUuid, _>(user_id)
+    .bind::<SqlUuid, _>(landmark_id)
+    .bind::<Text, _>(title)
+    .bind::<Text, _>(normalize_alias_title(title))
+    .execute(conn)?;
+    Ok(())
+}
+
+fn record_curation(
+    user_id: Uuid,
+    landmark_id: Uuid,
+    action: LandmarkCurationAction,
+    details: &Value,
+    conn: &mut PgConnection,
+) -> Result<(), diesel::result::Error> {
+    sql_query(
+        r#"
+        INSERT INTO landmark_curations (user_id, landmark_id, action, details)
+        VALUES ($1, $2, $3, CAST($4 AS jsonb))
+        "#,
+    )
+    .bind::<SqlUuid, _>(user_id)
+    .bind::<SqlUuid, _>(landmark_id)
+    .bind::<Text, _>(action.to_db())
+    .bind::<Text, _>(details.to_string())
+    .execute(conn)?;
+    Ok(())
+}
+
+/// Search documents index the titles of referenced landmarks; they are refreshed by a job so
+/// curating a landmark seen by many traces stays quick.
+fn enqueue_search_documents_refresh(landmark_id: Uuid, pool: &DbPool) -> Result<(), PpdcError> {
+    let job_type = BackgroundJobType::RefreshLandmarkSearchDocuments;
+    BackgroundJob::enqueue(
+        job_type,
+        serde_json::to_value(RefreshLandmarkSearchDocumentsPayload { landmark_id })?,
+        Some(format!("{}:{}", job_type.to_db(), landmark_id)),
+        pool,
+    )?;
+    Ok(())
+}
+
+/// Reindexes the traces referencing the landmark.
+pub fn refresh_search_documents(landmark_id: Uuid, pool: &DbPool) -> Result<(), PpdcError> {
+    let mut conn = pool.get()?;
+    let trace_ids = references::table
+        .inner_join(trace_mirrors::table.on(trace_mirrors::id.eq(references::trace_mirror_id)))
+        .filter(references::landmark_id.eq(landmark_id))
+        .select(trace_mirrors::trace_id)
+        .distinct()
+        .load::<Uuid>(&mut conn)?;
+    drop(conn);
+    for trace_id in trace_ids {
+        TraceSearchDocument::refresh_for_trace(trace_id, pool)?;
+    }
+    Ok(())
+}
+
+#[derive(QueryableByName)]
+struct AnalysisIdRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+}
+
+fn bad_request(message: &str) -> PpdcError {
+    PpdcError::new(400, ErrorType::ApiError, message.to_string())
+}
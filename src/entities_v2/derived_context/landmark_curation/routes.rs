@@ -0,0 +1,71 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Json, Path},
+};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::landmark::{Landmark, LandmarkWithParentsAndElements};
+use crate::entities_v2::{error::PpdcError, session::Session};
+
+use super::model::{LandmarkCuration, MergeLandmarksDto, SplitLandmarkDto, UpdateLandmarkDto};
+use super::persist::{merge_landmarks, split_landmark, update_landmark};
+
+fn ensure_landmark_owner(landmark_id: Uuid, user_id: Uuid, pool: &DbPool) -> Result<(), PpdcError> {
+    if Landmark::find_user_id(landmark_id, pool)? != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn post_merge_landmarks_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<MergeLandmarksDto>,
+) -> Result<Json<LandmarkWithParentsAndElements>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_landmark_owner(payload.target_landmark_id, user_id, &pool)?;
+    for source_landmark_id in &payload.source_landmark_ids {
+        ensure_landmark_owner(*source_landmark_id, user_id, &pool)?;
+    }
+    let landmark = merge_landmarks(user_id, payload, &pool)?;
+    Ok(Json(Landmark::find_with_parents(landmark.id, &pool)?))
+}
+
+#[debug_handler]
+pub async fn post_split_landmark_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SplitLandmarkDto>,
+) -> Result<Json<LandmarkWithParentsAndElements>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_landmark_owner(id, user_id, &pool)?;
+    let landmark = split_landmark(user_id, id, payload, &pool)?;
+    Ok(Json(Landmark::find_with_parents(landmark.id, &pool)?))
+}
+
+#[debug_handler]
+pub async fn patch_landmark_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateLandmarkDto>,
+) -> Result<Json<LandmarkWithParentsAndElements>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_landmark_owner(id, user_id, &pool)?;
+    let landmark = update_landmark(user_id, id, payload, &pool)?;
+    Ok(Json(Landmark::find_with_parents(landmark.id, &pool)?))
+}
+
+#[debug_handler]
+pub async fn get_landmark_curations_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<LandmarkCuration>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_landmark_owner(id, user_id, &pool)?;
+    Ok(Json(LandmarkCuration::find_for_landmark(id, &pool)?))
+}
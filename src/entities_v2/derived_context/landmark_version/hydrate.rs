@@ -0,0 +1,27 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::PpdcError;
+use crate::schema::landmark_versions;
+
+use super::model::LandmarkVersion;
+
+impl LandmarkVersion {
+    pub fn find_for_analysis(
+        landscape_analysis_id: Uuid,
+        landmark_ids: &[Uuid],
+        pool: &DbPool,
+    ) -> Result<Vec<LandmarkVersion>, PpdcError> {
+        if landmark_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut conn = pool.get()?;
+        let versions = landmark_versions::table
+            .filter(landmark_versions::landscape_analysis_id.eq(landscape_analysis_id))
+            .filter(landmark_versions::landmark_id.eq_any(landmark_ids))
+            .select(LandmarkVersion::as_select())
+            .load::<LandmarkVersion>(&mut conn)?;
+        Ok(versions)
+    }
+}
@@ -0,0 +1,5 @@
+pub mod hydrate;
+pub mod model;
+pub mod persist;
+
+pub use model::{BackfillLandmarkVersionsPayload, LandmarkVersion, LandmarkVersionBackfillReport};
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities_v2::landmark::{Landmark, LandmarkType};
+use crate::entities_v2::shared::MaturingState;
+
+/// State of a landmark as seen by one landscape analysis. Landmarks are shared across lenses,
+/// so element counts live here, accumulated along the analysis parent chain of a lens.
+#[derive(Debug, Clone, Serialize, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::landmark_versions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct LandmarkVersion {
+    pub id: Uuid,
+    pub landmark_id: Uuid,
+    pub landscape_analysis_id: Uuid,
+    pub title: String,
+    pub subtitle: String,
+    pub content: String,
+    pub landmark_type: String,
+    pub maturing_state: String,
+    pub related_elements_count: i32,
+    pub last_related_element_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl LandmarkVersion {
+    /// Overlays the versioned fields on the shared landmark row.
+    pub fn apply_to(&self, landmark: Landmark) -> Landmark {
+        Landmark {
+            title: self.title.clone(),
+            subtitle: self.subtitle.clone(),
+            content: self.content.clone(),
+            landmark_type: LandmarkType::from_code(&self.landmark_type)
+                .unwrap_or(landmark.landmark_type),
+            maturing_state: MaturingState::from_code(&self.maturing_state)
+                .unwrap_or(landmark.maturing_state),
+            related_elements_count: self.related_elements_count,
+            last_related_element_at: self.last_related_element_at,
+            updated_at: self.updated_at,
+            ..landmark
+        }
+    }
+}
+
+/// Payload of a `BACKFILL_LANDMARK_VERSIONS` job: lenses are walked in id order after the cursor.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BackfillLandmarkVersionsPayload {
+    #[serde(default)]
+    pub after_lens_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct LandmarkVersionBackfillReport {
+    pub lenses: usize,
+    pub versions: usize,
+    pub last_lens_id: Option<Uuid>,
+}
+
+/// Elements of one analysis linked to one landmark.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisElementStats {
+    pub analysis_id: Uuid,
+    pub landmark_id: Uuid,
+    pub related_elements_count: i32,
+    pub last_related_element_at: Option<NaiveDateTime>,
+}
+
+/// Walks a lens chain from root to head once, giving every landmark linked to an analysis the
+/// element stats accumulated over that analysis and its ancestors.
+pub fn accumulate_chain_stats(
+    chain: &[Uuid],
+    element_stats: &[AnalysisElementStats],
+    links: &[(Uuid, Uuid)],
+) -> Vec<AnalysisElementStats> {
+    let mut stats_by_analysis: HashMap<Uuid, Vec<&AnalysisElementStats>> = HashMap::new();
+    for stats in element_stats {
+        stats_by_analysis
+            .entry(stats.analysis_id)
+            .or_default()
+            .push(stats);
+    }
+    let mut links_by_analysis: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (analysis_id, landmark_id) in links {
+        links_by_analysis
+            .entry(*analysis_id)
+            .or_default()
+            .push(*landmark_id);
+    }
+
+    let mut running: HashMap<Uuid, (i32, Option<NaiveDateTime>)> = HashMap::new();
+    let mut versions = Vec::new();
+    for analysis_id in chain {
+        for stats in stats_by_analysis.get(analysis_id).into_iter().flatten() {
+            let (count, last) = running.entry(stats.landmark_id).or_default();
+            *count += stats.related_elements_count;
+            *last = (*last).max(stats.last_related_element_at);
+        }
+        for landmark_id in links_by_analysis.get(analysis_id).into_iter().flatten() {
+            let (count, last) = running.get(landmark_id).copied().unwrap_or_default();
+            versions.push(AnalysisElementStats {
+                analysis_id: *analysis_id,
+                landmark_id: *landmark_id,
+                related_elements_count: count,
+                last_related_element_at: last,
+            });
+        }
+    }
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn at(day: u32) -> Option<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(2026, 10, day)?.and_hms_opt(12, 0, 0)
+    }
+
+    #[test]
+    fn chain_stats_accumulate_from_root_to_head() {
+        let (root, middle, head) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let (landmark, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let element_stats = vec![
+            AnalysisElementStats {
+                analysis_id: root,
+                landmark_id: landmark,
+                related_elements_count: 2,
+                last_related_element_at: at(3),
+            },
+            AnalysisElementStats {
+                analysis_id: head,
+                landmark_id: landmark,
+                related_elements_count: 1,
+                last_related_element_at: at(1),
+            },
+            AnalysisElementStats {
+                analysis_id: head,
+                landmark_id: other,
+                related_elements_count: 4,
+                last_related_element_at: None,
+            },
+        ];
+        let links = vec![
+            (root, landmark),
+            (middle, landmark),
+            (head, landmark),
+            (head, other),
+        ];
+
+        let versions = accumulate_chain_stats(&[root, middle, head], &element_stats, &links);
+
+        let summary = versions
+            .iter()
+            .map(|version| {
+                (
+                    version.analysis_id,
+                    version.landmark_id,
+                    version.related_elements_count,
+                    version.last_related_element_at,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (root, landmark, 2, at(3)),
+                (middle, landmark, 2, at(3)),
+                (head, landmark, 3, at(3)),
+                (head, other, 4, None),
+            ]
+        );
+    }
+}
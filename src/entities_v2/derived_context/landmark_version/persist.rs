@@ -0,0 +1,386 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Array, BigInt, Int4, Nullable, Timestamp, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::PpdcError;
+use crate::schema::elements;
+
+use super::model::{
+    accumulate_chain_stats, AnalysisElementStats, LandmarkVersion, LandmarkVersionBackfillReport,
+};
+
+#[derive(QueryableByName)]
+struct LensHeadRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    current_landscape_id: Uuid,
+}
+
+#[derive(QueryableByName)]
+struct ChainRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+    #[diesel(sql_type = Nullable<SqlUuid>)]
+    parent_id: Option<Uuid>,
+}
+
+#[derive(QueryableByName)]
+struct LinkRow {
+    #[diesel(sql_type = SqlUuid)]
+    landscape_analysis_id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    landmark_id: Uuid,
+}
+
+#[derive(QueryableByName)]
+struct ElementStatsRow {
+    #[diesel(sql_type = SqlUuid)]
+    analysis_id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    landmark_id: Uuid,
+    #[diesel(sql_type = Int4)]
+    related_elements_count: i32,
+    #[diesel(sql_type = Nullable<Timestamp>)]
+    last_related_element_at: Option<NaiveDateTime>,
+}
+
+/// Backfills the versions of up to `limit` lenses after `after_lens_id`, in id order.
+pub fn backfill_lens_batch(
+    after_lens_id: Option<Uuid>,
+    limit: i64,
+    pool: &DbPool,
+) -> Result<LandmarkVersionBackfillReport, PpdcError> {
+    let lenses = {
+        let mut conn = pool.get()?;
+        sql_query(
+            r#"
+            SELECT id, current_landscape_id
+            FROM lenses
+            WHERE current_landscape_id IS NOT NULL
+              AND ($1::uuid IS NULL OR id > $1)
+            ORDER BY id
+            LIMIT $2
+            "#,
+        )
+        .bind::<Nullable<SqlUuid>, _>(after_lens_id)
+        .bind::<BigInt, _>(limit)
+        .load::<LensHeadRow>(&mut conn)?
+    };
+    let mut report = LandmarkVersionBackfillReport {
+        lenses: lenses.len(),
+        ..Default::default()
+    };
+    for lens in &lenses {
+        let mut conn = pool.get()?;
+        report.versions += conn
+            .transaction(|conn| LandmarkVersion::backfill_chain(lens.current_landscape_id, conn))?;
+        report.last_lens_id = Some(lens.id);
+    }
+    Ok(report)
+}
+
+impl LandmarkVersion {
+    /// Writes the versions of every analysis from the chain root up to `head_analysis_id`, with
+    /// counts taken over each analysis lineage. The chain is read once and walked in memory.
+    pub fn backfill_chain(
+        head_analysis_id: Uuid,
+        conn: &mut PgConnection,
+    ) -> Result<usize, diesel::result::Error> {
+        let parents = sql_query(
+            r#"
+            WITH RECURSIVE chain AS (
+                SELECT id, parent_id
+                FROM landscape_analyses
+                WHERE id = $1
+                UNION
+                SELECT parent.id, parent.parent_id
+                FROM landscape_analyses parent
+                INNER JOIN chain ON parent.id = chain.parent_id
+            )
+            SELECT id, parent_id FROM chain
+            "#,
+        )
+        .bind::<SqlUuid, _>(head_analysis_id)
+        .load::<ChainRow>(conn)?
+        .into_iter()
+        .map(|row| (row.id, row.parent_id))
+        .collect::<HashMap<_, _>>();
+        let mut chain = Vec::with_capacity(parents.len());
+        let mut visited = HashSet::new();
+        let mut next = Some(head_analysis_id);
+        while let Some(analysis_id) = next.filter(|id| parents.contains_key(id)) {
+            if !visited.insert(analysis_id) {
+                break;
+            }
+            chain.push(analysis_id);
+            next = parents[&analysis_id];
+        }
+        chain.reverse();
+
+        let links = sql_query(
+            r#"
+            SELECT DISTINCT landscape_analysis_id, landmark_id
+            FROM landscape_landmarks
+            WHERE landscape_analysis_id = ANY($1)
+            "#,
+        )
+        .bind::<Array<SqlUuid>, _>(&chain)
+        .load::<LinkRow>(conn)?
+        .into_iter()
+        .map(|row| (row.landscape_analysis_id, row.landmark_id))
+        .collect::<Vec<_>>();
+        if links.is_empty() {
+            return Ok(0);
+        }
+
+        let element_stats = sql_query(
+            r#"
+            SELECT
+                e.analysis_id,
+                el.landmark_id,
+                COUNT(DISTINCT e.id)::INT AS related_elements_count,
+                MAX(e.interaction_date) AS last_related_element_at
+            FROM elements e
+            INNER JOIN element_landmarks el ON el.element_id = e.id
+            WHERE e.analysis_id = ANY($1)
+            GROUP BY e.analysis_id, el.landmark_id
+            "#,
+        )
+        .bind::<Array<SqlUuid>, _>(&chain)
+        .load::<ElementStatsRow>(conn)?
+        .into_iter()
+        .map(|row| AnalysisElementStats {
+            analysis_id: row.analysis_id,
+            landmark_id: row.landmark_id,
+            related_elements_count: row.related_elements_count,
+            last_related_element_at: row.last_related_element_at,
+        })
+        .collect::<Vec<_>>();
+
+        let versions = accumulate_chain_stats(&chain, &element_stats, &links);
+        let mut analysis_ids = Vec::with_capacity(versions.len());
+        let mut landmark_ids = Vec::with_capacity(versions.len());
+        let mut counts = Vec::with_capacity(versions.len());
+        let mut last_dates = Vec::with_capacity(versions.len());
+        for version in versions {
+            analysis_ids.push(version.analysis_id);
+            landmark_ids.push(version.landmark_id);
+            counts.push(version.related_elements_count);
+            last_dates.push(version.last_related_element_at);
+        }
+
+        sql_query(
+            r#"
+            INSERT INTO landmark_versions (
+                landmark_id,
+                landscape_analysis_id,
+                title,
+                subtitle,
+                content,
+                landmark_type,
+                maturing_state,
+                related_elements_count,
+                last_related_element_at
+            )
+            SELECT
+                l.id,
+                v.analysis_id,
+                l.title,
+                l.subtitle,
+                l.content,
+                l.landmark_type,
+                l.maturing_state,
+                v.related_elements_count,
+                v.last_related_element_at
+            FROM UNNEST($1, $2, $3, $4)
+                AS v(analysis_id, landmark_id, related_elements_count, last_related_element_at)
+            INNER JOIN landmarks l ON l.id = v.landmark_id
+            ON CONFLICT (landscape_analysis_id, landmark_id) DO UPDATE
+            SET related_elements_count = EXCLUDED.related_elements_count,
+                last_related_element_at = EXCLUDED.last_related_element_at,
+                updated_at = NOW()
+            "#,
+        )
+        .bind::<Array<SqlUuid>, _>(&analysis_ids)
+        .bind::<Array<SqlUuid>, _>(&landmark_ids)
+        .bind::<Array<Int4>, _>(&counts)
+        .bind::<Array<Nullable<Timestamp>>, _>(&last_dates)
+        .execute(conn)
+    }
+
+    /// Creates the version of a landmark for an analysis if it does not exist yet. Fields are
+    /// taken from the landmark row; counts carry over from the version in `base_analysis_id`,
+    /// or from the analysis parent when no base is given.
+    pub fn ensure_for_analysis(
+        landscape_analysis_id: Uuid,
+        landmark_id: Uuid,
+        base_analysis_id: Option<Uuid>,
+        conn: &mut PgConnection,
+    ) -> Result<(), diesel::result::Error> {
+        sql_query(
+            r#"
+            INSERT INTO landmark_versions (
+                landmark_id,
+                landscape_analysis_id,
+                title,
+                subtitle,
+                content,
+                landmark_type,
+                maturing_state,
+                related_elements_count,
+                last_related_element_at
+            )
+            SELECT
+                l.id,
+                a.id,
+                l.title,
+                l.subtitle,
+                l.content,
+                l.landmark_type,
+                l.maturing_state,
+                COALESCE(base.related_elements_count, 0),
+                base.last_related_element_at
+            FROM landmarks l
+            CROSS JOIN landscape_analyses a
+            LEFT JOIN landmark_versions base
+                ON base.landmark_id = l.id
+                AND base.landscape_analysis_id = COALESCE($3, a.parent_id)
+            WHERE l.id = $1
+              AND a.id = $2
+            ON CONFLICT (landscape_analysis_id, landmark_id) DO NOTHING
+            "#,
+        )
+        .bind::<SqlUuid, _>(landmark_id)
+        .bind::<SqlUuid, _>(landscape_analysis_id)
+        .bind::<Nullable<SqlUuid>, _>(base_analysis_id)
+        .execute(conn)?;
+        Ok(())
+    }
+
+    /// Carries every landmark version linked to `source` over to `target`.
+    pub fn copy_from_analysis(
+        source_landscape_analysis_id: Uuid,
+        target_landscape_analysis_id: Uuid,
+        conn: &mut PgConnection,
+    ) -> Result<usize, diesel::result::Error> {
+        sql_query(
+            r#"
+            INSERT INTO landmark_versions (
+                landmark_id,
+                landscape_analysis_id,
+                title,
+                subtitle,
+                content,
+                landmark_type,
+                maturing_state,
+                related_elements_count,
+                last_related_element_at
+            )
+            SELECT
+                l.id,
+                $2,
+                l.title,
+                l.subtitle,
+                l.content,
+                l.landmark_type,
+                l.maturing_state,
+                COALESCE(base.related_elements_count, 0),
+                base.last_related_element_at
+            FROM (
+                SELECT DISTINCT landmark_id
+                FROM landscape_landmarks
+                WHERE landscape_analysis_id = $1
+            ) source_links
+            INNER JOIN landmarks l ON l.id = source_links.landmark_id
+            LEFT JOIN landmark_versions base
+                ON base.landmark_id = l.id
+                AND base.landscape_analysis_id = $1
+            ON CONFLICT (landscape_analysis_id, landmark_id) DO NOTHING
+            "#,
+        )
+        .bind::<SqlUuid, _>(source_landscape_analysis_id)
+        .bind::<SqlUuid, _>(target_landscape_analysis_id)
+        .execute(conn)
+    }
+
+    /// Counts a newly linked element on the landmark version of the element's analysis.
+    pub fn record_element_link(
+        element_id: Uuid,
+        landmark_id: Uuid,
+        conn: &mut PgConnection,
+    ) -> Result<(), diesel::result::Error> {
+        let analysis_id = elements::table
+            .filter(elements::id.eq(element_id))
+            .select(elements::analysis_id)
+            .first::<Uuid>(conn)?;
+        LandmarkVersion::ensure_for_analysis(analysis_id, landmark_id, None, conn)?;
+
+        sql_query(
+            r#"
+            UPDATE landmark_versions v
+            SET related_elements_count = v.related_elements_count + 1,
+                last_related_element_at = GREATEST(v.last_related_element_at, e.interaction_date),
+                updated_at = NOW()
+            FROM elements e
+            WHERE e.id = $1
+              AND v.landmark_id = $2
+              AND v.landscape_analysis_id = e.analysis_id
+            "#,
+        )
+        .bind::<SqlUuid, _>(element_id)
+        .bind::<SqlUuid, _>(landmark_id)
+        .execute(conn)?;
+        Ok(())
+    }
+
+    /// Recomputes the counts of every version of a landmark from its element links, after
+    /// elements were moved between landmarks.
+    pub fn recount_for_landmark(
+        landmark_id: Uuid,
+        conn: &mut PgConnection,
+    ) -> Result<(), diesel::result::Error> {
+        sql_query(
+            r#"
+            WITH RECURSIVE lineage AS (
+                SELECT v.id AS version_id, v.landscape_analysis_id AS analysis_id
+                FROM landmark_versions v
+                WHERE v.landmark_id = $1
+                UNION
+                SELECT lineage.version_id, a.parent_id
+                FROM lineage
+                INNER JOIN landscape_analyses a ON a.id = lineage.analysis_id
+                WHERE a.parent_id IS NOT NULL
+            ),
+            counts AS (
+                SELECT
+                    lineage.version_id,
+                    COUNT(DISTINCT e.id)::INT AS related_elements_count,
+                    MAX(e.interaction_date) AS last_related_element_at
+                FROM lineage
+                INNER JOIN elements e ON e.analysis_id = lineage.analysis_id
+                INNER JOIN element_landmarks el
+                    ON el.element_id = e.id
+                    AND el.landmark_id = $1
+                GROUP BY lineage.version_id
+            )
+            UPDATE landmark_versions v
+            SET related_elements_count = COALESCE(counts.related_elements_count, 0),
+                last_related_element_at = counts.last_related_element_at,
+                updated_at = NOW()
+            FROM landmark_versions current
+            LEFT JOIN counts ON counts.version_id = current.id
+            WHERE v.id = current.id
+              AND current.landmark_id = $1
+            "#,
+        )
+        .bind::<SqlUuid, _>(landmark_id)
+        .execute(conn)?;
+        Ok(())
+    }
+}
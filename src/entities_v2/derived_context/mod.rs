@@ -1,5 +1,9 @@
 pub mod analysis_summary;
+pub mod bio_profile;
 pub mod element;
+pub mod embedding;
 pub mod landmark;
+pub mod landmark_curation;
+pub mod landmark_version;
 pub mod reference;
 pub mod trace_mirror;
@@ -1,7 +1,8 @@
 use chrono::NaiveDateTime;
 use diesel::dsl::sql;
 use diesel::prelude::*;
-use diesel::sql_types::Text;
+use diesel::sql_query;
+use diesel::sql_types::{Nullable, Text, Timestamp, Uuid as SqlUuid};
 use uuid::Uuid;
 
 use crate::db::DbPool;
@@ -63,7 +64,94 @@ fn tuple_to_trace_mirror(row: TraceMirrorTuple) -> TraceMirror {
     }
 }
 
+#[derive(QueryableByName)]
+struct LineageTraceMirrorRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+    #[diesel(sql_type = Text)]
+    title: String,
+    #[diesel(sql_type = Text)]
+    subtitle: String,
+    #[diesel(sql_type = Text)]
+    content: String,
+    #[diesel(sql_type = Text)]
+    trace_mirror_type: String,
+    #[diesel(sql_type = Text)]
+    tags: String,
+    #[diesel(sql_type = SqlUuid)]
+    trace_id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    landscape_analysis_id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    user_id: Uuid,
+    #[diesel(sql_type = Nullable<SqlUuid>)]
+    primary_landmark_id: Option<Uuid>,
+    #[diesel(sql_type = Timestamp)]
+    created_at: NaiveDateTime,
+    #[diesel(sql_type = Timestamp)]
+    updated_at: NaiveDateTime,
+}
+
 impl TraceMirror {
+    /// Mirrors of an analysis and all its ancestors in one query, the analysis's own first,
+    /// then each parent going back, oldest mirror first within an analysis.
+    pub fn find_by_analysis_lineage(
+        landscape_analysis_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<Vec<TraceMirror>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = sql_query(
+            r#"
+            WITH RECURSIVE lineage AS (
+                SELECT la.id, la.parent_id, 0 AS depth
+                FROM landscape_analyses la
+                WHERE la.id = $1
+                UNION ALL
+                SELECT parent.id, parent.parent_id, lineage.depth + 1
+                FROM landscape_analyses parent
+                INNER JOIN lineage ON parent.id = lineage.parent_id
+            )
+            SELECT
+                tm.id,
+                tm.title,
+                tm.subtitle,
+                tm.content,
+                tm.trace_mirror_type,
+                tm.tags::text AS tags,
+                tm.trace_id,
+                tm.landscape_analysis_id,
+                tm.user_id,
+                tm.primary_landmark_id,
+                tm.created_at,
+                tm.updated_at
+            FROM trace_mirrors tm
+            INNER JOIN lineage ON lineage.id = tm.landscape_analysis_id
+            ORDER BY lineage.depth ASC, tm.created_at ASC
+            "#,
+        )
+        .bind::<SqlUuid, _>(landscape_analysis_id)
+        .load::<LineageTraceMirrorRow>(&mut conn)?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                tuple_to_trace_mirror((
+                    row.id,
+                    row.title,
+                    row.subtitle,
+                    row.content,
+                    row.trace_mirror_type,
+                    row.tags,
+                    row.trace_id,
+                    row.landscape_analysis_id,
+                    row.user_id,
+                    row.primary_landmark_id,
+                    row.created_at,
+                    row.updated_at,
+                ))
+            })
+            .collect())
+    }
+
     pub fn find_full_trace_mirror(id: Uuid, pool: &DbPool) -> Result<TraceMirror, PpdcError> {
         let mut conn = pool.get()?;
         let row = trace_mirrors::table
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::entities_v2::journal_export::model::slugify;
+
+use super::collect::ExportedRows;
+
+fn text<'a>(row: &'a Value, key: &str) -> &'a str {
+    row.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+fn short_id(row: &Value) -> &str {
+    let id = text(row, "id");
+    id.get(..8).unwrap_or(id)
+}
+
+fn named_stem(prefix: &str, title: &str, row: &Value) -> String {
+    let title = slugify(title);
+    [prefix, title.as_str(), short_id(row)]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Folder of each journal's markdown traces, keyed by journal id.
+pub fn journal_folders(journals: &[Value]) -> HashMap<String, String> {
+    journals
+        .iter()
+        .map(|journal| {
+            (
+                text(journal, "id").to_string(),
+                format!(
+                    "journals/{}",
+                    named_stem("", text(journal, "title"), journal)
+                ),
+            )
+        })
+        .collect()
+}
+
+pub fn trace_markdown_path(trace: &Value, folders: &HashMap<String, String>) -> String {
+    let folder = folders
+        .get(text(trace, "journal_id"))
+        .map(String::as_str)
+        .unwrap_or("journals/without-journal");
+    let date = text(trace, "interaction_date");
+    format!(
+        "{}/{}.md",
+        folder,
+        named_stem(date.get(..10).unwrap_or(date), text(trace, "title"), trace)
+    )
+}
+
+/// A trace as markdown with front matter, readable by the journal importer.
+pub fn trace_markdown(trace: &Value) -> String {
+    let date = text(trace, "interaction_date");
+    let mut out = String::from("---\n");
+    out.push_str(format!("id: {}\n", text(trace, "id")).as_str());
+    out.push_str(format!("title: \"{}\"\n", text(trace, "title").replace('\n', " ")).as_str());
+    out.push_str(format!("date: {}\n", date.get(..19).unwrap_or(date)).as_str());
+    out.push_str(format!("status: {}\n", text(trace, "status")).as_str());
+    out.push_str(format!("trace_type: {}\n", text(trace, "trace_type")).as_str());
+    if trace.get("is_encrypted").and_then(Value::as_bool) == Some(true) {
+        out.push_str("encrypted: true\n");
+    }
+    out.push_str("---\n\n");
+    out.push_str(text(trace, "content").trim_end());
+    out.push('\n');
+    out
+}
+
+pub fn readme(
+    tables: &[ExportedRows],
+    trace_count: usize,
+    asset_count: usize,
+    skipped_asset_count: usize,
+) -> String {
+    let mut out = String::from("# Your data\n\n");
+    out.push_str(
+        "This archive holds everything stored about your account. `manifest.json` lists every \
+         file.\n\n",
+    );
+    out.push_str("- `data/*.json`: one file per kind of record, as stored\n");
+    out.push_str(
+        format!(
+            "- `journals/`: your {} traces, drafts included, as markdown\n",
+            trace_count
+        )
+        .as_str(),
+    );
+    out.push_str(format!("- `assets/`: the {} files you uploaded\n", asset_count).as_str());
+    if skipped_asset_count > 0 {
+        out.push_str(
+            format!(
+                "- {} more files did not fit in the archive; `manifest.json` lists them\n",
+                skipped_asset_count
+            )
+            .as_str(),
+        );
+    }
+    out.push('\n');
+    out.push_str(
+        "Traces you encrypted on your device are exported as stored and can only be read with \
+         your key.\n\n",
+    );
+    out.push_str("| File | Records |\n|---|---|\n");
+    for table in tables {
+        out.push_str(format!("| `{}` | {} |\n", table.file_path(), table.rows.len()).as_str());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn traces_are_filed_under_their_journal() {
+        let journals = vec![json!({"id": "1234567890ab", "title": "Carnet de bord"})];
+        let folders = journal_folders(&journals);
+        let trace = json!({
+            "id": "abcdef0123",
+            "journal_id": "1234567890ab",
+            "title": "Matin",
+            "interaction_date": "2026-03-05T08:00:00.123",
+            "status": "DRAFT",
+            "trace_type": "USER_TRACE",
+            "is_encrypted": false,
+            "content": "Bonjour\n\n"
+        });
+        assert_eq!(
+            trace_markdown_path(&trace, &folders),
+            "journals/carnet-de-bord-12345678/2026-03-05-matin-abcdef01.md"
+        );
+        assert_eq!(
+            trace_markdown(&trace),
+            "---\nid: abcdef0123\ntitle: \"Matin\"\ndate: 2026-03-05T08:00:00\nstatus: DRAFT\ntrace_type: USER_TRACE\n---\n\nBonjour\n"
+        );
+    }
+}
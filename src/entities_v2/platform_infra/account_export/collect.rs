@@ -0,0 +1,601 @@
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Text, Uuid as SqlUuid};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{data_key, error::PpdcError};
+
+/// A table copied into account exports and the SQL selecting the user's rows, `$1` being
+/// the user id.
+///
+/// Secrets (password and token hashes, push tokens) are dropped through `redacted`. Tables
+/// left out entirely are listed in the tests' `EXCLUDED_TABLES`.
+struct ExportedTable {
+    name: &'static str,
+    from_sql: &'static str,
+    filter_sql: &'static str,
+    order_sql: &'static str,
+    redacted: &'static [&'static str],
+}
+
+const EXPORTED_TABLES: &[ExportedTable] = &[
+    ExportedTable {
+        name: "profile",
+        from_sql: "users t",
+        filter_sql: "t.id = $1",
+        order_sql: "t.created_at",
+        redacted: &["password"],
+    },
+    ExportedTable {
+        name: "user_roles",
+        from_sql: "user_roles t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "devices",
+        from_sql: "devices t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &["push_token"],
+    },
+    ExportedTable {
+        name: "journals",
+        from_sql: "journals t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "traces",
+        from_sql: "traces t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.interaction_date, t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "trace_attachments",
+        from_sql: "trace_attachments t JOIN traces tr ON tr.id = t.trace_id",
+        filter_sql: "tr.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "journal_sharing_policies",
+        from_sql: "journal_sharing_policies t",
+        filter_sql: "t.owner_user_id = $1 OR t.grantee_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "journal_share_links",
+        from_sql: "journal_share_links t",
+        filter_sql: "t.owner_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &["token_hash"],
+    },
+    ExportedTable {
+        name: "journal_import_batches",
+        from_sql: "journal_import_batches t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "journal_import_batch_traces",
+        from_sql: "journal_import_batch_traces t JOIN journal_import_batches b ON b.id = t.batch_id",
+        filter_sql: "b.user_id = $1",
+        order_sql: "t.batch_id, t.block_index",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "documents",
+        from_sql: "documents t",
+        filter_sql: "t.owner_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "albums",
+        from_sql: "albums t",
+        filter_sql: "t.owner_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "album_items",
+        from_sql: "album_items t JOIN albums a ON a.id = t.album_id",
+        filter_sql: "a.owner_user_id = $1",
+        order_sql: "t.album_id, t.ordering_index",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "posts",
+        from_sql: "posts t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "post_relations",
+        from_sql: "post_relations t JOIN posts p ON p.id = t.origin_post_id",
+        filter_sql: "p.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "post_grants",
+        from_sql: "post_grants t",
+        filter_sql: "t.owner_user_id = $1 OR t.grantee_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "user_post_states",
+        from_sql: "user_post_states t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "relationships",
+        from_sql: "relationships t",
+        filter_sql: "t.requester_user_id = $1 OR t.target_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "messages",
+        from_sql: "messages t",
+        filter_sql: "t.sender_user_id = $1 OR t.recipient_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "content_reports",
+        from_sql: "content_reports t",
+        filter_sql: "t.reporter_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "usage_events",
+        from_sql: "usage_events t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.occurred_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "lenses",
+        from_sql: "lenses t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "lens_targets",
+        from_sql: "lens_targets t JOIN lenses le ON le.id = t.lens_id",
+        filter_sql: "le.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "lens_heads",
+        from_sql: "lens_heads t JOIN lenses le ON le.id = t.lens_id",
+        filter_sql: "le.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "lens_analysis_scopes",
+        from_sql: "lens_analysis_scopes t JOIN lenses le ON le.id = t.lens_id",
+        filter_sql: "le.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "landscape_analyses",
+        from_sql: "landscape_analyses t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "analysis_configs",
+        from_sql: "analysis_configs t JOIN landscape_analyses la ON la.id = t.analysis_id",
+        filter_sql: "la.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "landscape_analysis_inputs",
+        from_sql: "landscape_analysis_inputs t JOIN landscape_analyses la ON la.id = t.landscape_analysis_id",
+        filter_sql: "la.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "analysis_events",
+        from_sql: "analysis_events t JOIN landscape_analyses la ON la.id = t.analysis_id",
+        filter_sql: "la.user_id = $1",
+        order_sql: "t.analysis_id, t.seq",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "analysis_summaries",
+        from_sql: "analysis_summaries t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "trace_mirrors",
+        from_sql: "trace_mirrors t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "landmarks",
+        from_sql: "landmarks t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "landmark_versions",
+        from_sql: "landmark_versions t JOIN landmarks l ON l.id = t.landmark_id",
+        filter_sql: "l.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "landmark_aliases",
+        from_sql: "landmark_aliases t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "landmark_curations",
+        from_sql: "landmark_curations t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "landmark_relations",
+        from_sql: "landmark_relations t JOIN landmarks l ON l.id = t.origin_landmark_id",
+        filter_sql: "l.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "landscape_landmarks",
+        from_sql: "landscape_landmarks t JOIN landscape_analyses la ON la.id = t.landscape_analysis_id",
+        filter_sql: "la.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "elements",
+        from_sql: "elements t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "element_relations",
+        from_sql: "element_relations t JOIN elements e ON e.id = t.origin_element_id",
+        filter_sql: "e.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "element_landmarks",
+        from_sql: "element_landmarks t JOIN elements e ON e.id = t.element_id",
+        filter_sql: "e.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "references",
+        from_sql: "\"references\" t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "bio_profiles",
+        from_sql: "bio_profiles t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "llm_calls",
+        from_sql: "llm_calls t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "analysis_unlock_sessions",
+        from_sql: "analysis_unlock_sessions t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "analysis_unlock_audit_events",
+        from_sql: "analysis_unlock_audit_events t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "outbound_emails",
+        from_sql: "outbound_emails t",
+        filter_sql: "t.recipient_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "notification_digests",
+        from_sql: "notification_digests t",
+        filter_sql: "t.recipient_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "assets",
+        from_sql: "assets t",
+        filter_sql: "t.owner_user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "account_exports",
+        from_sql: "account_exports t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+    ExportedTable {
+        name: "account_export_audit_events",
+        from_sql: "account_export_audit_events t",
+        filter_sql: "t.user_id = $1",
+        order_sql: "t.created_at",
+        redacted: &[],
+    },
+];
+
+#[derive(QueryableByName)]
+struct JsonRow {
+    #[diesel(sql_type = Text)]
+    row: String,
+}
+
+/// The user's rows of one table, as JSON objects with at-rest sealed values opened.
+#[derive(Debug, Clone)]
+pub struct ExportedRows {
+    pub name: &'static str,
+    pub rows: Vec<Value>,
+}
+
+impl ExportedRows {
+    pub fn file_path(&self) -> String {
+        format!("data/{}.json", self.name)
+    }
+}
+
+fn load_table(
+    table: &ExportedTable,
+    user_id: Uuid,
+    pool: &DbPool,
+) -> Result<ExportedRows, PpdcError> {
+    let mut conn = pool.get()?;
+    let query = format!(
+        "SELECT row_to_json(t)::text AS row FROM {from} WHERE {filter} ORDER BY {order}",
+        from = table.from_sql,
+        filter = table.filter_sql,
+        order = table.order_sql,
+    );
+    let rows = sql_query(query)
+        .bind::<SqlUuid, _>(user_id)
+        .load::<JsonRow>(&mut conn)?;
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            let mut value = serde_json::from_str::<Value>(&row.row)?;
+            if let Value::Object(fields) = &mut value {
+                for column in table.redacted {
+                    fields.remove(*column);
+                }
+            }
+            open_sealed_values(&mut value)?;
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>, PpdcError>>()?;
+    Ok(ExportedRows {
+        name: table.name,
+        rows,
+    })
+}
+
+/// Opens every at-rest sealed string in `value`; client-side encrypted content stays as is.
+pub fn open_sealed_values(value: &mut Value) -> Result<(), PpdcError> {
+    open_sealed_values_with(value, &data_key::open)
+}
+
+fn open_sealed_values_with(
+    value: &mut Value,
+    open: &impl Fn(String) -> Result<String, PpdcError>,
+) -> Result<(), PpdcError> {
+    match value {
+        Value::String(text) if data_key::envelope::is_sealed(text) => {
+            *text = open(std::mem::take(text))?;
+        }
+        Value::Array(items) => {
+            for item in items {
+                open_sealed_values_with(item, open)?;
+            }
+        }
+        Value::Object(fields) => {
+            for field in fields.values_mut() {
+                open_sealed_values_with(field, open)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Every exported table of the user, in a stable order.
+pub fn collect_account_data(user_id: Uuid, pool: &DbPool) -> Result<Vec<ExportedRows>, PpdcError> {
+    EXPORTED_TABLES
+        .iter()
+        .map(|table| load_table(table, user_id, pool))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tables holding user data that exports leave out: credentials and key material, and
+    /// search indexes and embeddings that only restate exported content.
+    const EXCLUDED_TABLES: &[&str] = &[
+        "sessions",
+        "user_secure_actions",
+        "data_keys",
+        "trace_search_documents",
+        "trace_search_chunks",
+    ];
+
+    #[test]
+    fn table_names_are_unique() {
+        let mut names = EXPORTED_TABLES
+            .iter()
+            .map(|table| table.name)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), EXPORTED_TABLES.len());
+    }
+
+    /// Tables of `schema.rs` with their columns, and the tables each one references.
+    fn schema_tables() -> Vec<(String, Vec<String>, Vec<String>)> {
+        let schema = include_str!("../../../schema.rs");
+        let mut tables: Vec<(String, Vec<String>, Vec<String>)> = Vec::new();
+        let mut in_table = false;
+        for line in schema.lines() {
+            if let Some(joinable) = line.strip_prefix("diesel::joinable!(") {
+                let (child, parent) = joinable.split_once(" -> ").unwrap();
+                let parent = parent.split_whitespace().next().unwrap();
+                if let Some(table) = tables.iter_mut().find(|table| table.0 == child) {
+                    table.2.push(parent.to_string());
+                }
+            } else if line.starts_with("    ") && line.ends_with(") {") && !in_table {
+                let name = line.split_whitespace().next().unwrap();
+                tables.push((name.to_string(), Vec::new(), Vec::new()));
+                in_table = true;
+            } else if line == "    }" {
+                in_table = false;
+            } else if in_table {
+                if let Some((column, _)) = line.trim().split_once(" -> ") {
+                    tables.last_mut().unwrap().1.push(column.to_string());
+                }
+            }
+        }
+        tables
+    }
+
+    fn exported_source(table: &ExportedTable) -> &str {
+        table
+            .from_sql
+            .split_whitespace()
+            .next()
+            .unwrap()
+            .trim_matches('"')
+    }
+
+    #[test]
+    fn tables_owned_by_users_are_exported_or_excluded() {
+        let exported = EXPORTED_TABLES
+            .iter()
+            .map(exported_source)
+            .collect::<Vec<_>>();
+        let tables = schema_tables();
+        let mut missing = Vec::new();
+        for (name, columns, parents) in &tables {
+            if exported.contains(&name.as_str()) || EXCLUDED_TABLES.contains(&name.as_str()) {
+                continue;
+            }
+            // A user column, a foreign key to an exported table, or an `<entity>_id` column
+            // such as `origin_element_id` pointing at one.
+            let owned = columns
+                .iter()
+                .any(|column| column == "user_id" || column.ends_with("_user_id"))
+                || parents
+                    .iter()
+                    .any(|parent| exported.contains(&parent.as_str()))
+                || columns.iter().any(|column| {
+                    exported.iter().any(|table| {
+                        table.strip_suffix('s').is_some_and(|entity| {
+                            column == &format!("{}_id", entity)
+                                || column.ends_with(&format!("_{}_id", entity))
+                        })
+                    })
+                });
+            if owned {
+                missing.push(name.as_str());
+            }
+        }
+        assert!(
+            missing.is_empty(),
+            "export or exclude these tables: {:?}",
+            missing
+        );
+        for name in EXCLUDED_TABLES {
+            assert!(tables.iter().any(|table| table.0 == *name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn sealed_values_are_opened_in_nested_rows() {
+        let key_id = Uuid::new_v4();
+        let key = zeroize::Zeroizing::new([7u8; 32]);
+        let sealed = data_key::envelope::seal_with(key_id, &key, "Trace du soir").unwrap();
+        let mut value = serde_json::json!({
+            "content": sealed,
+            "mirror": {"lines": [sealed.clone(), "plain"]},
+            "count": 2,
+        });
+
+        open_sealed_values_with(&mut value, &|text| {
+            data_key::envelope::open_with(&text, &key)
+        })
+        .expect("sealed values open");
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "content": "Trace du soir",
+                "mirror": {"lines": ["Trace du soir", "plain"]},
+                "count": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn plaintext_values_are_left_untouched() {
+        let mut value = serde_json::json!({"content": "hello", "tags": ["a"], "count": 2});
+        let expected = value.clone();
+        open_sealed_values(&mut value).expect("plaintext passes through");
+        assert_eq!(value, expected);
+    }
+}
@@ -0,0 +1,15 @@
+pub mod archive;
+pub mod collect;
+pub mod model;
+pub mod persist;
+pub mod routes;
+pub mod service;
+
+pub use model::{
+    AccountExport, AccountExportAuditEvent, AccountExportAuditEventType, AccountExportDetail,
+    AccountExportStatus, ExportAccountPayload, ExportAccountResult,
+};
+pub use routes::{
+    get_account_export_audit_route, get_account_export_route, get_account_exports_route,
+    post_account_export_download_link_route, post_account_export_route,
+};
@@ -0,0 +1,290 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AccountExportStatus {
+    Pending,
+    Running,
+    Ready,
+    Failed,
+    /// A ready export whose download link has lapsed; never stored.
+    Expired,
+}
+
+impl AccountExportStatus {
+    /// Whether a job run still has to build the archive; built exports are never rebuilt.
+    pub fn needs_build(self) -> bool {
+        matches!(
+            self,
+            AccountExportStatus::Pending
+                | AccountExportStatus::Running
+                | AccountExportStatus::Failed
+        )
+    }
+
+    pub fn to_db(self) -> &'static str {
+        match self {
+            AccountExportStatus::Pending => "PENDING",
+            AccountExportStatus::Running => "RUNNING",
+            AccountExportStatus::Ready | AccountExportStatus::Expired => "READY",
+            AccountExportStatus::Failed => "FAILED",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "RUNNING" => AccountExportStatus::Running,
+            "READY" => AccountExportStatus::Ready,
+            "FAILED" => AccountExportStatus::Failed,
+            _ => AccountExportStatus::Pending,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AccountExportAuditEventType {
+    Requested,
+    Started,
+    Completed,
+    Failed,
+    Emailed,
+    LinkIssued,
+}
+
+impl AccountExportAuditEventType {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            AccountExportAuditEventType::Requested => "REQUESTED",
+            AccountExportAuditEventType::Started => "STARTED",
+            AccountExportAuditEventType::Completed => "COMPLETED",
+            AccountExportAuditEventType::Failed => "FAILED",
+            AccountExportAuditEventType::Emailed => "EMAILED",
+            AccountExportAuditEventType::LinkIssued => "LINK_ISSUED",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "STARTED" => AccountExportAuditEventType::Started,
+            "COMPLETED" => AccountExportAuditEventType::Completed,
+            "FAILED" => AccountExportAuditEventType::Failed,
+            "EMAILED" => AccountExportAuditEventType::Emailed,
+            "LINK_ISSUED" => AccountExportAuditEventType::LinkIssued,
+            _ => AccountExportAuditEventType::Requested,
+        }
+    }
+}
+
+/// A "download all my data" request and the archive it produced.
+#[derive(Serialize, Debug, Clone)]
+pub struct AccountExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: AccountExportStatus,
+    pub job_id: Option<Uuid>,
+    pub asset_id: Option<Uuid>,
+    pub file_name: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub error: Option<String>,
+    pub link_expires_at: Option<NaiveDateTime>,
+    pub outbound_email_id: Option<Uuid>,
+    pub completed_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::account_exports)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct AccountExportRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: String,
+    pub job_id: Option<Uuid>,
+    pub asset_id: Option<Uuid>,
+    pub file_name: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub error: Option<String>,
+    pub link_expires_at: Option<NaiveDateTime>,
+    pub outbound_email_id: Option<Uuid>,
+    pub completed_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<AccountExportRow> for AccountExport {
+    fn from(row: AccountExportRow) -> Self {
+        let mut status = AccountExportStatus::from_db(&row.status);
+        if status == AccountExportStatus::Ready
+            && row
+                .link_expires_at
+                .is_some_and(|expires_at| expires_at <= Utc::now().naive_utc())
+        {
+            status = AccountExportStatus::Expired;
+        }
+        AccountExport {
+            id: row.id,
+            user_id: row.user_id,
+            status,
+            job_id: row.job_id,
+            asset_id: row.asset_id,
+            file_name: row.file_name,
+            size_bytes: row.size_bytes,
+            error: row.error,
+            link_expires_at: row.link_expires_at,
+            outbound_email_id: row.outbound_email_id,
+            completed_at: row.completed_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::account_exports)]
+pub struct NewAccountExport {
+    pub user_id: Uuid,
+    pub status: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AccountExportAuditEvent {
+    pub id: Uuid,
+    pub export_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: AccountExportAuditEventType,
+    pub detail: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::account_export_audit_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct AccountExportAuditEventRow {
+    pub id: Uuid,
+    pub export_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: String,
+    pub detail: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<AccountExportAuditEventRow> for AccountExportAuditEvent {
+    fn from(row: AccountExportAuditEventRow) -> Self {
+        AccountExportAuditEvent {
+            id: row.id,
+            export_id: row.export_id,
+            user_id: row.user_id,
+            event_type: AccountExportAuditEventType::from_db(&row.event_type),
+            detail: row.detail,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::account_export_audit_events)]
+pub struct NewAccountExportAuditEvent {
+    pub export_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: String,
+    pub detail: Option<String>,
+}
+
+/// A ready export with a freshly signed download link.
+#[derive(Serialize, Debug, Clone)]
+pub struct AccountExportDetail {
+    #[serde(flatten)]
+    pub export: AccountExport,
+    pub download_url: String,
+}
+
+/// Payload of an `EXPORT_ACCOUNT` job.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportAccountPayload {
+    pub account_export_id: Uuid,
+}
+
+/// Result stored on a finished `EXPORT_ACCOUNT` job.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportAccountResult {
+    pub account_export_id: Uuid,
+    pub asset_id: Uuid,
+    pub size_bytes: i64,
+    pub outbound_email_id: Option<Uuid>,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn ready_row(link_expires_at: NaiveDateTime) -> AccountExportRow {
+        let now = Utc::now().naive_utc();
+        AccountExportRow {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            status: AccountExportStatus::Ready.to_db().to_string(),
+            job_id: None,
+            asset_id: Some(Uuid::new_v4()),
+            file_name: Some("account-export.zip".to_string()),
+            size_bytes: Some(1),
+            error: None,
+            link_expires_at: Some(link_expires_at),
+            outbound_email_id: None,
+            completed_at: Some(now),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn ready_exports_expire_with_their_link() {
+        let now = Utc::now().naive_utc();
+        let ready = AccountExport::from(ready_row(now + Duration::hours(1)));
+        let expired = AccountExport::from(ready_row(now - Duration::hours(1)));
+
+        assert_eq!(ready.status, AccountExportStatus::Ready);
+        assert_eq!(expired.status, AccountExportStatus::Expired);
+        assert_eq!(expired.status.to_db(), "READY");
+    }
+
+    #[test]
+    fn only_unbuilt_exports_are_built_by_the_job() {
+        assert!(AccountExportStatus::Pending.needs_build());
+        assert!(AccountExportStatus::Running.needs_build());
+        assert!(AccountExportStatus::Failed.needs_build());
+        assert!(!AccountExportStatus::Ready.needs_build());
+        assert!(!AccountExportStatus::Expired.needs_build());
+    }
+
+    #[test]
+    fn statuses_and_audit_events_round_trip() {
+        for status in [
+            AccountExportStatus::Pending,
+            AccountExportStatus::Running,
+            AccountExportStatus::Ready,
+            AccountExportStatus::Failed,
+        ] {
+            assert_eq!(AccountExportStatus::from_db(status.to_db()), status);
+        }
+        for event_type in [
+            AccountExportAuditEventType::Requested,
+            AccountExportAuditEventType::Started,
+            AccountExportAuditEventType::Completed,
+            AccountExportAuditEventType::Failed,
+            AccountExportAuditEventType::Emailed,
+            AccountExportAuditEventType::LinkIssued,
+        ] {
+            assert_eq!(
+                AccountExportAuditEventType::from_db(event_type.to_db()),
+                event_type
+            );
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::schema::{account_export_audit_events, account_exports};
+
+use super::model::{
+    AccountExport, AccountExportAuditEvent, AccountExportAuditEventRow,
+    AccountExportAuditEventType, AccountExportRow, AccountExportStatus, NewAccountExport,
+    NewAccountExportAuditEvent,
+};
+
+impl NewAccountExport {
+    pub fn create(self, pool: &DbPool) -> Result<AccountExport, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = diesel::insert_into(account_exports::table)
+            .values(&self)
+            .returning(AccountExportRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+}
+
+impl AccountExport {
+    pub fn find(id: Uuid, pool: &DbPool) -> Result<AccountExport, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = account_exports::table
+            .find(id)
+            .select(AccountExportRow::as_select())
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| {
+                PpdcError::new(
+                    404,
+                    ErrorType::ApiError,
+                    "Account export not found".to_string(),
+                )
+            })?;
+        Ok(row.into())
+    }
+
+    pub fn list_for_user(user_id: Uuid, pool: &DbPool) -> Result<Vec<AccountExport>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = account_exports::table
+            .filter(account_exports::user_id.eq(user_id))
+            .order(account_exports::created_at.desc())
+            .select(AccountExportRow::as_select())
+            .load(&mut conn)?;
+        Ok(rows.into_iter().map(AccountExport::from).collect())
+    }
+
+    /// The user's export that is still queued or being built, if any.
+    pub fn find_in_progress_for_user(
+        user_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<Option<AccountExport>, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = account_exports::table
+            .filter(account_exports::user_id.eq(user_id))
+            .filter(account_exports::status.eq_any([
+                AccountExportStatus::Pending.to_db(),
+                AccountExportStatus::Running.to_db(),
+            ]))
+            .order(account_exports::created_at.desc())
+            .select(AccountExportRow::as_select())
+            .first(&mut conn)
+            .optional()?;
+        Ok(row.map(AccountExport::from))
+    }
+
+    /// The user's latest export built since `since`, expired or not.
+    pub fn find_latest_ready_since(
+        user_id: Uuid,
+        since: NaiveDateTime,
+        pool: &DbPool,
+    ) -> Result<Option<AccountExport>, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = account_exports::table
+            .filter(account_exports::user_id.eq(user_id))
+            .filter(account_exports::status.eq(AccountExportStatus::Ready.to_db()))
+            .filter(account_exports::created_at.gt(since))
+            .order(account_exports::created_at.desc())
+            .select(AccountExportRow::as_select())
+            .first(&mut conn)
+            .optional()?;
+        Ok(row.map(AccountExport::from))
+    }
+
+    /// Exports whose link has lapsed and whose archive is still stored.
+    pub fn find_with_expired_archives(
+        limit: i64,
+        pool: &DbPool,
+    ) -> Result<Vec<AccountExport>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = account_exports::table
+            .filter(account_exports::status.eq(AccountExportStatus::Ready.to_db()))
+            .filter(account_exports::asset_id.is_not_null())
+            .filter(account_exports::link_expires_at.le(Utc::now().naive_utc()))
+            .order(account_exports::link_expires_at.asc())
+            .limit(limit)
+            .select(AccountExportRow::as_select())
+            .load(&mut conn)?;
+        Ok(rows.into_iter().map(AccountExport::from).collect())
+    }
+
+    pub fn delete(self, pool: &DbPool) -> Result<(), PpdcError> {
+        let mut conn = pool.get()?;
+        diesel::delete(account_exports::table.find(self.id)).execute(&mut conn)?;
+        Ok(())
+    }
+
+    pub fn set_job(self, job_id: Uuid, pool: &DbPool) -> Result<AccountExport, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = diesel::update(account_exports::table.find(self.id))
+            .set((
+                account_exports::job_id.eq(Some(job_id)),
+                account_exports::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .returning(AccountExportRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+
+    pub fn mark_running(self, pool: &DbPool) -> Result<AccountExport, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = diesel::update(account_exports::table.find(self.id))
+            .set((
+                account_exports::status.eq(AccountExportStatus::Running.to_db()),
+                account_exports::error.eq(None::<String>),
+                account_exports::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .returning(AccountExportRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+
+    pub fn mark_ready(
+        self,
+        asset_id: Uuid,
+        file_name: String,
+        size_bytes: i64,
+        link_expires_at: NaiveDateTime,
+        pool: &DbPool,
+    ) -> Result<AccountExport, PpdcError> {
+        let mut conn = pool.get()?;
+        let now = Utc::now().naive_utc();
+        let row = diesel::update(account_exports::table.find(self.id))
+            .set((
+                account_exports::status.eq(AccountExportStatus::Ready.to_db()),
+                account_exports::asset_id.eq(Some(asset_id)),
+                account_exports::file_name.eq(Some(file_name)),
+                account_exports::size_bytes.eq(Some(size_bytes)),
+                account_exports::link_expires_at.eq(Some(link_expires_at)),
+                account_exports::completed_at.eq(Some(now)),
+                account_exports::updated_at.eq(now),
+            ))
+            .returning(AccountExportRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+
+    pub fn mark_failed(self, error: String, pool: &DbPool) -> Result<AccountExport, PpdcError> {
+        let mut conn = pool.get()?;
+        let now = Utc::now().naive_utc();
+        let row = diesel::update(account_exports::table.find(self.id))
+            .set((
+                account_exports::status.eq(AccountExportStatus::Failed.to_db()),
+                account_exports::error.eq(Some(error)),
+                account_exports::completed_at.eq(Some(now)),
+                account_exports::updated_at.eq(now),
+            ))
+            .returning(AccountExportRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+
+    pub fn set_outbound_email(
+        self,
+        outbound_email_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<AccountExport, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = diesel::update(account_exports::table.find(self.id))
+            .set((
+                account_exports::outbound_email_id.eq(Some(outbound_email_id)),
+                account_exports::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .returning(AccountExportRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+}
+
+impl AccountExportAuditEvent {
+    pub fn record(
+        export_id: Uuid,
+        user_id: Uuid,
+        event_type: AccountExportAuditEventType,
+        detail: Option<String>,
+        pool: &DbPool,
+    ) -> Result<AccountExportAuditEvent, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = diesel::insert_into(account_export_audit_events::table)
+            .values(NewAccountExportAuditEvent {
+                export_id,
+                user_id,
+                event_type: event_type.to_db().to_string(),
+                detail,
+            })
+            .returning(AccountExportAuditEventRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+
+    pub fn list_for_export(
+        export_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<Vec<AccountExportAuditEvent>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = account_export_audit_events::table
+            .filter(account_export_audit_events::export_id.eq(export_id))
+            .order(account_export_audit_events::created_at.asc())
+            .select(AccountExportAuditEventRow::as_select())
+            .load(&mut conn)?;
+        Ok(rows
+            .into_iter()
+            .map(AccountExportAuditEvent::from)
+            .collect())
+    }
+}
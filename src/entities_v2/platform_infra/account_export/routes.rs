@@ -0,0 +1,73 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Json, Path},
+};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{error::PpdcError, session::Session};
+
+use super::model::{AccountExport, AccountExportAuditEvent, AccountExportDetail};
+use super::service::{issue_download_link, request_account_export};
+
+fn find_owned_export(id: Uuid, user_id: Uuid, pool: &DbPool) -> Result<AccountExport, PpdcError> {
+    let export = AccountExport::find(id, pool)?;
+    if export.user_id != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    Ok(export)
+}
+
+/// Queues an archive of all the user's data; its link is emailed once built.
+#[debug_handler]
+pub async fn post_account_export_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<AccountExport>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    Ok(Json(request_account_export(user_id, &pool)?))
+}
+
+#[debug_handler]
+pub async fn get_account_exports_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<AccountExport>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    Ok(Json(AccountExport::list_for_user(user_id, &pool)?))
+}
+
+#[debug_handler]
+pub async fn get_account_export_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AccountExport>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    Ok(Json(find_owned_export(id, user_id, &pool)?))
+}
+
+/// Signs a new download link for a ready export.
+#[debug_handler]
+pub async fn post_account_export_download_link_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AccountExportDetail>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let export = find_owned_export(id, user_id, &pool)?;
+    Ok(Json(issue_download_link(export, &pool).await?))
+}
+
+#[debug_handler]
+pub async fn get_account_export_audit_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<AccountExportAuditEvent>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let export = find_owned_export(id, user_id, &pool)?;
+    Ok(Json(AccountExportAuditEvent::list_for_export(
+        export.id, &pool,
+    )?))
+}
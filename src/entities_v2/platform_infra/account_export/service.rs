@@ -0,0 +1,510 @@
+use std::collections::HashSet;
+use std::io::{Seek, Write};
+
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    asset::{
+        is_export_object_key, read_object_from_gcs, upload_export_file_for_user, Asset,
+        MAX_EXPORT_SIZE_BYTES,
+    },
+    background_job::{BackgroundJob, BackgroundJobType},
+    error::{ErrorType, PpdcError},
+    journal_export::{archive::ArchiveWriter, model::safe_file_name},
+    mailer::{self, NewOutboundEmail, OutboundEmailProvider},
+    user::User,
+};
+use crate::environment;
+
+use super::archive::{journal_folders, readme, trace_markdown, trace_markdown_path};
+use super::collect::{collect_account_data, ExportedRows};
+use super::model::{
+    AccountExport, AccountExportAuditEvent, AccountExportAuditEventType, AccountExportDetail,
+    AccountExportStatus, ExportAccountPayload, ExportAccountResult, NewAccountExport,
+};
+
+/// Users get one account archive per this many hours.
+const ACCOUNT_EXPORT_COOLDOWN_HOURS: i64 = 24;
+/// Room left under the export size limit for the README, manifest and ZIP headers.
+const ARCHIVE_SIZE_BUDGET: i64 = MAX_EXPORT_SIZE_BYTES - 16 * 1024 * 1024;
+const EXPIRED_ARCHIVES_BATCH_SIZE: i64 = 100;
+
+fn rows<'a>(tables: &'a [ExportedRows], name: &str) -> &'a [Value] {
+    tables
+        .iter()
+        .find(|table| table.name == name)
+        .map(|table| table.rows.as_slice())
+        .unwrap_or_default()
+}
+
+fn row_uuid(row: &Value, key: &str) -> Option<Uuid> {
+    row.get(key)
+        .and_then(Value::as_str)
+        .and_then(|value| Uuid::parse_str(value).ok())
+}
+
+/// Queues an export of all the user's data, or returns the one already in progress.
+///
+/// A user gets one archive per `ACCOUNT_EXPORT_COOLDOWN_HOURS`; the job is deduplicated per
+/// user so concurrent requests share one export.
+pub fn request_account_export(user_id: Uuid, pool: &DbPool) -> Result<AccountExport, PpdcError> {
+    if let Some(export) = AccountExport::find_in_progress_for_user(user_id, pool)? {
+        return Ok(export);
+    }
+    let since = Utc::now().naive_utc() - Duration::hours(ACCOUNT_EXPORT_COOLDOWN_HOURS);
+    if let Some(latest) = AccountExport::find_latest_ready_since(user_id, since, pool)? {
+        let retry_at = latest.created_at + Duration::hours(ACCOUNT_EXPORT_COOLDOWN_HOURS);
+        return Err(PpdcError::new(
+            429,
+            ErrorType::ApiError,
+            format!(
+                "An account export was already built recently; request a new one after {}",
+                retry_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+        ));
+    }
+    let export = NewAccountExport {
+        user_id,
+        status: AccountExportStatus::Pending.to_db().to_string(),
+    }
+    .create(pool)?;
+    let job = BackgroundJob::enqueue(
+        BackgroundJobType::ExportAccount,
+        serde_json::to_value(ExportAccountPayload {
+            account_export_id: export.id,
+        })?,
+        Some(format!(
+            "{}:{}",
+            BackgroundJobType::ExportAccount.to_db(),
+            user_id
+        )),
+        pool,
+    )?;
+    let payload = serde_json::from_value::<ExportAccountPayload>(job.payload.clone())?;
+    if payload.account_export_id != export.id {
+        // A concurrent request queued its export first.
+        export.delete(pool)?;
+        return AccountExport::find(payload.account_export_id, pool);
+    }
+    AccountExportAuditEvent::record(
+        export.id,
+        user_id,
+        AccountExportAuditEventType::Requested,
+        None,
+        pool,
+    )?;
+    export.set_job(job.id, pool)
+}
+
+/// An uploaded file as listed in `data/assets.json`.
+#[derive(Deserialize)]
+struct ArchivedAsset {
+    id: Uuid,
+    bucket: String,
+    object_key: String,
+    original_filename: String,
+    size_bytes: i64,
+}
+
+/// Writes the user's data to a ZIP: `data/*.json` per table, `journals/` with every trace
+/// as markdown, `assets/` with the uploaded files, a README and a `manifest.json`.
+///
+/// Uploaded files that would take the archive past `size_budget` bytes are listed in the
+/// manifest but left out.
+async fn build_account_archive<W: Write + Seek>(
+    export: &AccountExport,
+    tables: &[ExportedRows],
+    size_budget: i64,
+    writer: W,
+) -> Result<W, PpdcError> {
+    let mut archive = ArchiveWriter::to_writer(writer);
+    let mut files = Vec::new();
+    let mut written = 0i64;
+
+    for table in tables {
+        let path = table.file_path();
+        let bytes = serde_json::to_string_pretty(&table.rows)?;
+        archive.add(&path, bytes.as_bytes(), true)?;
+        written += bytes.len() as i64;
+        files.push(json!({ "path": path, "kind": "records", "records": table.rows.len() }));
+    }
+
+    let folders = journal_folders(rows(tables, "journals"));
+    let traces = rows(tables, "traces");
+    for trace in traces {
+        let path = trace_markdown_path(trace, &folders);
+        let markdown = trace_markdown(trace);
+        archive.add(&path, markdown.as_bytes(), true)?;
+        written += markdown.len() as i64;
+        files.push(json!({ "path": path, "kind": "trace", "id": trace.get("id") }));
+    }
+
+    // Export archives, this one's predecessors included, are not copied into new archives.
+    let export_asset_ids = rows(tables, "account_exports")
+        .iter()
+        .filter_map(|row| row_uuid(row, "asset_id"))
+        .collect::<HashSet<_>>();
+    let (mut asset_count, mut skipped_count) = (0, 0);
+    for row in rows(tables, "assets") {
+        let Ok(asset) = serde_json::from_value::<ArchivedAsset>(row.clone()) else {
+            continue;
+        };
+        if export_asset_ids.contains(&asset.id) || is_export_object_key(&asset.object_key) {
+            continue;
+        }
+        let path = format!(
+            "assets/{}-{}",
+            asset.id,
+            safe_file_name(&asset.original_filename)
+        );
+        if written + asset.size_bytes > size_budget {
+            skipped_count += 1;
+            files.push(json!({
+                "path": Value::Null,
+                "kind": "asset",
+                "id": asset.id,
+                "error": "Left out: the archive size limit is reached",
+            }));
+            continue;
+        }
+        match read_object_from_gcs(&asset.bucket, &asset.object_key).await {
+            Ok(bytes) => {
+                archive.add(&path, &bytes, false)?;
+                written += bytes.len() as i64;
+                asset_count += 1;
+                files.push(json!({ "path": path, "kind": "asset", "id": asset.id }));
+            }
+            Err(err) => files.push(json!({
+                "path": Value::Null,
+                "kind": "asset",
+                "id": asset.id,
+                "error": err.message,
+            })),
+        }
+    }
+
+    archive.add(
+        "README.md",
+        readme(tables, traces.len(), asset_count, skipped_count).as_bytes(),
+        true,
+    )?;
+    let manifest = json!({
+        "account_export_id": export.id,
+        "user_id": export.user_id,
+        "generated_at": Utc::now().naive_utc(),
+        "files": files,
+    });
+    archive.add(
+        "manifest.json",
+        serde_json::to_string_pretty(&manifest)?.as_bytes(),
+        true,
+    )?;
+    archive.finish_writer()
+}
+
+fn enqueue_account_export_email(
+    user: &User,
+    export: &AccountExport,
+    download_url: &str,
+    pool: &DbPool,
+) -> Result<Uuid, PpdcError> {
+    let expires_at = export
+        .link_expires_at
+        .unwrap_or_else(|| Utc::now().naive_utc());
+    let template =
+        mailer::account_export_ready_email(&user.display_name(), download_url, expires_at);
+    let email = NewOutboundEmail::new(
+        Some(user.id),
+        "ACCOUNT_EXPORT_READY".to_string(),
+        Some("ACCOUNT_EXPORT".to_string()),
+        Some(export.id),
+        user.email.clone(),
+        environment::get_resend_from_email(),
+        template.subject,
+        template.text_body,
+        template.html_body,
+        OutboundEmailProvider::Resend,
+        Some(Utc::now().naive_utc()),
+    )
+    .create(pool)?;
+    Ok(email.id)
+}
+
+async fn build_and_store(
+    export: AccountExport,
+    pool: &DbPool,
+) -> Result<ExportAccountResult, PpdcError> {
+    let user_id = export.user_id;
+    let tables = collect_account_data(user_id, pool)?;
+    let file = tempfile::tempfile().map_err(|err| {
+        PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!("Failed to create the export file: {}", err),
+        )
+    })?;
+    let file = build_account_archive(&export, &tables, ARCHIVE_SIZE_BUDGET, file).await?;
+    let file_name = format!("account-export-{}.zip", Utc::now().format("%Y-%m-%d"));
+    let asset =
+        upload_export_file_for_user(user_id, pool, file_name.clone(), "application/zip", file)
+            .await?;
+    let size_bytes = asset.size_bytes;
+    let (download_url, link_expires_at) = asset
+        .signed_read_url(environment::get_account_export_url_ttl_seconds())
+        .await?;
+    let export = export.mark_ready(asset.id, file_name, size_bytes, link_expires_at, pool)?;
+    AccountExportAuditEvent::record(
+        export.id,
+        user_id,
+        AccountExportAuditEventType::Completed,
+        Some(format!("{} bytes", size_bytes)),
+        pool,
+    )?;
+
+    let user = User::find(&user_id, pool)?;
+    let email_id = enqueue_account_export_email(&user, &export, &download_url, pool)?;
+    let export = export.set_outbound_email(email_id, pool)?;
+    AccountExportAuditEvent::record(
+        export.id,
+        user_id,
+        AccountExportAuditEventType::Emailed,
+        Some(format!("outbound_email_id={}", email_id)),
+        pool,
+    )?;
+    let _ = mailer::process_pending_email(email_id, pool).await;
+
+    Ok(ExportAccountResult {
+        account_export_id: export.id,
+        asset_id: asset.id,
+        size_bytes,
+        outbound_email_id: Some(email_id),
+    })
+}
+
+/// Runs an `EXPORT_ACCOUNT` job; failures are recorded on the export before the job retries.
+pub async fn run_account_export_job(
+    payload: ExportAccountPayload,
+    pool: &DbPool,
+) -> Result<ExportAccountResult, PpdcError> {
+    let export = AccountExport::find(payload.account_export_id, pool)?;
+    if !export.status.needs_build() {
+        return Ok(ExportAccountResult {
+            account_export_id: export.id,
+            asset_id: export.asset_id.ok_or_else(|| {
+                PpdcError::new(
+                    500,
+                    ErrorType::InternalError,
+                    "Ready account export has no archive".to_string(),
+                )
+            })?,
+            size_bytes: export.size_bytes.unwrap_or_default(),
+            outbound_email_id: export.outbound_email_id,
+        });
+    }
+    let (export_id, user_id) = (export.id, export.user_id);
+    let export = export.mark_running(pool)?;
+    AccountExportAuditEvent::record(
+        export_id,
+        user_id,
+        AccountExportAuditEventType::Started,
+        None,
+        pool,
+    )?;
+
+    match build_and_store(export, pool).await {
+        Ok(result) => Ok(result),
+        Err(err) => {
+            AccountExport::find(export_id, pool)?.mark_failed(err.message.clone(), pool)?;
+            AccountExportAuditEvent::record(
+                export_id,
+                user_id,
+                AccountExportAuditEventType::Failed,
+                Some(err.message.clone()),
+                pool,
+            )?;
+            Err(err)
+        }
+    }
+}
+
+/// Signs a fresh download link for a ready export, valid until the export's own link lapses.
+///
+/// Each issued link is audited.
+pub async fn issue_download_link(
+    export: AccountExport,
+    pool: &DbPool,
+) -> Result<AccountExportDetail, PpdcError> {
+    let (Some(asset_id), Some(link_expires_at)) = (export.asset_id, export.link_expires_at) else {
+        return Err(PpdcError::new(
+            409,
+            ErrorType::ApiError,
+            "Account export is not ready".to_string(),
+        ));
+    };
+    if export.status != AccountExportStatus::Ready {
+        return Err(PpdcError::new(
+            410,
+            ErrorType::ApiError,
+            "Account export expired, request a new one".to_string(),
+        ));
+    }
+    let remaining = (link_expires_at - Utc::now().naive_utc()).num_seconds();
+    let asset = Asset::find(asset_id, pool)?;
+    let (download_url, _) = asset.signed_read_url(remaining.max(1) as u64).await?;
+    AccountExportAuditEvent::record(
+        export.id,
+        export.user_id,
+        AccountExportAuditEventType::LinkIssued,
+        None,
+        pool,
+    )?;
+    Ok(AccountExportDetail {
+        export,
+        download_url,
+    })
+}
+
+/// Deletes the archives of account exports whose link has lapsed.
+pub async fn prune_expired_archives(pool: &DbPool) -> Result<usize, PpdcError> {
+    let mut deleted = 0;
+    for export in AccountExport::find_with_expired_archives(EXPIRED_ARCHIVES_BATCH_SIZE, pool)? {
+        let Some(asset_id) = export.asset_id else {
+            continue;
+        };
+        let result = match Asset::find(asset_id, pool) {
+            Ok(asset) => asset.delete(pool).await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(()) => deleted += 1,
+            Err(err) => tracing::warn!(
+                target: "account_export",
+                "expired_archive_delete_failed export_id={} asset_id={} error={}",
+                export.id,
+                asset_id,
+                err.message
+            ),
+        }
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use chrono::NaiveDateTime;
+    use zip::ZipArchive;
+
+    use super::*;
+
+    fn account_export() -> AccountExport {
+        let now = Utc::now().naive_utc();
+        AccountExport {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            status: AccountExportStatus::Running,
+            job_id: None,
+            asset_id: None,
+            file_name: None,
+            size_bytes: None,
+            error: None,
+            link_expires_at: None,
+            outbound_email_id: None,
+            completed_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn asset_row(id: Uuid, object_key: &str) -> Value {
+        json!({
+            "id": id,
+            "bucket": "bucket",
+            "object_key": object_key,
+            "original_filename": "photo.jpg",
+            "size_bytes": 10,
+            "created_at": NaiveDateTime::default(),
+        })
+    }
+
+    fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, path: &str) -> String {
+        let mut content = String::new();
+        archive
+            .by_name(path)
+            .expect(path)
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    }
+
+    #[tokio::test]
+    async fn archive_lists_every_file_in_its_manifest() {
+        let export = account_export();
+        let (photo_id, archive_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let tables = vec![
+            ExportedRows {
+                name: "journals",
+                rows: vec![json!({"id": "1234567890ab", "title": "Carnet"})],
+            },
+            ExportedRows {
+                name: "traces",
+                rows: vec![json!({
+                    "id": "abcdef0123",
+                    "journal_id": "1234567890ab",
+                    "title": "Matin",
+                    "interaction_date": "2026-03-05T08:00:00",
+                    "content": "Bonjour",
+                })],
+            },
+            ExportedRows {
+                name: "assets",
+                rows: vec![
+                    asset_row(photo_id, "users/u/original/photo.jpg"),
+                    asset_row(archive_id, "users/u/exports/a/account-export.zip"),
+                ],
+            },
+        ];
+
+        // A zero budget leaves the photo out without reading it from storage.
+        let cursor = build_account_archive(&export, &tables, 0, Cursor::new(Vec::new()))
+            .await
+            .unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(cursor.into_inner())).unwrap();
+
+        let trace_path = "journals/carnet-12345678/2026-03-05-matin-abcdef01.md";
+        assert!(read_entry(&mut archive, trace_path).contains("Bonjour"));
+        assert!(read_entry(&mut archive, "data/journals.json").contains("Carnet"));
+        assert!(read_entry(&mut archive, "README.md").contains("1 more files did not fit"));
+
+        let manifest =
+            serde_json::from_str::<Value>(&read_entry(&mut archive, "manifest.json")).unwrap();
+        assert_eq!(manifest["account_export_id"], json!(export.id));
+        let files = manifest["files"].as_array().unwrap();
+        let paths = files
+            .iter()
+            .filter_map(|file| file["path"].as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "data/journals.json",
+                "data/traces.json",
+                "data/assets.json",
+                trace_path
+            ]
+        );
+        let assets = files
+            .iter()
+            .filter(|file| file["kind"] == "asset")
+            .collect::<Vec<_>>();
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0]["id"], json!(photo_id));
+        assert!(assets[0]["path"].is_null());
+        assert_eq!(archive.len(), 6);
+    }
+}
@@ -14,6 +14,8 @@ use google_cloud_storage::{
 };
 use http::Method;
 use serde::{Deserialize, Serialize};
+use tokio::io::AsyncSeekExt;
+use tokio::sync::OnceCell;
 use uuid::Uuid;
 
 use crate::db::DbPool;
@@ -99,10 +101,22 @@ pub struct AssetSignedUrlResponse {
     pub public_url: Option<String>,
 }
 
+/// `LIKE` pattern of the object keys of export archives, which are pruned once their links
+/// expire.
+pub const EXPORT_OBJECT_KEY_PATTERN: &str = "users/%/exports/%";
+pub const MAX_EXPORT_SIZE_BYTES: i64 = 1024 * 1024 * 1024;
+
+/// Whether the object key is one of an export archive, see `EXPORT_OBJECT_KEY_PATTERN`.
+pub fn is_export_object_key(object_key: &str) -> bool {
+    object_key.starts_with("users/") && object_key.split('/').nth(2) == Some("exports")
+}
+
 #[derive(Debug, Clone, Copy)]
 enum AssetUploadPolicy {
     Generic,
     ImageOnly,
+    /// Archives the server builds for the user, such as journal exports.
+    Export,
 }
 
 type AssetTuple = (
@@ -400,6 +414,10 @@ impl Asset {
         Ok((url, expires_at))
     }
 
+    pub async fn read_bytes(&self) -> Result<Vec<u8>, PpdcError> {
+        read_object_from_gcs(&self.bucket, &self.object_key).await
+    }
+
     pub fn public_url(&self) -> Option<String> {
         let bucket = self.public_bucket.as_ref()?;
         let object_key = self.public_object_key.as_ref()?;
@@ -416,6 +434,15 @@ impl Asset {
 
         delete_object_from_gcs(bucket, object_key).await
     }
+
+    /// Deletes the stored objects, then the asset row.
+    pub async fn delete(self, pool: &DbPool) -> Result<(), PpdcError> {
+        self.delete_public_object_if_present().await?;
+        delete_object_from_gcs(&self.bucket, &self.object_key).await?;
+        let mut conn = pool.get()?;
+        diesel::delete(assets::table.filter(assets::id.eq(self.id))).execute(&mut conn)?;
+        Ok(())
+    }
 }
 
 impl NewAsset {
@@ -535,6 +562,28 @@ fn validate_image_upload(content_type: &str, size_bytes: usize) -> Result<(), Pp
     Ok(())
 }
 
+fn validate_export_upload(content_type: &str, size_bytes: usize) -> Result<(), PpdcError> {
+    let allowed = matches!(
+        content_type,
+        "application/zip" | "application/epub+zip" | "text/html"
+    );
+    if !allowed {
+        return Err(PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!("Unsupported export content type: {}", content_type),
+        ));
+    }
+    if size_bytes as i64 > MAX_EXPORT_SIZE_BYTES {
+        return Err(PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            format!("Export exceeds max size of {} bytes", MAX_EXPORT_SIZE_BYTES),
+        ));
+    }
+    Ok(())
+}
+
 fn validate_upload_with_policy(
     policy: AssetUploadPolicy,
     content_type: &str,
@@ -543,6 +592,7 @@ fn validate_upload_with_policy(
     match policy {
         AssetUploadPolicy::Generic => validate_upload(content_type, size_bytes),
         AssetUploadPolicy::ImageOnly => validate_image_upload(content_type, size_bytes),
+        AssetUploadPolicy::Export => validate_export_upload(content_type, size_bytes),
     }
 }
 
@@ -580,6 +630,81 @@ async fn upload_object_to_gcs(
     Ok(())
 }
 
+/// Streams a local file to storage instead of loading it in memory.
+async fn upload_file_to_gcs(
+    bucket_name: &str,
+    object_key: &str,
+    mime_type: &str,
+    file: tokio::fs::File,
+) -> Result<(), PpdcError> {
+    let storage = Storage::builder().build().await.map_err(|err| {
+        PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!("Failed to build GCS client: {}", err),
+        )
+    })?;
+
+    storage
+        .write_object(
+            format!("projects/_/buckets/{}", bucket_name),
+            object_key.to_string(),
+            file,
+        )
+        .set_content_type(mime_type.to_string())
+        .send_unbuffered()
+        .await
+        .map_err(|err| {
+            PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                format!("Failed to upload to GCS: {}", err),
+            )
+        })?;
+
+    Ok(())
+}
+
+/// Client shared by reads, which exports issue once per stored file.
+static READ_STORAGE: OnceCell<Storage> = OnceCell::const_new();
+
+pub async fn read_object_from_gcs(
+    bucket_name: &str,
+    object_key: &str,
+) -> Result<Vec<u8>, PpdcError> {
+    let storage_error = |err: google_cloud_storage::Error| {
+        PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!("Failed to read from GCS: {}", err),
+        )
+    };
+    let storage = READ_STORAGE
+        .get_or_try_init(|| Storage::builder().build())
+        .await
+        .map_err(|err| {
+            PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                format!("Failed to build GCS client: {}", err),
+            )
+        })?;
+
+    let mut response = storage
+        .read_object(
+            format!("projects/_/buckets/{}", bucket_name),
+            object_key.to_string(),
+        )
+        .send()
+        .await
+        .map_err(storage_error)?;
+    let mut content_bytes = Vec::new();
+    while let Some(chunk) = response.next().await.transpose().map_err(storage_error)? {
+        content_bytes.extend_from_slice(&chunk);
+    }
+    Ok(content_bytes)
+}
+
 async fn delete_object_from_gcs(bucket_name: &str, object_key: &str) -> Result<(), PpdcError> {
     let storage = StorageControl::builder().build().await.map_err(|err| {
         PpdcError::new(
@@ -625,6 +750,22 @@ pub async fn upload_asset_for_user(
     .await
 }
 
+fn asset_object_key(
+    user_id: Uuid,
+    policy: AssetUploadPolicy,
+    asset_id: Uuid,
+    original_filename: &str,
+) -> String {
+    let folder = match policy {
+        AssetUploadPolicy::Export => "exports",
+        _ => "original",
+    };
+    format!(
+        "users/{}/{}/{}/{}",
+        user_id, folder, asset_id, original_filename
+    )
+}
+
 async fn upload_asset_for_user_with_policy(
     user_id: Uuid,
     pool: &DbPool,
@@ -647,10 +788,7 @@ async fn upload_asset_for_user_with_policy(
 
     let asset_id = Uuid::new_v4();
     let bucket = crate::environment::get_gcs_bucket_name();
-    let object_key = format!(
-        "users/{}/original/{}/{}",
-        user_id, asset_id, original_filename
-    );
+    let object_key = asset_object_key(user_id, policy, asset_id, &original_filename);
 
     upload_object_to_gcs(&bucket, &object_key, &mime_type, content_bytes.clone()).await?;
     let requested_public_object_key = public_object_key;
@@ -781,6 +919,72 @@ async fn upload_asset_for_user_from_multipart_with_policy(
     .await
 }
 
+/// Stores a zip, EPUB or HTML archive built in memory for the user, such as a journal export.
+pub async fn upload_export_asset_for_user(
+    user_id: Uuid,
+    pool: &DbPool,
+    file_name: String,
+    content_type: &str,
+    content_bytes: Vec<u8>,
+) -> Result<AssetUploadResponse, PpdcError> {
+    upload_asset_for_user_with_policy(
+        user_id,
+        pool,
+        Some(file_name),
+        Some(content_type.to_string()),
+        content_bytes,
+        AssetUploadPolicy::Export,
+        None,
+    )
+    .await
+}
+
+/// Stores an export archive already written to `file`, such as an account export.
+pub async fn upload_export_file_for_user(
+    user_id: Uuid,
+    pool: &DbPool,
+    file_name: String,
+    content_type: &str,
+    file: std::fs::File,
+) -> Result<Asset, PpdcError> {
+    let file_error = |err: std::io::Error| {
+        PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!("Failed to read the export file: {}", err),
+        )
+    };
+    let size_bytes = file.metadata().map_err(file_error)?.len() as i64;
+    validate_export_upload(content_type, size_bytes as usize)?;
+    let original_filename = sanitize_filename(&file_name);
+    let asset_id = Uuid::new_v4();
+    let bucket = crate::environment::get_gcs_bucket_name();
+    let object_key = asset_object_key(
+        user_id,
+        AssetUploadPolicy::Export,
+        asset_id,
+        &original_filename,
+    );
+
+    let mut file = tokio::fs::File::from_std(file);
+    file.rewind().await.map_err(file_error)?;
+    upload_file_to_gcs(&bucket, &object_key, content_type, file).await?;
+
+    NewAsset {
+        id: asset_id,
+        owner_user_id: user_id,
+        bucket,
+        object_key,
+        mime_type: content_type.to_string(),
+        original_filename,
+        size_bytes,
+        status: AssetStatus::Ready,
+        public_bucket: None,
+        public_object_key: None,
+    }
+    .create(pool)
+}
+
 pub async fn upload_image_asset_for_user(
     user_id: Uuid,
     pool: &DbPool,
@@ -0,0 +1,5 @@
+pub mod model;
+pub mod persist;
+
+pub use model::{BackgroundJob, BackgroundJobStatus, BackgroundJobType, JobSchedule};
+pub use persist::retry_delay_seconds;
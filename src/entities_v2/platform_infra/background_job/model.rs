@@ -0,0 +1,173 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BackgroundJobType {
+    /// Enqueues one `RunLens` job per lens with runnable backlog.
+    RunPendingAnalyses,
+    /// Drains the analysis queue of the lens in `payload.lens_id`.
+    RunLens,
+    ReplanAutoplayLenses,
+    ProcessPendingEmails,
+    GenerateSharedJournalDailyDigests,
+    BackfillEmbeddings,
+    /// Deletes finished jobs past their retention window.
+    PruneFinishedJobs,
+    /// Rewraps data keys and reseals values that are plaintext or under a retired key.
+    ReencryptAtRest,
+    /// Builds the journal export archive described in `payload`.
+    ExportJournal,
+    /// Builds the account export archive of `payload` and emails its link.
+    ExportAccount,
+    /// Reindexes the traces referencing the landmark of `payload` after a curation.
+    RefreshLandmarkSearchDocuments,
+    /// Refreshes trace search documents still indexed with the legacy `simple` configuration.
+    ReindexTraceSearchDocuments,
+    /// Deletes export archives whose download links have expired.
+    PruneExpiredExports,
+    /// Writes landmark versions for a batch of lens chains after `payload.after_lens_id`, then
+    /// queues the next batch.
+    BackfillLandmarkVersions,
+}
+
+impl BackgroundJobType {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            BackgroundJobType::RunPendingAnalyses => "RUN_PENDING_ANALYSES",
+            BackgroundJobType::RunLens => "RUN_LENS",
+            BackgroundJobType::ReplanAutoplayLenses => "REPLAN_AUTOPLAY_LENSES",
+            BackgroundJobType::ProcessPendingEmails => "PROCESS_PENDING_EMAILS",
+            BackgroundJobType::GenerateSharedJournalDailyDigests => {
+                "GENERATE_SHARED_JOURNAL_DAILY_DIGESTS"
+            }
+            BackgroundJobType::BackfillEmbeddings => "BACKFILL_EMBEDDINGS",
+            BackgroundJobType::PruneFinishedJobs => "PRUNE_FINISHED_JOBS",
+            BackgroundJobType::ReencryptAtRest => "REENCRYPT_AT_REST",
+            BackgroundJobType::ExportJournal => "EXPORT_JOURNAL",
+            BackgroundJobType::ExportAccount => "EXPORT_ACCOUNT",
+            BackgroundJobType::RefreshLandmarkSearchDocuments => {
+                "REFRESH_LANDMARK_SEARCH_DOCUMENTS"
+            }
+            BackgroundJobType::ReindexTraceSearchDocuments => "REINDEX_TRACE_SEARCH_DOCUMENTS",
+            BackgroundJobType::PruneExpiredExports => "PRUNE_EXPIRED_EXPORTS",
+            BackgroundJobType::BackfillLandmarkVersions => "BACKFILL_LANDMARK_VERSIONS",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "RUN_PENDING_ANALYSES" => Some(BackgroundJobType::RunPendingAnalyses),
+            "RUN_LENS" => Some(BackgroundJobType::RunLens),
+            "REPLAN_AUTOPLAY_LENSES" => Some(BackgroundJobType::ReplanAutoplayLenses),
+            "PROCESS_PENDING_EMAILS" => Some(BackgroundJobType::ProcessPendingEmails),
+            "GENERATE_SHARED_JOURNAL_DAILY_DIGESTS" => {
+                Some(BackgroundJobType::GenerateSharedJournalDailyDigests)
+            }
+            "BACKFILL_EMBEDDINGS" => Some(BackgroundJobType::BackfillEmbeddings),
+            "PRUNE_FINISHED_JOBS" => Some(BackgroundJobType::PruneFinishedJobs),
+            "REENCRYPT_AT_REST" => Some(BackgroundJobType::ReencryptAtRest),
+            "EXPORT_JOURNAL" => Some(BackgroundJobType::ExportJournal),
+            "EXPORT_ACCOUNT" => Some(BackgroundJobType::ExportAccount),
+            "REFRESH_LANDMARK_SEARCH_DOCUMENTS" => {
+                Some(BackgroundJobType::RefreshLandmarkSearchDocuments)
+            }
+            "REINDEX_TRACE_SEARCH_DOCUMENTS" => {
+                Some(BackgroundJobType::ReindexTraceSearchDocuments)
+            }
+            "PRUNE_EXPIRED_EXPORTS" => Some(BackgroundJobType::PruneExpiredExports),
+            "BACKFILL_LANDMARK_VERSIONS" => Some(BackgroundJobType::BackfillLandmarkVersions),
+            _ => None,
+        }
+    }
+
+    /// Periodic sweeps are retried by their next scheduled run rather than by backoff.
+    pub fn max_attempts(self) -> i32 {
+        match self {
+            BackgroundJobType::RunLens => 3,
+            BackgroundJobType::ExportJournal
+            | BackgroundJobType::ExportAccount
+            | BackgroundJobType::RefreshLandmarkSearchDocuments
+            | BackgroundJobType::BackfillLandmarkVersions => 2,
+            _ => 1,
+        }
+    }
+
+    /// How long a worker may hold the job before another worker can reclaim it.
+    pub fn timeout_seconds(self) -> i32 {
+        match self {
+            BackgroundJobType::RunLens | BackgroundJobType::ExportAccount => 3600,
+            BackgroundJobType::BackfillEmbeddings
+            | BackgroundJobType::ReencryptAtRest
+            | BackgroundJobType::ReindexTraceSearchDocuments
+            | BackgroundJobType::ExportJournal
+            | BackgroundJobType::BackfillLandmarkVersions => 1800,
+            _ => 600,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BackgroundJobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl BackgroundJobStatus {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            BackgroundJobStatus::Pending => "PENDING",
+            BackgroundJobStatus::Running => "RUNNING",
+            BackgroundJobStatus::Succeeded => "SUCCEEDED",
+            BackgroundJobStatus::Failed => "FAILED",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "RUNNING" => BackgroundJobStatus::Running,
+            "SUCCEEDED" => BackgroundJobStatus::Succeeded,
+            "FAILED" => BackgroundJobStatus::Failed,
+            _ => BackgroundJobStatus::Pending,
+        }
+    }
+}
+
+/// A unit of work stored in `background_jobs` and executed by the in-process workers.
+#[derive(Serialize, Debug, Clone)]
+pub struct BackgroundJob {
+    pub id: Uuid,
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    pub status: BackgroundJobStatus,
+    pub dedupe_key: Option<String>,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub timeout_seconds: i32,
+    pub run_at: NaiveDateTime,
+    pub lock_owner: Option<Uuid>,
+    pub lock_until: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub result: Option<serde_json::Value>,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl BackgroundJob {
+    pub fn job_type_enum(&self) -> Option<BackgroundJobType> {
+        BackgroundJobType::from_db(&self.job_type)
+    }
+}
+
+/// A recurring job; whichever instance first sees `next_run_at` pass enqueues it.
+#[derive(Serialize, Debug, Clone)]
+pub struct JobSchedule {
+    pub job_type: BackgroundJobType,
+    pub interval_seconds: i32,
+}
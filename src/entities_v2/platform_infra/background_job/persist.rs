@@ -0,0 +1,433 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Int4, Nullable, Text, Timestamp, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+
+use super::model::{BackgroundJob, BackgroundJobStatus, BackgroundJobType, JobSchedule};
+
+const RETRY_BASE_DELAY_SECONDS: i64 = 30;
+const RETRY_MAX_DELAY_SECONDS: i64 = 3600;
+
+const JOB_COLUMNS: &str = r#"
+    id, job_type, payload::text AS payload, status, dedupe_key, attempts, max_attempts,
+    timeout_seconds, run_at, lock_owner, lock_until, last_error, result::text AS result,
+    started_at, finished_at, created_at, updated_at
+"#;
+
+#[derive(QueryableByName)]
+struct BackgroundJobRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+    #[diesel(sql_type = Text)]
+    job_type: String,
+    #[diesel(sql_type = Text)]
+    payload: String,
+    #[diesel(sql_type = Text)]
+    status: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    dedupe_key: Option<String>,
+    #[diesel(sql_type = Int4)]
+    attempts: i32,
+    #[diesel(sql_type = Int4)]
+    max_attempts: i32,
+    #[diesel(sql_type = Int4)]
+    timeout_seconds: i32,
+    #[diesel(sql_type = Timestamp)]
+    run_at: NaiveDateTime,
+    #[diesel(sql_type = Nullable<SqlUuid>)]
+    lock_owner: Option<Uuid>,
+    #[diesel(sql_type = Nullable<Timestamp>)]
+    lock_until: Option<NaiveDateTime>,
+    #[diesel(sql_type = Nullable<Text>)]
+    last_error: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    result: Option<String>,
+    #[diesel(sql_type = Nullable<Timestamp>)]
+    started_at: Option<NaiveDateTime>,
+    #[diesel(sql_type = Nullable<Timestamp>)]
+    finished_at: Option<NaiveDateTime>,
+    #[diesel(sql_type = Timestamp)]
+    created_at: NaiveDateTime,
+    #[diesel(sql_type = Timestamp)]
+    updated_at: NaiveDateTime,
+}
+
+impl TryFrom<BackgroundJobRow> for BackgroundJob {
+    type Error = PpdcError;
+
+    fn try_from(row: BackgroundJobRow) -> Result<Self, Self::Error> {
+        Ok(BackgroundJob {
+            id: row.id,
+            job_type: row.job_type,
+            payload: serde_json::from_str(&row.payload)?,
+            status: BackgroundJobStatus::from_db(&row.status),
+            dedupe_key: row.dedupe_key,
+            attempts: row.attempts,
+            max_attempts: row.max_attempts,
+            timeout_seconds: row.timeout_seconds,
+            run_at: row.run_at,
+            lock_owner: row.lock_owner,
+            lock_until: row.lock_until,
+            last_error: row.last_error,
+            result: row
+                .result
+                .as_deref()
+                .map(serde_json::from_str)
+                .transpose()?,
+            started_at: row.started_at,
+            finished_at: row.finished_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+#[derive(QueryableByName)]
+struct IdRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+}
+
+#[derive(QueryableByName)]
+struct JobTypeRow {
+    #[diesel(sql_type = Text)]
+    job_type: String,
+}
+
+/// Exponential backoff before the next attempt of a failed job.
+pub fn retry_delay_seconds(attempts: i32) -> i64 {
+    let exponent = attempts.clamp(1, 16) as u32 - 1;
+    (RETRY_BASE_DELAY_SECONDS * 2_i64.pow(exponent)).min(RETRY_MAX_DELAY_SECONDS)
+}
+
+impl BackgroundJob {
+    pub fn find(id: Uuid, pool: &DbPool) -> Result<BackgroundJob, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = sql_query(format!(
+            "SELECT {} FROM background_jobs WHERE id = $1",
+            JOB_COLUMNS
+        ))
+        .bind::<SqlUuid, _>(id)
+        .get_result::<BackgroundJobRow>(&mut conn)
+        .optional()?
+        .ok_or_else(|| {
+            PpdcError::new(
+                404,
+                ErrorType::ApiError,
+                format!("Background job {} not found", id),
+            )
+        })?;
+        row.try_into()
+    }
+
+    /// Queues a job to run now. When `dedupe_key` matches a pending or running job,
+    /// that job is returned instead of queueing a duplicate.
+    pub fn enqueue(
+        job_type: BackgroundJobType,
+        payload: serde_json::Value,
+        dedupe_key: Option<String>,
+        pool: &DbPool,
+    ) -> Result<BackgroundJob, PpdcError> {
+        let mut conn = pool.get()?;
+        let payload = serde_json::to_string(&payload)?;
+        let inserted = sql_query(
+            r#"
+INSERT INTO background_jobs (job_type, payload, dedupe_key, max_attempts, timeout_seconds)
+VALUES ($1, CAST($2 AS jsonb), $3, $4, $5)
+ON CONFLICT (dedupe_key) WHERE status IN ('PENDING', 'RUNNING') DO NOTHING
+RETURNING id
+            "#,
+        )
+        .bind::<Text, _>(job_type.to_db())
+        .bind::<Text, _>(&payload)
+        .bind::<Nullable<Text>, _>(&dedupe_key)
+        .bind::<Int4, _>(job_type.max_attempts())
+        .bind::<Int4, _>(job_type.timeout_seconds())
+        .get_result::<IdRow>(&mut conn)
+        .optional()?;
+
+        if let Some(row) = inserted {
+            return BackgroundJob::find(row.id, pool);
+        }
+        let existing = sql_query(format!(
+            "SELECT {} FROM background_jobs
+             WHERE dedupe_key = $1 AND status IN ('PENDING', 'RUNNING')",
+            JOB_COLUMNS
+        ))
+        .bind::<Nullable<Text>, _>(&dedupe_key)
+        .get_result::<BackgroundJobRow>(&mut conn)
+        .optional()?;
+        match existing {
+            Some(row) => row.try_into(),
+            // The active job finished between both statements: queue a fresh one.
+            None => BackgroundJob::enqueue_unchecked(job_type, &payload, dedupe_key, pool),
+        }
+    }
+
+    /// Queues a payload-less job, at most one pending or running per type.
+    pub fn enqueue_singleton(
+        job_type: BackgroundJobType,
+        pool: &DbPool,
+    ) -> Result<BackgroundJob, PpdcError> {
+        BackgroundJob::enqueue(
+            job_type,
+            serde_json::json!({}),
+            Some(job_type.to_db().to_string()),
+            pool,
+        )
+    }
+
+    fn enqueue_unchecked(
+        job_type: BackgroundJobType,
+        payload: &str,
+        dedupe_key: Option<String>,
+        pool: &DbPool,
+    ) -> Result<BackgroundJob, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = sql_query(
+            r#"
+INSERT INTO background_jobs (job_type, payload, dedupe_key, max_attempts, timeout_seconds)
+VALUES ($1, CAST($2 AS jsonb), $3, $4, $5)
+RETURNING id
+            "#,
+        )
+        .bind::<Text, _>(job_type.to_db())
+        .bind::<Text, _>(payload)
+        .bind::<Nullable<Text>, _>(&dedupe_key)
+        .bind::<Int4, _>(job_type.max_attempts())
+        .bind::<Int4, _>(job_type.timeout_seconds())
+        .get_result::<IdRow>(&mut conn)?;
+        BackgroundJob::find(row.id, pool)
+    }
+
+    /// Locks the oldest due job for `worker_id`, including running jobs whose lock expired.
+    ///
+    /// Jobs whose lock expired after their last attempt are marked failed instead, so a job
+    /// that keeps crashing or hanging its worker is not reclaimed forever.
+    pub fn claim_next(worker_id: Uuid, pool: &DbPool) -> Result<Option<BackgroundJob>, PpdcError> {
+        let mut conn = pool.get()?;
+        sql_query(
+            r#"
+UPDATE background_jobs
+SET status = 'FAILED',
+    last_error = COALESCE(last_error, 'Lock expired after the last attempt'),
+    lock_owner = NULL,
+    lock_until = NULL,
+    finished_at = NOW(),
+    updated_at = NOW()
+WHERE status = 'RUNNING'
+  AND lock_until IS NOT NULL
+  AND lock_until <= NOW()
+  AND attempts >= max_attempts
+            "#,
+        )
+        .execute(&mut conn)?;
+        let claimed = sql_query(
+            r#"
+WITH candidate AS (
+    SELECT bj.id
+    FROM background_jobs bj
+    WHERE (
+        (bj.status = 'PENDING' AND bj.run_at <= NOW())
+        OR (
+            bj.status = 'RUNNING'
+            AND bj.lock_until IS NOT NULL
+            AND bj.lock_until <= NOW()
+            AND bj.attempts < bj.max_attempts
+        )
+    )
+    ORDER BY bj.run_at ASC, bj.created_at ASC
+    LIMIT 1
+    FOR UPDATE SKIP LOCKED
+)
+UPDATE background_jobs bj
+SET status = 'RUNNING',
+    lock_owner = $1,
+    lock_until = NOW() + (bj.timeout_seconds * INTERVAL '1 second'),
+    attempts = bj.attempts + 1,
+    started_at = NOW(),
+    updated_at = NOW()
+FROM candidate
+WHERE bj.id = candidate.id
+RETURNING bj.id
+            "#,
+        )
+        .bind::<SqlUuid, _>(worker_id)
+        .get_result::<IdRow>(&mut conn)
+        .optional()?;
+
+        claimed
+            .map(|row| BackgroundJob::find(row.id, pool))
+            .transpose()
+    }
+
+    pub fn mark_succeeded(
+        id: Uuid,
+        worker_id: Uuid,
+        result: &serde_json::Value,
+        pool: &DbPool,
+    ) -> Result<(), PpdcError> {
+        let mut conn = pool.get()?;
+        sql_query(
+            r#"
+UPDATE background_jobs
+SET status = 'SUCCEEDED',
+    result = CAST($3 AS jsonb),
+    last_error = NULL,
+    lock_owner = NULL,
+    lock_until = NULL,
+    finished_at = NOW(),
+    updated_at = NOW()
+WHERE id = $1 AND lock_owner = $2
+            "#,
+        )
+        .bind::<SqlUuid, _>(id)
+        .bind::<SqlUuid, _>(worker_id)
+        .bind::<Text, _>(serde_json::to_string(result)?)
+        .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Schedules another attempt with backoff, or marks the job failed once attempts run out.
+    pub fn mark_failed(
+        &self,
+        worker_id: Uuid,
+        error: &str,
+        pool: &DbPool,
+    ) -> Result<(), PpdcError> {
+        let mut conn = pool.get()?;
+        sql_query(
+            r#"
+UPDATE background_jobs
+SET status = CASE WHEN attempts < max_attempts THEN 'PENDING' ELSE 'FAILED' END,
+    run_at = CASE
+        WHEN attempts < max_attempts THEN NOW() + ($3 * INTERVAL '1 second')
+        ELSE run_at
+    END,
+    finished_at = CASE WHEN attempts < max_attempts THEN NULL ELSE NOW() END,
+    last_error = $4,
+    lock_owner = NULL,
+    lock_until = NULL,
+    updated_at = NOW()
+WHERE id = $1 AND lock_owner = $2
+            "#,
+        )
+        .bind::<SqlUuid, _>(self.id)
+        .bind::<SqlUuid, _>(worker_id)
+        .bind::<BigInt, _>(retry_delay_seconds(self.attempts))
+        .bind::<Text, _>(error)
+        .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Pushes `lock_until` a full timeout ahead while the worker is still running the job.
+    /// Returns false once the job is no longer held by `worker_id`.
+    pub fn heartbeat(id: Uuid, worker_id: Uuid, pool: &DbPool) -> Result<bool, PpdcError> {
+        let mut conn = pool.get()?;
+        let updated = sql_query(
+            r#"
+UPDATE background_jobs
+SET lock_until = NOW() + (timeout_seconds * INTERVAL '1 second'),
+    updated_at = NOW()
+WHERE id = $1 AND status = 'RUNNING' AND lock_owner = $2
+            "#,
+        )
+        .bind::<SqlUuid, _>(id)
+        .bind::<SqlUuid, _>(worker_id)
+        .execute(&mut conn)?;
+        Ok(updated == 1)
+    }
+
+    /// Hands the running jobs of a stopping worker back to the queue without
+    /// counting the interrupted attempt.
+    pub fn release_owned(worker_id: Uuid, pool: &DbPool) -> Result<usize, PpdcError> {
+        let mut conn = pool.get()?;
+        let released = sql_query(
+            r#"
+UPDATE background_jobs
+SET status = 'PENDING',
+    attempts = GREATEST(attempts - 1, 0),
+    lock_owner = NULL,
+    lock_until = NULL,
+    updated_at = NOW()
+WHERE status = 'RUNNING' AND lock_owner = $1
+            "#,
+        )
+        .bind::<SqlUuid, _>(worker_id)
+        .execute(&mut conn)?;
+        Ok(released)
+    }
+
+    pub fn prune_finished(retention_days: i64, pool: &DbPool) -> Result<usize, PpdcError> {
+        let mut conn = pool.get()?;
+        let deleted = sql_query(
+            r#"
+DELETE FROM background_jobs
+WHERE status IN ('SUCCEEDED', 'FAILED')
+  AND finished_at < NOW() - ($1 * INTERVAL '1 day')
+            "#,
+        )
+        .bind::<BigInt, _>(retention_days)
+        .execute(&mut conn)?;
+        Ok(deleted)
+    }
+}
+
+impl JobSchedule {
+    /// Registers the schedules, keeping the next run of those already stored.
+    pub fn ensure_all(schedules: &[JobSchedule], pool: &DbPool) -> Result<(), PpdcError> {
+        let mut conn = pool.get()?;
+        for schedule in schedules {
+            sql_query(
+                r#"
+INSERT INTO job_schedules (job_type, interval_seconds)
+VALUES ($1, $2)
+ON CONFLICT (job_type) DO UPDATE
+SET interval_seconds = EXCLUDED.interval_seconds,
+    updated_at = NOW()
+                "#,
+            )
+            .bind::<Text, _>(schedule.job_type.to_db())
+            .bind::<Int4, _>(schedule.interval_seconds)
+            .execute(&mut conn)?;
+        }
+        Ok(())
+    }
+
+    /// Advances every due schedule and returns the job types to enqueue. The row update
+    /// makes each occurrence fire on a single instance.
+    pub fn claim_due(pool: &DbPool) -> Result<Vec<BackgroundJobType>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = sql_query(
+            r#"
+UPDATE job_schedules
+SET next_run_at = NOW() + (interval_seconds * INTERVAL '1 second'),
+    updated_at = NOW()
+WHERE next_run_at <= NOW()
+RETURNING job_type
+            "#,
+        )
+        .get_results::<JobTypeRow>(&mut conn)?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| BackgroundJobType::from_db(&row.job_type))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_and_is_capped() {
+        assert_eq!(retry_delay_seconds(1), 30);
+        assert_eq!(retry_delay_seconds(2), 60);
+        assert_eq!(retry_delay_seconds(3), 120);
+        assert_eq!(retry_delay_seconds(12), RETRY_MAX_DELAY_SECONDS);
+    }
+}
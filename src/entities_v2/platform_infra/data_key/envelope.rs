@@ -0,0 +1,214 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::db::{try_get_global_pool, DbPool};
+use crate::entities_v2::error::{ErrorType, PpdcError};
+
+use super::master_key::MasterKeyring;
+use super::model::DataKey;
+
+/// Unwrapped data key; only ever held in memory.
+pub type DataKeyBytes = Zeroizing<[u8; 32]>;
+
+/// Sealed values are stored as `enc:v1:<data_key_id>:<base64(nonce || ciphertext)>`.
+///
+/// The data key id is the associated data, so a value cannot be replayed under another key.
+/// Anything without the prefix is legacy plaintext and is returned as is.
+pub const SEALED_PREFIX: &str = "enc:v1:";
+pub(super) const NONCE_LENGTH: usize = 12;
+
+fn master_keyring_cell() -> &'static Result<Option<MasterKeyring>, String> {
+    static MASTER_KEYRING: OnceLock<Result<Option<MasterKeyring>, String>> = OnceLock::new();
+    MASTER_KEYRING.get_or_init(|| MasterKeyring::from_env().map_err(|err| err.message))
+}
+
+/// The configured master keyring, or `None` when at-rest encryption is disabled.
+pub fn master_keyring() -> Result<Option<&'static MasterKeyring>, PpdcError> {
+    match master_keyring_cell() {
+        Ok(keyring) => Ok(keyring.as_ref()),
+        Err(message) => Err(PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            message.clone(),
+        )),
+    }
+}
+
+fn unwrapped_keys() -> &'static Mutex<HashMap<Uuid, DataKeyBytes>> {
+    static UNWRAPPED_KEYS: OnceLock<Mutex<HashMap<Uuid, DataKeyBytes>>> = OnceLock::new();
+    UNWRAPPED_KEYS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub fn generate_data_key() -> DataKeyBytes {
+    let key = Aes256Gcm::generate_key(&mut OsRng);
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    bytes.copy_from_slice(key.as_slice());
+    bytes
+}
+
+/// Unwraps a data key once per process and keeps it for later reads.
+pub fn data_key_bytes(key: &DataKey) -> Result<DataKeyBytes, PpdcError> {
+    if let Some(bytes) = unwrapped_keys().lock().unwrap().get(&key.id) {
+        return Ok(bytes.clone());
+    }
+    let keyring = master_keyring()?.ok_or_else(encryption_disabled)?;
+    let bytes = keyring.unwrap(&key.master_key_id, &key.wrapped_key)?;
+    unwrapped_keys()
+        .lock()
+        .unwrap()
+        .insert(key.id, bytes.clone());
+    Ok(bytes)
+}
+
+fn data_key_bytes_by_id(key_id: Uuid, pool: &DbPool) -> Result<DataKeyBytes, PpdcError> {
+    if let Some(bytes) = unwrapped_keys().lock().unwrap().get(&key_id) {
+        return Ok(bytes.clone());
+    }
+    data_key_bytes(&DataKey::find(key_id, pool)?)
+}
+
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+/// Id of the data key that sealed `value`, if it is sealed.
+pub fn sealed_key_id(value: &str) -> Option<Uuid> {
+    split_sealed(value).map(|(key_id, _)| key_id)
+}
+
+fn split_sealed(value: &str) -> Option<(Uuid, &str)> {
+    let (key_id, payload) = value.strip_prefix(SEALED_PREFIX)?.split_once(':')?;
+    Some((Uuid::parse_str(key_id).ok()?, payload))
+}
+
+pub fn seal_with(key_id: Uuid, key: &DataKeyBytes, plaintext: &str) -> Result<String, PpdcError> {
+    let cipher = Aes256Gcm::new_from_slice(key.as_slice())
+        .map_err(|_| open_failed("invalid data key length"))?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let key_id_text = key_id.to_string();
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext.as_bytes(),
+                aad: key_id_text.as_bytes(),
+            },
+        )
+        .map_err(|_| open_failed("encryption failed"))?;
+    Ok(format!(
+        "{}{}:{}",
+        SEALED_PREFIX,
+        key_id_text,
+        BASE64.encode([nonce.as_slice(), ciphertext.as_slice()].concat())
+    ))
+}
+
+pub fn open_with(value: &str, key: &DataKeyBytes) -> Result<String, PpdcError> {
+    let (key_id, payload) = split_sealed(value).ok_or_else(|| open_failed("malformed envelope"))?;
+    let bytes = BASE64
+        .decode(payload)
+        .map_err(|_| open_failed("malformed envelope"))?;
+    if bytes.len() <= NONCE_LENGTH {
+        return Err(open_failed("malformed envelope"));
+    }
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+    let cipher = Aes256Gcm::new_from_slice(key.as_slice())
+        .map_err(|_| open_failed("invalid data key length"))?;
+    let key_id_text = key_id.to_string();
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: key_id_text.as_bytes(),
+            },
+        )
+        .map_err(|_| open_failed("data key does not match"))?;
+    String::from_utf8(plaintext).map_err(|_| open_failed("plaintext is not valid UTF-8"))
+}
+
+/// Seals a value under the owner's active data key; `None` owns platform data.
+///
+/// Empty values and every value while encryption is disabled are stored as is.
+pub fn seal_for_owner(
+    owner: Option<Uuid>,
+    plaintext: &str,
+    pool: &DbPool,
+) -> Result<String, PpdcError> {
+    if plaintext.is_empty() || master_keyring()?.is_none() {
+        return Ok(plaintext.to_string());
+    }
+    let key = DataKey::find_or_create_active(owner, pool)?;
+    seal_with(key.id, &data_key_bytes(&key)?, plaintext)
+}
+
+/// Opens a stored value in the hydrate layer; plaintext values pass through.
+pub fn open(value: String) -> Result<String, PpdcError> {
+    let Some((key_id, _)) = split_sealed(&value) else {
+        return Ok(value);
+    };
+    let pool = try_get_global_pool().ok_or_else(|| {
+        PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            "Database pool is not initialized; cannot open sealed data".to_string(),
+        )
+    })?;
+    open_with(&value, &data_key_bytes_by_id(key_id, pool)?)
+}
+
+fn encryption_disabled() -> PpdcError {
+    PpdcError::new(
+        500,
+        ErrorType::InternalError,
+        "AT_REST_MASTER_KEYS is not configured; cannot open sealed data".to_string(),
+    )
+}
+
+fn open_failed(reason: &str) -> PpdcError {
+    PpdcError::new(
+        500,
+        ErrorType::InternalError,
+        format!("Failed to open sealed value: {}", reason),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seals_and_opens_with_the_same_key() {
+        let key_id = Uuid::new_v4();
+        let key = Zeroizing::new([4u8; 32]);
+        let sealed = seal_with(key_id, &key, "Trace du matin").unwrap();
+
+        assert!(is_sealed(&sealed));
+        assert_eq!(sealed_key_id(&sealed), Some(key_id));
+        assert_eq!(open_with(&sealed, &key).unwrap(), "Trace du matin");
+        assert!(open_with(&sealed, &Zeroizing::new([5u8; 32])).is_err());
+    }
+
+    #[test]
+    fn rejects_envelopes_moved_to_another_key_id() {
+        let key = Zeroizing::new([4u8; 32]);
+        let sealed = seal_with(Uuid::new_v4(), &key, "secret").unwrap();
+        let (_, payload) = split_sealed(&sealed).unwrap();
+        let moved = format!("{}{}:{}", SEALED_PREFIX, Uuid::new_v4(), payload);
+
+        assert!(open_with(&moved, &key).is_err());
+    }
+
+    #[test]
+    fn plaintext_passes_through_open() {
+        assert!(!is_sealed("plain text"));
+        assert_eq!(sealed_key_id("enc:v1:not-a-uuid:abc"), None);
+        assert_eq!(open("plain text".to_string()).unwrap(), "plain text");
+    }
+}
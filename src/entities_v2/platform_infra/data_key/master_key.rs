@@ -0,0 +1,170 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use zeroize::Zeroizing;
+
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::environment;
+
+use super::envelope::{DataKeyBytes, NONCE_LENGTH};
+
+/// Master keys loaded from `AT_REST_MASTER_KEYS`; a local stand-in for a KMS.
+///
+/// Data keys are wrapped with AES-256-GCM under the active master key, with the master key
+/// id as associated data. Older master keys stay listed so their data keys can be rewrapped.
+pub struct MasterKeyring {
+    keys: Vec<(String, Zeroizing<[u8; 32]>)>,
+    active_id: String,
+}
+
+impl MasterKeyring {
+    /// Reads the keyring from the environment; `None` leaves at-rest encryption disabled.
+    pub fn from_env() -> Result<Option<Self>, PpdcError> {
+        let Some(value) = environment::get_at_rest_master_keys() else {
+            return Ok(None);
+        };
+        Self::parse(
+            &value,
+            environment::get_at_rest_active_master_key_id().as_deref(),
+        )
+        .map(Some)
+    }
+
+    fn parse(value: &str, active_id: Option<&str>) -> Result<Self, PpdcError> {
+        let mut keys = Vec::new();
+        for entry in value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (id, encoded) = entry
+                .split_once(':')
+                .ok_or_else(|| misconfigured("entries must be id:base64key"))?;
+            let bytes = Zeroizing::new(
+                BASE64
+                    .decode(encoded.trim())
+                    .map_err(|_| misconfigured("keys must be base64"))?,
+            );
+            let key = <[u8; 32]>::try_from(bytes.as_slice())
+                .map_err(|_| misconfigured("keys must be 32 bytes"))?;
+            keys.push((id.trim().to_string(), Zeroizing::new(key)));
+        }
+        let active_id = match active_id {
+            Some(id) => id.to_string(),
+            None => keys
+                .first()
+                .map(|(id, _)| id.clone())
+                .ok_or_else(|| misconfigured("at least one key is required"))?,
+        };
+        if !keys.iter().any(|(id, _)| *id == active_id) {
+            return Err(misconfigured("the active master key id is not listed"));
+        }
+        Ok(MasterKeyring { keys, active_id })
+    }
+
+    pub fn active_id(&self) -> &str {
+        &self.active_id
+    }
+
+    fn cipher(&self, master_key_id: &str) -> Result<Aes256Gcm, PpdcError> {
+        let (_, key) = self
+            .keys
+            .iter()
+            .find(|(id, _)| id == master_key_id)
+            .ok_or_else(|| {
+                PpdcError::new(
+                    500,
+                    ErrorType::InternalError,
+                    format!("Master key {} is not configured", master_key_id),
+                )
+            })?;
+        Aes256Gcm::new_from_slice(key.as_slice())
+            .map_err(|_| misconfigured("keys must be 32 bytes"))
+    }
+
+    /// Wraps a data key under the active master key.
+    pub fn wrap(&self, data_key: &DataKeyBytes) -> Result<String, PpdcError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher(&self.active_id)?
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: data_key.as_slice(),
+                    aad: self.active_id.as_bytes(),
+                },
+            )
+            .map_err(|_| misconfigured("failed to wrap a data key"))?;
+        Ok(BASE64.encode([nonce.as_slice(), ciphertext.as_slice()].concat()))
+    }
+
+    pub fn unwrap(
+        &self,
+        master_key_id: &str,
+        wrapped_key: &str,
+    ) -> Result<DataKeyBytes, PpdcError> {
+        let unwrap_failed = || {
+            PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                format!(
+                    "Failed to unwrap a data key with master key {}",
+                    master_key_id
+                ),
+            )
+        };
+        let bytes = BASE64.decode(wrapped_key).map_err(|_| unwrap_failed())?;
+        if bytes.len() <= NONCE_LENGTH {
+            return Err(unwrap_failed());
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+        let plaintext = Zeroizing::new(
+            self.cipher(master_key_id)?
+                .decrypt(
+                    Nonce::from_slice(nonce),
+                    Payload {
+                        msg: ciphertext,
+                        aad: master_key_id.as_bytes(),
+                    },
+                )
+                .map_err(|_| unwrap_failed())?,
+        );
+        let key = <[u8; 32]>::try_from(plaintext.as_slice()).map_err(|_| unwrap_failed())?;
+        Ok(Zeroizing::new(key))
+    }
+}
+
+fn misconfigured(reason: &str) -> PpdcError {
+    PpdcError::new(
+        500,
+        ErrorType::InternalError,
+        format!("AT_REST_MASTER_KEYS is misconfigured: {}", reason),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_under_active_key_and_unwraps_with_any_listed_key() {
+        let old = BASE64.encode([1u8; 32]);
+        let new = BASE64.encode([2u8; 32]);
+        let before = MasterKeyring::parse(&format!("old:{}", old), None).unwrap();
+        let wrapped = before.wrap(&Zeroizing::new([9u8; 32])).unwrap();
+
+        let after = MasterKeyring::parse(&format!("old:{},new:{}", old, new), Some("new")).unwrap();
+        assert_eq!(after.active_id(), "new");
+        assert_eq!(*after.unwrap("old", &wrapped).unwrap(), [9u8; 32]);
+        assert!(after.unwrap("new", &wrapped).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_keyrings() {
+        assert!(MasterKeyring::parse("nokey", None).is_err());
+        assert!(MasterKeyring::parse("k1:c2hvcnQ=", None).is_err());
+        let key = BASE64.encode([1u8; 32]);
+        assert!(MasterKeyring::parse(&format!("k1:{}", key), Some("k2")).is_err());
+    }
+}
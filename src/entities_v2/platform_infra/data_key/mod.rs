@@ -0,0 +1,11 @@
+pub mod envelope;
+pub mod master_key;
+pub mod model;
+pub mod persist;
+pub mod reencrypt;
+pub mod routes;
+
+pub use envelope::{open, seal_for_owner};
+pub use model::{DataKey, DataKeyStatus, RotateDataKeysDto};
+pub use reencrypt::{reencrypt_batch, ReencryptionReport};
+pub use routes::post_admin_data_keys_rotate_route;
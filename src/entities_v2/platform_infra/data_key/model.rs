@@ -0,0 +1,88 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DataKeyStatus {
+    /// Seals new writes for its owner.
+    Active,
+    /// Only opens values sealed before a rotation.
+    Retired,
+}
+
+impl DataKeyStatus {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            DataKeyStatus::Active => "ACTIVE",
+            DataKeyStatus::Retired => "RETIRED",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "ACTIVE" => DataKeyStatus::Active,
+            _ => DataKeyStatus::Retired,
+        }
+    }
+}
+
+/// A per-user data key, stored wrapped by one of the master keys.
+///
+/// Keys without a user seal platform data that has no owner, such as LLM calls made
+/// outside of an analysis.
+#[derive(Serialize, Debug, Clone)]
+pub struct DataKey {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub master_key_id: String,
+    #[serde(skip_serializing)]
+    pub wrapped_key: String,
+    pub status: DataKeyStatus,
+    pub created_at: NaiveDateTime,
+    pub retired_at: Option<NaiveDateTime>,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::data_keys)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct DataKeyRow {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub master_key_id: String,
+    pub wrapped_key: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub retired_at: Option<NaiveDateTime>,
+}
+
+impl From<DataKeyRow> for DataKey {
+    fn from(row: DataKeyRow) -> Self {
+        DataKey {
+            id: row.id,
+            user_id: row.user_id,
+            master_key_id: row.master_key_id,
+            wrapped_key: row.wrapped_key,
+            status: DataKeyStatus::from_db(&row.status),
+            created_at: row.created_at,
+            retired_at: row.retired_at,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::data_keys)]
+pub(super) struct NewDataKey {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub master_key_id: String,
+    pub wrapped_key: String,
+    pub status: String,
+}
+
+#[derive(Deserialize)]
+pub struct RotateDataKeysDto {
+    /// Rotates only this user's key; all keys when absent.
+    pub user_id: Option<Uuid>,
+}
@@ -0,0 +1,142 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Nullable, Text, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::schema::data_keys;
+
+use super::envelope::{data_key_bytes, generate_data_key, master_keyring};
+use super::model::{DataKey, DataKeyRow, DataKeyStatus};
+
+impl DataKey {
+    pub fn find(id: Uuid, pool: &DbPool) -> Result<DataKey, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = data_keys::table
+            .find(id)
+            .select(DataKeyRow::as_select())
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| {
+                PpdcError::new(
+                    500,
+                    ErrorType::InternalError,
+                    format!("Data key {} not found", id),
+                )
+            })?;
+        Ok(row.into())
+    }
+
+    fn find_active(owner: Option<Uuid>, pool: &DbPool) -> Result<Option<DataKey>, PpdcError> {
+        let mut conn = pool.get()?;
+        let mut query = data_keys::table
+            .filter(data_keys::status.eq(DataKeyStatus::Active.to_db()))
+            .into_boxed();
+        query = match owner {
+            Some(user_id) => query.filter(data_keys::user_id.eq(user_id)),
+            None => query.filter(data_keys::user_id.is_null()),
+        };
+        let row = query
+            .select(DataKeyRow::as_select())
+            .first(&mut conn)
+            .optional()?;
+        Ok(row.map(DataKey::from))
+    }
+
+    /// Returns the owner's active key, creating one under the active master key if needed.
+    pub fn find_or_create_active(owner: Option<Uuid>, pool: &DbPool) -> Result<DataKey, PpdcError> {
+        if let Some(key) = Self::find_active(owner, pool)? {
+            return Ok(key);
+        }
+        let keyring = master_keyring()?.ok_or_else(|| {
+            PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                "AT_REST_MASTER_KEYS is not configured".to_string(),
+            )
+        })?;
+        let wrapped_key = keyring.wrap(&generate_data_key())?;
+        let mut conn = pool.get()?;
+        // Concurrent writers race on the partial unique index; the loser reads the winner's key.
+        sql_query(
+            r#"
+            INSERT INTO data_keys (id, user_id, master_key_id, wrapped_key, status)
+            VALUES ($1, $2, $3, $4, 'ACTIVE')
+            ON CONFLICT ((COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid)))
+            WHERE status = 'ACTIVE'
+            DO NOTHING
+            "#,
+        )
+        .bind::<SqlUuid, _>(Uuid::new_v4())
+        .bind::<Nullable<SqlUuid>, _>(owner)
+        .bind::<Text, _>(keyring.active_id())
+        .bind::<Text, _>(&wrapped_key)
+        .execute(&mut conn)?;
+        Self::find_active(owner, pool)?.ok_or_else(|| {
+            PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                "Failed to create a data key".to_string(),
+            )
+        })
+    }
+
+    /// Retires the active keys of one user, or of every owner when `user_id` is `None`.
+    ///
+    /// Replacement keys are created on the next write; re-encryption moves old values over.
+    pub fn retire_active(user_id: Option<Uuid>, pool: &DbPool) -> Result<usize, PpdcError> {
+        let mut conn = pool.get()?;
+        let mut query = diesel::update(data_keys::table)
+            .filter(data_keys::status.eq(DataKeyStatus::Active.to_db()))
+            .into_boxed();
+        if let Some(user_id) = user_id {
+            query = query.filter(data_keys::user_id.eq(user_id));
+        }
+        let retired = query
+            .set((
+                data_keys::status.eq(DataKeyStatus::Retired.to_db()),
+                data_keys::retired_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(&mut conn)?;
+        Ok(retired)
+    }
+
+    /// Keys still wrapped by a master key other than `master_key_id`.
+    pub fn find_wrapped_by_other_master(
+        master_key_id: &str,
+        limit: i64,
+        pool: &DbPool,
+    ) -> Result<Vec<DataKey>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = data_keys::table
+            .filter(data_keys::master_key_id.ne(master_key_id))
+            .order(data_keys::created_at.asc())
+            .limit(limit)
+            .select(DataKeyRow::as_select())
+            .load(&mut conn)?;
+        Ok(rows.into_iter().map(DataKey::from).collect())
+    }
+
+    /// Rewraps this key under the active master key; the data key itself does not change.
+    pub fn rewrap(self, pool: &DbPool) -> Result<DataKey, PpdcError> {
+        let keyring = master_keyring()?.ok_or_else(|| {
+            PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                "AT_REST_MASTER_KEYS is not configured".to_string(),
+            )
+        })?;
+        let wrapped_key = keyring.wrap(&data_key_bytes(&self)?)?;
+        let mut conn = pool.get()?;
+        let row = diesel::update(data_keys::table.find(self.id))
+            .set((
+                data_keys::master_key_id.eq(keyring.active_id()),
+                data_keys::wrapped_key.eq(wrapped_key),
+            ))
+            .returning(DataKeyRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+}
@@ -0,0 +1,167 @@
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Nullable, Text, Uuid as SqlUuid};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::PpdcError;
+
+use super::envelope::{master_keyring, open, seal_for_owner};
+use super::model::DataKey;
+
+const REWRAP_BATCH_SIZE: i64 = 100;
+
+/// A table with sealed columns and the SQL that resolves each row's owner.
+///
+/// Client-encrypted traces are already ciphertext and are left alone.
+struct SealedTable {
+    table: &'static str,
+    owner_sql: &'static str,
+    from_sql: &'static str,
+    filter_sql: &'static str,
+    columns: &'static [&'static str],
+}
+
+const SEALED_TABLES: &[SealedTable] = &[
+    SealedTable {
+        table: "traces",
+        owner_sql: "t.user_id",
+        from_sql: "traces t",
+        filter_sql: "t.is_encrypted = false",
+        columns: &["content"],
+    },
+    SealedTable {
+        table: "messages",
+        owner_sql: "t.sender_user_id",
+        from_sql: "messages t",
+        filter_sql: "TRUE",
+        columns: &["content"],
+    },
+    SealedTable {
+        table: "llm_calls",
+        owner_sql: "la.user_id",
+        from_sql: "llm_calls t LEFT JOIN landscape_analyses la ON la.id = t.analysis_id",
+        filter_sql: "TRUE",
+        columns: &["prompt", "request", "response", "output", "user_prompt"],
+    },
+    SealedTable {
+        table: "trace_search_chunks",
+        owner_sql: "t.user_id",
+        from_sql: "trace_search_chunks t",
+        filter_sql: "TRUE",
+        columns: &["content"],
+    },
+];
+
+#[derive(QueryableByName)]
+struct StaleValueRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+    #[diesel(sql_type = Nullable<SqlUuid>)]
+    owner_id: Option<Uuid>,
+    #[diesel(sql_type = Text)]
+    value: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ReencryptionReport {
+    pub rewrapped_keys: usize,
+    pub resealed_values: usize,
+    pub skipped_values: usize,
+}
+
+/// Moves at-rest data onto the current keys, one bounded batch per call.
+///
+/// Data keys wrapped by an old master key are rewrapped, then values that are plaintext or
+/// sealed under a retired data key are resealed under their owner's active key. Rows edited
+/// in the meantime are skipped and picked up by the next run.
+pub fn reencrypt_batch(limit: i64, pool: &DbPool) -> Result<ReencryptionReport, PpdcError> {
+    let mut report = ReencryptionReport::default();
+    let Some(keyring) = master_keyring()? else {
+        return Ok(report);
+    };
+    for key in DataKey::find_wrapped_by_other_master(keyring.active_id(), REWRAP_BATCH_SIZE, pool)?
+    {
+        key.rewrap(pool)?;
+        report.rewrapped_keys += 1;
+    }
+    for sealed_table in SEALED_TABLES {
+        for column in sealed_table.columns {
+            let remaining = limit - (report.resealed_values + report.skipped_values) as i64;
+            if remaining <= 0 {
+                return Ok(report);
+            }
+            for row in find_stale_values(sealed_table, column, remaining, pool)? {
+                let plaintext = open(row.value.clone())?;
+                let sealed = seal_for_owner(row.owner_id, &plaintext, pool)?;
+                if replace_value(
+                    sealed_table.table,
+                    column,
+                    row.id,
+                    &row.value,
+                    &sealed,
+                    pool,
+                )? {
+                    report.resealed_values += 1;
+                } else {
+                    report.skipped_values += 1;
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn find_stale_values(
+    sealed_table: &SealedTable,
+    column: &str,
+    limit: i64,
+    pool: &DbPool,
+) -> Result<Vec<StaleValueRow>, PpdcError> {
+    let mut conn = pool.get()?;
+    let query = format!(
+        r#"
+        SELECT t.id, {owner} AS owner_id, t.{column} AS value
+        FROM {from}
+        LEFT JOIN data_keys dk
+          ON dk.status = 'ACTIVE'
+         AND COALESCE(dk.user_id, '00000000-0000-0000-0000-000000000000'::uuid)
+           = COALESCE({owner}, '00000000-0000-0000-0000-000000000000'::uuid)
+        WHERE {filter}
+          AND t.{column} <> ''
+          AND (dk.id IS NULL OR t.{column} NOT LIKE 'enc:v1:' || dk.id::text || ':%')
+        LIMIT $1
+        "#,
+        owner = sealed_table.owner_sql,
+        column = column,
+        from = sealed_table.from_sql,
+        filter = sealed_table.filter_sql,
+    );
+    let rows = sql_query(query)
+        .bind::<BigInt, _>(limit)
+        .load::<StaleValueRow>(&mut conn)?;
+    Ok(rows)
+}
+
+/// Swaps the stored value only if it has not changed since it was read.
+fn replace_value(
+    table: &str,
+    column: &str,
+    id: Uuid,
+    previous: &str,
+    sealed: &str,
+    pool: &DbPool,
+) -> Result<bool, PpdcError> {
+    let mut conn = pool.get()?;
+    let updated = sql_query(format!(
+        "UPDATE {table} SET {column} = $2 WHERE id = $1 AND {column} = $3",
+        table = table,
+        column = column,
+    ))
+    .bind::<SqlUuid, _>(id)
+    .bind::<Text, _>(sealed)
+    .bind::<Text, _>(previous)
+    .execute(&mut conn)?;
+    Ok(updated > 0)
+}
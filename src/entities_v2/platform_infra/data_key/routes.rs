@@ -0,0 +1,59 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Json},
+};
+use serde::Serialize;
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    background_job::{BackgroundJob, BackgroundJobType},
+    error::{ErrorType, PpdcError},
+    session::Session,
+    user::{User, UserRole},
+};
+
+use super::envelope::master_keyring;
+use super::model::{DataKey, RotateDataKeysDto};
+
+#[derive(Serialize)]
+pub struct DataKeyRotation {
+    pub retired_keys: usize,
+    pub reencryption_job: BackgroundJob,
+}
+
+fn ensure_admin(user_id: uuid::Uuid, pool: &DbPool) -> Result<(), PpdcError> {
+    let user = User::find(&user_id, pool)?;
+    if !user.has_role(UserRole::Admin, pool)? {
+        return Err(PpdcError::new(
+            403,
+            ErrorType::ApiError,
+            "Admin role required".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Retires the active data keys and enqueues re-encryption under fresh ones.
+#[debug_handler]
+pub async fn post_admin_data_keys_rotate_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Json(payload): Json<RotateDataKeysDto>,
+) -> Result<Json<DataKeyRotation>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_admin(user_id, &pool)?;
+    if master_keyring()?.is_none() {
+        return Err(PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "AT_REST_MASTER_KEYS is not configured".to_string(),
+        ));
+    }
+    let retired_keys = DataKey::retire_active(payload.user_id, &pool)?;
+    let reencryption_job =
+        BackgroundJob::enqueue_singleton(BackgroundJobType::ReencryptAtRest, &pool)?;
+    Ok(Json(DataKeyRotation {
+        retired_keys,
+        reencryption_job,
+    }))
+}
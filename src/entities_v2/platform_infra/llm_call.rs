@@ -1,6 +1,8 @@
 use crate::db::DbPool;
+use crate::entities_v2::data_key;
 use crate::entities_v2::error::PpdcError;
 use crate::entities_v2::{landscape_analysis::LandscapeAnalysis, session::Session};
+use crate::openai_handler::llm_provider::PromptRef;
 use crate::pagination::{PaginatedResponse, PaginationParams};
 use crate::schema::{landscape_analyses, llm_calls};
 use axum::{
@@ -11,6 +13,8 @@ use axum::{
 use chrono::NaiveDateTime;
 use diesel;
 use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Double, Nullable, Text, Timestamp, Uuid as SqlUuid};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -38,6 +42,11 @@ pub struct LlmCall {
     pub analysis_id: Option<Uuid>,
     pub system_prompt: String,
     pub user_prompt: String,
+    pub logical_call_id: Option<Uuid>,
+    pub attempt: i32,
+    pub prompt_id: Option<String>,
+    pub prompt_version: Option<i32>,
+    pub user_id: Option<Uuid>,
 }
 
 #[derive(Insertable, AsChangeset)]
@@ -60,6 +69,11 @@ pub struct NewLlmCall {
     pub analysis_id: Uuid,
     pub system_prompt: String,
     pub user_prompt: String,
+    pub logical_call_id: Option<Uuid>,
+    pub attempt: i32,
+    pub prompt_id: Option<String>,
+    pub prompt_version: Option<i32>,
+    pub user_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -70,7 +84,121 @@ pub struct LlmCallFiltersQuery {
     pub created_at_to: Option<NaiveDateTime>,
 }
 
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmCostGroupBy {
+    Analysis,
+    Lens,
+    User,
+    Day,
+}
+
+impl LlmCostGroupBy {
+    fn group_key_sql(self) -> &'static str {
+        match self {
+            Self::Analysis => "lc.analysis_id::text",
+            Self::Lens => "las.lens_id::text",
+            Self::User => "la.user_id::text",
+            Self::Day => "to_char(lc.created_at, 'YYYY-MM-DD')",
+        }
+    }
+
+    fn order_by_sql(self) -> &'static str {
+        match self {
+            Self::Day => "group_key DESC",
+            _ => "price DESC, group_key",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LlmCostFiltersQuery {
+    pub group_by: LlmCostGroupBy,
+    pub created_at_from: Option<NaiveDateTime>,
+    pub created_at_to: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, QueryableByName)]
+pub struct LlmCostAggregate {
+    #[diesel(sql_type = Text)]
+    pub group_key: String,
+    #[diesel(sql_type = BigInt)]
+    pub calls_count: i64,
+    #[diesel(sql_type = BigInt)]
+    pub input_tokens_used: i64,
+    #[diesel(sql_type = BigInt)]
+    pub reasoning_tokens_used: i64,
+    #[diesel(sql_type = BigInt)]
+    pub output_tokens_used: i64,
+    #[diesel(sql_type = Double)]
+    pub price: f64,
+    #[diesel(sql_type = Text)]
+    pub currency: String,
+}
+
 impl LlmCall {
+    /// Opens the payload columns sealed at rest; the system prompt is stored in the clear.
+    fn opened(self) -> Result<Self, PpdcError> {
+        Ok(LlmCall {
+            prompt: data_key::open(self.prompt)?,
+            request: data_key::open(self.request)?,
+            response: data_key::open(self.response)?,
+            output: data_key::open(self.output)?,
+            user_prompt: data_key::open(self.user_prompt)?,
+            ..self
+        })
+    }
+
+    fn opened_all(llm_calls: Vec<Self>) -> Result<Vec<Self>, PpdcError> {
+        llm_calls.into_iter().map(LlmCall::opened).collect()
+    }
+
+    /// Sums tokens and price per analysis, lens, user or day. An analysis scoped to
+    /// several lenses counts towards each of them.
+    pub fn aggregate_costs(
+        group_by: LlmCostGroupBy,
+        user_id: Option<Uuid>,
+        created_at_from: Option<NaiveDateTime>,
+        created_at_to: Option<NaiveDateTime>,
+        db: &DbPool,
+    ) -> Result<Vec<LlmCostAggregate>, PpdcError> {
+        let mut conn = db.get()?;
+        let lens_join = if group_by == LlmCostGroupBy::Lens {
+            "INNER JOIN lens_analysis_scopes las ON las.landscape_analysis_id = la.id"
+        } else {
+            ""
+        };
+        let query = format!(
+            r#"
+            SELECT
+                {group_key} AS group_key,
+                COUNT(*)::bigint AS calls_count,
+                COALESCE(SUM(lc.input_tokens_used), 0)::bigint AS input_tokens_used,
+                COALESCE(SUM(lc.reasoning_tokens_used), 0)::bigint AS reasoning_tokens_used,
+                COALESCE(SUM(lc.output_tokens_used), 0)::bigint AS output_tokens_used,
+                COALESCE(SUM(lc.price), 0)::float8 AS price,
+                COALESCE(MIN(lc.currency), 'USD') AS currency
+            FROM llm_calls lc
+            INNER JOIN landscape_analyses la ON la.id = lc.analysis_id
+            {lens_join}
+            WHERE ($1::uuid IS NULL OR la.user_id = $1)
+              AND ($2::timestamp IS NULL OR lc.created_at >= $2)
+              AND ($3::timestamp IS NULL OR lc.created_at <= $3)
+            GROUP BY 1
+            ORDER BY {order_by}
+            "#,
+            group_key = group_by.group_key_sql(),
+            lens_join = lens_join,
+            order_by = group_by.order_by_sql(),
+        );
+        let rows = sql_query(query)
+            .bind::<Nullable<SqlUuid>, _>(user_id)
+            .bind::<Nullable<Timestamp>, _>(created_at_from)
+            .bind::<Nullable<Timestamp>, _>(created_at_to)
+            .load::<LlmCostAggregate>(&mut conn)?;
+        Ok(rows)
+    }
+
     pub fn get_paginated_for_user(
         user_id: Uuid,
         offset: i64,
@@ -124,7 +252,7 @@ impl LlmCall {
             .offset(offset)
             .limit(limit)
             .load::<Self>(&mut conn)?;
-        Ok((llm_calls, total))
+        Ok((Self::opened_all(llm_calls)?, total))
     }
 
     pub fn get_by_id_for_user(id: Uuid, user_id: Uuid, db: &DbPool) -> Result<Self, PpdcError> {
@@ -138,7 +266,7 @@ impl LlmCall {
             .filter(landscape_analyses::user_id.eq(user_id))
             .select(LlmCall::as_select())
             .first::<Self>(&mut conn)?;
-        Ok(llm_call)
+        llm_call.opened()
     }
 
     pub fn get_paginated(offset: i64, limit: i64, db: &DbPool) -> Result<Vec<Self>, PpdcError> {
@@ -149,7 +277,7 @@ impl LlmCall {
             .offset(offset)
             .limit(limit)
             .load::<Self>(&mut conn)?;
-        Ok(llm_calls)
+        Self::opened_all(llm_calls)
     }
     pub fn get_by_id(id: Uuid, db: &DbPool) -> Result<Self, PpdcError> {
         let mut conn = db.get()?;
@@ -157,7 +285,7 @@ impl LlmCall {
             .select(LlmCall::as_select())
             .filter(llm_calls::id.eq(id))
             .first::<Self>(&mut conn)?;
-        Ok(llm_call)
+        llm_call.opened()
     }
     pub fn get_by_analysis_id(analysis_id: Uuid, db: &DbPool) -> Result<Vec<Self>, PpdcError> {
         let mut conn = db.get()?;
@@ -165,7 +293,7 @@ impl LlmCall {
             .select(LlmCall::as_select())
             .filter(llm_calls::analysis_id.eq(analysis_id))
             .load::<Self>(&mut conn)?;
-        Ok(llm_calls)
+        Self::opened_all(llm_calls)
     }
 
     pub fn get_by_analysis_id_for_user(
@@ -230,7 +358,7 @@ impl LlmCall {
             .offset(offset)
             .limit(limit)
             .load::<Self>(&mut conn)?;
-        Ok((llm_calls, total))
+        Ok((Self::opened_all(llm_calls)?, total))
     }
 }
 
@@ -272,16 +400,50 @@ impl NewLlmCall {
             analysis_id,
             system_prompt,
             user_prompt,
+            logical_call_id: None,
+            attempt: 1,
+            prompt_id: None,
+            prompt_version: None,
+            user_id: None,
         }
     }
 
+    /// Marks this row as attempt number `attempt` of one logical (retried) call.
+    pub fn with_attempt(mut self, logical_call_id: Uuid, attempt: i32) -> Self {
+        self.logical_call_id = Some(logical_call_id);
+        self.attempt = attempt;
+        self
+    }
+
+    /// Stamps the registry prompt id and version the call was built from.
+    pub fn with_prompt(mut self, prompt: Option<&PromptRef>) -> Self {
+        self.prompt_id = prompt.map(|prompt| prompt.prompt_id.clone());
+        self.prompt_version = prompt.map(|prompt| prompt.version);
+        self
+    }
+
+    /// Seals the payload columns under the analysis owner's data key before inserting.
     pub fn create(self, db: &DbPool) -> Result<LlmCall, PpdcError> {
         let mut conn = db.get()?;
+        let owner = landscape_analyses::table
+            .find(self.analysis_id)
+            .select(landscape_analyses::user_id)
+            .first::<Uuid>(&mut conn)
+            .optional()?;
+        let sealed = NewLlmCall {
+            prompt: data_key::seal_for_owner(owner, &self.prompt, db)?,
+            request: data_key::seal_for_owner(owner, &self.request, db)?,
+            response: data_key::seal_for_owner(owner, &self.response, db)?,
+            output: data_key::seal_for_owner(owner, &self.output, db)?,
+            user_prompt: data_key::seal_for_owner(owner, &self.user_prompt, db)?,
+            user_id: owner,
+            ..self
+        };
         let llm_call = diesel::insert_into(llm_calls::table)
-            .values(&self)
+            .values(&sealed)
             .returning(LlmCall::as_returning())
             .get_result(&mut conn)?;
-        Ok(llm_call)
+        llm_call.opened()
     }
 }
 
@@ -304,6 +466,23 @@ pub async fn get_llm_calls_route(
     Ok(Json(PaginatedResponse::new(llm_calls, pagination, total)))
 }
 
+#[debug_handler]
+pub async fn get_llm_call_costs_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Query(filters): Query<LlmCostFiltersQuery>,
+) -> Result<Json<Vec<LlmCostAggregate>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let aggregates = LlmCall::aggregate_costs(
+        filters.group_by,
+        Some(user_id),
+        filters.created_at_from,
+        filters.created_at_to,
+        &pool,
+    )?;
+    Ok(Json(aggregates))
+}
+
 #[debug_handler]
 pub async fn get_llm_calls_by_analysis_id_route(
     Extension(pool): Extension<DbPool>,
@@ -10,6 +10,7 @@ use uuid::Uuid;
 
 use crate::db::DbPool;
 use crate::entities_v2::{
+    background_job::{BackgroundJob, BackgroundJobType},
     error::{ErrorType, PpdcError},
     journal::Journal,
     post::{DigestVisiblePost, Post},
@@ -426,7 +427,7 @@ pub fn generate_shared_journal_daily_digests(
 pub async fn post_generate_shared_journal_daily_digests_route(
     Extension(pool): Extension<DbPool>,
     headers: HeaderMap,
-) -> Result<Json<SharedJournalDailyDigestsGenerationResponse>, PpdcError> {
+) -> Result<Json<BackgroundJob>, PpdcError> {
     let provided_token = headers
         .get("x-internal-cron-token")
         .and_then(|value| value.to_str().ok())
@@ -436,5 +437,8 @@ pub async fn post_generate_shared_journal_daily_digests_route(
         return Err(PpdcError::unauthorized());
     }
 
-    Ok(Json(generate_shared_journal_daily_digests(&pool)?))
+    Ok(Json(BackgroundJob::enqueue_singleton(
+        BackgroundJobType::GenerateSharedJournalDailyDigests,
+        &pool,
+    )?))
 }
@@ -13,10 +13,12 @@ pub use outbound_email::{
     process_pending_email, process_pending_emails, NewOutboundEmail, OutboundEmail,
     OutboundEmailProvider, OutboundEmailStatus,
 };
-pub use routes::post_process_pending_emails_route;
+pub use routes::{
+    post_process_pending_emails_route, process_due_pending_emails, PendingEmailsProcessResponse,
+};
 pub use templates::{
-    daily_recap_email, follow_request_received_email, journal_access_granted_email,
-    message_received_email, new_user_signup_email, password_reset_email,
-    shared_journal_daily_digest_email, shared_trace_finalized_email, EmailTemplate,
-    SharedJournalDigestEmailItem,
+    account_export_ready_email, daily_recap_email, follow_request_received_email,
+    journal_access_granted_email, message_received_email, new_user_signup_email,
+    password_reset_email, shared_journal_daily_digest_email, shared_trace_finalized_email,
+    EmailTemplate, SharedJournalDigestEmailItem,
 };
@@ -3,6 +3,7 @@ use serde::Serialize;
 use uuid::Uuid;
 
 use crate::db::DbPool;
+use crate::entities_v2::background_job::{BackgroundJob, BackgroundJobType};
 use crate::entities_v2::error::PpdcError;
 use crate::entities_v2::platform_infra::mailer::{
     process_pending_email, OutboundEmail, OutboundEmailStatus,
@@ -24,22 +25,12 @@ pub struct PendingEmailsProcessResponse {
     pub failed: Vec<PendingEmailsProcessError>,
 }
 
-#[debug_handler]
-pub async fn post_process_pending_emails_route(
-    Extension(pool): Extension<DbPool>,
-    headers: HeaderMap,
-) -> Result<Json<PendingEmailsProcessResponse>, PpdcError> {
-    let provided_token = headers
-        .get("x-internal-cron-token")
-        .and_then(|value| value.to_str().ok())
-        .ok_or_else(PpdcError::unauthorized)?;
-
-    if provided_token != environment::get_internal_cron_token() {
-        return Err(PpdcError::unauthorized());
-    }
-
+/// Sends a batch of due outbound emails claimed for this call.
+pub async fn process_due_pending_emails(
+    pool: &DbPool,
+) -> Result<PendingEmailsProcessResponse, PpdcError> {
     let claimed_emails =
-        OutboundEmail::claim_due_pending(EMAIL_CRON_BATCH_LIMIT, Uuid::new_v4(), &pool)?;
+        OutboundEmail::claim_due_pending(EMAIL_CRON_BATCH_LIMIT, Uuid::new_v4(), pool)?;
     let candidate_email_ids = claimed_emails
         .iter()
         .map(|email| email.id)
@@ -48,7 +39,7 @@ pub async fn post_process_pending_emails_route(
     let mut failed = Vec::new();
 
     for email in claimed_emails {
-        match process_pending_email(email.id, &pool).await {
+        match process_pending_email(email.id, pool).await {
             Ok(processed_email) => match processed_email.status_enum() {
                 OutboundEmailStatus::Sent => processed_email_ids.push(email.id),
                 OutboundEmailStatus::Failed => failed.push(PendingEmailsProcessError {
@@ -66,9 +57,29 @@ pub async fn post_process_pending_emails_route(
         }
     }
 
-    Ok(Json(PendingEmailsProcessResponse {
+    Ok(PendingEmailsProcessResponse {
         candidate_email_ids,
         processed_email_ids,
         failed,
-    }))
+    })
+}
+
+#[debug_handler]
+pub async fn post_process_pending_emails_route(
+    Extension(pool): Extension<DbPool>,
+    headers: HeaderMap,
+) -> Result<Json<BackgroundJob>, PpdcError> {
+    let provided_token = headers
+        .get("x-internal-cron-token")
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(PpdcError::unauthorized)?;
+
+    if provided_token != environment::get_internal_cron_token() {
+        return Err(PpdcError::unauthorized());
+    }
+
+    Ok(Json(BackgroundJob::enqueue_singleton(
+        BackgroundJobType::ProcessPendingEmails,
+        &pool,
+    )?))
 }
@@ -23,6 +23,8 @@ const JOURNAL_ACCESS_GRANTED_TEXT: &str = include_str!("templates/journal_access
 const JOURNAL_ACCESS_GRANTED_HTML: &str = include_str!("templates/journal_access_granted.html");
 const DAILY_RECAP_FEEDBACK_TEXT: &str = include_str!("templates/daily_recap_feedback.txt");
 const DAILY_RECAP_FEEDBACK_HTML: &str = include_str!("templates/daily_recap_feedback.html");
+const ACCOUNT_EXPORT_READY_TEXT: &str = include_str!("templates/account_export_ready.txt");
+const ACCOUNT_EXPORT_READY_HTML: &str = include_str!("templates/account_export_ready.html");
 const SHARED_JOURNAL_DAILY_DIGEST_TEXT: &str =
     include_str!("templates/shared_journal_daily_digest.txt");
 const SHARED_JOURNAL_DAILY_DIGEST_HTML: &str =
@@ -375,6 +377,40 @@ pub fn password_reset_email(recipient_display_name: &str, reset_url: &str) -> Em
     })
 }
 
+pub fn account_export_ready_email(
+    recipient_display_name: &str,
+    download_url: &str,
+    expires_at: NaiveDateTime,
+) -> EmailTemplate {
+    let subject = "Votre export de données hupo est prêt".to_string();
+    let expires_at = format!("{} (UTC)", expires_at.format("%d/%m/%Y à %H:%M"));
+    let text_body = render_template(
+        ACCOUNT_EXPORT_READY_TEXT,
+        &[
+            ("recipient_display_name", recipient_display_name.to_string()),
+            ("download_url", download_url.to_string()),
+            ("expires_at", expires_at.clone()),
+        ],
+    );
+    let html_body = render_template(
+        ACCOUNT_EXPORT_READY_HTML,
+        &[
+            (
+                "recipient_display_name",
+                escape_html(recipient_display_name),
+            ),
+            ("download_url", escape_html(download_url)),
+            ("expires_at", escape_html(&expires_at)),
+        ],
+    );
+
+    append_contact_preferences_footer(EmailTemplate {
+        subject,
+        text_body: Some(text_body),
+        html_body: Some(html_body),
+    })
+}
+
 pub fn new_user_signup_email(first_name: &str, last_name: &str, users_url: &str) -> EmailTemplate {
     let subject = format!("Nouvel utilisateur inscrit : {} {}", first_name, last_name);
     let text_body = render_template(
@@ -1,9 +1,13 @@
+pub mod account_export;
 pub mod asset;
+pub mod background_job;
+pub mod data_key;
 pub mod device;
 pub mod error;
 pub mod llm_call;
 pub mod mailer;
 pub mod notification;
+pub mod prompt_version;
 pub mod push;
 pub mod session;
 pub mod transcription;
@@ -0,0 +1,72 @@
+use diesel::prelude::*;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::schema::prompt_versions;
+use crate::work_analyzer::prompt_registry;
+
+use super::model::{PromptCatalogEntry, PromptVersion};
+
+impl PromptVersion {
+    pub fn find_active(prompt_id: &str, pool: &DbPool) -> Result<Option<PromptVersion>, PpdcError> {
+        let mut conn = pool.get()?;
+        let version = prompt_versions::table
+            .filter(prompt_versions::prompt_id.eq(prompt_id))
+            .filter(prompt_versions::is_active.eq(true))
+            .select(PromptVersion::as_select())
+            .first::<PromptVersion>(&mut conn)
+            .optional()?;
+        Ok(version)
+    }
+
+    pub fn find_version(
+        prompt_id: &str,
+        version: i32,
+        pool: &DbPool,
+    ) -> Result<PromptVersion, PpdcError> {
+        let mut conn = pool.get()?;
+        prompt_versions::table
+            .filter(prompt_versions::prompt_id.eq(prompt_id))
+            .filter(prompt_versions::version.eq(version))
+            .select(PromptVersion::as_select())
+            .first::<PromptVersion>(&mut conn)
+            .optional()?
+            .ok_or_else(|| {
+                PpdcError::new(
+                    404,
+                    ErrorType::ApiError,
+                    format!("Prompt {} has no version {}", prompt_id, version),
+                )
+            })
+    }
+
+    /// Lists every registered step with its stored versions, newest first.
+    pub fn find_catalog(pool: &DbPool) -> Result<Vec<PromptCatalogEntry>, PpdcError> {
+        let mut conn = pool.get()?;
+        let versions = prompt_versions::table
+            .order((
+                prompt_versions::prompt_id.asc(),
+                prompt_versions::version.desc(),
+            ))
+            .select(PromptVersion::as_select())
+            .load::<PromptVersion>(&mut conn)?;
+        Ok(prompt_registry::compiled_prompt_ids()
+            .map(|prompt_id| {
+                let versions = versions
+                    .iter()
+                    .filter(|version| version.prompt_id == prompt_id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                PromptCatalogEntry {
+                    prompt_id: prompt_id.to_string(),
+                    active_version: versions
+                        .iter()
+                        .find(|version| version.is_active)
+                        .map(|version| version.version)
+                        .unwrap_or(prompt_registry::COMPILED_VERSION),
+                    versions,
+                }
+            })
+            .collect())
+    }
+}
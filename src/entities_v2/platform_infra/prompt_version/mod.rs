@@ -0,0 +1,14 @@
+pub mod hydrate;
+pub mod model;
+pub mod persist;
+pub mod routes;
+
+pub use model::{
+    ActivatePromptVersionDto, NewPromptVersionDto, PromptCatalogEntry, PromptVersion,
+    PromptVersionSource,
+};
+pub use persist::import_directory;
+pub use routes::{
+    get_admin_prompt_version_route, get_admin_prompts_route, post_admin_prompt_activate_route,
+    post_admin_prompt_version_route, post_admin_prompts_import_route,
+};
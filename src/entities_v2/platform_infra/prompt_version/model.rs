@@ -0,0 +1,59 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptVersionSource {
+    Admin,
+    Directory,
+}
+
+impl PromptVersionSource {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            PromptVersionSource::Admin => "ADMIN",
+            PromptVersionSource::Directory => "DIRECTORY",
+        }
+    }
+}
+
+/// A stored revision of a pipeline step prompt; version 0 is reserved for the compiled one.
+#[derive(Serialize, Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::prompt_versions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PromptVersion {
+    pub id: Uuid,
+    pub prompt_id: String,
+    pub version: i32,
+    pub system_prompt: String,
+    pub schema: Option<String>,
+    pub source: String,
+    pub is_active: bool,
+    pub activated_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PromptCatalogEntry {
+    pub prompt_id: String,
+    /// Active stored version, or 0 when the compiled prompt is used.
+    pub active_version: i32,
+    pub versions: Vec<PromptVersion>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NewPromptVersionDto {
+    pub system_prompt: String,
+    /// Omitted to keep the compiled schema.
+    #[serde(default)]
+    pub schema: Option<serde_json::Value>,
+    #[serde(default)]
+    pub activate: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ActivatePromptVersionDto {
+    pub version: i32,
+}
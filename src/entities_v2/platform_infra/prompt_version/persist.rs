@@ -0,0 +1,154 @@
+use std::path::Path;
+
+use chrono::Utc;
+use diesel::dsl::max;
+use diesel::prelude::*;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::schema::prompt_versions;
+use crate::work_analyzer::prompt_registry;
+
+use super::model::{PromptVersion, PromptVersionSource};
+
+fn ensure_registered(prompt_id: &str) -> Result<(), PpdcError> {
+    if !prompt_registry::is_registered(prompt_id) {
+        return Err(PpdcError::new(
+            404,
+            ErrorType::ApiError,
+            format!("Unknown prompt id {}", prompt_id),
+        ));
+    }
+    Ok(())
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> PpdcError {
+    PpdcError::new(
+        500,
+        ErrorType::InternalError,
+        format!("Cannot read {}: {}", path.display(), err),
+    )
+}
+
+impl PromptVersion {
+    /// Stores a prompt as the next version of a step, without activating it.
+    pub fn create_next(
+        prompt_id: &str,
+        system_prompt: &str,
+        schema: Option<String>,
+        source: PromptVersionSource,
+        pool: &DbPool,
+    ) -> Result<PromptVersion, PpdcError> {
+        ensure_registered(prompt_id)?;
+        if system_prompt.trim().is_empty() {
+            return Err(PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                "system_prompt must not be empty".to_string(),
+            ));
+        }
+        let mut conn = pool.get()?;
+        let version = conn.transaction::<PromptVersion, diesel::result::Error, _>(|conn| {
+            let latest = prompt_versions::table
+                .filter(prompt_versions::prompt_id.eq(prompt_id))
+                .select(max(prompt_versions::version))
+                .first::<Option<i32>>(conn)?;
+            diesel::insert_into(prompt_versions::table)
+                .values((
+                    prompt_versions::prompt_id.eq(prompt_id),
+                    prompt_versions::version.eq(latest.unwrap_or(0) + 1),
+                    prompt_versions::system_prompt.eq(system_prompt),
+                    prompt_versions::schema.eq(schema),
+                    prompt_versions::source.eq(source.to_db()),
+                ))
+                .returning(PromptVersion::as_returning())
+                .get_result::<PromptVersion>(conn)
+        })?;
+        Ok(version)
+    }
+
+    /// Makes a stored version the one used by the pipeline; version 0 reverts to the compiled prompt.
+    pub fn activate(prompt_id: &str, version: i32, pool: &DbPool) -> Result<(), PpdcError> {
+        ensure_registered(prompt_id)?;
+        if version != prompt_registry::COMPILED_VERSION {
+            PromptVersion::find_version(prompt_id, version, pool)?;
+        }
+        let mut conn = pool.get()?;
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            diesel::update(
+                prompt_versions::table
+                    .filter(prompt_versions::prompt_id.eq(prompt_id))
+                    .filter(prompt_versions::is_active.eq(true)),
+            )
+            .set(prompt_versions::is_active.eq(false))
+            .execute(conn)?;
+            diesel::update(
+                prompt_versions::table
+                    .filter(prompt_versions::prompt_id.eq(prompt_id))
+                    .filter(prompt_versions::version.eq(version)),
+            )
+            .set((
+                prompt_versions::is_active.eq(true),
+                prompt_versions::activated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+            Ok(())
+        })?;
+        prompt_registry::invalidate_cache();
+        Ok(())
+    }
+}
+
+/// Imports `<dir>/<prompt_id>/v<N>/system.md` (plus an optional `schema.json`) as version N.
+/// Versions already stored are left untouched; returns the newly imported ones.
+pub fn import_directory(dir: &Path, pool: &DbPool) -> Result<Vec<PromptVersion>, PpdcError> {
+    let mut imported = vec![];
+    for prompt_id in prompt_registry::compiled_prompt_ids() {
+        let prompt_dir = dir.join(prompt_id);
+        if !prompt_dir.is_dir() {
+            continue;
+        }
+        let entries = std::fs::read_dir(&prompt_dir).map_err(|err| io_error(&prompt_dir, err))?;
+        for entry in entries {
+            let version_dir = entry.map_err(|err| io_error(&prompt_dir, err))?.path();
+            let Some(version) = version_dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix('v'))
+                .and_then(|number| number.parse::<i32>().ok())
+                .filter(|version| *version > prompt_registry::COMPILED_VERSION)
+            else {
+                continue;
+            };
+            let system_path = version_dir.join("system.md");
+            let system_prompt =
+                std::fs::read_to_string(&system_path).map_err(|err| io_error(&system_path, err))?;
+            let schema_path = version_dir.join("schema.json");
+            let schema = if schema_path.is_file() {
+                let schema = std::fs::read_to_string(&schema_path)
+                    .map_err(|err| io_error(&schema_path, err))?;
+                serde_json::from_str::<serde_json::Value>(&schema)?;
+                Some(schema)
+            } else {
+                None
+            };
+
+            let mut conn = pool.get()?;
+            let inserted = diesel::insert_into(prompt_versions::table)
+                .values((
+                    prompt_versions::prompt_id.eq(prompt_id),
+                    prompt_versions::version.eq(version),
+                    prompt_versions::system_prompt.eq(&system_prompt),
+                    prompt_versions::schema.eq(&schema),
+                    prompt_versions::source.eq(PromptVersionSource::Directory.to_db()),
+                ))
+                .on_conflict((prompt_versions::prompt_id, prompt_versions::version))
+                .do_nothing()
+                .returning(PromptVersion::as_returning())
+                .get_result::<PromptVersion>(&mut conn)
+                .optional()?;
+            imported.extend(inserted);
+        }
+    }
+    Ok(imported)
+}
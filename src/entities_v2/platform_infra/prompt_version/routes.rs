@@ -0,0 +1,115 @@
+use std::path::Path as FsPath;
+
+use axum::{
+    debug_handler,
+    extract::{Extension, Json, Path},
+};
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    error::{ErrorType, PpdcError},
+    session::Session,
+    user::{User, UserRole},
+};
+use crate::environment;
+
+use super::model::{
+    ActivatePromptVersionDto, NewPromptVersionDto, PromptCatalogEntry, PromptVersion,
+    PromptVersionSource,
+};
+use super::persist::import_directory;
+
+fn ensure_admin(user_id: uuid::Uuid, pool: &DbPool) -> Result<(), PpdcError> {
+    let user = User::find(&user_id, pool)?;
+    if !user.has_role(UserRole::Admin, pool)? {
+        return Err(PpdcError::new(
+            403,
+            ErrorType::ApiError,
+            "Admin role required".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn get_admin_prompts_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<PromptCatalogEntry>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_admin(user_id, &pool)?;
+    Ok(Json(PromptVersion::find_catalog(&pool)?))
+}
+
+#[debug_handler]
+pub async fn get_admin_prompt_version_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path((prompt_id, version)): Path<(String, i32)>,
+) -> Result<Json<PromptVersion>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_admin(user_id, &pool)?;
+    Ok(Json(PromptVersion::find_version(
+        &prompt_id, version, &pool,
+    )?))
+}
+
+#[debug_handler]
+pub async fn post_admin_prompt_version_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(prompt_id): Path<String>,
+    Json(payload): Json<NewPromptVersionDto>,
+) -> Result<Json<PromptVersion>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_admin(user_id, &pool)?;
+    let schema = payload.schema.as_ref().map(|schema| schema.to_string());
+    let version = PromptVersion::create_next(
+        &prompt_id,
+        &payload.system_prompt,
+        schema,
+        PromptVersionSource::Admin,
+        &pool,
+    )?;
+    if payload.activate {
+        PromptVersion::activate(&prompt_id, version.version, &pool)?;
+        return Ok(Json(PromptVersion::find_version(
+            &prompt_id,
+            version.version,
+            &pool,
+        )?));
+    }
+    Ok(Json(version))
+}
+
+/// Switches the version used by a step; `version: 0` goes back to the compiled prompt.
+#[debug_handler]
+pub async fn post_admin_prompt_activate_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(prompt_id): Path<String>,
+    Json(payload): Json<ActivatePromptVersionDto>,
+) -> Result<Json<Vec<PromptCatalogEntry>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_admin(user_id, &pool)?;
+    PromptVersion::activate(&prompt_id, payload.version, &pool)?;
+    Ok(Json(PromptVersion::find_catalog(&pool)?))
+}
+
+/// Imports the versions found under `PROMPT_REGISTRY_DIR`; they still need to be activated.
+#[debug_handler]
+pub async fn post_admin_prompts_import_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<Vec<PromptVersion>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_admin(user_id, &pool)?;
+    let dir = environment::get_prompt_registry_dir().ok_or_else(|| {
+        PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "PROMPT_REGISTRY_DIR is not configured".to_string(),
+        )
+    })?;
+    Ok(Json(import_directory(FsPath::new(&dir), &pool)?))
+}
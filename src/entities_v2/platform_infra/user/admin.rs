@@ -1,6 +1,7 @@
 use crate::db::DbPool;
 use crate::entities_v2::{
     error::{ErrorType, PpdcError},
+    llm_call::{LlmCall, LlmCostAggregate, LlmCostGroupBy},
     session::Session,
 };
 use crate::pagination::{PaginatedResponse, PaginationParams};
@@ -14,7 +15,7 @@ use chrono_tz::Tz;
 use diesel::prelude::*;
 use diesel::sql_query;
 use diesel::sql_types::{BigInt, Date, Text, Uuid as SqlUuid};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::enums::{UserPrincipalType, UserRole};
@@ -376,6 +377,31 @@ pub async fn get_admin_recent_user_activity_route(
     Ok(Json(PaginatedResponse::new(payload, pagination, total)))
 }
 
+#[derive(Deserialize)]
+pub struct AdminLlmCostsQuery {
+    pub group_by: LlmCostGroupBy,
+    pub user_id: Option<Uuid>,
+    pub created_at_from: Option<chrono::NaiveDateTime>,
+    pub created_at_to: Option<chrono::NaiveDateTime>,
+}
+
+#[debug_handler]
+pub async fn get_admin_llm_costs_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Query(params): Query<AdminLlmCostsQuery>,
+) -> Result<Json<Vec<LlmCostAggregate>>, PpdcError> {
+    let _admin_user = ensure_admin_session_user(&session, &pool)?;
+    let aggregates = LlmCall::aggregate_costs(
+        params.group_by,
+        params.user_id,
+        params.created_at_from,
+        params.created_at_to,
+        &pool,
+    )?;
+    Ok(Json(aggregates))
+}
+
 #[debug_handler]
 pub async fn get_admin_platform_overview_route(
     Extension(pool): Extension<DbPool>,
@@ -60,7 +60,9 @@ pub struct User {
     pub journal_theme: JournalTheme,
     pub current_lens_id: Option<Uuid>,
     pub week_analysis_weekday: WeekAnalysisWeekday,
+    pub monthly_recap_enabled: bool,
     pub timezone: String,
+    pub locale: String,
     pub context_anchor_at: Option<NaiveDateTime>,
     pub welcome_message: Option<String>,
     pub home_focus_view: HomeFocusView,
@@ -120,7 +122,9 @@ pub struct UserPseudonymizedAuthentifiedResponse {
     pub journal_theme: JournalTheme,
     pub current_lens_id: Option<Uuid>,
     pub week_analysis_weekday: WeekAnalysisWeekday,
+    pub monthly_recap_enabled: bool,
     pub timezone: String,
+    pub locale: String,
     pub context_anchor_at: Option<NaiveDateTime>,
     pub welcome_message: Option<String>,
     pub home_focus_view: HomeFocusView,
@@ -163,7 +167,9 @@ impl UserPseudonymizedAuthentifiedResponse {
             journal_theme: user.journal_theme,
             current_lens_id: user.current_lens_id,
             week_analysis_weekday: user.week_analysis_weekday,
+            monthly_recap_enabled: user.monthly_recap_enabled,
             timezone: user.timezone.clone(),
+            locale: user.locale.clone(),
             context_anchor_at: user.context_anchor_at,
             welcome_message: user.welcome_message.clone(),
             home_focus_view: user.home_focus_view,
@@ -265,7 +271,9 @@ pub struct UserPseudonymizedResponse {
     pub journal_theme: JournalTheme,
     pub current_lens_id: Option<Uuid>,
     pub week_analysis_weekday: WeekAnalysisWeekday,
+    pub monthly_recap_enabled: bool,
     pub timezone: String,
+    pub locale: String,
     pub context_anchor_at: Option<NaiveDateTime>,
     pub welcome_message: Option<String>,
     pub home_focus_view: HomeFocusView,
@@ -302,7 +310,9 @@ impl UserPseudonymizedResponse {
             journal_theme: user.journal_theme,
             current_lens_id: user.current_lens_id,
             week_analysis_weekday: user.week_analysis_weekday,
+            monthly_recap_enabled: user.monthly_recap_enabled,
             timezone: user.timezone.clone(),
+            locale: user.locale.clone(),
             context_anchor_at: user.context_anchor_at,
             welcome_message: user.welcome_message.clone(),
             home_focus_view: user.home_focus_view,
@@ -426,7 +436,9 @@ pub struct NewUser {
     pub journal_theme: Option<JournalTheme>,
     pub current_lens_id: Option<Uuid>,
     pub week_analysis_weekday: Option<WeekAnalysisWeekday>,
+    pub monthly_recap_enabled: Option<bool>,
     pub timezone: Option<String>,
+    pub locale: Option<String>,
     pub context_anchor_at: Option<NaiveDateTime>,
     pub welcome_message: Option<String>,
     pub home_focus_view: Option<HomeFocusView>,
@@ -489,7 +501,9 @@ impl NewServiceUserDto {
             journal_theme: None,
             current_lens_id: None,
             week_analysis_weekday: None,
+            monthly_recap_enabled: None,
             timezone: Some("UTC".to_string()),
+            locale: None,
             context_anchor_at: None,
             welcome_message: self.welcome_message,
             home_focus_view: None,
@@ -527,7 +541,9 @@ impl NewServiceUserDto {
             journal_theme: Some(existing_user.journal_theme),
             current_lens_id: existing_user.current_lens_id,
             week_analysis_weekday: Some(existing_user.week_analysis_weekday),
+            monthly_recap_enabled: Some(existing_user.monthly_recap_enabled),
             timezone: Some(existing_user.timezone.clone()),
+            locale: Some(existing_user.locale.clone()),
             context_anchor_at: existing_user.context_anchor_at,
             welcome_message: self.welcome_message,
             home_focus_view: Some(existing_user.home_focus_view),
@@ -573,6 +589,9 @@ impl NewUser {
         if payload.ai_features_enabled.is_none() {
             payload.ai_features_enabled = Some(true);
         }
+        if payload.monthly_recap_enabled.is_none() {
+            payload.monthly_recap_enabled = Some(false);
+        }
         if payload.principal_type == Some(UserPrincipalType::Service) {
             if payload.mentor_specific_prompt.is_none() {
                 payload.mentor_specific_prompt = Some(String::new());
@@ -1139,7 +1158,9 @@ mod tests {
             journal_theme: JournalTheme::Classic,
             current_lens_id: None,
             week_analysis_weekday: WeekAnalysisWeekday::Monday,
+            monthly_recap_enabled: false,
             timezone: "Europe/Monaco".to_string(),
+            locale: "fr-FR".to_string(),
             context_anchor_at: None,
             welcome_message: None,
             home_focus_view: HomeFocusView::Follows,
@@ -1173,7 +1194,9 @@ mod tests {
             journal_theme: None,
             current_lens_id: None,
             week_analysis_weekday: None,
+            monthly_recap_enabled: None,
             timezone: None,
+            locale: None,
             context_anchor_at: None,
             welcome_message: None,
             home_focus_view: None,
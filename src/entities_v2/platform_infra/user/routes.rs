@@ -4,6 +4,7 @@ use crate::entities_v2::{
     error::{ErrorType, PpdcError},
     feed::hydrate::count_recent_unread_feed_items,
     journal::Journal,
+    journal_import::dates::DateLocale,
     message::Message,
     platform_infra::mailer::{self, NewOutboundEmail, OutboundEmailProvider},
     platform_infra::usage_event::{UsageEvent, UsageEventType},
@@ -66,7 +67,9 @@ pub struct PatchUserDto {
     pub journal_theme: Option<JournalTheme>,
     pub current_lens_id: Option<Option<Uuid>>,
     pub week_analysis_weekday: Option<WeekAnalysisWeekday>,
+    pub monthly_recap_enabled: Option<bool>,
     pub timezone: Option<String>,
+    pub locale: Option<String>,
     pub context_anchor_at: Option<Option<chrono::NaiveDateTime>>,
     pub welcome_message: Option<Option<String>>,
     pub home_focus_view: Option<HomeFocusView>,
@@ -609,6 +612,15 @@ pub async fn patch_user_route(
             ));
         }
     }
+    if let Some(locale) = payload.locale.as_deref() {
+        if DateLocale::from_tag(locale).is_none() {
+            return Err(PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                format!("Unsupported locale: {}", locale),
+            ));
+        }
+    }
 
     let patched_biography = payload
         .biography
@@ -648,6 +660,8 @@ pub async fn patch_user_route(
              ai_features_enabled = COALESCE($28, ai_features_enabled),
              onboarding_version = COALESCE($29, onboarding_version),
              external_captures_default_journal_id = CASE WHEN $30 THEN $31 ELSE external_captures_default_journal_id END,
+             monthly_recap_enabled = COALESCE($32, monthly_recap_enabled),
+             locale = COALESCE($33, locale),
              updated_at = NOW()
          WHERE id = $1
          ",
@@ -695,6 +709,8 @@ pub async fn patch_user_route(
     .bind::<Nullable<Int4>, _>(payload.onboarding_version)
     .bind::<Bool, _>(payload.external_captures_default_journal_id.is_some())
     .bind::<Nullable<SqlUuid>, _>(payload.external_captures_default_journal_id.flatten())
+    .bind::<Nullable<Bool>, _>(payload.monthly_recap_enabled)
+    .bind::<Nullable<Text>, _>(payload.locale)
     .execute(&mut conn)?;
     let updated_user = User::find(&id, &pool)?;
 
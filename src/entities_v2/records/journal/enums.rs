@@ -5,6 +5,11 @@ pub enum JournalExportFormat {
     Markdown,
     Text,
     Json,
+    /// Markdown files with the trace images and attachments.
+    Zip,
+    /// One self-contained, print-ready HTML page.
+    Html,
+    Epub,
 }
 
 impl JournalExportFormat {
@@ -13,6 +18,28 @@ impl JournalExportFormat {
             JournalExportFormat::Markdown => "md",
             JournalExportFormat::Text => "txt",
             JournalExportFormat::Json => "json",
+            JournalExportFormat::Zip => "zip",
+            JournalExportFormat::Html => "html",
+            JournalExportFormat::Epub => "epub",
+        }
+    }
+
+    /// Whether the export is a file to download rather than text returned inline.
+    pub fn is_archive(self) -> bool {
+        matches!(
+            self,
+            JournalExportFormat::Zip | JournalExportFormat::Html | JournalExportFormat::Epub
+        )
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            JournalExportFormat::Markdown => "text/markdown",
+            JournalExportFormat::Text => "text/plain",
+            JournalExportFormat::Json => "application/json",
+            JournalExportFormat::Zip => "application/zip",
+            JournalExportFormat::Html => "text/html",
+            JournalExportFormat::Epub => "application/epub+zip",
         }
     }
 
@@ -22,6 +49,9 @@ impl JournalExportFormat {
             "md" | "markdown" => Some(JournalExportFormat::Markdown),
             "txt" | "text" => Some(JournalExportFormat::Text),
             "json" => Some(JournalExportFormat::Json),
+            "zip" => Some(JournalExportFormat::Zip),
+            "html" | "htm" => Some(JournalExportFormat::Html),
+            "epub" => Some(JournalExportFormat::Epub),
             _ => None,
         }
     }
@@ -42,8 +72,9 @@ impl<'de> Deserialize<'de> for JournalExportFormat {
         D: Deserializer<'de>,
     {
         let raw = String::deserialize(deserializer)?;
-        JournalExportFormat::from_api_value(&raw)
-            .ok_or_else(|| de::Error::custom("unknown format. expected one of: md, txt, json"))
+        JournalExportFormat::from_api_value(&raw).ok_or_else(|| {
+            de::Error::custom("unknown format. expected one of: md, txt, json, zip, html, epub")
+        })
     }
 }
 
@@ -1,4 +1,4 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -48,15 +48,48 @@ pub struct UpdateJournalDto {
     pub status: Option<JournalStatus>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct JournalExportDto {
     pub format: JournalExportFormat,
     #[serde(default)]
     pub include_messages: bool,
+    /// First and last interaction day exported, both included.
+    #[serde(default)]
+    pub from: Option<NaiveDate>,
+    #[serde(default)]
+    pub to: Option<NaiveDate>,
+    /// Adds the trace mirrors and the period recaps covering the exported days.
+    #[serde(default)]
+    pub include_ai_context: bool,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalExportStatus {
+    /// Queued or being built in the background.
+    Pending,
+    Ready,
+    Failed,
+}
+
+/// Text formats come back in `content`; archives as a signed `download_url`, or a `job_id`
+/// to poll when the export is built in the background.
 #[derive(Serialize, Debug, Clone)]
 pub struct JournalExportResponse {
     pub format: JournalExportFormat,
-    pub content: String,
+    pub status: JournalExportStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
@@ -0,0 +1,419 @@
+use std::io::{Cursor, Seek, Write};
+
+use serde_json::json;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::entities_v2::error::{ErrorType, PpdcError};
+
+use super::model::{slugify, JournalExport};
+use super::render::{
+    escape_html, messages_section, recaps_section, render_messages_markdown,
+    render_mirror_markdown, render_recap_markdown, render_trace_markdown, trace_section, FileLinks,
+};
+
+const EPUB_CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+const EPUB_STYLE: &str = "body { font-family: serif; line-height: 1.5; }
+.date { color: #656d76; font-size: 0.85em; }
+.subtitle { font-style: italic; }
+img { display: block; max-width: 100%; margin: 1em auto; }
+aside.mirror { border-left: 3px solid #8c959f; margin: 1em 0; padding-left: 1em; }
+article, section.recap { margin-bottom: 2em; }
+";
+
+/// A ZIP archive, built in memory unless another writer is given.
+pub struct ArchiveWriter<W: Write + Seek = Cursor<Vec<u8>>> {
+    zip: ZipWriter<W>,
+}
+
+impl ArchiveWriter {
+    pub fn new() -> Self {
+        ArchiveWriter::to_writer(Cursor::new(Vec::new()))
+    }
+
+    pub fn finish(self) -> Result<Vec<u8>, PpdcError> {
+        self.finish_writer().map(Cursor::into_inner)
+    }
+}
+
+impl<W: Write + Seek> ArchiveWriter<W> {
+    pub fn to_writer(writer: W) -> Self {
+        ArchiveWriter {
+            zip: ZipWriter::new(writer),
+        }
+    }
+
+    /// Stored files are kept uncompressed: images and documents usually already are.
+    pub fn add(&mut self, path: &str, bytes: &[u8], compress: bool) -> Result<(), PpdcError> {
+        let method = if compress {
+            CompressionMethod::Deflated
+        } else {
+            CompressionMethod::Stored
+        };
+        self.zip
+            .start_file(path, FileOptions::default().compression_method(method))
+            .map_err(|err| archive_error(path, err))?;
+        self.zip
+            .write_all(bytes)
+            .map_err(|err| archive_error(path, err))
+    }
+
+    pub fn finish_writer(mut self) -> Result<W, PpdcError> {
+        self.zip
+            .finish()
+            .map_err(|err| archive_error("archive", err))
+    }
+}
+
+impl Default for ArchiveWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn archive_error(path: &str, err: impl std::fmt::Display) -> PpdcError {
+    PpdcError::new(
+        500,
+        ErrorType::InternalError,
+        format!("Failed to write {} to the export: {}", path, err),
+    )
+}
+
+fn recap_path(export: &JournalExport, index: usize) -> String {
+    let recap = &export.recaps[index];
+    format!(
+        "ai_context/recaps/{}-{}.md",
+        recap.period_start.format("%Y-%m-%d"),
+        slugify(recap.analysis_type.to_db())
+    )
+}
+
+/// Markdown traces with their stored files, the messages and AI context, and a
+/// `journal.json` manifest listing what is where.
+pub fn build_zip(export: &JournalExport) -> Result<Vec<u8>, PpdcError> {
+    let mut archive = ArchiveWriter::new();
+
+    let traces = export
+        .traces
+        .iter()
+        .map(|exported| {
+            json!({
+                "id": exported.trace.id,
+                "title": exported.trace.title,
+                "interaction_date": exported.trace.interaction_date,
+                "file": format!("traces/{}.md", exported.file_stem),
+                "image": exported.image.as_ref().map(|file| &file.path),
+                "attachments": exported.attachments.iter().map(|attachment| json!({
+                    "name": attachment.attachment_name,
+                    "document_id": attachment.document.id,
+                    "file": attachment.file.as_ref().map(|file| &file.path),
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let recaps = export
+        .recaps
+        .iter()
+        .enumerate()
+        .map(|(index, recap)| {
+            json!({
+                "analysis_type": recap.analysis_type.to_db(),
+                "period_start": recap.period_start,
+                "period_end": recap.period_end,
+                "file": recap_path(export, index),
+            })
+        })
+        .collect::<Vec<_>>();
+    let manifest = json!({
+        "exported_at": export.exported_at,
+        "journal": export.journal,
+        "options": export.options,
+        "traces": traces,
+        "recaps": recaps,
+        "messages_file": (!export.messages.is_empty()).then_some("messages.md"),
+    });
+    archive.add(
+        "journal.json",
+        serde_json::to_string_pretty(&manifest)?.as_bytes(),
+        true,
+    )?;
+
+    for exported in &export.traces {
+        archive.add(
+            &format!("traces/{}.md", exported.file_stem),
+            render_trace_markdown(exported).as_bytes(),
+            true,
+        )?;
+        for (index, mirror) in exported.mirrors.iter().enumerate() {
+            archive.add(
+                &format!("ai_context/mirrors/{}-{}.md", exported.file_stem, index + 1),
+                render_mirror_markdown(mirror).as_bytes(),
+                true,
+            )?;
+        }
+    }
+    for file in export.files() {
+        archive.add(&file.path, &file.bytes, false)?;
+    }
+    if !export.messages.is_empty() {
+        archive.add(
+            "messages.md",
+            render_messages_markdown(&export.messages).as_bytes(),
+            true,
+        )?;
+    }
+    for (index, recap) in export.recaps.iter().enumerate() {
+        archive.add(
+            &recap_path(export, index),
+            render_recap_markdown(recap).as_bytes(),
+            true,
+        )?;
+    }
+    archive.finish()
+}
+
+fn xhtml_document(title: &str, body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<!DOCTYPE html>
+<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\">
+<head>
+<meta charset=\"UTF-8\" />
+<title>{title}</title>
+<link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\" />
+</head>
+<body>
+{body}</body>
+</html>
+",
+        title = escape_html(title),
+        body = body
+    )
+}
+
+struct EpubChapter {
+    id: String,
+    title: String,
+    body: String,
+}
+
+/// One chapter per month of traces, then the recaps and the messages.
+fn epub_chapters(export: &JournalExport) -> Vec<EpubChapter> {
+    let mut chapters: Vec<EpubChapter> = Vec::new();
+    let mut current_month = None;
+    for exported in &export.traces {
+        let month = exported.trace.interaction_date.format("%Y-%m").to_string();
+        if current_month.as_ref() != Some(&month) {
+            let title = exported.trace.interaction_date.format("%B %Y").to_string();
+            chapters.push(EpubChapter {
+                id: format!("month-{}", month),
+                body: format!("<h1>{}</h1>\n", escape_html(&title)),
+                title,
+            });
+            current_month = Some(month);
+        }
+        if let Some(chapter) = chapters.last_mut() {
+            chapter
+                .body
+                .push_str(trace_section(exported, FileLinks::Archive).as_str());
+        }
+    }
+    if !export.recaps.is_empty() {
+        chapters.push(EpubChapter {
+            id: "recaps".to_string(),
+            title: "Recaps".to_string(),
+            body: recaps_section(&export.recaps),
+        });
+    }
+    if !export.messages.is_empty() {
+        chapters.push(EpubChapter {
+            id: "messages".to_string(),
+            title: "Messages".to_string(),
+            body: messages_section(&export.messages),
+        });
+    }
+    chapters
+}
+
+/// An EPUB 3 book of the journal, with the trace images packaged alongside the chapters.
+pub fn build_epub(export: &JournalExport) -> Result<Vec<u8>, PpdcError> {
+    let journal = &export.journal;
+    let chapters = epub_chapters(export);
+    let images = export
+        .files()
+        .filter(|file| file.is_image())
+        .collect::<Vec<_>>();
+
+    let mut manifest = String::from(
+        "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n    <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n",
+    );
+    let mut spine = String::new();
+    let mut nav = String::new();
+    for chapter in &chapters {
+        manifest.push_str(
+            format!(
+                "    <item id=\"{id}\" href=\"{id}.xhtml\" media-type=\"application/xhtml+xml\"/>\n",
+                id = chapter.id
+            )
+            .as_str(),
+        );
+        spine.push_str(format!("    <itemref idref=\"{}\"/>\n", chapter.id).as_str());
+        nav.push_str(
+            format!(
+                "<li><a href=\"{}.xhtml\">{}</a></li>\n",
+                chapter.id,
+                escape_html(&chapter.title)
+            )
+            .as_str(),
+        );
+    }
+    for (index, image) in images.iter().enumerate() {
+        manifest.push_str(
+            format!(
+                "    <item id=\"image-{}\" href=\"{}\" media-type=\"{}\"/>\n",
+                index + 1,
+                escape_html(&image.path),
+                image.asset.mime_type
+            )
+            .as_str(),
+        );
+    }
+
+    let package = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\">
+  <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">
+    <dc:identifier id=\"book-id\">urn:uuid:{id}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:language>und</dc:language>
+    <meta property=\"dcterms:modified\">{modified}</meta>
+  </metadata>
+  <manifest>
+{manifest}  </manifest>
+  <spine>
+{spine}  </spine>
+</package>
+",
+        id = journal.id,
+        title = escape_html(&journal.title),
+        modified = export.exported_at.format("%Y-%m-%dT%H:%M:%SZ"),
+        manifest = manifest,
+        spine = spine
+    );
+    let nav = xhtml_document(
+        &journal.title,
+        &format!(
+            "<nav epub:type=\"toc\" id=\"toc\">\n<h1>{}</h1>\n<ol>\n{}</ol>\n</nav>\n",
+            escape_html(&journal.title),
+            nav
+        ),
+    );
+
+    let mut archive = ArchiveWriter::new();
+    archive.add("mimetype", b"application/epub+zip", false)?;
+    archive.add("META-INF/container.xml", EPUB_CONTAINER.as_bytes(), true)?;
+    archive.add("OEBPS/content.opf", package.as_bytes(), true)?;
+    archive.add("OEBPS/nav.xhtml", nav.as_bytes(), true)?;
+    archive.add("OEBPS/style.css", EPUB_STYLE.as_bytes(), true)?;
+    for chapter in &chapters {
+        archive.add(
+            &format!("OEBPS/{}.xhtml", chapter.id),
+            xhtml_document(&chapter.title, &chapter.body).as_bytes(),
+            true,
+        )?;
+    }
+    for image in images {
+        archive.add(&format!("OEBPS/{}", image.path), &image.bytes, false)?;
+    }
+    archive.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::entities_v2::journal::{
+        Journal, JournalExportDto, JournalExportFormat, JournalSharingMode, JournalStatus,
+        JournalType,
+    };
+
+    fn empty_export(format: JournalExportFormat) -> JournalExport {
+        let now = NaiveDate::from_ymd_opt(2026, 3, 5)
+            .and_then(|day| day.and_hms_opt(9, 0, 0))
+            .expect("valid date");
+        JournalExport {
+            journal: Journal {
+                id: Uuid::new_v4(),
+                user_id: Uuid::new_v4(),
+                title: "Carnet <1>".to_string(),
+                subtitle: String::new(),
+                content: String::new(),
+                is_encrypted: false,
+                last_trace_at: None,
+                current_draft_id: None,
+                status: JournalStatus::Active,
+                journal_type: JournalType::UserJournal,
+                sharing_mode: JournalSharingMode::Private,
+                created_at: now,
+                updated_at: now,
+            },
+            options: JournalExportDto {
+                format,
+                include_messages: false,
+                from: None,
+                to: None,
+                include_ai_context: false,
+            },
+            exported_at: now,
+            traces: Vec::new(),
+            messages: Vec::new(),
+            recaps: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn epub_starts_with_stored_mimetype() {
+        let bytes = build_epub(&empty_export(JournalExportFormat::Epub)).expect("epub builds");
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).expect("valid zip");
+        let mut mimetype = archive.by_index(0).expect("first entry");
+        assert_eq!(mimetype.name(), "mimetype");
+        assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+        let mut content = String::new();
+        mimetype.read_to_string(&mut content).expect("readable");
+        assert_eq!(content, "application/epub+zip");
+        drop(mimetype);
+
+        let mut package = String::new();
+        archive
+            .by_name("OEBPS/content.opf")
+            .expect("package document")
+            .read_to_string(&mut package)
+            .expect("readable");
+        assert!(package.contains("<dc:title>Carnet &lt;1&gt;</dc:title>"));
+    }
+
+    #[test]
+    fn zip_has_a_manifest() {
+        let bytes = build_zip(&empty_export(JournalExportFormat::Zip)).expect("zip builds");
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).expect("valid zip");
+        let mut manifest = String::new();
+        archive
+            .by_name("journal.json")
+            .expect("manifest")
+            .read_to_string(&mut manifest)
+            .expect("readable");
+        let manifest: serde_json::Value = serde_json::from_str(&manifest).expect("json");
+        assert_eq!(manifest["options"]["format"], "zip");
+        assert_eq!(manifest["traces"], json!([]));
+    }
+}
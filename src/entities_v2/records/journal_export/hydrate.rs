@@ -0,0 +1,63 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{asset::EXPORT_OBJECT_KEY_PATTERN, error::PpdcError};
+use crate::schema::{account_exports, assets, documents, trace_attachments, traces};
+
+/// Total size of the images and attachment files of the traces, read from the asset rows
+/// without loading the files.
+pub fn find_export_file_bytes(trace_ids: &[Uuid], pool: &DbPool) -> Result<i64, PpdcError> {
+    let mut conn = pool.get()?;
+    let image_ids = traces::table
+        .filter(traces::id.eq_any(trace_ids))
+        .filter(traces::content_image_asset_id.is_not_null())
+        .select(traces::content_image_asset_id)
+        .load::<Option<Uuid>>(&mut conn)?;
+    let attachment_ids = trace_attachments::table
+        .inner_join(documents::table)
+        .filter(trace_attachments::trace_id.eq_any(trace_ids))
+        .filter(documents::asset_id.is_not_null())
+        .select(documents::asset_id)
+        .load::<Option<Uuid>>(&mut conn)?;
+    let asset_ids = image_ids
+        .into_iter()
+        .chain(attachment_ids)
+        .flatten()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let sizes = assets::table
+        .filter(assets::id.eq_any(asset_ids))
+        .select(assets::size_bytes)
+        .load::<i64>(&mut conn)?;
+    Ok(sizes.into_iter().sum())
+}
+
+/// Journal export archives stored before `cutoff`; account export archives expire with their
+/// own links and are left out.
+pub fn find_journal_export_asset_ids_created_before(
+    cutoff: NaiveDateTime,
+    limit: i64,
+    pool: &DbPool,
+) -> Result<Vec<Uuid>, PpdcError> {
+    let mut conn = pool.get()?;
+    let ids = assets::table
+        .filter(assets::object_key.like(EXPORT_OBJECT_KEY_PATTERN))
+        .filter(assets::created_at.lt(cutoff))
+        .filter(
+            assets::id.nullable().ne_all(
+                account_exports::table
+                    .filter(account_exports::asset_id.is_not_null())
+                    .select(account_exports::asset_id),
+            ),
+        )
+        .order(assets::created_at.asc())
+        .limit(limit)
+        .select(assets::id)
+        .load::<Uuid>(&mut conn)?;
+    Ok(ids)
+}
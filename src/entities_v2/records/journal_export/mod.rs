@@ -0,0 +1,9 @@
+pub mod archive;
+pub mod hydrate;
+pub mod model;
+pub mod render;
+pub mod routes;
+pub mod service;
+
+pub use model::{ExportJournalPayload, ExportJournalResult, JournalExport};
+pub use routes::get_journal_export_route;
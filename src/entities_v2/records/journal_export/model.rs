@@ -0,0 +1,176 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities_v2::{
+    analysis_summary::PeriodRecap, asset::Asset, document::Document, journal::Journal,
+    journal::JournalExportDto, message::Message, trace::Trace, trace_mirror::TraceMirror,
+};
+
+/// A stored file copied into an export, with its path inside the archive.
+#[derive(Debug, Clone)]
+pub struct ExportedFile {
+    pub asset: Asset,
+    pub path: String,
+    /// Empty until the file is fetched from storage.
+    pub bytes: Vec<u8>,
+}
+
+impl ExportedFile {
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    pub fn is_image(&self) -> bool {
+        matches!(
+            self.asset.mime_type.as_str(),
+            "image/jpeg" | "image/png" | "image/webp" | "image/gif"
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportedAttachment {
+    pub attachment_name: String,
+    pub document: Document,
+    pub file: Option<ExportedFile>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportedTrace {
+    pub trace: Trace,
+    /// Name of the trace files in archives, unique within the export.
+    pub file_stem: String,
+    pub image: Option<ExportedFile>,
+    pub attachments: Vec<ExportedAttachment>,
+    pub mirrors: Vec<TraceMirror>,
+}
+
+/// Everything an export contains, loaded once and rendered in the requested format.
+#[derive(Debug, Clone)]
+pub struct JournalExport {
+    pub journal: Journal,
+    pub options: JournalExportDto,
+    pub exported_at: NaiveDateTime,
+    pub traces: Vec<ExportedTrace>,
+    pub messages: Vec<Message>,
+    pub recaps: Vec<PeriodRecap>,
+}
+
+impl JournalExport {
+    pub fn files(&self) -> impl Iterator<Item = &ExportedFile> {
+        self.traces.iter().flat_map(|trace| {
+            trace.image.iter().chain(
+                trace
+                    .attachments
+                    .iter()
+                    .filter_map(|attachment| attachment.file.as_ref()),
+            )
+        })
+    }
+
+    pub fn files_mut(&mut self) -> impl Iterator<Item = &mut ExportedFile> {
+        self.traces.iter_mut().flat_map(|trace| {
+            trace.image.iter_mut().chain(
+                trace
+                    .attachments
+                    .iter_mut()
+                    .filter_map(|attachment| attachment.file.as_mut()),
+            )
+        })
+    }
+
+    pub fn file_name(&self) -> String {
+        let title = slugify(&self.journal.title);
+        let title = if title.is_empty() {
+            "journal".to_string()
+        } else {
+            title
+        };
+        format!(
+            "{}-{}.{}",
+            title,
+            self.exported_at.format("%Y-%m-%d"),
+            self.options.format.to_api_value()
+        )
+    }
+}
+
+/// Payload of an `EXPORT_JOURNAL` job.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportJournalPayload {
+    pub user_id: Uuid,
+    pub journal_id: Uuid,
+    pub options: JournalExportDto,
+}
+
+/// Result stored on a finished `EXPORT_JOURNAL` job.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportJournalResult {
+    pub asset_id: Uuid,
+    pub file_name: String,
+    pub trace_count: usize,
+}
+
+/// Lowercase ASCII words joined by dashes, for file names.
+pub fn slugify(value: &str) -> String {
+    let mut slug = String::new();
+    for ch in value.chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= 48 {
+            break;
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// A stored file name reduced to characters that are safe in archive paths and EPUB hrefs.
+pub fn safe_file_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-' | '_') {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    let name = name.trim_matches(|ch| ch == '.' || ch == '-');
+    if name.is_empty() {
+        "file".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Whether an interaction day falls within the requested range.
+pub fn in_export_range(day: NaiveDate, from: Option<NaiveDate>, to: Option<NaiveDate>) -> bool {
+    from.is_none_or(|from| day >= from) && to.is_none_or(|to| day <= to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs_keep_ascii_words() {
+        assert_eq!(slugify("Journal d'été — 2026!"), "journal-d-t-2026");
+        assert_eq!(slugify("  "), "");
+        assert_eq!(safe_file_name("../Photo été 1.JPG"), "Photo--t--1.JPG");
+        assert_eq!(safe_file_name("..."), "file");
+    }
+
+    #[test]
+    fn range_bounds_are_inclusive() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).expect("valid date");
+        assert!(in_export_range(day(5), Some(day(5)), Some(day(5))));
+        assert!(!in_export_range(day(4), Some(day(5)), None));
+        assert!(!in_export_range(day(6), None, Some(day(5))));
+        assert!(in_export_range(day(6), None, None));
+    }
+}
@@ -0,0 +1,410 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::json;
+
+use crate::entities_v2::{
+    analysis_summary::PeriodRecap, error::PpdcError, message::Message, trace_mirror::TraceMirror,
+};
+
+use super::model::{ExportedFile, ExportedTrace, JournalExport};
+
+const JOURNAL_HTML: &str = include_str!("templates/journal.html");
+
+fn push_header(out: &mut String, heading: &str, export: &JournalExport) {
+    let journal = &export.journal;
+    out.push_str(heading);
+    out.push('\n');
+    out.push_str(format!("exported_at: {}\n", export.exported_at).as_str());
+    out.push_str(format!("journal_id: {}\n", journal.id).as_str());
+    out.push_str(format!("journal_title: {}\n", journal.title).as_str());
+    out.push_str(format!("journal_type: {}\n", journal.journal_type.to_db()).as_str());
+    if let Some(from) = export.options.from {
+        out.push_str(format!("from: {}\n", from).as_str());
+    }
+    if let Some(to) = export.options.to {
+        out.push_str(format!("to: {}\n", to).as_str());
+    }
+    out.push('\n');
+}
+
+fn push_messages(out: &mut String, heading: &str, messages: &[Message]) {
+    if messages.is_empty() {
+        return;
+    }
+    out.push_str(heading);
+    out.push_str("\n\n");
+    for message in messages {
+        out.push_str(format!("message_date: {}\n", message.created_at).as_str());
+        out.push_str(format!("message_type: {}\n", message.message_type.to_db()).as_str());
+        if let Some(trace_id) = message.trace_id {
+            out.push_str(format!("trace_id: {}\n", trace_id).as_str());
+        }
+        out.push_str(format!("title: {}\n", message.title).as_str());
+        out.push('\n');
+        out.push_str(message.content.as_str());
+        out.push('\n');
+        out.push('\n');
+    }
+}
+
+fn push_recap(out: &mut String, recap: &PeriodRecap) {
+    out.push_str(format!("recap_type: {}\n", recap.analysis_type.to_db()).as_str());
+    out.push_str(format!("period_start: {}\n", recap.period_start).as_str());
+    out.push_str(format!("period_end: {}\n", recap.period_end).as_str());
+    out.push_str(format!("title: {}\n", recap.summary.title).as_str());
+    out.push('\n');
+    out.push_str(recap.summary.content.as_str());
+    out.push('\n');
+    out.push('\n');
+}
+
+fn push_mirror(out: &mut String, mirror: &TraceMirror) {
+    out.push_str(format!("mirror_type: {}\n", mirror.trace_mirror_type.to_db()).as_str());
+    out.push_str(format!("trace_id: {}\n", mirror.trace_id).as_str());
+    out.push_str(format!("title: {}\n", mirror.title).as_str());
+    out.push('\n');
+    out.push_str(mirror.content.as_str());
+    out.push('\n');
+    out.push('\n');
+}
+
+fn push_ai_context(out: &mut String, heading: &str, export: &JournalExport) {
+    let has_mirrors = export.traces.iter().any(|trace| !trace.mirrors.is_empty());
+    if export.recaps.is_empty() && !has_mirrors {
+        return;
+    }
+    out.push_str(heading);
+    out.push_str("\n\n");
+    for recap in &export.recaps {
+        push_recap(out, recap);
+    }
+    for mirror in export.traces.iter().flat_map(|trace| &trace.mirrors) {
+        push_mirror(out, mirror);
+    }
+}
+
+pub fn render_markdown_export(export: &JournalExport) -> String {
+    let mut out = String::new();
+    push_header(&mut out, "# Journal Export", export);
+
+    for exported in &export.traces {
+        let trace = &exported.trace;
+        out.push_str(format!("export_generated_date: {}\n", trace.interaction_date).as_str());
+        out.push_str(trace.content.as_str());
+        out.push('\n');
+        out.push('\n');
+    }
+
+    push_messages(&mut out, "# Messages", &export.messages);
+    push_ai_context(&mut out, "# AI Context", export);
+    out
+}
+
+pub fn render_text_export(export: &JournalExport) -> String {
+    let mut out = String::new();
+    push_header(&mut out, "Journal Export", export);
+
+    for exported in &export.traces {
+        let trace = &exported.trace;
+        out.push_str(format!("export_generated_date: {}\n", trace.interaction_date).as_str());
+        out.push_str(trace.title.as_str());
+        out.push('\n');
+        out.push_str(trace.content.as_str());
+        out.push('\n');
+        out.push('\n');
+    }
+
+    push_messages(&mut out, "Messages", &export.messages);
+    push_ai_context(&mut out, "AI Context", export);
+    out
+}
+
+pub fn render_json_export(export: &JournalExport) -> Result<String, PpdcError> {
+    let mut payload = json!({
+        "exported_at": export.exported_at,
+        "journal": export.journal,
+        "from": export.options.from,
+        "to": export.options.to,
+        "traces": export.traces.iter().map(|trace| &trace.trace).collect::<Vec<_>>(),
+        "messages": export.messages
+    });
+    if export.options.include_ai_context {
+        payload["mirrors"] = json!(export
+            .traces
+            .iter()
+            .flat_map(|trace| &trace.mirrors)
+            .collect::<Vec<_>>());
+        payload["recaps"] = json!(export.recaps);
+    }
+    Ok(serde_json::to_string_pretty(&payload)?)
+}
+
+/// Markdown file of one trace in ZIP exports, with front matter the importer reads back.
+pub fn render_trace_markdown(exported: &ExportedTrace) -> String {
+    let trace = &exported.trace;
+    let mut out = String::new();
+    out.push_str("---\n");
+    out.push_str(format!("id: {}\n", trace.id).as_str());
+    out.push_str(format!("title: \"{}\"\n", trace.title.replace('\n', " ")).as_str());
+    out.push_str(
+        format!(
+            "date: {}\n",
+            trace.interaction_date.format("%Y-%m-%dT%H:%M:%S")
+        )
+        .as_str(),
+    );
+    out.push_str(format!("trace_type: {}\n", trace.trace_type.to_db()).as_str());
+    out.push_str("---\n\n");
+    out.push_str(trace.content.trim_end());
+    out.push('\n');
+
+    if let Some(image) = &exported.image {
+        out.push_str(format!("\n![]({})\n", archive_link(image)).as_str());
+    }
+    if !exported.attachments.is_empty() {
+        out.push_str("\n## Attachments\n\n");
+        for attachment in &exported.attachments {
+            match &attachment.file {
+                Some(file) => out.push_str(
+                    format!(
+                        "- [{}]({})\n",
+                        attachment.attachment_name,
+                        archive_link(file)
+                    )
+                    .as_str(),
+                ),
+                None => out.push_str(format!("- {}\n", attachment.attachment_name).as_str()),
+            }
+        }
+    }
+    out
+}
+
+/// Link from `traces/` to a file of the archive.
+fn archive_link(file: &ExportedFile) -> String {
+    format!("<../{}>", file.path)
+}
+
+pub fn render_mirror_markdown(mirror: &TraceMirror) -> String {
+    let mut out = String::new();
+    push_mirror(&mut out, mirror);
+    out
+}
+
+pub fn render_recap_markdown(recap: &PeriodRecap) -> String {
+    let mut out = String::new();
+    push_recap(&mut out, recap);
+    out
+}
+
+pub fn render_messages_markdown(messages: &[Message]) -> String {
+    let mut out = String::new();
+    push_messages(&mut out, "# Messages", messages);
+    out
+}
+
+/// How HTML sections reference the stored files of the export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileLinks {
+    /// Files embedded as data URIs, for a single self-contained page.
+    Inline,
+    /// Images referenced by their path in the archive; other files are only named.
+    Archive,
+}
+
+impl FileLinks {
+    fn href(self, file: &ExportedFile) -> Option<String> {
+        match self {
+            FileLinks::Inline => Some(format!(
+                "data:{};base64,{}",
+                file.asset.mime_type,
+                BASE64.encode(&file.bytes)
+            )),
+            FileLinks::Archive if file.is_image() => Some(file.path.clone()),
+            FileLinks::Archive => None,
+        }
+    }
+}
+
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Blank-line separated paragraphs, as XHTML so EPUB chapters can reuse them.
+fn paragraphs(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| {
+            format!(
+                "<p>{}</p>\n",
+                escape_html(paragraph).replace('\n', "<br />")
+            )
+        })
+        .collect()
+}
+
+pub fn trace_section(exported: &ExportedTrace, links: FileLinks) -> String {
+    let trace = &exported.trace;
+    let mut out = String::new();
+    out.push_str(format!("<article class=\"trace\" id=\"trace-{}\">\n", trace.id).as_str());
+    if !trace.title.trim().is_empty() {
+        out.push_str(format!("<h2>{}</h2>\n", escape_html(&trace.title)).as_str());
+    }
+    out.push_str(
+        format!(
+            "<p class=\"date\">{}</p>\n",
+            trace.interaction_date.format("%Y-%m-%d %H:%M")
+        )
+        .as_str(),
+    );
+    if !trace.subtitle.trim().is_empty() {
+        out.push_str(
+            format!(
+                "<p class=\"subtitle\">{}</p>\n",
+                escape_html(&trace.subtitle)
+            )
+            .as_str(),
+        );
+    }
+    out.push_str(paragraphs(&trace.content).as_str());
+
+    let images = exported.image.iter().chain(
+        exported
+            .attachments
+            .iter()
+            .filter_map(|attachment| attachment.file.as_ref())
+            .filter(|file| file.is_image()),
+    );
+    for image in images {
+        if let Some(src) = links.href(image) {
+            out.push_str(
+                format!(
+                    "<img src=\"{}\" alt=\"{}\" />\n",
+                    escape_html(&src),
+                    escape_html(image.file_name())
+                )
+                .as_str(),
+            );
+        }
+    }
+
+    let other_attachments = exported
+        .attachments
+        .iter()
+        .filter(|attachment| !attachment.file.as_ref().is_some_and(ExportedFile::is_image))
+        .collect::<Vec<_>>();
+    if !other_attachments.is_empty() {
+        out.push_str("<ul class=\"attachments\">\n");
+        for attachment in other_attachments {
+            let name = escape_html(&attachment.attachment_name);
+            match attachment.file.as_ref().and_then(|file| {
+                links
+                    .href(file)
+                    .map(|href| (href, escape_html(file.file_name())))
+            }) {
+                Some((href, file_name)) => out.push_str(
+                    format!(
+                        "<li><a href=\"{}\" download=\"{}\">{}</a></li>\n",
+                        escape_html(&href),
+                        file_name,
+                        name
+                    )
+                    .as_str(),
+                ),
+                None => out.push_str(format!("<li>{}</li>\n", name).as_str()),
+            }
+        }
+        out.push_str("</ul>\n");
+    }
+
+    for mirror in &exported.mirrors {
+        out.push_str("<aside class=\"mirror\">\n");
+        out.push_str(format!("<h3>{}</h3>\n", escape_html(&mirror.title)).as_str());
+        out.push_str(paragraphs(&mirror.content).as_str());
+        out.push_str("</aside>\n");
+    }
+    out.push_str("</article>\n");
+    out
+}
+
+pub fn recaps_section(recaps: &[PeriodRecap]) -> String {
+    let mut out = String::from("<section class=\"recaps\">\n<h2>Recaps</h2>\n");
+    for recap in recaps {
+        out.push_str("<section class=\"recap\">\n");
+        out.push_str(format!("<h3>{}</h3>\n", escape_html(&recap.summary.title)).as_str());
+        out.push_str(
+            format!(
+                "<p class=\"date\">{} – {}</p>\n",
+                recap.period_start.format("%Y-%m-%d"),
+                recap.period_end.format("%Y-%m-%d")
+            )
+            .as_str(),
+        );
+        out.push_str(paragraphs(&recap.summary.content).as_str());
+        out.push_str("</section>\n");
+    }
+    out.push_str("</section>\n");
+    out
+}
+
+pub fn messages_section(messages: &[Message]) -> String {
+    let mut out = String::from("<section class=\"messages\">\n<h2>Messages</h2>\n");
+    for message in messages {
+        out.push_str("<article>\n");
+        out.push_str(format!("<h3>{}</h3>\n", escape_html(&message.title)).as_str());
+        out.push_str(
+            format!(
+                "<p class=\"date\">{}</p>\n",
+                message.created_at.format("%Y-%m-%d %H:%M")
+            )
+            .as_str(),
+        );
+        out.push_str(paragraphs(&message.content).as_str());
+        out.push_str("</article>\n");
+    }
+    out.push_str("</section>\n");
+    out
+}
+
+/// A single HTML page with the stored files inlined, laid out to print as a PDF.
+pub fn render_html(export: &JournalExport) -> String {
+    let mut body = String::new();
+    for exported in &export.traces {
+        body.push_str(trace_section(exported, FileLinks::Inline).as_str());
+    }
+    if !export.recaps.is_empty() {
+        body.push_str(recaps_section(&export.recaps).as_str());
+    }
+    if !export.messages.is_empty() {
+        body.push_str(messages_section(&export.messages).as_str());
+    }
+
+    JOURNAL_HTML
+        .replace("{{title}}", &escape_html(&export.journal.title))
+        .replace("{{subtitle}}", &escape_html(&export.journal.subtitle))
+        .replace(
+            "{{exported_at}}",
+            &export.exported_at.format("%Y-%m-%d %H:%M").to_string(),
+        )
+        .replace("{{body}}", &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paragraphs_are_escaped_xhtml() {
+        assert_eq!(
+            paragraphs("a <b> & 'c'\nnext\r\n\r\n\n\nsecond"),
+            "<p>a &lt;b&gt; &amp; &#39;c&#39;<br />next</p>\n<p>second</p>\n"
+        );
+    }
+}
@@ -0,0 +1,21 @@
+use axum::{
+    debug_handler,
+    extract::{Extension, Json, Path},
+};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{error::PpdcError, journal::JournalExportResponse, session::Session};
+
+use super::service::export_job_response;
+
+#[debug_handler]
+pub async fn get_journal_export_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path((journal_id, job_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<JournalExportResponse>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let response = export_job_response(user_id, journal_id, job_id, &pool).await?;
+    Ok(Json(response))
+}
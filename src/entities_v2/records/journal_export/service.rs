@@ -0,0 +1,470 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDateTime, NaiveTime, Utc};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    analysis_summary::{AnalysisSummary, PeriodRecap},
+    asset::{upload_export_asset_for_user, Asset},
+    background_job::{BackgroundJob, BackgroundJobStatus, BackgroundJobType},
+    document::Document,
+    error::{ErrorType, PpdcError},
+    journal::{
+        Journal, JournalExportDto, JournalExportFormat, JournalExportResponse, JournalExportStatus,
+    },
+    message::Message,
+    trace::Trace,
+    trace_attachment::TraceAttachment,
+    trace_mirror::TraceMirror,
+};
+use crate::environment;
+
+use super::{
+    archive::{build_epub, build_zip},
+    hydrate::{find_export_file_bytes, find_journal_export_asset_ids_created_before},
+    model::{
+        in_export_range, safe_file_name, slugify, ExportJournalPayload, ExportJournalResult,
+        ExportedAttachment, ExportedFile, ExportedTrace, JournalExport,
+    },
+    render::{render_html, render_json_export, render_markdown_export, render_text_export},
+};
+
+/// Archives past either bound are built by a background job instead of during the request.
+const INLINE_EXPORT_MAX_TRACES: usize = 300;
+const INLINE_EXPORT_MAX_FILE_BYTES: i64 = 20 * 1024 * 1024;
+const MAX_EXPORTED_MESSAGES_PER_TRACE: i64 = 500;
+const EXPIRED_EXPORTS_BATCH_SIZE: i64 = 200;
+
+fn export_response(
+    format: JournalExportFormat,
+    status: JournalExportStatus,
+) -> JournalExportResponse {
+    JournalExportResponse {
+        format,
+        status,
+        content: None,
+        job_id: None,
+        asset_id: None,
+        file_name: None,
+        download_url: None,
+        expires_at: None,
+        error: None,
+    }
+}
+
+fn find_owned_journal(
+    user_id: Uuid,
+    journal_id: Uuid,
+    pool: &DbPool,
+) -> Result<Journal, PpdcError> {
+    let journal = Journal::find_full(journal_id, pool)?;
+    if journal.user_id != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    Ok(journal)
+}
+
+/// The asset, or `None` when it was deleted since the trace referenced it.
+fn find_asset(asset_id: Uuid, pool: &DbPool) -> Result<Option<Asset>, PpdcError> {
+    match Asset::find(asset_id, pool) {
+        Ok(asset) => Ok(Some(asset)),
+        Err(err) if err.status_code == 404 => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn trace_file_stem(trace: &Trace) -> String {
+    let id = trace.id.simple().to_string();
+    let title = slugify(&trace.title);
+    if title.is_empty() {
+        format!("{}-{}", trace.interaction_date.format("%Y-%m-%d"), &id[..8])
+    } else {
+        format!(
+            "{}-{}-{}",
+            trace.interaction_date.format("%Y-%m-%d"),
+            title,
+            &id[..8]
+        )
+    }
+}
+
+fn load_trace_files(
+    trace: &Trace,
+    pool: &DbPool,
+) -> Result<(Option<ExportedFile>, Vec<ExportedAttachment>), PpdcError> {
+    let folder = format!("assets/{}", trace.id);
+    let image = match trace.content_image_asset_id {
+        Some(asset_id) => find_asset(asset_id, pool)?.map(|asset| ExportedFile {
+            path: format!(
+                "{}/image-{}",
+                folder,
+                safe_file_name(&asset.original_filename)
+            ),
+            asset,
+            bytes: Vec::new(),
+        }),
+        None => None,
+    };
+
+    let mut attachments = Vec::new();
+    for (index, attachment) in TraceAttachment::find_for_trace(trace.id, pool)?
+        .into_iter()
+        .enumerate()
+    {
+        let document = Document::find_full(attachment.document_id, pool)?;
+        let file = match document.asset_id {
+            Some(asset_id) => find_asset(asset_id, pool)?.map(|asset| ExportedFile {
+                path: format!(
+                    "{}/{}-{}",
+                    folder,
+                    index + 1,
+                    safe_file_name(&asset.original_filename)
+                ),
+                asset,
+                bytes: Vec::new(),
+            }),
+            None => None,
+        };
+        attachments.push(ExportedAttachment {
+            attachment_name: attachment.attachment_name,
+            document,
+            file,
+        });
+    }
+    Ok((image, attachments))
+}
+
+/// Recaps of the journal covering the exported days; when several lenses recapped the same
+/// period, the latest summary is kept.
+fn load_recaps(
+    user_id: Uuid,
+    journal_id: Uuid,
+    options: &JournalExportDto,
+    traces: &[Trace],
+    pool: &DbPool,
+) -> Result<Vec<PeriodRecap>, PpdcError> {
+    let first_day = options
+        .from
+        .or_else(|| traces.first().map(|trace| trace.interaction_date.date()));
+    let last_day = options
+        .to
+        .or_else(|| traces.last().map(|trace| trace.interaction_date.date()));
+    let (Some(first_day), Some(last_day)) = (first_day, last_day) else {
+        return Ok(Vec::new());
+    };
+    let recaps = AnalysisSummary::find_recaps_for_journal_in_period(
+        user_id,
+        journal_id,
+        first_day.and_time(NaiveTime::MIN),
+        (last_day + Duration::days(1)).and_time(NaiveTime::MIN),
+        pool,
+    )?;
+
+    let mut latest: Vec<PeriodRecap> = Vec::new();
+    for recap in recaps {
+        match latest.iter_mut().find(|kept| {
+            kept.analysis_type == recap.analysis_type
+                && kept.period_start == recap.period_start
+                && kept.period_end == recap.period_end
+        }) {
+            Some(kept) if kept.summary.created_at < recap.summary.created_at => *kept = recap,
+            Some(_) => {}
+            None => latest.push(recap),
+        }
+    }
+    Ok(latest)
+}
+
+/// Finalized traces of the requested days, oldest first.
+fn find_export_traces(
+    journal_id: Uuid,
+    options: &JournalExportDto,
+    pool: &DbPool,
+) -> Result<Vec<Trace>, PpdcError> {
+    let mut traces = Trace::get_all_for_journal(journal_id, pool)?
+        .into_iter()
+        .filter(|trace| in_export_range(trace.interaction_date.date(), options.from, options.to))
+        .collect::<Vec<_>>();
+    traces.sort_by_key(|trace| (trace.interaction_date, trace.created_at));
+    Ok(traces)
+}
+
+/// Loads the given traces with what the format includes.
+///
+/// Stored files are only listed here; `fetch_export_files` downloads them.
+pub fn collect_journal_export(
+    user_id: Uuid,
+    journal: Journal,
+    options: JournalExportDto,
+    traces: Vec<Trace>,
+    pool: &DbPool,
+) -> Result<JournalExport, PpdcError> {
+    let messages = if options.include_messages {
+        let mut seen = HashSet::new();
+        let mut collected = Vec::new();
+        for trace in &traces {
+            let trace_messages = Message::find_for_trace_conversation(
+                user_id,
+                trace.id,
+                MAX_EXPORTED_MESSAGES_PER_TRACE,
+                pool,
+            )?;
+            for message in trace_messages {
+                if seen.insert(message.id) {
+                    collected.push(message);
+                }
+            }
+        }
+        collected.sort_by_key(|message| message.created_at);
+        collected
+    } else {
+        vec![]
+    };
+
+    let recaps = if options.include_ai_context {
+        load_recaps(user_id, journal.id, &options, &traces, pool)?
+    } else {
+        vec![]
+    };
+
+    let mut exported_traces = Vec::with_capacity(traces.len());
+    for trace in traces {
+        let (image, attachments) = if options.format.is_archive() {
+            load_trace_files(&trace, pool)?
+        } else {
+            (None, Vec::new())
+        };
+        let mirrors = if options.include_ai_context {
+            TraceMirror::find_by_trace(trace.id, pool)?
+        } else {
+            Vec::new()
+        };
+        exported_traces.push(ExportedTrace {
+            file_stem: trace_file_stem(&trace),
+            trace,
+            image,
+            attachments,
+            mirrors,
+        });
+    }
+
+    Ok(JournalExport {
+        journal,
+        options,
+        exported_at: Utc::now().naive_utc(),
+        traces: exported_traces,
+        messages,
+        recaps,
+    })
+}
+
+pub async fn fetch_export_files(export: &mut JournalExport) -> Result<(), PpdcError> {
+    for file in export.files_mut() {
+        file.bytes = file.asset.read_bytes().await?;
+    }
+    Ok(())
+}
+
+/// Builds the archive, stores it as an asset of the user and signs a download link.
+async fn store_export_archive(
+    user_id: Uuid,
+    export: &JournalExport,
+    pool: &DbPool,
+) -> Result<(Asset, String, NaiveDateTime), PpdcError> {
+    let format = export.options.format;
+    let bytes = match format {
+        JournalExportFormat::Zip => build_zip(export)?,
+        JournalExportFormat::Epub => build_epub(export)?,
+        JournalExportFormat::Html => render_html(export).into_bytes(),
+        _ => {
+            return Err(PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                format!("{} exports are not archives", format.to_api_value()),
+            ))
+        }
+    };
+    let uploaded =
+        upload_export_asset_for_user(user_id, pool, export.file_name(), format.mime_type(), bytes)
+            .await?;
+    let (url, expires_at) = uploaded
+        .asset
+        .signed_read_url(environment::get_journal_export_url_ttl_seconds())
+        .await?;
+    Ok((uploaded.asset, url, expires_at))
+}
+
+/// Exports a journal of the user.
+///
+/// Text formats are returned inline. Archives are built right away when small, and otherwise
+/// queued as an `EXPORT_JOURNAL` job whose download link `export_job_response` returns.
+pub async fn export_journal(
+    user_id: Uuid,
+    journal_id: Uuid,
+    options: JournalExportDto,
+    pool: &DbPool,
+) -> Result<JournalExportResponse, PpdcError> {
+    if let (Some(from), Some(to)) = (options.from, options.to) {
+        if from > to {
+            return Err(PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                "`from` must not be after `to`".to_string(),
+            ));
+        }
+    }
+    let journal = find_owned_journal(user_id, journal_id, pool)?;
+    let format = options.format;
+    let traces = find_export_traces(journal.id, &options, pool)?;
+
+    if !format.is_archive() {
+        let export = collect_journal_export(user_id, journal, options, traces, pool)?;
+        let content = match format {
+            JournalExportFormat::Text => render_text_export(&export),
+            JournalExportFormat::Json => render_json_export(&export)?,
+            _ => render_markdown_export(&export),
+        };
+        return Ok(JournalExportResponse {
+            content: Some(content),
+            ..export_response(format, JournalExportStatus::Ready)
+        });
+    }
+
+    let trace_ids = traces.iter().map(|trace| trace.id).collect::<Vec<_>>();
+    if traces.len() > INLINE_EXPORT_MAX_TRACES
+        || find_export_file_bytes(&trace_ids, pool)? > INLINE_EXPORT_MAX_FILE_BYTES
+    {
+        let payload = ExportJournalPayload {
+            user_id,
+            journal_id,
+            options,
+        };
+        let dedupe_key = format!(
+            "{}:{}:{}",
+            BackgroundJobType::ExportJournal.to_db(),
+            journal_id,
+            serde_json::to_string(&payload.options)?
+        );
+        let job = BackgroundJob::enqueue(
+            BackgroundJobType::ExportJournal,
+            serde_json::to_value(&payload)?,
+            Some(dedupe_key),
+            pool,
+        )?;
+        return Ok(JournalExportResponse {
+            job_id: Some(job.id),
+            ..export_response(format, JournalExportStatus::Pending)
+        });
+    }
+
+    let mut export = collect_journal_export(user_id, journal, options, traces, pool)?;
+    fetch_export_files(&mut export).await?;
+    let (asset, download_url, expires_at) = store_export_archive(user_id, &export, pool).await?;
+    Ok(JournalExportResponse {
+        asset_id: Some(asset.id),
+        file_name: Some(export.file_name()),
+        download_url: Some(download_url),
+        expires_at: Some(expires_at),
+        ..export_response(format, JournalExportStatus::Ready)
+    })
+}
+
+/// Runs an `EXPORT_JOURNAL` job.
+pub async fn run_export_job(
+    payload: ExportJournalPayload,
+    pool: &DbPool,
+) -> Result<ExportJournalResult, PpdcError> {
+    let journal = find_owned_journal(payload.user_id, payload.journal_id, pool)?;
+    let traces = find_export_traces(journal.id, &payload.options, pool)?;
+    let mut export =
+        collect_journal_export(payload.user_id, journal, payload.options, traces, pool)?;
+    fetch_export_files(&mut export).await?;
+    let (asset, _, _) = store_export_archive(payload.user_id, &export, pool).await?;
+    Ok(ExportJournalResult {
+        asset_id: asset.id,
+        file_name: export.file_name(),
+        trace_count: export.traces.len(),
+    })
+}
+
+/// State of a background export of the user's journal, with a fresh download link once built.
+pub async fn export_job_response(
+    user_id: Uuid,
+    journal_id: Uuid,
+    job_id: Uuid,
+    pool: &DbPool,
+) -> Result<JournalExportResponse, PpdcError> {
+    let not_found = || PpdcError::new(404, ErrorType::ApiError, "Export not found".to_string());
+    let job = BackgroundJob::find(job_id, pool)?;
+    if job.job_type_enum() != Some(BackgroundJobType::ExportJournal) {
+        return Err(not_found());
+    }
+    let payload = serde_json::from_value::<ExportJournalPayload>(job.payload.clone())?;
+    if payload.user_id != user_id || payload.journal_id != journal_id {
+        return Err(not_found());
+    }
+    let format = payload.options.format;
+
+    match job.status {
+        BackgroundJobStatus::Pending | BackgroundJobStatus::Running => Ok(JournalExportResponse {
+            job_id: Some(job.id),
+            ..export_response(format, JournalExportStatus::Pending)
+        }),
+        BackgroundJobStatus::Failed => Ok(JournalExportResponse {
+            job_id: Some(job.id),
+            error: job.last_error.clone(),
+            ..export_response(format, JournalExportStatus::Failed)
+        }),
+        BackgroundJobStatus::Succeeded => {
+            let result = job.result.clone().ok_or_else(not_found)?;
+            let result = serde_json::from_value::<ExportJournalResult>(result)?;
+            let asset = Asset::find(result.asset_id, pool).map_err(|err| {
+                if err.status_code == 404 {
+                    PpdcError::new(
+                        410,
+                        ErrorType::ApiError,
+                        "Export expired, request a new one".to_string(),
+                    )
+                } else {
+                    err
+                }
+            })?;
+            let (download_url, expires_at) = asset
+                .signed_read_url(environment::get_journal_export_url_ttl_seconds())
+                .await?;
+            Ok(JournalExportResponse {
+                job_id: Some(job.id),
+                asset_id: Some(asset.id),
+                file_name: Some(result.file_name),
+                download_url: Some(download_url),
+                expires_at: Some(expires_at),
+                ..export_response(format, JournalExportStatus::Ready)
+            })
+        }
+    }
+}
+
+/// Deletes journal export archives once their download links have expired.
+pub async fn prune_expired_exports(pool: &DbPool) -> Result<usize, PpdcError> {
+    let ttl = Duration::seconds(environment::get_journal_export_url_ttl_seconds() as i64);
+    let cutoff = Utc::now().naive_utc() - ttl;
+    let asset_ids =
+        find_journal_export_asset_ids_created_before(cutoff, EXPIRED_EXPORTS_BATCH_SIZE, pool)?;
+    let mut deleted = 0;
+    for asset_id in asset_ids {
+        let Some(asset) = find_asset(asset_id, pool)? else {
+            continue;
+        };
+        match asset.delete(pool).await {
+            Ok(()) => deleted += 1,
+            Err(err) => tracing::warn!(
+                target: "journal_export",
+                "expired_export_delete_failed asset_id={} error={}",
+                asset_id,
+                err.message
+            ),
+        }
+    }
+    Ok(deleted)
+}
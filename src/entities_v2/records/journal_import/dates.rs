@@ -0,0 +1,580 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use regex::{Captures, Regex};
+
+use super::model::DateConfidence;
+
+/// Month names of the supported languages; a name shared by two languages has one entry.
+const MONTHS: &[(&str, u32)] = &[
+    ("janvier", 1),
+    ("january", 1),
+    ("jan", 1),
+    ("enero", 1),
+    ("januar", 1),
+    ("jänner", 1),
+    ("gennaio", 1),
+    ("février", 2),
+    ("fevrier", 2),
+    ("february", 2),
+    ("feb", 2),
+    ("febrero", 2),
+    ("februar", 2),
+    ("febbraio", 2),
+    ("mars", 3),
+    ("march", 3),
+    ("mar", 3),
+    ("marzo", 3),
+    ("märz", 3),
+    ("maerz", 3),
+    ("avril", 4),
+    ("april", 4),
+    ("apr", 4),
+    ("abril", 4),
+    ("aprile", 4),
+    ("mai", 5),
+    ("may", 5),
+    ("mayo", 5),
+    ("maggio", 5),
+    ("juin", 6),
+    ("june", 6),
+    ("jun", 6),
+    ("junio", 6),
+    ("juni", 6),
+    ("giugno", 6),
+    ("juillet", 7),
+    ("july", 7),
+    ("jul", 7),
+    ("julio", 7),
+    ("juli", 7),
+    ("luglio", 7),
+    ("août", 8),
+    ("aout", 8),
+    ("august", 8),
+    ("aug", 8),
+    ("agosto", 8),
+    ("septembre", 9),
+    ("september", 9),
+    ("sept", 9),
+    ("sep", 9),
+    ("septiembre", 9),
+    ("setiembre", 9),
+    ("settembre", 9),
+    ("octobre", 10),
+    ("october", 10),
+    ("oct", 10),
+    ("octubre", 10),
+    ("oktober", 10),
+    ("ottobre", 10),
+    ("novembre", 11),
+    ("november", 11),
+    ("nov", 11),
+    ("noviembre", 11),
+    ("décembre", 12),
+    ("decembre", 12),
+    ("december", 12),
+    ("dec", 12),
+    ("diciembre", 12),
+    ("dezember", 12),
+    ("dicembre", 12),
+];
+
+const WEEKDAYS: &[&str] = &[
+    "lundi",
+    "mardi",
+    "mercredi",
+    "jeudi",
+    "vendredi",
+    "samedi",
+    "dimanche",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+    "mon",
+    "tue",
+    "tues",
+    "wed",
+    "thu",
+    "thur",
+    "thurs",
+    "fri",
+    "sat",
+    "sun",
+    "lunes",
+    "martes",
+    "miércoles",
+    "miercoles",
+    "jueves",
+    "viernes",
+    "sábado",
+    "sabado",
+    "domingo",
+    "montag",
+    "dienstag",
+    "mittwoch",
+    "donnerstag",
+    "freitag",
+    "samstag",
+    "sonnabend",
+    "sonntag",
+    "lunedì",
+    "lunedi",
+    "martedì",
+    "martedi",
+    "mercoledì",
+    "mercoledi",
+    "giovedì",
+    "giovedi",
+    "venerdì",
+    "venerdi",
+    "sabato",
+    "domenica",
+];
+
+/// Relative headers and their offset in days from the import date.
+const RELATIVE_DAYS: &[(&str, i64)] = &[
+    ("today", 0),
+    ("aujourd'hui", 0),
+    ("aujourd’hui", 0),
+    ("hoy", 0),
+    ("heute", 0),
+    ("oggi", 0),
+    ("yesterday", -1),
+    ("hier", -1),
+    ("ayer", -1),
+    ("gestern", -1),
+    ("ieri", -1),
+    ("the day before yesterday", -2),
+    ("day before yesterday", -2),
+    ("avant-hier", -2),
+    ("avant hier", -2),
+    ("anteayer", -2),
+    ("antier", -2),
+    ("vorgestern", -2),
+    ("l'altro ieri", -2),
+    ("altro ieri", -2),
+    ("ieri l'altro", -2),
+];
+
+const SUPPORTED_LANGUAGES: &[&str] = &["fr", "en", "es", "de", "it"];
+
+/// Regions writing numeric dates month first.
+const MONTH_FIRST_REGIONS: &[&str] = &["us"];
+
+/// Order of day and month in numeric dates such as `03/04/2026`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumericDateOrder {
+    #[default]
+    DayFirst,
+    MonthFirst,
+}
+
+/// Locale date headers are read with.
+///
+/// Long-form and relative headers are recognised in every supported language; the locale
+/// decides how numeric dates are read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DateLocale {
+    pub numeric_order: NumericDateOrder,
+}
+
+impl DateLocale {
+    /// Reads a tag such as `fr`, `en-US` or `de_DE`; `None` for unsupported languages.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let normalized = tag.trim().to_lowercase().replace('_', "-");
+        let mut parts = normalized.split('-');
+        let language = parts.next()?;
+        if !SUPPORTED_LANGUAGES.contains(&language) {
+            return None;
+        }
+        let region = parts.next();
+        if region.is_some_and(|region| region.len() != 2) || parts.next().is_some() {
+            return None;
+        }
+        let numeric_order = if region.is_some_and(|region| MONTH_FIRST_REGIONS.contains(&region)) {
+            NumericDateOrder::MonthFirst
+        } else {
+            NumericDateOrder::DayFirst
+        };
+        Some(DateLocale { numeric_order })
+    }
+}
+
+/// Day a header names, before missing years and relative days are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderDay {
+    Calendar {
+        year: Option<i32>,
+        month: u32,
+        day: u32,
+    },
+    /// Days from the import date, as in "Yesterday".
+    Relative(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderDate {
+    pub day: HeaderDay,
+    pub time: Option<NaiveTime>,
+}
+
+/// ISO form of the header date; a missing year is written `--MM-DD`.
+impl fmt::Display for HeaderDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.day {
+            HeaderDay::Calendar {
+                year: Some(year),
+                month,
+                day,
+            } => write!(f, "{:04}-{:02}-{:02}", year, month, day)?,
+            HeaderDay::Calendar {
+                year: None,
+                month,
+                day,
+            } => write!(f, "--{:02}-{:02}", month, day)?,
+            HeaderDay::Relative(offset) => write!(f, "{:+}d", offset)?,
+        }
+        if let Some(time) = self.time {
+            write!(f, " {}", time.format("%H:%M"))?;
+        }
+        Ok(())
+    }
+}
+
+fn alternation(words: impl Iterator<Item = &'static str>) -> String {
+    let mut words = words.collect::<Vec<_>>();
+    // Longest first so that `sept` is not read as `sep`.
+    words.sort_by_key(|word| std::cmp::Reverse(word.chars().count()));
+    words
+        .into_iter()
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+const TIME_PATTERN: &str = r"(?:\s*,?\s*(?:à|at|um|alle|a las|a|-)?\s*(?P<hour>\d{1,2})(?:(?:h|:)(?P<minute>\d{2})?\s*(?P<meridiem>[ap]\.?m\.?)?|\s*(?P<meridiem_only>[ap]\.?m\.?))(?:\s*uhr)?)?";
+
+struct HeaderPatterns {
+    iso_date_time: Regex,
+    iso_date: Regex,
+    day_month: Regex,
+    month_day: Regex,
+    numeric: Regex,
+    relative: Regex,
+}
+
+fn header_patterns() -> &'static HeaderPatterns {
+    static PATTERNS: OnceLock<HeaderPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let months = alternation(MONTHS.iter().map(|(name, _)| *name));
+        let weekday = format!(
+            r"(?:(?:{})\.?,?\s+)?",
+            alternation(WEEKDAYS.iter().copied())
+        );
+        let day = r"(?P<day>\d{1,2})(?:er|st|nd|rd|th|º|°|\.)?";
+        let build = |pattern: String| Regex::new(&pattern).expect("valid regex");
+        HeaderPatterns {
+            iso_date_time: build(r"(\d{4})-(\d{2})-(\d{2})\s(\d{2}):(\d{2})".to_string()),
+            iso_date: build(r"(\d{4})-(\d{2})-(\d{2})".to_string()),
+            day_month: build(format!(
+                r"^(?i){weekday}{day}\s+(?:de\s+)?(?P<month>{months})\.?(?:,?\s+(?:de\s+)?(?P<year>\d{{4}}))?{TIME_PATTERN}[\s.:]*$"
+            )),
+            month_day: build(format!(
+                r"^(?i){weekday}(?P<month>{months})\.?\s+{day},?(?:\s+(?P<year>\d{{4}}))?{TIME_PATTERN}[\s.:]*$"
+            )),
+            numeric: build(format!(
+                r"^(?i){weekday}(?P<first>\d{{1,2}})[/.\-](?P<second>\d{{1,2}})[/.\-](?P<year>\d{{4}}){TIME_PATTERN}[\s.:]*$"
+            )),
+            relative: build(format!(
+                r"^(?i)(?P<relative>{})[\s,.:!]*{TIME_PATTERN}[\s.:]*$",
+                alternation(RELATIVE_DAYS.iter().map(|(word, _)| *word))
+            )),
+        }
+    })
+}
+
+fn capture_number<T: std::str::FromStr>(captures: &Captures, name: &str) -> Option<T> {
+    captures.name(name)?.as_str().parse().ok()
+}
+
+fn capture_time(captures: &Captures) -> Option<Option<NaiveTime>> {
+    let Some(hour) = capture_number::<u32>(captures, "hour") else {
+        return Some(None);
+    };
+    let minute = capture_number::<u32>(captures, "minute").unwrap_or(0);
+    let meridiem = captures
+        .name("meridiem")
+        .or_else(|| captures.name("meridiem_only"))
+        .map(|meridiem| meridiem.as_str().to_lowercase());
+    let hour = match meridiem
+        .as_deref()
+        .map(|meridiem| meridiem.starts_with('p'))
+    {
+        Some(true) if hour < 12 => hour + 12,
+        Some(false) if hour == 12 => 0,
+        Some(_) if hour > 12 => return None,
+        _ => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0).map(Some)
+}
+
+fn calendar_date(
+    year: Option<i32>,
+    month: u32,
+    day: u32,
+    time: Option<NaiveTime>,
+) -> Option<HeaderDate> {
+    // A header naming an impossible day is not a date header.
+    NaiveDate::from_ymd_opt(year.unwrap_or(2000), month, day)?;
+    Some(HeaderDate {
+        day: HeaderDay::Calendar { year, month, day },
+        time,
+    })
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    MONTHS
+        .iter()
+        .find(|(month, _)| *month == name)
+        .map(|(_, number)| *number)
+}
+
+pub(super) fn strip_markdown_heading_prefix(line: &str) -> &str {
+    let trimmed = line.trim_start();
+    let without_hashes = trimmed.trim_start_matches('#').trim_start();
+    if without_hashes.is_empty() {
+        trimmed
+    } else {
+        without_hashes
+    }
+}
+
+/// Date a header line names, in any supported language.
+///
+/// ISO dates are found anywhere in the line; long-form, numeric and relative dates must make
+/// up the whole header, optionally after a weekday and followed by a time.
+pub fn parse_date_header(line: &str, locale: DateLocale) -> Option<HeaderDate> {
+    let line = strip_markdown_heading_prefix(line).trim();
+    let patterns = header_patterns();
+
+    if let Some(captures) = patterns.iso_date_time.captures(line) {
+        let time =
+            NaiveTime::from_hms_opt(captures[4].parse().ok()?, captures[5].parse().ok()?, 0)?;
+        return calendar_date(
+            captures[1].parse().ok(),
+            captures[2].parse().ok()?,
+            captures[3].parse().ok()?,
+            Some(time),
+        );
+    }
+    if let Some(captures) = patterns.iso_date.captures(line) {
+        return calendar_date(
+            captures[1].parse().ok(),
+            captures[2].parse().ok()?,
+            captures[3].parse().ok()?,
+            None,
+        );
+    }
+
+    if let Some(captures) = patterns
+        .day_month
+        .captures(line)
+        .or_else(|| patterns.month_day.captures(line))
+    {
+        return calendar_date(
+            capture_number(&captures, "year"),
+            month_number(&captures["month"])?,
+            capture_number(&captures, "day")?,
+            capture_time(&captures)?,
+        );
+    }
+    if let Some(captures) = patterns.numeric.captures(line) {
+        let first = capture_number(&captures, "first")?;
+        let second = capture_number(&captures, "second")?;
+        let (day, month) = match locale.numeric_order {
+            NumericDateOrder::DayFirst => (first, second),
+            NumericDateOrder::MonthFirst => (second, first),
+        };
+        return calendar_date(
+            capture_number(&captures, "year"),
+            month,
+            day,
+            capture_time(&captures)?,
+        );
+    }
+    if let Some(captures) = patterns.relative.captures(line) {
+        let word = captures["relative"].to_lowercase();
+        let offset = RELATIVE_DAYS
+            .iter()
+            .find(|(relative, _)| *relative == word)
+            .map(|(_, offset)| *offset)?;
+        return Some(HeaderDate {
+            day: HeaderDay::Relative(offset),
+            time: capture_time(&captures)?,
+        });
+    }
+    None
+}
+
+/// The year putting `month`/`day` closest to `anchor`, within a year either way.
+fn closest_year(month: u32, day: u32, anchor: NaiveDate) -> Option<NaiveDate> {
+    [anchor.year() - 1, anchor.year(), anchor.year() + 1]
+        .into_iter()
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+        .min_by_key(|date| (*date - anchor).num_days().abs())
+}
+
+/// Resolves a sequence of header dates into dates with their confidence.
+///
+/// A missing year is taken from the closest dated header before it, or else after it, so a
+/// journal running from December into January moves to the next year; with no year anywhere,
+/// the most recent such day up to `today` is used. Relative headers count from `today`.
+pub fn resolve_header_dates(
+    headers: &[Option<HeaderDate>],
+    today: NaiveDate,
+) -> Vec<Option<(NaiveDateTime, DateConfidence)>> {
+    let explicit_dates = headers
+        .iter()
+        .map(|header| match header.map(|header| header.day) {
+            Some(HeaderDay::Calendar {
+                year: Some(year),
+                month,
+                day,
+            }) => NaiveDate::from_ymd_opt(year, month, day),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut previous: Option<NaiveDate> = None;
+    let mut resolved = Vec::with_capacity(headers.len());
+    for (index, header) in headers.iter().enumerate() {
+        let Some(header) = header else {
+            resolved.push(None);
+            continue;
+        };
+        let day = match header.day {
+            HeaderDay::Calendar { year: Some(_), .. } => {
+                explicit_dates[index].map(|date| (date, DateConfidence::Explicit))
+            }
+            HeaderDay::Calendar {
+                year: None,
+                month,
+                day,
+            } => {
+                let anchor =
+                    previous.or_else(|| explicit_dates[index..].iter().flatten().next().copied());
+                let date = match anchor {
+                    Some(anchor) => closest_year(month, day, anchor),
+                    None => (0..=4)
+                        .filter_map(|back| NaiveDate::from_ymd_opt(today.year() - back, month, day))
+                        .find(|date| *date <= today),
+                };
+                date.map(|date| (date, DateConfidence::YearInferred))
+            }
+            HeaderDay::Relative(offset) => {
+                Some((today + Duration::days(offset), DateConfidence::Relative))
+            }
+        };
+        if let Some((date, _)) = day {
+            previous = Some(date);
+        }
+        resolved.push(day.map(|(date, confidence)| {
+            let time = header
+                .time
+                .unwrap_or_else(|| NaiveTime::from_hms_opt(12, 0, 0).expect("valid noon"));
+            (date.and_time(time), confidence)
+        }));
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Option<String> {
+        parse_date_header(line, DateLocale::default()).map(|date| date.to_string())
+    }
+
+    #[test]
+    fn reads_long_form_dates_in_every_language() {
+        assert_eq!(
+            parse("Mardi 2 décembre 2026 17h30"),
+            Some("2026-12-02 17:30".to_string())
+        );
+        assert_eq!(
+            parse("Tuesday, December 2nd, 2026 at 5:30 pm"),
+            Some("2026-12-02 17:30".to_string())
+        );
+        assert_eq!(parse("2 December 2026"), Some("2026-12-02".to_string()));
+        assert_eq!(
+            parse("martes, 2 de diciembre de 2026"),
+            Some("2026-12-02".to_string())
+        );
+        assert_eq!(
+            parse("Dienstag, 2. Dezember 2026"),
+            Some("2026-12-02".to_string())
+        );
+        assert_eq!(parse("martedì 2 dicembre"), Some("--12-02".to_string()));
+        assert_eq!(parse("Yesterday"), Some("-1d".to_string()));
+        assert_eq!(parse("Hier à 9h"), Some("-1d 09:00".to_string()));
+        assert_eq!(parse("Une journée à la mer"), None);
+        assert_eq!(parse("31 février 2026"), None);
+    }
+
+    #[test]
+    fn numeric_dates_follow_the_locale() {
+        let us = DateLocale::from_tag("en-US").expect("supported locale");
+        let french = DateLocale::from_tag("fr_FR").expect("supported locale");
+        assert_eq!(
+            parse_date_header("03/04/2026", us).map(|date| date.to_string()),
+            Some("2026-03-04".to_string())
+        );
+        assert_eq!(
+            parse_date_header("03/04/2026", french).map(|date| date.to_string()),
+            Some("2026-04-03".to_string())
+        );
+        assert_eq!(DateLocale::from_tag("pt-BR"), None);
+    }
+
+    #[test]
+    fn missing_years_follow_the_surrounding_blocks() {
+        let today = NaiveDate::from_ymd_opt(2026, 6, 1).expect("valid date");
+        let header = |line: &str| parse_date_header(line, DateLocale::default());
+        let resolved = resolve_header_dates(
+            &[
+                header("30 décembre"),
+                header("Mardi 31 décembre 2024"),
+                header("2 janvier"),
+                None,
+                header("Yesterday"),
+            ],
+            today,
+        );
+        let dates = resolved
+            .iter()
+            .map(|date| date.map(|(date, confidence)| (date.date().to_string(), confidence)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            dates,
+            vec![
+                Some(("2024-12-30".to_string(), DateConfidence::YearInferred)),
+                Some(("2024-12-31".to_string(), DateConfidence::Explicit)),
+                Some(("2025-01-02".to_string(), DateConfidence::YearInferred)),
+                None,
+                Some(("2026-05-31".to_string(), DateConfidence::Relative)),
+            ]
+        );
+        assert_eq!(
+            resolve_header_dates(&[header("3 décembre")], today)[0].map(|(date, _)| date.date()),
+            NaiveDate::from_ymd_opt(2025, 12, 3)
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::entities_v2::error::{ErrorType, PpdcError};
+
+use super::super::importer::{
+    ImportContext, ImportFile, ImportFormat, ImportSource, ImportedAttachment, ImportedEntry,
+    JournalImporter, ParsedImport,
+};
+use super::super::model::DateConfidence;
+use super::markdown::{collapse_blank_lines, normalize_tags, split_title};
+
+/// Day One JSON export, alone or zipped with its `photos/`, `videos/`, `audios/` and `pdfs/`.
+pub struct DayOneImporter;
+
+#[derive(Deserialize)]
+struct DayOneExport {
+    entries: Vec<DayOneEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DayOneEntry {
+    #[serde(default)]
+    uuid: Option<String>,
+    creation_date: DateTime<Utc>,
+    #[serde(default)]
+    time_zone: Option<String>,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    photos: Vec<DayOneMedia>,
+    #[serde(default)]
+    videos: Vec<DayOneMedia>,
+    #[serde(default)]
+    audios: Vec<DayOneMedia>,
+    #[serde(default)]
+    pdf_attachments: Vec<DayOneMedia>,
+}
+
+#[derive(Deserialize)]
+struct DayOneMedia {
+    #[serde(default)]
+    md5: Option<String>,
+}
+
+fn export_files(source: &ImportSource) -> impl Iterator<Item = &ImportFile> {
+    source
+        .files_with_extension(&["json"])
+        .filter(|file| file.text().contains("\"entries\""))
+}
+
+/// Day One escapes markdown punctuation in plain text, as in `1\. item`.
+fn unescape_markdown(text: &str) -> String {
+    let escape_regex = Regex::new(r"\\([\\`*_{}\[\]()#+\-.!>|~])").expect("valid regex");
+    escape_regex.replace_all(text, "$1").to_string()
+}
+
+/// Media are stored as `<folder>/<md5>.<extension>`.
+fn media_file<'a>(source: &'a ImportSource, folder: &str, md5: &str) -> Option<&'a ImportFile> {
+    source.files.iter().find(|file| {
+        file.stem().eq_ignore_ascii_case(md5)
+            && file
+                .path
+                .rsplit('/')
+                .nth(1)
+                .is_some_and(|parent| parent == folder)
+    })
+}
+
+impl DayOneEntry {
+    fn into_entry(self, source: &ImportSource) -> ImportedEntry {
+        let moment_regex =
+            Regex::new(r"!\[[^\]]*\]\(dayone-moment:/+[^)]*\)").expect("valid regex");
+        let text = unescape_markdown(&moment_regex.replace_all(&self.text, ""));
+        let (title, body) = split_title(&text);
+        let content = collapse_blank_lines(body);
+        let time_zone = self
+            .time_zone
+            .as_deref()
+            .and_then(|time_zone| time_zone.parse::<Tz>().ok())
+            .unwrap_or(Tz::UTC);
+        let interaction_date = self.creation_date.with_timezone(&time_zone).naive_local();
+
+        let attachments = [
+            ("photos", &self.photos),
+            ("videos", &self.videos),
+            ("audios", &self.audios),
+            ("pdfs", &self.pdf_attachments),
+        ]
+        .into_iter()
+        .flat_map(|(folder, media)| {
+            media
+                .iter()
+                .filter_map(|media| media_file(source, folder, media.md5.as_deref()?))
+                .map(ImportedAttachment::from_file)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+        let header = title
+            .clone()
+            .or_else(|| content.lines().next().map(|line| line.to_string()))
+            .or(self.uuid)
+            .unwrap_or_default();
+        ImportedEntry {
+            title,
+            date: Some((interaction_date, DateConfidence::Explicit)),
+            tags: normalize_tags(self.tags),
+            attachments,
+            ..ImportedEntry::new(header, content)
+        }
+    }
+}
+
+impl JournalImporter for DayOneImporter {
+    fn format(&self) -> ImportFormat {
+        ImportFormat::DayOne
+    }
+
+    fn detects(&self, source: &ImportSource) -> bool {
+        export_files(source).next().is_some()
+    }
+
+    /// Entries of every journal of the export, oldest first.
+    fn parse(
+        &self,
+        source: &ImportSource,
+        _context: &ImportContext,
+    ) -> Result<ParsedImport, PpdcError> {
+        let mut entries = Vec::new();
+        for file in export_files(source) {
+            let export = serde_json::from_slice::<DayOneExport>(&file.bytes).map_err(|err| {
+                PpdcError::new(
+                    400,
+                    ErrorType::ApiError,
+                    format!("Invalid Day One export {}: {}", file.path, err),
+                )
+            })?;
+            entries.extend(export.entries);
+        }
+        entries.sort_by_key(|entry| entry.creation_date);
+        Ok(ParsedImport {
+            entries: entries
+                .into_iter()
+                .map(|entry| entry.into_entry(source))
+                .collect(),
+            journal_subtitle: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::super::super::dates::DateLocale;
+    use super::*;
+
+    #[test]
+    fn maps_entries_with_local_dates_tags_and_photos() {
+        let json = r##"{"metadata":{"version":"1.0"},"entries":[{
+            "uuid":"A1","creationDate":"2024-03-05T22:30:00Z","timeZone":"Europe/Paris",
+            "text":"# Soirée\n\nRetour à 23h\\. ![](dayone-moment://P1)\n","tags":["Famille"],
+            "photos":[{"identifier":"P1","md5":"abc123","type":"jpeg"}]}]}"##;
+        let source = ImportSource {
+            files: vec![
+                ImportFile {
+                    path: "Journal.json".to_string(),
+                    bytes: json.as_bytes().to_vec(),
+                },
+                ImportFile {
+                    path: "photos/abc123.jpeg".to_string(),
+                    bytes: vec![1, 2],
+                },
+            ],
+        };
+        assert!(DayOneImporter.detects(&source));
+
+        let entry = DayOneImporter
+            .parse(
+                &source,
+                &ImportContext {
+                    locale: DateLocale::default(),
+                    today: NaiveDate::from_ymd_opt(2026, 3, 15).expect("valid date"),
+                },
+            )
+            .expect("valid export")
+            .entries
+            .remove(0);
+        assert_eq!(entry.title.as_deref(), Some("Soirée"));
+        assert_eq!(entry.content, "Retour à 23h.");
+        assert_eq!(entry.tags, vec!["famille"]);
+        assert_eq!(entry.attachments[0].file_name, "abc123.jpeg");
+        assert_eq!(
+            entry.date.map(|(date, _)| date),
+            NaiveDate::from_ymd_opt(2024, 3, 5).and_then(|d| d.and_hms_opt(23, 30, 0))
+        );
+    }
+}
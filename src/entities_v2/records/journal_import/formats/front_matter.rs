@@ -0,0 +1,128 @@
+use crate::entities_v2::error::PpdcError;
+
+use super::super::importer::{
+    ImportContext, ImportFile, ImportFormat, ImportSource, ImportedEntry, JournalImporter,
+    ParsedImport,
+};
+use super::super::model::DateConfidence;
+use super::markdown::{
+    normalize_tags, parse_loose_date, sort_by_date_undated_last, split_front_matter, split_title,
+    take_embedded_files,
+};
+
+/// Markdown entries with YAML front matter (`date`, `title`, `tags`), as static site and
+/// plain-text journal tools write them.
+pub struct FrontMatterImporter;
+
+const DATE_KEYS: &[&str] = &["date", "created", "created_at"];
+
+fn entry_files(source: &ImportSource) -> impl Iterator<Item = &ImportFile> {
+    source
+        .files_with_extension(&["md", "markdown"])
+        .filter(|file| split_front_matter(&file.text()).0.is_some())
+}
+
+impl JournalImporter for FrontMatterImporter {
+    fn format(&self) -> ImportFormat {
+        ImportFormat::FrontMatter
+    }
+
+    fn detects(&self, source: &ImportSource) -> bool {
+        entry_files(source).next().is_some()
+    }
+
+    fn parse(
+        &self,
+        source: &ImportSource,
+        _context: &ImportContext,
+    ) -> Result<ParsedImport, PpdcError> {
+        let mut entries = entry_files(source)
+            .map(|file| {
+                let text = file.text();
+                let (front_matter, body) = split_front_matter(&text);
+                let front_matter = front_matter.unwrap_or_default();
+                let (heading, body) = split_title(body);
+                let (content, attachments) = take_embedded_files(body, &file.path, source);
+                let title = front_matter
+                    .scalar("title")
+                    .map(|title| title.to_string())
+                    .or(heading);
+                let date = DATE_KEYS
+                    .iter()
+                    .find_map(|key| front_matter.scalar(key).and_then(parse_loose_date));
+                ImportedEntry {
+                    title: title.clone(),
+                    date: date.map(|date| (date, DateConfidence::Explicit)),
+                    tags: normalize_tags(front_matter.list("tags")),
+                    attachments,
+                    ..ImportedEntry::new(title.unwrap_or_else(|| file.stem().to_string()), content)
+                }
+            })
+            .collect::<Vec<_>>();
+        sort_by_date_undated_last(&mut entries);
+        Ok(ParsedImport {
+            entries,
+            journal_subtitle: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::super::super::dates::DateLocale;
+    use super::*;
+
+    fn file(path: &str, text: &str) -> ImportFile {
+        ImportFile {
+            path: path.to_string(),
+            bytes: text.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn reads_front_matter_and_keeps_undated_entries_last() {
+        let source = ImportSource {
+            files: vec![
+                file("posts/a-draft.md", "---\ntitle: Brouillon\n---\nPas de date.\n"),
+                file(
+                    "posts/later.md",
+                    "---\ndate: 2024-03-06\ntags:\n  - Atlas\n---\n# Suite\n\nDeuxième jour.\n",
+                ),
+                file(
+                    "posts/first.md",
+                    "---\ntitle: \"Début\"\ncreated: 2024-03-05\ntags: [Work]\n---\nPremier jour.\n",
+                ),
+                file("posts/notes.md", "# Sans front matter\n"),
+            ],
+        };
+        assert!(FrontMatterImporter.detects(&source));
+
+        let entries = FrontMatterImporter
+            .parse(
+                &source,
+                &ImportContext {
+                    locale: DateLocale::default(),
+                    today: NaiveDate::from_ymd_opt(2026, 3, 15).expect("valid date"),
+                },
+            )
+            .expect("valid export")
+            .entries;
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| entry.title.as_deref())
+                .collect::<Vec<_>>(),
+            vec![Some("Début"), Some("Suite"), Some("Brouillon")]
+        );
+        assert_eq!(entries[0].tags, vec!["work"]);
+        assert_eq!(entries[1].tags, vec!["atlas"]);
+        assert_eq!(entries[1].content, "Deuxième jour.");
+        assert_eq!(
+            entries[0].date.map(|(date, _)| date),
+            NaiveDate::from_ymd_opt(2024, 3, 5).and_then(|d| d.and_hms_opt(12, 0, 0))
+        );
+        assert!(entries[2].date.is_none());
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use regex::Regex;
+
+use super::super::importer::{ImportSource, ImportedAttachment, ImportedEntry};
+
+/// Value of a front matter key: a scalar or a list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrontMatterValue {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FrontMatter {
+    fields: HashMap<String, FrontMatterValue>,
+}
+
+impl FrontMatter {
+    pub fn scalar(&self, key: &str) -> Option<&str> {
+        match self.fields.get(key)? {
+            FrontMatterValue::Scalar(value) if !value.is_empty() => Some(value),
+            _ => None,
+        }
+    }
+
+    /// A list key, also accepting a comma separated scalar.
+    pub fn list(&self, key: &str) -> Vec<String> {
+        match self.fields.get(key) {
+            Some(FrontMatterValue::List(values)) => values.clone(),
+            Some(FrontMatterValue::Scalar(value)) => value
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    let quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+    if quoted {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Splits a leading `---` block off a markdown file.
+///
+/// Only the YAML subset journal tools write is read: `key: value`, inline `[a, b]` lists and
+/// `- item` lists. Text without front matter is returned whole.
+pub fn split_front_matter(text: &str) -> (Option<FrontMatter>, &str) {
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (None, text);
+    };
+    let mut offset = 0;
+    let mut end = None;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            end = Some((offset, offset + line.len()));
+            break;
+        }
+        offset += line.len();
+    }
+    let Some((yaml_end, body_start)) = end else {
+        return (None, text);
+    };
+
+    let mut fields = HashMap::new();
+    let mut current_list: Option<(String, Vec<String>)> = None;
+    for line in rest[..yaml_end].lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let (Some(item), Some((_, items))) = (trimmed.strip_prefix("- "), current_list.as_mut())
+        {
+            items.push(unquote(item));
+            continue;
+        }
+        if let Some((key, items)) = current_list.take() {
+            fields.insert(key, FrontMatterValue::List(items));
+        }
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+        if value.is_empty() {
+            current_list = Some((key, Vec::new()));
+        } else if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            let items = inner
+                .split(',')
+                .map(unquote)
+                .filter(|item| !item.is_empty())
+                .collect();
+            fields.insert(key, FrontMatterValue::List(items));
+        } else {
+            fields.insert(key, FrontMatterValue::Scalar(unquote(value)));
+        }
+    }
+    if let Some((key, items)) = current_list {
+        fields.insert(key, FrontMatterValue::List(items));
+    }
+
+    (Some(FrontMatter { fields }), &rest[body_start..])
+}
+
+/// Orders entries by date, keeping undated ones last in file order so they do not take the place
+/// of the first dated entry when dates are resolved.
+pub fn sort_by_date_undated_last(entries: &mut [ImportedEntry]) {
+    entries.sort_by_key(|entry| (entry.date.is_none(), entry.date.map(|(date, _)| date)));
+}
+
+/// Reads the date formats front matter and file names use; dates alone are set at noon.
+pub fn parse_loose_date(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Some(date_time.naive_local());
+    }
+    for format in [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ] {
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(date_time);
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+}
+
+/// Tags without their leading `#`, lowercased and deduplicated in order.
+pub fn normalize_tags(tags: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#').trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+/// Joins a relative link to the folder of the note, resolving `.` and `..`.
+fn join_relative(dir: &str, link: &str) -> String {
+    let mut parts = dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    for part in link.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Moves the local files a note embeds into attachments and drops their embeds from the text.
+///
+/// Handles `![alt](relative/path)` links and Obsidian `![[file.png]]` embeds; remote links
+/// and embeds of files missing from the upload are left as they are.
+pub fn take_embedded_files(
+    body: &str,
+    note_path: &str,
+    source: &ImportSource,
+) -> (String, Vec<ImportedAttachment>) {
+    let link_regex =
+        Regex::new(r"!\[[^\]]*\]\(<?([^)>]+?)>?(?:\s+\x22[^\x22]*\x22)?\)").expect("valid regex");
+    let wiki_regex = Regex::new(r"!\[\[([^\]|#]+)(?:[|#][^\]]*)?\]\]").expect("valid regex");
+    let dir = parent_dir(note_path);
+    let mut attachments: Vec<ImportedAttachment> = Vec::new();
+    let mut attach = |file: &ImportedAttachment| {
+        if !attachments
+            .iter()
+            .any(|existing| existing.file_name == file.file_name)
+        {
+            attachments.push(file.clone());
+        }
+    };
+
+    let body = link_regex.replace_all(body, |captures: &regex::Captures| {
+        let link = captures[1].trim();
+        if link.contains("://") {
+            return captures[0].to_string();
+        }
+        let decoded = urlencoding::decode(link)
+            .map(|link| link.into_owned())
+            .unwrap_or_else(|_| link.to_string());
+        match source.find_path(&join_relative(dir, &decoded)) {
+            Some(file) => {
+                attach(&ImportedAttachment::from_file(file));
+                String::new()
+            }
+            None => captures[0].to_string(),
+        }
+    });
+    let body = wiki_regex.replace_all(&body, |captures: &regex::Captures| {
+        let target = captures[1].trim();
+        let file = source
+            .find_path(&join_relative(dir, target))
+            .or_else(|| source.find_path(target))
+            .or_else(|| source.find_file_name(target.rsplit('/').next().unwrap_or(target)));
+        match file.filter(|file| file.extension().is_some_and(|ext| ext != "md")) {
+            Some(file) => {
+                attach(&ImportedAttachment::from_file(file));
+                String::new()
+            }
+            None => captures[0].to_string(),
+        }
+    });
+
+    (collapse_blank_lines(&body), attachments)
+}
+
+/// Trims the text and keeps at most one blank line between paragraphs.
+pub fn collapse_blank_lines(text: &str) -> String {
+    let mut collapsed = String::new();
+    let mut blank_run = 0;
+    for line in text.trim().lines() {
+        if line.trim().is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        collapsed.push_str(line.trim_end());
+        collapsed.push('\n');
+    }
+    collapsed.trim_end().to_string()
+}
+
+/// A leading `# Title` line and the text after it.
+pub fn split_title(body: &str) -> (Option<String>, &str) {
+    let trimmed = body.trim_start();
+    let Some(rest) = trimmed.strip_prefix("# ") else {
+        return (None, body);
+    };
+    let (title, rest) = rest.split_once('\n').unwrap_or((rest, ""));
+    let title = title.trim();
+    if title.is_empty() {
+        return (None, body);
+    }
+    (Some(title.to_string()), rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::importer::ImportFile;
+    use super::*;
+
+    #[test]
+    fn reads_scalars_and_both_list_styles() {
+        let text = "---\ntitle: \"Un jour\"\ndate: 2024-03-05\ntags: [work, Family]\nmood:\n  - calm\n  - tired\n---\nBody\n";
+        let (front_matter, body) = split_front_matter(text);
+        let front_matter = front_matter.expect("front matter");
+
+        assert_eq!(front_matter.scalar("title"), Some("Un jour"));
+        assert_eq!(front_matter.list("tags"), vec!["work", "Family"]);
+        assert_eq!(front_matter.list("mood"), vec!["calm", "tired"]);
+        assert_eq!(body, "Body\n");
+        assert_eq!(
+            parse_loose_date(front_matter.scalar("date").expect("date")),
+            NaiveDate::from_ymd_opt(2024, 3, 5).and_then(|d| d.and_hms_opt(12, 0, 0))
+        );
+    }
+
+    #[test]
+    fn embedded_local_files_become_attachments() {
+        let source = ImportSource {
+            files: vec![
+                ImportFile {
+                    path: "vault/attachments/photo.png".to_string(),
+                    bytes: vec![1],
+                },
+                ImportFile {
+                    path: "vault/Page/img 1.jpg".to_string(),
+                    bytes: vec![2],
+                },
+            ],
+        };
+        let body = "Matin.\n\n![[photo.png|300]]\n\n![alt](Page/img%201.jpg)\n\n![remote](https://x.test/a.png)\n\n![[Other note]]";
+        let (body, attachments) = take_embedded_files(body, "vault/2024-03-05.md", &source);
+
+        assert_eq!(
+            attachments
+                .iter()
+                .map(|attachment| attachment.file_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["img 1.jpg", "photo.png"]
+        );
+        assert_eq!(
+            body,
+            "Matin.\n\n![remote](https://x.test/a.png)\n\n![[Other note]]"
+        );
+    }
+}
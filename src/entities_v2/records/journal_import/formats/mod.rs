@@ -0,0 +1,6 @@
+pub mod day_one;
+pub mod front_matter;
+pub mod markdown;
+pub mod notion;
+pub mod obsidian;
+pub mod text;
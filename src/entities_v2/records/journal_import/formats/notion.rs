@@ -0,0 +1,170 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use regex::Regex;
+
+use crate::entities_v2::error::PpdcError;
+
+use super::super::importer::{
+    ImportContext, ImportFile, ImportFormat, ImportSource, ImportedEntry, JournalImporter,
+    ParsedImport,
+};
+use super::super::model::DateConfidence;
+use super::markdown::{
+    normalize_tags, sort_by_date_undated_last, split_title, take_embedded_files,
+};
+
+/// Notion markdown export: one page per file, named `<Title> <32 hex id>.md`, with its
+/// properties as `Key: value` lines under the title and its images in a folder of the same name.
+pub struct NotionImporter;
+
+/// Properties the entry date is read from, in order of preference.
+const DATE_PROPERTIES: &[&str] = &["date", "created", "created time"];
+const TAG_PROPERTIES: &[&str] = &["tags", "tag"];
+
+fn page_id_regex() -> Regex {
+    Regex::new(r"^(?P<title>.*?)\s+[0-9a-f]{32}$").expect("valid regex")
+}
+
+fn pages(source: &ImportSource) -> impl Iterator<Item = &ImportFile> {
+    let page_id_regex = page_id_regex();
+    source
+        .files_with_extension(&["md"])
+        .filter(move |file| page_id_regex.is_match(file.stem()))
+}
+
+/// Notion writes dates as `March 5, 2024` or `March 5, 2024 3:04 PM`, ranges with an arrow.
+fn parse_notion_date(value: &str) -> Option<NaiveDateTime> {
+    let value = value.split('→').next()?.trim();
+    for format in ["%B %d, %Y %I:%M %p", "%B %d, %Y %H:%M"] {
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(date_time);
+        }
+    }
+    ["%B %d, %Y", "%Y/%m/%d", "%Y-%m-%d"]
+        .into_iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+}
+
+/// Splits the `Key: value` property lines Notion puts right under the page title.
+fn split_properties(body: &str) -> (Vec<(String, String)>, &str) {
+    let body = body.trim_start_matches(['\n', '\r']);
+    let property_regex = Regex::new(r"^([^:\n]{1,40}):\s(.*)$").expect("valid regex");
+    let mut properties = Vec::new();
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let Some(captures) = property_regex.captures(line.trim_end()) else {
+            break;
+        };
+        properties.push((
+            captures[1].trim().to_lowercase(),
+            captures[2].trim().to_string(),
+        ));
+        offset += line.len();
+    }
+    (properties, &body[offset..])
+}
+
+impl JournalImporter for NotionImporter {
+    fn format(&self) -> ImportFormat {
+        ImportFormat::Notion
+    }
+
+    fn detects(&self, source: &ImportSource) -> bool {
+        pages(source).next().is_some()
+    }
+
+    fn parse(
+        &self,
+        source: &ImportSource,
+        _context: &ImportContext,
+    ) -> Result<ParsedImport, PpdcError> {
+        let page_id_regex = page_id_regex();
+        let mut entries = pages(source)
+            .map(|file| {
+                let text = file.text();
+                let (title, body) = split_title(&text);
+                let (properties, body) = split_properties(body);
+                let property = |names: &[&str]| {
+                    names.iter().find_map(|name| {
+                        properties
+                            .iter()
+                            .find(|(key, _)| key == name)
+                            .map(|(_, value)| value.as_str())
+                    })
+                };
+                let (content, attachments) = take_embedded_files(body, &file.path, source);
+                let title = title.or_else(|| {
+                    page_id_regex
+                        .captures(file.stem())
+                        .map(|captures| captures["title"].to_string())
+                });
+                let tags = property(TAG_PROPERTIES)
+                    .map(|tags| {
+                        tags.split(',')
+                            .map(|tag| tag.to_string())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                ImportedEntry {
+                    title: title.clone(),
+                    date: property(DATE_PROPERTIES)
+                        .and_then(parse_notion_date)
+                        .map(|date| (date, DateConfidence::Explicit)),
+                    tags: normalize_tags(tags),
+                    attachments,
+                    ..ImportedEntry::new(title.unwrap_or_default(), content)
+                }
+            })
+            .collect::<Vec<_>>();
+        sort_by_date_undated_last(&mut entries);
+        Ok(ParsedImport {
+            entries,
+            journal_subtitle: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::dates::DateLocale;
+    use super::*;
+
+    #[test]
+    fn reads_title_properties_and_page_images() {
+        let page = "# Réunion d'équipe\n\nCreated: March 5, 2024 3:04 PM\nTags: Work, Atlas\n\nOn a tranché.\n\n![Screenshot](R%C3%A9union%20d'%C3%A9quipe%200123456789abcdef0123456789abcdef/shot.png)\n";
+        let source = ImportSource {
+            files: vec![
+                ImportFile {
+                    path: "Export/Réunion d'équipe 0123456789abcdef0123456789abcdef.md".to_string(),
+                    bytes: page.as_bytes().to_vec(),
+                },
+                ImportFile {
+                    path: "Export/Réunion d'équipe 0123456789abcdef0123456789abcdef/shot.png"
+                        .to_string(),
+                    bytes: vec![1],
+                },
+            ],
+        };
+        assert!(NotionImporter.detects(&source));
+
+        let entry = NotionImporter
+            .parse(
+                &source,
+                &ImportContext {
+                    locale: DateLocale::default(),
+                    today: NaiveDate::from_ymd_opt(2026, 3, 15).expect("valid date"),
+                },
+            )
+            .expect("valid export")
+            .entries
+            .remove(0);
+        assert_eq!(entry.title.as_deref(), Some("Réunion d'équipe"));
+        assert_eq!(entry.content, "On a tranché.");
+        assert_eq!(entry.tags, vec!["work", "atlas"]);
+        assert_eq!(entry.attachments[0].file_name, "shot.png");
+        assert_eq!(
+            entry.date.map(|(date, _)| date),
+            NaiveDate::from_ymd_opt(2024, 3, 5).and_then(|d| d.and_hms_opt(15, 4, 0))
+        );
+    }
+}
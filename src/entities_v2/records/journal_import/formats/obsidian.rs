@@ -0,0 +1,133 @@
+use chrono::NaiveDate;
+
+use crate::entities_v2::error::PpdcError;
+
+use super::super::importer::{
+    ImportContext, ImportFile, ImportFormat, ImportSource, ImportedEntry, JournalImporter,
+    ParsedImport,
+};
+use super::super::model::DateConfidence;
+use super::markdown::{normalize_tags, parse_loose_date, split_front_matter, take_embedded_files};
+
+/// Obsidian vault with one daily note per day, named `YYYY-MM-DD.md`; other notes are skipped.
+pub struct ObsidianImporter;
+
+fn daily_note_date(file: &ImportFile) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(file.stem(), "%Y-%m-%d").ok()
+}
+
+fn daily_notes(source: &ImportSource) -> impl Iterator<Item = (&ImportFile, NaiveDate)> {
+    source
+        .files_with_extension(&["md"])
+        .filter(|file| !file.path.split('/').any(|part| part.starts_with('.')))
+        .filter_map(|file| daily_note_date(file).map(|date| (file, date)))
+}
+
+impl JournalImporter for ObsidianImporter {
+    fn format(&self) -> ImportFormat {
+        ImportFormat::Obsidian
+    }
+
+    fn detects(&self, source: &ImportSource) -> bool {
+        let has_vault_config = source
+            .files
+            .iter()
+            .any(|file| file.path.split('/').any(|part| part == ".obsidian"));
+        has_vault_config
+            || daily_notes(source).any(|(file, _)| split_front_matter(&file.text()).0.is_none())
+    }
+
+    fn parse(
+        &self,
+        source: &ImportSource,
+        _context: &ImportContext,
+    ) -> Result<ParsedImport, PpdcError> {
+        let mut notes = daily_notes(source).collect::<Vec<_>>();
+        notes.sort_by_key(|(_, date)| *date);
+
+        let entries = notes
+            .into_iter()
+            .map(|(file, date)| {
+                let text = file.text();
+                let (front_matter, body) = split_front_matter(&text);
+                let (content, attachments) = take_embedded_files(body, &file.path, source);
+                let front_matter = front_matter.unwrap_or_default();
+                let date = front_matter
+                    .scalar("date")
+                    .and_then(parse_loose_date)
+                    .filter(|date_time| date_time.date() == date)
+                    .or_else(|| date.and_hms_opt(12, 0, 0));
+                ImportedEntry {
+                    title: front_matter.scalar("title").map(|title| title.to_string()),
+                    date: date.map(|date| (date, DateConfidence::Explicit)),
+                    tags: normalize_tags(front_matter.list("tags")),
+                    attachments,
+                    ..ImportedEntry::new(file.stem().to_string(), content)
+                }
+            })
+            .collect();
+        Ok(ParsedImport {
+            entries,
+            journal_subtitle: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::dates::DateLocale;
+    use super::*;
+
+    fn file(path: &str, bytes: &[u8]) -> ImportFile {
+        ImportFile {
+            path: path.to_string(),
+            bytes: bytes.to_vec(),
+        }
+    }
+
+    #[test]
+    fn reads_daily_notes_in_date_order() {
+        let source = ImportSource {
+            files: vec![
+                file("vault/.obsidian/app.json", b"{}"),
+                file(
+                    "vault/Daily/2024-03-06.md",
+                    b"---\ndate: 2024-03-06 08:30\ntags: [Atlas]\n---\nSoir. ![[photo.png]]\n",
+                ),
+                file(
+                    "vault/Daily/2024-03-05.md",
+                    b"---\ndate: 2024-01-01\ntitle: Lundi\n---\nMatin.\n",
+                ),
+                file("vault/Projects/Atlas.md", b"Pas une note du jour.\n"),
+                file("vault/.trash/2024-03-04.md", b"Deleted.\n"),
+                file("vault/attachments/photo.png", &[1]),
+            ],
+        };
+        assert!(ObsidianImporter.detects(&source));
+
+        let entries = ObsidianImporter
+            .parse(
+                &source,
+                &ImportContext {
+                    locale: DateLocale::default(),
+                    today: NaiveDate::from_ymd_opt(2026, 3, 15).expect("valid date"),
+                },
+            )
+            .expect("valid vault")
+            .entries;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title.as_deref(), Some("Lundi"));
+        assert_eq!(entries[0].content, "Matin.");
+        // A front matter date of another day is ignored in favour of the note name.
+        assert_eq!(
+            entries[0].date.map(|(date, _)| date),
+            NaiveDate::from_ymd_opt(2024, 3, 5).and_then(|d| d.and_hms_opt(12, 0, 0))
+        );
+        assert_eq!(
+            entries[1].date.map(|(date, _)| date),
+            NaiveDate::from_ymd_opt(2024, 3, 6).and_then(|d| d.and_hms_opt(8, 30, 0))
+        );
+        assert_eq!(entries[1].tags, vec!["atlas"]);
+        assert_eq!(entries[1].attachments[0].file_name, "photo.png");
+    }
+}
@@ -0,0 +1,64 @@
+use crate::entities_v2::error::PpdcError;
+
+use super::super::importer::{
+    ImportContext, ImportFile, ImportFormat, ImportSource, JournalImporter, ParsedImport,
+};
+use super::super::model::ImportBlock;
+use super::super::parser::{blocks_to_entries, extract_blocks};
+
+const TEXT_EXTENSIONS: &[&str] = &["txt", "md", "markdown"];
+
+/// Free text split on date headers, one file or every text file of an archive.
+pub struct TextImporter;
+
+fn purpose_block_to_subtitle(block: &ImportBlock) -> String {
+    let mut lines = block.content.lines();
+    let first_line = lines.next().unwrap_or_default();
+    if first_line.trim() == block.header.trim() {
+        let remaining = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+        if !remaining.is_empty() {
+            return remaining;
+        }
+    }
+    block.content.trim().to_string()
+}
+
+fn text_files(source: &ImportSource) -> Vec<&ImportFile> {
+    if source.files.len() == 1 {
+        return source.files.iter().collect();
+    }
+    source.files_with_extension(TEXT_EXTENSIONS).collect()
+}
+
+impl JournalImporter for TextImporter {
+    fn format(&self) -> ImportFormat {
+        ImportFormat::Text
+    }
+
+    fn detects(&self, source: &ImportSource) -> bool {
+        !text_files(source).is_empty()
+    }
+
+    /// The undated block opening the first file describes the journal rather than a day.
+    fn parse(
+        &self,
+        source: &ImportSource,
+        context: &ImportContext,
+    ) -> Result<ParsedImport, PpdcError> {
+        let mut parsed = ParsedImport::default();
+        for (file_index, file) in text_files(source).into_iter().enumerate() {
+            let mut blocks = extract_blocks(&file.text(), context.locale)
+                .into_iter()
+                .filter(|block| !block.header.trim().is_empty())
+                .collect::<Vec<_>>();
+            if file_index == 0 && blocks.first().is_some_and(|block| block.date.is_none()) {
+                let subtitle = purpose_block_to_subtitle(&blocks.remove(0));
+                parsed.journal_subtitle = (!subtitle.is_empty()).then_some(subtitle);
+            }
+            parsed
+                .entries
+                .extend(blocks_to_entries(blocks, context.today));
+        }
+        Ok(parsed)
+    }
+}
@@ -0,0 +1,343 @@
+use std::io::{Cursor, Read};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::entities_v2::error::{ErrorType, PpdcError};
+
+use super::dates::DateLocale;
+use super::formats::{
+    day_one::DayOneImporter, front_matter::FrontMatterImporter, notion::NotionImporter,
+    obsidian::ObsidianImporter, text::TextImporter,
+};
+use super::model::DateConfidence;
+
+/// Uncompressed size a zip upload may expand to.
+const MAX_ARCHIVE_BYTES: u64 = 512 * 1024 * 1024;
+const MAX_ARCHIVE_FILES: usize = 20_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    /// Free text split on date headers.
+    Text,
+    DayOne,
+    Obsidian,
+    Notion,
+    /// Markdown files with YAML front matter.
+    FrontMatter,
+}
+
+impl ImportFormat {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            ImportFormat::Text => "text",
+            ImportFormat::DayOne => "day_one",
+            ImportFormat::Obsidian => "obsidian",
+            ImportFormat::Notion => "notion",
+            ImportFormat::FrontMatter => "front_matter",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "day_one" => ImportFormat::DayOne,
+            "obsidian" => ImportFormat::Obsidian,
+            "notion" => ImportFormat::Notion,
+            "front_matter" => ImportFormat::FrontMatter,
+            _ => ImportFormat::Text,
+        }
+    }
+
+    pub fn importer(self) -> Box<dyn JournalImporter> {
+        match self {
+            ImportFormat::Text => Box::new(TextImporter),
+            ImportFormat::DayOne => Box::new(DayOneImporter),
+            ImportFormat::Obsidian => Box::new(ObsidianImporter),
+            ImportFormat::Notion => Box::new(NotionImporter),
+            ImportFormat::FrontMatter => Box::new(FrontMatterImporter),
+        }
+    }
+
+    /// Format of an upload, from the most to the least specific; free text is the fallback.
+    pub fn detect(source: &ImportSource) -> Self {
+        [
+            ImportFormat::DayOne,
+            ImportFormat::Notion,
+            ImportFormat::Obsidian,
+            ImportFormat::FrontMatter,
+        ]
+        .into_iter()
+        .find(|format| format.importer().detects(source))
+        .unwrap_or(ImportFormat::Text)
+    }
+}
+
+/// One file of an upload, with its path inside the archive.
+#[derive(Debug, Clone)]
+pub struct ImportFile {
+    pub path: String,
+    pub bytes: Vec<u8>,
+}
+
+impl ImportFile {
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.bytes)
+            .trim_start_matches('\u{feff}')
+            .to_string()
+    }
+
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    pub fn extension(&self) -> Option<String> {
+        let file_name = self.file_name();
+        let (stem, extension) = file_name.rsplit_once('.')?;
+        (!stem.is_empty()).then(|| extension.to_lowercase())
+    }
+
+    pub fn stem(&self) -> &str {
+        let file_name = self.file_name();
+        match file_name.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() => stem,
+            _ => file_name,
+        }
+    }
+}
+
+/// The uploaded file, or the files of an uploaded zip archive.
+#[derive(Debug, Clone)]
+pub struct ImportSource {
+    pub files: Vec<ImportFile>,
+}
+
+impl ImportSource {
+    /// Reads the upload on a blocking thread, since a zip archive is unpacked in memory.
+    pub async fn read_upload(file_name: Option<String>, bytes: Vec<u8>) -> Result<Self, PpdcError> {
+        tokio::task::spawn_blocking(move || Self::from_upload(file_name.as_deref(), bytes))
+            .await
+            .map_err(|err| {
+                PpdcError::new(
+                    500,
+                    ErrorType::InternalError,
+                    format!("Failed to read the upload: {}", err),
+                )
+            })?
+    }
+
+    pub fn from_upload(file_name: Option<&str>, bytes: Vec<u8>) -> Result<Self, PpdcError> {
+        let is_zip = bytes.starts_with(b"PK\x03\x04")
+            || file_name.is_some_and(|name| name.to_lowercase().ends_with(".zip"));
+        if is_zip {
+            return Self::from_zip(bytes);
+        }
+        Ok(ImportSource {
+            files: vec![ImportFile {
+                path: file_name.unwrap_or("import.txt").to_string(),
+                bytes,
+            }],
+        })
+    }
+
+    fn from_zip(bytes: Vec<u8>) -> Result<Self, PpdcError> {
+        Self::from_zip_with_budget(bytes, MAX_ARCHIVE_BYTES)
+    }
+
+    fn from_zip_with_budget(bytes: Vec<u8>, max_bytes: u64) -> Result<Self, PpdcError> {
+        let invalid = |message: String| PpdcError::new(400, ErrorType::ApiError, message);
+        let too_large = || invalid("Zip archive is too large once extracted".to_string());
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+            .map_err(|err| invalid(format!("Invalid zip archive: {}", err)))?;
+        if archive.len() > MAX_ARCHIVE_FILES {
+            return Err(invalid("Zip archive has too many files".to_string()));
+        }
+
+        let mut files = Vec::new();
+        let mut total_bytes = 0u64;
+        for index in 0..archive.len() {
+            let entry = archive
+                .by_index(index)
+                .map_err(|err| invalid(format!("Invalid zip archive: {}", err)))?;
+            let Some(path) = entry
+                .enclosed_name()
+                .map(|path| path.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+            let path = path.replace('\\', "/");
+            if entry.is_dir() || is_archive_noise(&path) {
+                continue;
+            }
+            // Declared sizes can lie: the budget is enforced on the bytes actually read.
+            let remaining = max_bytes - total_bytes;
+            if entry.size() > remaining {
+                return Err(too_large());
+            }
+            let mut file_bytes = Vec::with_capacity(entry.size() as usize);
+            entry
+                .take(remaining + 1)
+                .read_to_end(&mut file_bytes)
+                .map_err(|err| invalid(format!("Failed to read zip entry {}: {}", path, err)))?;
+            total_bytes += file_bytes.len() as u64;
+            if total_bytes > max_bytes {
+                return Err(too_large());
+            }
+            files.push(ImportFile {
+                path,
+                bytes: file_bytes,
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(ImportSource { files })
+    }
+
+    pub fn files_with_extension<'a>(
+        &'a self,
+        extensions: &'a [&str],
+    ) -> impl Iterator<Item = &'a ImportFile> + 'a {
+        self.files.iter().filter(|file| {
+            file.extension()
+                .is_some_and(|extension| extensions.contains(&extension.as_str()))
+        })
+    }
+
+    pub fn find_path(&self, path: &str) -> Option<&ImportFile> {
+        self.files.iter().find(|file| file.path == path)
+    }
+
+    /// First file with this name in any folder, as Obsidian resolves embeds.
+    pub fn find_file_name(&self, file_name: &str) -> Option<&ImportFile> {
+        self.files
+            .iter()
+            .find(|file| file.file_name().eq_ignore_ascii_case(file_name))
+    }
+}
+
+fn is_archive_noise(path: &str) -> bool {
+    path.starts_with("__MACOSX/") || path.rsplit('/').next() == Some(".DS_Store")
+}
+
+/// A file to attach to the imported trace.
+#[derive(Debug, Clone)]
+pub struct ImportedAttachment {
+    pub file_name: String,
+    pub bytes: Vec<u8>,
+}
+
+impl ImportedAttachment {
+    pub fn from_file(file: &ImportFile) -> Self {
+        ImportedAttachment {
+            file_name: file.file_name().to_string(),
+            bytes: file.bytes.clone(),
+        }
+    }
+
+    /// Whether the file can be the trace image; other files become document attachments.
+    pub fn is_trace_image(&self) -> bool {
+        mime_guess::from_path(&self.file_name)
+            .first()
+            .is_some_and(|mime| {
+                matches!(
+                    mime.essence_str(),
+                    "image/jpeg" | "image/png" | "image/webp" | "image/gif"
+                )
+            })
+    }
+}
+
+/// One entry read from an upload, before it becomes a trace.
+#[derive(Debug, Clone)]
+pub struct ImportedEntry {
+    /// Line or name identifying the entry in previews and failures.
+    pub header: String,
+    pub title: Option<String>,
+    pub content: String,
+    pub date: Option<(NaiveDateTime, DateConfidence)>,
+    pub tags: Vec<String>,
+    pub attachments: Vec<ImportedAttachment>,
+}
+
+impl ImportedEntry {
+    pub fn new(header: String, content: String) -> Self {
+        ImportedEntry {
+            header,
+            title: None,
+            content,
+            date: None,
+            tags: Vec::new(),
+            attachments: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParsedImport {
+    pub entries: Vec<ImportedEntry>,
+    /// Description of the journal found in the upload, used when the journal has none.
+    pub journal_subtitle: Option<String>,
+}
+
+/// What an importer needs to read dates written for the importing user.
+#[derive(Debug, Clone, Copy)]
+pub struct ImportContext {
+    pub locale: DateLocale,
+    /// Day of the import in the user's timezone, which relative headers count from.
+    pub today: NaiveDate,
+}
+
+/// Reads the entries of one export format.
+pub trait JournalImporter: Send + Sync {
+    fn format(&self) -> ImportFormat;
+
+    /// Whether the upload looks like an export of this format.
+    fn detects(&self, source: &ImportSource) -> bool;
+
+    fn parse(
+        &self,
+        source: &ImportSource,
+        context: &ImportContext,
+    ) -> Result<ParsedImport, PpdcError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::{write::FileOptions, ZipWriter};
+
+    use super::*;
+
+    fn zip_of(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, bytes) in files {
+            zip.start_file(*path, FileOptions::default())
+                .expect("start file");
+            zip.write_all(bytes).expect("write file");
+        }
+        zip.finish().expect("finish zip").into_inner()
+    }
+
+    #[test]
+    fn zip_entries_are_read_within_the_extracted_budget() {
+        let archive = zip_of(&[
+            ("vault/2024-03-05.md", b"Matin."),
+            ("__MACOSX/vault/._2024-03-05.md", b"noise"),
+            ("vault/2024-03-06.md", b"Soir."),
+        ]);
+
+        let source =
+            ImportSource::from_zip_with_budget(archive.clone(), 11).expect("within budget");
+        assert_eq!(
+            source
+                .files
+                .iter()
+                .map(|file| file.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["vault/2024-03-05.md", "vault/2024-03-06.md"]
+        );
+
+        let err = ImportSource::from_zip_with_budget(archive, 10).expect_err("over budget");
+        assert_eq!(err.status_code, 400);
+    }
+}
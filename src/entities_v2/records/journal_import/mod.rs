@@ -1,6 +1,15 @@
+pub mod dates;
+pub mod formats;
+pub mod importer;
 pub mod model;
 pub mod parser;
+pub mod persist;
 pub mod routes;
 pub mod service;
 
-pub use routes::post_import_text_route;
+pub use importer::{ImportFormat, ImportSource, ImportedEntry, JournalImporter};
+pub use model::{ImportBatch, ImportBatchDetail, ImportBatchStatus, ImportOptions};
+pub use routes::{
+    get_journal_import_route, get_journal_imports_route, post_import_text_route,
+    post_journal_import_rollback_route,
+};
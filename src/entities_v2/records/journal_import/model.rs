@@ -1,11 +1,66 @@
-use serde::Serialize;
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::entities_v2::embedding::model::content_hash;
+
+use super::dates::{DateLocale, HeaderDate};
+use super::importer::ImportFormat;
+
 #[derive(Debug, Clone)]
 pub struct ImportBlock {
     pub header: String,
     pub content: String,
-    pub date: Option<String>,
+    pub date: Option<HeaderDate>,
+}
+
+/// How a block's interaction date was resolved, from most to least reliable.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DateConfidence {
+    /// Fixed by the user for this import.
+    UserProvided,
+    /// Read from the block header.
+    Explicit,
+    /// Read from a header without a year; the year is taken from the surrounding blocks.
+    YearInferred,
+    /// Read from a header such as "Yesterday", counted from the import date.
+    Relative,
+    /// No date in the header; one day after the previous block.
+    FollowsPrevious,
+    /// No date before this block; the import time.
+    ImportTime,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportBlockOutcome {
+    /// Would be created; only returned by dry runs.
+    New,
+    Created,
+    /// Same content as a trace of the journal or an earlier block; skipped.
+    Duplicate,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportBlockPreview {
+    pub block_index: usize,
+    pub header: String,
+    pub title: Option<String>,
+    pub content: String,
+    pub interaction_date: NaiveDateTime,
+    pub date_confidence: DateConfidence,
+    pub tags: Vec<String>,
+    /// File names of the attachments the trace gets.
+    pub attachments: Vec<String>,
+    pub outcome: ImportBlockOutcome,
+    pub trace_id: Option<Uuid>,
+    pub duplicate_of_trace_id: Option<Uuid>,
+    pub duplicate_of_block_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -17,9 +72,156 @@ pub struct ImportTraceFailure {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ImportJournalResult {
+    pub dry_run: bool,
+    pub format: ImportFormat,
+    /// Batch recording the import; `None` for dry runs.
+    pub batch_id: Option<Uuid>,
     pub created_count: usize,
+    pub skipped_count: usize,
     pub failed_count: usize,
     pub total_blocks: usize,
     pub created_trace_ids: Vec<Uuid>,
     pub failures: Vec<ImportTraceFailure>,
+    pub blocks: Vec<ImportBlockPreview>,
+}
+
+/// Options sent alongside the imported file.
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// Parses and checks the file without creating anything.
+    pub dry_run: bool,
+    /// Interaction dates fixed by the user, by block index of the preview.
+    pub date_overrides: HashMap<usize, NaiveDateTime>,
+    pub file_name: Option<String>,
+    /// Format of the upload; detected from its files when not given.
+    pub format: Option<ImportFormat>,
+    /// Locale date headers are read with; the user's locale when not given.
+    pub locale: Option<DateLocale>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImportBatchStatus {
+    Completed,
+    RolledBack,
+}
+
+impl ImportBatchStatus {
+    pub fn to_db(self) -> &'static str {
+        match self {
+            ImportBatchStatus::Completed => "COMPLETED",
+            ImportBatchStatus::RolledBack => "ROLLED_BACK",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "ROLLED_BACK" => ImportBatchStatus::RolledBack,
+            _ => ImportBatchStatus::Completed,
+        }
+    }
+}
+
+/// A committed import: the traces it created can be rolled back together.
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportBatch {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub journal_id: Uuid,
+    pub status: ImportBatchStatus,
+    pub format: ImportFormat,
+    pub file_name: Option<String>,
+    pub total_blocks: i32,
+    pub created_count: i32,
+    pub skipped_count: i32,
+    pub failed_count: i32,
+    pub created_at: NaiveDateTime,
+    pub rolled_back_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportBatchDetail {
+    #[serde(flatten)]
+    pub batch: ImportBatch,
+    /// Traces of the batch that still exist.
+    pub trace_ids: Vec<Uuid>,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::journal_import_batches)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct ImportBatchRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub journal_id: Uuid,
+    pub status: String,
+    pub format: String,
+    pub file_name: Option<String>,
+    pub total_blocks: i32,
+    pub created_count: i32,
+    pub skipped_count: i32,
+    pub failed_count: i32,
+    pub created_at: NaiveDateTime,
+    pub rolled_back_at: Option<NaiveDateTime>,
+}
+
+impl From<ImportBatchRow> for ImportBatch {
+    fn from(row: ImportBatchRow) -> Self {
+        ImportBatch {
+            id: row.id,
+            user_id: row.user_id,
+            journal_id: row.journal_id,
+            status: ImportBatchStatus::from_db(&row.status),
+            format: ImportFormat::from_db(&row.format),
+            file_name: row.file_name,
+            total_blocks: row.total_blocks,
+            created_count: row.created_count,
+            skipped_count: row.skipped_count,
+            failed_count: row.failed_count,
+            created_at: row.created_at,
+            rolled_back_at: row.rolled_back_at,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::journal_import_batches)]
+pub(super) struct NewImportBatchRow {
+    pub user_id: Uuid,
+    pub journal_id: Uuid,
+    pub status: String,
+    pub format: String,
+    pub file_name: Option<String>,
+    pub total_blocks: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::journal_import_batch_traces)]
+pub(super) struct NewImportBatchTraceRow {
+    pub batch_id: Uuid,
+    pub trace_id: Uuid,
+    pub block_index: i32,
+    pub content_hash: String,
+}
+
+/// Key two contents are duplicates under: whitespace runs collapse and ends are trimmed.
+pub fn import_content_hash(content: &str) -> String {
+    content_hash(&content.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_hash_ignores_whitespace_layout() {
+        assert_eq!(
+            import_content_hash("  Une journée\n\nà la mer. "),
+            import_content_hash("Une journée à la mer.")
+        );
+        assert_ne!(
+            import_content_hash("Une journée à la mer."),
+            import_content_hash("Une journée à la montagne.")
+        );
+    }
 }
@@ -1,52 +1,15 @@
+use std::collections::HashMap;
+
 use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
-use regex::Regex;
 use uuid::Uuid;
 
 use crate::entities_v2::trace::NewTrace;
 
-use super::model::ImportBlock;
-
-fn year_from_month(month: &str) -> i32 {
-    match month {
-        "janvier" | "fevrier" | "février" => 2026,
-        _ => 2025,
-    }
-}
-
-fn month_number(month: &str) -> Option<u32> {
-    match month {
-        "janvier" => Some(1),
-        "fevrier" | "février" => Some(2),
-        "mars" => Some(3),
-        "avril" => Some(4),
-        "mai" => Some(5),
-        "juin" => Some(6),
-        "juillet" => Some(7),
-        "aout" | "août" => Some(8),
-        "septembre" => Some(9),
-        "octobre" => Some(10),
-        "novembre" => Some(11),
-        "decembre" | "décembre" => Some(12),
-        _ => None,
-    }
-}
-
-fn french_date_regex() -> Regex {
-    Regex::new(
-        r"^(?i)(lundi|mardi|mercredi|jeudi|vendredi|samedi|dimanche)\s+(?P<day>\d{1,2})(?:er)?\s+(?P<month>janvier|février|fevrier|mars|avril|mai|juin|juillet|août|aout|septembre|octobre|novembre|décembre|decembre)(?:\s+(?P<year>\d{4}))?(?:\s+(?P<hour>\d{1,2})(?:h|:)(?P<minute>\d{2}))?$",
-    )
-    .expect("valid regex")
-}
-
-fn strip_markdown_heading_prefix(line: &str) -> &str {
-    let trimmed = line.trim_start();
-    let without_hashes = trimmed.trim_start_matches('#').trim_start();
-    if without_hashes.is_empty() {
-        trimmed
-    } else {
-        without_hashes
-    }
-}
+use super::dates::{
+    parse_date_header, resolve_header_dates, strip_markdown_heading_prefix, DateLocale,
+};
+use super::importer::ImportedEntry;
+use super::model::{DateConfidence, ImportBlock};
 
 fn is_export_generated_date_line(line: &str) -> bool {
     let normalized_line = strip_markdown_heading_prefix(line).trim();
@@ -55,40 +18,7 @@ fn is_export_generated_date_line(line: &str) -> bool {
         .starts_with("export_generated_date:")
 }
 
-fn get_block_date(line: &str) -> Option<String> {
-    let normalized_line = strip_markdown_heading_prefix(line);
-    let date_time_regex = Regex::new(r"(\d{4}-\d{2}-\d{2}\s\d{2}:\d{2})").expect("valid regex");
-    let date_regex = Regex::new(r"(\d{4}-\d{2}-\d{2})").expect("valid regex");
-
-    if let Some(date_time) = date_time_regex.captures(normalized_line) {
-        return Some(date_time[1].to_string());
-    }
-    if let Some(date) = date_regex.captures(normalized_line) {
-        return Some(date[1].to_string());
-    }
-
-    let captures = french_date_regex().captures(normalized_line)?;
-    let day = captures.name("day")?.as_str().parse::<u32>().ok()?;
-    let month_name = captures.name("month")?.as_str().to_lowercase();
-    let month = month_number(&month_name)?;
-    let year = captures
-        .name("year")
-        .and_then(|y| y.as_str().parse::<i32>().ok())
-        .unwrap_or_else(|| year_from_month(&month_name));
-
-    if let (Some(hour), Some(minute)) = (captures.name("hour"), captures.name("minute")) {
-        let hour = hour.as_str().parse::<u32>().ok()?;
-        let minute = minute.as_str().parse::<u32>().ok()?;
-        return Some(format!(
-            "{:04}-{:02}-{:02} {:02}:{:02}",
-            year, month, day, hour, minute
-        ));
-    }
-
-    Some(format!("{:04}-{:02}-{:02}", year, month, day))
-}
-
-fn extract_date(value: Option<&str>) -> Option<NaiveDateTime> {
+pub(super) fn extract_date(value: Option<&str>) -> Option<NaiveDateTime> {
     let value = value?;
     if let Ok(date_time) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M") {
         return Some(date_time);
@@ -99,12 +29,12 @@ fn extract_date(value: Option<&str>) -> Option<NaiveDateTime> {
     None
 }
 
-fn is_start_of_block(line: &str) -> bool {
+fn is_start_of_block(line: &str, locale: DateLocale) -> bool {
     let trimmed = line.trim_start();
-    trimmed.starts_with('#') || get_block_date(trimmed).is_some()
+    trimmed.starts_with('#') || parse_date_header(trimmed, locale).is_some()
 }
 
-pub fn extract_blocks(text: &str) -> Vec<ImportBlock> {
+pub fn extract_blocks(text: &str, locale: DateLocale) -> Vec<ImportBlock> {
     let mut blocks = Vec::new();
     let mut current_block = ImportBlock {
         header: String::new(),
@@ -118,13 +48,13 @@ pub fn extract_blocks(text: &str) -> Vec<ImportBlock> {
             last_empty = true;
             continue;
         }
-        if last_empty && is_start_of_block(line) {
+        if last_empty && is_start_of_block(line, locale) {
             current_block.content = current_block.content.trim_matches('\n').to_string();
             blocks.push(current_block);
             current_block = ImportBlock {
                 header: line.to_string(),
                 content: String::new(),
-                date: get_block_date(line),
+                date: parse_date_header(line, locale),
             };
         }
         current_block.content.push_str(line);
@@ -137,47 +67,85 @@ pub fn extract_blocks(text: &str) -> Vec<ImportBlock> {
     blocks
 }
 
-pub fn blocks_to_new_traces(
-    blocks: Vec<ImportBlock>,
+/// Turns dated blocks into import entries, dropping export header lines.
+///
+/// Dates are resolved over the whole sequence so that headers without a year take it from
+/// their neighbours; relative headers count from `today`.
+pub fn blocks_to_entries(blocks: Vec<ImportBlock>, today: NaiveDate) -> Vec<ImportedEntry> {
+    let headers = blocks.iter().map(|block| block.date).collect::<Vec<_>>();
+    blocks
+        .into_iter()
+        .zip(resolve_header_dates(&headers, today))
+        .map(|(block, date)| {
+            let content = if is_export_generated_date_line(&block.header) {
+                block.content.lines().skip(1).collect::<Vec<_>>().join("\n")
+            } else {
+                block.content.clone()
+            };
+            ImportedEntry {
+                date,
+                ..ImportedEntry::new(block.header, content)
+            }
+        })
+        .collect()
+}
+
+/// Trace content of an entry: its text followed by its tags as hashtags.
+fn entry_content(entry: &ImportedEntry) -> String {
+    if entry.tags.is_empty() {
+        return entry.content.clone();
+    }
+    let hashtags = entry
+        .tags
+        .iter()
+        .map(|tag| format!("#{}", tag.split_whitespace().collect::<Vec<_>>().join("-")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if entry.content.trim().is_empty() {
+        hashtags
+    } else {
+        format!("{}\n\n{}", entry.content.trim_end(), hashtags)
+    }
+}
+
+/// Turns entries into traces with their resolved date and how it was resolved.
+///
+/// `date_overrides` are dates the user fixed by entry index; entries without a date follow the
+/// previous entry by one day, or fall back to the import time.
+pub fn entries_to_new_traces(
+    entries: Vec<ImportedEntry>,
     user_id: Uuid,
     journal_id: Uuid,
-) -> Vec<(usize, ImportBlock, NewTrace)> {
+    date_overrides: &HashMap<usize, NaiveDateTime>,
+) -> Vec<(usize, ImportedEntry, NewTrace, DateConfidence)> {
     let mut traces = Vec::new();
     let mut previous_date: Option<NaiveDateTime> = None;
 
-    for (block_index, block) in blocks.into_iter().enumerate() {
-        if block.header.trim().is_empty() {
-            continue;
-        }
-
-        let trace_content = if is_export_generated_date_line(&block.header) {
-            block.content.lines().skip(1).collect::<Vec<_>>().join("\n")
-        } else {
-            block.content.clone()
-        };
-
-        let interaction_date = if let Some(date) = extract_date(block.date.as_deref()) {
-            previous_date = Some(date);
-            date
-        } else if let Some(previous) = previous_date {
-            let next = previous + Duration::days(1);
-            previous_date = Some(next);
-            next
-        } else {
-            let fallback = Utc::now().naive_utc();
-            previous_date = Some(fallback);
-            fallback
-        };
+    for (entry_index, entry) in entries.into_iter().enumerate() {
+        let (interaction_date, date_confidence) =
+            if let Some(date) = date_overrides.get(&entry_index) {
+                (*date, DateConfidence::UserProvided)
+            } else if let Some(date) = entry.date {
+                date
+            } else if let Some(previous) = previous_date {
+                (
+                    previous + Duration::days(1),
+                    DateConfidence::FollowsPrevious,
+                )
+            } else {
+                (Utc::now().naive_utc(), DateConfidence::ImportTime)
+            };
+        previous_date = Some(interaction_date);
 
         let trace = NewTrace::new(
+            entry.title.clone().unwrap_or_default(),
             "".to_string(),
-            "".to_string(),
-            trace_content,
+            entry_content(&entry),
             interaction_date,
             user_id,
             journal_id,
         );
-        traces.push((block_index, block, trace));
+        traces.push((entry_index, entry, trace, date_confidence));
     }
 
     traces
@@ -187,6 +155,22 @@ pub fn blocks_to_new_traces(
 mod tests {
     use super::*;
 
+    fn get_block_date(line: &str) -> Option<String> {
+        parse_date_header(line, DateLocale::default()).map(|date| date.to_string())
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 15).expect("valid fixed date")
+    }
+
+    fn block(header: &str, content: &str) -> ImportBlock {
+        ImportBlock {
+            header: header.to_string(),
+            content: content.to_string(),
+            date: parse_date_header(header, DateLocale::default()),
+        }
+    }
+
     #[test]
     fn parses_french_date_with_explicit_year() {
         let date = get_block_date("Mardi 2 décembre 2026");
@@ -194,9 +178,9 @@ mod tests {
     }
 
     #[test]
-    fn parses_french_date_without_year_with_fallback() {
+    fn parses_french_date_without_year_as_yearless() {
         let date = get_block_date("Mardi 3 décembre");
-        assert_eq!(date, Some("2025-12-03".to_string()));
+        assert_eq!(date, Some("--12-03".to_string()));
     }
 
     #[test]
@@ -218,14 +202,20 @@ mod tests {
     #[test]
     fn extracts_blocks_with_blank_line_separators() {
         let text = "### 2026-02-04\nA\n\n### 2026-02-05\nB\n";
-        let blocks = extract_blocks(text);
+        let blocks = extract_blocks(text, DateLocale::default());
         let real_blocks = blocks
             .into_iter()
             .filter(|block| !block.header.is_empty())
             .collect::<Vec<_>>();
         assert_eq!(real_blocks.len(), 2);
-        assert_eq!(real_blocks[0].date, Some("2026-02-04".to_string()));
-        assert_eq!(real_blocks[1].date, Some("2026-02-05".to_string()));
+        assert_eq!(
+            real_blocks[0].date.map(|date| date.to_string()),
+            Some("2026-02-04".to_string())
+        );
+        assert_eq!(
+            real_blocks[1].date.map(|date| date.to_string()),
+            Some("2026-02-05".to_string())
+        );
     }
 
     #[test]
@@ -244,19 +234,13 @@ mod tests {
     fn missing_date_uses_previous_plus_one_day() {
         let user_id = Uuid::new_v4();
         let journal_id = Uuid::new_v4();
-        let blocks = vec![
-            ImportBlock {
-                header: "### 2026-02-04".to_string(),
-                content: "A".to_string(),
-                date: Some("2026-02-04".to_string()),
-            },
-            ImportBlock {
-                header: "### no date".to_string(),
-                content: "B".to_string(),
-                date: None,
-            },
-        ];
-        let traces = blocks_to_new_traces(blocks, user_id, journal_id);
+        let blocks = vec![block("### 2026-02-04", "A"), block("### no date", "B")];
+        let traces = entries_to_new_traces(
+            blocks_to_entries(blocks, today()),
+            user_id,
+            journal_id,
+            &HashMap::new(),
+        );
         assert_eq!(traces.len(), 2);
         assert_eq!(
             traces[0].2.interaction_date,
@@ -272,19 +256,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resolved_dates_carry_their_confidence_and_overrides() {
+        let blocks = vec![
+            block("Mardi 3 décembre", "A"),
+            block("### no date", "B"),
+            block("### still no date", "C"),
+        ];
+        let fixed = NaiveDate::from_ymd_opt(2026, 1, 10)
+            .and_then(|d| d.and_hms_opt(9, 0, 0))
+            .expect("valid fixed date");
+        let traces = entries_to_new_traces(
+            blocks_to_entries(blocks, today()),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            &HashMap::from([(1, fixed)]),
+        );
+
+        assert_eq!(traces[0].3, DateConfidence::YearInferred);
+        assert_eq!(traces[1].3, DateConfidence::UserProvided);
+        assert_eq!(traces[1].2.interaction_date, fixed);
+        assert_eq!(traces[2].3, DateConfidence::FollowsPrevious);
+        assert_eq!(traces[2].2.interaction_date, fixed + Duration::days(1));
+    }
+
     #[test]
     fn export_generated_date_header_is_not_kept_in_trace_content() {
         let user_id = Uuid::new_v4();
         let journal_id = Uuid::new_v4();
-        let blocks = vec![ImportBlock {
-            header: "export_generated_date: 2026-03-09 11:00:00".to_string(),
-            content: "export_generated_date: 2026-03-09 11:00:00\nDid focused work on exports."
-                .to_string(),
-            date: Some("2026-03-09 11:00".to_string()),
-        }];
+        let blocks = vec![block(
+            "export_generated_date: 2026-03-09 11:00:00",
+            "export_generated_date: 2026-03-09 11:00:00\nDid focused work on exports.",
+        )];
 
-        let traces = blocks_to_new_traces(blocks, user_id, journal_id);
+        let traces = entries_to_new_traces(
+            blocks_to_entries(blocks, today()),
+            user_id,
+            journal_id,
+            &HashMap::new(),
+        );
         assert_eq!(traces.len(), 1);
         assert_eq!(traces[0].2.content, "Did focused work on exports.");
     }
+
+    #[test]
+    fn splits_blocks_on_headers_of_the_user_locale() {
+        let text = "Yesterday\nA\n\n03/04/2026\nB\n\nTuesday, March 10\nC\n";
+        let us = DateLocale::from_tag("en-US").expect("supported locale");
+        let blocks = extract_blocks(text, us)
+            .into_iter()
+            .filter(|block| !block.header.is_empty())
+            .collect::<Vec<_>>();
+        let entries = blocks_to_entries(blocks, today());
+
+        let dates = entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .date
+                    .map(|(date, confidence)| (date.date(), confidence))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dates,
+            vec![
+                Some((today() - Duration::days(1), DateConfidence::Relative)),
+                NaiveDate::from_ymd_opt(2026, 3, 4).map(|date| (date, DateConfidence::Explicit)),
+                NaiveDate::from_ymd_opt(2026, 3, 10)
+                    .map(|date| (date, DateConfidence::YearInferred)),
+            ]
+        );
+    }
 }
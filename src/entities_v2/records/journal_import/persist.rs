@@ -0,0 +1,178 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::schema::{
+    documents, journal_import_batch_traces, journal_import_batches, trace_attachments, traces,
+};
+
+use super::importer::ImportFormat;
+use super::model::{
+    ImportBatch, ImportBatchDetail, ImportBatchRow, ImportBatchStatus, NewImportBatchRow,
+    NewImportBatchTraceRow,
+};
+
+impl ImportBatch {
+    pub(super) fn create(
+        user_id: Uuid,
+        journal_id: Uuid,
+        format: ImportFormat,
+        file_name: Option<String>,
+        total_blocks: usize,
+        pool: &DbPool,
+    ) -> Result<ImportBatch, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = diesel::insert_into(journal_import_batches::table)
+            .values(&NewImportBatchRow {
+                user_id,
+                journal_id,
+                status: ImportBatchStatus::Completed.to_db().to_string(),
+                format: format.to_db().to_string(),
+                file_name,
+                total_blocks: total_blocks as i32,
+            })
+            .returning(ImportBatchRow::as_returning())
+            .get_result(&mut conn)?;
+        Ok(row.into())
+    }
+
+    pub(super) fn add_trace(
+        &self,
+        trace_id: Uuid,
+        block_index: usize,
+        content_hash: String,
+        pool: &DbPool,
+    ) -> Result<(), PpdcError> {
+        let mut conn = pool.get()?;
+        diesel::insert_into(journal_import_batch_traces::table)
+            .values(&NewImportBatchTraceRow {
+                batch_id: self.id,
+                trace_id,
+                block_index: block_index as i32,
+                content_hash,
+            })
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    pub(super) fn record_counts(
+        mut self,
+        created_count: usize,
+        skipped_count: usize,
+        failed_count: usize,
+        pool: &DbPool,
+    ) -> Result<ImportBatch, PpdcError> {
+        let mut conn = pool.get()?;
+        diesel::update(journal_import_batches::table.find(self.id))
+            .set((
+                journal_import_batches::created_count.eq(created_count as i32),
+                journal_import_batches::skipped_count.eq(skipped_count as i32),
+                journal_import_batches::failed_count.eq(failed_count as i32),
+            ))
+            .execute(&mut conn)?;
+        self.created_count = created_count as i32;
+        self.skipped_count = skipped_count as i32;
+        self.failed_count = failed_count as i32;
+        Ok(self)
+    }
+
+    pub fn find(id: Uuid, pool: &DbPool) -> Result<ImportBatch, PpdcError> {
+        let mut conn = pool.get()?;
+        let row = journal_import_batches::table
+            .find(id)
+            .select(ImportBatchRow::as_select())
+            .first(&mut conn)
+            .optional()?
+            .ok_or_else(|| {
+                PpdcError::new(404, ErrorType::ApiError, "Import not found".to_string())
+            })?;
+        Ok(row.into())
+    }
+
+    pub fn list_for_journal(
+        journal_id: Uuid,
+        pool: &DbPool,
+    ) -> Result<Vec<ImportBatch>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = journal_import_batches::table
+            .filter(journal_import_batches::journal_id.eq(journal_id))
+            .order(journal_import_batches::created_at.desc())
+            .select(ImportBatchRow::as_select())
+            .load(&mut conn)?;
+        Ok(rows.into_iter().map(ImportBatch::from).collect())
+    }
+
+    pub fn with_trace_ids(self, pool: &DbPool) -> Result<ImportBatchDetail, PpdcError> {
+        let mut conn = pool.get()?;
+        let trace_ids = journal_import_batch_traces::table
+            .filter(journal_import_batch_traces::batch_id.eq(self.id))
+            .order(journal_import_batch_traces::block_index.asc())
+            .select(journal_import_batch_traces::trace_id)
+            .load::<Uuid>(&mut conn)?;
+        Ok(ImportBatchDetail {
+            batch: self,
+            trace_ids,
+        })
+    }
+
+    /// Deletes the traces the import created with their attached documents, marks it rolled
+    /// back and returns the assets left to delete from storage.
+    ///
+    /// Derived analysis data goes with the traces through the usual cascades.
+    pub(super) fn rollback(self, pool: &DbPool) -> Result<(ImportBatch, Vec<Uuid>), PpdcError> {
+        if self.status == ImportBatchStatus::RolledBack {
+            return Err(PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                "Import is already rolled back".to_string(),
+            ));
+        }
+        let mut conn = pool.get()?;
+        let rolled_back_at = Utc::now().naive_utc();
+        let asset_ids = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let trace_ids = journal_import_batch_traces::table
+                .filter(journal_import_batch_traces::batch_id.eq(self.id))
+                .inner_join(traces::table.on(traces::id.eq(journal_import_batch_traces::trace_id)))
+                .filter(traces::journal_id.eq(self.journal_id))
+                .select(traces::id)
+                .load::<Uuid>(conn)?;
+            let document_ids = trace_attachments::table
+                .filter(trace_attachments::trace_id.eq_any(&trace_ids))
+                .select(trace_attachments::document_id)
+                .load::<Uuid>(conn)?;
+            let mut asset_ids = traces::table
+                .filter(traces::id.eq_any(&trace_ids))
+                .select(traces::content_image_asset_id)
+                .load::<Option<Uuid>>(conn)?;
+            asset_ids.extend(
+                documents::table
+                    .filter(documents::id.eq_any(&document_ids))
+                    .select(documents::asset_id)
+                    .load::<Option<Uuid>>(conn)?,
+            );
+            diesel::delete(traces::table.filter(traces::id.eq_any(&trace_ids))).execute(conn)?;
+            diesel::delete(documents::table.filter(documents::id.eq_any(&document_ids)))
+                .execute(conn)?;
+            diesel::update(journal_import_batches::table.find(self.id))
+                .set((
+                    journal_import_batches::status.eq(ImportBatchStatus::RolledBack.to_db()),
+                    journal_import_batches::rolled_back_at.eq(Some(rolled_back_at)),
+                ))
+                .execute(conn)?;
+            let mut asset_ids = asset_ids.into_iter().flatten().collect::<Vec<_>>();
+            asset_ids.sort();
+            asset_ids.dedup();
+            Ok(asset_ids)
+        })?;
+        Ok((
+            ImportBatch {
+                status: ImportBatchStatus::RolledBack,
+                rolled_back_at: Some(rolled_back_at),
+                ..self
+            },
+            asset_ids,
+        ))
+    }
+}
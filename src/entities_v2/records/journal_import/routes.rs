@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use axum::{
     extract::{Extension, Multipart, Path},
     Json,
@@ -7,32 +9,68 @@ use uuid::Uuid;
 use crate::db::DbPool;
 use crate::entities_v2::{
     error::{ErrorType, PpdcError},
+    journal::Journal,
     session::Session,
 };
 
-use super::{model::ImportJournalResult, service::import_journal_text};
+use super::{
+    dates::DateLocale,
+    importer::{ImportFormat, ImportSource},
+    model::{ImportBatch, ImportBatchDetail, ImportJournalResult, ImportOptions},
+    parser::extract_date,
+    service::{import_journal, rollback_import},
+};
 
-pub async fn post_import_text_route(
-    Extension(pool): Extension<DbPool>,
-    Extension(session): Extension<Session>,
-    Path(journal_id): Path<Uuid>,
-    mut multipart: Multipart,
-) -> Result<Json<ImportJournalResult>, PpdcError> {
-    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+/// Files and options of a multipart import upload.
+pub struct ImportUpload {
+    pub source: ImportSource,
+    pub options: ImportOptions,
+}
 
-    let mut preferred_file_bytes: Option<Vec<u8>> = None;
-    let mut fallback_file_bytes: Option<Vec<u8>> = None;
+fn multipart_error(err: impl std::fmt::Display) -> PpdcError {
+    PpdcError::new(
+        400,
+        ErrorType::ApiError,
+        format!("Multipart error: {}", err),
+    )
+}
 
-    while let Some(field) = multipart.next_field().await.map_err(|err| {
-        PpdcError::new(
-            400,
-            ErrorType::ApiError,
-            format!("Multipart error: {}", err),
-        )
-    })? {
+/// Reads the uploaded file (the `file` field, or else the first file field), unpacking zip
+/// archives, along with the optional `dry_run`, `format`, `locale` and `date_overrides` text
+/// fields.
+///
+/// `date_overrides` is a JSON object from block index to `YYYY-MM-DD` or `YYYY-MM-DD HH:MM`.
+pub async fn read_import_upload(multipart: &mut Multipart) -> Result<ImportUpload, PpdcError> {
+    let mut preferred_file: Option<(Vec<u8>, Option<String>)> = None;
+    let mut fallback_file: Option<(Vec<u8>, Option<String>)> = None;
+    let mut options = ImportOptions::default();
+
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
         let field_name = field.name().map(|name| name.to_string());
-        let is_file_field = field.file_name().is_some();
-        if !is_file_field {
+        let file_name = field.file_name().map(|name| name.to_string());
+        if file_name.is_none() {
+            let value = field.text().await.map_err(multipart_error)?;
+            match field_name.as_deref() {
+                Some("dry_run") => {
+                    options.dry_run = matches!(value.trim(), "true" | "1");
+                }
+                Some("format") if !value.trim().is_empty() => {
+                    options.format = Some(parse_import_format(&value)?);
+                }
+                Some("date_overrides") if !value.trim().is_empty() => {
+                    options.date_overrides = parse_date_overrides(&value)?;
+                }
+                Some("locale") if !value.trim().is_empty() => {
+                    options.locale = Some(DateLocale::from_tag(&value).ok_or_else(|| {
+                        PpdcError::new(
+                            400,
+                            ErrorType::ApiError,
+                            format!("Unsupported locale: {}", value.trim()),
+                        )
+                    })?);
+                }
+                _ => {}
+            }
             continue;
         }
 
@@ -45,25 +83,119 @@ pub async fn post_import_text_route(
         })?;
 
         if field_name.as_deref() == Some("file") {
-            preferred_file_bytes = Some(bytes.to_vec());
-            break;
-        }
-        if fallback_file_bytes.is_none() {
-            fallback_file_bytes = Some(bytes.to_vec());
+            preferred_file = Some((bytes.to_vec(), file_name));
+        } else if fallback_file.is_none() {
+            fallback_file = Some((bytes.to_vec(), file_name));
         }
     }
 
-    let file_bytes = preferred_file_bytes
-        .or(fallback_file_bytes)
-        .ok_or_else(|| {
-            PpdcError::new(
-                400,
-                ErrorType::ApiError,
-                "No file provided in multipart payload".to_string(),
-            )
-        })?;
+    let (file_bytes, file_name) = preferred_file.or(fallback_file).ok_or_else(|| {
+        PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "No file provided in multipart payload".to_string(),
+        )
+    })?;
+    let source = ImportSource::read_upload(file_name.clone(), file_bytes).await?;
+    options.file_name = file_name;
+
+    Ok(ImportUpload { source, options })
+}
+
+fn parse_import_format(value: &str) -> Result<ImportFormat, PpdcError> {
+    serde_json::from_value(serde_json::Value::String(value.trim().to_string())).map_err(|_| {
+        PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            format!("Unsupported import format: {}", value.trim()),
+        )
+    })
+}
+
+fn parse_date_overrides(value: &str) -> Result<HashMap<usize, chrono::NaiveDateTime>, PpdcError> {
+    let invalid = |message: String| PpdcError::new(400, ErrorType::ApiError, message);
+    let raw = serde_json::from_str::<HashMap<String, String>>(value)
+        .map_err(|err| invalid(format!("Invalid date_overrides: {}", err)))?;
+    raw.into_iter()
+        .map(|(block_index, date)| {
+            let index = block_index.trim().parse::<usize>().map_err(|_| {
+                invalid(format!(
+                    "Invalid block index in date_overrides: {}",
+                    block_index
+                ))
+            })?;
+            let date = extract_date(Some(date.trim()))
+                .ok_or_else(|| invalid(format!("Invalid date in date_overrides: {}", date)))?;
+            Ok((index, date))
+        })
+        .collect()
+}
 
-    let raw_text = String::from_utf8_lossy(&file_bytes).to_string();
-    let result = import_journal_text(user_id, journal_id, &raw_text, &pool)?;
+fn ensure_journal_owner(journal_id: Uuid, user_id: Uuid, pool: &DbPool) -> Result<(), PpdcError> {
+    let journal = Journal::find_full(journal_id, pool)?;
+    if journal.user_id != user_id {
+        return Err(PpdcError::unauthorized());
+    }
+    Ok(())
+}
+
+fn find_journal_batch(
+    journal_id: Uuid,
+    batch_id: Uuid,
+    pool: &DbPool,
+) -> Result<ImportBatch, PpdcError> {
+    let batch = ImportBatch::find(batch_id, pool)?;
+    if batch.journal_id != journal_id {
+        return Err(PpdcError::new(
+            404,
+            ErrorType::ApiError,
+            "Import not found".to_string(),
+        ));
+    }
+    Ok(batch)
+}
+
+pub async fn post_import_text_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(journal_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<ImportJournalResult>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    let upload = read_import_upload(&mut multipart).await?;
+    let result =
+        import_journal(user_id, journal_id, &upload.source, &upload.options, &pool).await?;
     Ok(Json(result))
 }
+
+pub async fn get_journal_imports_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path(journal_id): Path<Uuid>,
+) -> Result<Json<Vec<ImportBatch>>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_journal_owner(journal_id, user_id, &pool)?;
+    Ok(Json(ImportBatch::list_for_journal(journal_id, &pool)?))
+}
+
+pub async fn get_journal_import_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path((journal_id, batch_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ImportBatchDetail>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_journal_owner(journal_id, user_id, &pool)?;
+    let batch = find_journal_batch(journal_id, batch_id, &pool)?;
+    Ok(Json(batch.with_trace_ids(&pool)?))
+}
+
+pub async fn post_journal_import_rollback_route(
+    Extension(pool): Extension<DbPool>,
+    Extension(session): Extension<Session>,
+    Path((journal_id, batch_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ImportBatch>, PpdcError> {
+    let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
+    ensure_journal_owner(journal_id, user_id, &pool)?;
+    let batch = find_journal_batch(journal_id, batch_id, &pool)?;
+    Ok(Json(rollback_import(batch, &pool).await?))
+}
@@ -1,31 +1,129 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use chrono_tz::Tz;
 use uuid::Uuid;
 
 use crate::db::DbPool;
-use crate::entities_v2::error::{ErrorType, PpdcError};
-use crate::entities_v2::journal::Journal;
-use crate::entities_v2::trace::TraceStatus;
+use crate::entities_v2::{
+    document::{Document, DocumentContentSource, DocumentRole, NewDocumentDto},
+    error::{ErrorType, PpdcError},
+    journal::Journal,
+    platform_infra::asset::{
+        upload_asset_for_user, upload_image_asset_for_user, Asset, AssetUploadResponse,
+    },
+    trace::{NewTrace, Trace, TraceStatus},
+    trace_attachment::NewTraceAttachment,
+    user::User,
+};
 
 use super::{
-    model::{ImportBlock, ImportJournalResult, ImportTraceFailure},
-    parser::{blocks_to_new_traces, extract_blocks},
+    dates::DateLocale,
+    importer::{ImportContext, ImportFormat, ImportSource, ImportedAttachment},
+    model::{
+        import_content_hash, ImportBatch, ImportBlockOutcome, ImportBlockPreview,
+        ImportJournalResult, ImportOptions, ImportTraceFailure,
+    },
+    parser::entries_to_new_traces,
 };
 
-fn purpose_block_to_subtitle(block: &ImportBlock) -> String {
-    let mut lines = block.content.lines();
-    let first_line = lines.next().unwrap_or_default();
-    if first_line.trim() == block.header.trim() {
-        let remaining = lines.collect::<Vec<_>>().join("\n").trim().to_string();
-        if !remaining.is_empty() {
-            return remaining;
+/// Creates the trace of an entry as a draft, uploads its files and finalizes it.
+///
+/// The first image becomes the trace image; other files are attached as documents. The draft
+/// is added to the batch before its uploads so a rollback also removes half-imported entries.
+async fn create_imported_trace(
+    mut trace: NewTrace,
+    mut attachments: Vec<ImportedAttachment>,
+    batch: &ImportBatch,
+    block_index: usize,
+    hash: String,
+    pool: &DbPool,
+) -> Result<Trace, (Option<Uuid>, PpdcError)> {
+    let user_id = trace.user_id;
+    if let Some(position) = attachments
+        .iter()
+        .position(ImportedAttachment::is_trace_image)
+    {
+        let image = attachments.remove(position);
+        let AssetUploadResponse { asset, .. } =
+            upload_image_asset_for_user(user_id, pool, Some(image.file_name), None, image.bytes)
+                .await
+                .map_err(|err| (None, err))?;
+        trace.content_image_asset_id = Some(asset.id);
+    }
+
+    let created_trace = trace.create(pool).map_err(|err| (None, err))?;
+    let trace_id = created_trace.id;
+    batch
+        .add_trace(trace_id, block_index, hash, pool)
+        .map_err(|err| (Some(trace_id), err))?;
+    for attachment in attachments {
+        let AssetUploadResponse { asset, .. } = upload_asset_for_user(
+            user_id,
+            pool,
+            Some(attachment.file_name.clone()),
+            None,
+            attachment.bytes,
+        )
+        .await
+        .map_err(|err| (Some(trace_id), err))?;
+        let document = Document::create(
+            NewDocumentDto {
+                status: None,
+                document_role: DocumentRole::Reference,
+                document_type: None,
+                content_source: DocumentContentSource::InternalAsset,
+                title: Some(attachment.file_name.clone()),
+                subtitle: None,
+                description: None,
+                author_name: None,
+                content: None,
+                content_format: None,
+                asset_id: Some(asset.id),
+                external_content_url: None,
+                cover_image_asset_id: None,
+                cover_image_external_url: None,
+            },
+            user_id,
+            pool,
+        )
+        .map_err(|err| (Some(trace_id), err))?;
+        NewTraceAttachment {
+            trace_id,
+            document_id: document.id,
+            attachment_name: attachment.file_name,
         }
+        .create(pool)
+        .map_err(|err| (Some(trace_id), err))?;
     }
-    block.content.trim().to_string()
+    created_trace
+        .set_status(TraceStatus::Finalized, pool)
+        .map_err(|err| (Some(trace_id), err))
 }
 
-pub fn import_journal_text(
+/// Reads dates with the requested locale, else the user's, counting relative dates from the
+/// user's current day.
+fn import_context(user: &User, options: &ImportOptions) -> ImportContext {
+    let time_zone = user.timezone.parse::<Tz>().unwrap_or(chrono_tz::UTC);
+    ImportContext {
+        locale: options
+            .locale
+            .or_else(|| DateLocale::from_tag(&user.locale))
+            .unwrap_or_default(),
+        today: Utc::now().with_timezone(&time_zone).date_naive(),
+    }
+}
+
+/// Imports an export into a journal, one finalized trace per entry.
+///
+/// The format is detected from the upload unless given. Entries whose content is already in
+/// the journal, or earlier in the upload, are skipped, so importing the same export twice
+/// creates nothing new. A dry run returns the same per-entry report without writing anything.
+pub async fn import_journal(
     user_id: Uuid,
     journal_id: Uuid,
-    raw_text: &str,
+    source: &ImportSource,
+    options: &ImportOptions,
     pool: &DbPool,
 ) -> Result<ImportJournalResult, PpdcError> {
     let journal = Journal::find_full(journal_id, pool)?;
@@ -33,11 +131,12 @@ pub fn import_journal_text(
         return Err(PpdcError::unauthorized());
     }
 
-    let mut blocks = extract_blocks(raw_text)
-        .into_iter()
-        .filter(|block| !block.header.trim().is_empty())
-        .collect::<Vec<_>>();
-    if blocks.is_empty() {
+    let format = options
+        .format
+        .unwrap_or_else(|| ImportFormat::detect(source));
+    let context = import_context(&User::find(&user_id, pool)?, options);
+    let parsed = format.importer().parse(source, &context)?;
+    if parsed.entries.is_empty() {
         return Err(PpdcError::new(
             400,
             ErrorType::ApiError,
@@ -45,46 +144,145 @@ pub fn import_journal_text(
         ));
     }
 
-    if blocks.first().is_some_and(|block| block.date.is_none()) {
-        let purpose_block = blocks.remove(0);
-        let subtitle_candidate = purpose_block_to_subtitle(&purpose_block);
-        if !subtitle_candidate.is_empty() {
-            let mut journal = Journal::find_full(journal_id, pool)?;
-            if journal.subtitle.trim().is_empty() {
-                journal.subtitle = subtitle_candidate;
-                journal.update(pool)?;
-            }
+    if let Some(subtitle) = parsed.journal_subtitle.filter(|_| !options.dry_run) {
+        let mut journal = Journal::find_full(journal_id, pool)?;
+        if journal.subtitle.trim().is_empty() {
+            journal.subtitle = subtitle;
+            journal.update(pool)?;
         }
     }
 
-    let traces = blocks_to_new_traces(blocks, user_id, journal_id);
+    if let Some(block_index) = options
+        .date_overrides
+        .keys()
+        .find(|block_index| **block_index >= parsed.entries.len())
+    {
+        return Err(PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            format!("date_overrides refers to unknown block {}", block_index),
+        ));
+    }
+
+    let traces =
+        entries_to_new_traces(parsed.entries, user_id, journal_id, &options.date_overrides);
     let total_blocks = traces.len();
+    let existing_hashes = Trace::get_all_for_journal(journal_id, pool)?
+        .into_iter()
+        .filter(|trace| !trace.is_encrypted)
+        .map(|trace| (import_content_hash(&trace.content), trace.id))
+        .collect::<HashMap<_, _>>();
+    let mut seen_hashes: HashMap<String, usize> = HashMap::new();
+    let batch = if options.dry_run {
+        None
+    } else {
+        Some(ImportBatch::create(
+            user_id,
+            journal_id,
+            format,
+            options.file_name.clone(),
+            total_blocks,
+            pool,
+        )?)
+    };
+
     let mut created_trace_ids = Vec::new();
     let mut failures = Vec::new();
+    let mut previews = Vec::with_capacity(total_blocks);
+    let mut skipped_count = 0;
 
-    for (block_index, block, trace) in traces {
-        match trace.create(pool) {
-            Ok(created_trace) => match created_trace.set_status(TraceStatus::Finalized, pool) {
-                Ok(finalized_trace) => created_trace_ids.push(finalized_trace.id),
-                Err(err) => failures.push(ImportTraceFailure {
+    for (block_index, entry, trace, date_confidence) in traces {
+        let hash = import_content_hash(&trace.content);
+        let mut preview = ImportBlockPreview {
+            block_index,
+            header: entry.header.clone(),
+            title: entry.title.clone(),
+            content: trace.content.clone(),
+            interaction_date: trace.interaction_date,
+            date_confidence,
+            tags: entry.tags.clone(),
+            attachments: entry
+                .attachments
+                .iter()
+                .map(|attachment| attachment.file_name.clone())
+                .collect(),
+            outcome: ImportBlockOutcome::New,
+            trace_id: None,
+            duplicate_of_trace_id: existing_hashes.get(&hash).copied(),
+            duplicate_of_block_index: seen_hashes.get(&hash).copied(),
+        };
+        if preview.duplicate_of_trace_id.is_some() || preview.duplicate_of_block_index.is_some() {
+            preview.outcome = ImportBlockOutcome::Duplicate;
+            skipped_count += 1;
+            previews.push(preview);
+            continue;
+        }
+        seen_hashes.insert(hash.clone(), block_index);
+
+        let Some(batch) = batch.as_ref() else {
+            previews.push(preview);
+            continue;
+        };
+        let created =
+            create_imported_trace(trace, entry.attachments, batch, block_index, hash, pool).await;
+        match created {
+            Ok(finalized_trace) => {
+                created_trace_ids.push(finalized_trace.id);
+                preview.outcome = ImportBlockOutcome::Created;
+                preview.trace_id = Some(finalized_trace.id);
+            }
+            Err((trace_id, err)) => {
+                preview.outcome = ImportBlockOutcome::Failed;
+                preview.trace_id = trace_id;
+                failures.push(ImportTraceFailure {
                     block_index,
-                    header: block.header,
+                    header: entry.header,
                     error: err.message,
-                }),
-            },
-            Err(err) => failures.push(ImportTraceFailure {
-                block_index,
-                header: block.header,
-                error: err.message,
-            }),
+                });
+            }
         }
+        previews.push(preview);
     }
 
+    let batch = batch
+        .map(|batch| {
+            batch.record_counts(created_trace_ids.len(), skipped_count, failures.len(), pool)
+        })
+        .transpose()?;
+
     Ok(ImportJournalResult {
+        dry_run: options.dry_run,
+        format,
+        batch_id: batch.map(|batch| batch.id),
         created_count: created_trace_ids.len(),
+        skipped_count,
         failed_count: failures.len(),
         total_blocks,
         created_trace_ids,
         failures,
+        blocks: previews,
     })
 }
+
+/// Rolls the import back, then deletes the files it uploaded from storage.
+///
+/// A file that cannot be deleted is logged and left behind; the rollback itself stands.
+pub async fn rollback_import(batch: ImportBatch, pool: &DbPool) -> Result<ImportBatch, PpdcError> {
+    let (batch, asset_ids) = batch.rollback(pool)?;
+    for asset_id in asset_ids {
+        let deleted = match Asset::find(asset_id, pool) {
+            Ok(asset) => asset.delete(pool).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = deleted {
+            tracing::warn!(
+                target: "journal_import",
+                "import_asset_delete_failed batch_id={} asset_id={} error={}",
+                batch.id,
+                asset_id,
+                err.message
+            );
+        }
+    }
+    Ok(batch)
+}
@@ -1,5 +1,6 @@
 pub mod document;
 pub mod journal;
+pub mod journal_export;
 pub mod journal_import;
 pub mod journal_share_link;
 pub mod trace;
@@ -0,0 +1,213 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zeroize::Zeroizing;
+
+use crate::entities_v2::error::{ErrorType, PpdcError};
+
+pub const TRACE_ENCRYPTION_VERSION: u32 = 1;
+pub const TRACE_ENCRYPTION_ALGORITHM: &str = "AES-256-GCM";
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+const MAX_KEY_ID_LENGTH: usize = 128;
+
+/// Symmetric per-user data key that encrypts trace content on the client.
+pub type TraceDataKey = Zeroizing<[u8; 32]>;
+
+/// Version 1 of the `encryption_metadata` carried by encrypted traces.
+///
+/// `content` holds the base64 AES-256-GCM ciphertext (tag appended) of the UTF-8 plaintext,
+/// encrypted with the data key named by `key_id` and the base64 `nonce`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TraceEncryptionMetadata {
+    pub version: u32,
+    pub algorithm: String,
+    pub key_id: String,
+    pub nonce: String,
+}
+
+impl TraceEncryptionMetadata {
+    pub fn parse(value: &Value) -> Result<Self, PpdcError> {
+        let metadata = serde_json::from_value::<TraceEncryptionMetadata>(value.clone())
+            .map_err(|err| invalid_metadata(format!("malformed encryption_metadata: {}", err)))?;
+        metadata.validate()?;
+        Ok(metadata)
+    }
+
+    fn validate(&self) -> Result<(), PpdcError> {
+        if self.version != TRACE_ENCRYPTION_VERSION {
+            return Err(invalid_metadata(format!(
+                "unsupported encryption_metadata.version {}",
+                self.version
+            )));
+        }
+        if self.algorithm != TRACE_ENCRYPTION_ALGORITHM {
+            return Err(invalid_metadata(format!(
+                "unsupported encryption_metadata.algorithm {}",
+                self.algorithm
+            )));
+        }
+        let key_id = self.key_id.trim();
+        if key_id.is_empty() || key_id.len() > MAX_KEY_ID_LENGTH {
+            return Err(invalid_metadata(format!(
+                "encryption_metadata.key_id must be 1 to {} characters",
+                MAX_KEY_ID_LENGTH
+            )));
+        }
+        self.nonce_bytes()?;
+        Ok(())
+    }
+
+    fn nonce_bytes(&self) -> Result<[u8; NONCE_LENGTH], PpdcError> {
+        BASE64
+            .decode(&self.nonce)
+            .ok()
+            .and_then(|bytes| <[u8; NONCE_LENGTH]>::try_from(bytes.as_slice()).ok())
+            .ok_or_else(|| {
+                invalid_metadata(format!(
+                    "encryption_metadata.nonce must be {} base64-encoded bytes",
+                    NONCE_LENGTH
+                ))
+            })
+    }
+
+    /// Decrypts trace content in memory; the plaintext must never be written back.
+    pub fn decrypt_content(
+        &self,
+        content: &str,
+        key: &TraceDataKey,
+    ) -> Result<Zeroizing<String>, PpdcError> {
+        if content.is_empty() {
+            return Ok(Zeroizing::new(String::new()));
+        }
+        let ciphertext = decode_ciphertext(content)?;
+        let nonce = self.nonce_bytes()?;
+        let cipher = Aes256Gcm::new_from_slice(key.as_slice())
+            .map_err(|_| decryption_failed("invalid data key length"))?;
+        let plaintext = Zeroizing::new(
+            cipher
+                .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+                .map_err(|_| decryption_failed("data key does not match this trace"))?,
+        );
+        let text = std::str::from_utf8(&plaintext)
+            .map_err(|_| decryption_failed("decrypted content is not valid UTF-8"))?;
+        Ok(Zeroizing::new(text.to_string()))
+    }
+}
+
+/// Checks the encryption fields of a trace about to be written.
+pub fn validate_trace_encryption(
+    is_encrypted: bool,
+    encryption_metadata: Option<&Value>,
+    content: &str,
+) -> Result<(), PpdcError> {
+    match (is_encrypted, encryption_metadata) {
+        (true, None) => Err(PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "encryption_metadata is required when is_encrypted is true".to_string(),
+        )),
+        (true, Some(value)) => {
+            TraceEncryptionMetadata::parse(value)?;
+            if !content.is_empty() {
+                decode_ciphertext(content)?;
+            }
+            Ok(())
+        }
+        (false, Some(value)) if !value.is_null() => Err(PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "encryption_metadata must be null when is_encrypted is false".to_string(),
+        )),
+        (false, _) => Ok(()),
+    }
+}
+
+fn decode_ciphertext(content: &str) -> Result<Vec<u8>, PpdcError> {
+    let ciphertext = BASE64.decode(content.trim()).map_err(|_| {
+        PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "content of an encrypted trace must be base64 ciphertext".to_string(),
+        )
+    })?;
+    if ciphertext.len() < TAG_LENGTH {
+        return Err(PpdcError::new(
+            400,
+            ErrorType::ApiError,
+            "content of an encrypted trace is shorter than the authentication tag".to_string(),
+        ));
+    }
+    Ok(ciphertext)
+}
+
+fn invalid_metadata(message: String) -> PpdcError {
+    PpdcError::new(400, ErrorType::ApiError, message)
+}
+
+fn decryption_failed(reason: &str) -> PpdcError {
+    PpdcError::new(
+        422,
+        ErrorType::ApiError,
+        format!("Failed to decrypt trace content: {}", reason),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn encrypt(plaintext: &str, key: &TraceDataKey, nonce: &[u8; NONCE_LENGTH]) -> String {
+        let cipher = Aes256Gcm::new_from_slice(key.as_slice()).unwrap();
+        BASE64.encode(
+            cipher
+                .encrypt(Nonce::from_slice(nonce), plaintext.as_bytes())
+                .unwrap(),
+        )
+    }
+
+    fn metadata(nonce: &[u8; NONCE_LENGTH]) -> Value {
+        json!({
+            "version": 1,
+            "algorithm": "AES-256-GCM",
+            "key_id": "user-key-1",
+            "nonce": BASE64.encode(nonce),
+        })
+    }
+
+    #[test]
+    fn decrypts_content_with_matching_key() {
+        let key = Zeroizing::new([7u8; 32]);
+        let nonce = [3u8; NONCE_LENGTH];
+        let content = encrypt("Journal du jour", &key, &nonce);
+        let metadata = TraceEncryptionMetadata::parse(&metadata(&nonce)).unwrap();
+
+        assert_eq!(
+            metadata.decrypt_content(&content, &key).unwrap().as_str(),
+            "Journal du jour"
+        );
+        assert!(metadata
+            .decrypt_content(&content, &Zeroizing::new([8u8; 32]))
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_or_malformed_metadata() {
+        let nonce = [3u8; NONCE_LENGTH];
+        let mut unsupported = metadata(&nonce);
+        unsupported["version"] = json!(2);
+        assert!(TraceEncryptionMetadata::parse(&unsupported).is_err());
+
+        let mut short_nonce = metadata(&nonce);
+        short_nonce["nonce"] = json!(BASE64.encode([1u8; 8]));
+        assert!(TraceEncryptionMetadata::parse(&short_nonce).is_err());
+
+        assert!(validate_trace_encryption(true, Some(&metadata(&nonce)), "not base64!").is_err());
+        assert!(validate_trace_encryption(false, Some(&metadata(&nonce)), "plain").is_err());
+        assert!(validate_trace_encryption(false, None, "plain").is_ok());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::db::DbPool;
+use crate::entities_v2::data_key;
 use crate::entities_v2::error::{ErrorType, PpdcError};
 use crate::entities_v2::session::Session;
 use crate::schema::traces;
@@ -26,7 +27,7 @@ pub fn heatmap_sum_trace_content_len(
     from: NaiveDate,
     to: NaiveDate,
     user_id: Uuid,
-) -> QueryResult<Vec<HeatmapRow>> {
+) -> Result<Vec<HeatmapRow>, PpdcError> {
     let from_dt = from.and_hms_opt(0, 0, 0).expect("valid day start");
     let to_exclusive_dt = (to + Duration::days(1))
         .and_hms_opt(0, 0, 0)
@@ -42,6 +43,7 @@ pub fn heatmap_sum_trace_content_len(
     let mut by_day = HashMap::<NaiveDate, i64>::new();
     for (interaction_date, content) in rows {
         let day = interaction_date.date();
+        let content = data_key::open(content)?;
         let value = i64::try_from(content.chars().count()).unwrap_or(i64::MAX);
         *by_day.entry(day).or_insert(0) += value;
     }
@@ -6,6 +6,7 @@ use serde_json::Value;
 use uuid::Uuid;
 
 use crate::db::DbPool;
+use crate::entities_v2::data_key;
 use crate::entities_v2::error::PpdcError;
 use crate::schema::traces;
 
@@ -36,7 +37,7 @@ type TraceTuple = (
     NaiveDateTime,
 );
 
-fn tuple_to_trace(row: TraceTuple) -> Trace {
+fn tuple_to_trace(row: TraceTuple) -> Result<Trace, PpdcError> {
     let (
         id,
         derived_from_trace_id,
@@ -62,13 +63,13 @@ fn tuple_to_trace(row: TraceTuple) -> Trace {
         updated_at,
     ) = row;
 
-    Trace {
+    Ok(Trace {
         id,
         derived_from_trace_id,
         title,
         subtitle,
         interaction_date,
-        content,
+        content: data_key::open(content)?,
         is_encrypted,
         encryption_metadata: encryption_metadata_json
             .and_then(|json| serde_json::from_str::<Value>(&json).ok()),
@@ -86,15 +87,26 @@ fn tuple_to_trace(row: TraceTuple) -> Trace {
         finalized_at,
         created_at,
         updated_at,
-    }
+    })
 }
 
 impl Trace {
     pub fn find_full_trace(id: Uuid, pool: &DbPool) -> Result<Trace, PpdcError> {
+        Trace::find_full_traces(&[id], pool)?
+            .pop()
+            .ok_or(diesel::result::Error::NotFound)
+            .map_err(PpdcError::from)
+    }
+
+    /// Loads the traces in one query, in no particular order; missing ids are skipped.
+    pub fn find_full_traces(ids: &[Uuid], pool: &DbPool) -> Result<Vec<Trace>, PpdcError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
         let mut conn = pool.get()?;
 
-        let row = traces::table
-            .filter(traces::id.eq(id))
+        let rows = traces::table
+            .filter(traces::id.eq_any(ids))
             .select((
                 traces::id,
                 traces::derived_from_trace_id,
@@ -119,8 +131,8 @@ impl Trace {
                 traces::created_at,
                 traces::updated_at,
             ))
-            .first::<TraceTuple>(&mut conn)?;
+            .load::<TraceTuple>(&mut conn)?;
 
-        Ok(tuple_to_trace(row))
+        rows.into_iter().map(tuple_to_trace).collect()
     }
 }
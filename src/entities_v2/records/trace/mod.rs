@@ -1,3 +1,4 @@
+pub mod encryption;
 pub mod enums;
 pub mod heatmap;
 pub mod hydrate;
@@ -6,6 +7,7 @@ pub mod model;
 pub mod persist;
 pub mod routes;
 
+pub use encryption::{TraceDataKey, TraceEncryptionMetadata};
 pub use heatmap::get_user_heatmap_route;
 pub use model::{
     JournalTraceView, NewTrace, NewTraceDto, PatchTraceDto, Trace, TraceSharingSensitivity,
@@ -7,6 +7,7 @@ use serde_json::Value;
 use uuid::Uuid;
 
 use crate::db::DbPool;
+use crate::entities_v2::data_key;
 use crate::entities_v2::error::PpdcError;
 use crate::entities_v2::post::PostStatus;
 use crate::entities_v2::post_grant::PostGrant;
@@ -198,15 +199,17 @@ pub(crate) struct TraceRow {
     pub updated_at: NaiveDateTime,
 }
 
-impl From<TraceRow> for Trace {
-    fn from(row: TraceRow) -> Self {
-        Trace {
+impl TryFrom<TraceRow> for Trace {
+    type Error = PpdcError;
+
+    fn try_from(row: TraceRow) -> Result<Self, PpdcError> {
+        Ok(Trace {
             id: row.id,
             derived_from_trace_id: row.derived_from_trace_id,
             title: row.title,
             subtitle: row.subtitle,
             interaction_date: row.interaction_date,
-            content: row.content,
+            content: data_key::open(row.content)?,
             is_encrypted: row.is_encrypted,
             encryption_metadata: row
                 .encryption_metadata
@@ -225,7 +228,7 @@ impl From<TraceRow> for Trace {
             finalized_at: row.finalized_at,
             created_at: row.created_at,
             updated_at: row.updated_at,
-        }
+        })
     }
 }
 
@@ -270,7 +273,7 @@ impl Trace {
         .get_result::<TraceRow>(&mut conn)
         .optional()?;
 
-        Ok(row.map(Trace::from))
+        row.map(Trace::try_from).transpose()
     }
 
     fn find_first_user_trace_for_user(
@@ -291,7 +294,7 @@ impl Trace {
         .get_result::<TraceRow>(&mut conn)
         .optional()?;
 
-        Ok(row.map(Trace::from))
+        row.map(Trace::try_from).transpose()
     }
 
     pub fn get_most_recent_for_user(
@@ -311,7 +314,7 @@ impl Trace {
         .get_result::<TraceRow>(&mut conn)
         .optional()?;
 
-        Ok(row.map(Trace::from))
+        row.map(Trace::try_from).transpose()
     }
 
     pub fn get_between(
@@ -340,7 +343,7 @@ impl Trace {
         .bind::<Timestamp, _>(to)
         .load::<TraceRow>(&mut conn)?;
 
-        Ok(rows.into_iter().map(Trace::from).collect())
+        rows.into_iter().map(Trace::try_from).collect()
     }
 
     pub fn get_before(
@@ -364,7 +367,7 @@ impl Trace {
         .bind::<Timestamp, _>(until)
         .load::<TraceRow>(&mut conn)?;
 
-        Ok(rows.into_iter().map(Trace::from).collect())
+        rows.into_iter().map(Trace::try_from).collect()
     }
 
     pub fn get_first(user_id: Uuid, pool: &DbPool) -> Result<Option<Trace>, PpdcError> {
@@ -383,7 +386,7 @@ impl Trace {
         .optional()?;
 
         if let Some(row) = latest_hlp {
-            return Ok(Some(row.into()));
+            return Ok(Some(row.try_into()?));
         }
 
         let latest_bio = diesel::sql_query(
@@ -399,7 +402,7 @@ impl Trace {
         .optional()?;
 
         if let Some(row) = latest_bio {
-            return Ok(Some(row.into()));
+            return Ok(Some(row.try_into()?));
         }
 
         Trace::find_first_user_trace_for_user(user_id, pool)
@@ -428,7 +431,7 @@ impl Trace {
             .optional()?;
 
             if let Some(row) = latest_bio {
-                return Ok(Some(row.into()));
+                return Ok(Some(row.try_into()?));
             }
 
             return Trace::find_first_user_trace_for_user(user_id, pool);
@@ -455,7 +458,7 @@ impl Trace {
         .get_result::<TraceRow>(&mut conn)
         .optional()?;
 
-        Ok(next.map(Trace::from))
+        next.map(Trace::try_from).transpose()
     }
 
     pub fn get_all_for_user(user_id: Uuid, pool: &DbPool) -> Result<Vec<Trace>, PpdcError> {
@@ -493,7 +496,12 @@ impl Trace {
         .bind::<diesel::sql_types::BigInt, _>(limit)
         .load::<TraceRow>(&mut conn)?;
 
-        Ok((rows.into_iter().map(Trace::from).collect(), total))
+        Ok((
+            rows.into_iter()
+                .map(Trace::try_from)
+                .collect::<Result<_, _>>()?,
+            total,
+        ))
     }
 
     pub fn get_all_for_journal(journal_id: Uuid, pool: &DbPool) -> Result<Vec<Trace>, PpdcError> {
@@ -648,35 +656,37 @@ impl Trace {
                         finalized_at,
                         created_at,
                         updated_at,
-                    )| Trace {
-                        id,
-                        derived_from_trace_id,
-                        title,
-                        subtitle,
-                        interaction_date,
-                        content,
-                        is_encrypted,
-                        encryption_metadata: encryption_metadata
-                            .and_then(|json| serde_json::from_str::<Value>(&json).ok()),
-                        content_image_asset_id,
-                        sharing_sensitivity: TraceSharingSensitivity::from_db(
-                            &sharing_sensitivity_raw,
-                        ),
-                        timeout_start_at,
-                        timeout_at,
-                        journal_id,
-                        user_id,
-                        trace_type: TraceType::from_db(&trace_type_raw),
-                        status: TraceStatus::from_db(&status_raw),
-                        version_integer,
-                        is_blank,
-                        start_writing_at,
-                        finalized_at,
-                        created_at,
-                        updated_at,
+                    )| {
+                        Ok(Trace {
+                            id,
+                            derived_from_trace_id,
+                            title,
+                            subtitle,
+                            interaction_date,
+                            content: data_key::open(content)?,
+                            is_encrypted,
+                            encryption_metadata: encryption_metadata
+                                .and_then(|json| serde_json::from_str::<Value>(&json).ok()),
+                            content_image_asset_id,
+                            sharing_sensitivity: TraceSharingSensitivity::from_db(
+                                &sharing_sensitivity_raw,
+                            ),
+                            timeout_start_at,
+                            timeout_at,
+                            journal_id,
+                            user_id,
+                            trace_type: TraceType::from_db(&trace_type_raw),
+                            status: TraceStatus::from_db(&status_raw),
+                            version_integer,
+                            is_blank,
+                            start_writing_at,
+                            finalized_at,
+                            created_at,
+                            updated_at,
+                        })
                     },
                 )
-                .collect(),
+                .collect::<Result<Vec<_>, PpdcError>>()?,
             total,
         ))
     }
@@ -835,35 +845,37 @@ impl Trace {
                     interaction_date,
                     created_at,
                     updated_at,
-                )| JournalTraceView {
-                    id,
-                    post_id: Some(post_id),
-                    version_integer: None,
-                    journal_id,
-                    title,
-                    subtitle: None,
-                    content,
-                    derived_from_trace_id: None,
-                    is_encrypted: None,
-                    encryption_metadata: None,
-                    content_image_asset_id,
-                    sharing_sensitivity: None,
-                    timeout_start_at: None,
-                    timeout_at: None,
-                    user_id: Some(user_id),
-                    trace_type: None,
-                    status: None,
-                    start_writing_at: None,
-                    finalized_at,
-                    seen: false,
-                    last_seen_at: None,
-                    seen_by_preview: None,
-                    interaction_date,
-                    created_at,
-                    updated_at,
+                )| {
+                    Ok(JournalTraceView {
+                        id,
+                        post_id: Some(post_id),
+                        version_integer: None,
+                        journal_id,
+                        title,
+                        subtitle: None,
+                        content: data_key::open(content)?,
+                        derived_from_trace_id: None,
+                        is_encrypted: None,
+                        encryption_metadata: None,
+                        content_image_asset_id,
+                        sharing_sensitivity: None,
+                        timeout_start_at: None,
+                        timeout_at: None,
+                        user_id: Some(user_id),
+                        trace_type: None,
+                        status: None,
+                        start_writing_at: None,
+                        finalized_at,
+                        seen: false,
+                        last_seen_at: None,
+                        seen_by_preview: None,
+                        interaction_date,
+                        created_at,
+                        updated_at,
+                    })
                 },
             )
-            .collect();
+            .collect::<Result<Vec<_>, PpdcError>>()?;
 
         Ok((traces, total))
     }
@@ -956,7 +968,12 @@ impl Trace {
         .bind::<diesel::sql_types::BigInt, _>(limit)
         .load::<TraceRow>(&mut conn)?;
 
-        Ok((rows.into_iter().map(Trace::from).collect(), total))
+        Ok((
+            rows.into_iter()
+                .map(Trace::try_from)
+                .collect::<Result<_, _>>()?,
+            total,
+        ))
     }
 
     pub fn get_expired_drafts_for_user(
@@ -978,7 +995,7 @@ impl Trace {
         .bind::<SqlUuid, _>(user_id)
         .load::<TraceRow>(&mut conn)?;
 
-        Ok(rows.into_iter().map(Trace::from).collect())
+        rows.into_iter().map(Trace::try_from).collect()
     }
 }
 
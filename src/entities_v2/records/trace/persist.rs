@@ -3,12 +3,14 @@ use diesel::prelude::*;
 use diesel::sql_types::{BigInt, Bool, Nullable, Text, Timestamp, Timestamptz, Uuid as SqlUuid};
 
 use crate::db::DbPool;
+use crate::entities_v2::data_key;
 use crate::entities_v2::error::{ErrorType, PpdcError};
 use crate::entities_v2::journal::{Journal, JournalStatus, JournalType};
 use crate::entities_v2::post::{enforce_publication_invariant_for_source, PostSourceRef};
 use crate::entities_v2::trace_search::TraceSearchDocument;
 use crate::schema::trace_attachments;
 
+use super::encryption::validate_trace_encryption;
 use super::model::{NewTrace, Trace, TraceStatus};
 
 #[derive(QueryableByName)]
@@ -75,6 +77,19 @@ fn compute_is_blank_with_conn(
         && !has_attachments)
 }
 
+/// Content as written to `traces`: sealed at rest, unless the client already encrypted it.
+fn stored_content(
+    user_id: uuid::Uuid,
+    is_encrypted: bool,
+    content: &str,
+    pool: &DbPool,
+) -> Result<String, PpdcError> {
+    if is_encrypted {
+        return Ok(content.to_string());
+    }
+    data_key::seal_for_owner(Some(user_id), content, pool)
+}
+
 impl Trace {
     pub fn update(self, pool: &DbPool) -> Result<Trace, PpdcError> {
         self.update_internal(None, pool)
@@ -93,16 +108,15 @@ impl Trace {
         expected_version_integer: Option<i32>,
         pool: &DbPool,
     ) -> Result<Trace, PpdcError> {
-        if self.is_encrypted && self.encryption_metadata.is_none() {
-            return Err(PpdcError::new(
-                400,
-                ErrorType::ApiError,
-                "encryption_metadata is required when is_encrypted is true".to_string(),
-            ));
-        }
+        validate_trace_encryption(
+            self.is_encrypted,
+            self.encryption_metadata.as_ref(),
+            &self.content,
+        )?;
         if self.timeout_at.is_some() && self.timeout_start_at.is_none() {
             self.timeout_start_at = Some(Utc::now());
         }
+        let stored_content = stored_content(self.user_id, self.is_encrypted, &self.content, pool)?;
         let mut conn = pool.get()?;
 
         let updated = conn.transaction::<bool, diesel::result::Error, _>(|conn| {
@@ -143,7 +157,7 @@ impl Trace {
                 .bind::<SqlUuid, _>(self.id)
                 .bind::<Text, _>(&self.title)
                 .bind::<Text, _>(&self.subtitle)
-                .bind::<Text, _>(&self.content)
+                .bind::<Text, _>(&stored_content)
                 .bind::<Nullable<SqlUuid>, _>(self.derived_from_trace_id)
                 .bind::<Bool, _>(self.is_encrypted)
                 .bind::<Nullable<Text>, _>(
@@ -196,7 +210,7 @@ impl Trace {
                 .bind::<SqlUuid, _>(self.id)
                 .bind::<Text, _>(&self.title)
                 .bind::<Text, _>(&self.subtitle)
-                .bind::<Text, _>(&self.content)
+                .bind::<Text, _>(&stored_content)
                 .bind::<Nullable<SqlUuid>, _>(self.derived_from_trace_id)
                 .bind::<Bool, _>(self.is_encrypted)
                 .bind::<Nullable<Text>, _>(
@@ -286,19 +300,18 @@ impl Trace {
 
 impl NewTrace {
     pub fn create(mut self, pool: &DbPool) -> Result<Trace, PpdcError> {
-        if self.is_encrypted && self.encryption_metadata.is_none() {
-            return Err(PpdcError::new(
-                400,
-                ErrorType::ApiError,
-                "encryption_metadata is required when is_encrypted is true".to_string(),
-            ));
-        }
+        validate_trace_encryption(
+            self.is_encrypted,
+            self.encryption_metadata.as_ref(),
+            &self.content,
+        )?;
         if self.timeout_at.is_some() && self.timeout_start_at.is_none() {
             self.timeout_start_at = Some(Utc::now());
         }
         self.is_blank = self.title.trim().is_empty()
             && self.content.trim().is_empty()
             && self.content_image_asset_id.is_none();
+        let stored_content = stored_content(self.user_id, self.is_encrypted, &self.content, pool)?;
         let mut conn = pool.get()?;
 
         let inserted = conn.transaction::<IdRow, diesel::result::Error, _>(|conn| {
@@ -353,7 +366,7 @@ impl NewTrace {
             .bind::<Nullable<SqlUuid>, _>(self.derived_from_trace_id)
             .bind::<Text, _>(&self.title)
             .bind::<Text, _>(&self.subtitle)
-            .bind::<Text, _>(&self.content)
+            .bind::<Text, _>(&stored_content)
             .bind::<Bool, _>(self.is_encrypted)
             .bind::<Nullable<Text>, _>(
                 self.encryption_metadata
@@ -379,17 +392,16 @@ impl NewTrace {
     }
 
     pub fn create_finalized(mut self, pool: &DbPool) -> Result<Trace, PpdcError> {
-        if self.is_encrypted && self.encryption_metadata.is_none() {
-            return Err(PpdcError::new(
-                400,
-                ErrorType::ApiError,
-                "encryption_metadata is required when is_encrypted is true".to_string(),
-            ));
-        }
+        validate_trace_encryption(
+            self.is_encrypted,
+            self.encryption_metadata.as_ref(),
+            &self.content,
+        )?;
         self.timeout_start_at = None;
         self.timeout_at = None;
         self.is_blank = false;
         let finalized_at = Utc::now().naive_utc();
+        let stored_content = stored_content(self.user_id, self.is_encrypted, &self.content, pool)?;
         let mut conn = pool.get()?;
 
         let inserted = conn.transaction::<IdRow, diesel::result::Error, _>(|conn| {
@@ -444,7 +456,7 @@ impl NewTrace {
             .bind::<Nullable<SqlUuid>, _>(self.derived_from_trace_id)
             .bind::<Text, _>(&self.title)
             .bind::<Text, _>(&self.subtitle)
-            .bind::<Text, _>(&self.content)
+            .bind::<Text, _>(&stored_content)
             .bind::<Bool, _>(self.is_encrypted)
             .bind::<Nullable<Text>, _>(
                 self.encryption_metadata
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Array, Text, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::error::PpdcError;
+
+use super::model::{TraceSearchHighlight, TraceSearchMatch};
+
+/// Markers `ts_headline` wraps matches and separates fragments with; they cannot occur in text.
+const START_SEL: char = '\u{2}';
+const STOP_SEL: char = '\u{3}';
+const FRAGMENT_DELIMITER: char = '\u{1}';
+const MAX_FRAGMENTS: usize = 3;
+
+#[derive(QueryableByName)]
+struct HeadlineRow {
+    #[diesel(sql_type = SqlUuid)]
+    trace_id: Uuid,
+    #[diesel(sql_type = Text)]
+    headline: String,
+}
+
+/// `ts_headline` fragments of each trace content around the query matches.
+///
+/// Contents are passed in plaintext since the index keeps no copy of them; each trace is
+/// highlighted with the configuration it is indexed with.
+pub(super) fn find_highlights(
+    query: &str,
+    contents: &[(Uuid, String)],
+    pool: &DbPool,
+) -> Result<HashMap<Uuid, Vec<TraceSearchHighlight>>, PpdcError> {
+    if contents.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let trace_ids = contents.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+    let bodies = contents
+        .iter()
+        .map(|(_, content)| content.clone())
+        .collect::<Vec<_>>();
+    let options = format!(
+        "StartSel={}, StopSel={}, MaxFragments={}, MaxWords=30, MinWords=12, FragmentDelimiter={}",
+        START_SEL, STOP_SEL, MAX_FRAGMENTS, FRAGMENT_DELIMITER
+    );
+
+    let mut conn = pool.get()?;
+    let rows = sql_query(
+        r#"
+        SELECT
+            input.trace_id,
+            ts_headline(
+                tsd.search_config::regconfig,
+                input.body,
+                websearch_to_tsquery(tsd.search_config::regconfig, $3),
+                $4
+            ) AS headline
+        FROM unnest($1::uuid[], $2::text[]) AS input(trace_id, body)
+        INNER JOIN trace_search_documents tsd
+          ON tsd.trace_id = input.trace_id
+        WHERE input.body <> ''
+        "#,
+    )
+    .bind::<Array<SqlUuid>, _>(&trace_ids)
+    .bind::<Array<Text>, _>(&bodies)
+    .bind::<Text, _>(query)
+    .bind::<Text, _>(options)
+    .load::<HeadlineRow>(&mut conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.trace_id, parse_headline(&row.headline)))
+        .collect())
+}
+
+/// Splits a headline into fragments and turns its markers into character offsets.
+/// Fragments without a match are dropped.
+fn parse_headline(headline: &str) -> Vec<TraceSearchHighlight> {
+    headline
+        .split(FRAGMENT_DELIMITER)
+        .filter_map(|fragment| {
+            let mut text = String::new();
+            let mut matches = Vec::new();
+            let mut position = 0;
+            let mut start = None;
+            for c in fragment.trim().chars() {
+                match c {
+                    START_SEL => start = Some(position),
+                    STOP_SEL => {
+                        if let Some(start) = start.take() {
+                            matches.push(TraceSearchMatch {
+                                start,
+                                end: position,
+                            });
+                        }
+                    }
+                    _ => {
+                        text.push(c);
+                        position += 1;
+                    }
+                }
+            }
+            (!matches.is_empty()).then_some(TraceSearchHighlight { text, matches })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_markers_into_character_offsets() {
+        let headline = format!(
+            " Les {s}projets{e} d'été {d} rien ici {d}Un {s}projet{e} et {s}équipe{e}",
+            s = START_SEL,
+            e = STOP_SEL,
+            d = FRAGMENT_DELIMITER
+        );
+        let highlights = parse_headline(&headline);
+
+        assert_eq!(highlights.len(), 2);
+        assert_eq!(highlights[0].text, "Les projets d'été");
+        assert_eq!(
+            highlights[0].matches,
+            vec![TraceSearchMatch { start: 4, end: 11 }]
+        );
+        assert_eq!(highlights[1].text, "Un projet et équipe");
+        let equipe = &highlights[1].matches[1];
+        let matched = highlights[1]
+            .text
+            .chars()
+            .skip(equipe.start)
+            .take(equipe.end - equipe.start)
+            .collect::<String>();
+        assert_eq!(matched, "équipe");
+    }
+}
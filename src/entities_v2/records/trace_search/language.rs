@@ -0,0 +1,80 @@
+use serde::Serialize;
+
+/// Stop words telling French from English; words common to both are left out.
+const FRENCH_MARKERS: &[&str] = &[
+    "le", "la", "les", "des", "une", "est", "et", "du", "que", "qui", "pas", "pour", "dans", "sur",
+    "avec", "mais", "je", "nous", "vous", "il", "elle", "ce", "cette", "au", "aux", "été", "très",
+    "plus", "ai", "mon", "ma", "mes", "j", "c", "d", "l", "qu",
+];
+const ENGLISH_MARKERS: &[&str] = &[
+    "the", "and", "is", "are", "was", "of", "to", "that", "it", "for", "with", "this", "but",
+    "you", "we", "have", "be", "not", "at", "from", "my", "i", "been", "very", "they", "what",
+];
+/// Marker words a language needs before a trace is indexed with its configuration.
+const MIN_MARKERS: usize = 3;
+
+/// Language a trace is indexed in, picked from the stop words of its text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchLanguage {
+    French,
+    English,
+    Unknown,
+}
+
+impl SearchLanguage {
+    /// Postgres text search configuration the trace is indexed and queried with.
+    pub fn text_search_config(self) -> &'static str {
+        match self {
+            SearchLanguage::French => "french_unaccent",
+            SearchLanguage::English => "english_unaccent",
+            SearchLanguage::Unknown => "simple_unaccent",
+        }
+    }
+
+    /// A language wins with at least `MIN_MARKERS` marker words and twice as many as the other.
+    pub fn detect(text: &str) -> Self {
+        let mut french = 0;
+        let mut english = 0;
+        for word in text
+            .split(|c: char| !c.is_alphabetic())
+            .filter(|word| !word.is_empty())
+        {
+            let word = word.to_lowercase();
+            if FRENCH_MARKERS.contains(&word.as_str()) {
+                french += 1;
+            } else if ENGLISH_MARKERS.contains(&word.as_str()) {
+                english += 1;
+            }
+        }
+        if french >= MIN_MARKERS && french >= english * 2 {
+            SearchLanguage::French
+        } else if english >= MIN_MARKERS && english >= french * 2 {
+            SearchLanguage::English
+        } else {
+            SearchLanguage::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_french_and_english_and_falls_back_to_simple() {
+        assert_eq!(
+            SearchLanguage::detect("J'ai avancé sur les projets de l'équipe avec Marie."),
+            SearchLanguage::French
+        );
+        assert_eq!(
+            SearchLanguage::detect("I worked on the map project with the team this morning."),
+            SearchLanguage::English
+        );
+        assert_eq!(SearchLanguage::detect("Atlas v2"), SearchLanguage::Unknown);
+        assert_eq!(
+            SearchLanguage::Unknown.text_search_config(),
+            "simple_unaccent"
+        );
+    }
+}
@@ -1,5 +1,13 @@
+pub mod highlight;
+pub mod language;
 pub mod model;
 pub mod routes;
+pub mod semantic;
 
-pub use model::{TraceSearchDocument, TraceSearchItem, TraceSearchParams};
+pub use language::SearchLanguage;
+pub use model::{
+    TraceSearchDocument, TraceSearchFacets, TraceSearchFilters, TraceSearchHighlight,
+    TraceSearchItem, TraceSearchMode, TraceSearchParams, TraceSearchResponse, TraceSearchSnippet,
+};
 pub use routes::get_trace_search_route;
+pub use semantic::{backfill_chunk_embeddings, SemanticQuery};
@@ -1,6 +1,9 @@
+use chrono::{Months, NaiveDate};
 use diesel::prelude::*;
 use diesel::sql_query;
-use diesel::sql_types::{Array, BigInt, Bool, Float, Int4, Text, Timestamp, Uuid as SqlUuid};
+use diesel::sql_types::{
+    Array, BigInt, Bool, Date, Float, Int4, Nullable, Text, Timestamp, Uuid as SqlUuid,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -8,9 +11,111 @@ use uuid::Uuid;
 use crate::db::DbPool;
 use crate::entities_v2::{
     error::{ErrorType, PpdcError},
-    trace::{Trace, TraceStatus},
+    trace::{Trace, TraceStatus, TraceType},
 };
-use crate::pagination::PaginationParams;
+use crate::pagination::{PaginatedResponse, PaginationParams};
+
+use super::highlight;
+use super::language::SearchLanguage;
+use super::semantic::{self, SemanticQuery};
+
+/// Journal, landmark, high-level project, date and trace type filters on `tsd`; binds `$1` user
+/// and `$3`..`$8`.
+/// Configurations documents are indexed with; `simple` remains on documents indexed before the
+/// language was detected, until `REINDEX_TRACE_SEARCH_DOCUMENTS` refreshes them.
+const SEARCH_CONFIGS: [&str; 4] = [
+    "simple",
+    "simple_unaccent",
+    "french_unaccent",
+    "english_unaccent",
+];
+const LEGACY_SEARCH_CONFIG: &str = "simple";
+
+/// Matches the query against each document with its configuration. One branch per configuration
+/// keeps each tsquery constant, so the GIN index on `search_vector` can serve every branch.
+fn lexical_match_sql() -> String {
+    let branches = SEARCH_CONFIGS
+        .iter()
+        .map(|config| {
+            format!(
+                "(tsd.search_config = '{config}' AND tsd.search_vector @@ websearch_to_tsquery('{config}', $2))"
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("({})", branches.join(" OR "))
+}
+
+/// The query parsed with the configuration of the document, for ranking matched rows.
+fn lexical_query_sql() -> String {
+    let branches = SEARCH_CONFIGS
+        .iter()
+        .map(|config| format!("WHEN '{config}' THEN websearch_to_tsquery('{config}', $2)"))
+        .collect::<Vec<_>>();
+    format!("CASE tsd.search_config {} END", branches.join(" "))
+}
+
+pub(super) const TRACE_SEARCH_FILTER_SQL: &str = r#"
+        AND (cardinality($3::uuid[]) = 0 OR tsd.journal_id = ANY($3))
+        AND (
+          cardinality($4::uuid[]) = 0
+          OR EXISTS (
+              SELECT 1
+              FROM "references" r
+              INNER JOIN trace_mirrors tm
+                ON tm.id = r.trace_mirror_id
+              INNER JOIN landscape_analyses la
+                ON la.id = r.landscape_analysis_id
+               AND la.landscape_analysis_type = 'TRACE_INCREMENTAL'
+              INNER JOIN users u
+                ON u.id = $1
+              INNER JOIN lens_analysis_scopes las
+                ON las.landscape_analysis_id = r.landscape_analysis_id
+               AND las.lens_id = u.current_lens_id
+              INNER JOIN landmarks l
+                ON l.id = r.landmark_id
+              WHERE tm.trace_id = tsd.trace_id
+                AND r.landmark_id = ANY($4)
+                AND l.landmark_type <> 'HIGH_LEVEL_PROJECT'
+          )
+        )
+        AND (
+          cardinality($5::uuid[]) = 0
+          OR EXISTS (
+              SELECT 1
+              FROM "references" r
+              INNER JOIN trace_mirrors tm
+                ON tm.id = r.trace_mirror_id
+              INNER JOIN landscape_analyses la
+                ON la.id = r.landscape_analysis_id
+               AND la.landscape_analysis_type = 'TRACE_INCREMENTAL'
+              INNER JOIN users u
+                ON u.id = $1
+              INNER JOIN lens_analysis_scopes las
+                ON las.landscape_analysis_id = r.landscape_analysis_id
+               AND las.lens_id = u.current_lens_id
+              INNER JOIN landmarks l
+                ON l.id = r.landmark_id
+              WHERE tm.trace_id = tsd.trace_id
+                AND r.landmark_id = ANY($5)
+                AND l.landmark_type = 'HIGH_LEVEL_PROJECT'
+          )
+        )
+        AND ($6::date IS NULL OR tsd.interaction_date >= $6)
+        AND ($7::date IS NULL OR tsd.interaction_date < $7 + 1)
+        AND (
+          cardinality($8::text[]) = 0
+          OR EXISTS (
+              SELECT 1
+              FROM traces t
+              WHERE t.id = tsd.trace_id
+                AND t.trace_type = ANY($8)
+          )
+        )
+"#;
+
+/// Candidates taken from full-text search before fusing with semantic search.
+const HYBRID_LEXICAL_CANDIDATES: i64 = 100;
+const SEMANTIC_SOURCE: &str = "semantic";
 
 #[derive(Debug)]
 pub struct TraceSearchDocument;
@@ -18,19 +123,116 @@ pub struct TraceSearchDocument;
 #[derive(Debug, Deserialize)]
 pub struct TraceSearchParams {
     pub q: String,
+    #[serde(default)]
+    pub mode: TraceSearchMode,
+    /// Inclusive first day of `interaction_date`.
+    pub from: Option<NaiveDate>,
+    /// Inclusive last day of `interaction_date`.
+    pub to: Option<NaiveDate>,
     #[serde(flatten)]
     pub pagination: PaginationParams,
 }
 
+/// `lexical` ranks by full-text match, `semantic` by passage embeddings, and `hybrid` fuses
+/// both rankings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceSearchMode {
+    #[default]
+    Lexical,
+    Semantic,
+    Hybrid,
+}
+
+#[derive(Debug, Default)]
+pub struct TraceSearchFilters {
+    pub journal_ids: Vec<Uuid>,
+    pub landmark_ids: Vec<Uuid>,
+    pub high_level_project_landmark_ids: Vec<Uuid>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub trace_types: Vec<TraceType>,
+}
+
+impl TraceSearchFilters {
+    pub(super) fn trace_type_values(&self) -> Vec<String> {
+        self.trace_types
+            .iter()
+            .map(|trace_type| trace_type.to_db().to_string())
+            .collect()
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct TraceSearchItem {
     pub trace: Trace,
     pub score: f32,
     pub matched_sources: Vec<String>,
+    pub snippets: Vec<TraceSearchSnippet>,
+    pub highlights: Vec<TraceSearchHighlight>,
     pub landmarks: Vec<TraceSearchLandmark>,
     pub high_level_project_landmarks: Vec<TraceSearchLandmark>,
 }
 
+/// A passage of the trace that matched; `score` is its similarity for semantic matches.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceSearchSnippet {
+    pub chunk_index: i32,
+    pub text: String,
+    pub score: Option<f32>,
+}
+
+/// A `ts_headline` fragment of the trace content; `matches` are character offsets in `text`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceSearchHighlight {
+    pub text: String,
+    pub matches: Vec<TraceSearchMatch>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TraceSearchMatch {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraceSearchResponse {
+    #[serde(flatten)]
+    pub page: PaginatedResponse<TraceSearchItem>,
+    /// Mode the results were ranked with; `lexical` when the query could not be embedded.
+    pub mode: TraceSearchMode,
+    pub facets: TraceSearchFacets,
+}
+
+/// Counts over every trace matching the search and its filters, not only the returned page.
+#[derive(Debug, Default, Serialize)]
+pub struct TraceSearchFacets {
+    pub journals: Vec<TraceSearchJournalFacet>,
+    pub trace_types: Vec<TraceSearchTraceTypeFacet>,
+    /// One range per month with matches, newest first; bounds are inclusive like `from`/`to`.
+    pub date_ranges: Vec<TraceSearchDateRangeFacet>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraceSearchJournalFacet {
+    pub journal_id: Uuid,
+    pub title: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraceSearchTraceTypeFacet {
+    pub trace_type: TraceType,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraceSearchDateRangeFacet {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub count: i64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct TraceSearchLandmark {
     pub id: Uuid,
@@ -41,7 +243,33 @@ pub struct TraceSearchLandmark {
 }
 
 #[derive(QueryableByName)]
-struct CountRow {
+struct TraceIdRow {
+    #[diesel(sql_type = SqlUuid)]
+    trace_id: Uuid,
+}
+
+#[derive(QueryableByName)]
+struct JournalFacetRow {
+    #[diesel(sql_type = SqlUuid)]
+    journal_id: Uuid,
+    #[diesel(sql_type = Text)]
+    title: String,
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct TraceTypeFacetRow {
+    #[diesel(sql_type = Text)]
+    trace_type: String,
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct MonthFacetRow {
+    #[diesel(sql_type = Date)]
+    month: NaiveDate,
     #[diesel(sql_type = BigInt)]
     count: i64,
 }
@@ -56,6 +284,29 @@ struct TraceSearchRow {
     matched_sources: String,
 }
 
+struct SearchHit {
+    trace_id: Uuid,
+    score: f32,
+    matched_sources: Vec<String>,
+    snippets: Vec<TraceSearchSnippet>,
+}
+
+impl From<TraceSearchRow> for SearchHit {
+    fn from(row: TraceSearchRow) -> Self {
+        SearchHit {
+            trace_id: row.trace_id,
+            score: row.score,
+            matched_sources: row
+                .matched_sources
+                .split(',')
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+                .collect(),
+            snippets: Vec::new(),
+        }
+    }
+}
+
 #[derive(QueryableByName)]
 struct TraceSearchLandmarkRow {
     #[diesel(sql_type = SqlUuid)]
@@ -78,6 +329,31 @@ struct TraceSearchLandmarkRow {
 }
 
 impl TraceSearchDocument {
+    /// Refreshes up to `batch_size` documents still indexed with the legacy configuration, so
+    /// they get their detected language. Returns how many were refreshed; callers repeat until
+    /// it returns 0.
+    pub fn reindex_legacy_documents(batch_size: i64, pool: &DbPool) -> Result<usize, PpdcError> {
+        let trace_ids = {
+            let mut conn = pool.get()?;
+            sql_query(
+                r#"
+                SELECT trace_id
+                FROM trace_search_documents
+                WHERE search_config = $1
+                ORDER BY refreshed_at ASC
+                LIMIT $2
+                "#,
+            )
+            .bind::<Text, _>(LEGACY_SEARCH_CONFIG)
+            .bind::<BigInt, _>(batch_size)
+            .load::<TraceIdRow>(&mut conn)?
+        };
+        for row in &trace_ids {
+            Self::refresh_for_trace(row.trace_id, pool)?;
+        }
+        Ok(trace_ids.len())
+    }
+
     pub fn refresh_for_trace(trace_id: Uuid, pool: &DbPool) -> Result<(), PpdcError> {
         let trace = Trace::find_full_trace(trace_id, pool)?;
         let mut conn = pool.get()?;
@@ -86,6 +362,7 @@ impl TraceSearchDocument {
             diesel::sql_query("DELETE FROM trace_search_documents WHERE trace_id = $1")
                 .bind::<SqlUuid, _>(trace_id)
                 .execute(&mut conn)?;
+            semantic::delete_chunks(trace_id, &mut conn)?;
             return Ok(());
         }
 
@@ -93,9 +370,17 @@ impl TraceSearchDocument {
             diesel::sql_query("DELETE FROM trace_search_documents WHERE trace_id = $1")
                 .bind::<SqlUuid, _>(trace_id)
                 .execute(&mut conn)?;
+            semantic::delete_chunks(trace_id, &mut conn)?;
             return Ok(());
         };
 
+        semantic::refresh_chunks(&trace, &mut conn, pool)?;
+        let language = if trace.is_encrypted {
+            SearchLanguage::Unknown
+        } else {
+            SearchLanguage::detect(&format!("{}\n{}", trace.title, trace.content))
+        };
+
         sql_query(
             r#"
             WITH mirror_parts AS (
@@ -199,6 +484,7 @@ impl TraceSearchDocument {
                     $4::timestamp AS interaction_date,
                     $5::text AS title,
                     $6::text AS content,
+                    $7::regconfig AS search_config,
                     mp.mirror_text,
                     mp.tag_text,
                     ep.element_text,
@@ -217,7 +503,7 @@ impl TraceSearchDocument {
                 journal_id,
                 interaction_date,
                 title,
-                content,
+                content_vector,
                 mirror_text,
                 tag_text,
                 element_text,
@@ -225,6 +511,7 @@ impl TraceSearchDocument {
                 landmark_text,
                 high_level_project_landmark_text,
                 search_vector,
+                search_config,
                 refreshed_at
             )
             SELECT
@@ -233,21 +520,22 @@ impl TraceSearchDocument {
                 journal_id,
                 interaction_date,
                 title,
-                content,
+                to_tsvector(search_config, content),
                 mirror_text,
                 tag_text,
                 element_text,
                 reference_text,
                 landmark_text,
                 high_level_project_landmark_text,
-                setweight(to_tsvector('simple', title), 'A') ||
-                setweight(to_tsvector('simple', content), 'A') ||
-                setweight(to_tsvector('simple', tag_text), 'A') ||
-                setweight(to_tsvector('simple', mirror_text), 'B') ||
-                setweight(to_tsvector('simple', element_text), 'C') ||
-                setweight(to_tsvector('simple', reference_text), 'C') ||
-                setweight(to_tsvector('simple', landmark_text), 'C') ||
-                setweight(to_tsvector('simple', high_level_project_landmark_text), 'C'),
+                setweight(to_tsvector(search_config, title), 'A') ||
+                setweight(to_tsvector(search_config, content), 'A') ||
+                setweight(to_tsvector(search_config, tag_text), 'A') ||
+                setweight(to_tsvector(search_config, mirror_text), 'B') ||
+                setweight(to_tsvector(search_config, element_text), 'C') ||
+                setweight(to_tsvector(search_config, reference_text), 'C') ||
+                setweight(to_tsvector(search_config, landmark_text), 'C') ||
+                setweight(to_tsvector(search_config, high_level_project_landmark_text), 'C'),
+                search_config::text,
                 NOW()
             FROM doc
             ON CONFLICT (trace_id) DO UPDATE
@@ -255,7 +543,7 @@ impl TraceSearchDocument {
                 journal_id = EXCLUDED.journal_id,
                 interaction_date = EXCLUDED.interaction_date,
                 title = EXCLUDED.title,
-                content = EXCLUDED.content,
+                content_vector = EXCLUDED.content_vector,
                 mirror_text = EXCLUDED.mirror_text,
                 tag_text = EXCLUDED.tag_text,
                 element_text = EXCLUDED.element_text,
@@ -263,6 +551,7 @@ impl TraceSearchDocument {
                 landmark_text = EXCLUDED.landmark_text,
                 high_level_project_landmark_text = EXCLUDED.high_level_project_landmark_text,
                 search_vector = EXCLUDED.search_vector,
+                search_config = EXCLUDED.search_config,
                 refreshed_at = NOW()
             "#,
         )
@@ -272,21 +561,23 @@ impl TraceSearchDocument {
         .bind::<Timestamp, _>(trace.interaction_date)
         .bind::<Text, _>(trace.title)
         .bind::<Text, _>(trace.content)
+        .bind::<Text, _>(language.text_search_config())
         .execute(&mut conn)?;
 
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn search_for_user(
         user_id: Uuid,
         query: &str,
-        journal_ids: &[Uuid],
-        landmark_ids: &[Uuid],
-        high_level_project_landmark_ids: &[Uuid],
+        mode: TraceSearchMode,
+        semantic_query: Option<&SemanticQuery>,
+        filters: &TraceSearchFilters,
         offset: i64,
         limit: i64,
         pool: &DbPool,
-    ) -> Result<(Vec<TraceSearchItem>, i64), PpdcError> {
+    ) -> Result<(Vec<TraceSearchItem>, i64, TraceSearchFacets), PpdcError> {
         let query = query.trim();
         if query.is_empty() {
             return Err(PpdcError::new(
@@ -295,187 +586,316 @@ impl TraceSearchDocument {
                 "q is required".to_string(),
             ));
         }
+        let semantic_query = match (mode, semantic_query) {
+            (TraceSearchMode::Lexical, _) => None,
+            (_, Some(semantic_query)) => Some(semantic_query),
+            (_, None) => {
+                return Err(PpdcError::new(
+                    400,
+                    ErrorType::ApiError,
+                    "Semantic search needs an embedded query".to_string(),
+                ))
+            }
+        };
 
+        let (hits, matched_ids) = match semantic_query {
+            None => {
+                let matched_ids = Self::find_lexical_ids(user_id, query, filters, pool)?;
+                let hits = Self::find_lexical(user_id, query, filters, offset, limit, pool)?
+                    .into_iter()
+                    .map(SearchHit::from)
+                    .collect::<Vec<_>>();
+                (hits, matched_ids)
+            }
+            Some(semantic_query) if mode == TraceSearchMode::Semantic => {
+                let semantic =
+                    semantic::find_semantic_hits(user_id, semantic_query, filters, pool)?;
+                let matched_ids = semantic.iter().map(|hit| hit.trace_id).collect();
+                let hits = semantic
+                    .into_iter()
+                    .skip(offset as usize)
+                    .take(limit as usize)
+                    .map(|hit| SearchHit {
+                        trace_id: hit.trace_id,
+                        score: hit.score,
+                        matched_sources: vec![SEMANTIC_SOURCE.to_string()],
+                        snippets: hit.snippets,
+                    })
+                    .collect::<Vec<_>>();
+                (hits, matched_ids)
+            }
+            Some(semantic_query) => {
+                let lexical = Self::find_lexical(
+                    user_id,
+                    query,
+                    filters,
+                    0,
+                    HYBRID_LEXICAL_CANDIDATES,
+                    pool,
+                )?;
+                let semantic =
+                    semantic::find_semantic_hits(user_id, semantic_query, filters, pool)?;
+                let fused = semantic::reciprocal_rank_fusion(&[
+                    lexical.iter().map(|row| row.trace_id).collect(),
+                    semantic.iter().map(|hit| hit.trace_id).collect(),
+                ]);
+                let matched_ids = fused.iter().map(|(trace_id, _)| *trace_id).collect();
+                let mut lexical = lexical
+                    .into_iter()
+                    .map(|row| (row.trace_id, SearchHit::from(row)))
+                    .collect::<HashMap<_, _>>();
+                let mut semantic = semantic
+                    .into_iter()
+                    .map(|hit| (hit.trace_id, hit))
+                    .collect::<HashMap<_, _>>();
+                let hits = fused
+                    .into_iter()
+                    .skip(offset as usize)
+                    .take(limit as usize)
+                    .map(|(trace_id, score)| {
+                        let mut hit = lexical.remove(&trace_id).unwrap_or(SearchHit {
+                            trace_id,
+                            score,
+                            matched_sources: Vec::new(),
+                            snippets: Vec::new(),
+                        });
+                        hit.score = score;
+                        if let Some(semantic_hit) = semantic.remove(&trace_id) {
+                            hit.matched_sources.push(SEMANTIC_SOURCE.to_string());
+                            hit.snippets = semantic_hit.snippets;
+                        }
+                        hit
+                    })
+                    .collect::<Vec<_>>();
+                (hits, matched_ids)
+            }
+        };
+
+        let items = Self::hydrate_hits(user_id, query, hits, pool)?;
+        let facets = Self::find_facets(&matched_ids, pool)?;
+        Ok((items, matched_ids.len() as i64, facets))
+    }
+
+    fn find_lexical_ids(
+        user_id: Uuid,
+        query: &str,
+        filters: &TraceSearchFilters,
+        pool: &DbPool,
+    ) -> Result<Vec<Uuid>, PpdcError> {
         let mut conn = pool.get()?;
-        let total = sql_query(
+        let rows = sql_query(format!(
             r#"
-            WITH q AS (SELECT websearch_to_tsquery('simple', $2) AS query)
-            SELECT COUNT(*)::bigint AS count
-            FROM trace_search_documents tsd, q
+            SELECT tsd.trace_id
+            FROM trace_search_documents tsd
             WHERE tsd.user_id = $1
-              AND (cardinality($3::uuid[]) = 0 OR tsd.journal_id = ANY($3))
-              AND tsd.search_vector @@ q.query
-              AND (
-                cardinality($4::uuid[]) = 0
-                OR EXISTS (
-                    SELECT 1
-                    FROM "references" r
-                    INNER JOIN trace_mirrors tm
-                      ON tm.id = r.trace_mirror_id
-                    INNER JOIN landscape_analyses la
-                      ON la.id = r.landscape_analysis_id
-                     AND la.landscape_analysis_type = 'TRACE_INCREMENTAL'
-                    INNER JOIN users u
-                      ON u.id = $1
-                    INNER JOIN lens_analysis_scopes las
-                      ON las.landscape_analysis_id = r.landscape_analysis_id
-                     AND las.lens_id = u.current_lens_id
-                    INNER JOIN landmarks l
-                      ON l.id = r.landmark_id
-                    WHERE tm.trace_id = tsd.trace_id
-                      AND r.landmark_id = ANY($4)
-                      AND l.landmark_type <> 'HIGH_LEVEL_PROJECT'
-                )
-              )
-              AND (
-                cardinality($5::uuid[]) = 0
-                OR EXISTS (
-                    SELECT 1
-                    FROM "references" r
-                    INNER JOIN trace_mirrors tm
-                      ON tm.id = r.trace_mirror_id
-                    INNER JOIN landscape_analyses la
-                      ON la.id = r.landscape_analysis_id
-                     AND la.landscape_analysis_type = 'TRACE_INCREMENTAL'
-                    INNER JOIN users u
-                      ON u.id = $1
-                    INNER JOIN lens_analysis_scopes las
-                      ON las.landscape_analysis_id = r.landscape_analysis_id
-                     AND las.lens_id = u.current_lens_id
-                    INNER JOIN landmarks l
-                      ON l.id = r.landmark_id
-                    WHERE tm.trace_id = tsd.trace_id
-                      AND r.landmark_id = ANY($5)
-                      AND l.landmark_type = 'HIGH_LEVEL_PROJECT'
-                )
-              )
+              AND {matches}
+              {filters}
             "#,
-        )
+            matches = lexical_match_sql(),
+            filters = TRACE_SEARCH_FILTER_SQL
+        ))
         .bind::<SqlUuid, _>(user_id)
         .bind::<Text, _>(query)
-        .bind::<Array<SqlUuid>, _>(journal_ids)
-        .bind::<Array<SqlUuid>, _>(landmark_ids)
-        .bind::<Array<SqlUuid>, _>(high_level_project_landmark_ids)
-        .get_result::<CountRow>(&mut conn)?
-        .count;
+        .bind::<Array<SqlUuid>, _>(&filters.journal_ids)
+        .bind::<Array<SqlUuid>, _>(&filters.landmark_ids)
+        .bind::<Array<SqlUuid>, _>(&filters.high_level_project_landmark_ids)
+        .bind::<Nullable<Date>, _>(filters.from)
+        .bind::<Nullable<Date>, _>(filters.to)
+        .bind::<Array<Text>, _>(filters.trace_type_values())
+        .load::<TraceIdRow>(&mut conn)?;
+        Ok(rows.into_iter().map(|row| row.trace_id).collect())
+    }
 
-        let rows = sql_query(
+    fn find_lexical(
+        user_id: Uuid,
+        query: &str,
+        filters: &TraceSearchFilters,
+        offset: i64,
+        limit: i64,
+        pool: &DbPool,
+    ) -> Result<Vec<TraceSearchRow>, PpdcError> {
+        let mut conn = pool.get()?;
+        let rows = sql_query(format!(
             r#"
-            WITH q AS (SELECT websearch_to_tsquery('simple', $2) AS query)
             SELECT
                 tsd.trace_id,
                 ts_rank_cd(tsd.search_vector, q.query)::real AS score,
                 ARRAY_TO_STRING(
                     ARRAY_REMOVE(ARRAY[
-                        CASE WHEN setweight(to_tsvector('simple', tsd.title), 'A') @@ q.query
-                               OR setweight(to_tsvector('simple', tsd.content), 'A') @@ q.query
+                        CASE WHEN setweight(to_tsvector(tsd.search_config::regconfig, tsd.title), 'A') @@ q.query
+                               OR tsd.content_vector @@ q.query
                              THEN 'trace' END,
-                        CASE WHEN setweight(to_tsvector('simple', tsd.tag_text), 'A') @@ q.query
+                        CASE WHEN setweight(to_tsvector(tsd.search_config::regconfig, tsd.tag_text), 'A') @@ q.query
                              THEN 'tag' END,
-                        CASE WHEN setweight(to_tsvector('simple', tsd.mirror_text), 'B') @@ q.query
+                        CASE WHEN setweight(to_tsvector(tsd.search_config::regconfig, tsd.mirror_text), 'B') @@ q.query
                              THEN 'trace_mirror' END,
-                        CASE WHEN setweight(to_tsvector('simple', tsd.element_text), 'C') @@ q.query
+                        CASE WHEN setweight(to_tsvector(tsd.search_config::regconfig, tsd.element_text), 'C') @@ q.query
                              THEN 'element' END,
-                        CASE WHEN setweight(to_tsvector('simple', tsd.reference_text), 'C') @@ q.query
+                        CASE WHEN setweight(to_tsvector(tsd.search_config::regconfig, tsd.reference_text), 'C') @@ q.query
                              THEN 'reference' END,
-                        CASE WHEN setweight(to_tsvector('simple', tsd.landmark_text), 'C') @@ q.query
+                        CASE WHEN setweight(to_tsvector(tsd.search_config::regconfig, tsd.landmark_text), 'C') @@ q.query
                              THEN 'landmark' END,
-                        CASE WHEN setweight(to_tsvector('simple', tsd.high_level_project_landmark_text), 'C') @@ q.query
+                        CASE WHEN setweight(to_tsvector(tsd.search_config::regconfig, tsd.high_level_project_landmark_text), 'C') @@ q.query
                              THEN 'high_level_project_landmark' END
                     ], NULL),
                     ','
                 ) AS matched_sources
-            FROM trace_search_documents tsd, q
+            FROM trace_search_documents tsd
+            CROSS JOIN LATERAL (
+                SELECT {query} AS query
+            ) q
             WHERE tsd.user_id = $1
-              AND (cardinality($3::uuid[]) = 0 OR tsd.journal_id = ANY($3))
-              AND tsd.search_vector @@ q.query
-              AND (
-                cardinality($4::uuid[]) = 0
-                OR EXISTS (
-                    SELECT 1
-                    FROM "references" r
-                    INNER JOIN trace_mirrors tm
-                      ON tm.id = r.trace_mirror_id
-                    INNER JOIN landscape_analyses la
-                      ON la.id = r.landscape_analysis_id
-                     AND la.landscape_analysis_type = 'TRACE_INCREMENTAL'
-                    INNER JOIN users u
-                      ON u.id = $1
-                    INNER JOIN lens_analysis_scopes las
-                      ON las.landscape_analysis_id = r.landscape_analysis_id
-                     AND las.lens_id = u.current_lens_id
-                    INNER JOIN landmarks l
-                      ON l.id = r.landmark_id
-                    WHERE tm.trace_id = tsd.trace_id
-                      AND r.landmark_id = ANY($4)
-                      AND l.landmark_type <> 'HIGH_LEVEL_PROJECT'
-                )
-              )
-              AND (
-                cardinality($5::uuid[]) = 0
-                OR EXISTS (
-                    SELECT 1
-                    FROM "references" r
-                    INNER JOIN trace_mirrors tm
-                      ON tm.id = r.trace_mirror_id
-                    INNER JOIN landscape_analyses la
-                      ON la.id = r.landscape_analysis_id
-                     AND la.landscape_analysis_type = 'TRACE_INCREMENTAL'
-                    INNER JOIN users u
-                      ON u.id = $1
-                    INNER JOIN lens_analysis_scopes las
-                      ON las.landscape_analysis_id = r.landscape_analysis_id
-                     AND las.lens_id = u.current_lens_id
-                    INNER JOIN landmarks l
-                      ON l.id = r.landmark_id
-                    WHERE tm.trace_id = tsd.trace_id
-                      AND r.landmark_id = ANY($5)
-                      AND l.landmark_type = 'HIGH_LEVEL_PROJECT'
-                )
-              )
+              AND {matches}
+              {filters}
             ORDER BY score DESC, tsd.interaction_date DESC
-            OFFSET $6
-            LIMIT $7
+            OFFSET $9
+            LIMIT $10
             "#,
-        )
+            query = lexical_query_sql(),
+            matches = lexical_match_sql(),
+            filters = TRACE_SEARCH_FILTER_SQL
+        ))
         .bind::<SqlUuid, _>(user_id)
         .bind::<Text, _>(query)
-        .bind::<Array<SqlUuid>, _>(journal_ids)
-        .bind::<Array<SqlUuid>, _>(landmark_ids)
-        .bind::<Array<SqlUuid>, _>(high_level_project_landmark_ids)
+        .bind::<Array<SqlUuid>, _>(&filters.journal_ids)
+        .bind::<Array<SqlUuid>, _>(&filters.landmark_ids)
+        .bind::<Array<SqlUuid>, _>(&filters.high_level_project_landmark_ids)
+        .bind::<Nullable<Date>, _>(filters.from)
+        .bind::<Nullable<Date>, _>(filters.to)
+        .bind::<Array<Text>, _>(filters.trace_type_values())
         .bind::<BigInt, _>(offset)
         .bind::<BigInt, _>(limit)
         .load::<TraceSearchRow>(&mut conn)?;
+        Ok(rows)
+    }
 
-        let trace_ids = rows.iter().map(|row| row.trace_id).collect::<Vec<_>>();
+    /// Loads the traces of a result page with their landmarks; hits found by full-text search
+    /// only get the passages containing a query term as snippets.
+    fn hydrate_hits(
+        user_id: Uuid,
+        query: &str,
+        hits: Vec<SearchHit>,
+        pool: &DbPool,
+    ) -> Result<Vec<TraceSearchItem>, PpdcError> {
+        let trace_ids = hits.iter().map(|hit| hit.trace_id).collect::<Vec<_>>();
         let landmark_context =
             Self::hydrate_ranked_landmarks_for_traces(user_id, query, &trace_ids, pool)?;
+        let lexical_trace_ids = hits
+            .iter()
+            .filter(|hit| hit.snippets.is_empty())
+            .map(|hit| hit.trace_id)
+            .collect::<Vec<_>>();
+        let mut lexical_snippets =
+            semantic::find_lexical_snippets(&lexical_trace_ids, query, pool)?;
+        let mut traces = Trace::find_full_traces(&trace_ids, pool)?
+            .into_iter()
+            .map(|trace| (trace.id, trace))
+            .collect::<HashMap<_, _>>();
+        let readable_contents = traces
+            .values()
+            .filter(|trace| !trace.is_encrypted)
+            .map(|trace| (trace.id, trace.content.clone()))
+            .collect::<Vec<_>>();
+        let mut highlights = highlight::find_highlights(query, &readable_contents, pool)?;
 
-        let items = rows
+        // A trace deleted since it was ranked is left out of the page.
+        Ok(hits
             .into_iter()
-            .map(|row| {
-                let trace = Trace::find_full_trace(row.trace_id, pool)?;
-                let context = landmark_context.get(&row.trace_id);
-                Ok(TraceSearchItem {
+            .filter_map(|hit| traces.remove(&hit.trace_id).map(|trace| (hit, trace)))
+            .map(|(hit, trace)| {
+                let context = landmark_context.get(&hit.trace_id);
+                let snippets = if hit.snippets.is_empty() {
+                    lexical_snippets.remove(&hit.trace_id).unwrap_or_default()
+                } else {
+                    hit.snippets
+                };
+                TraceSearchItem {
                     trace,
-                    score: row.score,
-                    matched_sources: row
-                        .matched_sources
-                        .split(',')
-                        .filter(|value| !value.is_empty())
-                        .map(|value| value.to_string())
-                        .collect(),
+                    score: hit.score,
+                    matched_sources: hit.matched_sources,
+                    snippets,
+                    highlights: highlights.remove(&hit.trace_id).unwrap_or_default(),
                     landmarks: context
                         .map(|context| context.landmarks.clone())
                         .unwrap_or_default(),
                     high_level_project_landmarks: context
                         .map(|context| context.high_level_project_landmarks.clone())
                         .unwrap_or_default(),
-                })
+                }
             })
-            .collect::<Result<Vec<_>, PpdcError>>()?;
+            .collect())
+    }
 
-        Ok((items, total))
+    fn find_facets(trace_ids: &[Uuid], pool: &DbPool) -> Result<TraceSearchFacets, PpdcError> {
+        if trace_ids.is_empty() {
+            return Ok(TraceSearchFacets::default());
+        }
+        let mut conn = pool.get()?;
+        let journals = sql_query(
+            r#"
+            SELECT tsd.journal_id, j.title, COUNT(*)::bigint AS count
+            FROM trace_search_documents tsd
+            INNER JOIN journals j
+              ON j.id = tsd.journal_id
+            WHERE tsd.trace_id = ANY($1)
+            GROUP BY tsd.journal_id, j.title
+            ORDER BY count DESC, j.title ASC
+            "#,
+        )
+        .bind::<Array<SqlUuid>, _>(trace_ids)
+        .load::<JournalFacetRow>(&mut conn)?;
+        let trace_types = sql_query(
+            r#"
+            SELECT t.trace_type, COUNT(*)::bigint AS count
+            FROM traces t
+            WHERE t.id = ANY($1)
+            GROUP BY t.trace_type
+            ORDER BY count DESC, t.trace_type ASC
+            "#,
+        )
+        .bind::<Array<SqlUuid>, _>(trace_ids)
+        .load::<TraceTypeFacetRow>(&mut conn)?;
+        let months = sql_query(
+            r#"
+            SELECT date_trunc('month', tsd.interaction_date)::date AS month, COUNT(*)::bigint AS count
+            FROM trace_search_documents tsd
+            WHERE tsd.trace_id = ANY($1)
+            GROUP BY month
+            ORDER BY month DESC
+            "#,
+        )
+        .bind::<Array<SqlUuid>, _>(trace_ids)
+        .load::<MonthFacetRow>(&mut conn)?;
+
+        Ok(TraceSearchFacets {
+            journals: journals
+                .into_iter()
+                .map(|row| TraceSearchJournalFacet {
+                    journal_id: row.journal_id,
+                    title: row.title,
+                    count: row.count,
+                })
+                .collect(),
+            trace_types: trace_types
+                .into_iter()
+                .map(|row| TraceSearchTraceTypeFacet {
+                    trace_type: TraceType::from_db(&row.trace_type),
+                    count: row.count,
+                })
+                .collect(),
+            date_ranges: months
+                .into_iter()
+                .map(|row| TraceSearchDateRangeFacet {
+                    from: row.month,
+                    to: row
+                        .month
+                        .checked_add_months(Months::new(1))
+                        .and_then(|next| next.pred_opt())
+                        .unwrap_or(row.month),
+                    count: row.count,
+                })
+                .collect(),
+        })
     }
 
     fn hydrate_ranked_landmarks_for_traces(
@@ -494,9 +914,6 @@ impl TraceSearchDocument {
             WITH input_trace_ids AS (
                 SELECT unnest($2::uuid[]) AS trace_id
             ),
-            q AS (
-                SELECT websearch_to_tsquery('simple', $3) AS query
-            ),
             candidates AS (
                 SELECT
                     tm.trace_id,
@@ -543,18 +960,24 @@ impl TraceSearchDocument {
                     g.subtitle,
                     g.landmark_type = 'HIGH_LEVEL_PROJECT' AS is_high_level_project,
                     (
-                        setweight(to_tsvector('simple', g.title), 'A') ||
-                        setweight(to_tsvector('simple', g.subtitle), 'B')
+                        setweight(to_tsvector(q.search_config, g.title), 'A') ||
+                        setweight(to_tsvector(q.search_config, g.subtitle), 'B')
                     ) @@ q.query AS matched_query,
                     ts_rank_cd(
-                        setweight(to_tsvector('simple', g.title), 'A') ||
-                        setweight(to_tsvector('simple', g.subtitle), 'B'),
+                        setweight(to_tsvector(q.search_config, g.title), 'A') ||
+                        setweight(to_tsvector(q.search_config, g.subtitle), 'B'),
                         q.query
                     )::real AS score,
                     g.relation_priority,
                     g.updated_at
                 FROM grouped g
-                CROSS JOIN q
+                INNER JOIN trace_search_documents tsd
+                  ON tsd.trace_id = g.trace_id
+                CROSS JOIN LATERAL (
+                    SELECT
+                        tsd.search_config::regconfig AS search_config,
+                        websearch_to_tsquery(tsd.search_config::regconfig, $3) AS query
+                ) q
             ),
             ranked AS (
                 SELECT
@@ -616,3 +1039,25 @@ struct TraceSearchLandmarkContext {
     landmarks: Vec<TraceSearchLandmark>,
     high_level_project_landmarks: Vec<TraceSearchLandmark>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lexical_match_covers_every_indexed_configuration() {
+        let matches = lexical_match_sql();
+        let query = lexical_query_sql();
+        for language in [
+            SearchLanguage::French,
+            SearchLanguage::English,
+            SearchLanguage::Unknown,
+        ] {
+            let config = language.text_search_config();
+            assert!(SEARCH_CONFIGS.contains(&config));
+            assert!(matches.contains(&format!("websearch_to_tsquery('{config}', $2)")));
+            assert!(query.contains(&format!("WHEN '{config}'")));
+        }
+        assert!(matches.contains(&format!("tsd.search_config = '{LEGACY_SEARCH_CONFIG}'")));
+    }
+}
@@ -4,10 +4,18 @@ use axum::{
 };
 
 use crate::db::DbPool;
-use crate::entities_v2::{error::PpdcError, session::Session};
+use crate::entities_v2::{
+    error::{ErrorType, PpdcError},
+    session::Session,
+};
+use crate::openai_handler::EmbeddingProviderKind;
 use crate::pagination::{parse_repeated_query_param, PaginatedResponse};
 
-use super::model::{TraceSearchDocument, TraceSearchItem, TraceSearchParams};
+use super::model::{
+    TraceSearchDocument, TraceSearchFilters, TraceSearchMode, TraceSearchParams,
+    TraceSearchResponse,
+};
+use super::semantic::SemanticQuery;
 
 #[debug_handler]
 pub async fn get_trace_search_route(
@@ -15,23 +23,68 @@ pub async fn get_trace_search_route(
     Extension(session): Extension<Session>,
     RawQuery(raw_query): RawQuery,
     Query(params): Query<TraceSearchParams>,
-) -> Result<Json<PaginatedResponse<TraceSearchItem>>, PpdcError> {
+) -> Result<Json<TraceSearchResponse>, PpdcError> {
     let user_id = session.user_id.ok_or_else(PpdcError::unauthorized)?;
     let pagination = params.pagination.validate()?;
-    let journal_ids = parse_repeated_query_param(raw_query.as_deref(), "journal_id")?;
-    let landmark_ids = parse_repeated_query_param(raw_query.as_deref(), "landmark_id")?;
-    let high_level_project_landmark_ids =
-        parse_repeated_query_param(raw_query.as_deref(), "high_level_project_landmark_id")?;
-    let (items, total) = TraceSearchDocument::search_for_user(
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from > to {
+            return Err(PpdcError::new(
+                400,
+                ErrorType::ApiError,
+                "`from` must be <= `to`".to_string(),
+            ));
+        }
+    }
+    let filters = TraceSearchFilters {
+        journal_ids: parse_repeated_query_param(raw_query.as_deref(), "journal_id")?,
+        landmark_ids: parse_repeated_query_param(raw_query.as_deref(), "landmark_id")?,
+        high_level_project_landmark_ids: parse_repeated_query_param(
+            raw_query.as_deref(),
+            "high_level_project_landmark_id",
+        )?,
+        from: params.from,
+        to: params.to,
+        trace_types: parse_repeated_query_param(raw_query.as_deref(), "trace_type")?,
+    };
+    let mut mode = params.mode;
+    let semantic_query = match mode {
+        TraceSearchMode::Lexical => None,
+        _ if params.q.trim().is_empty() => None,
+        _ => {
+            let provider = EmbeddingProviderKind::from_env().build();
+            match provider.embed(&[params.q.trim().to_string()]).await {
+                Ok(mut vectors) => Some(SemanticQuery {
+                    model: provider.model().to_string(),
+                    vector: vectors.pop().unwrap_or_default(),
+                }),
+                // Search stays available while the embedding provider is down.
+                Err(err) => {
+                    tracing::warn!(
+                        target: "trace_search",
+                        "query_embedding_failed user_id={} error={}",
+                        user_id,
+                        err
+                    );
+                    mode = TraceSearchMode::Lexical;
+                    None
+                }
+            }
+        }
+    };
+    let (items, total, facets) = TraceSearchDocument::search_for_user(
         user_id,
         &params.q,
-        &journal_ids,
-        &landmark_ids,
-        &high_level_project_landmark_ids,
+        mode,
+        semantic_query.as_ref(),
+        &filters,
         pagination.offset,
         pagination.limit,
         &pool,
     )?;
 
-    Ok(Json(PaginatedResponse::new(items, pagination, total)))
+    Ok(Json(TraceSearchResponse {
+        page: PaginatedResponse::new(items, pagination, total),
+        mode,
+        facets,
+    }))
 }
@@ -0,0 +1,550 @@
+use std::collections::HashMap;
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Array, BigInt, Date, Float, Int4, Nullable, Text, Uuid as SqlUuid};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::data_key;
+use crate::entities_v2::embedding::model::content_hash;
+use crate::entities_v2::error::PpdcError;
+use crate::entities_v2::trace::Trace;
+use crate::openai_handler::embedding_provider::EmbeddingProvider;
+
+use super::model::{TraceSearchFilters, TraceSearchSnippet, TRACE_SEARCH_FILTER_SQL};
+
+/// Passages are cut near this many characters, on paragraph then sentence boundaries.
+const CHUNK_TARGET_CHARS: usize = 800;
+/// Passages less similar than this to the query are not semantic matches.
+const SEMANTIC_MIN_SCORE: f32 = 0.25;
+/// Closest passages ranked per query; also the HNSW candidate list size.
+const SEMANTIC_CANDIDATE_PASSAGES: i64 = 200;
+/// Reciprocal-rank fusion constant; keeps the first ranks from dominating the fused order.
+const RRF_K: f32 = 60.0;
+pub(super) const MAX_SNIPPETS: usize = 3;
+
+/// A query embedded with the model the passages were embedded with.
+pub struct SemanticQuery {
+    pub model: String,
+    pub vector: Vec<f32>,
+}
+
+/// A trace ranked by its closest passages.
+pub(super) struct SemanticHit {
+    pub trace_id: Uuid,
+    pub score: f32,
+    pub snippets: Vec<TraceSearchSnippet>,
+}
+
+#[derive(QueryableByName)]
+struct ChunkHashRow {
+    #[diesel(sql_type = Int4)]
+    chunk_index: i32,
+    #[diesel(sql_type = Text)]
+    content_hash: String,
+}
+
+#[derive(QueryableByName)]
+struct PendingChunkRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+    #[diesel(sql_type = Text)]
+    title: String,
+    #[diesel(sql_type = Text)]
+    content: String,
+    #[diesel(sql_type = Text)]
+    content_hash: String,
+}
+
+#[derive(QueryableByName)]
+struct ScoredChunkRow {
+    #[diesel(sql_type = SqlUuid)]
+    trace_id: Uuid,
+    #[diesel(sql_type = Int4)]
+    chunk_index: i32,
+    #[diesel(sql_type = Text)]
+    content: String,
+    #[diesel(sql_type = Float)]
+    score: f32,
+}
+
+#[derive(QueryableByName)]
+struct ChunkContentRow {
+    #[diesel(sql_type = SqlUuid)]
+    trace_id: Uuid,
+    #[diesel(sql_type = Int4)]
+    chunk_index: i32,
+    #[diesel(sql_type = Text)]
+    content: String,
+}
+
+/// Splits trace content into passages of about `CHUNK_TARGET_CHARS` characters.
+///
+/// Paragraphs are kept together when they fit; longer ones are cut between sentences, and
+/// sentences longer than a passage between words.
+pub fn chunk_trace_text(content: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in content
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+    {
+        let mut separator = "\n\n";
+        for piece in split_paragraph(paragraph) {
+            if !current.is_empty()
+                && current.chars().count() + piece.chars().count() + separator.len()
+                    > CHUNK_TARGET_CHARS
+            {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push_str(separator);
+            }
+            current.push_str(&piece);
+            separator = " ";
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn split_paragraph(paragraph: &str) -> Vec<String> {
+    if paragraph.chars().count() <= CHUNK_TARGET_CHARS {
+        return vec![paragraph.to_string()];
+    }
+    let mut pieces = Vec::new();
+    let mut sentence = String::new();
+    for word in paragraph.split_whitespace() {
+        if !sentence.is_empty()
+            && sentence.chars().count() + word.chars().count() + 1 > CHUNK_TARGET_CHARS
+        {
+            pieces.push(std::mem::take(&mut sentence));
+        }
+        if !sentence.is_empty() {
+            sentence.push(' ');
+        }
+        sentence.push_str(word);
+        if word.ends_with(['.', '!', '?', '…']) {
+            pieces.push(std::mem::take(&mut sentence));
+        }
+    }
+    if !sentence.is_empty() {
+        pieces.push(sentence);
+    }
+    pieces
+}
+
+/// Text a passage is embedded from; the title gives short passages their context.
+fn chunk_embedding_text(title: &str, chunk: &str) -> String {
+    if title.trim().is_empty() {
+        chunk.to_string()
+    } else {
+        format!("{}\n{}", title.trim(), chunk)
+    }
+}
+
+/// Rewrites the passages of a finalized trace whose text changed and drops the extra ones.
+///
+/// Changed passages lose their vector until the embeddings backfill reaches them.
+/// Client-encrypted traces have no readable text and keep no passages.
+pub(super) fn refresh_chunks(
+    trace: &Trace,
+    conn: &mut PgConnection,
+    pool: &DbPool,
+) -> Result<(), PpdcError> {
+    if trace.is_encrypted {
+        return delete_chunks(trace.id, conn);
+    }
+    let chunks = chunk_trace_text(&trace.content);
+    let stored =
+        sql_query("SELECT chunk_index, content_hash FROM trace_search_chunks WHERE trace_id = $1")
+            .bind::<SqlUuid, _>(trace.id)
+            .load::<ChunkHashRow>(conn)?
+            .into_iter()
+            .map(|row| (row.chunk_index, row.content_hash))
+            .collect::<HashMap<_, _>>();
+
+    for (chunk_index, chunk) in chunks.iter().enumerate() {
+        let chunk_index = chunk_index as i32;
+        let hash = content_hash(&chunk_embedding_text(&trace.title, chunk));
+        if stored.get(&chunk_index) == Some(&hash) {
+            continue;
+        }
+        let content = data_key::seal_for_owner(Some(trace.user_id), chunk, pool)?;
+        sql_query(
+            r#"
+            INSERT INTO trace_search_chunks (trace_id, user_id, chunk_index, content, content_hash)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (trace_id, chunk_index) DO UPDATE
+            SET user_id = EXCLUDED.user_id,
+                content = EXCLUDED.content,
+                content_hash = EXCLUDED.content_hash,
+                model = NULL,
+                vector = NULL,
+                updated_at = NOW()
+            "#,
+        )
+        .bind::<SqlUuid, _>(trace.id)
+        .bind::<SqlUuid, _>(trace.user_id)
+        .bind::<Int4, _>(chunk_index)
+        .bind::<Text, _>(content)
+        .bind::<Text, _>(hash)
+        .execute(conn)?;
+    }
+
+    sql_query("DELETE FROM trace_search_chunks WHERE trace_id = $1 AND chunk_index >= $2")
+        .bind::<SqlUuid, _>(trace.id)
+        .bind::<Int4, _>(chunks.len() as i32)
+        .execute(conn)?;
+    Ok(())
+}
+
+pub(super) fn delete_chunks(trace_id: Uuid, conn: &mut PgConnection) -> Result<(), PpdcError> {
+    sql_query("DELETE FROM trace_search_chunks WHERE trace_id = $1")
+        .bind::<SqlUuid, _>(trace_id)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Embeds up to `batch_size` trace passages that have no vector for the provider's model.
+/// Returns how many were embedded; callers repeat until it returns 0.
+pub async fn backfill_chunk_embeddings(
+    provider: &dyn EmbeddingProvider,
+    batch_size: i64,
+    pool: &DbPool,
+) -> Result<usize, PpdcError> {
+    let rows = {
+        let mut conn = pool.get()?;
+        sql_query(
+            r#"
+            SELECT c.id, t.title, c.content, c.content_hash
+            FROM trace_search_chunks c
+            INNER JOIN traces t ON t.id = c.trace_id
+            WHERE c.vector IS NULL OR c.model IS DISTINCT FROM $1
+            ORDER BY c.updated_at ASC
+            LIMIT $2
+            "#,
+        )
+        .bind::<Text, _>(provider.model())
+        .bind::<BigInt, _>(batch_size)
+        .load::<PendingChunkRow>(&mut conn)?
+    };
+    if rows.is_empty() {
+        return Ok(0);
+    }
+    let texts = rows
+        .iter()
+        .map(|row| {
+            Ok(chunk_embedding_text(
+                &row.title,
+                &data_key::open(row.content.clone())?,
+            ))
+        })
+        .collect::<Result<Vec<_>, PpdcError>>()?;
+    let vectors = provider.embed(&texts).await?;
+
+    // Passages rewritten while embedding keep their reset vector for the next batch.
+    let mut conn = pool.get()?;
+    let mut embedded = 0;
+    for (row, vector) in rows.iter().zip(vectors) {
+        embedded += sql_query(
+            "UPDATE trace_search_chunks SET model = $2, vector = CAST($3 AS vector) WHERE id = $1 AND content_hash = $4",
+        )
+        .bind::<SqlUuid, _>(row.id)
+        .bind::<Text, _>(provider.model())
+        .bind::<Array<Float>, _>(vector)
+        .bind::<Text, _>(&row.content_hash)
+        .execute(&mut conn)?;
+    }
+    Ok(embedded)
+}
+
+/// Traces of the user whose passages are close to the query, best first.
+///
+/// Only the `SEMANTIC_CANDIDATE_PASSAGES` closest passages are ranked, through the HNSW index
+/// when the model has its size. Each trace scores as its closest passage and keeps up to
+/// `MAX_SNIPPETS` of them.
+pub(super) fn find_semantic_hits(
+    user_id: Uuid,
+    query: &SemanticQuery,
+    filters: &TraceSearchFilters,
+    pool: &DbPool,
+) -> Result<Vec<SemanticHit>, PpdcError> {
+    if query.vector.is_empty() {
+        return Ok(Vec::new());
+    }
+    // The size is a number, and the index only matches a literal `vector(n)` cast.
+    let distance = format!(
+        "CAST(c.vector AS vector({dims})) <=> CAST(CAST($9 AS vector) AS vector({dims}))",
+        dims = query.vector.len()
+    );
+    let mut conn = pool.get()?;
+    let rows = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        sql_query(format!(
+            "SET LOCAL hnsw.ef_search = {}",
+            SEMANTIC_CANDIDATE_PASSAGES
+        ))
+        .execute(conn)?;
+        sql_query(format!(
+            r#"
+            SELECT c.trace_id, c.chunk_index, c.content, CAST(1 - ({distance}) AS REAL) AS score
+            FROM trace_search_chunks c
+            INNER JOIN trace_search_documents tsd
+              ON tsd.trace_id = c.trace_id
+            WHERE c.user_id = $1
+              AND tsd.user_id = $1
+              AND c.model = $2
+              AND c.vector IS NOT NULL
+              AND vector_dims(c.vector) = {dims}
+              {filters}
+              AND {distance} <= $10
+            ORDER BY {distance}
+            LIMIT $11
+            "#,
+            distance = distance,
+            dims = query.vector.len(),
+            filters = TRACE_SEARCH_FILTER_SQL
+        ))
+        .bind::<SqlUuid, _>(user_id)
+        .bind::<Text, _>(&query.model)
+        .bind::<Array<SqlUuid>, _>(&filters.journal_ids)
+        .bind::<Array<SqlUuid>, _>(&filters.landmark_ids)
+        .bind::<Array<SqlUuid>, _>(&filters.high_level_project_landmark_ids)
+        .bind::<Nullable<Date>, _>(filters.from)
+        .bind::<Nullable<Date>, _>(filters.to)
+        .bind::<Array<Text>, _>(filters.trace_type_values())
+        .bind::<Array<Float>, _>(&query.vector)
+        .bind::<Float, _>(1.0 - SEMANTIC_MIN_SCORE)
+        .bind::<BigInt, _>(SEMANTIC_CANDIDATE_PASSAGES)
+        .load::<ScoredChunkRow>(conn)
+    })?;
+
+    // Rows come closest first, so each trace keeps its best passages in order.
+    let mut order = Vec::new();
+    let mut by_trace: HashMap<Uuid, Vec<ScoredChunkRow>> = HashMap::new();
+    for row in rows {
+        let chunks = by_trace.entry(row.trace_id).or_insert_with(|| {
+            order.push(row.trace_id);
+            Vec::new()
+        });
+        if chunks.len() < MAX_SNIPPETS {
+            chunks.push(row);
+        }
+    }
+
+    order
+        .into_iter()
+        .map(|trace_id| {
+            let snippets = by_trace
+                .remove(&trace_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|row| {
+                    Ok(TraceSearchSnippet {
+                        chunk_index: row.chunk_index,
+                        text: data_key::open(row.content)?,
+                        score: Some(row.score),
+                    })
+                })
+                .collect::<Result<Vec<_>, PpdcError>>()?;
+            Ok(SemanticHit {
+                trace_id,
+                score: snippets[0].score.unwrap_or_default(),
+                snippets,
+            })
+        })
+        .collect()
+}
+
+/// Passages of each trace containing a query term, for results found by full-text search.
+pub(super) fn find_lexical_snippets(
+    trace_ids: &[Uuid],
+    query: &str,
+    pool: &DbPool,
+) -> Result<HashMap<Uuid, Vec<TraceSearchSnippet>>, PpdcError> {
+    let terms = query_terms(query);
+    if trace_ids.is_empty() || terms.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let mut conn = pool.get()?;
+    let rows = sql_query(
+        r#"
+        SELECT trace_id, chunk_index, content
+        FROM trace_search_chunks
+        WHERE trace_id = ANY($1)
+        ORDER BY trace_id, chunk_index
+        "#,
+    )
+    .bind::<Array<SqlUuid>, _>(trace_ids)
+    .load::<ChunkContentRow>(&mut conn)?;
+
+    let mut snippets: HashMap<Uuid, Vec<TraceSearchSnippet>> = HashMap::new();
+    for row in rows {
+        let trace_snippets = snippets.entry(row.trace_id).or_default();
+        if trace_snippets.len() >= MAX_SNIPPETS {
+            continue;
+        }
+        let text = data_key::open(row.content)?;
+        if matches_any_term(&text, &terms) {
+            trace_snippets.push(TraceSearchSnippet {
+                chunk_index: row.chunk_index,
+                text,
+                score: None,
+            });
+        }
+    }
+    Ok(snippets)
+}
+
+/// Fuses rankings by summing `1 / (RRF_K + rank)` over the rankings an id appears in.
+///
+/// Ties keep the order in which ids were first seen.
+pub(super) fn reciprocal_rank_fusion(rankings: &[Vec<Uuid>]) -> Vec<(Uuid, f32)> {
+    let mut order = Vec::new();
+    let mut scores: HashMap<Uuid, f32> = HashMap::new();
+    for ranking in rankings {
+        for (rank, id) in ranking.iter().enumerate() {
+            let score = scores.entry(*id).or_insert_with(|| {
+                order.push(*id);
+                0.0
+            });
+            *score += 1.0 / (RRF_K + rank as f32 + 1.0);
+        }
+    }
+    let mut fused = order
+        .into_iter()
+        .map(|id| (id, scores[&id]))
+        .collect::<Vec<_>>();
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1));
+    fused
+}
+
+/// Words of a web-search query, lowercased and without accents. Operators and the words or
+/// phrases negated with `-` are dropped, since passages containing them are not matches.
+fn query_terms(query: &str) -> Vec<String> {
+    let mut kept = String::new();
+    let mut chars = query.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            kept.push(' ');
+            continue;
+        }
+        let negated = c == '-';
+        let quoted = if negated {
+            chars.next_if_eq(&'"').is_some()
+        } else {
+            c == '"'
+        };
+        let mut segment = String::new();
+        if !negated && !quoted {
+            segment.push(c);
+        }
+        while let Some(next) = chars.next_if(|next| {
+            if quoted {
+                *next != '"'
+            } else {
+                !next.is_whitespace()
+            }
+        }) {
+            segment.push(next);
+        }
+        if quoted {
+            chars.next();
+        }
+        if !negated {
+            kept.push_str(&segment);
+            kept.push(' ');
+        }
+    }
+    fold_for_match(&kept)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.chars().count() > 1 && *term != "or")
+        .map(str::to_string)
+        .collect()
+}
+
+fn matches_any_term(text: &str, terms: &[String]) -> bool {
+    let folded = fold_for_match(text);
+    terms.iter().any(|term| folded.contains(term.as_str()))
+}
+
+/// Lowercases and strips the accents of Latin letters, so "Été" matches "ete".
+fn fold_for_match(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => folded.push('a'),
+            'ç' => folded.push('c'),
+            'è' | 'é' | 'ê' | 'ë' => folded.push('e'),
+            'ì' | 'í' | 'î' | 'ï' => folded.push('i'),
+            'ñ' => folded.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' => folded.push('o'),
+            'ù' | 'ú' | 'û' | 'ü' => folded.push('u'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'œ' => folded.push_str("oe"),
+            'æ' => folded.push_str("ae"),
+            _ => folded.push(c),
+        }
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_keep_paragraphs_and_cut_long_ones_between_sentences() {
+        assert!(chunk_trace_text("  \n\n ").is_empty());
+        assert_eq!(
+            chunk_trace_text("Premier paragraphe.\n\nSecond paragraphe."),
+            vec!["Premier paragraphe.\n\nSecond paragraphe.".to_string()]
+        );
+
+        let sentence = "Une phrase assez longue sur le projet de cartographie du quartier. ";
+        let long_paragraph = sentence.repeat(30);
+        let chunks = chunk_trace_text(&long_paragraph);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.chars().count() <= CHUNK_TARGET_CHARS);
+            assert!(chunk.ends_with('.'));
+        }
+
+        let run_on = "mot ".repeat(500);
+        assert!(chunk_trace_text(&run_on)
+            .iter()
+            .all(|chunk| chunk.chars().count() <= CHUNK_TARGET_CHARS));
+    }
+
+    #[test]
+    fn fusion_favors_ids_ranked_by_both_lists() {
+        let [a, b, c] = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let fused = reciprocal_rank_fusion(&[vec![a, b], vec![c, b]]);
+        assert_eq!(fused[0].0, b);
+        assert_eq!(fused[1].0, a);
+        assert_eq!(fused[2].0, c);
+        assert!((fused[0].1 - 2.0 / 62.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn terms_match_without_accents() {
+        let terms = query_terms("\"réunion d'équipe\" OR Été");
+        assert_eq!(terms, vec!["reunion", "equipe", "ete"]);
+        assert!(matches_any_term("La Réunion a eu lieu.", &terms));
+        assert!(!matches_any_term("Rien à voir.", &terms));
+    }
+
+    #[test]
+    fn negated_terms_and_phrases_are_dropped() {
+        assert_eq!(
+            query_terms("atlas -Été -\"hors sujet\" bien-être"),
+            vec!["atlas", "bien", "etre"]
+        );
+        assert!(query_terms("-seul").is_empty());
+    }
+}
@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::db::DbPool;
+use crate::entities_v2::data_key;
 use crate::entities_v2::error::{ErrorType, PpdcError};
 use crate::entities_v2::user::{User, UserPublicResponse};
 use crate::schema::{messages, users};
@@ -60,7 +61,7 @@ struct UnreadRow {
     unread: i64,
 }
 
-fn tuple_to_message(row: MessageTuple) -> Message {
+fn tuple_to_message(row: MessageTuple) -> Result<Message, PpdcError> {
     let (
         id,
         sender_user_id,
@@ -92,7 +93,7 @@ fn tuple_to_message(row: MessageTuple) -> Message {
         .as_deref()
         .and_then(|json| serde_json::from_str::<MessageMetadata>(json).ok());
 
-    Message {
+    Ok(Message {
         id,
         sender_user_id,
         recipient_user_id,
@@ -103,14 +104,14 @@ fn tuple_to_message(row: MessageTuple) -> Message {
         message_type: MessageType::from_db(&message_type_raw),
         processing_state: MessageProcessingState::from_db(&processing_state_raw),
         title,
-        content,
+        content: data_key::open(content)?,
         attachment_type,
         attachment,
         metadata,
         seen_at,
         created_at,
         updated_at,
-    }
+    })
 }
 
 impl Message {
@@ -157,7 +158,7 @@ impl Message {
             ))
             .first::<MessageTuple>(&mut conn)
             .optional()?;
-        row.map(tuple_to_message).ok_or_else(|| {
+        row.map(tuple_to_message).transpose()?.ok_or_else(|| {
             PpdcError::new(404, ErrorType::ApiError, "Message not found".to_string())
         })
     }
@@ -251,9 +252,8 @@ impl Message {
             ))
             .load::<MessageTuple>(&mut conn)?
             .into_iter()
-            .map(tuple_to_message)
-            .map(|message| (message.id, message))
-            .collect();
+            .map(|row| tuple_to_message(row).map(|message| (message.id, message)))
+            .collect::<Result<_, PpdcError>>()?;
 
         let partner_ids: Vec<Uuid> = head_rows.iter().map(|row| row.partner_id).collect();
         let mut partner_map: HashMap<Uuid, UserPublicResponse> = users::table
@@ -368,9 +368,8 @@ impl Message {
             ))
             .load::<MessageTuple>(conn)?
             .into_iter()
-            .map(tuple_to_message)
-            .map(|message| (message.id, message))
-            .collect();
+            .map(|row| tuple_to_message(row).map(|message| (message.id, message)))
+            .collect::<Result<_, PpdcError>>()?;
 
         let partner_ids: Vec<Uuid> = head_rows.iter().map(|row| row.partner_id).collect();
         let mut partner_map: HashMap<Uuid, UserPublicResponse> = users::table
@@ -459,7 +458,12 @@ impl Message {
             .offset(offset)
             .limit(limit.max(1))
             .load::<MessageTuple>(&mut conn)?;
-        Ok((rows.into_iter().map(tuple_to_message).collect(), total))
+        Ok((
+            rows.into_iter()
+                .map(tuple_to_message)
+                .collect::<Result<_, _>>()?,
+            total,
+        ))
     }
 
     pub fn find_for_participant(
@@ -568,7 +572,12 @@ impl Message {
             .offset(offset)
             .limit(limit.max(1))
             .load::<MessageTuple>(&mut conn)?;
-        Ok((rows.into_iter().map(tuple_to_message).collect(), total))
+        Ok((
+            rows.into_iter()
+                .map(tuple_to_message)
+                .collect::<Result<_, _>>()?,
+            total,
+        ))
     }
 
     pub fn find_for_trace_conversation(
@@ -630,7 +639,12 @@ impl Message {
             .offset(offset)
             .limit(limit.max(1))
             .load::<MessageTuple>(&mut conn)?;
-        Ok((rows.into_iter().map(tuple_to_message).collect(), total))
+        Ok((
+            rows.into_iter()
+                .map(tuple_to_message)
+                .collect::<Result<_, _>>()?,
+            total,
+        ))
     }
 
     pub fn find_for_trace_context_conversation_paginated(
@@ -713,7 +727,12 @@ impl Message {
             .offset(offset)
             .limit(limit.max(1))
             .load::<MessageTuple>(&mut conn)?;
-        Ok((rows.into_iter().map(tuple_to_message).collect(), total))
+        Ok((
+            rows.into_iter()
+                .map(tuple_to_message)
+                .collect::<Result<_, _>>()?,
+            total,
+        ))
     }
 
     pub fn find_for_post_conversation_paginated(
@@ -764,7 +783,12 @@ impl Message {
             .offset(offset)
             .limit(limit.max(1))
             .load::<MessageTuple>(&mut conn)?;
-        Ok((rows.into_iter().map(tuple_to_message).collect(), total))
+        Ok((
+            rows.into_iter()
+                .map(tuple_to_message)
+                .collect::<Result<_, _>>()?,
+            total,
+        ))
     }
 
     pub fn find_latest_feedback_for_analysis(
@@ -801,7 +825,7 @@ impl Message {
             .order(messages::created_at.desc())
             .first::<MessageTuple>(&mut conn)
             .optional()?;
-        Ok(row.map(tuple_to_message))
+        row.map(tuple_to_message).transpose()
     }
 
     pub fn find_recent_mentor_feedbacks_for_user(
@@ -837,6 +861,6 @@ impl Message {
             .order(messages::created_at.desc())
             .limit(limit.max(1))
             .load::<MessageTuple>(&mut conn)?;
-        Ok(rows.into_iter().map(tuple_to_message).collect())
+        rows.into_iter().map(tuple_to_message).collect()
     }
 }
@@ -4,6 +4,7 @@ use diesel::sql_types::{BigInt, Nullable, Text, Timestamp, Uuid as SqlUuid};
 use uuid::Uuid;
 
 use crate::db::DbPool;
+use crate::entities_v2::data_key;
 use crate::entities_v2::error::{ErrorType, PpdcError};
 
 use super::attachment::MessageAttachment;
@@ -68,6 +69,7 @@ fn serialize_metadata(metadata: Option<&MessageMetadata>) -> Result<Option<Strin
 
 impl Message {
     pub fn update(self, pool: &DbPool) -> Result<Message, PpdcError> {
+        let content = data_key::seal_for_owner(Some(self.sender_user_id), &self.content, pool)?;
         let mut conn = pool.get()?;
         let (attachment_type_db, attachment_json) =
             serialize_attachment(self.attachment_type, self.attachment.as_ref())?;
@@ -101,7 +103,7 @@ impl Message {
         .bind::<Text, _>(self.message_type.to_db())
         .bind::<Text, _>(self.processing_state.to_db())
         .bind::<Text, _>(self.title)
-        .bind::<Text, _>(self.content)
+        .bind::<Text, _>(content)
         .bind::<Nullable<Text>, _>(attachment_type_db)
         .bind::<Nullable<Text>, _>(attachment_json)
         .bind::<Nullable<Text>, _>(metadata_json)
@@ -258,6 +260,7 @@ impl Message {
 
 impl NewMessage {
     pub fn create(self, pool: &DbPool) -> Result<Message, PpdcError> {
+        let content = data_key::seal_for_owner(Some(self.sender_user_id), &self.content, pool)?;
         let mut conn = pool.get()?;
         let (attachment_type_db, attachment_json) =
             serialize_attachment(self.attachment_type, self.attachment.as_ref())?;
@@ -296,7 +299,7 @@ impl NewMessage {
         .bind::<Text, _>(self.message_type.to_db())
         .bind::<Text, _>(self.processing_state.to_db())
         .bind::<Text, _>(self.title)
-        .bind::<Text, _>(self.content)
+        .bind::<Text, _>(content)
         .bind::<Nullable<Text>, _>(attachment_type_db)
         .bind::<Nullable<Text>, _>(attachment_json)
         .bind::<Nullable<Text>, _>(metadata_json)
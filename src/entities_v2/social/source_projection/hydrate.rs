@@ -4,8 +4,8 @@ use uuid::Uuid;
 
 use crate::entities_v2::post::{Post, PostSourceRef};
 use crate::entities_v2::{
-    album::AlbumCompletionStatus, document::DocumentStatus, error::PpdcError, post::PostType,
-    trace::TraceStatus,
+    album::AlbumCompletionStatus, data_key, document::DocumentStatus, error::PpdcError,
+    post::PostType, trace::TraceStatus,
 };
 use crate::schema::{albums, documents, traces};
 
@@ -67,24 +67,22 @@ pub fn load_source_projection_map(
                 String,
             )>(conn)?;
 
-        projections.extend(rows.into_iter().map(
-            |(id, journal_id, title, subtitle, content, cover_image_asset_id, status_raw)| {
-                (
-                    PostSourceRef::Trace(id),
-                    SourceProjection {
-                        source_kind: SourceProjectionKind::Trace,
-                        source_id: id,
-                        journal_id,
-                        title,
-                        subtitle,
-                        content,
-                        cover_image_asset_id,
-                        default_post_type: PostType::Idea,
-                        state: SourceProjectionState::Trace(TraceStatus::from_db(&status_raw)),
-                    },
-                )
-            },
-        ));
+        for (id, journal_id, title, subtitle, content, cover_image_asset_id, status_raw) in rows {
+            projections.insert(
+                PostSourceRef::Trace(id),
+                SourceProjection {
+                    source_kind: SourceProjectionKind::Trace,
+                    source_id: id,
+                    journal_id,
+                    title,
+                    subtitle,
+                    content: data_key::open(content)?,
+                    cover_image_asset_id,
+                    default_post_type: PostType::Idea,
+                    state: SourceProjectionState::Trace(TraceStatus::from_db(&status_raw)),
+                },
+            );
+        }
     }
 
     if !document_ids.is_empty() {
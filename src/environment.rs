@@ -37,6 +37,103 @@ pub fn get_openai_api_base_url() -> String {
     std::env::var("OPENAI_API_BASE_URL").unwrap_or_else(|_| "https://api.openai.com".to_string())
 }
 
+pub fn get_llm_provider() -> String {
+    dotenv().ok();
+    std::env::var("LLM_PROVIDER").unwrap_or_else(|_| "openai_responses".to_string())
+}
+
+pub fn get_local_llm_base_url() -> String {
+    dotenv().ok();
+    std::env::var("LOCAL_LLM_BASE_URL").unwrap_or_else(|_| "http://localhost:11434".to_string())
+}
+
+pub fn get_local_llm_model() -> Option<String> {
+    dotenv().ok();
+    std::env::var("LOCAL_LLM_MODEL")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub fn get_llm_fixtures_dir() -> String {
+    dotenv().ok();
+    std::env::var("LLM_FIXTURES_DIR").unwrap_or_else(|_| "test_data/llm_fixtures".to_string())
+}
+
+pub fn get_llm_fixtures_record_with() -> Option<String> {
+    dotenv().ok();
+    std::env::var("LLM_FIXTURES_RECORD_WITH")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub fn get_prompt_registry_dir() -> Option<String> {
+    dotenv().ok();
+    std::env::var("PROMPT_REGISTRY_DIR")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub fn get_llm_max_attempts() -> u32 {
+    dotenv().ok();
+    std::env::var("LLM_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .unwrap_or(4)
+}
+
+pub fn get_llm_retry_base_delay_ms() -> u64 {
+    dotenv().ok();
+    std::env::var("LLM_RETRY_BASE_DELAY_MS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(1000)
+}
+
+pub fn get_llm_retry_max_delay_ms() -> u64 {
+    dotenv().ok();
+    std::env::var("LLM_RETRY_MAX_DELAY_MS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(60_000)
+}
+
+pub fn get_llm_request_timeout_seconds() -> u64 {
+    dotenv().ok();
+    std::env::var("LLM_REQUEST_TIMEOUT_SECONDS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(300)
+}
+
+pub fn get_llm_max_concurrency_per_model() -> usize {
+    dotenv().ok();
+    std::env::var("LLM_MAX_CONCURRENCY_PER_MODEL")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(4)
+}
+
+pub fn get_embedding_provider() -> String {
+    dotenv().ok();
+    std::env::var("EMBEDDING_PROVIDER").unwrap_or_else(|_| "openai".to_string())
+}
+
+pub fn get_embedding_model() -> String {
+    dotenv().ok();
+    std::env::var("EMBEDDING_MODEL").unwrap_or_else(|_| "text-embedding-3-small".to_string())
+}
+
+pub fn get_matching_candidates_top_k() -> usize {
+    dotenv().ok();
+    std::env::var("MATCHING_CANDIDATES_TOP_K")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(20)
+}
+
 pub fn get_resend_api_key() -> String {
     dotenv().ok();
     std::env::var("RESEND_API_KEY")
@@ -81,6 +178,32 @@ pub fn get_auth_marker_cookie_domain() -> Option<String> {
         .filter(|value| !value.is_empty())
 }
 
+pub fn get_analysis_unlock_private_key() -> Option<String> {
+    dotenv().ok();
+    std::env::var("ANALYSIS_UNLOCK_PRIVATE_KEY")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Master keys for at-rest encryption as `id:base64key` pairs separated by commas.
+pub fn get_at_rest_master_keys() -> Option<String> {
+    dotenv().ok();
+    std::env::var("AT_REST_MASTER_KEYS")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Master key that wraps new data keys; defaults to the first entry of `AT_REST_MASTER_KEYS`.
+pub fn get_at_rest_active_master_key_id() -> Option<String> {
+    dotenv().ok();
+    std::env::var("AT_REST_ACTIVE_MASTER_KEY_ID")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
 pub fn get_observability_mode() -> String {
     dotenv().ok();
     std::env::var("OBSERVABILITY_MODE").unwrap_or_else(|_| "redacted".to_string())
@@ -112,6 +235,51 @@ pub fn get_assets_signed_url_ttl_seconds() -> u64 {
         .unwrap_or(3600)
 }
 
+/// Lifetime of journal export download links; signed URLs cannot outlive seven days.
+pub fn get_journal_export_url_ttl_seconds() -> u64 {
+    dotenv().ok();
+    std::env::var("JOURNAL_EXPORT_URL_TTL_SECONDS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(24 * 3600)
+        .min(7 * 24 * 3600)
+}
+
+/// Lifetime of account export download links, emailed when the archive is ready.
+pub fn get_account_export_url_ttl_seconds() -> u64 {
+    dotenv().ok();
+    std::env::var("ACCOUNT_EXPORT_URL_TTL_SECONDS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(3 * 24 * 3600)
+        .min(7 * 24 * 3600)
+}
+
+pub fn get_job_worker_concurrency() -> usize {
+    dotenv().ok();
+    std::env::var("JOB_WORKER_CONCURRENCY")
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(4)
+}
+
+/// Whether this instance enqueues the recurring jobs; workers run either way.
+pub fn get_job_scheduler_enabled() -> bool {
+    dotenv().ok();
+    std::env::var("JOB_SCHEDULER_ENABLED")
+        .map(|value| !matches!(value.trim(), "0" | "false" | "FALSE" | "no"))
+        .unwrap_or(true)
+}
+
+pub fn get_job_shutdown_grace_seconds() -> u64 {
+    dotenv().ok();
+    std::env::var("JOB_SHUTDOWN_GRACE_SECONDS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(30)
+}
+
 pub fn get_internal_cron_token() -> String {
     dotenv().ok();
     std::env::var("INTERNAL_CRON_TOKEN").expect("INTERNAL_CRON_TOKEN should be provided")
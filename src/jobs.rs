@@ -0,0 +1,43 @@
+//! In-process background jobs: a bounded worker pool draining `background_jobs`
+//! and a scheduler enqueueing the recurring ones. Both stop on the shutdown signal.
+
+pub mod handlers;
+pub mod scheduler;
+pub mod worker;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::db::DbPool;
+use crate::environment;
+
+/// Spawns the workers and, unless disabled, the scheduler. The handle resolves once
+/// both have stopped after `shutdown` flips to true.
+pub fn start(pool: DbPool, shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+    let concurrency = environment::get_job_worker_concurrency();
+    let scheduler_enabled = environment::get_job_scheduler_enabled();
+    tracing::info!(
+        target: "jobs",
+        "jobs_started concurrency={} scheduler_enabled={}",
+        concurrency,
+        scheduler_enabled
+    );
+    tokio::spawn(async move {
+        let workers = worker::run_workers(pool.clone(), concurrency, shutdown.clone());
+        if scheduler_enabled {
+            tokio::join!(workers, scheduler::run_scheduler(pool, shutdown));
+        } else {
+            workers.await;
+        }
+        tracing::info!(target: "jobs", "jobs_stopped");
+    })
+}
+
+/// Resolves once shutdown was requested or the sender is gone.
+pub(crate) async fn shutdown_requested(shutdown: &mut watch::Receiver<bool>) {
+    while !*shutdown.borrow_and_update() {
+        if shutdown.changed().await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::{
+    account_export::{self, ExportAccountPayload},
+    background_job::{BackgroundJob, BackgroundJobType},
+    data_key::{self, ReencryptionReport},
+    embedding::{self, EmbeddingsBackfillResponse},
+    error::{ErrorType, PpdcError},
+    journal_export::{self, ExportJournalPayload},
+    landmark_curation::{self, RefreshLandmarkSearchDocumentsPayload},
+    landmark_version::{self, BackfillLandmarkVersionsPayload, LandmarkVersionBackfillReport},
+    landscape_analysis, mailer,
+    trace_search::TraceSearchDocument,
+};
+use crate::work_analyzer;
+
+/// Upper bound on backfill batches per job so one run cannot monopolise a worker.
+const EMBEDDING_BACKFILL_MAX_BATCHES: usize = 50;
+const FINISHED_JOB_RETENTION_DAYS: i64 = 14;
+const REENCRYPTION_BATCH_SIZE: i64 = 500;
+const REENCRYPTION_MAX_BATCHES: usize = 20;
+const SEARCH_REINDEX_BATCH_SIZE: i64 = 200;
+const SEARCH_REINDEX_MAX_BATCHES: usize = 20;
+const LANDMARK_VERSION_BACKFILL_LENSES: i64 = 50;
+
+#[derive(Serialize, Deserialize)]
+pub struct RunLensPayload {
+    pub lens_id: Uuid,
+}
+
+#[derive(Serialize)]
+struct PendingAnalysesSweepResult {
+    candidate_lens_ids: Vec<Uuid>,
+    job_ids: Vec<Uuid>,
+}
+
+/// Runs a claimed job and returns the result stored on it.
+pub async fn execute(job: &BackgroundJob, pool: &DbPool) -> Result<serde_json::Value, PpdcError> {
+    let job_type = job.job_type_enum().ok_or_else(|| {
+        PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!("Unknown job type {}", job.job_type),
+        )
+    })?;
+    let result = match job_type {
+        BackgroundJobType::RunPendingAnalyses => {
+            serde_json::to_value(blocking(pool, enqueue_pending_lens_runs).await?)?
+        }
+        BackgroundJobType::RunLens => {
+            let payload = serde_json::from_value::<RunLensPayload>(job.payload.clone())?;
+            let lens = work_analyzer::run_lens(payload.lens_id).await?;
+            serde_json::json!({ "lens_id": lens.id })
+        }
+        BackgroundJobType::ReplanAutoplayLenses => {
+            serde_json::to_value(blocking(pool, landscape_analysis::replan_autoplay_lenses).await?)?
+        }
+        BackgroundJobType::ProcessPendingEmails => {
+            serde_json::to_value(mailer::process_due_pending_emails(pool).await?)?
+        }
+        BackgroundJobType::GenerateSharedJournalDailyDigests => serde_json::to_value(
+            blocking(pool, mailer::generate_shared_journal_daily_digests).await?,
+        )?,
+        BackgroundJobType::BackfillEmbeddings => {
+            serde_json::to_value(backfill_embeddings(pool).await?)?
+        }
+        BackgroundJobType::PruneFinishedJobs => {
+            let deleted = blocking(pool, |pool| {
+                BackgroundJob::prune_finished(FINISHED_JOB_RETENTION_DAYS, pool)
+            })
+            .await?;
+            serde_json::json!({ "deleted": deleted })
+        }
+        BackgroundJobType::ReencryptAtRest => {
+            serde_json::to_value(blocking(pool, reencrypt_at_rest).await?)?
+        }
+        BackgroundJobType::ExportJournal => {
+            let payload = serde_json::from_value::<ExportJournalPayload>(job.payload.clone())?;
+            serde_json::to_value(journal_export::service::run_export_job(payload, pool).await?)?
+        }
+        BackgroundJobType::ExportAccount => {
+            let payload = serde_json::from_value::<ExportAccountPayload>(job.payload.clone())?;
+            serde_json::to_value(
+                account_export::service::run_account_export_job(payload, pool).await?,
+            )?
+        }
+        BackgroundJobType::RefreshLandmarkSearchDocuments => {
+            let payload = serde_json::from_value::<RefreshLandmarkSearchDocumentsPayload>(
+                job.payload.clone(),
+            )?;
+            let landmark_id = payload.landmark_id;
+            blocking(pool, move |pool| {
+                landmark_curation::persist::refresh_search_documents(landmark_id, pool)
+            })
+            .await?;
+            serde_json::json!({ "landmark_id": payload.landmark_id })
+        }
+        BackgroundJobType::ReindexTraceSearchDocuments => {
+            serde_json::json!({ "reindexed": blocking(pool, reindex_trace_search_documents).await? })
+        }
+        BackgroundJobType::PruneExpiredExports => {
+            let journal_exports = journal_export::service::prune_expired_exports(pool).await?;
+            let account_exports = account_export::service::prune_expired_archives(pool).await?;
+            serde_json::json!({
+                "journal_exports": journal_exports,
+                "account_exports": account_exports,
+            })
+        }
+        BackgroundJobType::BackfillLandmarkVersions => {
+            let payload =
+                serde_json::from_value::<BackfillLandmarkVersionsPayload>(job.payload.clone())?;
+            serde_json::to_value(
+                blocking(pool, move |pool| backfill_landmark_versions(payload, pool)).await?,
+            )?
+        }
+    };
+    Ok(result)
+}
+
+/// Runs sync database work on the blocking pool so the worker keeps renewing the job lock.
+async fn blocking<T, F>(pool: &DbPool, work: F) -> Result<T, PpdcError>
+where
+    T: Send + 'static,
+    F: FnOnce(&DbPool) -> Result<T, PpdcError> + Send + 'static,
+{
+    let pool = pool.clone();
+    tokio::task::spawn_blocking(move || work(&pool))
+        .await
+        .map_err(|err| {
+            PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                format!("Job task failed: {}", err),
+            )
+        })?
+}
+
+/// Queues one lens run per lens with runnable backlog, so lenses run in parallel.
+fn enqueue_pending_lens_runs(pool: &DbPool) -> Result<PendingAnalysesSweepResult, PpdcError> {
+    let candidate_lens_ids = landscape_analysis::find_lens_ids_with_pending_analyses(pool)?;
+    let mut job_ids = Vec::with_capacity(candidate_lens_ids.len());
+    for lens_id in candidate_lens_ids.iter().copied() {
+        let job = BackgroundJob::enqueue(
+            BackgroundJobType::RunLens,
+            serde_json::to_value(RunLensPayload { lens_id })?,
+            Some(format!(
+                "{}:{}",
+                BackgroundJobType::RunLens.to_db(),
+                lens_id
+            )),
+            pool,
+        )?;
+        job_ids.push(job.id);
+    }
+    Ok(PendingAnalysesSweepResult {
+        candidate_lens_ids,
+        job_ids,
+    })
+}
+
+fn reindex_trace_search_documents(pool: &DbPool) -> Result<usize, PpdcError> {
+    let mut reindexed = 0;
+    for _ in 0..SEARCH_REINDEX_MAX_BATCHES {
+        let batch = TraceSearchDocument::reindex_legacy_documents(SEARCH_REINDEX_BATCH_SIZE, pool)?;
+        reindexed += batch;
+        if batch == 0 {
+            break;
+        }
+    }
+    Ok(reindexed)
+}
+
+async fn backfill_embeddings(pool: &DbPool) -> Result<EmbeddingsBackfillResponse, PpdcError> {
+    let mut total = EmbeddingsBackfillResponse::default();
+    for _ in 0..EMBEDDING_BACKFILL_MAX_BATCHES {
+        let batch = embedding::backfill_embeddings_batch(pool).await?;
+        total.model = batch.model;
+        total.landmarks_embedded += batch.landmarks_embedded;
+        total.elements_embedded += batch.elements_embedded;
+        total.trace_chunks_embedded += batch.trace_chunks_embedded;
+        if batch.landmarks_embedded == 0
+            && batch.elements_embedded == 0
+            && batch.trace_chunks_embedded == 0
+        {
+            break;
+        }
+    }
+    Ok(total)
+}
+
+fn reencrypt_at_rest(pool: &DbPool) -> Result<ReencryptionReport, PpdcError> {
+    let mut total = ReencryptionReport::default();
+    for _ in 0..REENCRYPTION_MAX_BATCHES {
+        let batch = data_key::reencrypt_batch(REENCRYPTION_BATCH_SIZE, pool)?;
+        total.rewrapped_keys += batch.rewrapped_keys;
+        total.resealed_values += batch.resealed_values;
+        total.skipped_values += batch.skipped_values;
+        if batch.rewrapped_keys == 0 && batch.resealed_values == 0 {
+            break;
+        }
+    }
+    Ok(total)
+}
+
+/// Backfills one batch of lenses and queues the batch after it, until every lens is done.
+fn backfill_landmark_versions(
+    payload: BackfillLandmarkVersionsPayload,
+    pool: &DbPool,
+) -> Result<LandmarkVersionBackfillReport, PpdcError> {
+    let report = landmark_version::persist::backfill_lens_batch(
+        payload.after_lens_id,
+        LANDMARK_VERSION_BACKFILL_LENSES,
+        pool,
+    )?;
+    if report.lenses as i64 == LANDMARK_VERSION_BACKFILL_LENSES {
+        if let Some(after_lens_id) = report.last_lens_id {
+            let job_type = BackgroundJobType::BackfillLandmarkVersions;
+            BackgroundJob::enqueue(
+                job_type,
+                serde_json::to_value(BackfillLandmarkVersionsPayload {
+                    after_lens_id: Some(after_lens_id),
+                })?,
+                Some(format!("{}:{}", job_type.to_db(), after_lens_id)),
+                pool,
+            )?;
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use crate::db::DbPool;
+use crate::entities_v2::background_job::{BackgroundJob, BackgroundJobType, JobSchedule};
+
+use super::shutdown_requested;
+
+const SCHEDULER_TICK: Duration = Duration::from_secs(5);
+
+/// Recurring jobs and their period, replacing the external cron calls.
+pub fn default_schedules() -> Vec<JobSchedule> {
+    [
+        (BackgroundJobType::RunPendingAnalyses, 60),
+        (BackgroundJobType::ProcessPendingEmails, 60),
+        (
+            BackgroundJobType::GenerateSharedJournalDailyDigests,
+            15 * 60,
+        ),
+        (BackgroundJobType::BackfillEmbeddings, 10 * 60),
+        (BackgroundJobType::PruneFinishedJobs, 24 * 60 * 60),
+        (BackgroundJobType::ReencryptAtRest, 60 * 60),
+        (BackgroundJobType::ReindexTraceSearchDocuments, 60 * 60),
+        (BackgroundJobType::PruneExpiredExports, 60 * 60),
+    ]
+    .into_iter()
+    .map(|(job_type, interval_seconds)| JobSchedule {
+        job_type,
+        interval_seconds,
+    })
+    .collect()
+}
+
+pub async fn run_scheduler(pool: DbPool, mut shutdown: watch::Receiver<bool>) {
+    if let Err(err) = JobSchedule::ensure_all(&default_schedules(), &pool) {
+        tracing::warn!(target: "jobs", "job_schedules_register_failed error={}", err.message);
+    }
+
+    loop {
+        match JobSchedule::claim_due(&pool) {
+            Ok(due) => {
+                for job_type in due {
+                    if let Err(err) = BackgroundJob::enqueue_singleton(job_type, &pool) {
+                        tracing::warn!(
+                            target: "jobs",
+                            "scheduled_job_enqueue_failed type={} error={}",
+                            job_type.to_db(),
+                            err.message
+                        );
+                    }
+                }
+            }
+            Err(err) => {
+                tracing::warn!(target: "jobs", "job_schedules_claim_failed error={}", err.message);
+            }
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(SCHEDULER_TICK) => {}
+            _ = shutdown_requested(&mut shutdown) => break,
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{watch, Semaphore};
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::background_job::BackgroundJob;
+use crate::environment;
+
+use super::{handlers, shutdown_requested};
+
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Claims due jobs while fewer than `concurrency` are running. On shutdown, stops
+/// claiming, waits up to the grace period for running jobs and requeues the rest.
+pub async fn run_workers(pool: DbPool, concurrency: usize, mut shutdown: watch::Receiver<bool>) {
+    let worker_id = Uuid::new_v4();
+    let permits = Arc::new(Semaphore::new(concurrency));
+    let mut running = JoinSet::new();
+
+    loop {
+        while running.try_join_next().is_some() {}
+
+        let permit = tokio::select! {
+            permit = permits.clone().acquire_owned() => match permit {
+                Ok(permit) => permit,
+                Err(_) => break,
+            },
+            _ = shutdown_requested(&mut shutdown) => break,
+        };
+
+        match BackgroundJob::claim_next(worker_id, &pool) {
+            Ok(Some(job)) => {
+                let pool = pool.clone();
+                running.spawn(async move {
+                    run_job(job, worker_id, &pool).await;
+                    drop(permit);
+                });
+                continue;
+            }
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(target: "jobs", "job_claim_failed error={}", err.message);
+            }
+        }
+        drop(permit);
+
+        tokio::select! {
+            _ = tokio::time::sleep(IDLE_POLL_INTERVAL) => {}
+            _ = shutdown_requested(&mut shutdown) => break,
+        }
+    }
+
+    let grace = Duration::from_secs(environment::get_job_shutdown_grace_seconds());
+    if drain_or_abort(&mut running, grace).await {
+        match BackgroundJob::release_owned(worker_id, &pool) {
+            Ok(released) => tracing::warn!(
+                target: "jobs",
+                "jobs_released_on_shutdown worker_id={} count={}",
+                worker_id,
+                released
+            ),
+            Err(err) => tracing::warn!(
+                target: "jobs",
+                "jobs_release_failed worker_id={} error={}",
+                worker_id,
+                err.message
+            ),
+        }
+    }
+}
+
+/// Waits up to `grace` for the running jobs, then aborts the rest and waits for their
+/// drop guards to run. Returns whether any job had to be aborted.
+async fn drain_or_abort(running: &mut JoinSet<()>, grace: Duration) -> bool {
+    let drained = tokio::time::timeout(grace, async {
+        while running.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_ok() {
+        return false;
+    }
+    running.abort_all();
+    while running.join_next().await.is_some() {}
+    true
+}
+
+/// Renews the job lock a few times per timeout so a long job is not reclaimed while it runs.
+fn heartbeat_interval(timeout_seconds: i32) -> Duration {
+    Duration::from_secs((timeout_seconds / 3).max(1) as u64)
+}
+
+async fn run_job(job: BackgroundJob, worker_id: Uuid, pool: &DbPool) {
+    tracing::info!(
+        target: "jobs",
+        "job_started id={} type={} attempt={}",
+        job.id,
+        job.job_type,
+        job.attempts
+    );
+    let execution = handlers::execute(&job, pool);
+    tokio::pin!(execution);
+    let mut heartbeat = tokio::time::interval(heartbeat_interval(job.timeout_seconds));
+    heartbeat.tick().await;
+    let execution_result = loop {
+        tokio::select! {
+            result = &mut execution => break result,
+            _ = heartbeat.tick() => match BackgroundJob::heartbeat(job.id, worker_id, pool) {
+                Ok(true) => {}
+                Ok(false) => tracing::warn!(
+                    target: "jobs",
+                    "job_lock_lost id={} type={} worker_id={}",
+                    job.id,
+                    job.job_type,
+                    worker_id
+                ),
+                Err(err) => tracing::warn!(
+                    target: "jobs",
+                    "job_heartbeat_failed id={} error={}",
+                    job.id,
+                    err.message
+                ),
+            },
+        }
+    };
+    let outcome = match execution_result {
+        Ok(result) => BackgroundJob::mark_succeeded(job.id, worker_id, &result, pool),
+        Err(err) => {
+            tracing::warn!(
+                target: "jobs",
+                "job_failed id={} type={} attempt={} error={}",
+                job.id,
+                job.job_type,
+                job.attempts,
+                err.message
+            );
+            job.mark_failed(worker_id, &err.message, pool)
+        }
+    };
+    if let Err(err) = outcome {
+        tracing::warn!(
+            target: "jobs",
+            "job_status_update_failed id={} error={}",
+            job.id,
+            err.message
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+
+    struct SetOnDrop(Arc<AtomicBool>);
+
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn drain_returns_without_abort_when_jobs_finish_in_grace() {
+        let mut running = JoinSet::new();
+        running.spawn(async {});
+        assert!(!drain_or_abort(&mut running, Duration::from_secs(1)).await);
+        assert!(running.is_empty());
+    }
+
+    #[tokio::test]
+    async fn abort_after_grace_runs_drop_guards_of_stuck_jobs() {
+        let released = Arc::new(AtomicBool::new(false));
+        let guard = SetOnDrop(released.clone());
+        let mut running = JoinSet::new();
+        running.spawn(async move {
+            let _guard = guard;
+            std::future::pending::<()>().await;
+        });
+
+        assert!(drain_or_abort(&mut running, Duration::from_millis(10)).await);
+        assert!(running.is_empty());
+        assert!(released.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn heartbeat_renews_well_before_the_lock_expires() {
+        assert_eq!(heartbeat_interval(3600), Duration::from_secs(1200));
+        assert_eq!(heartbeat_interval(0), Duration::from_secs(1));
+    }
+}
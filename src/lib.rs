@@ -2,11 +2,11 @@ pub mod db;
 pub mod entities_v2;
 pub mod environment;
 pub mod http;
+pub mod jobs;
 pub mod logging;
 pub mod openai_handler;
 pub mod pagination;
 pub mod router;
 pub mod schema;
 pub mod sessions_service;
-pub mod threadpool;
 pub mod work_analyzer;
@@ -13,9 +13,55 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let mut conn = pool.get()?;
     conn.run_pending_migrations(MIGRATIONS)?;
 
+    if let Some(dir) = web_server::environment::get_prompt_registry_dir() {
+        if let Err(err) = web_server::entities_v2::prompt_version::import_directory(
+            std::path::Path::new(&dir),
+            &pool,
+        ) {
+            tracing::warn!(
+                "prompt_registry_import_failed dir={} error={}",
+                dir,
+                err.message
+            );
+        }
+    }
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let jobs = web_server::jobs::start(pool.clone(), shutdown_rx);
+
     let app = web_server::router::create_router().layer(Extension(pool.clone()));
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            let _ = shutdown_tx.send(true);
+        })
+        .await?;
+
+    jobs.await?;
     Ok(())
 }
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("shutdown_signal_received");
+}
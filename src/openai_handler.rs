@@ -1,8 +1,14 @@
+pub mod embedding_provider;
 pub mod gpt_handler;
 pub mod gpt_responses_handler;
+pub mod llm_pricing;
+pub mod llm_provider;
+pub mod llm_retry;
 pub mod whisper_handler;
 //pub mod gpt_reasoning_handler;
 pub mod gpt_request;
 
+pub use embedding_provider::{EmbeddingProvider, EmbeddingProviderKind};
 pub use gpt_request::GptRequestConfig;
 pub use gpt_responses_handler::{GptReasoningEffort, GptVerbosity};
+pub use llm_provider::{LlmProvider, LlmProviderKind, LlmProviderSelection};
@@ -0,0 +1,120 @@
+pub mod openai;
+pub mod stub;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::environment;
+use crate::openai_handler::llm_provider::LlmProviderError;
+
+pub use openai::OpenAiEmbeddingProvider;
+pub use stub::StubEmbeddingProvider;
+
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Vectors are only compared with vectors of the same model.
+    fn model(&self) -> &str;
+
+    /// One vector per input, in input order.
+    async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, LlmProviderError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingProviderKind {
+    OpenAi,
+    Stub,
+}
+
+impl EmbeddingProviderKind {
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "openai" => Some(Self::OpenAi),
+            "stub" | "local_stub" => Some(Self::Stub),
+            _ => None,
+        }
+    }
+
+    pub fn to_db(self) -> &'static str {
+        match self {
+            Self::OpenAi => "openai",
+            Self::Stub => "stub",
+        }
+    }
+
+    pub fn build(self) -> Arc<dyn EmbeddingProvider> {
+        match self {
+            Self::OpenAi => Arc::new(OpenAiEmbeddingProvider::from_env()),
+            Self::Stub => Arc::new(StubEmbeddingProvider::default()),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let configured = environment::get_embedding_provider();
+        Self::from_db(&configured).unwrap_or_else(|| {
+            tracing::warn!(
+                target: "work_analyzer",
+                "embedding_provider_unknown value={} fallback={}",
+                configured,
+                Self::OpenAi.to_db()
+            );
+            Self::OpenAi
+        })
+    }
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let mut dot = 0.0f32;
+    let mut norm_a = 0.0f32;
+    let mut norm_b = 0.0f32;
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Indices of the `k` candidates closest to `query`, most similar first.
+pub fn top_k(query: &[f32], candidates: &[Vec<f32>], k: usize) -> Vec<usize> {
+    let mut scored: Vec<(usize, f32)> = candidates
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| (index, cosine_similarity(query, candidate)))
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    scored.into_iter().take(k).map(|(index, _)| index).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cosine_similarity_handles_degenerate_vectors() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn top_k_orders_by_similarity() {
+        let candidates = vec![
+            vec![0.0, 1.0],
+            vec![1.0, 0.1],
+            vec![1.0, 0.0],
+            vec![-1.0, 0.0],
+        ];
+        assert_eq!(top_k(&[1.0, 0.0], &candidates, 2), vec![2, 1]);
+        assert_eq!(top_k(&[1.0, 0.0], &candidates, 10).len(), 4);
+    }
+}
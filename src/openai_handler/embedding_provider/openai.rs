@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::environment;
+use crate::openai_handler::llm_provider::{http_client, LlmProviderError};
+
+use super::EmbeddingProvider;
+
+/// The embeddings endpoint accepts up to 2048 inputs; smaller batches keep payloads modest.
+const MAX_INPUTS_PER_REQUEST: usize = 256;
+
+#[derive(Debug, Serialize)]
+struct EmbeddingsRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingItem {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// OpenAI `/v1/embeddings` backend.
+pub struct OpenAiEmbeddingProvider {
+    base_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl OpenAiEmbeddingProvider {
+    pub fn new(base_url: String, api_key: String, model: String) -> Self {
+        Self {
+            base_url,
+            api_key,
+            model,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(
+            environment::get_openai_api_base_url(),
+            environment::get_openai_api_key(),
+            environment::get_embedding_model(),
+        )
+    }
+
+    fn embeddings_url(&self) -> String {
+        let base = self.base_url.trim_end_matches('/');
+        if base.ends_with("/v1") {
+            format!("{}/embeddings", base)
+        } else {
+            format!("{}/v1/embeddings", base)
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiEmbeddingProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, LlmProviderError> {
+        let mut vectors = Vec::with_capacity(inputs.len());
+        for chunk in inputs.chunks(MAX_INPUTS_PER_REQUEST) {
+            let resp = http_client()
+                .post(self.embeddings_url())
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&EmbeddingsRequest {
+                    model: &self.model,
+                    input: chunk,
+                })
+                .send()
+                .await
+                .map_err(|e| format!("Failed to send embeddings request: {e}"))?;
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            if !status.is_success() {
+                return Err(format!("Embeddings API error ({status}): {body}").into());
+            }
+            let mut data = serde_json::from_str::<EmbeddingsResponse>(&body)?.data;
+            if data.len() != chunk.len() {
+                return Err(format!(
+                    "Embeddings API returned {} vectors for {} inputs",
+                    data.len(),
+                    chunk.len()
+                )
+                .into());
+            }
+            data.sort_by_key(|item| item.index);
+            vectors.extend(data.into_iter().map(|item| item.embedding));
+        }
+        Ok(vectors)
+    }
+}
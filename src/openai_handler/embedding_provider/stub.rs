@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use crate::openai_handler::llm_provider::LlmProviderError;
+
+use super::EmbeddingProvider;
+
+const DEFAULT_DIMENSIONS: usize = 256;
+const TRIGRAM_WEIGHT: f32 = 0.5;
+
+/// Deterministic, offline embeddings: hashed bag of words and character trigrams.
+/// Texts sharing vocabulary land close together, which is enough for tests and local runs.
+pub struct StubEmbeddingProvider {
+    dimensions: usize,
+    model: String,
+}
+
+impl StubEmbeddingProvider {
+    pub fn new(dimensions: usize) -> Self {
+        let dimensions = dimensions.max(1);
+        Self {
+            dimensions,
+            model: format!("stub-hash-{}", dimensions),
+        }
+    }
+
+    fn add_feature(&self, vector: &mut [f32], feature: &str, weight: f32) {
+        let digest = Sha256::digest(feature.as_bytes());
+        let mut bucket_bytes = [0u8; 8];
+        bucket_bytes.copy_from_slice(&digest[..8]);
+        let bucket = (u64::from_le_bytes(bucket_bytes) % self.dimensions as u64) as usize;
+        let sign = if digest[8] & 1 == 0 { 1.0 } else { -1.0 };
+        vector[bucket] += sign * weight;
+    }
+
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimensions];
+        let lowered = text.to_lowercase();
+        for token in lowered
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|token| !token.is_empty())
+        {
+            self.add_feature(&mut vector, token, 1.0);
+            let chars: Vec<char> = format!(" {} ", token).chars().collect();
+            for trigram in chars.windows(3) {
+                let trigram: String = trigram.iter().collect();
+                self.add_feature(&mut vector, &format!("#{}", trigram), TRIGRAM_WEIGHT);
+            }
+        }
+        let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|value| *value /= norm);
+        }
+        vector
+    }
+}
+
+impl Default for StubEmbeddingProvider {
+    fn default() -> Self {
+        Self::new(DEFAULT_DIMENSIONS)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for StubEmbeddingProvider {
+    fn name(&self) -> &'static str {
+        "stub"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, LlmProviderError> {
+        Ok(inputs.iter().map(|input| self.embed_text(input)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openai_handler::embedding_provider::cosine_similarity;
+
+    #[test]
+    fn stub_is_deterministic_and_topical() {
+        let provider = StubEmbeddingProvider::default();
+        let book = provider.embed_text("Reading Dune by Frank Herbert");
+        assert_eq!(book, provider.embed_text("Reading Dune by Frank Herbert"));
+
+        let close = provider.embed_text("Dune (Frank Herbert)");
+        let far = provider.embed_text("Weekly swimming habit");
+        assert!(cosine_similarity(&book, &close) > cosine_similarity(&book, &far));
+    }
+}
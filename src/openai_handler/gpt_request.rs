@@ -1,7 +1,10 @@
 use crate::entities_v2::error::PpdcError;
 use crate::openai_handler::gpt_responses_handler::{
-    make_gpt_request, GptReasoningEffort, GptVerbosity,
+    make_llm_request, GptReasoningEffort, GptVerbosity,
 };
+use crate::openai_handler::llm_provider::{self, LlmProvider, LlmRequest, PromptRef};
+use crate::openai_handler::llm_retry::LlmRetryPolicy;
+use std::sync::Arc;
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -14,6 +17,9 @@ pub struct GptRequestConfig {
     pub display_name: Option<String>,
     pub reasoning_effort: Option<GptReasoningEffort>,
     pub verbosity: Option<GptVerbosity>,
+    pub provider: Option<Arc<dyn LlmProvider>>,
+    pub retry_policy: Option<LlmRetryPolicy>,
+    pub prompt: Option<PromptRef>,
 }
 
 impl GptRequestConfig {
@@ -33,6 +39,9 @@ impl GptRequestConfig {
             display_name: None,
             reasoning_effort: None,
             verbosity: None,
+            provider: None,
+            retry_policy: None,
+            prompt: None,
         }
     }
 
@@ -51,20 +60,45 @@ impl GptRequestConfig {
         self
     }
 
+    /// Pins this step to a provider, bypassing the analysis-level selection.
+    pub fn with_provider(mut self, provider: Arc<dyn LlmProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Records which registry prompt version this request uses.
+    pub fn with_prompt(mut self, prompt: PromptRef) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: LlmRetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
     pub async fn execute<T>(&self) -> Result<T, PpdcError>
     where
         T: for<'de> serde::Deserialize<'de>,
     {
-        Ok(make_gpt_request(
-            self.model.clone(),
-            self.reasoning_effort.clone(),
-            self.verbosity.clone(),
-            self.system_prompt.clone(),
-            self.user_prompt.clone(),
-            self.schema.clone(),
-            self.display_name.as_deref(),
-            self.analysis_id,
-        )
-        .await?)
+        let provider = self
+            .provider
+            .clone()
+            .unwrap_or_else(|| llm_provider::resolve_provider(self.display_name.as_deref()));
+        let request = LlmRequest {
+            model: llm_provider::resolve_model(self.model.clone()),
+            system_prompt: self.system_prompt.clone(),
+            user_prompt: self.user_prompt.clone(),
+            schema: self.schema.clone(),
+            reasoning_effort: self.reasoning_effort.clone(),
+            verbosity: self.verbosity.clone(),
+            display_name: self.display_name.clone(),
+            prompt: self.prompt.clone(),
+        };
+        let retry_policy = self
+            .retry_policy
+            .clone()
+            .unwrap_or_else(LlmRetryPolicy::from_env);
+        Ok(make_llm_request(provider.as_ref(), request, &retry_policy, self.analysis_id).await?)
     }
 }
@@ -1,14 +1,20 @@
 use crate::db;
 use crate::entities_v2::llm_call::NewLlmCall;
 use crate::environment;
-use crate::work_analyzer::observability::format_text_log_field;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use crate::openai_handler::llm_pricing::{self, LlmUsage, PRICE_CURRENCY};
+use crate::openai_handler::llm_provider::{self, LlmProvider, LlmRawResponse, LlmRequest};
+use crate::openai_handler::llm_retry::{self, LlmRetryPolicy};
+use crate::work_analyzer::observability::{
+    format_text_log_field, is_sensitive_text_redacted, record_event, AnalysisEventData,
+};
+use reqwest::StatusCode;
+use serde::Deserialize;
 use serde_json::Value;
 use tracing::info;
 use uuid::Uuid;
 
 pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4.1-mini-2025-04-14";
+const REDACTED_TEXT: &str = "[redacted]";
 
 #[derive(Clone)]
 pub enum GptReasoningEffort {
@@ -18,7 +24,7 @@ pub enum GptReasoningEffort {
 }
 
 impl GptReasoningEffort {
-    fn as_str(&self) -> &'static str {
+    pub(crate) fn as_str(&self) -> &'static str {
         match self {
             Self::Low => "low",
             Self::Medium => "medium",
@@ -35,7 +41,7 @@ pub enum GptVerbosity {
 }
 
 impl GptVerbosity {
-    fn as_str(&self) -> &'static str {
+    pub(crate) fn as_str(&self) -> &'static str {
         match self {
             Self::Low => "low",
             Self::Medium => "medium",
@@ -44,46 +50,6 @@ impl GptVerbosity {
     }
 }
 
-#[derive(Debug, Serialize)]
-struct GPTReasoning {
-    effort: String,
-}
-
-#[derive(Debug, Serialize)]
-struct GPTTextFormat {
-    #[serde(rename = "type")]
-    kind: String,
-    name: String,
-    schema: serde_json::Value,
-    strict: bool,
-}
-
-#[derive(Debug, Serialize)]
-struct GPTText {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    format: Option<GPTTextFormat>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    verbosity: Option<String>,
-}
-
-#[derive(Debug, Serialize)]
-struct GPTRequest {
-    model: String,
-    input: Vec<GPTMessage>,
-    max_output_tokens: u32,
-    text: GPTText,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    temperature: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    reasoning: Option<GPTReasoning>,
-}
-
-#[derive(Debug, Serialize)]
-struct GPTMessage {
-    role: String,
-    content: String,
-}
-
 /// Shape minimal du /v1/responses qu'on veut parser
 #[derive(Debug, Deserialize)]
 struct GPTResponse {
@@ -122,15 +88,6 @@ struct ChatCompletionMessage {
     content: String,
 }
 
-fn build_responses_url(base_url: &str) -> String {
-    let base = base_url.trim_end_matches('/');
-    if base.ends_with("/v1") {
-        format!("{}/responses", base)
-    } else {
-        format!("{}/v1/responses", base)
-    }
-}
-
 fn extract_output_text(body: &str) -> Option<String> {
     if let Ok(resp) = serde_json::from_str::<GPTResponse>(body) {
         return resp
@@ -192,130 +149,338 @@ pub async fn make_gpt_request<T>(
 where
     T: for<'de> serde::Deserialize<'de>,
 {
-    let api_key = environment::get_openai_api_key();
-    let base_url = environment::get_openai_api_base_url();
-    let request_url = build_responses_url(&base_url);
-    let client = Client::new();
-
-    let reasoning = reasoning_effort.map(|effort| GPTReasoning {
-        effort: effort.as_str().to_string(),
-    });
-    let gpt_request = GPTRequest {
-        model: model.clone(),
-        input: vec![
-            GPTMessage {
-                role: "system".to_string(),
-                content: system_prompt.clone(),
-            },
-            GPTMessage {
-                role: "user".to_string(),
-                content: user_prompt.clone(),
-            },
-        ],
-        max_output_tokens: 22500,
-        temperature: if reasoning.is_some() { None } else { Some(0.1) },
-        reasoning,
-        text: GPTText {
-            format: schema.clone().map(|schema| GPTTextFormat {
-                kind: "json_schema".to_string(),
-                name: "reference_schema".to_string(),
-                schema,
-                strict: true,
-            }),
-            verbosity: verbosity.map(|verbosity| verbosity.as_str().to_string()),
-        },
+    let request = LlmRequest {
+        model: llm_provider::resolve_model(model),
+        system_prompt,
+        user_prompt,
+        schema,
+        reasoning_effort,
+        verbosity,
+        display_name: display_name.map(str::to_string),
+        prompt: None,
     };
+    let provider = llm_provider::resolve_provider(display_name);
+    make_llm_request(
+        provider.as_ref(),
+        request,
+        &LlmRetryPolicy::from_env(),
+        analysis_id,
+    )
+    .await
+}
 
-    // Serialize request to JSON string for persistence
-    let request_json = serde_json::to_string(&gpt_request)
-        .unwrap_or_else(|e| format!("Failed to serialize request: {e}"));
-    let schema_json = serde_json::to_string(&schema)
-        .unwrap_or_else(|e| format!("Failed to serialize schema: {e}"));
-    let full_prompt = format!("System: {}\n\nUser: {}", system_prompt, user_prompt);
-
-    let resp = client
-        .post(&request_url)
-        .header("Authorization", format!("Bearer {}", api_key))
-        .header("Content-Type", "application/json")
-        .json(&gpt_request)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to GPT: {e}"))?;
-
-    let status = resp.status();
-    let body = resp.text().await.unwrap_or_default();
+struct LlmAttemptRecord<'a> {
+    request: &'a LlmRequest,
+    analysis_id: Option<Uuid>,
+    logical_call_id: Uuid,
+    attempt: u32,
+    call_status: String,
+    model: String,
+    request_url: String,
+    request_json: String,
+    body: String,
+    output_text: String,
+    usage: LlmUsage,
+    price: f64,
+}
 
-    // Determine call status based on HTTP status and response
-    let call_status = if !status.is_success() {
-        format!("error_{}", status.as_u16())
+fn persist_llm_attempt(record: LlmAttemptRecord<'_>) {
+    let env = environment::get_env();
+    let (true, Some(analysis_id), Some(pool)) = (
+        env != "bintest",
+        record.analysis_id,
+        db::try_get_global_pool(),
+    ) else {
+        return;
+    };
+    let request = record.request;
+    let schema_json = serde_json::to_string(&request.schema)
+        .unwrap_or_else(|e| format!("Failed to serialize schema: {e}"));
+    // Prompts built from decrypted traces must not outlive the request.
+    let redacted = is_sensitive_text_redacted();
+    let user_prompt = if redacted {
+        REDACTED_TEXT.to_string()
+    } else {
+        request.user_prompt.clone()
+    };
+    let request_json = if redacted {
+        REDACTED_TEXT.to_string()
     } else {
-        "completed".to_string()
+        record.request_json
     };
+    let full_prompt = format!("System: {}\n\nUser: {}", request.system_prompt, user_prompt);
+    let new_call = NewLlmCall::new(
+        record.call_status,
+        record.model,
+        full_prompt,
+        request.display_name.clone().unwrap_or_default(),
+        schema_json,
+        request_json,
+        record.request_url,
+        record.body,
+        record.output_text,
+        record.usage.input_tokens,
+        record.usage.reasoning_tokens,
+        record.usage.output_tokens,
+        record.price,
+        PRICE_CURRENCY.to_string(),
+        analysis_id,
+        request.system_prompt.clone(),
+        user_prompt,
+    )
+    .with_attempt(record.logical_call_id, record.attempt as i32)
+    .with_prompt(request.prompt.as_ref());
+
+    // Try to persist, but don't fail the whole request if persistence fails
+    if let Err(e) = new_call.create(pool) {
+        tracing::warn!(
+            target: "work_analyzer",
+            "analysis_id: {} llm_persist_failed name={} attempt={} error={}",
+            analysis_id,
+            request.display_name.as_deref().unwrap_or("unknown"),
+            record.attempt,
+            e
+        );
+    }
+}
+
+fn record_llm_event(analysis_id: Option<Uuid>, event: AnalysisEventData) {
+    if let Some(analysis_id) = analysis_id {
+        record_event(analysis_id, event);
+    }
+}
 
-    // Try to parse response to extract output text (best effort before persistence)
-    let output_text = extract_output_text(&body).unwrap_or_default();
-    let (output_text, output_text_nuls_removed) = strip_nul_chars(&output_text);
+pub async fn make_llm_request<T>(
+    provider: &dyn LlmProvider,
+    request: LlmRequest,
+    retry_policy: &LlmRetryPolicy,
+    analysis_id: Option<Uuid>,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+where
+    T: for<'de> serde::Deserialize<'de>,
+{
+    let logical_call_id = Uuid::new_v4();
+    let step = request
+        .display_name
+        .clone()
+        .unwrap_or_else(|| "unknown".to_string());
+    record_llm_event(
+        analysis_id,
+        AnalysisEventData::LlmCallStarted {
+            step: step.clone(),
+            llm_call_id: logical_call_id.to_string(),
+        },
+    );
+    let result = run_llm_attempts(
+        provider,
+        request,
+        retry_policy,
+        analysis_id,
+        logical_call_id,
+    )
+    .await;
+    record_llm_event(
+        analysis_id,
+        AnalysisEventData::LlmCallFinished {
+            step,
+            llm_call_id: logical_call_id.to_string(),
+            ok: result.is_ok(),
+        },
+    );
+    result
+}
 
+async fn run_llm_attempts<T>(
+    provider: &dyn LlmProvider,
+    request: LlmRequest,
+    retry_policy: &LlmRetryPolicy,
+    analysis_id: Option<Uuid>,
+    logical_call_id: Uuid,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+where
+    T: for<'de> serde::Deserialize<'de>,
+{
+    let display_name = request.display_name.as_deref();
     let resolved_log_header = analysis_id
         .map(|id| format!("analysis_id: {}", id))
         .unwrap_or_else(|| "analysis_id: unknown".to_string());
-    info!(
-        target: "work_analyzer",
-        "{} llm_result name={} {} {}",
-        resolved_log_header,
-        display_name.unwrap_or("unknown"),
-        format_text_log_field("prompt", &user_prompt),
-        format_text_log_field("output", &output_text)
-    );
-    if output_text_nuls_removed > 0 {
-        tracing::warn!(
+    let mut attempt: u32 = 0;
+
+    let body = loop {
+        attempt += 1;
+        let can_retry = attempt < retry_policy.max_attempts;
+
+        let sent = {
+            let _permit = llm_retry::acquire_model_permit(&request.model).await;
+            tokio::time::timeout(retry_policy.request_timeout, provider.send(&request)).await
+        };
+        let sent = match sent {
+            Ok(Ok(raw)) => Ok(raw),
+            Ok(Err(e)) => Err(("error_transport".to_string(), e.to_string())),
+            Err(_) => Err((
+                "error_timeout".to_string(),
+                format!(
+                    "LLM request timed out after {}s",
+                    retry_policy.request_timeout.as_secs()
+                ),
+            )),
+        };
+        let raw = match sent {
+            Ok(raw) => raw,
+            Err((call_status, message)) => {
+                tracing::warn!(
+                    target: "work_analyzer",
+                    "{} llm_attempt_failed name={} provider={} attempt={} status={} error={}",
+                    resolved_log_header,
+                    display_name.unwrap_or("unknown"),
+                    provider.name(),
+                    attempt,
+                    call_status,
+                    message
+                );
+                persist_llm_attempt(LlmAttemptRecord {
+                    request: &request,
+                    analysis_id,
+                    logical_call_id,
+                    attempt,
+                    call_status,
+                    model: request.model.clone(),
+                    request_url: String::new(),
+                    request_json: String::new(),
+                    body: message.clone(),
+                    output_text: String::new(),
+                    usage: LlmUsage::default(),
+                    price: 0.0,
+                });
+                if can_retry {
+                    let delay = retry_policy.backoff_delay(attempt);
+                    record_llm_event(
+                        analysis_id,
+                        AnalysisEventData::Error {
+                            step: display_name.unwrap_or("unknown").to_string(),
+                            message,
+                            retry_in_seconds: Some(delay.as_secs()),
+                        },
+                    );
+                    tokio::time::sleep(delay).await;
+                    continue;
+                }
+                return Err(message.into());
+            }
+        };
+
+        let LlmRawResponse {
+            request_url,
+            request_json,
+            http_status,
+            body,
+            retry_after,
+            model,
+            billable,
+        } = raw;
+        let status = StatusCode::from_u16(http_status).unwrap_or(StatusCode::BAD_GATEWAY);
+
+        // Determine call status based on HTTP status and response
+        let call_status = if !status.is_success() {
+            format!("error_{}", status.as_u16())
+        } else {
+            "completed".to_string()
+        };
+
+        // Try to parse response to extract output text (best effort before persistence)
+        let output_text = extract_output_text(&body).unwrap_or_default();
+        let (output_text, output_text_nuls_removed) = strip_nul_chars(&output_text);
+
+        let usage = llm_pricing::extract_usage(&body).unwrap_or_default();
+        let price = if billable {
+            llm_pricing::compute_cost(&model, &usage)
+        } else {
+            0.0
+        };
+
+        info!(
             target: "work_analyzer",
-            "{} llm_output_sanitized_nul name={} removed_nuls={}",
+            "{} llm_result name={} provider={} attempt={} status={} input_tokens={} output_tokens={} price={} {} {}",
             resolved_log_header,
             display_name.unwrap_or("unknown"),
-            output_text_nuls_removed
+            provider.name(),
+            attempt,
+            call_status,
+            usage.input_tokens,
+            usage.output_tokens,
+            price,
+            format_text_log_field("prompt", &request.user_prompt),
+            format_text_log_field("output", &output_text)
         );
-    }
+        if output_text_nuls_removed > 0 {
+            tracing::warn!(
+                target: "work_analyzer",
+                "{} llm_output_sanitized_nul name={} removed_nuls={}",
+                resolved_log_header,
+                display_name.unwrap_or("unknown"),
+                output_text_nuls_removed
+            );
+        }
 
-    let env = environment::get_env();
-    if env != "bintest" && analysis_id.is_some() {
         // Persist the LLM call to database before attempting full parsing
-        let pool = db::get_global_pool();
-        let new_call = NewLlmCall::new(
-            call_status.clone(),
+        persist_llm_attempt(LlmAttemptRecord {
+            request: &request,
+            analysis_id,
+            logical_call_id,
+            attempt,
+            call_status,
             model,
-            full_prompt,
-            display_name.unwrap_or("").to_string(),
-            schema_json,
+            request_url,
             request_json,
-            request_url.clone(),
-            body.clone(),
-            output_text.clone(),
-            0,                 // input_tokens_used - not available in current response structure
-            0,   // reasoning_tokens_used - not available in current response structure
-            0,   // output_tokens_used - not available in current response structure
-            0.0, // price - not available in current response structure
-            "USD".to_string(), // currency - default
-            analysis_id.unwrap(),
-            system_prompt,
-            user_prompt,
-        );
+            body: body.clone(),
+            output_text,
+            usage,
+            price,
+        });
 
-        // Try to persist, but don't fail the whole request if persistence fails
-        if let Err(e) = new_call.create(pool) {
+        if status.is_success() {
+            break body;
+        }
+        let delay = if can_retry && llm_retry::is_retryable_status(http_status) {
+            let delay = retry_policy.delay_before_retry(attempt, retry_after);
+            if delay.is_none() {
+                tracing::warn!(
+                    target: "work_analyzer",
+                    "{} llm_retry_after_exceeds_max_delay name={} attempt={} status={} retry_after_ms={}",
+                    resolved_log_header,
+                    display_name.unwrap_or("unknown"),
+                    attempt,
+                    http_status,
+                    retry_after.unwrap_or_default().as_millis()
+                );
+            }
+            delay
+        } else {
+            None
+        };
+        if let Some(delay) = delay {
             tracing::warn!(
                 target: "work_analyzer",
-                "{} llm_persist_failed name={} error={}",
+                "{} llm_retry_scheduled name={} attempt={} status={} delay_ms={}",
                 resolved_log_header,
                 display_name.unwrap_or("unknown"),
-                e
+                attempt,
+                http_status,
+                delay.as_millis()
+            );
+            record_llm_event(
+                analysis_id,
+                AnalysisEventData::Error {
+                    step: display_name.unwrap_or("unknown").to_string(),
+                    message: format!("LLM request failed with HTTP status {}", http_status),
+                    retry_in_seconds: Some(delay.as_secs()),
+                },
             );
+            tokio::time::sleep(delay).await;
+            continue;
         }
-    }
-
-    if !status.is_success() {
         return Err(format!("GPT API error ({status}): {body}").into());
-    }
+    };
 
     let json_text = if let Ok(gpt_resp) = serde_json::from_str::<GPTResponse>(&body) {
         if gpt_resp.status != "completed" {
@@ -355,7 +520,7 @@ where
 
     // Si pas de schéma, on parse directement le texte brut comme String.
     // Sinon, on parse comme JSON puis on nettoie les NUL dans tous les champs texte.
-    let result: T = if schema.is_none() {
+    let result: T = if request.schema.is_none() {
         serde_json::from_value(Value::String(json_text))
             .map_err(|e| format!("Failed to deserialize text into target type: {e}"))?
     } else {
@@ -380,8 +545,165 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{sanitize_json_value_nuls, strip_nul_chars};
+    use super::{make_llm_request, sanitize_json_value_nuls, strip_nul_chars};
+    use crate::openai_handler::llm_provider::{LlmRequest, OpenAiResponsesProvider};
+    use crate::openai_handler::llm_retry::LlmRetryPolicy;
+    use axum::{http::StatusCode, response::IntoResponse, routing::post, Router};
     use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct FakeReply {
+        status: u16,
+        headers: Vec<(&'static str, &'static str)>,
+        body: String,
+        delay: Duration,
+    }
+
+    fn completed_body(text: &str) -> String {
+        json!({
+            "status": "completed",
+            "output": [{
+                "type": "message",
+                "content": [{ "type": "output_text", "text": text }]
+            }],
+            "usage": { "input_tokens": 10, "output_tokens": 5 }
+        })
+        .to_string()
+    }
+
+    fn reply(status: u16, body: &str) -> FakeReply {
+        FakeReply {
+            status,
+            headers: vec![],
+            body: body.to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Serves `replies` in order on a local port (the last one repeats) and counts hits.
+    async fn spawn_fake_llm_server(replies: Vec<FakeReply>) -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let handler_hits = hits.clone();
+        let app = Router::new().route(
+            "/v1/responses",
+            post(move || {
+                let hits = handler_hits.clone();
+                let replies = replies.clone();
+                async move {
+                    let index = hits.fetch_add(1, Ordering::SeqCst).min(replies.len() - 1);
+                    let reply = replies[index].clone();
+                    tokio::time::sleep(reply.delay).await;
+                    let mut response = (
+                        StatusCode::from_u16(reply.status).unwrap(),
+                        reply.body.clone(),
+                    )
+                        .into_response();
+                    for (name, value) in reply.headers {
+                        response.headers_mut().insert(name, value.parse().unwrap());
+                    }
+                    response
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{address}"), hits)
+    }
+
+    fn test_request() -> LlmRequest {
+        LlmRequest {
+            model: "gpt-4.1-mini".to_string(),
+            system_prompt: "system".to_string(),
+            user_prompt: "user".to_string(),
+            schema: None,
+            reasoning_effort: None,
+            verbosity: None,
+            display_name: Some("Test / Retry".to_string()),
+            prompt: None,
+        }
+    }
+
+    fn fast_policy(max_attempts: u32, request_timeout: Duration) -> LlmRetryPolicy {
+        LlmRetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(50),
+            request_timeout,
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limits_and_server_errors_until_success() {
+        let rate_limited = FakeReply {
+            headers: vec![("retry-after-ms", "10")],
+            ..reply(429, "rate limited")
+        };
+        let (base_url, hits) = spawn_fake_llm_server(vec![
+            rate_limited,
+            reply(503, "unavailable"),
+            reply(200, &completed_body("hello")),
+        ])
+        .await;
+        let provider = OpenAiResponsesProvider::new(base_url, "test-key".to_string());
+
+        let output: String = make_llm_request(
+            &provider,
+            test_request(),
+            &fast_policy(4, Duration::from_secs(5)),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(output, "hello");
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts_on_timeouts() {
+        let slow = FakeReply {
+            delay: Duration::from_millis(500),
+            ..reply(200, &completed_body("late"))
+        };
+        let (base_url, hits) = spawn_fake_llm_server(vec![slow]).await;
+        let provider = OpenAiResponsesProvider::new(base_url, "test-key".to_string());
+
+        let error = make_llm_request::<String>(
+            &provider,
+            test_request(),
+            &fast_policy(2, Duration::from_millis(50)),
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(error.to_string().contains("timed out"));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        let (base_url, hits) = spawn_fake_llm_server(vec![reply(400, "bad request")]).await;
+        let provider = OpenAiResponsesProvider::new(base_url, "test-key".to_string());
+
+        let error = make_llm_request::<String>(
+            &provider,
+            test_request(),
+            &fast_policy(4, Duration::from_secs(5)),
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(error.to_string().contains("400"));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
 
     #[test]
     fn strip_nul_chars_removes_nuls_only() {
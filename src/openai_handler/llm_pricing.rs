@@ -0,0 +1,275 @@
+use serde::Deserialize;
+
+pub const PRICE_CURRENCY: &str = "USD";
+
+/// Token counts reported by the provider for one call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LlmUsage {
+    pub input_tokens: i32,
+    pub cached_input_tokens: i32,
+    pub reasoning_tokens: i32,
+    pub output_tokens: i32,
+}
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub cached_input: f64,
+    pub output: f64,
+}
+
+/// Keyed by model prefix so dated snapshots ("gpt-4.1-mini-2025-04-14") share their family price.
+/// Longer prefixes win, so "gpt-4.1-mini" is not billed as "gpt-4.1".
+const MODEL_PRICES: &[(&str, ModelPrice)] = &[
+    (
+        "gpt-4.1-nano",
+        ModelPrice {
+            input: 0.10,
+            cached_input: 0.025,
+            output: 0.40,
+        },
+    ),
+    (
+        "gpt-4.1-mini",
+        ModelPrice {
+            input: 0.40,
+            cached_input: 0.10,
+            output: 1.60,
+        },
+    ),
+    (
+        "gpt-4.1",
+        ModelPrice {
+            input: 2.00,
+            cached_input: 0.50,
+            output: 8.00,
+        },
+    ),
+    (
+        "gpt-4o-mini",
+        ModelPrice {
+            input: 0.15,
+            cached_input: 0.075,
+            output: 0.60,
+        },
+    ),
+    (
+        "gpt-4o",
+        ModelPrice {
+            input: 2.50,
+            cached_input: 1.25,
+            output: 10.00,
+        },
+    ),
+    (
+        "gpt-5-nano",
+        ModelPrice {
+            input: 0.05,
+            cached_input: 0.005,
+            output: 0.40,
+        },
+    ),
+    (
+        "gpt-5-mini",
+        ModelPrice {
+            input: 0.25,
+            cached_input: 0.025,
+            output: 2.00,
+        },
+    ),
+    (
+        "gpt-5.1",
+        ModelPrice {
+            input: 1.25,
+            cached_input: 0.125,
+            output: 10.00,
+        },
+    ),
+    (
+        "gpt-5",
+        ModelPrice {
+            input: 1.25,
+            cached_input: 0.125,
+            output: 10.00,
+        },
+    ),
+    (
+        "o4-mini",
+        ModelPrice {
+            input: 1.10,
+            cached_input: 0.275,
+            output: 4.40,
+        },
+    ),
+    (
+        "o3",
+        ModelPrice {
+            input: 2.00,
+            cached_input: 0.50,
+            output: 8.00,
+        },
+    ),
+];
+
+pub fn price_for_model(model: &str) -> Option<ModelPrice> {
+    MODEL_PRICES
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| *price)
+}
+
+/// Cost of a call in `PRICE_CURRENCY`. Unknown models (local, fixtures) cost nothing.
+/// Reasoning tokens are already counted in `output_tokens` by both OpenAI APIs.
+pub fn compute_cost(model: &str, usage: &LlmUsage) -> f64 {
+    let Some(price) = price_for_model(model) else {
+        return 0.0;
+    };
+    let cached = usage.cached_input_tokens.clamp(0, usage.input_tokens) as f64;
+    let uncached = usage.input_tokens as f64 - cached;
+    (uncached * price.input
+        + cached * price.cached_input
+        + usage.output_tokens as f64 * price.output)
+        / 1_000_000.0
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenDetails {
+    #[serde(default)]
+    cached_tokens: Option<i32>,
+    #[serde(default)]
+    reasoning_tokens: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UsageBlock {
+    // Responses API
+    #[serde(default)]
+    input_tokens: Option<i32>,
+    #[serde(default)]
+    output_tokens: Option<i32>,
+    #[serde(default)]
+    input_tokens_details: Option<TokenDetails>,
+    #[serde(default)]
+    output_tokens_details: Option<TokenDetails>,
+    // Chat Completions API
+    #[serde(default)]
+    prompt_tokens: Option<i32>,
+    #[serde(default)]
+    completion_tokens: Option<i32>,
+    #[serde(default)]
+    prompt_tokens_details: Option<TokenDetails>,
+    #[serde(default)]
+    completion_tokens_details: Option<TokenDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BodyWithUsage {
+    usage: Option<UsageBlock>,
+}
+
+/// Reads the `usage` block of a Responses or Chat Completions body.
+pub fn extract_usage(body: &str) -> Option<LlmUsage> {
+    let usage = serde_json::from_str::<BodyWithUsage>(body).ok()?.usage?;
+    let input_details = usage
+        .input_tokens_details
+        .as_ref()
+        .or(usage.prompt_tokens_details.as_ref());
+    let output_details = usage
+        .output_tokens_details
+        .as_ref()
+        .or(usage.completion_tokens_details.as_ref());
+    Some(LlmUsage {
+        input_tokens: usage.input_tokens.or(usage.prompt_tokens).unwrap_or(0),
+        cached_input_tokens: input_details
+            .and_then(|details| details.cached_tokens)
+            .unwrap_or(0),
+        reasoning_tokens: output_details
+            .and_then(|details| details.reasoning_tokens)
+            .unwrap_or(0),
+        output_tokens: usage.output_tokens.or(usage.completion_tokens).unwrap_or(0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_prefix_wins() {
+        assert_eq!(
+            price_for_model("gpt-4.1-mini-2025-04-14").map(|price| price.input),
+            Some(0.40)
+        );
+        assert_eq!(
+            price_for_model("gpt-4.1").map(|price| price.input),
+            Some(2.00)
+        );
+        assert_eq!(
+            price_for_model("gpt-5.1").map(|price| price.output),
+            Some(10.00)
+        );
+        assert_eq!(price_for_model("llama3.1:8b"), None);
+    }
+
+    #[test]
+    fn extracts_responses_usage() {
+        let body = r#"{
+            "status": "completed",
+            "output": [],
+            "usage": {
+                "input_tokens": 1200,
+                "input_tokens_details": { "cached_tokens": 200 },
+                "output_tokens": 300,
+                "output_tokens_details": { "reasoning_tokens": 100 },
+                "total_tokens": 1500
+            }
+        }"#;
+        assert_eq!(
+            extract_usage(body),
+            Some(LlmUsage {
+                input_tokens: 1200,
+                cached_input_tokens: 200,
+                reasoning_tokens: 100,
+                output_tokens: 300,
+            })
+        );
+    }
+
+    #[test]
+    fn extracts_chat_completions_usage() {
+        let body = r#"{
+            "choices": [{ "message": { "content": "ok" } }],
+            "usage": {
+                "prompt_tokens": 50,
+                "completion_tokens": 20,
+                "completion_tokens_details": { "reasoning_tokens": 5 },
+                "total_tokens": 70
+            }
+        }"#;
+        assert_eq!(
+            extract_usage(body),
+            Some(LlmUsage {
+                input_tokens: 50,
+                cached_input_tokens: 0,
+                reasoning_tokens: 5,
+                output_tokens: 20,
+            })
+        );
+        assert_eq!(extract_usage(r#"{"choices": []}"#), None);
+    }
+
+    #[test]
+    fn computes_cost_with_cached_input_discount() {
+        let usage = LlmUsage {
+            input_tokens: 1_000_000,
+            cached_input_tokens: 500_000,
+            reasoning_tokens: 0,
+            output_tokens: 1_000_000,
+        };
+        let cost = compute_cost("gpt-4.1-mini", &usage);
+        assert!((cost - (0.20 + 0.05 + 1.60)).abs() < 1e-9);
+        assert_eq!(compute_cost("llama3.1", &usage), 0.0);
+    }
+}
@@ -0,0 +1,211 @@
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::environment;
+use crate::openai_handler::llm_retry;
+
+use super::{http_client, LlmProvider, LlmProviderError, LlmRawResponse, LlmRequest};
+
+#[derive(Debug, Serialize)]
+pub(super) struct ChatMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct ChatJsonSchema {
+    name: String,
+    schema: serde_json::Value,
+    strict: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct ChatResponseFormat {
+    #[serde(rename = "type")]
+    kind: String,
+    json_schema: ChatJsonSchema,
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct ChatCompletionsRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_completion_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning_effort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verbosity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ChatResponseFormat>,
+    stream: bool,
+}
+
+pub(super) fn build_chat_completions_url(base_url: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    if base.ends_with("/v1") {
+        format!("{}/chat/completions", base)
+    } else {
+        format!("{}/v1/chat/completions", base)
+    }
+}
+
+/// Builds a `/v1/chat/completions` body. Local servers only get the portable subset.
+pub(super) fn build_chat_completions_body(
+    request: &LlmRequest,
+    openai_extensions: bool,
+) -> ChatCompletionsRequest {
+    let reasoning_effort = if openai_extensions {
+        request
+            .reasoning_effort
+            .as_ref()
+            .map(|effort| effort.as_str().to_string())
+    } else {
+        None
+    };
+    ChatCompletionsRequest {
+        model: request.model.clone(),
+        messages: vec![
+            ChatMessage {
+                role: "system".to_string(),
+                content: request.system_prompt.clone(),
+            },
+            ChatMessage {
+                role: "user".to_string(),
+                content: request.user_prompt.clone(),
+            },
+        ],
+        max_completion_tokens: if openai_extensions { Some(22500) } else { None },
+        max_tokens: if openai_extensions { None } else { Some(22500) },
+        temperature: if reasoning_effort.is_some() {
+            None
+        } else {
+            Some(0.1)
+        },
+        reasoning_effort,
+        verbosity: if openai_extensions {
+            request
+                .verbosity
+                .as_ref()
+                .map(|verbosity| verbosity.as_str().to_string())
+        } else {
+            None
+        },
+        response_format: request.schema.clone().map(|schema| ChatResponseFormat {
+            kind: "json_schema".to_string(),
+            json_schema: ChatJsonSchema {
+                name: "reference_schema".to_string(),
+                schema,
+                strict: true,
+            },
+        }),
+        stream: false,
+    }
+}
+
+pub(super) async fn send_chat_completions(
+    base_url: &str,
+    api_key: Option<&str>,
+    body: &ChatCompletionsRequest,
+) -> Result<LlmRawResponse, LlmProviderError> {
+    let request_url = build_chat_completions_url(base_url);
+    let body_model = body.model.clone();
+    let request_json =
+        serde_json::to_string(body).unwrap_or_else(|e| format!("Failed to serialize request: {e}"));
+
+    let mut http_request = http_client()
+        .post(&request_url)
+        .header("Content-Type", "application/json")
+        .json(body);
+    if let Some(api_key) = api_key {
+        http_request = http_request.header("Authorization", format!("Bearer {}", api_key));
+    }
+    let resp = http_request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {request_url}: {e}"))?;
+
+    let http_status = resp.status().as_u16();
+    let retry_after = llm_retry::retry_after_from_headers(resp.headers());
+    let body = resp.text().await.unwrap_or_default();
+    Ok(LlmRawResponse {
+        request_url,
+        request_json,
+        http_status,
+        body,
+        retry_after,
+        model: body_model,
+        billable: true,
+    })
+}
+
+/// OpenAI `/v1/chat/completions` backend.
+pub struct OpenAiChatCompletionsProvider {
+    base_url: String,
+    api_key: String,
+}
+
+impl OpenAiChatCompletionsProvider {
+    pub fn new(base_url: String, api_key: String) -> Self {
+        Self { base_url, api_key }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(
+            environment::get_openai_api_base_url(),
+            environment::get_openai_api_key(),
+        )
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiChatCompletionsProvider {
+    fn name(&self) -> &'static str {
+        "openai_chat_completions"
+    }
+
+    async fn send(&self, request: &LlmRequest) -> Result<LlmRawResponse, LlmProviderError> {
+        let body = build_chat_completions_body(request, true);
+        send_chat_completions(&self.base_url, Some(&self.api_key), &body).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chat_completions_url_handles_optional_v1_suffix() {
+        assert_eq!(
+            build_chat_completions_url("http://localhost:11434/"),
+            "http://localhost:11434/v1/chat/completions"
+        );
+        assert_eq!(
+            build_chat_completions_url("https://api.openai.com/v1"),
+            "https://api.openai.com/v1/chat/completions"
+        );
+    }
+
+    #[test]
+    fn local_body_omits_openai_extensions() {
+        let request = LlmRequest {
+            model: "llama3.1".to_string(),
+            system_prompt: "system".to_string(),
+            user_prompt: "user".to_string(),
+            schema: Some(serde_json::json!({"type": "object"})),
+            reasoning_effort: Some(crate::openai_handler::GptReasoningEffort::Low),
+            verbosity: None,
+            display_name: None,
+            prompt: None,
+        };
+        let body = serde_json::to_value(build_chat_completions_body(&request, false)).unwrap();
+        assert!(body.get("reasoning_effort").is_none());
+        assert!(body.get("max_completion_tokens").is_none());
+        assert_eq!(body["max_tokens"], 22500);
+        assert_eq!(body["response_format"]["type"], "json_schema");
+    }
+}
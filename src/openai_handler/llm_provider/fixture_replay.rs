@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::environment;
+
+use super::{LlmProvider, LlmProviderError, LlmProviderKind, LlmRawResponse, LlmRequest};
+
+/// One recorded exchange. Either the raw provider `body` or a bare `output` is enough to replay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmFixture {
+    pub display_name: String,
+    pub prompt_hash: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub output: Option<serde_json::Value>,
+}
+
+impl LlmFixture {
+    fn replay_body(&self) -> Option<String> {
+        if let Some(body) = &self.body {
+            return Some(body.clone());
+        }
+        let content = match self.output.as_ref()? {
+            serde_json::Value::String(text) => text.clone(),
+            value => value.to_string(),
+        };
+        Some(
+            serde_json::json!({
+                "choices": [{ "message": { "content": content } }]
+            })
+            .to_string(),
+        )
+    }
+}
+
+enum FixtureStore {
+    Directory(PathBuf),
+    Memory(RwLock<HashMap<(String, String), LlmFixture>>),
+}
+
+/// Prompt hash under which a fixture answers every prompt of its step.
+pub const ANY_PROMPT_HASH: &str = "default";
+
+/// Deterministic backend serving recorded responses keyed by `display_name` + prompt hash.
+/// With a recorder, misses are forwarded to the live provider and written back.
+pub struct FixtureReplayProvider {
+    store: FixtureStore,
+    recorder: Option<Arc<dyn LlmProvider>>,
+}
+
+pub fn prompt_hash(system_prompt: &str, user_prompt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(system_prompt.as_bytes());
+    hasher.update(b"\n\n");
+    hasher.update(user_prompt.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn display_name_slug(display_name: &str) -> String {
+    let slug = display_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let slug = slug.trim_matches('_').to_string();
+    if slug.is_empty() {
+        "unnamed".to_string()
+    } else {
+        slug
+    }
+}
+
+impl FixtureReplayProvider {
+    pub fn from_directory(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            store: FixtureStore::Directory(directory.into()),
+            recorder: None,
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            store: FixtureStore::Memory(RwLock::new(HashMap::new())),
+            recorder: None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let provider = Self::from_directory(environment::get_llm_fixtures_dir());
+        match environment::get_llm_fixtures_record_with()
+            .as_deref()
+            .and_then(LlmProviderKind::from_db)
+        {
+            Some(LlmProviderKind::FixtureReplay) | None => provider,
+            Some(kind) => provider.with_recorder(kind.build()),
+        }
+    }
+
+    pub fn with_recorder(mut self, recorder: Arc<dyn LlmProvider>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Registers a fixture returning `output` for the given step and prompts.
+    pub fn with_output(
+        self,
+        display_name: &str,
+        system_prompt: &str,
+        user_prompt: &str,
+        output: serde_json::Value,
+    ) -> Self {
+        let fixture = LlmFixture {
+            display_name: display_name.to_string(),
+            prompt_hash: prompt_hash(system_prompt, user_prompt),
+            model: None,
+            body: None,
+            output: Some(output),
+        };
+        self.register(fixture)
+    }
+
+    /// Registers a fixture returning `output` for every prompt of the given step.
+    pub fn with_step_output(self, display_name: &str, output: serde_json::Value) -> Self {
+        let fixture = LlmFixture {
+            display_name: display_name.to_string(),
+            prompt_hash: ANY_PROMPT_HASH.to_string(),
+            model: None,
+            body: None,
+            output: Some(output),
+        };
+        self.register(fixture)
+    }
+
+    fn register(self, fixture: LlmFixture) -> Self {
+        if let Err(e) = self.save(&fixture) {
+            tracing::warn!(
+                target: "work_analyzer",
+                "llm_fixture_save_failed name={} error={}",
+                fixture.display_name,
+                e
+            );
+        }
+        self
+    }
+
+    fn fixture_path(directory: &Path, display_name: &str, prompt_hash: &str) -> PathBuf {
+        directory
+            .join(display_name_slug(display_name))
+            .join(format!("{prompt_hash}.json"))
+    }
+
+    fn load(&self, display_name: &str, prompt_hash: &str) -> Option<LlmFixture> {
+        match &self.store {
+            FixtureStore::Directory(directory) => {
+                let path = Self::fixture_path(directory, display_name, prompt_hash);
+                let content = std::fs::read_to_string(path).ok()?;
+                serde_json::from_str(&content).ok()
+            }
+            FixtureStore::Memory(fixtures) => fixtures
+                .read()
+                .ok()?
+                .get(&(display_name.to_string(), prompt_hash.to_string()))
+                .cloned(),
+        }
+    }
+
+    fn save(&self, fixture: &LlmFixture) -> Result<(), LlmProviderError> {
+        match &self.store {
+            FixtureStore::Directory(directory) => {
+                let path =
+                    Self::fixture_path(directory, &fixture.display_name, &fixture.prompt_hash);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, serde_json::to_string_pretty(fixture)?)?;
+            }
+            FixtureStore::Memory(fixtures) => {
+                fixtures
+                    .write()
+                    .map_err(|_| "Fixture store lock poisoned".to_string())?
+                    .insert(
+                        (fixture.display_name.clone(), fixture.prompt_hash.clone()),
+                        fixture.clone(),
+                    );
+            }
+        }
+        Ok(())
+    }
+
+    fn describe_location(&self, display_name: &str, prompt_hash: &str) -> String {
+        match &self.store {
+            FixtureStore::Directory(directory) => {
+                Self::fixture_path(directory, display_name, prompt_hash)
+                    .display()
+                    .to_string()
+            }
+            FixtureStore::Memory(_) => "memory".to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for FixtureReplayProvider {
+    fn name(&self) -> &'static str {
+        "fixture_replay"
+    }
+
+    async fn send(&self, request: &LlmRequest) -> Result<LlmRawResponse, LlmProviderError> {
+        let display_name = request.display_name.as_deref().unwrap_or("");
+        let prompt_hash = prompt_hash(&request.system_prompt, &request.user_prompt);
+        let request_url = format!(
+            "fixture://{}/{}",
+            display_name_slug(display_name),
+            prompt_hash
+        );
+
+        if let Some(fixture) = self
+            .load(display_name, &prompt_hash)
+            .or_else(|| self.load(display_name, ANY_PROMPT_HASH))
+        {
+            if let Some(body) = fixture.replay_body() {
+                return Ok(LlmRawResponse {
+                    request_url,
+                    request_json: String::new(),
+                    http_status: 200,
+                    body,
+                    retry_after: None,
+                    model: fixture.model.unwrap_or_else(|| request.model.clone()),
+                    billable: false,
+                });
+            }
+        }
+
+        let Some(recorder) = &self.recorder else {
+            return Err(format!(
+                "No recorded LLM response for name={} prompt_hash={} (looked in {})",
+                display_name,
+                prompt_hash,
+                self.describe_location(display_name, &prompt_hash)
+            )
+            .into());
+        };
+
+        let response = recorder.send(request).await?;
+        if response.is_success() {
+            self.save(&LlmFixture {
+                display_name: display_name.to_string(),
+                prompt_hash,
+                model: Some(response.model.clone()),
+                body: Some(response.body.clone()),
+                output: None,
+            })?;
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(user_prompt: &str) -> LlmRequest {
+        LlmRequest {
+            model: "gpt-4.1-mini".to_string(),
+            system_prompt: "system".to_string(),
+            user_prompt: user_prompt.to_string(),
+            schema: None,
+            reasoning_effort: None,
+            verbosity: None,
+            display_name: Some("Mirror / Header Extraction".to_string()),
+            prompt: None,
+        }
+    }
+
+    #[test]
+    fn prompt_hash_is_deterministic_and_prompt_sensitive() {
+        assert_eq!(prompt_hash("a", "b"), prompt_hash("a", "b"));
+        assert_ne!(prompt_hash("a", "b"), prompt_hash("a", "c"));
+        assert_eq!(
+            display_name_slug("Mirror / Header Extraction"),
+            "mirror___header_extraction"
+        );
+    }
+
+    #[tokio::test]
+    async fn replays_registered_output() {
+        let provider = FixtureReplayProvider::in_memory().with_output(
+            "Mirror / Header Extraction",
+            "system",
+            "trace",
+            serde_json::json!({ "title": "Hello" }),
+        );
+        let response = provider.send(&request("trace")).await.unwrap();
+        assert_eq!(response.http_status, 200);
+        assert!(!response.billable);
+        let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(
+            body["choices"][0]["message"]["content"],
+            r#"{"title":"Hello"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn step_output_answers_any_prompt() {
+        let provider = FixtureReplayProvider::in_memory()
+            .with_step_output("Mirror / Header Extraction", serde_json::json!("fallback"));
+        let response = provider.send(&request("anything")).await.unwrap();
+        assert!(response.body.contains("fallback"));
+    }
+
+    #[tokio::test]
+    async fn missing_fixture_is_an_error() {
+        let provider = FixtureReplayProvider::in_memory();
+        let error = provider.send(&request("unknown")).await.unwrap_err();
+        assert!(error.to_string().contains("No recorded LLM response"));
+    }
+
+    #[tokio::test]
+    async fn records_misses_through_the_recorder() {
+        let live = Arc::new(FixtureReplayProvider::in_memory().with_output(
+            "Mirror / Header Extraction",
+            "system",
+            "trace",
+            serde_json::json!("recorded"),
+        ));
+        let directory = std::env::temp_dir().join(format!("llm_fixtures_{}", uuid::Uuid::new_v4()));
+        let recording = FixtureReplayProvider::from_directory(&directory).with_recorder(live);
+        recording.send(&request("trace")).await.unwrap();
+
+        let replay = FixtureReplayProvider::from_directory(&directory);
+        let response = replay.send(&request("trace")).await.unwrap();
+        assert!(response.body.contains("recorded"));
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+
+use crate::environment;
+
+use super::chat_completions::{build_chat_completions_body, send_chat_completions};
+use super::{LlmProvider, LlmProviderError, LlmRawResponse, LlmRequest};
+
+/// Ollama / llama.cpp server backend, through their OpenAI-compatible chat endpoint.
+pub struct LocalLlmProvider {
+    base_url: String,
+    model_override: Option<String>,
+}
+
+impl LocalLlmProvider {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            base_url,
+            model_override: None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self {
+            base_url: environment::get_local_llm_base_url(),
+            model_override: environment::get_local_llm_model(),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for LocalLlmProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn send(&self, request: &LlmRequest) -> Result<LlmRawResponse, LlmProviderError> {
+        // Pipeline steps ask for OpenAI model names; a local server only knows what it has pulled.
+        let mut request = request.clone();
+        if let Some(model) = &self.model_override {
+            request.model = model.clone();
+        }
+        let body = build_chat_completions_body(&request, false);
+        let mut response = send_chat_completions(&self.base_url, None, &body).await?;
+        response.billable = false;
+        Ok(response)
+    }
+}
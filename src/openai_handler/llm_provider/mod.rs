@@ -0,0 +1,252 @@
+pub mod chat_completions;
+pub mod fixture_replay;
+pub mod local;
+pub mod openai_responses;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::environment;
+use crate::openai_handler::gpt_responses_handler::{GptReasoningEffort, GptVerbosity};
+
+pub use chat_completions::OpenAiChatCompletionsProvider;
+pub use fixture_replay::FixtureReplayProvider;
+pub use local::LocalLlmProvider;
+pub use openai_responses::OpenAiResponsesProvider;
+
+pub type LlmProviderError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Provider-agnostic description of one LLM call.
+#[derive(Clone)]
+pub struct LlmRequest {
+    pub model: String,
+    pub system_prompt: String,
+    pub user_prompt: String,
+    pub schema: Option<serde_json::Value>,
+    pub reasoning_effort: Option<GptReasoningEffort>,
+    pub verbosity: Option<GptVerbosity>,
+    pub display_name: Option<String>,
+    pub prompt: Option<PromptRef>,
+}
+
+/// Registry prompt a request was built from, stamped on the persisted `LlmCall`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptRef {
+    pub prompt_id: String,
+    pub version: i32,
+}
+
+/// What a provider hands back: the raw exchange, parsed later by the handler.
+#[derive(Debug, Clone)]
+pub struct LlmRawResponse {
+    pub request_url: String,
+    pub request_json: String,
+    pub http_status: u16,
+    pub body: String,
+    pub retry_after: Option<Duration>,
+    /// Model that actually answered, which may differ from the requested one.
+    pub model: String,
+    /// False for local servers and replayed fixtures, which are priced at zero.
+    pub billable: bool,
+}
+
+impl LlmRawResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.http_status)
+    }
+}
+
+static HTTP_CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Shared HTTP client so connections are pooled across calls; timeouts are set per attempt.
+pub(crate) fn http_client() -> &'static Client {
+    HTTP_CLIENT.get_or_init(Client::new)
+}
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn send(&self, request: &LlmRequest) -> Result<LlmRawResponse, LlmProviderError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmProviderKind {
+    OpenAiResponses,
+    OpenAiChatCompletions,
+    Local,
+    FixtureReplay,
+}
+
+impl LlmProviderKind {
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "openai_responses" | "openai" => Some(Self::OpenAiResponses),
+            "openai_chat_completions" | "chat_completions" => Some(Self::OpenAiChatCompletions),
+            "local" | "ollama" | "llama_cpp" => Some(Self::Local),
+            "fixture_replay" | "fixture" => Some(Self::FixtureReplay),
+            _ => None,
+        }
+    }
+
+    pub fn to_db(self) -> &'static str {
+        match self {
+            Self::OpenAiResponses => "openai_responses",
+            Self::OpenAiChatCompletions => "openai_chat_completions",
+            Self::Local => "local",
+            Self::FixtureReplay => "fixture_replay",
+        }
+    }
+
+    pub fn build(self) -> Arc<dyn LlmProvider> {
+        match self {
+            Self::OpenAiResponses => Arc::new(OpenAiResponsesProvider::from_env()),
+            Self::OpenAiChatCompletions => Arc::new(OpenAiChatCompletionsProvider::from_env()),
+            Self::Local => Arc::new(LocalLlmProvider::from_env()),
+            Self::FixtureReplay => Arc::new(FixtureReplayProvider::from_env()),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let configured = environment::get_llm_provider();
+        Self::from_db(&configured).unwrap_or_else(|| {
+            tracing::warn!(
+                target: "work_analyzer",
+                "llm_provider_unknown value={} fallback={}",
+                configured,
+                Self::OpenAiResponses.to_db()
+            );
+            Self::OpenAiResponses
+        })
+    }
+}
+
+/// Provider choice for a whole analysis, with optional overrides keyed by step display name.
+/// `model`, when set, replaces the model requested by every step (used by lens replays).
+#[derive(Clone)]
+pub struct LlmProviderSelection {
+    pub default: Arc<dyn LlmProvider>,
+    pub by_step: HashMap<String, Arc<dyn LlmProvider>>,
+    pub model: Option<String>,
+}
+
+impl LlmProviderSelection {
+    pub fn new(default: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            default,
+            by_step: HashMap::new(),
+            model: None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(LlmProviderKind::from_env().build())
+    }
+
+    pub fn with_step_provider(
+        mut self,
+        display_name: impl Into<String>,
+        provider: Arc<dyn LlmProvider>,
+    ) -> Self {
+        self.by_step.insert(display_name.into(), provider);
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn provider_for(&self, display_name: Option<&str>) -> Arc<dyn LlmProvider> {
+        display_name
+            .and_then(|name| self.by_step.get(name))
+            .cloned()
+            .unwrap_or_else(|| self.default.clone())
+    }
+}
+
+tokio::task_local! {
+    static LLM_PROVIDER_SELECTION: LlmProviderSelection;
+}
+
+/// Runs `future` with `selection` as the provider choice for every LLM call it makes.
+pub async fn with_selection<F>(selection: LlmProviderSelection, future: F) -> F::Output
+where
+    F: Future,
+{
+    LLM_PROVIDER_SELECTION.scope(selection, future).await
+}
+
+/// Resolves the provider for a step: the scoped analysis selection first, then `LLM_PROVIDER`.
+pub fn resolve_provider(display_name: Option<&str>) -> Arc<dyn LlmProvider> {
+    LLM_PROVIDER_SELECTION
+        .try_with(|selection| selection.provider_for(display_name))
+        .unwrap_or_else(|_| LlmProviderKind::from_env().build())
+}
+
+/// Resolves the model for a step: the scoped analysis override if any, else the step's own model.
+pub fn resolve_model(requested: String) -> String {
+    LLM_PROVIDER_SELECTION
+        .try_with(|selection| selection.model.clone())
+        .ok()
+        .flatten()
+        .unwrap_or(requested)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_kind_round_trips_through_db_value() {
+        for kind in [
+            LlmProviderKind::OpenAiResponses,
+            LlmProviderKind::OpenAiChatCompletions,
+            LlmProviderKind::Local,
+            LlmProviderKind::FixtureReplay,
+        ] {
+            assert_eq!(LlmProviderKind::from_db(kind.to_db()), Some(kind));
+        }
+        assert_eq!(
+            LlmProviderKind::from_db("ollama"),
+            Some(LlmProviderKind::Local)
+        );
+        assert_eq!(LlmProviderKind::from_db("unknown"), None);
+    }
+
+    #[tokio::test]
+    async fn scoped_selection_prefers_step_override() {
+        let default: Arc<dyn LlmProvider> = Arc::new(FixtureReplayProvider::in_memory());
+        let step: Arc<dyn LlmProvider> =
+            Arc::new(LocalLlmProvider::new("http://localhost:11434".to_string()));
+        let selection = LlmProviderSelection::new(default).with_step_provider("Matching", step);
+
+        let (matching, other) = with_selection(selection, async {
+            (
+                resolve_provider(Some("Matching")).name(),
+                resolve_provider(Some("Other")).name(),
+            )
+        })
+        .await;
+        assert_eq!(matching, "local");
+        assert_eq!(other, "fixture_replay");
+    }
+
+    #[tokio::test]
+    async fn scoped_model_override_replaces_requested_model() {
+        let default: Arc<dyn LlmProvider> = Arc::new(FixtureReplayProvider::in_memory());
+        let selection = LlmProviderSelection::new(default).with_model("gpt-5-mini");
+
+        let overridden = with_selection(selection, async {
+            resolve_model("gpt-4.1-mini".to_string())
+        })
+        .await;
+        assert_eq!(overridden, "gpt-5-mini");
+        assert_eq!(resolve_model("gpt-4.1-mini".to_string()), "gpt-4.1-mini");
+    }
+}
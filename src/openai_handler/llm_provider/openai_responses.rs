@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::environment;
+use crate::openai_handler::llm_retry;
+
+use super::{http_client, LlmProvider, LlmProviderError, LlmRawResponse, LlmRequest};
+
+#[derive(Debug, Serialize)]
+struct GPTReasoning {
+    effort: String,
+}
+
+#[derive(Debug, Serialize)]
+struct GPTTextFormat {
+    #[serde(rename = "type")]
+    kind: String,
+    name: String,
+    schema: serde_json::Value,
+    strict: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct GPTText {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<GPTTextFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verbosity: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct GPTRequest {
+    model: String,
+    input: Vec<GPTMessage>,
+    max_output_tokens: u32,
+    text: GPTText,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning: Option<GPTReasoning>,
+}
+
+#[derive(Debug, Serialize)]
+struct GPTMessage {
+    role: String,
+    content: String,
+}
+
+/// OpenAI `/v1/responses` backend.
+pub struct OpenAiResponsesProvider {
+    base_url: String,
+    api_key: String,
+}
+
+impl OpenAiResponsesProvider {
+    pub fn new(base_url: String, api_key: String) -> Self {
+        Self { base_url, api_key }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(
+            environment::get_openai_api_base_url(),
+            environment::get_openai_api_key(),
+        )
+    }
+}
+
+fn build_responses_url(base_url: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    if base.ends_with("/v1") {
+        format!("{}/responses", base)
+    } else {
+        format!("{}/v1/responses", base)
+    }
+}
+
+fn build_request_body(request: &LlmRequest) -> GPTRequest {
+    let reasoning = request
+        .reasoning_effort
+        .as_ref()
+        .map(|effort| GPTReasoning {
+            effort: effort.as_str().to_string(),
+        });
+    GPTRequest {
+        model: request.model.clone(),
+        input: vec![
+            GPTMessage {
+                role: "system".to_string(),
+                content: request.system_prompt.clone(),
+            },
+            GPTMessage {
+                role: "user".to_string(),
+                content: request.user_prompt.clone(),
+            },
+        ],
+        max_output_tokens: 22500,
+        temperature: if reasoning.is_some() { None } else { Some(0.1) },
+        reasoning,
+        text: GPTText {
+            format: request.schema.clone().map(|schema| GPTTextFormat {
+                kind: "json_schema".to_string(),
+                name: "reference_schema".to_string(),
+                schema,
+                strict: true,
+            }),
+            verbosity: request
+                .verbosity
+                .as_ref()
+                .map(|verbosity| verbosity.as_str().to_string()),
+        },
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiResponsesProvider {
+    fn name(&self) -> &'static str {
+        "openai_responses"
+    }
+
+    async fn send(&self, request: &LlmRequest) -> Result<LlmRawResponse, LlmProviderError> {
+        let request_url = build_responses_url(&self.base_url);
+        let body = build_request_body(request);
+        let request_json = serde_json::to_string(&body)
+            .unwrap_or_else(|e| format!("Failed to serialize request: {e}"));
+
+        let resp = http_client()
+            .post(&request_url)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request to GPT: {e}"))?;
+
+        let http_status = resp.status().as_u16();
+        let retry_after = llm_retry::retry_after_from_headers(resp.headers());
+        let body = resp.text().await.unwrap_or_default();
+        Ok(LlmRawResponse {
+            request_url,
+            request_json,
+            http_status,
+            body,
+            retry_after,
+            model: request.model.clone(),
+            billable: true,
+        })
+    }
+}
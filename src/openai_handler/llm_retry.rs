@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::environment;
+
+/// How many times and how patiently a logical LLM call is attempted.
+#[derive(Debug, Clone)]
+pub struct LlmRetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub request_timeout: Duration,
+}
+
+impl LlmRetryPolicy {
+    pub fn from_env() -> Self {
+        Self {
+            max_attempts: environment::get_llm_max_attempts().max(1),
+            base_delay: Duration::from_millis(environment::get_llm_retry_base_delay_ms()),
+            max_delay: Duration::from_millis(environment::get_llm_retry_max_delay_ms()),
+            request_timeout: Duration::from_secs(environment::get_llm_request_timeout_seconds()),
+        }
+    }
+
+    pub fn no_retry(request_timeout: Duration) -> Self {
+        Self {
+            max_attempts: 1,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            request_timeout,
+        }
+    }
+
+    /// Exponential backoff with equal jitter: half the capped delay, plus a random half.
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let capped = self
+            .base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay);
+        let half = capped / 2;
+        let jitter_ms = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter_ms)
+    }
+
+    /// Delay before the attempt following `attempt`; a server `Retry-After` wins over backoff
+    /// and is waited in full. `None` when it asks for longer than `max_delay`: give up instead.
+    pub fn delay_before_retry(
+        &self,
+        attempt: u32,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        match retry_after {
+            Some(retry_after) if retry_after > self.max_delay => None,
+            Some(retry_after) => Some(retry_after),
+            None => Some(self.backoff_delay(attempt)),
+        }
+    }
+}
+
+pub fn is_retryable_status(http_status: u16) -> bool {
+    matches!(http_status, 408 | 429 | 500..=599)
+}
+
+/// Reads `retry-after-ms` (OpenAI) or `Retry-After` in seconds or HTTP-date form.
+pub fn retry_after_from_headers(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(ms) = headers
+        .get("retry-after-ms")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<f64>().ok())
+    {
+        return Some(Duration::from_millis(ms.max(0.0) as u64));
+    }
+    let value = headers.get("retry-after")?.to_str().ok()?.trim();
+    parse_retry_after(value)
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some(Duration::from_millis((seconds.max(0.0) * 1000.0) as u64));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let remaining = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(remaining.to_std().unwrap_or(Duration::ZERO))
+}
+
+static MODEL_LIMITERS: OnceLock<Mutex<HashMap<String, Arc<Semaphore>>>> = OnceLock::new();
+
+/// Process-wide cap on in-flight requests per model, shared by every lens and pipeline step.
+pub async fn acquire_model_permit(model: &str) -> OwnedSemaphorePermit {
+    let semaphore = {
+        let mut limiters = MODEL_LIMITERS
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        limiters
+            .entry(model.to_string())
+            .or_insert_with(|| {
+                Arc::new(Semaphore::new(
+                    environment::get_llm_max_concurrency_per_model().max(1),
+                ))
+            })
+            .clone()
+    };
+    semaphore
+        .acquire_owned()
+        .await
+        .expect("LLM model semaphore is never closed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> LlmRetryPolicy {
+        LlmRetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            request_timeout: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let policy = policy();
+        for _ in 0..20 {
+            let first = policy.backoff_delay(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let third = policy.backoff_delay(3);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+            let tenth = policy.backoff_delay(10);
+            assert!(tenth >= Duration::from_millis(500) && tenth <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn retry_after_overrides_backoff_within_cap() {
+        let policy = policy();
+        assert_eq!(
+            policy.delay_before_retry(1, Some(Duration::from_millis(300))),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(
+            policy.delay_before_retry(1, Some(Duration::from_millis(1000))),
+            Some(Duration::from_millis(1000))
+        );
+    }
+
+    #[test]
+    fn retry_after_beyond_cap_gives_up() {
+        assert_eq!(
+            policy().delay_before_retry(1, Some(Duration::from_secs(60))),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_retry_after_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", "2".parse().unwrap());
+        assert_eq!(
+            retry_after_from_headers(&headers),
+            Some(Duration::from_secs(2))
+        );
+        headers.insert("retry-after-ms", "150".parse().unwrap());
+        assert_eq!(
+            retry_after_from_headers(&headers),
+            Some(Duration::from_millis(150))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn retryable_statuses() {
+        assert!(is_retryable_status(429));
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(400));
+        assert!(!is_retryable_status(409));
+        assert!(!is_retryable_status(200));
+    }
+}
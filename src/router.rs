@@ -12,11 +12,13 @@ use tower_http::{
 };
 
 use crate::entities_v2::{
-    album, analysis_summary, asset, content_report, device, document, element,
+    account_export, album, analysis_config, analysis_event, analysis_summary, analysis_unlock,
+    asset, bio_profile, content_report, data_key, device, document, element, embedding,
     error::{ErrorType, PpdcError},
-    feed, journal, journal_share_link, journal_sharing_policy, landmark, landscape_analysis, lens,
-    llm_call, mailer, message, post, post_grant, reference, relationship, trace, trace_mirror,
-    trace_search, transcription, url_preview, usage_event, user, user_post_state,
+    feed, journal, journal_export, journal_import, journal_share_link, journal_sharing_policy,
+    landmark, landmark_curation, landscape_analysis, landscape_diff, landscape_graph, lens,
+    llm_call, mailer, message, post, post_grant, prompt_version, reference, relationship, trace,
+    trace_mirror, trace_search, transcription, url_preview, usage_event, user, user_post_state,
     user_secure_action,
 };
 use crate::{environment, sessions_service};
@@ -68,6 +70,11 @@ pub fn create_router() -> Router {
                 .get(landscape_analysis::get_last_analysis_route),
         )
         .route("/:id/lens", get(lens::get_user_lenses_route))
+        .route("/:id/landmarks", get(landmark::get_user_landmarks_route))
+        .route(
+            "/:id/bio_profile",
+            get(bio_profile::get_user_bio_profile_route),
+        )
         .route("/:id/traces", get(trace::get_all_traces_for_user_route))
         .route("/:id/journals", get(journal::get_user_journals_route))
         .route("/:id/heatmaps", get(trace::get_user_heatmap_route))
@@ -90,7 +97,8 @@ pub fn create_router() -> Router {
         .route(
             "/recent_activity",
             get(user::get_admin_recent_user_activity_route),
-        );
+        )
+        .route("/llm_costs", get(user::get_admin_llm_costs_route));
 
     let admin_router = Router::new()
         .nest("/analytics", admin_analytics_router)
@@ -110,6 +118,27 @@ pub fn create_router() -> Router {
             "/service_users/:id",
             get(user::get_admin_service_user_route).put(user::put_admin_service_user_route),
         )
+        .route("/prompts", get(prompt_version::get_admin_prompts_route))
+        .route(
+            "/prompts/import",
+            post(prompt_version::post_admin_prompts_import_route),
+        )
+        .route(
+            "/prompts/:prompt_id/versions",
+            post(prompt_version::post_admin_prompt_version_route),
+        )
+        .route(
+            "/prompts/:prompt_id/versions/:version",
+            get(prompt_version::get_admin_prompt_version_route),
+        )
+        .route(
+            "/prompts/:prompt_id/activate",
+            post(prompt_version::post_admin_prompt_activate_route),
+        )
+        .route(
+            "/data_keys/rotate",
+            post(data_key::post_admin_data_keys_rotate_route),
+        )
         .layer(from_fn(sessions_service::auth_middleware_custom));
 
     let traces_router = Router::new()
@@ -259,6 +288,10 @@ pub fn create_router() -> Router {
             delete(journal_share_link::delete_journal_share_link_route),
         )
         .route("/:id/exports", post(journal::post_journal_export_route))
+        .route(
+            "/:id/exports/:job_id",
+            get(journal_export::get_journal_export_route),
+        )
         .route(
             "/:id/sharing_policies",
             get(journal_sharing_policy::get_journal_sharing_policies_route)
@@ -279,7 +312,20 @@ pub fn create_router() -> Router {
             post(journal_sharing_policy::post_journal_sharing_policy_history_decision_route),
         )
         .route("/:id/traces", get(trace::get_traces_for_journal_route))
-        .route("/:id/imports", post(journal::post_journal_import_route))
+        .route(
+            "/:id/imports",
+            get(journal_import::get_journal_imports_route)
+                .post(journal::post_journal_import_route)
+                .layer(DefaultBodyLimit::max(200 * 1024 * 1024)),
+        )
+        .route(
+            "/:id/imports/:batch_id",
+            get(journal_import::get_journal_import_route),
+        )
+        .route(
+            "/:id/imports/:batch_id/rollback",
+            post(journal_import::post_journal_import_rollback_route),
+        )
         .layer(from_fn(sessions_service::auth_middleware_custom));
 
     let relationships_router = Router::new()
@@ -314,6 +360,7 @@ pub fn create_router() -> Router {
             get(landscape_analysis::get_current_lens_analysis_route)
                 .post(landscape_analysis::post_analysis_route),
         )
+        .route("/recaps", post(landscape_analysis::post_custom_recap_route))
         .route(
             "/:id",
             delete(landscape_analysis::delete_analysis_route)
@@ -329,6 +376,14 @@ pub fn create_router() -> Router {
             get(landscape_analysis::get_landmarks_route),
         )
         .route("/:id/elements", get(landscape_analysis::get_elements_route))
+        .route(
+            "/:id/diff/:other_id",
+            get(landscape_diff::get_analysis_diff_route),
+        )
+        .route(
+            "/:id/config",
+            get(analysis_config::get_analysis_config_route),
+        )
         .route(
             "/:id/traces",
             get(landscape_analysis::get_analysis_traces_route),
@@ -347,6 +402,14 @@ pub fn create_router() -> Router {
             "/:id/llm_calls",
             get(llm_call::get_llm_calls_by_analysis_id_route),
         )
+        .route(
+            "/:id/events",
+            get(analysis_event::get_analysis_events_route),
+        )
+        .route(
+            "/:id/events/stream",
+            get(analysis_event::get_analysis_events_stream_route),
+        )
         .layer(from_fn(sessions_service::auth_middleware_custom));
     let internal_router = Router::new()
         .route(
@@ -360,11 +423,51 @@ pub fn create_router() -> Router {
         .route(
             "/process_pending_emails",
             post(mailer::post_process_pending_emails_route),
+        )
+        .route(
+            "/backfill_embeddings",
+            post(embedding::post_backfill_embeddings_route),
         );
     let internal_router = internal_router.route(
         "/generate_shared_journal_daily_digests",
         post(mailer::post_generate_shared_journal_daily_digests_route),
     );
+    let account_exports_router = Router::new()
+        .route(
+            "/",
+            get(account_export::get_account_exports_route)
+                .post(account_export::post_account_export_route),
+        )
+        .route("/:id", get(account_export::get_account_export_route))
+        .route(
+            "/:id/audit",
+            get(account_export::get_account_export_audit_route),
+        )
+        .route(
+            "/:id/download_link",
+            post(account_export::post_account_export_download_link_route),
+        )
+        .layer(from_fn(sessions_service::auth_middleware_custom));
+
+    let analysis_unlock_sessions_router = Router::new()
+        .route(
+            "/",
+            get(analysis_unlock::get_analysis_unlock_sessions_route)
+                .post(analysis_unlock::post_analysis_unlock_session_route),
+        )
+        .route(
+            "/public_key",
+            get(analysis_unlock::get_analysis_unlock_public_key_route),
+        )
+        .route(
+            "/:id",
+            delete(analysis_unlock::delete_analysis_unlock_session_route),
+        )
+        .route(
+            "/:id/audit",
+            get(analysis_unlock::get_analysis_unlock_session_audit_route),
+        )
+        .layer(from_fn(sessions_service::auth_middleware_custom));
     let analysis_summaries_router = Router::new()
         .route(
             "/:id",
@@ -373,7 +476,23 @@ pub fn create_router() -> Router {
         )
         .layer(from_fn(sessions_service::auth_middleware_custom));
     let landmarks_router = Router::new()
-        .route("/:id", get(landmark::get_landmark_route))
+        .route(
+            "/merge",
+            post(landmark_curation::post_merge_landmarks_route),
+        )
+        .route(
+            "/:id",
+            get(landmark::get_landmark_route).patch(landmark_curation::patch_landmark_route),
+        )
+        .route(
+            "/:id/split",
+            post(landmark_curation::post_split_landmark_route),
+        )
+        .route(
+            "/:id/curations",
+            get(landmark_curation::get_landmark_curations_route),
+        )
+        .route("/:id/timeline", get(landmark::get_landmark_timeline_route))
         .layer(from_fn(sessions_service::auth_middleware_custom));
     let elements_router = Router::new()
         .route("/", get(element::get_elements_route))
@@ -389,6 +508,12 @@ pub fn create_router() -> Router {
             get(lens::get_lens_week_events_route),
         )
         .route("/:id/retry", post(lens::post_lens_retry_route))
+        .route("/:id/replay", post(lens::post_lens_replay_route))
+        .route(
+            "/:id/diff/:other_id",
+            get(landscape_diff::get_lens_diff_route),
+        )
+        .route("/:id/graph", get(landscape_graph::get_lens_graph_route))
         .route(
             "/:id",
             delete(lens::delete_lens_route).put(lens::put_lens_route),
@@ -397,6 +522,7 @@ pub fn create_router() -> Router {
 
     let llm_calls_router = Router::new()
         .route("/", get(llm_call::get_llm_calls_route))
+        .route("/costs", get(llm_call::get_llm_call_costs_route))
         .route("/:id", get(llm_call::get_llm_call_route))
         .layer(from_fn(sessions_service::auth_middleware_custom));
     let messages_router = Router::new()
@@ -517,6 +643,8 @@ pub fn create_router() -> Router {
         .nest("/analysis", analysis_router)
         .nest("/internal", internal_router)
         .nest("/analysis_summaries", analysis_summaries_router)
+        .nest("/analysis_unlock_sessions", analysis_unlock_sessions_router)
+        .nest("/account_exports", account_exports_router)
         .nest("/lens", lens_router)
         .nest("/llm_calls", llm_calls_router)
         .nest("/messages", messages_router)
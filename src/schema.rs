@@ -4,6 +4,39 @@ pub mod sql_types {
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "tsvector", schema = "pg_catalog"))]
     pub struct Tsvector;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "vector"))]
+    pub struct Vector;
+}
+
+diesel::table! {
+    account_export_audit_events (id) {
+        id -> Uuid,
+        export_id -> Uuid,
+        user_id -> Uuid,
+        event_type -> Text,
+        detail -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    account_exports (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        status -> Text,
+        job_id -> Nullable<Uuid>,
+        asset_id -> Nullable<Uuid>,
+        file_name -> Nullable<Text>,
+        size_bytes -> Nullable<Int8>,
+        error -> Nullable<Text>,
+        link_expires_at -> Nullable<Timestamp>,
+        outbound_email_id -> Nullable<Uuid>,
+        completed_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
 }
 
 diesel::table! {
@@ -32,6 +65,30 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    analysis_configs (analysis_id) {
+        analysis_id -> Uuid,
+        model -> Text,
+        matching_confidence_threshold -> Float4,
+        feature_flag_run_high_level_analysis -> Bool,
+        llm_provider -> Text,
+        is_override -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    analysis_events (id) {
+        id -> Uuid,
+        analysis_id -> Uuid,
+        seq -> Int8,
+        event_type -> Text,
+        payload -> Jsonb,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     analysis_summaries (id) {
         id -> Uuid,
@@ -49,6 +106,35 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    analysis_unlock_audit_events (id) {
+        id -> Uuid,
+        session_id -> Uuid,
+        user_id -> Uuid,
+        event_type -> Text,
+        trace_id -> Nullable<Uuid>,
+        analysis_id -> Nullable<Uuid>,
+        detail -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    analysis_unlock_sessions (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        lens_id -> Uuid,
+        key_id -> Text,
+        status -> Text,
+        consent_derived_context -> Bool,
+        expires_at -> Timestamp,
+        closed_at -> Nullable<Timestamp>,
+        decrypted_trace_count -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     assets (id) {
         id -> Uuid,
@@ -66,6 +152,41 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    background_jobs (id) {
+        id -> Uuid,
+        job_type -> Text,
+        payload -> Jsonb,
+        status -> Text,
+        dedupe_key -> Nullable<Text>,
+        attempts -> Int4,
+        max_attempts -> Int4,
+        timeout_seconds -> Int4,
+        run_at -> Timestamp,
+        lock_owner -> Nullable<Uuid>,
+        lock_until -> Nullable<Timestamp>,
+        last_error -> Nullable<Text>,
+        result -> Nullable<Jsonb>,
+        started_at -> Nullable<Timestamp>,
+        finished_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    bio_profiles (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        version -> Int4,
+        trace_id -> Uuid,
+        analysis_id -> Uuid,
+        summary -> Text,
+        profile -> Jsonb,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     content_reports (id) {
         id -> Uuid,
@@ -91,6 +212,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    data_keys (id) {
+        id -> Uuid,
+        user_id -> Nullable<Uuid>,
+        master_key_id -> Text,
+        wrapped_key -> Text,
+        status -> Text,
+        created_at -> Timestamp,
+        retired_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     devices (id) {
         id -> Uuid,
@@ -178,6 +311,54 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    embeddings (id) {
+        id -> Uuid,
+        entity_type -> Text,
+        entity_id -> Uuid,
+        model -> Text,
+        content_hash -> Text,
+        vector -> Array<Float4>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    job_schedules (job_type) {
+        job_type -> Text,
+        interval_seconds -> Int4,
+        next_run_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    journal_import_batch_traces (batch_id, trace_id) {
+        batch_id -> Uuid,
+        trace_id -> Uuid,
+        block_index -> Int4,
+        content_hash -> Text,
+    }
+}
+
+diesel::table! {
+    journal_import_batches (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        journal_id -> Uuid,
+        status -> Text,
+        file_name -> Nullable<Text>,
+        total_blocks -> Int4,
+        created_count -> Int4,
+        skipped_count -> Int4,
+        failed_count -> Int4,
+        created_at -> Timestamp,
+        rolled_back_at -> Nullable<Timestamp>,
+        format -> Text,
+    }
+}
+
 diesel::table! {
     journal_sharing_policies (id) {
         id -> Uuid,
@@ -226,6 +407,28 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    landmark_aliases (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        landmark_id -> Uuid,
+        title -> Text,
+        normalized_title -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    landmark_curations (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        landmark_id -> Uuid,
+        action -> Text,
+        details -> Jsonb,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     landmark_relations (id) {
         id -> Uuid,
@@ -237,6 +440,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    landmark_versions (id) {
+        id -> Uuid,
+        landmark_id -> Uuid,
+        landscape_analysis_id -> Uuid,
+        title -> Text,
+        subtitle -> Text,
+        content -> Text,
+        landmark_type -> Text,
+        maturing_state -> Text,
+        related_elements_count -> Int4,
+        last_related_element_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     landmarks (id) {
         id -> Uuid,
@@ -371,6 +591,11 @@ diesel::table! {
         system_prompt -> Text,
         user_prompt -> Text,
         display_name -> Text,
+        logical_call_id -> Nullable<Uuid>,
+        attempt -> Int4,
+        prompt_id -> Nullable<Text>,
+        prompt_version -> Nullable<Int4>,
+        user_id -> Nullable<Uuid>,
     }
 }
 
@@ -481,6 +706,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    prompt_versions (id) {
+        id -> Uuid,
+        prompt_id -> Text,
+        version -> Int4,
+        system_prompt -> Text,
+        schema -> Nullable<Text>,
+        source -> Text,
+        is_active -> Bool,
+        activated_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     references (id) {
         id -> Uuid,
@@ -556,6 +795,24 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::Vector;
+
+    trace_search_chunks (id) {
+        id -> Uuid,
+        trace_id -> Uuid,
+        user_id -> Uuid,
+        chunk_index -> Int4,
+        content -> Text,
+        content_hash -> Text,
+        model -> Nullable<Text>,
+        vector -> Nullable<Vector>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::Tsvector;
@@ -566,7 +823,6 @@ diesel::table! {
         journal_id -> Uuid,
         interaction_date -> Timestamp,
         title -> Text,
-        content -> Text,
         mirror_text -> Text,
         tag_text -> Text,
         element_text -> Text,
@@ -575,6 +831,8 @@ diesel::table! {
         high_level_project_landmark_text -> Text,
         search_vector -> Tsvector,
         refreshed_at -> Timestamp,
+        content_vector -> Tsvector,
+        search_config -> Text,
     }
 }
 
@@ -688,17 +946,34 @@ diesel::table! {
         ai_features_enabled -> Bool,
         external_captures_default_journal_id -> Nullable<Uuid>,
         mentor_specific_prompt -> Nullable<Text>,
+        monthly_recap_enabled -> Bool,
+        locale -> Text,
     }
 }
 
+diesel::joinable!(account_export_audit_events -> account_exports (export_id));
+diesel::joinable!(account_export_audit_events -> users (user_id));
+diesel::joinable!(account_exports -> assets (asset_id));
+diesel::joinable!(account_exports -> background_jobs (job_id));
+diesel::joinable!(account_exports -> outbound_emails (outbound_email_id));
+diesel::joinable!(account_exports -> users (user_id));
 diesel::joinable!(album_items -> albums (album_id));
 diesel::joinable!(album_items -> traces (trace_id));
 diesel::joinable!(albums -> assets (cover_image_asset_id));
 diesel::joinable!(albums -> users (owner_user_id));
+diesel::joinable!(analysis_configs -> landscape_analyses (analysis_id));
+diesel::joinable!(analysis_events -> landscape_analyses (analysis_id));
 diesel::joinable!(analysis_summaries -> landscape_analyses (landscape_analysis_id));
 diesel::joinable!(analysis_summaries -> users (user_id));
+diesel::joinable!(analysis_unlock_audit_events -> analysis_unlock_sessions (session_id));
+diesel::joinable!(analysis_unlock_sessions -> lenses (lens_id));
+diesel::joinable!(analysis_unlock_sessions -> users (user_id));
+diesel::joinable!(bio_profiles -> landscape_analyses (analysis_id));
+diesel::joinable!(bio_profiles -> traces (trace_id));
+diesel::joinable!(bio_profiles -> users (user_id));
 diesel::joinable!(content_reports -> messages (reported_message_id));
 diesel::joinable!(content_reports -> posts (reported_post_id));
+diesel::joinable!(data_keys -> users (user_id));
 diesel::joinable!(devices -> users (user_id));
 diesel::joinable!(documents -> users (owner_user_id));
 diesel::joinable!(element_landmarks -> elements (element_id));
@@ -707,10 +982,20 @@ diesel::joinable!(elements -> landscape_analyses (analysis_id));
 diesel::joinable!(elements -> trace_mirrors (trace_mirror_id));
 diesel::joinable!(elements -> traces (trace_id));
 diesel::joinable!(elements -> users (user_id));
+diesel::joinable!(journal_import_batch_traces -> journal_import_batches (batch_id));
+diesel::joinable!(journal_import_batch_traces -> traces (trace_id));
+diesel::joinable!(journal_import_batches -> journals (journal_id));
+diesel::joinable!(journal_import_batches -> users (user_id));
 diesel::joinable!(journal_sharing_policies -> journals (journal_id));
 diesel::joinable!(journal_share_links -> journals (journal_id));
 diesel::joinable!(journal_share_links -> posts (scoped_post_id));
 diesel::joinable!(journal_share_links -> users (owner_user_id));
+diesel::joinable!(landmark_aliases -> landmarks (landmark_id));
+diesel::joinable!(landmark_aliases -> users (user_id));
+diesel::joinable!(landmark_curations -> landmarks (landmark_id));
+diesel::joinable!(landmark_curations -> users (user_id));
+diesel::joinable!(landmark_versions -> landmarks (landmark_id));
+diesel::joinable!(landmark_versions -> landscape_analyses (landscape_analysis_id));
 diesel::joinable!(landmarks -> landscape_analyses (analysis_id));
 diesel::joinable!(landmarks -> users (user_id));
 diesel::joinable!(landscape_analyses -> traces (analyzed_trace_id));
@@ -729,6 +1014,7 @@ diesel::joinable!(lens_targets -> traces (trace_id));
 diesel::joinable!(lenses -> traces (target_trace_id));
 diesel::joinable!(lenses -> users (user_id));
 diesel::joinable!(llm_calls -> landscape_analyses (analysis_id));
+diesel::joinable!(llm_calls -> users (user_id));
 diesel::joinable!(messages -> landscape_analyses (landscape_analysis_id));
 diesel::joinable!(messages -> posts (post_id));
 diesel::joinable!(messages -> traces (trace_id));
@@ -751,6 +1037,8 @@ diesel::joinable!(trace_attachments -> traces (trace_id));
 diesel::joinable!(trace_mirrors -> landmarks (primary_landmark_id));
 diesel::joinable!(trace_mirrors -> traces (trace_id));
 diesel::joinable!(trace_mirrors -> users (user_id));
+diesel::joinable!(trace_search_chunks -> traces (trace_id));
+diesel::joinable!(trace_search_chunks -> users (user_id));
 diesel::joinable!(trace_search_documents -> journals (journal_id));
 diesel::joinable!(trace_search_documents -> traces (trace_id));
 diesel::joinable!(trace_search_documents -> users (user_id));
@@ -764,20 +1052,36 @@ diesel::joinable!(user_roles -> users (user_id));
 diesel::joinable!(user_secure_actions -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    account_export_audit_events,
+    account_exports,
     album_items,
     albums,
+    analysis_configs,
+    analysis_events,
     analysis_summaries,
+    analysis_unlock_audit_events,
+    analysis_unlock_sessions,
     assets,
+    background_jobs,
+    bio_profiles,
     content_reports,
+    data_keys,
     devices,
     documents,
     element_landmarks,
     element_relations,
     elements,
+    embeddings,
+    job_schedules,
+    journal_import_batch_traces,
+    journal_import_batches,
     journal_sharing_policies,
     journal_share_links,
     journals,
+    landmark_aliases,
+    landmark_curations,
     landmark_relations,
+    landmark_versions,
     landmarks,
     landscape_analyses,
     landscape_analysis_inputs,
@@ -793,11 +1097,13 @@ diesel::allow_tables_to_appear_in_same_query!(
     post_grants,
     post_relations,
     posts,
+    prompt_versions,
     references,
     relationships,
     sessions,
     trace_attachments,
     trace_mirrors,
+    trace_search_chunks,
     trace_search_documents,
     traces,
     usage_events,
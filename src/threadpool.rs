@@ -1,60 +0,0 @@
-use std::{
-    sync::{mpsc, Arc, Mutex},
-    thread,
-};
-
-pub struct ThreadPool {
-    pub threads: Vec<Worker>,
-    sender: mpsc::Sender<Job>,
-}
-
-type Job = Box<dyn FnOnce() + Send + 'static>;
-
-impl ThreadPool {
-    ///Create a new ThreadPool
-    ///
-    ///The size is the number of threads in the pool
-    ///
-    /// # Panics
-    ///
-    /// The `new` function will panic if the size is zero
-    pub fn new(size: usize) -> ThreadPool {
-        assert!(size > 0);
-
-        let (sender, receiver) = mpsc::channel();
-
-        let mut threads = Vec::with_capacity(size);
-
-        let receiver = Arc::new(Mutex::new(receiver));
-
-        for id in 0..size {
-            threads.push(Worker::new(id, Arc::clone(&receiver)));
-        }
-        ThreadPool { threads, sender }
-    }
-
-    pub fn execute<F>(&self, f: F)
-    where
-        F: FnOnce() + Send + 'static,
-    {
-        let job = Box::new(f);
-
-        self.sender.send(job).unwrap();
-    }
-}
-
-pub struct Worker {
-    pub id: usize,
-    pub thread: thread::JoinHandle<()>,
-}
-
-impl Worker {
-    pub fn new(id: usize, receiver: Arc<Mutex<mpsc::Receiver<Job>>>) -> Worker {
-        let thread = thread::spawn(move || loop {
-            let job = receiver.lock().unwrap().recv().unwrap();
-
-            job();
-        });
-        Worker { id, thread }
-    }
-}
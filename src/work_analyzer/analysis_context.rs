@@ -5,6 +5,7 @@ use uuid::Uuid;
 use crate::db::DbPool;
 use crate::entities_v2::error::PpdcError;
 use crate::entities_v2::{
+    bio_profile::BioProfile,
     landmark::{Landmark, LandmarkType},
     landscape_analysis::LandscapeAnalysis,
 };
@@ -13,6 +14,8 @@ pub struct AnalysisContext {
     pub analysis_id: Uuid,
     pub user_id: Uuid,
     pub pool: DbPool,
+    /// Latest profile built from the user's bio trace, if any.
+    pub bio_profile: Option<BioProfile>,
 }
 
 pub fn load_previous_landscape_inputs(
@@ -1,24 +1,97 @@
 use uuid::Uuid;
 
 use crate::db::DbPool;
+use crate::entities_v2::analysis_config::{AnalysisConfigOverride, RecordedAnalysisConfig};
 use crate::entities_v2::error::PpdcError;
 use crate::entities_v2::{
+    bio_profile::BioProfile,
     element::Element,
     landmark::Landmark,
     landscape_analysis::{LandscapeAnalysis, LandscapeProcessingState},
     trace::{Trace, TraceType},
     trace_mirror::{self, TraceMirror, TraceMirrorType},
 };
+use crate::openai_handler::llm_provider::{self, LlmProviderSelection};
 use crate::work_analyzer::{
     active_context_filtering,
     analysis_context::{load_previous_landscape_inputs, AnalysisContext},
-    element_pipeline_v2, high_level_analysis, hlp_pipeline, mirror_pipeline,
+    bio_pipeline, element_pipeline_v2, high_level_analysis, hlp_pipeline, matching,
+    mirror_pipeline,
+    observability::{record_event, AnalysisEventData},
 };
 
 pub struct AnalysisConfig {
     pub model: String,
     pub matching_confidence_threshold: f32,
     pub feature_flag_run_high_level_analysis: bool,
+    pub llm_providers: LlmProviderSelection,
+    /// Set when the configuration comes from a replay override; carried over to child analyses.
+    pub is_override: bool,
+}
+
+impl AnalysisConfig {
+    pub fn from_env() -> Self {
+        Self {
+            model: "gpt-4.1-mini".to_string(),
+            // Matching has never dropped low-confidence results; only a replay override opts in.
+            matching_confidence_threshold: 0.0,
+            feature_flag_run_high_level_analysis: true,
+            llm_providers: LlmProviderSelection::from_env(),
+            is_override: false,
+        }
+    }
+
+    pub fn with_override(mut self, config_override: &AnalysisConfigOverride) -> Self {
+        if let Some(model) = &config_override.model {
+            self.model = model.clone();
+            self.llm_providers = self.llm_providers.with_model(model.clone());
+        }
+        if let Some(threshold) = config_override.matching_confidence_threshold {
+            self.matching_confidence_threshold = threshold;
+        }
+        if let Some(run_high_level_analysis) = config_override.feature_flag_run_high_level_analysis
+        {
+            self.feature_flag_run_high_level_analysis = run_high_level_analysis;
+        }
+        self.is_override = true;
+        self
+    }
+
+    fn from_recorded(recorded: &RecordedAnalysisConfig) -> Self {
+        let config = Self::from_env();
+        if !recorded.is_override {
+            return config;
+        }
+        config.with_override(&AnalysisConfigOverride {
+            model: Some(recorded.model.clone()),
+            matching_confidence_threshold: Some(recorded.matching_confidence_threshold),
+            feature_flag_run_high_level_analysis: Some(
+                recorded.feature_flag_run_high_level_analysis,
+            ),
+        })
+    }
+
+    /// Uses the override recorded for the analysis, else the one of its parent, else the default,
+    /// and records the result as the configuration the analysis runs with.
+    pub fn resolve_for_analysis(
+        analysis_id: Uuid,
+        parent_analysis_id: Option<Uuid>,
+        pool: &DbPool,
+    ) -> Result<Self, PpdcError> {
+        let recorded = match RecordedAnalysisConfig::find(analysis_id, pool)? {
+            Some(recorded) => Some(recorded),
+            None => match parent_analysis_id {
+                Some(parent_analysis_id) => RecordedAnalysisConfig::find(parent_analysis_id, pool)?,
+                None => None,
+            },
+        };
+        let config = recorded
+            .as_ref()
+            .map(Self::from_recorded)
+            .unwrap_or_else(Self::from_env);
+        config.record(analysis_id, pool)?;
+        Ok(config)
+    }
 }
 
 pub struct AnalysisInputs {
@@ -61,10 +134,24 @@ impl AnalysisProcessor {
     }
 
     pub async fn process(self) -> Result<LandscapeAnalysis, PpdcError> {
+        let llm_providers = self.config.llm_providers.clone();
+        let threshold = self.config.matching_confidence_threshold;
+        llm_provider::with_selection(
+            llm_providers,
+            matching::with_confidence_threshold(threshold, self.run_steps()),
+        )
+        .await
+    }
+
+    async fn run_steps(self) -> Result<LandscapeAnalysis, PpdcError> {
         let state = self.create_initial_state().await?;
 
         let state = if self.inputs.trace.trace_type == TraceType::HighLevelProjectsDefinition {
-            let hlp_pipeline_output = hlp_pipeline::run(&self.context, &self.inputs.trace).await?;
+            self.record_step_started("hlp_pipeline");
+            let hlp_pipeline_output = self.record_step_result(
+                "hlp_pipeline",
+                hlp_pipeline::run(&self.context, &self.inputs.trace).await,
+            )?;
             let mut current_landmarks = hlp_pipeline_output.created_high_level_projects;
             current_landmarks.extend(hlp_pipeline_output.created_related_landmarks);
             AnalysisStateTraceBroker {
@@ -73,13 +160,41 @@ impl AnalysisProcessor {
                 current_landmarks,
                 current_elements: vec![],
             }
+        } else if self.inputs.trace.trace_type == TraceType::BioTrace {
+            self.record_step_started("bio_pipeline");
+            let bio_pipeline_output = self.record_step_result(
+                "bio_pipeline",
+                bio_pipeline::run(&self.context, &self.inputs.trace).await,
+            )?;
+            AnalysisStateTraceBroker {
+                current_landscape: state.current_landscape,
+                current_trace_mirror: bio_pipeline_output.trace_mirror,
+                current_landmarks: bio_pipeline_output.created_landmarks,
+                current_elements: vec![],
+            }
         } else {
-            let state = self.run_mirror_pipeline(state).await?;
-            let state = self.run_grammatical_extraction_pipeline(state).await?;
-            self.run_active_context_filtering_pipeline(state).await?
+            self.record_step_started("mirror_pipeline");
+            let state =
+                self.record_step_result("mirror_pipeline", self.run_mirror_pipeline(state).await)?;
+            self.record_step_started("grammatical_extraction");
+            let state = self.record_step_result(
+                "grammatical_extraction",
+                self.run_grammatical_extraction_pipeline(state).await,
+            )?;
+            self.record_step_started("active_context_filtering");
+            self.record_step_result(
+                "active_context_filtering",
+                self.run_active_context_filtering_pipeline(state).await,
+            )?
         };
 
-        self.run_high_level_analysis_pipeline(state).await?;
+        if self.config.feature_flag_run_high_level_analysis {
+            self.record_step_started("high_level_analysis");
+            self.record_step_result(
+                "high_level_analysis",
+                self.run_high_level_analysis_pipeline(state).await,
+            )?;
+        }
 
         let mut analysis =
             LandscapeAnalysis::find_full_analysis(self.context.analysis_id, &self.context.pool)?;
@@ -88,6 +203,36 @@ impl AnalysisProcessor {
         Ok(analysis)
     }
 
+    fn record_step_started(&self, step: &str) {
+        record_event(
+            self.context.analysis_id,
+            AnalysisEventData::StepStarted {
+                step: step.to_string(),
+            },
+        );
+    }
+
+    /// Logs the outcome of a step as `StepFinished` or `Error`, passing the result through.
+    fn record_step_result<T>(
+        &self,
+        step: &str,
+        result: Result<T, PpdcError>,
+    ) -> Result<T, PpdcError> {
+        let event = match &result {
+            Ok(_) => AnalysisEventData::StepFinished {
+                step: step.to_string(),
+                summary: None,
+            },
+            Err(e) => AnalysisEventData::Error {
+                step: step.to_string(),
+                message: e.message.clone(),
+                retry_in_seconds: None,
+            },
+        };
+        record_event(self.context.analysis_id, event);
+        result
+    }
+
     async fn create_initial_state(&self) -> Result<AnalysisStateInitial, PpdcError> {
         let landscape =
             LandscapeAnalysis::find_full_analysis(self.context.analysis_id, &self.context.pool)?;
@@ -208,23 +353,19 @@ impl AnalysisProcessor {
 
     pub fn setup(
         analysis_id: Uuid,
-        trace_id: Uuid,
+        trace: Trace,
         previous_landscape_id: Option<Uuid>,
+        analysis_config: AnalysisConfig,
         pool: &DbPool,
     ) -> Result<AnalysisProcessor, PpdcError> {
-        let trace = Trace::find_full_trace(trace_id, pool)?;
         let user_id = trace.user_id;
         let (previous_landscape, previous_landscape_landmarks, user_high_level_projects) =
             load_previous_landscape_inputs(previous_landscape_id, pool)?;
-        let analysis_config = AnalysisConfig {
-            model: "gpt-4.1-mini".to_string(),
-            matching_confidence_threshold: 0.3,
-            feature_flag_run_high_level_analysis: false,
-        };
         let context = AnalysisContext {
             analysis_id,
             user_id,
             pool: pool.clone(),
+            bio_profile: BioProfile::find_in_lineage(user_id, analysis_id, None, pool)?,
         };
         let inputs = AnalysisInputs {
             trace,
@@ -15,12 +15,68 @@ use crate::entities_v2::{
     trace::Trace,
     user::{User, UserPrincipalType},
 };
+use crate::openai_handler::llm_provider;
 use crate::work_analyzer::analysis_processor;
+use crate::work_analyzer::analysis_unlock;
 use crate::work_analyzer::period_analysis_processor;
 
 const LENS_RUN_LOCK_TTL_SECONDS: i64 = 1800;
 const MAX_ANALYSES_PER_RUN: usize = 100;
 
+/// Undoes what an interrupted run still holds, e.g. when the job running the lens is
+/// aborted on shutdown: the in-flight analysis goes back to pending and the run lock is freed.
+struct LensRunGuard {
+    lens: Lens,
+    worker_id: Uuid,
+    in_flight: Option<LandscapeAnalysis>,
+    pool: &'static DbPool,
+    armed: bool,
+}
+
+impl LensRunGuard {
+    fn release(mut self) -> Result<bool, PpdcError> {
+        self.armed = false;
+        self.lens.release_run_lock(self.worker_id, self.pool)
+    }
+}
+
+impl Drop for LensRunGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        if let Some(analysis) = self.in_flight.take() {
+            let analysis_id = analysis.id;
+            if let Err(err) =
+                analysis.set_processing_state(LandscapeProcessingState::Pending, self.pool)
+            {
+                tracing::error!(
+                    target: "work_analyzer",
+                    "run_lens_interrupted_requeue_failed lens_id={} analysis_id={} error={}",
+                    self.lens.id,
+                    analysis_id,
+                    err
+                );
+            }
+        }
+        match self.lens.release_run_lock(self.worker_id, self.pool) {
+            Ok(_) => tracing::warn!(
+                target: "work_analyzer",
+                "run_lens_interrupted lens_id={} worker_id={}",
+                self.lens.id,
+                self.worker_id
+            ),
+            Err(err) => tracing::error!(
+                target: "work_analyzer",
+                "run_lens_interrupted_lock_release_failed lens_id={} worker_id={} error={}",
+                self.lens.id,
+                self.worker_id,
+                err
+            ),
+        }
+    }
+}
+
 pub async fn run_lens(lens_id: Uuid) -> Result<Lens, PpdcError> {
     let pool = get_global_pool();
     let mut lens = Lens::find_full_lens(lens_id, pool)?;
@@ -77,9 +133,29 @@ pub async fn run_lens(lens_id: Uuid) -> Result<Lens, PpdcError> {
         worker_id
     );
 
-    let run_result = run_claim_loop(&mut lens, worker_id, claim_cutoff_at, pool).await;
+    let mut guard = LensRunGuard {
+        lens: lens.clone(),
+        worker_id,
+        in_flight: None,
+        pool,
+        armed: true,
+    };
+    let unlocked = analysis_unlock::holds_key_for_lens(lens.id);
+    let lens_id = lens.id;
+    let claim_loop = run_claim_loop(
+        &mut lens,
+        worker_id,
+        claim_cutoff_at,
+        &mut guard.in_flight,
+        pool,
+    );
+    let run_result = if unlocked {
+        analysis_unlock::with_unlocked_lens(lens_id, claim_loop).await
+    } else {
+        claim_loop.await
+    };
 
-    let release_result = lens.release_run_lock(worker_id, pool);
+    let release_result = guard.release();
     match (run_result, release_result) {
         (Ok(()), Ok(_)) => {
             tracing::info!(
@@ -160,6 +236,7 @@ async fn run_claim_loop(
     lens: &mut Lens,
     worker_id: Uuid,
     claim_cutoff_at: chrono::NaiveDateTime,
+    in_flight: &mut Option<LandscapeAnalysis>,
     pool: &DbPool,
 ) -> Result<(), PpdcError> {
     for iteration in 0..MAX_ANALYSES_PER_RUN {
@@ -192,6 +269,25 @@ async fn run_claim_loop(
             claimed_analysis.period_start,
             claimed_analysis.period_end
         );
+        *in_flight = Some(claimed_analysis.clone());
+
+        if let Some(trace_id) = claimed_analysis.analyzed_trace_id {
+            let trace = Trace::find_full_trace(trace_id, pool)?;
+            if !analysis_unlock::can_decrypt_trace(&trace) {
+                tracing::info!(
+                    target: "work_analyzer",
+                    "run_lens_locked_encrypted_trace lens_id={} worker_id={} analysis_id={} trace_id={}",
+                    lens.id,
+                    worker_id,
+                    claimed_analysis.id,
+                    trace_id
+                );
+                // Left for the instance holding the key instead of being claimed again in a loop.
+                claimed_analysis.set_processing_state(LandscapeProcessingState::Locked, pool)?;
+                *in_flight = None;
+                continue;
+            }
+        }
 
         let claimed_analysis_for_fail = claimed_analysis.clone();
         let claimed_analysis_id = claimed_analysis.id;
@@ -229,6 +325,7 @@ async fn run_claim_loop(
             run_claimed_analysis(lens, claimed_analysis.clone(), previous_landscape_id, pool).await
         }
         .await;
+        *in_flight = None;
 
         if let Err(err) = run_result {
             tracing::error!(
@@ -301,22 +398,30 @@ async fn run_claimed_analysis(
         analysis.landscape_analysis_type.to_db(),
         analysis.analyzed_trace_id
     );
+    let analysis_config = analysis_processor::AnalysisConfig::resolve_for_analysis(
+        analysis.id,
+        previous_landscape_id,
+        pool,
+    )?;
+    let llm_providers = analysis_config.llm_providers.clone();
     let completed_analysis = if let Some(trace_id) = analysis.analyzed_trace_id {
         let trace = Trace::find_full_trace(trace_id, pool)?;
-        if trace.is_encrypted {
-            return Err(PpdcError::new(
-                400,
-                ErrorType::ApiError,
-                format!(
-                    "Trace {} is encrypted and cannot be processed server-side",
-                    trace_id
-                ),
-            ));
-        }
+        let trace = analysis_unlock::decrypt_trace_for_analysis(trace, Some(analysis.id), pool)?
+            .ok_or_else(|| {
+                PpdcError::new(
+                    400,
+                    ErrorType::ApiError,
+                    format!(
+                        "Trace {} is encrypted and no unlock session covers it",
+                        trace_id
+                    ),
+                )
+            })?;
         let processor = analysis_processor::AnalysisProcessor::setup(
             analysis.id,
-            trace_id,
+            trace,
             previous_landscape_id,
+            analysis_config,
             pool,
         )?;
         processor.process().await?
@@ -327,7 +432,7 @@ async fn run_claimed_analysis(
             previous_landscape_id,
             pool,
         )?;
-        processor.process_daily_recap().await?
+        llm_provider::with_selection(llm_providers, processor.process_daily_recap()).await?
     } else if analysis.landscape_analysis_type == LandscapeAnalysisType::WeeklyRecap {
         let processor = period_analysis_processor::PeriodAnalysisProcessor::setup(
             analysis.id,
@@ -335,7 +440,15 @@ async fn run_claimed_analysis(
             previous_landscape_id,
             pool,
         )?;
-        processor.process_weekly_recap().await?
+        llm_provider::with_selection(llm_providers, processor.process_weekly_recap()).await?
+    } else if analysis.landscape_analysis_type.is_period_rollup() {
+        let processor = period_analysis_processor::PeriodAnalysisProcessor::setup(
+            analysis.id,
+            analysis.user_id,
+            previous_landscape_id,
+            pool,
+        )?;
+        llm_provider::with_selection(llm_providers, processor.process_period_rollup()).await?
     } else {
         analysis.set_processing_state(LandscapeProcessingState::Completed, pool)?
     };
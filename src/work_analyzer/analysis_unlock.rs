@@ -0,0 +1,255 @@
+use chrono::{NaiveDateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::db::{get_global_pool, DbPool};
+use crate::entities_v2::analysis_unlock::{AnalysisUnlockSession, AnalysisUnlockSessionStatus};
+use crate::entities_v2::error::PpdcError;
+use crate::entities_v2::landscape_analysis::{
+    has_pending_encrypted_analyses_for_key, reopen_locked_encrypted_analyses_for_key,
+};
+use crate::entities_v2::trace::{Trace, TraceDataKey, TraceEncryptionMetadata};
+use crate::work_analyzer::analysis_queue::run_lens;
+use crate::work_analyzer::observability::with_sensitive_text_redacted;
+
+const UNLOCKED_RUN_RETRY_SECONDS: u64 = 5;
+
+struct UnlockedKey {
+    session_id: Uuid,
+    user_id: Uuid,
+    key: TraceDataKey,
+    expires_at: NaiveDateTime,
+}
+
+tokio::task_local! {
+    static UNLOCKED_LENS: Uuid;
+}
+
+/// Data keys of active unlock sessions, by `(lens_id, key_id)`. Never persisted or logged.
+fn keyring() -> &'static Mutex<HashMap<(Uuid, String), UnlockedKey>> {
+    static KEYRING: OnceLock<Mutex<HashMap<(Uuid, String), UnlockedKey>>> = OnceLock::new();
+    KEYRING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn hold_key(session: &AnalysisUnlockSession, key: TraceDataKey) {
+    keyring().lock().unwrap().insert(
+        (session.lens_id, session.key_id.clone()),
+        UnlockedKey {
+            session_id: session.id,
+            user_id: session.user_id,
+            key,
+            expires_at: session.expires_at,
+        },
+    );
+}
+
+fn is_key_held(session_id: Uuid) -> bool {
+    keyring()
+        .lock()
+        .unwrap()
+        .values()
+        .any(|unlocked| unlocked.session_id == session_id)
+}
+
+/// Drops the data key of a session; returns whether this process was holding it.
+pub fn release_key(session_id: Uuid) -> bool {
+    let mut keyring = keyring().lock().unwrap();
+    let held = keyring.len();
+    keyring.retain(|_, unlocked| unlocked.session_id != session_id);
+    keyring.len() < held
+}
+
+/// Whether this process holds a data key unlocked for the lens.
+pub fn holds_key_for_lens(lens_id: Uuid) -> bool {
+    keyring()
+        .lock()
+        .unwrap()
+        .keys()
+        .any(|(held_lens_id, _)| *held_lens_id == lens_id)
+}
+
+/// Runs `future` as a run of `lens_id`: keys unlocked for that lens can decrypt its traces,
+/// and user text stays out of logs and persisted LLM calls for the whole run.
+pub async fn with_unlocked_lens<F>(lens_id: Uuid, future: F) -> F::Output
+where
+    F: Future,
+{
+    UNLOCKED_LENS
+        .scope(lens_id, with_sensitive_text_redacted(future))
+        .await
+}
+
+/// Key for `key_id` unlocked for the lens of the current run. Outside `with_unlocked_lens`
+/// nothing can be decrypted, so a decrypted trace never reaches an unredacted prompt.
+fn find_key(user_id: Uuid, key_id: &str) -> Option<(Uuid, TraceDataKey)> {
+    let lens_id = UNLOCKED_LENS.try_with(|lens_id| *lens_id).ok()?;
+    let now = Utc::now().naive_utc();
+    keyring()
+        .lock()
+        .unwrap()
+        .get(&(lens_id, key_id.to_string()))
+        .filter(|unlocked| unlocked.user_id == user_id && unlocked.expires_at > now)
+        .map(|unlocked| (unlocked.session_id, unlocked.key.clone()))
+}
+
+/// Whether the current lens run can decrypt the trace; plaintext traces always can.
+pub fn can_decrypt_trace(trace: &Trace) -> bool {
+    if !trace.is_encrypted {
+        return true;
+    }
+    trace
+        .encryption_metadata
+        .as_ref()
+        .and_then(|value| TraceEncryptionMetadata::parse(value).ok())
+        .is_some_and(|metadata| find_key(trace.user_id, &metadata.key_id).is_some())
+}
+
+/// Returns an in-memory plaintext copy of the trace for analysis, or `None` when no key unlocked
+/// for the current lens run covers it. Each decryption is audited on the unlock session.
+pub fn decrypt_trace_for_analysis(
+    trace: Trace,
+    analysis_id: Option<Uuid>,
+    pool: &DbPool,
+) -> Result<Option<Trace>, PpdcError> {
+    if !trace.is_encrypted {
+        return Ok(Some(trace));
+    }
+    let Some(metadata) = trace
+        .encryption_metadata
+        .as_ref()
+        .and_then(|value| TraceEncryptionMetadata::parse(value).ok())
+    else {
+        return Ok(None);
+    };
+    let Some((session_id, key)) = find_key(trace.user_id, &metadata.key_id) else {
+        return Ok(None);
+    };
+    let content = metadata.decrypt_content(&trace.content, &key)?;
+    AnalysisUnlockSession::record_trace_decrypted(
+        session_id,
+        trace.user_id,
+        trace.id,
+        analysis_id,
+        pool,
+    )?;
+    Ok(Some(Trace {
+        content: content.to_string(),
+        is_encrypted: false,
+        encryption_metadata: None,
+        ..trace
+    }))
+}
+
+/// Holds the session key and runs its lens until no encrypted analysis is left, the session
+/// expires, or it is revoked. The key is dropped before the task ends.
+pub fn spawn_unlocked_lens_run(session: AnalysisUnlockSession, key: TraceDataKey) {
+    hold_key(&session, key);
+    tokio::spawn(async move {
+        let pool = get_global_pool();
+        let final_status = run_until_done(&session, pool).await;
+        release_key(session.id);
+        let Some((status, detail)) = final_status else {
+            return;
+        };
+        if let Err(err) = session.clone().finish(status, detail, pool) {
+            tracing::error!(
+                target: "work_analyzer",
+                "analysis_unlock_finish_failed session_id={} lens_id={} error={}",
+                session.id,
+                session.lens_id,
+                err
+            );
+        }
+    });
+}
+
+async fn run_until_done(
+    session: &AnalysisUnlockSession,
+    pool: &DbPool,
+) -> Option<(AnalysisUnlockSessionStatus, Option<String>)> {
+    loop {
+        if !is_key_held(session.id) {
+            return None;
+        }
+        if session.expires_at <= Utc::now().naive_utc() {
+            return Some((AnalysisUnlockSessionStatus::Expired, None));
+        }
+        // Another instance without the key may have locked them since the last pass.
+        if let Err(err) =
+            reopen_locked_encrypted_analyses_for_key(session.lens_id, &session.key_id, pool)
+        {
+            return Some((AnalysisUnlockSessionStatus::Closed, Some(err.message)));
+        }
+        if let Err(err) = run_lens(session.lens_id).await {
+            tracing::warn!(
+                target: "work_analyzer",
+                "analysis_unlock_run_failed session_id={} lens_id={} error={}",
+                session.id,
+                session.lens_id,
+                err
+            );
+        }
+        match has_pending_encrypted_analyses_for_key(session.lens_id, &session.key_id, pool) {
+            Ok(false) => return Some((AnalysisUnlockSessionStatus::Closed, None)),
+            Ok(true) => {}
+            Err(err) => {
+                return Some((AnalysisUnlockSessionStatus::Closed, Some(err.message)));
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(UNLOCKED_RUN_RETRY_SECONDS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zeroize::Zeroizing;
+
+    use super::*;
+    use crate::work_analyzer::observability::is_sensitive_text_redacted;
+
+    #[tokio::test]
+    async fn held_keys_only_decrypt_inside_a_run_of_their_lens() {
+        let session_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let lens_id = Uuid::new_v4();
+        let key_id = format!("key-{}", session_id);
+        keyring().lock().unwrap().insert(
+            (lens_id, key_id.clone()),
+            UnlockedKey {
+                session_id,
+                user_id,
+                key: Zeroizing::new([7u8; 32]),
+                expires_at: Utc::now().naive_utc() + chrono::Duration::minutes(5),
+            },
+        );
+
+        assert!(holds_key_for_lens(lens_id));
+        assert!(find_key(user_id, &key_id).is_none());
+        assert!(
+            with_unlocked_lens(Uuid::new_v4(), async { find_key(user_id, &key_id) })
+                .await
+                .is_none()
+        );
+        assert!(
+            with_unlocked_lens(lens_id, async { find_key(Uuid::new_v4(), &key_id) })
+                .await
+                .is_none()
+        );
+        let (found_session_id, redacted) = with_unlocked_lens(lens_id, async {
+            (
+                find_key(user_id, &key_id).map(|(id, _)| id),
+                is_sensitive_text_redacted(),
+            )
+        })
+        .await;
+        assert_eq!(found_session_id, Some(session_id));
+        assert!(redacted);
+
+        assert!(release_key(session_id));
+        assert!(!holds_key_for_lens(lens_id));
+        assert!(!release_key(session_id));
+    }
+}
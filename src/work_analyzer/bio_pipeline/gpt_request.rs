@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities_v2::bio_profile::{BioProfile, BioProfileEntryKind};
+use crate::entities_v2::error::PpdcError;
+use crate::openai_handler::GptRequestConfig;
+use crate::work_analyzer::prompt_registry;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BioEntryDraft {
+    pub id: i32,
+    pub kind: BioProfileEntryKind,
+    pub title: String,
+    pub subtitle: String,
+    pub content: String,
+    pub spans: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BioExtractionResult {
+    pub summary: String,
+    pub entries: Vec<BioEntryDraft>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BioPromptInput {
+    trace_type: &'static str,
+    trace_text: String,
+    previous_profile: Vec<PreviousEntryPromptItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PreviousEntryPromptItem {
+    kind: BioProfileEntryKind,
+    title: String,
+    subtitle: String,
+}
+
+pub async fn extract_bio_profile(
+    trace_text: &str,
+    previous_profile: Option<&BioProfile>,
+    analysis_id: Uuid,
+) -> Result<BioExtractionResult, PpdcError> {
+    let prompt = prompt_registry::prompt("bio_pipeline")?;
+    let system_prompt = prompt.system_prompt.clone();
+    let schema = prompt.schema_json()?;
+    let previous_profile = previous_profile
+        .map(|profile| {
+            BioProfileEntryKind::ALL
+                .into_iter()
+                .flat_map(|kind| {
+                    profile.sections.section(kind).iter().map(move |entry| {
+                        PreviousEntryPromptItem {
+                            kind,
+                            title: entry.title.clone(),
+                            subtitle: entry.subtitle.clone(),
+                        }
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let user_prompt = serde_json::to_string_pretty(&BioPromptInput {
+        trace_type: "BIO_TRACE",
+        trace_text: trace_text.to_string(),
+        previous_profile,
+    })?;
+
+    let request = GptRequestConfig::new(
+        "gpt-4.1-mini".to_string(),
+        system_prompt,
+        user_prompt,
+        Some(schema),
+        Some(analysis_id),
+    )
+    .with_display_name("Bio Pipeline / Extraction")
+    .with_prompt(prompt.prompt_ref());
+
+    request.execute().await
+}
@@ -0,0 +1,5 @@
+pub mod gpt_request;
+pub mod orchestration;
+pub mod persistence;
+
+pub use orchestration::{run, BioPipelineOutput};
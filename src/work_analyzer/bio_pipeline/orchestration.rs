@@ -0,0 +1,30 @@
+use crate::entities_v2::error::PpdcError;
+use crate::entities_v2::{
+    bio_profile::BioProfile, landmark::Landmark, trace::Trace, trace_mirror::TraceMirror,
+};
+use crate::work_analyzer::analysis_context::AnalysisContext;
+
+use super::{gpt_request::extract_bio_profile, persistence::persist_bio_profile};
+
+#[derive(Debug, Clone)]
+pub struct BioPipelineOutput {
+    pub trace_mirror: TraceMirror,
+    pub created_landmarks: Vec<Landmark>,
+    pub bio_profile: BioProfile,
+}
+
+/// Builds the next version of the user profile from a bio trace, starting from the profile
+/// known to the analysis context.
+pub async fn run(context: &AnalysisContext, trace: &Trace) -> Result<BioPipelineOutput, PpdcError> {
+    let previous_profile = context.bio_profile.as_ref();
+    let extraction =
+        extract_bio_profile(&trace.content, previous_profile, context.analysis_id).await?;
+    let (trace_mirror, created_landmarks, bio_profile) =
+        persist_bio_profile(context, trace, &extraction, previous_profile)?;
+
+    Ok(BioPipelineOutput {
+        trace_mirror,
+        created_landmarks,
+        bio_profile,
+    })
+}
@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+
+use crate::entities_v2::error::PpdcError;
+use crate::entities_v2::{
+    bio_profile::{BioProfile, BioProfileEntry, BioProfileSections, NewBioProfile},
+    landmark::{Landmark, NewLandmark},
+    reference::{NewReference, ReferenceType},
+    trace::Trace,
+    trace_mirror::{NewTraceMirror, TraceMirror, TraceMirrorType},
+    MaturingState,
+};
+use crate::work_analyzer::analysis_context::AnalysisContext;
+
+use super::gpt_request::{BioEntryDraft, BioExtractionResult};
+
+fn normalize_title(title: &str) -> String {
+    title.trim().to_lowercase()
+}
+
+/// Landmark of the previous profile describing the same fact, used as parent of the new one.
+fn find_previous_landmark_id(
+    draft: &BioEntryDraft,
+    previous_profile: Option<&BioProfile>,
+) -> Option<uuid::Uuid> {
+    let title = normalize_title(&draft.title);
+    previous_profile?
+        .sections
+        .section(draft.kind)
+        .iter()
+        .find(|entry| normalize_title(&entry.title) == title)
+        .map(|entry| entry.landmark_id)
+}
+
+pub fn persist_bio_profile(
+    context: &AnalysisContext,
+    trace: &Trace,
+    extraction: &BioExtractionResult,
+    previous_profile: Option<&BioProfile>,
+) -> Result<(TraceMirror, Vec<Landmark>, BioProfile), PpdcError> {
+    let trace_mirror = NewTraceMirror::new(
+        "Biography".to_string(),
+        "".to_string(),
+        trace.content.clone(),
+        TraceMirrorType::Bio,
+        Vec::new(),
+        trace.id,
+        context.analysis_id,
+        context.user_id,
+        None,
+        Some(trace.interaction_date),
+    )
+    .create(&context.pool)?;
+
+    let mut sections = BioProfileSections::default();
+    let mut created_landmarks = Vec::new();
+    let mut seen_entries = HashSet::new();
+    let mut tag_id_counter: i32 = -1;
+
+    for draft in &extraction.entries {
+        if !seen_entries.insert((draft.kind, normalize_title(&draft.title))) {
+            continue;
+        }
+
+        let landmark = NewLandmark::new(
+            draft.title.clone(),
+            draft.subtitle.clone(),
+            draft.content.clone(),
+            draft.kind.landmark_type(),
+            MaturingState::Draft,
+            context.analysis_id,
+            context.user_id,
+            find_previous_landmark_id(draft, previous_profile),
+        )
+        .create(&context.pool)?;
+
+        let mut mentions: Vec<String> = draft
+            .spans
+            .iter()
+            .map(|span| span.trim().to_string())
+            .filter(|span| !span.is_empty())
+            .collect();
+        if mentions.is_empty() {
+            mentions.push(draft.title.clone());
+        }
+
+        let mut seen_mentions = HashSet::new();
+        for mention in mentions {
+            if !seen_mentions.insert(mention.clone()) {
+                continue;
+            }
+
+            NewReference::new(
+                tag_id_counter,
+                trace_mirror.id,
+                Some(landmark.id),
+                context.analysis_id,
+                context.user_id,
+                mention,
+                ReferenceType::PlainDesc,
+                vec!["bio_profile".to_string()],
+                vec![],
+                None,
+                false,
+            )
+            .create(&context.pool)?;
+            tag_id_counter -= 1;
+        }
+
+        sections.section_mut(draft.kind).push(BioProfileEntry {
+            landmark_id: landmark.id,
+            title: landmark.title.clone(),
+            subtitle: landmark.subtitle.clone(),
+            content: landmark.content.clone(),
+        });
+        created_landmarks.push(landmark);
+    }
+
+    let profile = NewBioProfile {
+        user_id: context.user_id,
+        trace_id: trace.id,
+        analysis_id: context.analysis_id,
+        summary: extraction.summary.trim().to_string(),
+        sections,
+    }
+    .create(&context.pool)?;
+
+    Ok((trace_mirror, created_landmarks, profile))
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::db::{self, DbPool};
+use crate::entities_v2::element::Element;
+use crate::entities_v2::embedding::{
+    embedding_text, model::content_hash, Embedding, EmbeddingEntityType, NewEmbedding,
+};
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::openai_handler::embedding_provider::{self, EmbeddingProvider, EmbeddingProviderKind};
+
+/// A landmark as seen by retrieval: its id and the text its vector is computed from.
+pub struct RetrievalCandidate {
+    pub landmark_id: Uuid,
+    pub text: String,
+}
+
+/// Vectors for `candidates`, reusing stored ones whose text is unchanged and storing the rest.
+/// Without a database every vector is computed on the fly.
+pub async fn embed_landmarks(
+    provider: &dyn EmbeddingProvider,
+    candidates: &[RetrievalCandidate],
+    pool: Option<&DbPool>,
+) -> Result<Vec<Vec<f32>>, PpdcError> {
+    let mut stored: HashMap<Uuid, Embedding> = HashMap::new();
+    if let Some(pool) = pool {
+        let ids: Vec<Uuid> = candidates.iter().map(|c| c.landmark_id).collect();
+        stored = Embedding::find_for_entities(
+            EmbeddingEntityType::Landmark,
+            &ids,
+            provider.model(),
+            pool,
+        )?
+        .into_iter()
+        .map(|embedding| (embedding.entity_id, embedding))
+        .collect();
+    }
+
+    let mut vectors: Vec<Option<Vec<f32>>> = candidates
+        .iter()
+        .map(|candidate| {
+            stored
+                .remove(&candidate.landmark_id)
+                .filter(|embedding| embedding.content_hash == content_hash(&candidate.text))
+                .map(|embedding| embedding.vector)
+        })
+        .collect();
+
+    let missing: Vec<usize> = (0..candidates.len())
+        .filter(|index| vectors[*index].is_none())
+        .collect();
+    if !missing.is_empty() {
+        let texts: Vec<String> = missing
+            .iter()
+            .map(|index| candidates[*index].text.clone())
+            .collect();
+        let computed = provider.embed(&texts).await?;
+        for (index, vector) in missing.into_iter().zip(computed) {
+            if let Some(pool) = pool {
+                let candidate = &candidates[index];
+                let new_embedding = NewEmbedding::new(
+                    EmbeddingEntityType::Landmark,
+                    candidate.landmark_id,
+                    provider.model().to_string(),
+                    &candidate.text,
+                    vector.clone(),
+                );
+                if let Err(e) = new_embedding.upsert(pool) {
+                    tracing::warn!(
+                        target: "work_analyzer",
+                        "embedding_persist_failed landmark_id={} error={}",
+                        candidate.landmark_id,
+                        e
+                    );
+                }
+            }
+            vectors[index] = Some(vector);
+        }
+    }
+
+    vectors
+        .into_iter()
+        .map(|vector| {
+            vector.ok_or_else(|| {
+                PpdcError::new(
+                    500,
+                    ErrorType::InternalError,
+                    "Embedding provider returned fewer vectors than requested".to_string(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// For each query, the indices of its `k` nearest candidates, most similar first.
+pub async fn retrieve_top_k(
+    provider: &dyn EmbeddingProvider,
+    queries: &[String],
+    candidates: &[RetrievalCandidate],
+    k: usize,
+    pool: Option<&DbPool>,
+) -> Result<Vec<Vec<usize>>, PpdcError> {
+    let candidate_vectors = embed_landmarks(provider, candidates, pool).await?;
+    let query_vectors = provider.embed(queries).await?;
+    Ok(query_vectors
+        .iter()
+        .map(|query| embedding_provider::top_k(query, &candidate_vectors, k))
+        .collect())
+}
+
+/// `retrieve_top_k` with the configured provider and the global pool, as used by matching.
+pub async fn retrieve_top_k_from_env(
+    queries: &[String],
+    candidates: &[RetrievalCandidate],
+    k: usize,
+) -> Result<Vec<Vec<usize>>, PpdcError> {
+    let provider = EmbeddingProviderKind::from_env().build();
+    retrieve_top_k(
+        provider.as_ref(),
+        queries,
+        candidates,
+        k,
+        db::try_get_global_pool(),
+    )
+    .await
+}
+
+/// Embeds up to `batch_size` landmarks or elements that have no vector for the provider's model.
+/// Returns how many were embedded; callers repeat until it returns 0.
+pub async fn backfill_embeddings(
+    provider: &dyn EmbeddingProvider,
+    entity_type: EmbeddingEntityType,
+    batch_size: i64,
+    pool: &DbPool,
+) -> Result<usize, PpdcError> {
+    let sources =
+        Embedding::find_sources_missing_embedding(entity_type, provider.model(), batch_size, pool)?;
+    if sources.is_empty() {
+        return Ok(0);
+    }
+    let texts: Vec<String> = sources.iter().map(|source| source.text()).collect();
+    let vectors = provider.embed(&texts).await?;
+    let mut embedded = 0;
+    for ((source, text), vector) in sources.iter().zip(&texts).zip(vectors) {
+        NewEmbedding::new(
+            entity_type,
+            source.id,
+            provider.model().to_string(),
+            text,
+            vector,
+        )
+        .upsert(pool)?;
+        embedded += 1;
+    }
+    Ok(embedded)
+}
+
+/// Embeds elements right after matching creates them, so similarity search sees them
+/// before the next backfill. Failures are only logged; the backfill picks them up later.
+pub async fn embed_new_elements(
+    provider: &dyn EmbeddingProvider,
+    elements: &[Element],
+    pool: &DbPool,
+) -> usize {
+    if elements.is_empty() {
+        return 0;
+    }
+    let texts: Vec<String> = elements
+        .iter()
+        .map(|element| embedding_text(&element.title, &element.subtitle, &element.content))
+        .collect();
+    let vectors = match provider.embed(&texts).await {
+        Ok(vectors) => vectors,
+        Err(e) => {
+            tracing::warn!(
+                target: "work_analyzer",
+                "element_embedding_failed elements={} error={}",
+                elements.len(),
+                e
+            );
+            return 0;
+        }
+    };
+    let mut embedded = 0;
+    for ((element, text), vector) in elements.iter().zip(&texts).zip(vectors) {
+        let new_embedding = NewEmbedding::new(
+            EmbeddingEntityType::Element,
+            element.id,
+            provider.model().to_string(),
+            text,
+            vector,
+        );
+        match new_embedding.upsert(pool) {
+            Ok(()) => embedded += 1,
+            Err(e) => tracing::warn!(
+                target: "work_analyzer",
+                "embedding_persist_failed element_id={} error={}",
+                element.id,
+                e
+            ),
+        }
+    }
+    embedded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openai_handler::embedding_provider::StubEmbeddingProvider;
+
+    #[tokio::test]
+    async fn retrieves_topical_candidates_without_database() {
+        let provider = StubEmbeddingProvider::default();
+        let candidates: Vec<RetrievalCandidate> = [
+            "Dune\nFrank Herbert novel",
+            "Swimming\nWeekly habit",
+            "Rust programming\nLearning the borrow checker",
+            "Foundation\nIsaac Asimov novel",
+        ]
+        .into_iter()
+        .map(|text| RetrievalCandidate {
+            landmark_id: Uuid::new_v4(),
+            text: text.to_string(),
+        })
+        .collect();
+
+        let top = retrieve_top_k(
+            &provider,
+            &["Reading Dune by Frank Herbert".to_string()],
+            &candidates,
+            2,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].len(), 2);
+        assert_eq!(top[0][0], 0);
+    }
+}
@@ -4,7 +4,9 @@ use uuid::Uuid;
 use crate::entities_v2::error::PpdcError;
 use crate::openai_handler::GptRequestConfig;
 
-use super::gpt_request::{grammatical_extraction_schema, GrammaticalExtractionOutput};
+use crate::work_analyzer::prompt_registry;
+
+use super::gpt_request::GrammaticalExtractionOutput;
 
 #[derive(Debug, Serialize)]
 struct GrammaticalCorrectionPromptInput {
@@ -22,23 +24,23 @@ pub async fn correct_extraction(
     previous_output: GrammaticalExtractionOutput,
 ) -> Result<GrammaticalExtractionOutput, PpdcError> {
     let repair_hint = build_done_repair_hint(trace_text, &previous_output);
-    let correction_system_prompt = include_str!("correction_system.md").to_string();
+    let prompt = prompt_registry::prompt("element_pipeline_v2.correction")?;
     let correction_user_prompt = serde_json::to_string_pretty(&GrammaticalCorrectionPromptInput {
         previous_system_prompt,
         previous_user_prompt,
         previous_output,
         repair_hint,
     })?;
-    let schema = grammatical_extraction_schema()?;
 
     let request = GptRequestConfig::new(
         "gpt-4.1-mini".to_string(),
-        correction_system_prompt,
+        prompt.system_prompt.clone(),
         correction_user_prompt,
-        Some(schema),
+        Some(prompt.schema_json()?),
         Some(analysis_id),
     )
-    .with_display_name("Element Pipeline V2 / Grammatical Extraction Correction");
+    .with_display_name("Element Pipeline V2 / Grammatical Extraction Correction")
+    .with_prompt(prompt.prompt_ref());
 
     request.execute().await
 }
@@ -12,6 +12,7 @@ use crate::entities_v2::{
 };
 use crate::openai_handler::GptRequestConfig;
 use crate::work_analyzer::analysis_context::AnalysisContext;
+use crate::work_analyzer::prompt_registry::{self, PromptTemplate};
 
 #[derive(Debug, Serialize)]
 struct LandmarkSummary {
@@ -182,34 +183,35 @@ pub struct EvaluativeClaim {
     pub spans: Vec<String>,
 }
 
-pub fn grammatical_extraction_system_prompt() -> String {
-    include_str!("system.md").to_string()
-}
-
-pub fn grammatical_extraction_schema() -> Result<serde_json::Value, PpdcError> {
-    Ok(serde_json::from_str(include_str!("schema.json"))?)
-}
-
 pub async fn request_extraction_from_prompts(
     analysis_id: Uuid,
-    system_prompt: String,
+    prompt: &PromptTemplate,
     user_prompt: String,
 ) -> Result<GrammaticalExtractionOutput, PpdcError> {
-    let schema = grammatical_extraction_schema()?;
     let request = GptRequestConfig::new(
         "gpt-4.1-mini".to_string(),
-        system_prompt,
+        prompt.system_prompt.clone(),
         user_prompt,
-        Some(schema),
+        Some(prompt.schema_json()?),
         Some(analysis_id),
     )
-    .with_display_name("Element Pipeline V2 / Grammatical Extraction");
+    .with_display_name("Element Pipeline V2 / Grammatical Extraction")
+    .with_prompt(prompt.prompt_ref());
     request.execute().await
 }
 
-pub async fn request_extraction_with_prompts(
-    context: &AnalysisContext,
-    trace_mirror: &TraceMirror,
+/// A reference of the tagged trace text together with the landmark it points to.
+#[derive(Debug, Clone)]
+pub struct TaggedLandmark {
+    pub tag_id: i32,
+    pub mention: String,
+    pub landmark: Landmark,
+}
+
+pub async fn request_extraction_for_references(
+    analysis_id: Uuid,
+    trace_text: &str,
+    references: Vec<TaggedLandmark>,
 ) -> Result<
     (
         GrammaticalExtractionOutput,
@@ -221,20 +223,16 @@ pub async fn request_extraction_with_prompts(
     PpdcError,
 > {
     let (prompt_input, tag_to_landmark_id, hlp_id_to_uuid) =
-        build_prompt_input(context, trace_mirror)?;
-    let system_prompt = grammatical_extraction_system_prompt();
+        build_prompt_input(trace_text, references);
+    let prompt = prompt_registry::prompt("element_pipeline_v2.extraction")?;
     let user_prompt = serde_json::to_string_pretty(&prompt_input)?;
-    let raw_extraction = request_extraction_from_prompts(
-        context.analysis_id,
-        system_prompt.clone(),
-        user_prompt.clone(),
-    )
-    .await?;
+    let raw_extraction =
+        request_extraction_from_prompts(analysis_id, &prompt, user_prompt.clone()).await?;
     Ok((
         raw_extraction,
         tag_to_landmark_id,
         hlp_id_to_uuid,
-        system_prompt,
+        prompt.system_prompt,
         user_prompt,
     ))
 }
@@ -257,23 +255,12 @@ fn normalize_hlp_span_for_prompt(span: &str) -> String {
     format!("[{} ... {}]", first_50, last_50)
 }
 
-fn build_prompt_input(
+pub fn load_tagged_landmarks(
     context: &AnalysisContext,
     trace_mirror: &TraceMirror,
-) -> Result<
-    (
-        GrammaticalPromptInput,
-        HashMap<i32, Uuid>,
-        HashMap<i32, Uuid>,
-    ),
-    PpdcError,
-> {
+) -> Result<Vec<TaggedLandmark>, PpdcError> {
     let references = Reference::find_for_trace_mirror(trace_mirror.id, &context.pool)?;
-    let mut prompt_references = Vec::new();
-    let mut tag_to_landmark_id = HashMap::new();
-    let mut hlp_id_to_uuid = HashMap::new();
-    let mut high_level_projects: Vec<HighLevelProjectPromptItem> = Vec::new();
-    let mut high_level_project_index_by_landmark_id: HashMap<Uuid, usize> = HashMap::new();
+    let mut tagged_landmarks = Vec::new();
 
     for reference in references {
         let Some(landmark_id) = reference.landmark_id else {
@@ -299,11 +286,41 @@ fn build_prompt_input(
             }
         };
 
-        if let Some(existing_landmark_id) = tag_to_landmark_id.get(&reference.tag_id) {
+        tagged_landmarks.push(TaggedLandmark {
+            tag_id: reference.tag_id,
+            mention: reference.mention,
+            landmark,
+        });
+    }
+
+    Ok(tagged_landmarks)
+}
+
+fn build_prompt_input(
+    trace_text: &str,
+    references: Vec<TaggedLandmark>,
+) -> (
+    GrammaticalPromptInput,
+    HashMap<i32, Uuid>,
+    HashMap<i32, Uuid>,
+) {
+    let mut prompt_references = Vec::new();
+    let mut tag_to_landmark_id = HashMap::new();
+    let mut hlp_id_to_uuid = HashMap::new();
+    let mut high_level_projects: Vec<HighLevelProjectPromptItem> = Vec::new();
+    let mut high_level_project_index_by_landmark_id: HashMap<Uuid, usize> = HashMap::new();
+
+    for TaggedLandmark {
+        tag_id,
+        mention,
+        landmark,
+    } in references
+    {
+        let landmark_id = landmark.id;
+        if let Some(existing_landmark_id) = tag_to_landmark_id.get(&tag_id) {
             if *existing_landmark_id != landmark_id {
                 warn!(
-                    trace_mirror_id = %trace_mirror.id,
-                    tag_id = reference.tag_id,
+                    tag_id = tag_id,
                     existing_landmark_id = %existing_landmark_id,
                     new_landmark_id = %landmark_id,
                     "Duplicate tag_id mapped to different landmarks, keeping first mapping"
@@ -312,14 +329,14 @@ fn build_prompt_input(
             }
         }
 
-        tag_to_landmark_id.insert(reference.tag_id, landmark_id);
+        tag_to_landmark_id.insert(tag_id, landmark_id);
         if landmark.landmark_type == LandmarkType::HighLevelProject {
             if let Some(existing_index) = high_level_project_index_by_landmark_id
                 .get(&landmark_id)
                 .copied()
             {
                 let spans = &mut high_level_projects[existing_index].spans;
-                let normalized_span = normalize_hlp_span_for_prompt(&reference.mention);
+                let normalized_span = normalize_hlp_span_for_prompt(&mention);
                 if !spans.iter().any(|span| span == &normalized_span) {
                     spans.push(normalized_span);
                 }
@@ -333,14 +350,14 @@ fn build_prompt_input(
                     title: landmark.title.clone(),
                     subtitle: landmark.subtitle.clone(),
                     content: landmark.content.clone(),
-                    spans: vec![normalize_hlp_span_for_prompt(&reference.mention)],
+                    spans: vec![normalize_hlp_span_for_prompt(&mention)],
                 });
             }
         }
 
         prompt_references.push(ReferencePromptItem {
-            tag_id: reference.tag_id,
-            mention: reference.mention,
+            tag_id,
+            mention,
             landmark: LandmarkSummary {
                 title: landmark.title,
                 content: landmark.content,
@@ -349,13 +366,13 @@ fn build_prompt_input(
         });
     }
 
-    Ok((
+    (
         GrammaticalPromptInput {
-            trace_text: trace_mirror.content.clone(),
+            trace_text: trace_text.to_string(),
             references: prompt_references,
             high_level_projects,
         },
         tag_to_landmark_id,
         hlp_id_to_uuid,
-    ))
+    )
 }
@@ -1,46 +1,84 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
 use crate::entities_v2::error::PpdcError;
 use crate::entities_v2::{element::Element, trace_mirror::TraceMirror};
+use crate::openai_handler::EmbeddingProviderKind;
 use crate::work_analyzer::analysis_context::AnalysisContext;
+use crate::work_analyzer::candidate_retrieval;
 
 use super::gpt_correction::correct_extraction;
-use super::gpt_request::{request_extraction_with_prompts, GrammaticalExtractionOutput};
+use super::gpt_request::{load_tagged_landmarks, request_extraction_for_references};
 use super::persistence::persist_extraction;
 
+pub use super::gpt_request::{GrammaticalExtractionOutput, TaggedLandmark};
+
 #[derive(Debug, Clone)]
 pub struct GrammaticalStageOutput {
     pub created_elements: Vec<Element>,
     pub raw_extraction: GrammaticalExtractionOutput,
 }
 
+/// Corrected extraction with the prompt-local tag and high level project ids it refers to.
+#[derive(Debug, Clone)]
+pub struct GrammaticalExtraction {
+    pub raw_extraction: GrammaticalExtractionOutput,
+    pub tag_to_landmark_id: HashMap<i32, Uuid>,
+    pub hlp_id_to_uuid: HashMap<i32, Uuid>,
+}
+
 pub async fn run(
     context: &AnalysisContext,
     trace_mirror: &TraceMirror,
 ) -> Result<GrammaticalStageOutput, PpdcError> {
+    let references = load_tagged_landmarks(context, trace_mirror)?;
+    let extraction = extract(context.analysis_id, &trace_mirror.content, references).await?;
+    let created_elements = persist_extraction(
+        context,
+        trace_mirror,
+        &extraction.raw_extraction,
+        &extraction.tag_to_landmark_id,
+        &extraction.hlp_id_to_uuid,
+    )?;
+    candidate_retrieval::embed_new_elements(
+        EmbeddingProviderKind::from_env().build().as_ref(),
+        &created_elements,
+        &context.pool,
+    )
+    .await;
+
+    Ok(GrammaticalStageOutput {
+        created_elements,
+        raw_extraction: extraction.raw_extraction,
+    })
+}
+
+/// Runs the extraction and correction calls on a tagged trace text, without touching the database.
+pub async fn extract(
+    analysis_id: Uuid,
+    trace_text: &str,
+    references: Vec<TaggedLandmark>,
+) -> Result<GrammaticalExtraction, PpdcError> {
     let (
         initial_extraction,
         tag_to_landmark_id,
         hlp_id_to_uuid,
         previous_system_prompt,
         previous_user_prompt,
-    ) = request_extraction_with_prompts(context, trace_mirror).await?;
+    ) = request_extraction_for_references(analysis_id, trace_text, references).await?;
     let raw_extraction = correct_extraction(
-        context.analysis_id,
-        &trace_mirror.content,
+        analysis_id,
+        trace_text,
         previous_system_prompt,
         previous_user_prompt,
         initial_extraction,
     )
     .await?;
-    let created_elements = persist_extraction(
-        context,
-        trace_mirror,
-        &raw_extraction,
-        &tag_to_landmark_id,
-        &hlp_id_to_uuid,
-    )?;
 
-    Ok(GrammaticalStageOutput {
-        created_elements,
+    Ok(GrammaticalExtraction {
         raw_extraction,
+        tag_to_landmark_id,
+        hlp_id_to_uuid,
     })
 }
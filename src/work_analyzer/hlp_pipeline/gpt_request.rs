@@ -3,6 +3,7 @@ use uuid::Uuid;
 
 use crate::entities_v2::error::PpdcError;
 use crate::openai_handler::GptRequestConfig;
+use crate::work_analyzer::prompt_registry;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HighLevelProjectDraft {
@@ -40,8 +41,9 @@ pub async fn extract_high_level_projects(
     trace_text: &str,
     analysis_id: Uuid,
 ) -> Result<HlpExtractionResult, PpdcError> {
-    let system_prompt = include_str!("system.md").to_string();
-    let schema: serde_json::Value = serde_json::from_str(include_str!("schema.json"))?;
+    let prompt = prompt_registry::prompt("hlp_pipeline")?;
+    let system_prompt = prompt.system_prompt.clone();
+    let schema = prompt.schema_json()?;
     let user_prompt = serde_json::to_string_pretty(&HlpPromptInput {
         trace_type: "HIGH_LEVEL_PROJECTS_DEFINITION",
         trace_text: trace_text.to_string(),
@@ -54,7 +56,8 @@ pub async fn extract_high_level_projects(
         Some(schema),
         Some(analysis_id),
     )
-    .with_display_name("HLP Pipeline / Extraction");
+    .with_display_name("HLP Pipeline / Extraction")
+    .with_prompt(prompt.prompt_ref());
 
     request.execute().await
 }
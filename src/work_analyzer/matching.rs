@@ -1,9 +1,36 @@
+use crate::entities_v2::embedding::embedding_text;
 use crate::entities_v2::error::PpdcError;
 use crate::entities_v2::landmark::{Landmark, LandmarkType};
+use crate::environment;
 use crate::openai_handler::GptRequestConfig;
+use crate::work_analyzer::candidate_retrieval::{self, RetrievalCandidate};
+use crate::work_analyzer::observability::{
+    record_event, AnalysisEventData, CandidateBrief, Decision,
+};
+use crate::work_analyzer::prompt_registry;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::future::Future;
 use uuid::Uuid;
 
+tokio::task_local! {
+    static MATCHING_CONFIDENCE_THRESHOLD: f32;
+}
+
+/// Runs `future` with `threshold` as the minimum confidence for an LLM match to be kept.
+pub async fn with_confidence_threshold<F>(threshold: f32, future: F) -> F::Output
+where
+    F: Future,
+{
+    MATCHING_CONFIDENCE_THRESHOLD.scope(threshold, future).await
+}
+
+/// Matches below this confidence create a new landmark instead; no threshold outside an analysis.
+fn confidence_threshold() -> f32 {
+    MATCHING_CONFIDENCE_THRESHOLD
+        .try_with(|threshold| *threshold)
+        .unwrap_or(0.0)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Matches {
     pub matches: Vec<MatchingResult>,
@@ -53,6 +80,11 @@ where
 
 pub trait ElementWithIdentifier {
     fn identifier(&self) -> String;
+
+    /// Text used to retrieve candidate landmarks by embedding similarity.
+    fn embedding_text(&self) -> String {
+        self.identifier()
+    }
 }
 
 pub struct ElementMatched<ElementWithIdentifier> {
@@ -81,6 +113,18 @@ where
     for element in &elements {
         for landmark in landmarks {
             if element.identifier() == landmark.title {
+                record_event(
+                    analysis_id,
+                    AnalysisEventData::DecisionMade {
+                        step: display_name.to_string(),
+                        local_id: element.identifier(),
+                        decision: Decision::Match {
+                            landmark_id: landmark.id.to_string(),
+                        },
+                        confidence: 1.0,
+                        rationale: Some("exact title match".to_string()),
+                    },
+                );
                 result.push(ElementMatched {
                     element: element.clone(),
                     candidate_id: Some(landmark.id.to_string()),
@@ -97,13 +141,42 @@ where
         return Ok(result);
     }
 
-    // Now we match the remaining elements to the landmarks using the GPT API.
+    // Then we preselect the closest landmarks by embedding, so the GPT call only sees
+    // the union of each element's top-k candidates.
     let landmarks_for_matching: Vec<LandmarkForMatching> = landmarks
         .iter()
         .map(|landmark| LandmarkForMatching::from(landmark))
         .collect();
+    let candidates_by_element = preselect_candidates(
+        &to_process_elements,
+        &landmarks_for_matching,
+        environment::get_matching_candidates_top_k(),
+        analysis_id,
+    )
+    .await;
+    record_candidates_retrieved(
+        analysis_id,
+        display_name,
+        &candidates_by_element,
+        &landmarks_for_matching,
+    );
+    let selected_indices: std::collections::HashSet<usize> =
+        candidates_by_element.iter().flatten().copied().collect();
+    let landmarks_for_matching: Vec<LandmarkForMatching> = landmarks_for_matching
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| selected_indices.contains(index))
+        .map(|(_, landmark)| landmark)
+        .collect();
+
+    // Now we match the remaining elements to the candidates using the GPT API.
     let elements_local_array = LocalArray::from_vec(to_process_elements);
     let landmarks_local_array = LocalArray::from_vec(landmarks_for_matching);
+    let element_local_ids: Vec<String> = elements_local_array
+        .items
+        .iter()
+        .map(|item| item.local_id.clone())
+        .collect();
     let user_prompt: String = format!(
         "
         Elements: {}\n\n
@@ -112,18 +185,18 @@ where
         serde_json::to_string(&elements_local_array.items)?,
         serde_json::to_string(&landmarks_local_array.items)?
     );
-    let schema = include_str!("prompts/matching/schema.json").to_string();
-    let system_prompt = system_prompt
-        .unwrap_or(include_str!("prompts/matching/system.md"))
-        .to_string();
-    let gpt_request_config = GptRequestConfig::new(
+    let prompt = prompt_registry::prompt("matching")?;
+    let mut gpt_request_config = GptRequestConfig::new(
         "gpt-4.1-mini".to_string(),
-        system_prompt.to_string(),
+        system_prompt.unwrap_or(&prompt.system_prompt),
         &user_prompt,
-        Some(serde_json::from_str(&schema)?),
+        Some(prompt.schema_json()?),
         Some(analysis_id),
     )
     .with_display_name(display_name);
+    if system_prompt.is_none() {
+        gpt_request_config = gpt_request_config.with_prompt(prompt.prompt_ref());
+    }
     let matching_results: Matches = gpt_request_config.execute().await?;
 
     let attached_elements: Vec<ElementMatched<E>> =
@@ -132,12 +205,122 @@ where
             elements_local_array,
             landmarks_local_array,
         );
+    for (local_id, matched) in element_local_ids.iter().zip(&attached_elements) {
+        record_decision_made(analysis_id, display_name, local_id, matched);
+    }
 
     result.extend(attached_elements);
 
     Ok(result)
 }
 
+/// Indices into `landmarks` of each element's candidates. Small landscapes skip retrieval,
+/// and a failing embedding provider falls back to offering every landmark.
+async fn preselect_candidates<E>(
+    elements: &[E],
+    landmarks: &[LandmarkForMatching],
+    top_k: usize,
+    analysis_id: Uuid,
+) -> Vec<Vec<usize>>
+where
+    E: ElementWithIdentifier,
+{
+    let all_landmarks = || vec![(0..landmarks.len()).collect::<Vec<usize>>(); elements.len()];
+    if top_k == 0 || landmarks.len() <= top_k {
+        return all_landmarks();
+    }
+    let queries: Vec<String> = elements
+        .iter()
+        .map(|element| element.embedding_text())
+        .collect();
+    let candidates: Vec<RetrievalCandidate> = landmarks
+        .iter()
+        .map(|landmark| RetrievalCandidate {
+            landmark_id: landmark.id,
+            text: embedding_text(&landmark.title, &landmark.subtitle, &landmark.content),
+        })
+        .collect();
+    match candidate_retrieval::retrieve_top_k_from_env(&queries, &candidates, top_k).await {
+        Ok(candidates_by_element) => candidates_by_element,
+        Err(e) => {
+            tracing::warn!(
+                target: "work_analyzer",
+                "analysis_id: {} candidate_retrieval_failed landmarks={} error={}",
+                analysis_id,
+                landmarks.len(),
+                e
+            );
+            all_landmarks()
+        }
+    }
+}
+
+fn record_candidates_retrieved(
+    analysis_id: Uuid,
+    display_name: &str,
+    candidates_by_element: &[Vec<usize>],
+    landmarks: &[LandmarkForMatching],
+) {
+    for (local_id, candidate_indices) in candidates_by_element.iter().enumerate() {
+        let candidates: Vec<&LandmarkForMatching> = candidate_indices
+            .iter()
+            .map(|index| &landmarks[*index])
+            .collect();
+        let landmark_types: std::collections::HashSet<&'static str> = candidates
+            .iter()
+            .map(|landmark| landmark.landmark_type.to_code())
+            .collect();
+        let landmark_type = match landmark_types.len() {
+            1 => landmark_types.into_iter().next().unwrap_or_default(),
+            _ => "MIXED",
+        };
+        record_event(
+            analysis_id,
+            AnalysisEventData::CandidatesRetrieved {
+                step: display_name.to_string(),
+                local_id: local_id.to_string(),
+                landmark_type: landmark_type.to_string(),
+                k: candidates.len(),
+                candidates: candidates
+                    .iter()
+                    .map(|landmark| CandidateBrief {
+                        landmark_id: landmark.id.to_string(),
+                        title: landmark.title.clone(),
+                    })
+                    .collect(),
+            },
+        );
+    }
+}
+
+fn record_decision_made<E>(
+    analysis_id: Uuid,
+    display_name: &str,
+    local_id: &str,
+    matched: &ElementMatched<E>,
+) where
+    E: ElementWithIdentifier,
+{
+    let decision = match &matched.candidate_id {
+        Some(landmark_id) => Decision::Match {
+            landmark_id: landmark_id.clone(),
+        },
+        None => Decision::Create {
+            proposed_title: matched.element.identifier(),
+        },
+    };
+    record_event(
+        analysis_id,
+        AnalysisEventData::DecisionMade {
+            step: display_name.to_string(),
+            local_id: local_id.to_string(),
+            decision,
+            confidence: matched.confidence,
+            rationale: None,
+        },
+    );
+}
+
 pub fn attach_matching_results_to_elements_with_identifier<E>(
     matching_results: Vec<MatchingResult>,
     elements_local_array: LocalArray<E>,
@@ -164,6 +347,9 @@ where
                     .map(|item| item.item.id.to_string());
                 // TODO if no candidate found, there is an error in the matching_results vector.
             }
+            if confidence < confidence_threshold() {
+                candidate_id = None;
+            }
         }
         matched_elements.push(ElementMatched {
             element: element.item,
@@ -200,6 +386,8 @@ impl From<&Landmark> for LandmarkForMatching {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::openai_handler::llm_provider::{self, FixtureReplayProvider, LlmProviderSelection};
+    use std::sync::Arc;
 
     #[derive(Clone, Debug, Serialize, Deserialize)]
     struct TestLandmarkMatching {
@@ -256,4 +444,105 @@ mod tests {
         assert_eq!(result[0].confidence, 0.85);
         assert_eq!(result[0].candidate_id, Some(landmark_id.to_string()));
     }
+
+    #[tokio::test]
+    async fn matches_below_the_confidence_threshold_are_dropped() {
+        let element = TestLandmarkMatching {
+            temporary_id: "My Resource".to_string(),
+            matching_key: "My Resource".to_string(),
+            landmark_type: LandmarkType::Resource,
+            candidate_id: None,
+            confidence: 0.0,
+        };
+        let landmarks = LocalArray::from_vec(vec![LandmarkForMatching {
+            id: Uuid::new_v4(),
+            title: "Landmark Title".to_string(),
+            subtitle: String::new(),
+            content: String::new(),
+            landmark_type: LandmarkType::Resource,
+        }]);
+        let matching_results = vec![MatchingResult {
+            element_id: Some("0".to_string()),
+            candidate_id: Some("0".to_string()),
+            confidence: 0.25,
+        }];
+
+        let result: Vec<ElementMatched<TestLandmarkMatching>> =
+            with_confidence_threshold(0.3, async move {
+                attach_matching_results_to_elements_with_identifier(
+                    matching_results,
+                    LocalArray::from_vec(vec![element]),
+                    landmarks,
+                )
+            })
+            .await;
+
+        assert_eq!(result[0].candidate_id, None);
+        assert_eq!(result[0].confidence, 0.25);
+    }
+
+    fn test_landmark(title: &str) -> Landmark {
+        let now = chrono::Utc::now().naive_utc();
+        Landmark {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            subtitle: String::new(),
+            content: String::new(),
+            external_content_url: None,
+            comment: None,
+            image_url: None,
+            landmark_type: LandmarkType::Resource,
+            maturing_state: crate::entities_v2::MaturingState::Finished,
+            related_elements_count: 0,
+            last_related_element_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_match_elements_runs_offline_with_fixture_provider() {
+        let landmarks = vec![test_landmark("Exact"), test_landmark("Other")];
+        let elements = vec![
+            TestLandmarkMatching {
+                temporary_id: "0".to_string(),
+                matching_key: "Exact".to_string(),
+                landmark_type: LandmarkType::Resource,
+                candidate_id: None,
+                confidence: 0.0,
+            },
+            TestLandmarkMatching {
+                temporary_id: "1".to_string(),
+                matching_key: "Fuzzy other".to_string(),
+                landmark_type: LandmarkType::Resource,
+                candidate_id: None,
+                confidence: 0.0,
+            },
+        ];
+        let provider = FixtureReplayProvider::in_memory().with_step_output(
+            "Test / Matching",
+            serde_json::json!({
+                "matches": [{ "element_id": "0", "candidate_id": "1", "confidence": 0.9 }]
+            }),
+        );
+        let selection = LlmProviderSelection::new(Arc::new(provider));
+
+        let matched = llm_provider::with_selection(
+            selection,
+            match_elements(
+                elements,
+                &landmarks,
+                None,
+                Uuid::new_v4(),
+                "Test / Matching",
+            ),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(matched.len(), 2);
+        assert_eq!(matched[0].candidate_id, Some(landmarks[0].id.to_string()));
+        assert_eq!(matched[1].candidate_id, Some(landmarks[1].id.to_string()));
+        assert_eq!(matched[1].confidence, 0.9);
+    }
 }
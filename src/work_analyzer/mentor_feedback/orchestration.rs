@@ -4,6 +4,7 @@ use crate::entities_v2::message::{MentorFeedbackMetadata, Message};
 use crate::entities_v2::user::User;
 use crate::openai_handler::{GptReasoningEffort, GptRequestConfig, GptVerbosity};
 use crate::work_analyzer::analysis_context::AnalysisContext;
+use crate::work_analyzer::prompt_registry;
 use serde::{Deserialize, Serialize};
 
 use super::context::build as build_context;
@@ -40,8 +41,9 @@ pub async fn send(
     );
     let mentor_biography = prompt_context.mentor.biography.clone();
     let mentor_specific_prompt = prompt_context.mentor.mentor_specific_prompt.clone();
-    let system_prompt = include_str!("system.md").to_string();
-    let schema: serde_json::Value = serde_json::from_str(include_str!("schema.json"))?;
+    let prompt = prompt_registry::prompt("mentor_feedback")?;
+    let system_prompt = prompt.system_prompt.clone();
+    let schema = prompt.schema_json()?;
     let user_prompt = serde_json::to_string_pretty(&MentorFeedbackPromptInput {
         mentor_name,
         mentor_biography,
@@ -59,6 +61,7 @@ pub async fn send(
     .with_reasoning_effort(GptReasoningEffort::Low)
     .with_verbosity(GptVerbosity::Low)
     .with_display_name("Mentor Feedback / Day Feedback")
+    .with_prompt(prompt.prompt_ref())
     .execute::<MentorFeedbackDraft>()
     .await?;
 
@@ -9,6 +9,7 @@ use crate::entities_v2::message::{
 use crate::entities_v2::trace::Trace;
 use crate::entities_v2::user::User;
 use crate::openai_handler::{GptReasoningEffort, GptRequestConfig, GptVerbosity};
+use crate::work_analyzer::prompt_registry;
 
 use super::context::build as build_context;
 
@@ -123,8 +124,9 @@ async fn run_standard_reply_pipeline(
         pool,
     )?;
 
-    let system_prompt = include_str!("system.md").to_string();
-    let schema: serde_json::Value = serde_json::from_str(include_str!("schema.json"))?;
+    let prompt = prompt_registry::prompt("message_processing.mentor_reply")?;
+    let system_prompt = prompt.system_prompt.clone();
+    let schema = prompt.schema_json()?;
     let user_prompt = serde_json::to_string_pretty(&prompt_context)?;
     let reply = GptRequestConfig::new(
         "gpt-5.1".to_string(),
@@ -136,6 +138,7 @@ async fn run_standard_reply_pipeline(
     .with_reasoning_effort(GptReasoningEffort::Low)
     .with_verbosity(GptVerbosity::Low)
     .with_display_name("Message Processing / Mentor Reply")
+    .with_prompt(prompt.prompt_ref())
     .execute::<TraceReplyDraft>()
     .await?;
 
@@ -186,8 +189,9 @@ async fn run_tarot_reply_pipeline(
         pool,
     )?;
 
-    let system_prompt = include_str!("tarot_system.md").to_string();
-    let schema: serde_json::Value = serde_json::from_str(include_str!("tarot_schema.json"))?;
+    let prompt = prompt_registry::prompt("message_processing.tarot_reply")?;
+    let system_prompt = prompt.system_prompt.clone();
+    let schema = prompt.schema_json()?;
     let user_prompt = serde_json::to_string_pretty(&prompt_context)?;
     let reply = GptRequestConfig::new(
         "gpt-5.1".to_string(),
@@ -199,6 +203,7 @@ async fn run_tarot_reply_pipeline(
     .with_reasoning_effort(GptReasoningEffort::Low)
     .with_verbosity(GptVerbosity::Low)
     .with_display_name("Message Processing / Tarot Reading Reply")
+    .with_prompt(prompt.prompt_ref())
     .execute::<TarotReplyDraft>()
     .await?;
 
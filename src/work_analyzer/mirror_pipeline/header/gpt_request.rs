@@ -3,9 +3,11 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::entities_v2::bio_profile::{BioProfile, BioProfileEntry};
 use crate::entities_v2::error::PpdcError;
-use crate::entities_v2::{landmark::Landmark, trace::Trace};
+use crate::entities_v2::landmark::Landmark;
 use crate::openai_handler::GptRequestConfig;
+use crate::work_analyzer::prompt_registry;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MirrorHeader {
@@ -16,7 +18,7 @@ pub struct MirrorHeader {
     pub high_level_projects: Vec<SelectedHighLevelProject>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MirrorHeaderTraceMirrorType {
     #[serde(alias = "BIO")]
     Bio,
@@ -36,6 +38,35 @@ pub struct SelectedHighLevelProject {
 struct MirrorHeaderPromptInput {
     trace_text: String,
     high_level_projects: Vec<HighLevelProjectPromptItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_profile: Option<UserProfilePromptItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct UserProfilePromptItem {
+    summary: String,
+    roles: Vec<String>,
+    skills: Vec<String>,
+    places: Vec<String>,
+    organizations: Vec<String>,
+}
+
+impl UserProfilePromptItem {
+    fn from_profile(profile: &BioProfile) -> Self {
+        let titles = |entries: &[BioProfileEntry]| {
+            entries
+                .iter()
+                .map(|entry| entry.title.clone())
+                .collect::<Vec<_>>()
+        };
+        Self {
+            summary: profile.summary.clone(),
+            roles: titles(&profile.sections.roles),
+            skills: titles(&profile.sections.skills),
+            places: titles(&profile.sections.places),
+            organizations: titles(&profile.sections.organizations),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -55,15 +86,17 @@ pub fn build_high_level_project_index_map(high_level_projects: &[Landmark]) -> H
 }
 
 pub async fn extract_mirror_header(
-    trace: &Trace,
+    trace_text: &str,
     analysis_id: Uuid,
     high_level_projects: &[Landmark],
+    bio_profile: Option<&BioProfile>,
 ) -> Result<MirrorHeader, PpdcError> {
-    let system_prompt = include_str!("system.md").to_string();
-    let schema: serde_json::Value = serde_json::from_str(include_str!("schema.json"))?;
+    let prompt = prompt_registry::prompt("mirror_header")?;
+    let system_prompt = prompt.system_prompt.clone();
+    let schema = prompt.schema_json()?;
 
     let prompt_input = MirrorHeaderPromptInput {
-        trace_text: trace.content.clone(),
+        trace_text: trace_text.to_string(),
         high_level_projects: high_level_projects
             .iter()
             .enumerate()
@@ -74,6 +107,7 @@ pub async fn extract_mirror_header(
                 content: hlp.content.clone(),
             })
             .collect::<Vec<_>>(),
+        user_profile: bio_profile.map(UserProfilePromptItem::from_profile),
     };
 
     let user_prompt = serde_json::to_string_pretty(&prompt_input)?;
@@ -84,7 +118,8 @@ pub async fn extract_mirror_header(
         Some(schema),
         Some(analysis_id),
     )
-    .with_display_name("Mirror / Header Extraction");
+    .with_display_name("Mirror / Header Extraction")
+    .with_prompt(prompt.prompt_ref());
 
     config.execute().await
 }
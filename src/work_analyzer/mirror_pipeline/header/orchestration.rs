@@ -20,8 +20,13 @@ pub async fn run(
     context: &AnalysisContext,
 ) -> Result<TraceMirror, PpdcError> {
     let hlp_index_to_uuid = build_high_level_project_index_map(&high_level_projects);
-    let mut header =
-        extract_mirror_header(trace, context.analysis_id, &high_level_projects).await?;
+    let mut header = extract_mirror_header(
+        &trace.content,
+        context.analysis_id,
+        &high_level_projects,
+        context.bio_profile.as_ref(),
+    )
+    .await?;
     if let Some(journal_id) = trace.journal_id {
         let journal = Journal::find_full(journal_id, &context.pool)?;
         if journal.journal_type == JournalType::MetaJournal {
@@ -4,6 +4,7 @@ use crate::entities_v2::MaturingState;
 use crate::openai_handler::GptRequestConfig;
 use crate::work_analyzer::analysis_context::AnalysisContext;
 use crate::work_analyzer::mirror_pipeline::primary_resource::matching::PrimaryResourceMatched;
+use crate::work_analyzer::prompt_registry;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,16 +27,16 @@ pub async fn run(
             "Primary resource already created".to_string(),
         ));
     }
-    let system_prompt = include_str!("../prompts/primary_resource/creation/system.md").to_string();
-    let schema = include_str!("../prompts/primary_resource/creation/schema.json").to_string();
+    let prompt = prompt_registry::prompt("primary_resource.creation")?;
     let gpt_request_config = GptRequestConfig::new(
         "gpt-4.1-mini".to_string(),
-        system_prompt,
+        prompt.system_prompt.clone(),
         &serde_json::to_string(&element)?,
-        Some(serde_json::from_str(&schema)?),
+        Some(prompt.schema_json()?),
         Some(context.analysis_id),
     )
-    .with_display_name("Mirror / Primary Resource Creation");
+    .with_display_name("Mirror / Primary Resource Creation")
+    .with_prompt(prompt.prompt_ref());
     let primary_resource_created = gpt_request_config.execute().await?;
     let new_landmark = create_primary_resource(primary_resource_created, context).await?;
     Ok(new_landmark)
@@ -2,6 +2,7 @@ use crate::entities_v2::error::{ErrorType, PpdcError};
 use crate::entities_v2::trace::Trace;
 use crate::openai_handler::GptRequestConfig;
 use crate::work_analyzer::matching::ElementWithIdentifier;
+use crate::work_analyzer::prompt_registry;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -19,6 +20,18 @@ impl ElementWithIdentifier for PrimaryResourceSuggestion {
     fn identifier(&self) -> String {
         self.resource_identifier.clone()
     }
+
+    fn embedding_text(&self) -> String {
+        [
+            Some(self.resource_identifier.as_str()),
+            self.author.as_deref(),
+            self.theme.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
 }
 
 /// Returns the evidence strings that are not exact substrings of `trace_content`.
@@ -34,10 +47,9 @@ pub async fn extract(
     trace: &Trace,
     analysis_id: Uuid,
 ) -> Result<PrimaryResourceSuggestion, PpdcError> {
-    let system_prompt =
-        include_str!("../prompts/primary_resource/suggestion/system.md").to_string();
-    let schema_str = include_str!("../prompts/primary_resource/suggestion/schema.json");
-    let schema: serde_json::Value = serde_json::from_str(schema_str)?;
+    let prompt = prompt_registry::prompt("primary_resource.suggestion")?;
+    let system_prompt = prompt.system_prompt.clone();
+    let schema = prompt.schema_json()?;
     let trace_content = trace.content.clone();
     let mut last_invalid: Option<Vec<String>> = None;
     let mut retry_count = 0u32;
@@ -62,7 +74,8 @@ pub async fn extract(
             Some(schema.clone()),
             Some(analysis_id),
         )
-        .with_display_name("Mirror / Primary Resource Suggestion");
+        .with_display_name("Mirror / Primary Resource Suggestion")
+        .with_prompt(prompt.prompt_ref());
         let result: PrimaryResourceSuggestion = config.execute().await?;
 
         let invalid = invalid_evidence(&result.evidence, &trace_content);
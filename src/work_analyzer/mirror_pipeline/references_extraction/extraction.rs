@@ -1,6 +1,7 @@
 use crate::db::DbPool;
 use crate::entities_v2::error::PpdcError;
 use crate::entities_v2::{landmark::Landmark, trace_mirror::TraceMirror};
+use crate::work_analyzer::prompt_registry;
 use uuid::Uuid;
 
 use super::{
@@ -8,7 +9,6 @@ use super::{
     gpt_request::{
         build_context, build_high_level_project_index_map, build_high_level_projects_context,
         build_references_user_prompt, get_references_drafts_from_prompts,
-        references_extraction_system_prompt,
     },
     persistence::persist_references_and_landmarks,
 };
@@ -31,23 +31,11 @@ pub async fn run(
     let high_level_projects_context =
         build_high_level_projects_context(&trace_mirror_high_level_projects_references, pool)?;
     let hlp_index_to_uuid = build_high_level_project_index_map(&high_level_projects_context);
-    let initial_system_prompt = references_extraction_system_prompt();
-    let initial_user_prompt = build_references_user_prompt(
+    let extraction_result = extract_references(
+        analysis_id,
         &trace_mirror.content,
         &context,
         &high_level_projects_context,
-    )?;
-    let extraction_result = get_references_drafts_from_prompts(
-        initial_system_prompt.clone(),
-        initial_user_prompt.clone(),
-        analysis_id,
-    )
-    .await?;
-    let extraction_result = correct_references_drafts(
-        analysis_id,
-        initial_system_prompt,
-        initial_user_prompt,
-        extraction_result,
     )
     .await?;
     let ReferencesExtractionResult {
@@ -69,3 +57,25 @@ pub async fn run(
     trace_mirror.content = tagged_text;
     trace_mirror.update(pool)
 }
+
+/// Runs the extraction and correction calls on a trace text, without reading or writing the database.
+pub async fn extract_references(
+    analysis_id: Uuid,
+    trace_text: &str,
+    context: &[LandmarkReferenceContextItem],
+    high_level_projects_context: &[HighLevelProjectContextItem],
+) -> Result<ReferencesExtractionResult, PpdcError> {
+    let prompt = prompt_registry::prompt("references_extraction")?;
+    let initial_user_prompt =
+        build_references_user_prompt(trace_text, context, high_level_projects_context)?;
+    let extraction_result =
+        get_references_drafts_from_prompts(&prompt, initial_user_prompt.clone(), analysis_id)
+            .await?;
+    correct_references_drafts(
+        analysis_id,
+        prompt.system_prompt,
+        initial_user_prompt,
+        extraction_result,
+    )
+    .await
+}
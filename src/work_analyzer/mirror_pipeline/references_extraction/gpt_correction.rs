@@ -5,7 +5,9 @@ use uuid::Uuid;
 use crate::entities_v2::error::PpdcError;
 use crate::openai_handler::GptRequestConfig;
 
-use super::gpt_request::{references_extraction_schema, ReferencesExtractionResult};
+use crate::work_analyzer::prompt_registry;
+
+use super::gpt_request::ReferencesExtractionResult;
 
 #[derive(Debug, Serialize)]
 struct ReferencesCorrectionPromptInput {
@@ -22,7 +24,7 @@ pub async fn correct_references_drafts(
     previous_output: ReferencesExtractionResult,
 ) -> Result<ReferencesExtractionResult, PpdcError> {
     let repair_hint = build_date_extraction_repair_hint(&previous_output);
-    let correction_system_prompt = include_str!("correction_system.md").to_string();
+    let prompt = prompt_registry::prompt("references_extraction.correction")?;
     let correction_prompt_input = ReferencesCorrectionPromptInput {
         previous_system_prompt,
         previous_user_prompt,
@@ -30,16 +32,16 @@ pub async fn correct_references_drafts(
         repair_hint,
     };
     let correction_user_prompt = serde_json::to_string_pretty(&correction_prompt_input)?;
-    let schema = references_extraction_schema()?;
 
     let config = GptRequestConfig::new(
         "gpt-4.1-mini".to_string(),
-        correction_system_prompt,
+        prompt.system_prompt.clone(),
         correction_user_prompt,
-        Some(schema),
+        Some(prompt.schema_json()?),
         Some(analysis_id),
     )
-    .with_display_name("Mirror / References Extraction Correction");
+    .with_display_name("Mirror / References Extraction Correction")
+    .with_prompt(prompt.prompt_ref());
 
     config.execute().await
 }
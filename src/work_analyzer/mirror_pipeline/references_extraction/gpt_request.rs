@@ -5,6 +5,7 @@ use crate::entities_v2::error::PpdcError;
 use crate::entities_v2::landmark::{Landmark, LandmarkType};
 use crate::entities_v2::reference::model::{Reference, ReferenceType};
 use crate::openai_handler::GptRequestConfig;
+use crate::work_analyzer::prompt_registry::PromptTemplate;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 use uuid::Uuid;
@@ -70,14 +71,6 @@ pub struct HighLevelProjectContextItem {
     pub spans: Vec<String>,
 }
 
-pub fn references_extraction_system_prompt() -> String {
-    include_str!("system.md").to_string()
-}
-
-pub fn references_extraction_schema() -> Result<serde_json::Value, PpdcError> {
-    Ok(serde_json::from_str(include_str!("schema.json"))?)
-}
-
 pub fn build_references_user_prompt(
     text: &str,
     context: &[LandmarkReferenceContextItem],
@@ -94,18 +87,19 @@ pub fn build_references_user_prompt(
 }
 
 pub async fn get_references_drafts_from_prompts(
-    system_prompt: String,
+    prompt: &PromptTemplate,
     user_prompt: String,
     analysis_id: Uuid,
 ) -> Result<ReferencesExtractionResult, PpdcError> {
-    let schema = references_extraction_schema()?;
     let gpt_request_config = GptRequestConfig::new(
         "gpt-4.1-mini".to_string(),
-        system_prompt,
+        prompt.system_prompt.clone(),
         user_prompt,
-        Some(schema),
+        Some(prompt.schema_json()?),
         Some(analysis_id),
-    );
+    )
+    .with_display_name("Mirror / References Extraction")
+    .with_prompt(prompt.prompt_ref());
     let result: ReferencesExtractionResult = gpt_request_config.execute().await?;
     Ok(result)
 }
@@ -1,11 +1,14 @@
 pub mod active_context_filtering;
 pub mod analysis_context;
 pub mod analysis_processor;
+pub mod candidate_retrieval;
 pub mod period_analysis_processor;
 //pub mod match_elements_and_landmarks;
 pub mod element_pipeline_v2;
 //pub mod update_landmarks;
 pub mod analysis_queue;
+pub mod analysis_unlock;
+pub mod bio_pipeline;
 pub mod high_level_analysis;
 pub mod hlp_pipeline;
 pub mod matching;
@@ -14,7 +17,9 @@ pub mod message_processing;
 pub mod mirror_pipeline;
 pub mod observability;
 pub mod period_summary;
+pub mod pipeline_eval;
 pub mod plausible_landmarks_context;
+pub mod prompt_registry;
 
 pub use analysis_queue::run_lens;
 pub use message_processing::run_message;
@@ -1,7 +1,9 @@
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use uuid::Uuid;
 
+use crate::db;
 use crate::environment;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,8 +23,24 @@ pub fn get_observability_mode() -> ObservabilityMode {
     }
 }
 
+tokio::task_local! {
+    static SENSITIVE_TEXT_REDACTED: ();
+}
+
+/// Runs `future` with user text kept out of logs and persisted LLM calls, whatever the mode.
+pub async fn with_sensitive_text_redacted<F>(future: F) -> F::Output
+where
+    F: Future,
+{
+    SENSITIVE_TEXT_REDACTED.scope((), future).await
+}
+
+pub fn is_sensitive_text_redacted() -> bool {
+    SENSITIVE_TEXT_REDACTED.try_with(|_| ()).is_ok()
+}
+
 pub fn should_log_sensitive_text() -> bool {
-    matches!(get_observability_mode(), ObservabilityMode::Full)
+    matches!(get_observability_mode(), ObservabilityMode::Full) && !is_sensitive_text_redacted()
 }
 
 pub fn format_text_log_field(name: &str, value: &str) -> String {
@@ -85,6 +103,41 @@ pub enum AnalysisEventData {
     },
 }
 
+impl AnalysisEventData {
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            AnalysisEventData::StepStarted { .. } => "StepStarted",
+            AnalysisEventData::StepFinished { .. } => "StepFinished",
+            AnalysisEventData::LlmCallStarted { .. } => "LlmCallStarted",
+            AnalysisEventData::LlmCallFinished { .. } => "LlmCallFinished",
+            AnalysisEventData::CandidatesRetrieved { .. } => "CandidatesRetrieved",
+            AnalysisEventData::DecisionMade { .. } => "DecisionMade",
+            AnalysisEventData::Error { .. } => "Error",
+        }
+    }
+}
+
+/// Appends an event to the analysis log. Never fails the pipeline: without a database
+/// (unit tests, bintest) the event is dropped, and write errors are only logged.
+pub fn record_event(analysis_id: Uuid, event: AnalysisEventData) {
+    if environment::get_env() == "bintest" {
+        return;
+    }
+    let Some(pool) = db::try_get_global_pool() else {
+        return;
+    };
+    let event_type = event.event_type();
+    if let Err(e) = AnalysisEvent::create(analysis_id, event, pool) {
+        tracing::warn!(
+            target: "work_analyzer",
+            "analysis_id: {} analysis_event_persist_failed type={} error={}",
+            analysis_id,
+            event_type,
+            e
+        );
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CandidateBrief {
     pub landmark_id: String,
@@ -97,3 +150,37 @@ pub enum Decision {
     Create { proposed_title: String },
     Skip,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_type_matches_serialized_tag() {
+        let events = vec![
+            AnalysisEventData::StepStarted {
+                step: "mirror_pipeline".to_string(),
+            },
+            AnalysisEventData::DecisionMade {
+                step: "Matching".to_string(),
+                local_id: "0".to_string(),
+                decision: Decision::Create {
+                    proposed_title: "New landmark".to_string(),
+                },
+                confidence: 0.4,
+                rationale: None,
+            },
+            AnalysisEventData::Error {
+                step: "Matching".to_string(),
+                message: "rate limited".to_string(),
+                retry_in_seconds: Some(2),
+            },
+        ];
+        for event in events {
+            let value = serde_json::to_value(&event).unwrap();
+            assert_eq!(value["type"], event.event_type());
+            let round_trip: AnalysisEventData = serde_json::from_value(value).unwrap();
+            assert_eq!(round_trip.event_type(), event.event_type());
+        }
+    }
+}
@@ -1,8 +1,10 @@
 pub mod day_context;
 pub mod orchestration;
 pub mod persistence;
+pub mod rollup_context;
 pub mod week_context;
 
 pub use day_context::{build as build_day_context, DaySummaryPromptContext};
-pub use orchestration::{run_day, run_week};
+pub use orchestration::{run_day, run_rollup, run_week};
+pub use rollup_context::{build as build_rollup_context, RollupSummaryPromptContext};
 pub use week_context::{build as build_week_context, WeekSummaryPromptContext};
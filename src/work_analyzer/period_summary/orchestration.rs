@@ -4,9 +4,11 @@ use crate::entities_v2::error::PpdcError;
 use crate::entities_v2::landscape_analysis::LandscapeAnalysis;
 use crate::openai_handler::GptRequestConfig;
 use crate::work_analyzer::analysis_context::AnalysisContext;
+use crate::work_analyzer::prompt_registry;
 
 use super::day_context::build as build_day_context;
 use super::persistence::upsert_period_recap;
+use super::rollup_context::build as build_rollup_context;
 use super::week_context::build as build_week_context;
 use serde::Deserialize;
 
@@ -23,8 +25,9 @@ pub async fn run_day(
     analysis: &LandscapeAnalysis,
 ) -> Result<AnalysisSummary, PpdcError> {
     let prompt_context = build_day_context(context, analysis)?;
-    let system_prompt = include_str!("day_system.md").to_string();
-    let schema: serde_json::Value = serde_json::from_str(include_str!("day_schema.json"))?;
+    let prompt = prompt_registry::prompt("period_summary.day")?;
+    let system_prompt = prompt.system_prompt.clone();
+    let schema = prompt.schema_json()?;
     let user_prompt = serde_json::to_string_pretty(&prompt_context)?;
 
     let summary = GptRequestConfig::new(
@@ -35,6 +38,7 @@ pub async fn run_day(
         Some(context.analysis_id),
     )
     .with_display_name("Period Summary / Day Summary")
+    .with_prompt(prompt.prompt_ref())
     .execute::<DaySummaryDraft>()
     .await?;
 
@@ -52,8 +56,9 @@ pub async fn run_week(
     analysis: &LandscapeAnalysis,
 ) -> Result<AnalysisSummary, PpdcError> {
     let prompt_context = build_week_context(context, analysis)?;
-    let system_prompt = include_str!("week_system.md").to_string();
-    let schema: serde_json::Value = serde_json::from_str(include_str!("week_schema.json"))?;
+    let prompt = prompt_registry::prompt("period_summary.week")?;
+    let system_prompt = prompt.system_prompt.clone();
+    let schema = prompt.schema_json()?;
     let user_prompt = serde_json::to_string_pretty(&prompt_context)?;
 
     let summary = GptRequestConfig::new(
@@ -64,6 +69,38 @@ pub async fn run_week(
         Some(context.analysis_id),
     )
     .with_display_name("Period Summary / Week Summary")
+    .with_prompt(prompt.prompt_ref())
+    .execute::<DaySummaryDraft>()
+    .await?;
+
+    upsert_period_recap(
+        context,
+        summary.title,
+        summary.short_content,
+        summary.content,
+        summary.meaningful_event,
+    )
+}
+
+pub async fn run_rollup(
+    context: &AnalysisContext,
+    analysis: &LandscapeAnalysis,
+) -> Result<AnalysisSummary, PpdcError> {
+    let prompt_context = build_rollup_context(context, analysis)?;
+    let prompt = prompt_registry::prompt("period_summary.rollup")?;
+    let system_prompt = prompt.system_prompt.clone();
+    let schema = prompt.schema_json()?;
+    let user_prompt = serde_json::to_string_pretty(&prompt_context)?;
+
+    let summary = GptRequestConfig::new(
+        "gpt-4.1-mini".to_string(),
+        system_prompt,
+        user_prompt,
+        Some(schema),
+        Some(context.analysis_id),
+    )
+    .with_display_name("Period Summary / Rollup Summary")
+    .with_prompt(prompt.prompt_ref())
     .execute::<DaySummaryDraft>()
     .await?;
 
@@ -0,0 +1,303 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::entities_v2::analysis_summary::AnalysisSummary;
+use crate::entities_v2::error::PpdcError;
+use crate::entities_v2::landscape_analysis::model::LandscapeAnalysisType;
+use crate::entities_v2::landscape_analysis::LandscapeAnalysis;
+use crate::work_analyzer::analysis_context::AnalysisContext;
+
+use super::week_context::{
+    find_current_lens_high_level_projects, find_period_summary_for_analysis,
+    HighLevelProjectContextItem, SummaryContextItem,
+};
+
+#[derive(Debug, Serialize)]
+pub struct RollupSummaryPromptContext {
+    pub recap_type: &'static str,
+    pub period_start: NaiveDateTime,
+    pub period_end: NaiveDateTime,
+    pub previous_period_summary: Option<RollupPeriodContextItem>,
+    pub weeks: Vec<RollupPeriodContextItem>,
+    pub days: Vec<RollupPeriodContextItem>,
+    pub no_summaries_note: Option<String>,
+    pub high_level_projects: Vec<HighLevelProjectContextItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RollupPeriodContextItem {
+    pub analysis_id: Uuid,
+    pub period_start: NaiveDateTime,
+    pub period_end: NaiveDateTime,
+    /// True when the summarized period runs past the rollup period.
+    pub partial: bool,
+    pub summary: SummaryContextItem,
+}
+
+/// Builds the rollup context from the weekly summaries overlapping the period, plus daily summaries for days no fully included week covers.
+pub fn build(
+    context: &AnalysisContext,
+    analysis: &LandscapeAnalysis,
+) -> Result<RollupSummaryPromptContext, PpdcError> {
+    let scoped_lenses = analysis.get_scoped_lenses(&context.pool)?;
+    let current_lens = scoped_lenses
+        .iter()
+        .find(|lens| lens.current_landscape_id == Some(analysis.id))
+        .or_else(|| scoped_lenses.first());
+
+    let (weeks, days, previous_period_summary, high_level_projects) =
+        if let Some(current_lens) = current_lens {
+            let all_scope_analyses = current_lens.get_analysis_scope(&context.pool)?;
+            let weeks = summarized_periods_overlapping(
+                &all_scope_analyses,
+                LandscapeAnalysisType::WeeklyRecap,
+                analysis,
+                &context.pool,
+            )?;
+            let days = summarized_periods_overlapping(
+                &all_scope_analyses,
+                LandscapeAnalysisType::DailyRecap,
+                analysis,
+                &context.pool,
+            )?
+            .into_iter()
+            .filter(|day| !covered_by_full_week(day, &weeks))
+            .collect::<Vec<_>>();
+            let previous_period_summary =
+                if analysis.landscape_analysis_type == LandscapeAnalysisType::MonthlyRecap {
+                    find_previous_period_summary(analysis, &all_scope_analyses, &context.pool)?
+                } else {
+                    None
+                };
+            (
+                weeks,
+                days,
+                previous_period_summary,
+                find_current_lens_high_level_projects(
+                    current_lens.current_landscape_id,
+                    &context.pool,
+                )?,
+            )
+        } else {
+            (vec![], vec![], None, vec![])
+        };
+    let no_summaries_note = if weeks.is_empty() && days.is_empty() {
+        Some("No weekly or daily recap exists for this period.".to_string())
+    } else {
+        None
+    };
+
+    Ok(RollupSummaryPromptContext {
+        recap_type: analysis.landscape_analysis_type.to_db(),
+        period_start: analysis.period_start,
+        period_end: analysis.period_end,
+        previous_period_summary,
+        weeks,
+        days,
+        no_summaries_note,
+        high_level_projects,
+    })
+}
+
+/// Analyses of `analysis_type` overlapping the rollup period, flagged partial when they run
+/// past it (the weeks straddling the first and last days of a month).
+fn overlapping_periods<'a>(
+    scope_analyses: &'a [LandscapeAnalysis],
+    analysis_type: LandscapeAnalysisType,
+    rollup_analysis: &LandscapeAnalysis,
+) -> Vec<(&'a LandscapeAnalysis, bool)> {
+    let mut candidates = scope_analyses
+        .iter()
+        .filter(|analysis| analysis.landscape_analysis_type == analysis_type)
+        .filter(|analysis| analysis.period_start < rollup_analysis.period_end)
+        .filter(|analysis| analysis.period_end > rollup_analysis.period_start)
+        .map(|analysis| {
+            let partial = analysis.period_start < rollup_analysis.period_start
+                || analysis.period_end > rollup_analysis.period_end;
+            (analysis, partial)
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(analysis, _)| (analysis.period_start, analysis.created_at));
+    candidates
+}
+
+fn summarized_periods_overlapping(
+    scope_analyses: &[LandscapeAnalysis],
+    analysis_type: LandscapeAnalysisType,
+    rollup_analysis: &LandscapeAnalysis,
+    pool: &DbPool,
+) -> Result<Vec<RollupPeriodContextItem>, PpdcError> {
+    let mut items = Vec::new();
+    for (candidate, partial) in overlapping_periods(scope_analyses, analysis_type, rollup_analysis)
+    {
+        let Some(summary) = find_period_summary_for_analysis(candidate.id, pool)? else {
+            continue;
+        };
+        let mut item = period_context_item(candidate, summary);
+        item.partial = partial;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// A partial week also summarizes days outside the period, so its days keep their own summaries.
+fn covered_by_full_week(day: &RollupPeriodContextItem, weeks: &[RollupPeriodContextItem]) -> bool {
+    weeks.iter().any(|week| {
+        !week.partial && day.period_start >= week.period_start && day.period_end <= week.period_end
+    })
+}
+
+fn find_previous_period_summary(
+    current_analysis: &LandscapeAnalysis,
+    scope_analyses: &[LandscapeAnalysis],
+    pool: &DbPool,
+) -> Result<Option<RollupPeriodContextItem>, PpdcError> {
+    let mut candidates = scope_analyses
+        .iter()
+        .filter(|analysis| {
+            analysis.landscape_analysis_type == current_analysis.landscape_analysis_type
+        })
+        .filter(|analysis| analysis.id != current_analysis.id)
+        .filter(|analysis| analysis.period_end <= current_analysis.period_start)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|analysis| std::cmp::Reverse(analysis.period_end));
+
+    for candidate in candidates {
+        if let Some(summary) = find_period_summary_for_analysis(candidate.id, pool)? {
+            return Ok(Some(period_context_item(candidate, summary)));
+        }
+    }
+    Ok(None)
+}
+
+fn period_context_item(
+    analysis: &LandscapeAnalysis,
+    summary: AnalysisSummary,
+) -> RollupPeriodContextItem {
+    RollupPeriodContextItem {
+        analysis_id: analysis.id,
+        period_start: analysis.period_start,
+        period_end: analysis.period_end,
+        partial: false,
+        summary: SummaryContextItem {
+            title: summary.title,
+            short_content: summary.short_content,
+            content: summary.content,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate};
+
+    use super::*;
+    use crate::entities_v2::landscape_analysis::LandscapeProcessingState;
+
+    fn midnight(year: i32, month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    fn analysis(
+        analysis_type: LandscapeAnalysisType,
+        period_start: NaiveDateTime,
+        period_end: NaiveDateTime,
+    ) -> LandscapeAnalysis {
+        LandscapeAnalysis {
+            id: Uuid::new_v4(),
+            title: String::new(),
+            subtitle: String::new(),
+            plain_text_state_summary: String::new(),
+            interaction_date: None,
+            period_start,
+            period_end,
+            user_id: Uuid::nil(),
+            parent_analysis_id: None,
+            replayed_from_id: None,
+            analyzed_trace_id: None,
+            trace_mirror_id: None,
+            landscape_analysis_type: analysis_type,
+            processing_state: LandscapeProcessingState::Completed,
+            failure_reason: None,
+            created_at: period_end,
+            updated_at: period_end,
+        }
+    }
+
+    fn item(analysis: &LandscapeAnalysis, partial: bool) -> RollupPeriodContextItem {
+        RollupPeriodContextItem {
+            analysis_id: analysis.id,
+            period_start: analysis.period_start,
+            period_end: analysis.period_end,
+            partial,
+            summary: SummaryContextItem {
+                title: String::new(),
+                short_content: String::new(),
+                content: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn month_starting_on_a_wednesday_keeps_its_straddling_weeks() {
+        // October 2025 starts on a Wednesday and ends on a Friday.
+        let month = analysis(
+            LandscapeAnalysisType::MonthlyRecap,
+            midnight(2025, 10, 1),
+            midnight(2025, 11, 1),
+        );
+        let mut scope = (0..6)
+            .map(|week| {
+                let start = midnight(2025, 9, 22) + Duration::weeks(week);
+                analysis(
+                    LandscapeAnalysisType::WeeklyRecap,
+                    start,
+                    start + Duration::weeks(1),
+                )
+            })
+            .collect::<Vec<_>>();
+        scope.push(analysis(
+            LandscapeAnalysisType::DailyRecap,
+            midnight(2025, 10, 1),
+            midnight(2025, 10, 2),
+        ));
+        scope.push(analysis(
+            LandscapeAnalysisType::DailyRecap,
+            midnight(2025, 10, 7),
+            midnight(2025, 10, 8),
+        ));
+
+        let weeks = overlapping_periods(&scope, LandscapeAnalysisType::WeeklyRecap, &month);
+        let spans = weeks
+            .iter()
+            .map(|(week, partial)| (week.period_start, *partial))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            vec![
+                (midnight(2025, 9, 29), true),
+                (midnight(2025, 10, 6), false),
+                (midnight(2025, 10, 13), false),
+                (midnight(2025, 10, 20), false),
+                (midnight(2025, 10, 27), true),
+            ]
+        );
+
+        let week_items = weeks
+            .iter()
+            .map(|(week, partial)| item(week, *partial))
+            .collect::<Vec<_>>();
+        let kept_days = overlapping_periods(&scope, LandscapeAnalysisType::DailyRecap, &month)
+            .into_iter()
+            .map(|(day, _)| item(day, false))
+            .filter(|day| !covered_by_full_week(day, &week_items))
+            .map(|day| day.period_start)
+            .collect::<Vec<_>>();
+        assert_eq!(kept_days, vec![midnight(2025, 10, 1)]);
+    }
+}
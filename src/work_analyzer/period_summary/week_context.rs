@@ -11,6 +11,7 @@ use crate::entities_v2::landscape_analysis::model::LandscapeAnalysisType;
 use crate::entities_v2::landscape_analysis::LandscapeAnalysis;
 use crate::entities_v2::trace::{Trace, TraceType};
 use crate::work_analyzer::analysis_context::AnalysisContext;
+use crate::work_analyzer::analysis_unlock::decrypt_trace_for_analysis;
 
 #[derive(Debug, Serialize)]
 pub struct WeekSummaryPromptContext {
@@ -201,7 +202,7 @@ fn build_daily_analysis_map(
     by_day
 }
 
-fn find_period_summary_for_analysis(
+pub(super) fn find_period_summary_for_analysis(
     analysis_id: Uuid,
     pool: &DbPool,
 ) -> Result<Option<AnalysisSummary>, PpdcError> {
@@ -247,7 +248,7 @@ fn find_previous_weeks_summaries(
     Ok(previous_summaries)
 }
 
-fn find_current_lens_high_level_projects(
+pub(super) fn find_current_lens_high_level_projects(
     current_landscape_id: Option<Uuid>,
     pool: &DbPool,
 ) -> Result<Vec<HighLevelProjectContextItem>, PpdcError> {
@@ -285,6 +286,10 @@ fn find_user_traces_by_day_for_period(
 
     let mut by_day: HashMap<NaiveDate, Vec<WeekDayUserTraceContextItem>> = HashMap::new();
     for trace in user_traces {
+        // Ciphertext is never sent to the model: encrypted traces need an unlocked key.
+        let Some(trace) = decrypt_trace_for_analysis(trace, None, pool)? else {
+            continue;
+        };
         by_day
             .entry(trace.interaction_date.date())
             .or_default()
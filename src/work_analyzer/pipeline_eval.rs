@@ -0,0 +1,590 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::entities_v2::landmark::{Landmark, LandmarkType};
+use crate::entities_v2::MaturingState;
+use crate::work_analyzer::element_pipeline_v2::grammatical_extraction::{self, TaggedLandmark};
+use crate::work_analyzer::mirror_pipeline::header::gpt_request::{
+    extract_mirror_header, MirrorHeaderTraceMirrorType,
+};
+use crate::work_analyzer::mirror_pipeline::references_extraction::extraction::{
+    extract_references, HighLevelProjectContextItem, IdentificationStatus,
+    LandmarkReferenceContextItem, ReferencesExtractionResult,
+};
+
+pub const STEP_TRACE_MIRROR_TYPE: &str = "mirror_header.trace_mirror_type";
+pub const STEP_REFERENCE_MENTIONS: &str = "references_extraction.mentions";
+pub const STEP_REFERENCE_MATCHES: &str = "references_extraction.landmark_matches";
+pub const STEP_ELEMENT_LANDMARKS: &str = "element_pipeline_v2.landmark_links";
+
+/// A landmark (or high level project) already known to the user; `id` is the stable key
+/// expectations refer to it by.
+#[derive(Deserialize, Debug, Clone)]
+pub struct GoldenLandmark {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub subtitle: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default = "default_landmark_type")]
+    pub landmark_type: LandmarkType,
+}
+
+fn default_landmark_type() -> LandmarkType {
+    LandmarkType::Resource
+}
+
+/// An expected reference; `landmark` is the id of the existing landmark it should match,
+/// or `null` when a new landmark should be created.
+#[derive(Deserialize, Debug, Clone)]
+pub struct GoldenReference {
+    pub mention: String,
+    #[serde(default)]
+    pub landmark: Option<String>,
+}
+
+/// Expectations per step; a step left unset is not scored for the case.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct GoldenExpectation {
+    #[serde(default)]
+    pub trace_mirror_type: Option<MirrorHeaderTraceMirrorType>,
+    #[serde(default)]
+    pub references: Option<Vec<GoldenReference>>,
+    /// Ids of the landmarks the extracted elements should link to; a landmark created from an
+    /// unmatched reference is `new:<mention>`.
+    #[serde(default)]
+    pub element_landmarks: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GoldenCase {
+    #[serde(default)]
+    pub name: String,
+    pub trace_text: String,
+    #[serde(default)]
+    pub landmarks: Vec<GoldenLandmark>,
+    #[serde(default)]
+    pub high_level_projects: Vec<GoldenLandmark>,
+    #[serde(default)]
+    pub expected: GoldenExpectation,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct StepOutcome {
+    pub true_positives: usize,
+    pub false_positives: Vec<String>,
+    pub false_negatives: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct StepMetrics {
+    pub cases: usize,
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+    pub precision: Option<f64>,
+    pub recall: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CaseReport {
+    pub name: String,
+    pub steps: BTreeMap<String, StepOutcome>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EvalReport {
+    pub mode: String,
+    pub generated_at: String,
+    pub steps: BTreeMap<String, StepMetrics>,
+    pub cases: Vec<CaseReport>,
+}
+
+fn normalize(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Compares two sets of labels after normalizing case and whitespace.
+pub fn score_sets(expected: &[String], predicted: &[String]) -> StepOutcome {
+    let expected = expected
+        .iter()
+        .map(|value| normalize(value))
+        .collect::<BTreeSet<_>>();
+    let predicted = predicted
+        .iter()
+        .map(|value| normalize(value))
+        .collect::<BTreeSet<_>>();
+    StepOutcome {
+        true_positives: expected.intersection(&predicted).count(),
+        false_positives: predicted.difference(&expected).cloned().collect(),
+        false_negatives: expected.difference(&predicted).cloned().collect(),
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> Option<f64> {
+    (denominator > 0).then(|| numerator as f64 / denominator as f64)
+}
+
+/// Sums outcomes over the cases where each step was scored (micro-averaged).
+pub fn aggregate(cases: &[CaseReport]) -> BTreeMap<String, StepMetrics> {
+    let mut steps = BTreeMap::<String, StepMetrics>::new();
+    for case in cases {
+        for (step, outcome) in &case.steps {
+            let metrics = steps.entry(step.clone()).or_default();
+            metrics.cases += 1;
+            metrics.true_positives += outcome.true_positives;
+            metrics.false_positives += outcome.false_positives.len();
+            metrics.false_negatives += outcome.false_negatives.len();
+        }
+    }
+    for metrics in steps.values_mut() {
+        metrics.precision = ratio(
+            metrics.true_positives,
+            metrics.true_positives + metrics.false_positives,
+        );
+        metrics.recall = ratio(
+            metrics.true_positives,
+            metrics.true_positives + metrics.false_negatives,
+        );
+    }
+    steps
+}
+
+fn in_memory_landmark(
+    title: &str,
+    subtitle: &str,
+    content: &str,
+    landmark_type: LandmarkType,
+) -> Landmark {
+    let now = Utc::now().naive_utc();
+    Landmark {
+        id: Uuid::new_v4(),
+        title: title.to_string(),
+        subtitle: subtitle.to_string(),
+        content: content.to_string(),
+        external_content_url: None,
+        comment: None,
+        image_url: None,
+        landmark_type,
+        maturing_state: MaturingState::Finished,
+        related_elements_count: 0,
+        last_related_element_at: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+fn reference_label(mention: &str, landmark_id: Option<&str>) -> String {
+    format!("{} -> {}", mention, landmark_id.unwrap_or("(new)"))
+}
+
+fn new_landmark_label(mention: &str) -> String {
+    format!("new:{}", mention)
+}
+
+/// Landmarks each reference ends up pointing to, as the references persistence would link them.
+fn tagged_landmarks(
+    result: &ReferencesExtractionResult,
+    context_landmarks: &[Landmark],
+) -> Vec<TaggedLandmark> {
+    let mut landmark_by_tag = HashMap::<i32, Landmark>::new();
+    let mut tagged = vec![];
+    for reference in &result.references {
+        let matched = (reference.identification_status == IdentificationStatus::Matched)
+            .then_some(reference.landmark_id)
+            .flatten()
+            .and_then(|index| context_landmarks.get(index as usize))
+            .cloned();
+        let landmark = matched
+            .or_else(|| {
+                reference
+                    .same_object_tag_id
+                    .and_then(|tag_id| landmark_by_tag.get(&tag_id).cloned())
+            })
+            .unwrap_or_else(|| {
+                in_memory_landmark(
+                    &reference.title_suggestion,
+                    "",
+                    &reference.description,
+                    reference.landmark_type,
+                )
+            });
+        landmark_by_tag.insert(reference.tag_id, landmark.clone());
+        tagged.push(TaggedLandmark {
+            tag_id: reference.tag_id,
+            mention: reference.mention.clone(),
+            landmark,
+        });
+    }
+    tagged
+}
+
+/// Runs one case through the header, references and grammatical extraction steps.
+/// No database is used: LLM calls go to whatever provider selection is in scope.
+pub async fn run_case(case: &GoldenCase) -> Result<BTreeMap<String, StepOutcome>, PpdcError> {
+    let analysis_id = Uuid::nil();
+    let mut steps = BTreeMap::new();
+    let high_level_projects = case
+        .high_level_projects
+        .iter()
+        .map(|hlp| {
+            in_memory_landmark(
+                &hlp.title,
+                &hlp.subtitle,
+                &hlp.content,
+                LandmarkType::HighLevelProject,
+            )
+        })
+        .collect::<Vec<_>>();
+    let landmarks = case
+        .landmarks
+        .iter()
+        .map(|landmark| {
+            in_memory_landmark(
+                &landmark.title,
+                &landmark.subtitle,
+                &landmark.content,
+                landmark.landmark_type,
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut labels_by_id = high_level_projects
+        .iter()
+        .zip(&case.high_level_projects)
+        .chain(landmarks.iter().zip(&case.landmarks))
+        .map(|(landmark, golden)| (landmark.id, golden.id.clone()))
+        .collect::<HashMap<_, _>>();
+
+    let header =
+        extract_mirror_header(&case.trace_text, analysis_id, &high_level_projects, None).await?;
+    if let Some(expected_type) = case.expected.trace_mirror_type {
+        steps.insert(
+            STEP_TRACE_MIRROR_TYPE.to_string(),
+            score_sets(
+                &[format!("{:?}", expected_type)],
+                &[format!("{:?}", header.trace_mirror_type)],
+            ),
+        );
+    }
+
+    let mut selected_hlps = Vec::<HighLevelProjectContextItem>::new();
+    let mut hlp_references = vec![];
+    for (tag_index, selection) in header.high_level_projects.iter().enumerate() {
+        let Some(hlp) = high_level_projects.get(selection.id as usize) else {
+            continue;
+        };
+        hlp_references.push(TaggedLandmark {
+            tag_id: -((tag_index as i32) + 1),
+            mention: selection.span.clone(),
+            landmark: hlp.clone(),
+        });
+        match selected_hlps.iter_mut().find(|item| item.uuid == hlp.id) {
+            Some(item) => item.spans.push(selection.span.clone()),
+            None => selected_hlps.push(HighLevelProjectContextItem {
+                uuid: hlp.id,
+                id: selected_hlps.len() as i32,
+                title: hlp.title.clone(),
+                subtitle: hlp.subtitle.clone(),
+                content: hlp.content.clone(),
+                spans: vec![selection.span.clone()],
+            }),
+        }
+    }
+
+    let context = landmarks
+        .iter()
+        .enumerate()
+        .map(|(index, landmark)| LandmarkReferenceContextItem {
+            uuid: landmark.id,
+            landmark_id: index as i32,
+            title: landmark.title.clone(),
+            subtitle: landmark.subtitle.clone(),
+            content: landmark.content.clone(),
+            landmark_type: landmark.landmark_type,
+            existing_references: vec![],
+        })
+        .collect::<Vec<_>>();
+    let references =
+        extract_references(analysis_id, &case.trace_text, &context, &selected_hlps).await?;
+    if let Some(expected_references) = &case.expected.references {
+        let predicted_matches = references
+            .references
+            .iter()
+            .map(|reference| {
+                let matched = (reference.identification_status == IdentificationStatus::Matched)
+                    .then_some(reference.landmark_id)
+                    .flatten()
+                    .and_then(|index| case.landmarks.get(index as usize));
+                reference_label(
+                    &reference.mention,
+                    matched.map(|landmark| landmark.id.as_str()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let expected_matches = expected_references
+            .iter()
+            .map(|reference| reference_label(&reference.mention, reference.landmark.as_deref()))
+            .collect::<Vec<_>>();
+        steps.insert(
+            STEP_REFERENCE_MENTIONS.to_string(),
+            score_sets(
+                &expected_references
+                    .iter()
+                    .map(|reference| reference.mention.clone())
+                    .collect::<Vec<_>>(),
+                &references
+                    .references
+                    .iter()
+                    .map(|reference| reference.mention.clone())
+                    .collect::<Vec<_>>(),
+            ),
+        );
+        steps.insert(
+            STEP_REFERENCE_MATCHES.to_string(),
+            score_sets(&expected_matches, &predicted_matches),
+        );
+    }
+
+    let Some(expected_element_landmarks) = &case.expected.element_landmarks else {
+        return Ok(steps);
+    };
+    let mut element_references = hlp_references;
+    element_references.extend(tagged_landmarks(&references, &landmarks));
+    for reference in &element_references {
+        labels_by_id
+            .entry(reference.landmark.id)
+            .or_insert_with(|| new_landmark_label(&reference.mention));
+    }
+    let extraction =
+        grammatical_extraction::extract(analysis_id, &references.tagged_text, element_references)
+            .await?;
+    let output = &extraction.raw_extraction;
+    let tag_ids = output
+        .transactions
+        .iter()
+        .flat_map(|claim| claim.references_tags_id.iter())
+        .chain(
+            output
+                .descriptives
+                .iter()
+                .flat_map(|claim| claim.references_tags_id.iter()),
+        )
+        .filter_map(|tag_id| extraction.tag_to_landmark_id.get(tag_id));
+    let hlp_ids = output
+        .transactions
+        .iter()
+        .flat_map(|claim| claim.high_level_project_ids.iter())
+        .chain(
+            output
+                .descriptives
+                .iter()
+                .flat_map(|claim| claim.high_level_project_ids.iter()),
+        )
+        .filter_map(|hlp_id| extraction.hlp_id_to_uuid.get(hlp_id));
+    let predicted_landmarks = tag_ids
+        .chain(hlp_ids)
+        .filter_map(|landmark_id| labels_by_id.get(landmark_id).cloned())
+        .collect::<Vec<_>>();
+    steps.insert(
+        STEP_ELEMENT_LANDMARKS.to_string(),
+        score_sets(expected_element_landmarks, &predicted_landmarks),
+    );
+    Ok(steps)
+}
+
+/// Reads every `*.json` golden case of a directory, sorted by file name.
+pub fn load_cases(dir: &Path) -> Result<Vec<GoldenCase>, PpdcError> {
+    let read_error = |err: std::io::Error| {
+        PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!("Cannot read golden cases in {}: {}", dir.display(), err),
+        )
+    };
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(read_error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(read_error)?;
+    paths.retain(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"));
+    paths.sort();
+
+    let mut cases = vec![];
+    for path in paths {
+        let content = std::fs::read_to_string(&path).map_err(read_error)?;
+        let mut case: GoldenCase = serde_json::from_str(&content)?;
+        if case.name.is_empty() {
+            case.name = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .to_string();
+        }
+        cases.push(case);
+    }
+    Ok(cases)
+}
+
+/// Runs all cases; a case that fails is reported with its error and left out of the metrics.
+pub async fn run_cases(cases: &[GoldenCase], mode: &str) -> EvalReport {
+    let mut reports = vec![];
+    for case in cases {
+        let report = match run_case(case).await {
+            Ok(steps) => CaseReport {
+                name: case.name.clone(),
+                steps,
+                error: None,
+            },
+            Err(err) => CaseReport {
+                name: case.name.clone(),
+                steps: BTreeMap::new(),
+                error: Some(err.message),
+            },
+        };
+        reports.push(report);
+    }
+    EvalReport {
+        mode: mode.to_string(),
+        generated_at: Utc::now().to_rfc3339(),
+        steps: aggregate(&reports),
+        cases: reports,
+    }
+}
+
+fn format_ratio(value: Option<f64>) -> String {
+    value
+        .map(|value| format!("{:.2}", value))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+impl EvalReport {
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!(
+            "# Pipeline eval ({})\n\n| Step | Cases | TP | FP | FN | Precision | Recall |\n|---|---|---|---|---|---|---|\n",
+            self.mode
+        );
+        for (step, metrics) in &self.steps {
+            markdown.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} |\n",
+                step,
+                metrics.cases,
+                metrics.true_positives,
+                metrics.false_positives,
+                metrics.false_negatives,
+                format_ratio(metrics.precision),
+                format_ratio(metrics.recall),
+            ));
+        }
+
+        let mut details = String::new();
+        for case in &self.cases {
+            if let Some(error) = &case.error {
+                details.push_str(&format!("- **{}**: error: {}\n", case.name, error));
+                continue;
+            }
+            for (step, outcome) in &case.steps {
+                for value in &outcome.false_positives {
+                    details.push_str(&format!(
+                        "- **{}** `{}` unexpected: {}\n",
+                        case.name, step, value
+                    ));
+                }
+                for value in &outcome.false_negatives {
+                    details.push_str(&format!(
+                        "- **{}** `{}` missing: {}\n",
+                        case.name, step, value
+                    ));
+                }
+            }
+        }
+        if !details.is_empty() {
+            markdown.push_str("\n## Mismatches\n\n");
+            markdown.push_str(&details);
+        }
+        markdown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::openai_handler::llm_provider::{self, FixtureReplayProvider, LlmProviderSelection};
+
+    fn labels(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn scores_sets_ignoring_case_and_spacing() {
+        let outcome = score_sets(
+            &labels(&["Dune -> Dune", "HOM -> (new)"]),
+            &labels(&["dune  -> dune", "HOM -> Socio project"]),
+        );
+        assert_eq!(outcome.true_positives, 1);
+        assert_eq!(outcome.false_positives, labels(&["hom -> socio project"]));
+        assert_eq!(outcome.false_negatives, labels(&["hom -> (new)"]));
+    }
+
+    #[test]
+    fn aggregates_precision_and_recall_over_scored_cases() {
+        let case = |outcome: StepOutcome| CaseReport {
+            name: String::new(),
+            steps: BTreeMap::from([(STEP_REFERENCE_MATCHES.to_string(), outcome)]),
+            error: None,
+        };
+        let steps = aggregate(&[
+            case(score_sets(&labels(&["a", "b"]), &labels(&["a", "c"]))),
+            case(score_sets(&labels(&["d"]), &labels(&["d"]))),
+            CaseReport {
+                name: String::new(),
+                steps: BTreeMap::new(),
+                error: Some("fixture missing".to_string()),
+            },
+        ]);
+        let metrics = &steps[STEP_REFERENCE_MATCHES];
+        assert_eq!(metrics.cases, 2);
+        assert_eq!(metrics.precision, Some(2.0 / 3.0));
+        assert_eq!(metrics.recall, Some(2.0 / 3.0));
+    }
+
+    #[tokio::test]
+    async fn committed_cases_replay_from_recorded_fixtures() {
+        let test_data = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data");
+        let cases = load_cases(&test_data.join("eval_cases")).unwrap();
+        let provider = Arc::new(FixtureReplayProvider::from_directory(
+            test_data.join("llm_fixtures"),
+        ));
+        let report = llm_provider::with_selection(
+            LlmProviderSelection::new(provider),
+            run_cases(&cases, "recorded"),
+        )
+        .await;
+
+        assert!(cases.len() >= 5);
+        for case in &report.cases {
+            assert_eq!(case.error, None, "case {}", case.name);
+        }
+        let overmatching = report
+            .cases
+            .iter()
+            .find(|case| case.name == "overmatching_related_entity")
+            .unwrap();
+        assert_eq!(
+            overmatching.steps[STEP_REFERENCE_MATCHES].false_positives,
+            labels(&["hom -> projet_socio"])
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::db;
+use crate::entities_v2::error::{ErrorType, PpdcError};
+use crate::entities_v2::prompt_version::PromptVersion;
+use crate::openai_handler::llm_provider::PromptRef;
+
+/// Version number reported for the prompts compiled into the binary.
+pub const COMPILED_VERSION: i32 = 0;
+
+const ACTIVE_PROMPT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+struct CompiledPrompt {
+    prompt_id: &'static str,
+    system_prompt: &'static str,
+    schema: Option<&'static str>,
+}
+
+const COMPILED_PROMPTS: &[CompiledPrompt] = &[
+    CompiledPrompt {
+        prompt_id: "matching",
+        system_prompt: include_str!("prompts/matching/system.md"),
+        schema: Some(include_str!("prompts/matching/schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "element_pipeline_v2.extraction",
+        system_prompt: include_str!("element_pipeline_v2/system.md"),
+        schema: Some(include_str!("element_pipeline_v2/schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "element_pipeline_v2.correction",
+        system_prompt: include_str!("element_pipeline_v2/correction_system.md"),
+        schema: Some(include_str!("element_pipeline_v2/schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "references_extraction",
+        system_prompt: include_str!("mirror_pipeline/references_extraction/system.md"),
+        schema: Some(include_str!(
+            "mirror_pipeline/references_extraction/schema.json"
+        )),
+    },
+    CompiledPrompt {
+        prompt_id: "references_extraction.correction",
+        system_prompt: include_str!("mirror_pipeline/references_extraction/correction_system.md"),
+        schema: Some(include_str!(
+            "mirror_pipeline/references_extraction/schema.json"
+        )),
+    },
+    CompiledPrompt {
+        prompt_id: "mirror_header",
+        system_prompt: include_str!("mirror_pipeline/header/system.md"),
+        schema: Some(include_str!("mirror_pipeline/header/schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "primary_resource.suggestion",
+        system_prompt: include_str!(
+            "mirror_pipeline/prompts/primary_resource/suggestion/system.md"
+        ),
+        schema: Some(include_str!(
+            "mirror_pipeline/prompts/primary_resource/suggestion/schema.json"
+        )),
+    },
+    CompiledPrompt {
+        prompt_id: "primary_resource.creation",
+        system_prompt: include_str!("mirror_pipeline/prompts/primary_resource/creation/system.md"),
+        schema: Some(include_str!(
+            "mirror_pipeline/prompts/primary_resource/creation/schema.json"
+        )),
+    },
+    CompiledPrompt {
+        prompt_id: "hlp_pipeline",
+        system_prompt: include_str!("hlp_pipeline/system.md"),
+        schema: Some(include_str!("hlp_pipeline/schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "bio_pipeline",
+        system_prompt: include_str!("bio_pipeline/system.md"),
+        schema: Some(include_str!("bio_pipeline/schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "mentor_feedback",
+        system_prompt: include_str!("mentor_feedback/system.md"),
+        schema: Some(include_str!("mentor_feedback/schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "period_summary.day",
+        system_prompt: include_str!("period_summary/day_system.md"),
+        schema: Some(include_str!("period_summary/day_schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "period_summary.week",
+        system_prompt: include_str!("period_summary/week_system.md"),
+        schema: Some(include_str!("period_summary/week_schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "period_summary.rollup",
+        system_prompt: include_str!("period_summary/rollup_system.md"),
+        schema: Some(include_str!("period_summary/week_schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "message_processing.mentor_reply",
+        system_prompt: include_str!("message_processing/system.md"),
+        schema: Some(include_str!("message_processing/schema.json")),
+    },
+    CompiledPrompt {
+        prompt_id: "message_processing.tarot_reply",
+        system_prompt: include_str!("message_processing/tarot_system.md"),
+        schema: Some(include_str!("message_processing/tarot_schema.json")),
+    },
+];
+
+/// A system prompt and response schema as sent for one pipeline step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    pub prompt_id: String,
+    pub version: i32,
+    pub system_prompt: String,
+    pub schema: Option<String>,
+}
+
+impl PromptTemplate {
+    pub fn schema_json(&self) -> Result<serde_json::Value, PpdcError> {
+        let schema = self.schema.as_deref().ok_or_else(|| {
+            PpdcError::new(
+                500,
+                ErrorType::InternalError,
+                format!("Prompt {} has no schema", self.prompt_id),
+            )
+        })?;
+        Ok(serde_json::from_str(schema)?)
+    }
+
+    pub fn prompt_ref(&self) -> PromptRef {
+        PromptRef {
+            prompt_id: self.prompt_id.clone(),
+            version: self.version,
+        }
+    }
+}
+
+pub fn compiled_prompt_ids() -> impl Iterator<Item = &'static str> {
+    COMPILED_PROMPTS.iter().map(|compiled| compiled.prompt_id)
+}
+
+pub fn is_registered(prompt_id: &str) -> bool {
+    compiled_prompt_ids().any(|id| id == prompt_id)
+}
+
+pub fn compiled(prompt_id: &str) -> Option<PromptTemplate> {
+    COMPILED_PROMPTS
+        .iter()
+        .find(|compiled| compiled.prompt_id == prompt_id)
+        .map(|compiled| PromptTemplate {
+            prompt_id: compiled.prompt_id.to_string(),
+            version: COMPILED_VERSION,
+            system_prompt: compiled.system_prompt.to_string(),
+            schema: compiled.schema.map(str::to_string),
+        })
+}
+
+type ActivePromptCache = HashMap<String, (Instant, Option<PromptVersion>)>;
+
+static ACTIVE_PROMPTS: OnceLock<Mutex<ActivePromptCache>> = OnceLock::new();
+
+fn active_prompts() -> &'static Mutex<ActivePromptCache> {
+    ACTIVE_PROMPTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Drops cached active versions so the next request reads the table again.
+pub fn invalidate_cache() {
+    active_prompts()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clear();
+}
+
+fn find_active_version(prompt_id: &str) -> Option<PromptVersion> {
+    let pool = db::try_get_global_pool()?;
+    if let Some((loaded_at, version)) = active_prompts()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(prompt_id)
+    {
+        if loaded_at.elapsed() < ACTIVE_PROMPT_CACHE_TTL {
+            return version.clone();
+        }
+    }
+    let version = match PromptVersion::find_active(prompt_id, pool) {
+        Ok(version) => version,
+        Err(err) => {
+            tracing::warn!(
+                target: "work_analyzer",
+                "prompt_registry_lookup_failed prompt_id={} error={}",
+                prompt_id,
+                err.message
+            );
+            return None;
+        }
+    };
+    active_prompts()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(prompt_id.to_string(), (Instant::now(), version.clone()));
+    version
+}
+
+/// Returns the version activated for a step, falling back to the compiled prompt.
+/// A stored version without a schema keeps the compiled schema.
+pub fn prompt(prompt_id: &str) -> Result<PromptTemplate, PpdcError> {
+    let compiled = compiled(prompt_id).ok_or_else(|| {
+        PpdcError::new(
+            500,
+            ErrorType::InternalError,
+            format!("Unknown prompt id {}", prompt_id),
+        )
+    })?;
+    Ok(match find_active_version(prompt_id) {
+        Some(version) => PromptTemplate {
+            prompt_id: compiled.prompt_id,
+            version: version.version,
+            system_prompt: version.system_prompt,
+            schema: version.schema.or(compiled.schema),
+        },
+        None => compiled,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn compiled_catalog_has_unique_ids_and_valid_schemas() {
+        let mut seen = HashSet::new();
+        for prompt_id in compiled_prompt_ids() {
+            assert!(seen.insert(prompt_id), "duplicate prompt id {}", prompt_id);
+            let template = compiled(prompt_id).unwrap();
+            assert_eq!(template.version, COMPILED_VERSION);
+            if template.schema.is_some() {
+                template.schema_json().unwrap();
+            }
+        }
+    }
+}